@@ -1,5 +1,9 @@
-use crate::commands::get_app_state_or_err;
-use crate::models::{AppSettings, MihomoConfig};
+use crate::commands::reload::StaleBackupStatus;
+use crate::commands::{capture_crash_report, get_app_state_or_err, CommandError};
+use crate::mihomo::CoreConfigTestResult;
+use crate::models::{AppSettings, LatencyThresholds, MihomoConfig, ValidationIssue};
+use crate::utils::{self, PortConflict};
+use std::collections::HashSet;
 #[cfg(unix)]
 use std::os::unix::{ffi::OsStrExt, fs::MetadataExt};
 
@@ -92,7 +96,13 @@ pub async fn get_config_proxies() -> Result<Vec<ProxyServerInfo>, String> {
 
 /// 保存 MiHomo 配置
 #[tauri::command]
-pub async fn save_config(config: MihomoConfig) -> Result<(), String> {
+pub async fn save_config(config: MihomoConfig) -> Result<(), CommandError> {
+    save_config_inner(config)
+        .await
+        .inspect_err(|e| capture_crash_report("save_config", e))
+}
+
+async fn save_config_inner(config: MihomoConfig) -> Result<(), CommandError> {
     use crate::commands::reload::{reload_config, ConfigBackup, ReloadOptions};
 
     let state = get_app_state_or_err()?;
@@ -101,7 +111,7 @@ pub async fn save_config(config: MihomoConfig) -> Result<(), String> {
     state
         .config_manager
         .validate_mihomo_config(&config)
-        .map_err(|e| e.to_string())?;
+        .map_err(CommandError::validation)?;
 
     // 自动处理 dns-hijack 逻辑
     // 如果 DNS 已关闭，则清空 dns-hijack 以避免日志刷屏和潜在冲突
@@ -127,13 +137,10 @@ pub async fn save_config(config: MihomoConfig) -> Result<(), String> {
     }
 
     // 创建配置备份
-    let backup = ConfigBackup::create(state).map_err(|e| e.to_string())?;
+    let backup = ConfigBackup::create(state)?;
 
     // 保存配置
-    state
-        .config_manager
-        .save_mihomo_config(&config_to_save)
-        .map_err(|e| e.to_string())?;
+    state.config_manager.save_mihomo_config(&config_to_save)?;
 
     // 如果 MiHomo 正在运行，重新加载配置
     if state.mihomo_manager.is_running().await {
@@ -147,7 +154,7 @@ pub async fn save_config(config: MihomoConfig) -> Result<(), String> {
                 // 尝试用回滚后的配置重新加载
                 let _ = reload_config(None, &ReloadOptions::quick()).await;
             }
-            return Err(format!("配置保存成功但重载失败: {}", e));
+            return Err(CommandError::core(format!("配置保存成功但重载失败: {}", e)));
         }
     }
 
@@ -163,6 +170,10 @@ pub async fn save_config(config: MihomoConfig) -> Result<(), String> {
         if let Some(tun) = &config_to_save.tun {
             app_settings.mihomo.tun = tun.clone();
         }
+        // 同步 Sniffer 配置
+        if let Some(sniffer) = &config_to_save.sniffer {
+            app_settings.mihomo.sniffer = Some(sniffer.clone());
+        }
         // 同步其他设置（注意：不同步端口，端口由 set_ports 命令单独管理，
         // 避免运行时的 0 值覆盖用户设置）
         app_settings.mihomo.allow_lan = config_to_save.allow_lan;
@@ -179,6 +190,76 @@ pub async fn save_config(config: MihomoConfig) -> Result<(), String> {
     Ok(())
 }
 
+/// 校验 MiHomo 配置，返回带字段路径的问题列表（不保存）
+#[tauri::command]
+pub async fn validate_config(config: MihomoConfig) -> Result<Vec<ValidationIssue>, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.config_manager.collect_validation_issues(&config))
+}
+
+/// 使用核心测试配置文件（`mihomo -t`），返回带行号/字段位置的错误
+#[tauri::command]
+pub async fn test_config_file(path: String) -> Result<CoreConfigTestResult, String> {
+    crate::mihomo::test_config_file(std::path::Path::new(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 使用核心测试配置内容（原始配置编辑器粘贴的 YAML）
+///
+/// 内容按 Profile 解析后与当前设置合并，生成与激活时一致的运行时配置，
+/// 写入数据目录下的临时文件后测试，相对路径与运行时配置一致。
+#[tauri::command]
+pub async fn test_config_content(content: String) -> Result<CoreConfigTestResult, String> {
+    use crate::commands::reload::{apply_provider_overrides, build_base_config_from_settings};
+
+    let state = get_app_state_or_err()?;
+    let profile = match crate::config::Composer::parse_yaml(&content) {
+        Ok(profile) => profile,
+        Err(e) => return Ok(CoreConfigTestResult::failed(&e.to_string())),
+    };
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    let mut config = build_base_config_from_settings(&settings.mihomo);
+    config.proxies = profile.proxies;
+    config.proxy_groups = profile.proxy_groups;
+    config.proxy_providers = profile.proxy_providers;
+    config.rule_providers = profile.rule_providers;
+    config.rules = profile.rules;
+    config.sub_rules = profile.sub_rules;
+    apply_provider_overrides(&settings.mihomo, &mut config);
+    let yaml = serde_yaml::to_string(&config).map_err(|e| e.to_string())?;
+
+    let data_dir = utils::get_app_data_dir().map_err(|e| e.to_string())?;
+    let path = data_dir.join(format!("config-test-{}.yaml", uuid::Uuid::new_v4()));
+    std::fs::write(&path, yaml).map_err(|e| e.to_string())?;
+
+    let result = crate::mihomo::test_config_file(&path).await;
+    let _ = std::fs::remove_file(&path);
+    result.map_err(|e| e.to_string())
+}
+
+/// 从异常退出遗留的备份恢复配置
+///
+/// 当前配置校验失败而 `config.yaml.bak` 可用时用备份覆盖，并在核心运行时重载；
+/// 当前配置可用时删除无用的备份。返回处理前的检查结果。
+#[tauri::command]
+pub async fn recover_from_backup(app: tauri::AppHandle) -> Result<StaleBackupStatus, String> {
+    use crate::commands::reload::{recover_stale_backup, reload_config, ReloadOptions};
+
+    let state = get_app_state_or_err()?;
+    let status = recover_stale_backup(state)
+        .await
+        .map_err(|e| format!("恢复配置失败: {}", e))?;
+
+    if status == StaleBackupStatus::Restorable {
+        reload_config(Some(&app), &ReloadOptions::default()).await?;
+    }
+    Ok(status)
+}
+
 /// 获取应用设置
 #[tauri::command]
 pub async fn get_app_settings() -> Result<AppSettings, String> {
@@ -190,20 +271,295 @@ pub async fn get_app_settings() -> Result<AppSettings, String> {
         .map_err(|e| e.to_string())
 }
 
+/// 设置延迟着色阈值（需满足 0 < good < medium）
+#[tauri::command]
+pub async fn set_latency_thresholds(thresholds: LatencyThresholds) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    if thresholds.good == 0 || thresholds.good >= thresholds.medium {
+        return Err("延迟阈值需满足 0 < 良好 < 一般".to_string());
+    }
+
+    let mut settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    settings.latency_thresholds = thresholds;
+    state
+        .config_manager
+        .save_app_settings(&settings)
+        .map_err(|e| e.to_string())
+}
+
+/// 检查本地服务端口冲突
+///
+/// 包括代理端口、外部控制器、DNS 监听和 Sub-Store 端口之间的重复，
+/// 以及被其他程序占用的端口（本应用正在监听的端口除外）
+#[tauri::command]
+pub async fn check_port_conflicts() -> Result<Vec<PortConflict>, String> {
+    let state = get_app_state_or_err()?;
+
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    let (substore_port, substore_running) = {
+        let substore = state.substore_manager.lock().await;
+        (substore.api_port(), substore.is_running().await)
+    };
+
+    let ports = utils::collect_listen_ports(&settings.mihomo, substore_port);
+    let mut owned_ports = HashSet::new();
+    if state.mihomo_manager.is_running().await {
+        owned_ports.extend(
+            ports
+                .iter()
+                .map(|listen| listen.port)
+                .filter(|port| *port != substore_port),
+        );
+    }
+    if substore_running {
+        owned_ports.insert(substore_port);
+    }
+
+    Ok(utils::check_port_conflicts(&ports, &owned_ports))
+}
+
 /// 保存应用设置
 #[tauri::command]
-pub async fn save_app_settings(settings: AppSettings) -> Result<(), String> {
+pub async fn save_app_settings(
+    app: tauri::AppHandle,
+    mut settings: AppSettings,
+) -> Result<(), String> {
     let state = get_app_state_or_err()?;
 
+    // 密钥只能通过 rotate_api_secret 修改，避免前端旧数据覆盖已轮换的密钥
+    settings.mihomo.secret = state.api_secret();
+
+    // 拒绝端口互相冲突或新端口已被占用的设置（未修改的端口不做占用检查）
+    let current = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    let substore_port = state.substore_manager.lock().await.api_port();
+    let owned_ports: HashSet<u16> = utils::collect_listen_ports(&current.mihomo, substore_port)
+        .into_iter()
+        .map(|listen| listen.port)
+        .collect();
+    let conflicts = utils::check_port_conflicts(
+        &utils::collect_listen_ports(&settings.mihomo, substore_port),
+        &owned_ports,
+    );
+    if !conflicts.is_empty() {
+        let messages: Vec<String> = conflicts.into_iter().map(|c| c.message).collect();
+        return Err(format!("端口冲突：{}", messages.join("；")));
+    }
+
+    // 核心 GEO 自动更新只能通过 set_geo_auto_update 修改；应用侧新开启规则数据库
+    // 自动更新时关闭核心侧更新，两边不同时下载
+    settings.mihomo.geo_auto_update = current.mihomo.geo_auto_update;
+    settings.mihomo.geo_update_interval = current.mihomo.geo_update_interval;
+    let app_geo_update_enabled = settings.rule_databases.iter().any(|db| {
+        db.auto_update
+            && !current
+                .rule_databases
+                .iter()
+                .any(|old| old.id == db.id && old.auto_update)
+    });
+
     state
         .config_manager
         .save_app_settings(&settings)
         .map_err(|e| e.to_string())?;
+    crate::commands::proxy::apply_connection_limit(&app, state, &settings.connection_limit);
+
+    if app_geo_update_enabled && current.mihomo.geo_auto_update {
+        use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+        apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |mihomo| {
+            mihomo.geo_auto_update = false;
+            Ok(())
+        })
+        .await?;
+        log::info!("Disabled core GEO auto update (app owns rule database updates)");
+    }
 
     log::info!("App settings saved");
     Ok(())
 }
 
+/// 轮换 API 密钥
+///
+/// 新密钥写入 settings.json 和 config.yaml 并同步到各 API 客户端，
+/// 核心运行中时重启核心，确认核心接受新密钥后才返回成功；
+/// 核心未接受新密钥时恢复旧密钥并再次重启核心。
+#[tauri::command]
+pub async fn rotate_api_secret(app: tauri::AppHandle) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+    // 与 Profile 切换互斥，避免重载时写回旧密钥
+    let _guard = state.profile_switch_lock.lock().await;
+
+    let old_secret = state.api_secret();
+    let new_secret = utils::generate_api_secret();
+    apply_api_secret(state, &new_secret).await?;
+
+    if !state.mihomo_manager.is_running().await {
+        log::info!("API secret rotated (core not running)");
+        return Ok(());
+    }
+
+    let restarted = match crate::commands::proxy::restart_proxy(app.clone()).await {
+        Ok(()) => state
+            .mihomo_manager
+            .check_health()
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = restarted {
+        log::warn!(
+            "Core rejected the new API secret, restoring the old one: {}",
+            e
+        );
+        apply_api_secret(state, &old_secret).await?;
+        if let Err(restore_err) = crate::commands::proxy::restart_proxy(app).await {
+            log::error!(
+                "Failed to restart core with the old API secret: {}",
+                restore_err
+            );
+        }
+        return Err(format!("核心未接受新的 API 密钥，已恢复旧密钥：{}", e));
+    }
+
+    log::info!("API secret rotated");
+    Ok(())
+}
+
+/// 将 API 密钥写入 settings.json 和 config.yaml，并同步到各 API 客户端
+async fn apply_api_secret(state: &crate::commands::AppState, secret: &str) -> Result<(), String> {
+    let mut settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    settings.mihomo.secret = secret.to_string();
+    state
+        .config_manager
+        .save_app_settings(&settings)
+        .map_err(|e| e.to_string())?;
+
+    let mut config = state
+        .config_manager
+        .load_mihomo_config()
+        .map_err(|e| e.to_string())?;
+    config.secret = secret.to_string();
+    state
+        .config_manager
+        .save_mihomo_config(&config)
+        .map_err(|e| e.to_string())?;
+
+    if let Ok(mut current) = state.api_secret.write() {
+        *current = secret.to_string();
+    }
+    state.mihomo_api.set_secret(secret.to_string());
+    state.mihomo_manager.set_secret(secret.to_string());
+    state.log_streamer.set_secret(secret.to_string()).await;
+    Ok(())
+}
+
+/// HTTPS 控制器的默认监听地址
+const DEFAULT_CONTROLLER_TLS_LISTEN: &str = "0.0.0.0:9443";
+
+/// 启用或关闭 HTTPS 外部控制器（`external-controller-tls` + `tls`）
+///
+/// 启用时需要提供证书和 PKCS#8 私钥路径，应用前先校验两者是否配对。
+/// 启用后应用内的 API 客户端和日志流改走 HTTPS 并信任该证书。
+/// 需要重启核心。
+#[tauri::command]
+pub async fn set_controller_tls(
+    app: tauri::AppHandle,
+    enabled: bool,
+    listen: Option<String>,
+    cert_path: Option<String>,
+    key_path: Option<String>,
+) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let state = get_app_state_or_err()?;
+
+    let tls = if enabled {
+        let listen = listen
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .unwrap_or_else(|| DEFAULT_CONTROLLER_TLS_LISTEN.to_string());
+        if listen
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok())
+            .map_or(true, |port| port == 0)
+        {
+            return Err(format!("无效的监听地址: {}", listen));
+        }
+        let cert_path = cert_path.ok_or("请提供证书路径")?;
+        let key_path = key_path.ok_or("请提供私钥路径")?;
+        crate::mihomo::validate_tls_pair(
+            std::path::Path::new(&cert_path),
+            std::path::Path::new(&key_path),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+        Some((listen, cert_path, key_path))
+    } else {
+        None
+    };
+
+    let mut settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    let (old_url, old_cert) = crate::mihomo::controller_endpoint(&settings.mihomo);
+    let apply = |mihomo: &mut crate::models::MihomoSettings| match &tls {
+        Some((listen, cert_path, key_path)) => {
+            mihomo.external_controller_tls = Some(listen.clone());
+            mihomo.tls_certificate = Some(cert_path.clone());
+            mihomo.tls_private_key = Some(key_path.clone());
+        }
+        None => {
+            mihomo.external_controller_tls = None;
+            mihomo.tls_certificate = None;
+            mihomo.tls_private_key = None;
+        }
+    };
+    apply(&mut settings.mihomo);
+
+    // 先切换客户端地址，核心重启后的 API 调用直接走新地址；失败时切回
+    let (new_url, new_cert) = crate::mihomo::controller_endpoint(&settings.mihomo);
+    state
+        .mihomo_api
+        .set_endpoint(new_url.clone(), new_cert.as_deref())
+        .map_err(|e| e.to_string())?;
+    state.log_streamer.set_endpoint(new_url, new_cert).await;
+
+    let result = apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |mihomo| {
+        apply(mihomo);
+        Ok(())
+    })
+    .await;
+
+    if let Err(e) = result {
+        let _ = state
+            .mihomo_api
+            .set_endpoint(old_url.clone(), old_cert.as_deref());
+        state.log_streamer.set_endpoint(old_url, old_cert).await;
+        return Err(e);
+    }
+
+    log::info!(
+        "Controller TLS {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
 /// 下载资源文件响应
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -232,7 +588,7 @@ pub async fn download_resource(
 ) -> Result<DownloadResourceResult, String> {
     log::info!("Downloading resource: {} -> {}", url, file_name);
 
-    let target_dir = crate::utils::get_app_data_dir().map_err(|e| e.to_string())?;
+    let target_dir = utils::get_app_data_dir().map_err(|e| e.to_string())?;
     let target_path = target_dir.join(&file_name);
 
     // 检查是否启用 JsDelivr 加速
@@ -513,10 +869,20 @@ pub async fn download_resource(
         content.len()
     );
 
-    // 如果 mihomo 正在运行，立即重载配置以使其重新读取本地 GEO 资源。
-    //
-    // 注意：这里刻意不调用 core 的 update_geo 接口，避免核心在某些实现下触发网络下载。
-    // GEO 资源由 Conflux 统一下载到数据目录，并通过 reload_configs 触发重载即可。
+    reload_after_resource_download().await?;
+
+    Ok(DownloadResourceResult {
+        downloaded: true,
+        etag: new_etag,
+        remote_modified: new_modified,
+    })
+}
+
+/// 如果 mihomo 正在运行，立即重载配置以使其重新读取本地 GEO 资源。
+///
+/// 注意：这里刻意不调用 core 的 update_geo 接口，避免核心在某些实现下触发网络下载。
+/// GEO 资源由 Conflux 统一下载到数据目录，并通过 reload_configs 触发重载即可。
+async fn reload_after_resource_download() -> Result<(), String> {
     let state = get_app_state_or_err()?;
     if state.mihomo_manager.is_running().await {
         let config_path = state.config_manager.mihomo_config_path();
@@ -529,12 +895,147 @@ pub async fn download_resource(
             Err(e) => log::warn!("Failed to reload config: {}", e),
         }
     }
+    Ok(())
+}
 
-    Ok(DownloadResourceResult {
-        downloaded: true,
-        etag: new_etag,
-        remote_modified: new_modified,
-    })
+/// GEO 数据库下载进度事件
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeoDownloadProgress {
+    id: String,
+    file_name: String,
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// 进度事件的最小间隔字节数，避免事件过于频繁
+const GEO_PROGRESS_STEP_BYTES: u64 = 256 * 1024;
+
+/// 下载规则数据库（支持进度、断点续传与完整性校验）
+///
+/// `which` 为 settings.json 中 ruleDatabases 的 id（如 geoip / geosite）。
+/// 下载过程通过 `geo-download-progress` 事件报告进度；中断后重试会从已下载位置续传。
+/// 优先使用 JsDelivr 加速（若启用），失败时回退到另一来源。
+#[tauri::command]
+pub async fn download_geo_with_progress(
+    app: tauri::AppHandle,
+    which: String,
+) -> Result<DownloadResourceResult, String> {
+    use tauri::Emitter;
+
+    let state = get_app_state_or_err()?;
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    let db = app_settings
+        .rule_databases
+        .iter()
+        .find(|db| db.id == which)
+        .cloned()
+        .ok_or_else(|| format!("未找到规则数据库: {}", which))?;
+
+    let target_dir = utils::get_app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create target dir {:?}: {}", target_dir, e))?;
+    let target_path = target_dir.join(&db.file_name);
+
+    // 候选下载地址：优先使用用户选择的来源，另一来源作为回退
+    let mut candidates = vec![db.url.clone()];
+    if crate::utils::is_github_resource_url(&db.url) {
+        let mirror = crate::utils::convert_github_to_jsdelivr(&db.url);
+        if mirror != db.url {
+            if app_settings.use_jsdelivr {
+                candidates.insert(0, mirror);
+            } else {
+                candidates.push(mirror);
+            }
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(15))
+        .read_timeout(std::time::Duration::from_secs(60))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut last_error = String::new();
+    for url in candidates {
+        log::info!("Downloading geo database '{}' from {}", db.id, url);
+        let part_path = crate::utils::part_path_for(&target_path, &url);
+
+        let mut last_emitted = 0u64;
+        let download =
+            crate::utils::download_resumable(&client, &url, &part_path, |downloaded, total| {
+                if downloaded < last_emitted + GEO_PROGRESS_STEP_BYTES && Some(downloaded) != total
+                {
+                    return;
+                }
+                last_emitted = downloaded;
+                let _ = app.emit(
+                    "geo-download-progress",
+                    GeoDownloadProgress {
+                        id: db.id.clone(),
+                        file_name: db.file_name.clone(),
+                        downloaded,
+                        total,
+                    },
+                );
+            })
+            .await;
+
+        let (etag, remote_modified) = match download {
+            Ok(meta) => meta,
+            Err(e) => {
+                last_error = e.to_string();
+                continue;
+            }
+        };
+
+        // 完整性校验：发布方提供 .sha256sum 时比对摘要，且文件不能为空
+        let size = std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+        if size == 0 {
+            crate::utils::discard_partial(&part_path);
+            last_error = "下载的文件为空".to_string();
+            continue;
+        }
+        if let Some(expected) = crate::utils::fetch_sha256sum(&client, &url).await {
+            let actual = crate::utils::sha256_file(&part_path).map_err(|e| e.to_string())?;
+            if actual != expected {
+                // 续传数据可能已损坏，丢弃后换下一个来源重新下载
+                crate::utils::discard_partial(&part_path);
+                last_error = format!("文件校验失败 (期望 {}, 实际 {})", expected, actual);
+                continue;
+            }
+        }
+
+        // 原子替换目标文件
+        #[cfg(windows)]
+        if target_path.exists() {
+            std::fs::remove_file(&target_path)
+                .map_err(|e| format!("Failed to remove old file: {}", e))?;
+        }
+        std::fs::rename(&part_path, &target_path)
+            .map_err(|e| format!("Failed to replace file {:?}: {}", target_path, e))?;
+        crate::utils::discard_partial(&part_path);
+
+        log::info!(
+            "Geo database downloaded successfully: {:?} ({} bytes)",
+            target_path,
+            size
+        );
+
+        reload_after_resource_download().await?;
+
+        return Ok(DownloadResourceResult {
+            downloaded: true,
+            etag,
+            remote_modified,
+        });
+    }
+
+    Err(format!("下载 {} 失败: {}", db.name, last_error))
 }
 
 /// 重新加载 GEO 数据库
@@ -771,7 +1272,7 @@ pub struct ResourceFileInfo {
 pub async fn check_resource_files(
     file_names: Vec<String>,
 ) -> Result<Vec<ResourceFileInfo>, String> {
-    let data_dir = crate::utils::get_app_data_dir().map_err(|e| e.to_string())?;
+    let data_dir = utils::get_app_data_dir().map_err(|e| e.to_string())?;
 
     let mut results = Vec::new();
 
@@ -833,3 +1334,54 @@ pub async fn save_rules(rules: Vec<String>) -> Result<(), String> {
     log::info!("Rules saved and reloaded");
     Ok(())
 }
+
+/// 下载并安装面板（metacubexd / yacd 等），由核心通过 `external-ui` 提供
+///
+/// 面板与核心 API 同源，无需配置 `external-controller-cors`。
+/// 返回面板访问地址。
+#[tauri::command]
+pub async fn install_dashboard(
+    app: tauri::AppHandle,
+    url: Option<String>,
+) -> Result<String, String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let url = url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| utils::DEFAULT_DASHBOARD_URL.to_string());
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(format!("无效的面板下载地址: {}", url));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let part_path = utils::get_app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("dashboard.zip.part");
+    utils::discard_partial(&part_path);
+
+    log::info!("Downloading dashboard from {}", url);
+    let downloaded = utils::download_resumable(&client, &url, &part_path, |_, _| {}).await;
+    let bytes = downloaded
+        .map_err(|e| e.to_string())
+        .and_then(|_| std::fs::read(&part_path).map_err(|e| e.to_string()));
+    utils::discard_partial(&part_path);
+    utils::install_dashboard_archive(&bytes?).map_err(|e| e.to_string())?;
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.external_ui = Some(utils::DASHBOARD_DIR.to_string());
+        settings.external_ui_url = Some(url.clone());
+        Ok(())
+    })
+    .await?;
+
+    let state = get_app_state_or_err()?;
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    Ok(utils::dashboard_url(&settings.mihomo.external_controller))
+}
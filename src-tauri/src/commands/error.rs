@@ -0,0 +1,149 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+/// 命令错误
+///
+/// 序列化为 `{ code, message }`，前端可根据 `code` 区分错误类别；
+/// `Display` 只输出 `message`，与之前返回 `String` 时的文本保持一致。
+#[derive(Debug, Error)]
+pub enum CommandError {
+    /// 网络错误（订阅下载、WebDAV、核心 API 请求失败等）
+    #[error("{0}")]
+    Network(String),
+    /// 参数或配置校验失败
+    #[error("{0}")]
+    Validation(String),
+    /// 权限不足（TUN 需要管理员权限等）
+    #[error("{0}")]
+    Permission(String),
+    /// 目标资源不存在
+    #[error("{0}")]
+    NotFound(String),
+    /// 核心或其他内部错误
+    #[error("{0}")]
+    CoreError(String),
+}
+
+impl CommandError {
+    pub fn network(message: impl ToString) -> Self {
+        Self::Network(message.to_string())
+    }
+
+    pub fn validation(message: impl ToString) -> Self {
+        Self::Validation(message.to_string())
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    pub fn permission(message: impl ToString) -> Self {
+        Self::Permission(message.to_string())
+    }
+
+    pub fn core(message: impl ToString) -> Self {
+        Self::CoreError(message.to_string())
+    }
+
+    /// 稳定的错误码，供前端分支判断
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Network(_) => "NETWORK",
+            Self::Validation(_) => "VALIDATION",
+            Self::Permission(_) => "PERMISSION",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::CoreError(_) => "CORE_ERROR",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Network(message)
+            | Self::Validation(message)
+            | Self::Permission(message)
+            | Self::NotFound(message)
+            | Self::CoreError(message) => message,
+        }
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("CommandError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+/// 现有辅助函数大多返回 `String` 错误，默认归类为核心错误
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::CoreError(message)
+    }
+}
+
+/// 根据错误链中的底层错误类型推断类别
+impl From<anyhow::Error> for CommandError {
+    fn from(err: anyhow::Error) -> Self {
+        let message = err.to_string();
+
+        for cause in err.chain() {
+            if cause
+                .downcast_ref::<crate::mihomo::LaunchPermissionError>()
+                .is_some()
+            {
+                return Self::Permission(message);
+            }
+            if cause.downcast_ref::<reqwest::Error>().is_some() {
+                return Self::Network(message);
+            }
+            if let Some(io_err) = cause.downcast_ref::<std::io::Error>() {
+                match io_err.kind() {
+                    std::io::ErrorKind::NotFound => return Self::NotFound(message),
+                    std::io::ErrorKind::PermissionDenied => return Self::Permission(message),
+                    _ => {}
+                }
+            }
+        }
+
+        Self::CoreError(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_contains_code_and_message() {
+        let err = CommandError::validation("无效的配置");
+        let value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["code"], "VALIDATION");
+        assert_eq!(value["message"], "无效的配置");
+    }
+
+    #[test]
+    fn test_display_keeps_message() {
+        let err = CommandError::from("代理核心未运行".to_string());
+        assert_eq!(err.to_string(), "代理核心未运行");
+        assert_eq!(err.code(), "CORE_ERROR");
+    }
+
+    #[test]
+    fn test_from_anyhow_launch_permission() {
+        let err = anyhow::Error::new(crate::mihomo::LaunchPermissionError(
+            "用户取消了授权".to_string(),
+        ))
+        .context("启动核心失败");
+        assert_eq!(CommandError::from(err).code(), "PERMISSION");
+    }
+
+    #[test]
+    fn test_from_anyhow_io_not_found() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let err = CommandError::from(anyhow::Error::new(io_err));
+        assert_eq!(err.code(), "NOT_FOUND");
+    }
+}
@@ -1,7 +1,9 @@
+use std::fmt::Write;
 use tauri::AppHandle;
 
 use super::get_app_state_or_err;
-use crate::mihomo::LogLevel;
+use crate::mihomo::{LogLevel, MAX_STREAM_RECONNECT_SECS};
+use crate::utils::{recent_app_logs, redact_text};
 
 /// 开始日志流
 #[tauri::command]
@@ -32,3 +34,112 @@ pub async fn set_log_level(level: String) -> Result<(), String> {
     state.log_streamer.set_level(log_level).await;
     Ok(())
 }
+
+/// 设置日志/连接数据流断开后的重连间隔（秒）
+///
+/// 连续重连失败时在此基础上翻倍退避（最长 60 秒），恢复后发送 `stream-reconnected` 事件。
+#[tauri::command]
+pub async fn set_stream_reconnect_interval(secs: u64) -> Result<(), String> {
+    if !(1..=MAX_STREAM_RECONNECT_SECS).contains(&secs) {
+        return Err(format!(
+            "重连间隔应在 1-{} 秒之间",
+            MAX_STREAM_RECONNECT_SECS
+        ));
+    }
+    let state = get_app_state_or_err()?;
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.stream_reconnect_interval = secs;
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    crate::mihomo::set_stream_reconnect_interval(secs);
+    log::info!("Stream reconnect interval set to {}s", secs);
+    Ok(())
+}
+
+/// 导出日志到文件
+///
+/// 包含核心日志（`[core]`）和应用日志（`[app]`），按时间排序；
+/// `level_filter` 为最低级别，`since_timestamp` 为起始时间（Unix 毫秒时间戳）。
+/// 日志中的 API 密钥、WebDAV 密码等敏感信息会被脱敏。返回导出的条数。
+#[tauri::command]
+pub async fn export_logs(
+    target_path: String,
+    level_filter: Option<String>,
+    since_timestamp: Option<i64>,
+) -> Result<usize, String> {
+    let state = get_app_state_or_err()?;
+    let min_level = level_filter
+        .as_deref()
+        .map(LogLevel::from)
+        .unwrap_or(LogLevel::Debug);
+    let since = since_timestamp.unwrap_or(0);
+
+    let core_logs = state
+        .log_streamer
+        .recent(usize::MAX)
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.time,
+                LogLevel::from(entry.log_type.as_str()),
+                format!("[core] [{}] {}", entry.log_type, entry.payload),
+            )
+        });
+    let app_logs = recent_app_logs().into_iter().map(|entry| {
+        let level = match entry.level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warning,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug | log::Level::Trace => LogLevel::Debug,
+        };
+        (
+            entry.time,
+            level,
+            format!(
+                "[app] [{}] {}: {}",
+                level.as_str(),
+                entry.target,
+                entry.message
+            ),
+        )
+    });
+
+    let mut lines: Vec<(i64, String)> = core_logs
+        .chain(app_logs)
+        .filter(|(time, level, _)| *time >= since && *level >= min_level)
+        .map(|(time, _, line)| (time, line))
+        .collect();
+    // 稳定排序，同一时间的日志保持原有顺序
+    lines.sort_by_key(|(time, _)| *time);
+
+    let mut secrets = vec![state.api_secret()];
+    if let Ok(settings) = state.config_manager.load_app_settings() {
+        secrets.push(settings.webdav.password);
+    }
+
+    let mut content = format!(
+        "# Conflux logs exported at {}\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    for (time, line) in &lines {
+        let time = chrono::DateTime::from_timestamp_millis(*time)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S%.3f")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let _ = writeln!(content, "{} {}", time, redact_text(line, &secrets));
+    }
+
+    std::fs::write(&target_path, content).map_err(|e| format!("写入日志文件失败: {}", e))?;
+    log::info!("Exported {} log entries to {}", lines.len(), target_path);
+    Ok(lines.len())
+}
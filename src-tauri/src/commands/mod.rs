@@ -1,4 +1,5 @@
 pub mod config;
+pub mod error;
 pub mod logs;
 pub mod profile;
 pub mod proxy;
@@ -11,15 +12,22 @@ pub mod webdav;
 use anyhow::Result;
 use once_cell::sync::OnceCell;
 use std::sync::Arc;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 
-use crate::config::{ConfigManager, Workspace};
-use crate::mihomo::{LogStreamer, MihomoApi, MihomoManager};
+use crate::commands::reload::{inspect_stale_backup, ReloadDebouncer, StaleBackupStatus};
+use crate::config::{ConfigManager, ProfileWatcher, Workspace};
+use crate::mihomo::{
+    AutoSwitcher, ConnectionTracer, ConnectionWatchdog, CoreState, LogStreamer, MihomoApi,
+    MihomoManager, ObservabilityBudget, RuleHitCounter, SelectionScheduler, TemporaryDirect,
+    TrafficHistory,
+};
 use crate::substore::SubStoreManager;
 use crate::utils::generate_api_secret;
 use crate::utils::get_app_data_dir;
 
+pub use error::CommandError;
+
 /// 应用状态
 #[derive(Clone)]
 pub struct AppState {
@@ -27,15 +35,49 @@ pub struct AppState {
     pub mihomo_api: Arc<MihomoApi>,
     pub config_manager: Arc<ConfigManager>,
     pub log_streamer: Arc<LogStreamer>,
+    /// 自动切换最快节点的后台任务
+    pub auto_switcher: Arc<AutoSwitcher>,
+    /// 连接数看门狗
+    pub connection_watchdog: Arc<ConnectionWatchdog>,
+    /// 按时段自动选择节点
+    pub selection_scheduler: Arc<SelectionScheduler>,
+    /// 临时直连计时
+    pub temporary_direct: Arc<TemporaryDirect>,
+    /// 流量历史（后台采样）
+    pub traffic_history: Arc<TrafficHistory>,
+    /// 连接追踪（排查路由问题）
+    pub connection_tracer: Arc<ConnectionTracer>,
+    /// 规则命中计数（后台订阅连接）
+    pub rule_hits: Arc<RuleHitCounter>,
+    /// 诊断数据的内存预算
+    pub observability: Arc<ObservabilityBudget>,
     pub substore_manager: Arc<Mutex<SubStoreManager>>,
     pub system_proxy_enabled: Arc<Mutex<bool>>,
     pub enhanced_mode: Arc<Mutex<bool>>,
-    /// API 密钥（应用运行期间保持不变，确保认证一致性）
-    pub api_secret: String,
+    /// API 密钥（仅通过 rotate_api_secret 更新，确保各客户端认证一致）
+    pub api_secret: Arc<std::sync::RwLock<String>>,
     /// Profile 切换锁，防止并发重载导致的网络错误和状态不一致
     pub profile_switch_lock: Arc<Mutex<()>>,
     /// 记录最后请求激活的 Profile ID，用于跳过过期的重载任务
     pub pending_profile_id: Arc<Mutex<Option<String>>>,
+    /// Profile 编辑后的重载防抖
+    pub reload_debouncer: Arc<ReloadDebouncer>,
+    /// LocalWatch Profile 源文件监听
+    pub profile_watcher: Arc<ProfileWatcher>,
+    /// 以安全模式启动的原因（不自动启动核心、未应用上次的 Profile），正常启动时为 None
+    pub safe_mode: Option<crate::utils::SafeModeReason>,
+    /// 启动时（重新生成 config.yaml 之前）检查到的遗留备份状态
+    pub startup_backup: StaleBackupStatus,
+}
+
+impl AppState {
+    /// 当前 API 密钥
+    pub fn api_secret(&self) -> String {
+        self.api_secret
+            .read()
+            .map(|secret| secret.clone())
+            .unwrap_or_default()
+    }
 }
 
 /// 全局应用状态（用于非命令的地方访问）
@@ -78,8 +120,62 @@ pub fn require_active_subscription_with_proxies() -> Result<(), String> {
     Ok(())
 }
 
+/// 崩溃报告中附带的最近核心日志和应用日志条数
+const CRASH_REPORT_LOG_LINES: usize = 100;
+
+/// 命令返回内部错误时生成崩溃报告（需在设置中开启）
+///
+/// 目前由 `activate_profile`、`save_config` 和 `set_tun_mode` 调用。
+/// 只处理 `CoreError`，用户输入、网络、权限等错误不记录。
+/// 报告中的 API 密钥、WebDAV 密码和 URL 路径（订阅地址）会被脱敏。
+pub fn capture_crash_report(command: &str, err: &CommandError) {
+    if !matches!(err, CommandError::CoreError(_)) {
+        return;
+    }
+    let Some(state) = try_get_app_state() else {
+        return;
+    };
+    let Ok(settings) = state.config_manager.load_app_settings() else {
+        return;
+    };
+    if !settings.crash_reports_enabled {
+        return;
+    }
+
+    let mut report = crate::utils::CrashReport::new(command, err.message());
+    report.core_state = format!("{:?}", state.mihomo_manager.core_state().get());
+    report.active_profile_id = Workspace::new()
+        .and_then(|workspace| workspace.get_active_profile_id())
+        .ok()
+        .flatten();
+    // 日志页未打开时 log_streamer 没有数据，核心日志从日志文件读取
+    report.logs = crate::utils::core_log_tail(CRASH_REPORT_LOG_LINES)
+        .into_iter()
+        .map(|line| format!("[core] {}", line))
+        .collect();
+    let app_logs = crate::utils::recent_app_logs();
+    let skip = app_logs.len().saturating_sub(CRASH_REPORT_LOG_LINES);
+    report.logs.extend(app_logs[skip..].iter().map(|entry| {
+        format!(
+            "[app] [{}] {}: {}",
+            entry.level, entry.target, entry.message
+        )
+    }));
+    report.redact(&[state.api_secret(), settings.webdav.password]);
+
+    if let Err(e) = crate::utils::save_crash_report(&report) {
+        log::warn!("Failed to save crash report: {}", e);
+    }
+}
+
 /// 初始化应用状态
 pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
+    use crate::utils::{finish_startup_timings, record_startup_phase};
+    use std::time::Instant;
+
+    let init_start = Instant::now();
+    let mut phase = Instant::now();
+
     // 并行执行资源复制操作（MiHomo 二进制 + GeoData）
     // 这两个 IO 操作完全独立，并行可以减少启动时间
     let app_clone = app.clone();
@@ -106,14 +202,28 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         Ok(Err(e)) => log::warn!("Failed to copy bundled GeoData: {}", e),
         Err(e) => log::warn!("GeoData task panicked: {}", e),
     }
+    record_startup_phase("geodata", phase.elapsed());
+    phase = Instant::now();
 
     let config_manager = Arc::new(ConfigManager::new()?);
 
     // 从 settings 获取 API 配置
     let mut app_settings = config_manager.load_app_settings()?;
-
-    // 加载当前 config.yaml
-    let mut config = config_manager.load_mihomo_config()?;
+    crate::mihomo::set_stream_reconnect_interval(app_settings.stream_reconnect_interval);
+
+    // 在重新生成 config.yaml 之前检查上次异常退出遗留的备份，否则损坏的配置会被覆盖，
+    // 无法再判断备份是否可恢复
+    let startup_backup = inspect_stale_backup(config_manager.mihomo_config_path()).await;
+
+    // 加载当前 config.yaml（备份可恢复时允许解析失败，由用户选择是否恢复）
+    let mut config = match config_manager.load_mihomo_config() {
+        Ok(config) => config,
+        Err(e) if startup_backup == StaleBackupStatus::Restorable => {
+            log::warn!("Failed to load config.yaml, backup is restorable: {}", e);
+            crate::models::MihomoConfig::default()
+        }
+        Err(e) => return Err(e),
+    };
 
     // 检查是否有激活的 Profile
     // 如果没有激活的 Profile，使用基础配置重置 config.yaml（清除残留的代理和规则）
@@ -121,6 +231,9 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
     let workspace = Workspace::new()?;
     let active_profile_id = workspace.get_active_profile_id()?;
 
+    // 安全模式：不应用上次的 Profile，前端也不会自动启动核心，便于修复或删除出错的 Profile
+    let safe_mode = crate::utils::should_enter_safe_mode();
+
     let mut config_changed = false;
     // 跟踪 settings 是否需要保存（合并多次保存为单次）
     let mut settings_changed = false;
@@ -135,7 +248,11 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
     }
     base_config.external_controller = app_settings.mihomo.external_controller.clone();
 
-    if let Some(ref profile_id) = active_profile_id {
+    if safe_mode.is_some() {
+        log::warn!("Safe mode: skipping active profile, using base config");
+        config = base_config;
+        config_changed = true;
+    } else if let Some(ref profile_id) = active_profile_id {
         // 有活跃 profile，重新激活配置
         log::info!(
             "Active profile detected: {}, regenerating config",
@@ -145,6 +262,10 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         match workspace.activate_profile(profile_id, &base_config, Some(app_settings.use_jsdelivr))
         {
             Ok(mut runtime_config) => {
+                crate::commands::reload::apply_provider_overrides(
+                    &app_settings.mihomo,
+                    &mut runtime_config,
+                );
                 runtime_config.secret = base_config.secret.clone();
                 runtime_config.external_controller = base_config.external_controller.clone();
                 config = runtime_config;
@@ -160,6 +281,9 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         config_changed = true;
     }
 
+    record_startup_phase("config", phase.elapsed());
+    phase = Instant::now();
+
     // 确定使用哪个 secret：
     // 1. 如果 settings.json 有 secret，使用它
     // 2. 如果 settings.json 没有但 config.yaml 有，使用 config.yaml 的（可能 mihomo 正在运行）
@@ -183,7 +307,9 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         new_secret
     };
 
-    let api_url = format!("http://{}", app_settings.mihomo.external_controller);
+    record_startup_phase("secret", phase.elapsed());
+
+    let (api_url, ca_cert) = crate::mihomo::controller_endpoint(&app_settings.mihomo);
 
     // 同步 secret 和 external_controller 到 config.yaml
     // 确保 MiHomo 启动时使用正确的配置
@@ -195,24 +321,76 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         config_changed = true;
     }
 
+    if config_changed && startup_backup == StaleBackupStatus::Restorable {
+        log::warn!("Keeping current config.yaml until the user decides whether to recover backup");
+        config_changed = false;
+    }
+
     if config_changed {
         config_manager.save_mihomo_config(&config)?;
         log::debug!("Synced API settings to config.yaml (or reset to default)");
     }
 
     let mihomo_manager = Arc::new(MihomoManager::new(api_secret.clone())?);
+    mihomo_manager.set_priority(app_settings.core_priority);
     let mihomo_api = Arc::new(MihomoApi::new(api_url.clone(), api_secret.clone()));
-    let log_streamer = Arc::new(LogStreamer::new(api_url, api_secret.clone()));
+    let log_streamer = Arc::new(LogStreamer::new(api_url.clone(), api_secret.clone()));
+    if ca_cert.is_some() {
+        mihomo_api.set_endpoint(api_url.clone(), ca_cert.as_deref())?;
+        log_streamer.set_endpoint(api_url, ca_cert).await;
+    }
+    let auto_switcher = Arc::new(AutoSwitcher::new(
+        mihomo_api.clone(),
+        mihomo_manager.clone(),
+    ));
+    let connection_watchdog = Arc::new(ConnectionWatchdog::new(
+        mihomo_api.clone(),
+        mihomo_manager.clone(),
+    ));
+    let selection_scheduler = Arc::new(SelectionScheduler::new(
+        mihomo_api.clone(),
+        mihomo_manager.clone(),
+    ));
+    let temporary_direct = Arc::new(TemporaryDirect::new(
+        mihomo_api.clone(),
+        mihomo_manager.clone(),
+    ));
+    let connection_tracer = Arc::new(ConnectionTracer::new(
+        mihomo_api.clone(),
+        mihomo_manager.clone(),
+    ));
+    let traffic_history = Arc::new(TrafficHistory::new());
+    traffic_history.start(mihomo_api.clone(), mihomo_manager.clone());
+    let rule_hits = Arc::new(RuleHitCounter::new());
+    rule_hits.start(app.clone(), mihomo_api.clone(), mihomo_manager.clone());
+    let observability = Arc::new(ObservabilityBudget::new(
+        traffic_history.clone(),
+        log_streamer.clone(),
+        connection_tracer.clone(),
+        rule_hits.clone(),
+    ));
+    observability.apply_settings(&app_settings.observability);
+    observability.start();
+    mihomo_manager
+        .core_state()
+        .start(app.clone(), mihomo_manager.clone());
 
     // 检测系统当前的代理状态（恢复上次的状态）
-    let current_system_proxy = crate::system::SystemProxy::get_proxy_status().unwrap_or(false);
+    let current_system_proxy =
+        crate::commands::system::system_proxy_active(&app_settings.mihomo).unwrap_or(false);
     log::info!("Detected system proxy status: {}", current_system_proxy);
 
+    phase = Instant::now();
+
     // 检查 mihomo 是否运行（缓存结果，避免重复检查）
     // 注意：Windows 上 is_running 可能被修改，所以需要 mut
     #[allow(unused_mut)]
     let mut is_running = mihomo_manager.is_running().await;
     log::debug!("Initial mihomo running check: {}", is_running);
+    if is_running {
+        mihomo_manager.core_state().set(CoreState::Running);
+        crate::utils::clear_launch_failures();
+    }
 
     // Windows: 检查服务状态并尝试通过服务启动 mihomo
     #[cfg(target_os = "windows")]
@@ -293,6 +471,8 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         false
     };
 
+    record_startup_phase("core-status", phase.elapsed());
+
     // 同步 settings.json 中的 TUN 设置与实际运行状态（延迟保存）
     if app_settings.mihomo.tun.enable != enhanced_mode {
         log::info!(
@@ -324,12 +504,24 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         mihomo_api,
         config_manager,
         log_streamer,
+        auto_switcher,
+        connection_watchdog,
+        selection_scheduler,
+        temporary_direct,
+        traffic_history,
+        connection_tracer,
+        rule_hits,
+        observability,
         substore_manager,
         system_proxy_enabled: Arc::new(Mutex::new(current_system_proxy)),
         enhanced_mode: Arc::new(Mutex::new(enhanced_mode)),
-        api_secret,
+        api_secret: Arc::new(std::sync::RwLock::new(api_secret)),
         profile_switch_lock: Arc::new(Mutex::new(())),
         pending_profile_id: Arc::new(Mutex::new(None)),
+        reload_debouncer: Arc::new(ReloadDebouncer::new()),
+        profile_watcher: Arc::new(ProfileWatcher::new()),
+        safe_mode,
+        startup_backup,
     };
 
     // 也保存到全局状态，用于非命令的地方访问
@@ -340,13 +532,74 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
         let state = state.clone();
         let app_handle = app.clone();
         async move {
+            let started = Instant::now();
             let mut manager = state.substore_manager.lock().await;
             if let Err(e) = manager.start(app_handle).await {
                 log::warn!("Sub-Store background start failed: {}", e);
             }
+            record_startup_phase("substore", started.elapsed());
+            log::info!("Sub-Store startup took {}ms", started.elapsed().as_millis());
+        }
+    });
+
+    // 监听 LocalWatch Profile 的源文件
+    state.profile_watcher.start(app.clone());
+
+    // 每天检查订阅到期和流量
+    crate::config::start_subscription_monitor(app.clone());
+
+    // 网络切换后重新应用系统代理等
+    crate::system::start_network_monitor(app.clone());
+
+    // 检测其他 Clash/mihomo 实例，避免用户排查一个不受 Conflux 管理的核心
+    tokio::spawn({
+        let state = state.clone();
+        let app_handle = app.clone();
+        let ports = crate::mihomo::conflux_ports(&app_settings.mihomo);
+        async move {
+            let report = state.mihomo_manager.detect_external_core(&ports).await;
+            if report.is_empty() {
+                return;
+            }
+            for conflict in &report.port_conflicts {
+                log::warn!(
+                    "Port {} ({}) is used by external process {} (PID {})",
+                    conflict.port,
+                    conflict.purpose,
+                    conflict.process.name,
+                    conflict.process.pid
+                );
+            }
+            for process in &report.processes {
+                log::warn!(
+                    "External core process detected: {} (PID {})",
+                    process.name,
+                    process.pid
+                );
+            }
+            let _ = app_handle.emit("external-core-detected", report);
         }
     });
 
+    // 核心已运行时恢复自动切换最快节点
+    if is_running && app_settings.auto_switch.enabled {
+        state
+            .auto_switcher
+            .start(app.clone(), app_settings.auto_switch.clone());
+    }
+
+    if app_settings.connection_limit.enabled {
+        state
+            .connection_watchdog
+            .start(app.clone(), app_settings.connection_limit.clone());
+    }
+
+    if app_settings.selection_schedule.enabled {
+        state
+            .selection_scheduler
+            .start(app.clone(), app_settings.selection_schedule.clone());
+    }
+
     // 后台确保规则数据库资源存在（不阻塞核心启动/重启）：
     // - 若资源缺失且开启自动更新，则由应用下载到数据目录
     // - 下载完成后触发 reload_configs，使运行中的核心立即加载本地资源
@@ -361,6 +614,7 @@ pub async fn init_app_state(app: &AppHandle) -> Result<AppState> {
     });
 
     log::info!("App state initialized");
+    finish_startup_timings(init_start.elapsed());
     Ok(state)
 }
 
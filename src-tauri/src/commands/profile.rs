@@ -1,9 +1,19 @@
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 use tauri::{AppHandle, Emitter, State};
 
-use crate::commands::AppState;
-use crate::config::Workspace;
-use crate::models::{ProfileConfig, ProfileMetadata, ProxyConfig, ProxyProvider, RuleProvider};
+use crate::commands::{capture_crash_report, AppState, CommandError};
+use crate::config::{
+    audit_proxies, collect_subscription_warnings, load_rule_template, sanitize_profile_name,
+    to_share_link, Composer, RuleTemplateInfo, SubscriptionWarning, Workspace,
+};
+use crate::models::{
+    ExternalImportResult, ForeignImportResult, GroupCycle, NodeRename, ProfileConfig, ProfileDiff,
+    ProfileHistoryEntry, ProfileMetadata, ProviderCheck, ProxyConfig, ProxyProvider,
+    RegionGroupsPlan, RuleDedupReport, RuleImportResult, RuleProvider, RulesetRefreshResult,
+    SecurityFinding, ShadowedRule, ShareLink, ShareLinkExport, SubscriptionValidation,
+    TemplateApplyResult, TemplateMergeMode, MAX_PROFILE_NOTES_CHARS,
+};
 
 // ==================== Profile 管理 ====================
 
@@ -54,6 +64,46 @@ pub async fn create_local_profile(
     Ok(result)
 }
 
+/// 创建监听本地文件的 Profile（源文件变化时自动重新导入并重载）
+#[tauri::command]
+pub async fn create_watch_profile(
+    name: String,
+    file_path: String,
+) -> Result<ProfileMetadata, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let result = workspace
+        .create_from_watch(&name, &file_path)
+        .map_err(|e| e.to_string())?;
+    refresh_profile_watcher();
+    on_profile_changed(None, false).await?;
+    Ok(result)
+}
+
+/// 从其他 Clash 客户端（Clash Verge / ClashX 等）的数据目录导入 Profile
+#[tauri::command]
+pub async fn import_external_profiles(dir: String) -> Result<ExternalImportResult, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let result = workspace.import_from_dir(&dir).map_err(|e| e.to_string())?;
+    if !result.imported.is_empty() {
+        on_profile_changed(None, false).await?;
+    }
+    Ok(result)
+}
+
+/// 导入 Surge / QuantumultX 配置为本地 Profile，返回无法转换的行
+#[tauri::command]
+pub async fn import_foreign_profile(
+    name: String,
+    file_path: String,
+) -> Result<ForeignImportResult, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let result = workspace
+        .create_from_foreign(&name, &file_path)
+        .map_err(|e| e.to_string())?;
+    on_profile_changed(None, false).await?;
+    Ok(result)
+}
+
 /// 创建空白 Profile
 #[tauri::command]
 pub async fn create_blank_profile(name: String) -> Result<ProfileMetadata, String> {
@@ -63,11 +113,20 @@ pub async fn create_blank_profile(name: String) -> Result<ProfileMetadata, Strin
     Ok(result)
 }
 
+/// 校验 Profile 名称，返回实际保存时使用的名称
+///
+/// 创建和重命名时会做同样的整理：去掉控制字符、合并空白并限制长度，空名称会被拒绝。
+#[tauri::command]
+pub async fn validate_profile_name(name: String) -> Result<String, String> {
+    sanitize_profile_name(&name).map_err(|e| e.to_string())
+}
+
 /// 删除 Profile
 #[tauri::command]
 pub async fn delete_profile(id: String) -> Result<(), String> {
     let workspace = Workspace::new().map_err(|e| e.to_string())?;
     workspace.delete_profile(&id).map_err(|e| e.to_string())?;
+    refresh_profile_watcher();
     on_profile_changed(None, false).await?;
     Ok(())
 }
@@ -83,19 +142,61 @@ pub async fn rename_profile(id: String, new_name: String) -> Result<ProfileMetad
     Ok(result)
 }
 
+/// 获取 Profile 修改记录（按时间顺序）
+#[tauri::command]
+pub async fn get_profile_history(id: String) -> Result<Vec<ProfileHistoryEntry>, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let metadata = workspace.get_metadata(&id).map_err(|e| e.to_string())?;
+    Ok(metadata.history)
+}
+
+/// 获取 Profile 备注
+#[tauri::command]
+pub async fn get_profile_notes(id: String) -> Result<String, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let metadata = workspace.get_metadata(&id).map_err(|e| e.to_string())?;
+    Ok(metadata.notes)
+}
+
+/// 设置 Profile 备注
+#[tauri::command]
+pub async fn set_profile_notes(id: String, notes: String) -> Result<ProfileMetadata, String> {
+    if notes.chars().count() > MAX_PROFILE_NOTES_CHARS {
+        return Err(format!("备注不能超过 {} 个字符", MAX_PROFILE_NOTES_CHARS));
+    }
+
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    workspace.set_notes(&id, &notes).map_err(|e| e.to_string())
+}
+
 /// 激活 Profile
 ///
 /// 从 settings.json 读取用户设置（ports/DNS/TUN/secret 等），
 /// 合并 profile 内容（proxies/rules 等），生成完整的运行时配置。
 ///
+/// 指定 `secondary_id` 时进入多配置模式：节点和策略组来自 `id`，
+/// 规则和规则源来自 `secondary_id`；不指定时为单配置模式。
+///
 /// 优化：保存配置后立即返回，异步执行重载，通过事件通知完成状态。
 /// 这样用户体验更流畅，无需等待 MiHomo 核心重载完成。
 #[tauri::command]
 pub async fn activate_profile(
     app: AppHandle,
     id: String,
+    secondary_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
+    activate_profile_inner(app, id, secondary_id, state)
+        .await
+        .inspect_err(|e| capture_crash_report("activate_profile", e))
+}
+
+async fn activate_profile_inner(
+    app: AppHandle,
+    id: String,
+    secondary_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
     use crate::commands::proxy::detect_run_mode;
     use crate::commands::reload::{
         build_base_config_from_settings_with_proxy_state, detect_config_change_type_with_mode,
@@ -109,16 +210,10 @@ pub async fn activate_profile(
     let system_proxy_enabled = *state.system_proxy_enabled.lock().await;
 
     // 加载当前配置（用于变更类型检测）
-    let old_config = state
-        .config_manager
-        .load_mihomo_config()
-        .map_err(|e| e.to_string())?;
+    let old_config = state.config_manager.load_mihomo_config()?;
 
     // 从 settings.json 构建基础配置（包含 ports/DNS/TUN 等）
-    let app_settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+    let app_settings = state.config_manager.load_app_settings()?;
     let mut base_config = build_base_config_from_settings_with_proxy_state(
         &app_settings.mihomo,
         system_proxy_enabled,
@@ -126,22 +221,31 @@ pub async fn activate_profile(
 
     // 使用 AppState 中的 api_secret，确保与 MihomoApi 客户端一致
     // 这避免了 settings.json 中 secret 被意外清空导致的认证失败
-    base_config.secret = state.api_secret.clone();
+    base_config.secret = state.api_secret();
 
     // 生成运行时配置（合并 profile 内容）
-    let workspace = Workspace::new().map_err(|e| e.to_string())?;
-    let mut runtime_config = workspace
-        .activate_profile(&id, &base_config, Some(app_settings.use_jsdelivr))
-        .map_err(|e| e.to_string())?;
+    let workspace = Workspace::new()?;
+    let was_active = workspace.get_metadata(&id)?.active;
+    if let Some(secondary_id) = &secondary_id {
+        workspace.get_metadata(secondary_id)?;
+    }
+    let mut runtime_config = workspace.activate_profiles(
+        &id,
+        secondary_id.as_deref(),
+        &base_config,
+        Some(app_settings.use_jsdelivr),
+    )?;
+    crate::commands::reload::apply_provider_overrides(&app_settings.mihomo, &mut runtime_config);
+    // 重复激活当前配置时不写 metadata.json，避免每次重载都产生待同步的变更
+    if !was_active {
+        record_profile_history(&workspace, &id, "激活配置");
+    }
 
     // 确保 runtime_config 中的 secret 也是正确的
-    runtime_config.secret = state.api_secret.clone();
+    runtime_config.secret = state.api_secret();
 
     // 保存配置
-    state
-        .config_manager
-        .save_mihomo_config(&runtime_config)
-        .map_err(|e| e.to_string())?;
+    state.config_manager.save_mihomo_config(&runtime_config)?;
 
     // 如果 MiHomo 未运行，直接发送完成事件并返回
     if !is_running {
@@ -179,6 +283,7 @@ pub async fn activate_profile(
     let mihomo_manager = state.mihomo_manager.clone();
     let mihomo_api = state.mihomo_api.clone();
     let profile_id = id.clone();
+    let warm_providers = app_settings.warm_providers_on_activate;
 
     // 记录本次请求的 Profile ID
     {
@@ -218,14 +323,10 @@ pub async fn activate_profile(
             }
             ConfigChangeType::ApiRestart => {
                 log::warn!("[Profile] 执行 API restart (POST /restart)");
-                match mihomo_api.restart().await {
-                    Ok(_) => {
-                        // 等待 API 重新就绪
-                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                        Ok(())
-                    }
-                    Err(e) => Err(e.to_string()),
-                }
+                mihomo_manager
+                    .api_restart(&mihomo_api)
+                    .await
+                    .map_err(|e| e.to_string())
             }
             ConfigChangeType::ProcessRestart => {
                 log::warn!("[Profile] 执行进程级重启 (stop + start)");
@@ -251,6 +352,17 @@ pub async fn activate_profile(
                         "restart_reason": restart_reason,
                     }),
                 );
+
+                crate::commands::proxy::apply_suggested_selection(&app).await;
+
+                if warm_providers {
+                    match crate::commands::proxy::warm_providers(&mihomo_api).await {
+                        Ok(results) => {
+                            let _ = app.emit("providers-warmed", results);
+                        }
+                        Err(e) => log::warn!("[Profile] Provider 预热失败: {}", e),
+                    }
+                }
             }
             Err(e) => {
                 log::error!("[Profile] 配置应用失败: {}", e);
@@ -296,15 +408,21 @@ pub async fn refresh_profile(
 
     let workspace = Workspace::new().map_err(|e| e.to_string())?;
 
-    // 检查是否是当前活跃的 Profile
+    // 检查是否是当前活跃的 Profile（包括多配置模式下的副 Profile）
     let is_active = workspace
         .get_metadata(&id)
-        .map(|m| m.active)
+        .map(|m| m.in_use())
+        .unwrap_or(false);
+
+    let allow_empty = state
+        .config_manager
+        .load_app_settings()
+        .map(|s| s.allow_empty_subscription)
         .unwrap_or(false);
 
     // 刷新远程配置
     let metadata = workspace
-        .refresh_remote(&id)
+        .refresh_remote(&id, allow_empty)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -328,14 +446,23 @@ pub async fn refresh_profile(
         );
 
         // 使用 AppState 中的 api_secret，确保与 MihomoApi 客户端一致
-        base_config.secret = state.api_secret.clone();
+        base_config.secret = state.api_secret();
 
+        // 刷新的可能是副 Profile，按当前主 Profile 重新生成
+        let active_id = workspace
+            .get_active_profile_id()
+            .map_err(|e| e.to_string())?
+            .unwrap_or_else(|| id.clone());
         let mut runtime_config = workspace
-            .activate_profile(&id, &base_config, Some(app_settings.use_jsdelivr))
+            .activate_profile(&active_id, &base_config, Some(app_settings.use_jsdelivr))
             .map_err(|e| e.to_string())?;
+        crate::commands::reload::apply_provider_overrides(
+            &app_settings.mihomo,
+            &mut runtime_config,
+        );
 
         // 确保 runtime_config 中的 secret 也是正确的
-        runtime_config.secret = state.api_secret.clone();
+        runtime_config.secret = state.api_secret();
 
         state
             .config_manager
@@ -356,9 +483,11 @@ pub async fn refresh_profile(
 
         let _ = match change_result.change_type {
             ConfigChangeType::HotReload => reload_config(None, &ReloadOptions::safe()).await,
-            ConfigChangeType::ApiRestart => {
-                state.mihomo_api.restart().await.map_err(|e| e.to_string())
-            }
+            ConfigChangeType::ApiRestart => state
+                .mihomo_manager
+                .api_restart(&state.mihomo_api)
+                .await
+                .map_err(|e| e.to_string()),
             ConfigChangeType::ProcessRestart => state
                 .mihomo_manager
                 .restart()
@@ -370,6 +499,50 @@ pub async fn refresh_profile(
     Ok(metadata)
 }
 
+/// 只刷新 Profile 的规则源（http 类型），不重新下载订阅
+///
+/// Profile 正在使用且核心运行中时，通知核心更新下载到新内容的规则源。
+#[tauri::command]
+pub async fn refresh_profile_rulesets(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<RulesetRefreshResult>, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let metadata = workspace.get_metadata(&id).map_err(|e| e.to_string())?;
+
+    let mut results = workspace
+        .refresh_rulesets(&id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if metadata.in_use() && state.mihomo_manager.is_running().await {
+        for result in results.iter_mut().filter(|r| r.updated) {
+            if let Err(e) = state.mihomo_api.update_rule_provider(&result.name).await {
+                log::warn!("Failed to update rule provider '{}': {}", result.name, e);
+                result.error = Some(format!("核心更新失败: {}", e));
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// 检查 Profile 引用的规则源/代理源本地文件，返回缺失的项及处理建议
+///
+/// 激活前调用，提前发现会导致核心 "failed to load rule provider" 的问题；
+/// `download` 为 true 时先下载缺失的 http 规则源。
+#[tauri::command]
+pub async fn check_profile_providers(
+    id: String,
+    download: Option<bool>,
+) -> Result<Vec<ProviderCheck>, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    workspace
+        .check_providers(&id, download.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 解析配置文件（预览，不保存）
 #[tauri::command]
 pub async fn parse_config_file(path: String) -> Result<ProfileConfig, String> {
@@ -379,6 +552,51 @@ pub async fn parse_config_file(path: String) -> Result<ProfileConfig, String> {
         .map_err(|e| e.to_string())
 }
 
+/// 校验本地订阅文件（完整解析并检查引用关系，不导入）
+#[tauri::command]
+pub async fn validate_subscription_yaml(path: String) -> Result<SubscriptionValidation, String> {
+    let content = match Composer::read_yaml_file(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            return Ok(SubscriptionValidation {
+                errors: vec![format!("无法读取文件：{}", e)],
+                ..Default::default()
+            })
+        }
+    };
+
+    let config = match Composer::parse_yaml(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            return Ok(SubscriptionValidation {
+                errors: vec![e.to_string()],
+                ..Default::default()
+            })
+        }
+    };
+
+    Ok(SubscriptionValidation {
+        valid: true,
+        errors: Vec::new(),
+        warnings: Composer::validate(&config),
+        proxy_count: config.proxies.len(),
+        group_count: config.proxy_groups.len(),
+        rule_count: config.rules.len(),
+    })
+}
+
+/// 检查远程订阅的到期时间和流量，返回当前需要提醒的项
+#[tauri::command]
+pub async fn check_subscription_warnings(
+    state: State<'_, AppState>,
+) -> Result<Vec<SubscriptionWarning>, String> {
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    Ok(collect_subscription_warnings(&settings.subscription_alert))
+}
+
 /// 预览远程配置（不保存）
 #[tauri::command]
 pub async fn preview_remote_config(url: String) -> Result<ProfileConfig, String> {
@@ -389,6 +607,25 @@ pub async fn preview_remote_config(url: String) -> Result<ProfileConfig, String>
         .map_err(|e| e.to_string())
 }
 
+/// 比较两个 Profile 的节点、策略组和规则（`a_id` 为基准）
+#[tauri::command]
+pub async fn diff_profiles(a_id: String, b_id: String) -> Result<ProfileDiff, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (_, a) = workspace.get_profile(&a_id).map_err(|e| e.to_string())?;
+    let (_, b) = workspace.get_profile(&b_id).map_err(|e| e.to_string())?;
+    Ok(Composer::diff(&a, &b))
+}
+
+/// 预览刷新远程订阅后的变化（不保存）
+#[tauri::command]
+pub async fn preview_profile_refresh(id: String) -> Result<ProfileDiff, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    workspace
+        .preview_refresh(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 从路径中提取文件名，生成规则源相对路径 ./ruleset/{filename}
 /// 如果提取不到文件名，生成 uuid.yaml 或 uuid.txt
 fn to_ruleset_path(path: &str, format: &str) -> String {
@@ -453,14 +690,14 @@ pub async fn export_profile_config(
         let mut base_config = build_base_config_from_settings(&app_settings.mihomo);
 
         // 使用 AppState 中的 api_secret，确保与 MihomoApi 客户端一致
-        base_config.secret = state.api_secret.clone();
+        base_config.secret = state.api_secret();
 
         let mut config = workspace
             .generate_runtime_config(&id, &base_config, Some(app_settings.use_jsdelivr))
             .map_err(|e| e.to_string())?;
 
         // 确保 runtime_config 中的 secret 也是正确的
-        config.secret = state.api_secret.clone();
+        config.secret = state.api_secret();
         config
     };
 
@@ -534,12 +771,14 @@ pub async fn add_proxy(
         serde_yaml::Value::String("local".to_string()),
     );
 
+    let history = format!("添加节点 {}", proxy.name);
     config.proxies.push(proxy);
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    record_profile_history(&workspace, &profile_id, &history);
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -571,8 +810,9 @@ pub async fn update_proxy(
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    record_profile_history(&workspace, &profile_id, &format!("修改节点 {}", proxy_name));
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -592,6 +832,7 @@ pub async fn delete_proxy(
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    record_profile_history(&workspace, &profile_id, &format!("删除节点 {}", proxy_name));
 
     let is_active = workspace
         .get_active_profile_id()
@@ -601,6 +842,262 @@ pub async fn delete_proxy(
     Ok(())
 }
 
+/// 用正则替换批量重命名节点（同时更新策略组和规则中的引用）
+///
+/// `preview` 为 true 时只返回将要进行的重命名，不保存。
+#[tauri::command]
+pub async fn rename_nodes(
+    profile_id: String,
+    pattern: String,
+    replacement: String,
+    preview: bool,
+    state: State<'_, AppState>,
+) -> Result<Vec<NodeRename>, String> {
+    let pattern = regex::Regex::new(&pattern).map_err(|e| format!("无效的正则表达式: {}", e))?;
+
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (metadata, mut config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+
+    let renames =
+        Composer::rename_proxies(&mut config, &pattern, &replacement).map_err(|e| e.to_string())?;
+    if preview || renames.is_empty() {
+        return Ok(renames);
+    }
+
+    workspace
+        .update_config(&profile_id, &config)
+        .map_err(|e| e.to_string())?;
+    record_profile_history(
+        &workspace,
+        &profile_id,
+        &format!("批量重命名 {} 个节点", renames.len()),
+    );
+
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
+    log::info!(
+        "Renamed {} proxies in profile '{}'",
+        renames.len(),
+        profile_id
+    );
+    Ok(renames)
+}
+
+/// 地区选择策略组的默认名称
+const DEFAULT_REGION_SELECT_GROUP: &str = "🌐 地区选择";
+
+/// 按节点名称的正则表达式自动生成地区策略组
+///
+/// `patterns` 为地区策略组名称到正则表达式的映射，每个地区生成一个 `url-test` 策略组，
+/// 并生成一个列出所有地区组的 `select` 策略组（默认名为「🌐 地区选择」）。
+/// 代理源中的节点由核心按同一正则筛选；与手动创建的策略组同名时拒绝生成。
+/// `preview` 为 true 时只返回方案，不保存。
+#[tauri::command]
+pub async fn build_region_groups(
+    profile_id: String,
+    patterns: BTreeMap<String, String>,
+    select_group: Option<String>,
+    preview: bool,
+    state: State<'_, AppState>,
+) -> Result<RegionGroupsPlan, String> {
+    if patterns.is_empty() {
+        return Err("请至少添加一个地区".to_string());
+    }
+    // 地区组按名称排列
+    let patterns = patterns
+        .into_iter()
+        .map(|(name, pattern)| {
+            regex::Regex::new(&pattern)
+                .map(|regex| (name.trim().to_string(), regex))
+                .map_err(|e| format!("地区「{}」的正则表达式无效: {}", name, e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let select_group = select_group
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_REGION_SELECT_GROUP.to_string());
+
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (metadata, mut config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+
+    let plan = Composer::plan_region_groups(&config, &patterns, &select_group);
+    if preview {
+        return Ok(plan);
+    }
+    Composer::apply_region_groups(&mut config, &plan, &metadata.region_groups)
+        .map_err(|e| e.to_string())?;
+
+    let mut new_metadata = workspace
+        .update_config(&profile_id, &config)
+        .map_err(|e| e.to_string())?;
+    // 记录生成的策略组，再次生成时允许覆盖
+    new_metadata.region_groups = plan
+        .groups
+        .iter()
+        .map(|g| g.name.clone())
+        .chain(std::iter::once(plan.select_group.clone()))
+        .collect();
+    workspace
+        .update_metadata(&profile_id, &new_metadata)
+        .map_err(|e| e.to_string())?;
+    record_profile_history(
+        &workspace,
+        &profile_id,
+        &format!("按地区生成 {} 个策略组", plan.groups.len()),
+    );
+
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
+    log::info!(
+        "Built {} region groups in profile '{}'",
+        plan.groups.len(),
+        profile_id
+    );
+    Ok(plan)
+}
+
+/// 检查被前面更宽泛的规则完全覆盖、永远不会命中的规则（静态分析，不需要核心运行）
+#[tauri::command]
+pub async fn find_shadowed_rules(profile_id: String) -> Result<Vec<ShadowedRule>, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (_, config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+    Ok(Composer::find_shadowed_rules(&config))
+}
+
+/// 检查策略组之间的循环引用
+///
+/// `fix` 为 true 时从每个循环中移除一条引用以打断循环，并保存配置。
+#[tauri::command]
+pub async fn find_group_cycles(
+    profile_id: String,
+    fix: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GroupCycle>, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (metadata, mut config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+
+    if !fix.unwrap_or(false) {
+        return Ok(Composer::find_group_cycles(&config));
+    }
+    let cycles = Composer::break_group_cycles(&mut config);
+    if cycles.is_empty() {
+        return Ok(cycles);
+    }
+
+    workspace
+        .update_config(&profile_id, &config)
+        .map_err(|e| e.to_string())?;
+    record_profile_history(
+        &workspace,
+        &profile_id,
+        &format!("打断 {} 处策略组循环引用", cycles.len()),
+    );
+
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
+    log::info!(
+        "Broke {} proxy group cycles in profile '{}'",
+        cycles.len(),
+        profile_id
+    );
+    Ok(cycles)
+}
+
+/// 规则去重：移除完全相同的重复规则，报告同一匹配条件指向不同策略的冲突
+///
+/// `resolve_conflicts` 为 true 时同时移除冲突中不会被命中的规则（只保留最先出现的）。
+#[tauri::command]
+pub async fn dedup_rules(
+    profile_id: String,
+    resolve_conflicts: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<RuleDedupReport, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (metadata, mut config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+
+    let report = Composer::dedup_rules(&mut config, resolve_conflicts.unwrap_or(false));
+    if report.removed.is_empty() {
+        return Ok(report);
+    }
+
+    workspace
+        .update_config(&profile_id, &config)
+        .map_err(|e| e.to_string())?;
+    record_profile_history(
+        &workspace,
+        &profile_id,
+        &format!("规则去重，移除 {} 条", report.removed.len()),
+    );
+
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
+    log::info!(
+        "Removed {} rules from profile '{}'",
+        report.removed.len(),
+        profile_id
+    );
+    Ok(report)
+}
+
+/// 检查 Profile 中节点的不安全配置（跳过证书校验、弱加密、明文传输等），只读
+#[tauri::command]
+pub async fn audit_profile_security(id: String) -> Result<Vec<SecurityFinding>, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (_metadata, config) = workspace.get_profile(&id).map_err(|e| e.to_string())?;
+    Ok(audit_proxies(&config.proxies))
+}
+
+/// 将 Profile 中的节点导出为分享链接（ss / vmess / vless / trojan）
+///
+/// `proxy_names` 为空时导出全部节点；不支持的类型跳过并记录在 `skipped` 中。
+#[tauri::command]
+pub async fn export_share_links(
+    profile_id: String,
+    proxy_names: Option<Vec<String>>,
+) -> Result<ShareLinkExport, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (_metadata, config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+
+    let mut result = ShareLinkExport::default();
+    for proxy in &config.proxies {
+        if let Some(names) = &proxy_names {
+            if !names.contains(&proxy.name) {
+                continue;
+            }
+        }
+        match to_share_link(proxy) {
+            Some(link) => result.links.push(ShareLink {
+                name: proxy.name.clone(),
+                link,
+            }),
+            None => {
+                log::warn!(
+                    "Skip exporting share link for {} (type: {})",
+                    proxy.name,
+                    proxy.proxy_type
+                );
+                result.skipped.push(proxy.name.clone());
+            }
+        }
+    }
+
+    result.text = result
+        .links
+        .iter()
+        .map(|l| l.link.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(result)
+}
+
 // ==================== 规则即时添加 ====================
 
 /// 添加规则到 Profile
@@ -625,12 +1122,14 @@ pub async fn add_rule_to_profile(
             .unwrap_or(config.rules.len())
     });
 
+    let history = format!("添加规则 {}", rule);
     config.rules.insert(insert_pos, rule);
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    record_profile_history(&workspace, &profile_id, &history);
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -650,15 +1149,98 @@ pub async fn delete_rule_from_profile(
         return Err(format!("Rule index out of bounds: {}", index));
     }
 
-    config.rules.remove(index);
+    let removed = config.rules.remove(index);
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    record_profile_history(&workspace, &profile_id, &format!("删除规则 {}", removed));
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
+/// 从远程规则列表（纯文本，每行一条）导入规则，内联到 Profile
+///
+/// 新规则插入到 MATCH 之前，已存在的规则会被跳过。
+#[tauri::command]
+pub async fn import_rules_from_url(
+    profile_id: String,
+    url: String,
+    target_override: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<RuleImportResult, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let (metadata, mut config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+
+    let target_override = target_override
+        .map(|target| target.trim().to_string())
+        .filter(|target| !target.is_empty());
+    if let Some(target) = &target_override {
+        let exists = Composer::BUILTIN_TARGETS.contains(&target.as_str())
+            || config.proxy_groups.iter().any(|g| &g.name == target)
+            || config.proxies.iter().any(|p| &p.name == target);
+        if !exists {
+            return Err(format!("策略组不存在: {}", target));
+        }
+    }
+
+    let content = Composer::fetch_text(&url)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (rules, skipped) = Composer::parse_rule_list(&content, target_override.as_deref());
+    if rules.is_empty() {
+        return Err("规则列表中没有可导入的规则".to_string());
+    }
+
+    let warnings_before: HashSet<String> = Composer::validate(&config).into_iter().collect();
+
+    let mut existing: HashSet<String> = config.rules.iter().cloned().collect();
+    let mut new_rules = Vec::new();
+    let mut duplicates = 0;
+    for rule in rules {
+        if existing.insert(rule.clone()) {
+            new_rules.push(rule);
+        } else {
+            duplicates += 1;
+        }
+    }
+
+    let added = new_rules.len();
+    if added > 0 {
+        let insert_pos = config
+            .rules
+            .iter()
+            .position(|r| r.starts_with("MATCH,"))
+            .unwrap_or(config.rules.len());
+        config.rules.splice(insert_pos..insert_pos, new_rules);
+
+        workspace
+            .update_config(&profile_id, &config)
+            .map_err(|e| e.to_string())?;
+        record_profile_history(
+            &workspace,
+            &profile_id,
+            &format!("从 {} 导入 {} 条规则", url, added),
+        );
+
+        on_profile_changed(Some(&state), metadata.in_use()).await?;
+    }
+
+    let warnings = Composer::validate(&config)
+        .into_iter()
+        .filter(|warning| !warnings_before.contains(warning))
+        .collect();
+
+    Ok(RuleImportResult {
+        added,
+        skipped,
+        duplicates,
+        warnings,
+    })
+}
+
 /// 添加 rule-provider 到 Profile（如果已存在则更新）
 #[tauri::command]
 pub async fn add_rule_provider_to_profile(
@@ -680,12 +1262,14 @@ pub async fn add_rule_provider_to_profile(
     }
 
     // 支持 upsert：如果已存在则更新，否则添加
+    let history = format!("添加规则集 {}", name);
     config.rule_providers.insert(name, provider);
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    record_profile_history(&workspace, &profile_id, &history);
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -703,22 +1287,15 @@ pub async fn delete_rule_provider_from_profile(
 
     config.rule_providers.remove(&name);
 
-    // 移除引用该 provider 的规则
-    config.rules.retain(|rule| {
-        if rule.starts_with("RULE-SET,") {
-            let parts: Vec<&str> = rule.split(',').collect();
-            if parts.len() >= 2 && parts[1] == name {
-                return false;
-            }
-        }
-        true
-    });
+    // 移除引用该 provider 的规则（含子规则）
+    Composer::retain_rule_sets(&mut config, |provider| provider != name);
 
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    record_profile_history(&workspace, &profile_id, &format!("删除规则集 {}", name));
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -734,14 +1311,162 @@ pub async fn update_profile_config(
         .get_metadata(&profile_id)
         .map_err(|e| e.to_string())?;
 
-    let new_metadata = workspace
+    let mut new_metadata = workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
+    new_metadata.record_history("编辑配置");
+    workspace
+        .update_metadata(&profile_id, &new_metadata)
+        .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(new_metadata)
 }
 
+/// 将规则模板（规则、规则源、代理源、策略组）应用到多个 Profile
+///
+/// 每个 Profile 单独合并并校验，出现新的校验警告时不保存该 Profile。
+/// 目标中包含正在使用的 Profile 时，全部处理完后重载一次。
+#[tauri::command]
+pub async fn apply_template_to_profiles(
+    template: ProfileConfig,
+    profile_ids: Vec<String>,
+    merge_mode: TemplateMergeMode,
+    state: State<'_, AppState>,
+) -> Result<Vec<TemplateApplyResult>, String> {
+    if template.rules.is_empty()
+        && template.rule_providers.is_empty()
+        && template.proxy_providers.is_empty()
+        && template.proxy_groups.is_empty()
+    {
+        return Err("模板中没有规则、规则源、代理源或策略组".to_string());
+    }
+
+    let template = prepare_template(template);
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let mut results = Vec::with_capacity(profile_ids.len());
+    let mut reload_needed = false;
+
+    for profile_id in profile_ids {
+        let (result, in_use) =
+            apply_template_to_profile(&workspace, profile_id, &template, merge_mode);
+        reload_needed |= result.applied && in_use;
+        results.push(result);
+    }
+
+    on_profile_changed(Some(&state), reload_needed).await?;
+    Ok(results)
+}
+
+/// 列出内置规则模板
+#[tauri::command]
+pub async fn list_rule_templates() -> Result<Vec<RuleTemplateInfo>, String> {
+    Ok(crate::config::list_rule_templates())
+}
+
+/// 将内置规则模板应用到 Profile
+///
+/// 默认追加合并：模板规则插入到 MATCH 之前，与已有策略组重名的模板策略组重命名后追加，
+/// 不会覆盖用户的规则和策略组；需要整体替换时显式传入 `replace`。校验出现新的警告时不保存。
+#[tauri::command]
+pub async fn apply_rule_template(
+    profile_id: String,
+    template_id: String,
+    merge_mode: Option<TemplateMergeMode>,
+    state: State<'_, AppState>,
+) -> Result<TemplateApplyResult, String> {
+    let template = load_rule_template(&template_id).map_err(|e| e.to_string())?;
+    let template = prepare_template(template);
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+
+    let (result, in_use) = apply_template_to_profile(
+        &workspace,
+        profile_id,
+        &template,
+        merge_mode.unwrap_or(TemplateMergeMode::Append),
+    );
+    on_profile_changed(Some(&state), result.applied && in_use).await?;
+    Ok(result)
+}
+
+/// 去掉模板中的节点，规则源和代理源路径转换为相对路径
+fn prepare_template(mut template: ProfileConfig) -> ProfileConfig {
+    template.proxies.clear();
+    for provider in template.rule_providers.values_mut() {
+        if let Some(path) = &provider.path {
+            let format = provider.format.as_deref().unwrap_or("yaml");
+            provider.path = Some(to_ruleset_path(path, format));
+        }
+    }
+    for provider in template.proxy_providers.values_mut() {
+        if let Some(path) = &provider.path {
+            provider.path = Some(to_proxyset_path(path));
+        }
+    }
+    template
+}
+
+/// 合并模板到单个 Profile 并校验，返回结果及该 Profile 是否正在使用
+fn apply_template_to_profile(
+    workspace: &Workspace,
+    profile_id: String,
+    template: &ProfileConfig,
+    merge_mode: TemplateMergeMode,
+) -> (TemplateApplyResult, bool) {
+    let (metadata, mut config) = match workspace.get_profile(&profile_id) {
+        Ok(profile) => profile,
+        Err(e) => {
+            let result = TemplateApplyResult {
+                profile_id,
+                applied: false,
+                warnings: Vec::new(),
+                renamed_groups: Vec::new(),
+                error: Some(e.to_string()),
+            };
+            return (result, false);
+        }
+    };
+    let in_use = metadata.in_use();
+
+    let warnings_before: HashSet<String> = Composer::validate(&config).into_iter().collect();
+    let renamed_groups = Composer::apply_template(&mut config, template, merge_mode);
+    let warnings: Vec<String> = Composer::validate(&config)
+        .into_iter()
+        .filter(|warning| !warnings_before.contains(warning))
+        .collect();
+
+    if !warnings.is_empty() {
+        let result = TemplateApplyResult {
+            profile_id,
+            applied: false,
+            warnings,
+            renamed_groups,
+            error: Some("应用模板后配置校验未通过".to_string()),
+        };
+        return (result, in_use);
+    }
+
+    if let Err(e) = workspace.update_config(&profile_id, &config) {
+        let result = TemplateApplyResult {
+            profile_id,
+            applied: false,
+            warnings,
+            renamed_groups,
+            error: Some(e.to_string()),
+        };
+        return (result, in_use);
+    }
+    record_profile_history(workspace, &profile_id, "应用规则模板");
+    let result = TemplateApplyResult {
+        profile_id,
+        applied: true,
+        warnings,
+        renamed_groups,
+        error: None,
+    };
+    (result, in_use)
+}
+
 // ==================== Proxy Provider CRUD ====================
 
 /// 添加 proxy-provider 到 Profile（如果已存在则更新）
@@ -769,7 +1494,7 @@ pub async fn add_proxy_provider_to_profile(
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -801,7 +1526,7 @@ pub async fn update_proxy_provider_in_profile(
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -828,7 +1553,7 @@ pub async fn delete_proxy_provider_from_profile(
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -861,7 +1586,7 @@ pub async fn update_rule_provider_in_profile(
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     Ok(())
 }
 
@@ -926,7 +1651,7 @@ pub async fn rename_rule_provider_in_profile(
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     log::info!(
         "Renamed rule provider '{}' to '{}' in profile '{}'",
         old_name,
@@ -988,7 +1713,7 @@ pub async fn rename_proxy_provider_in_profile(
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     log::info!(
         "Renamed proxy provider '{}' to '{}' in profile '{}'",
         old_name,
@@ -1032,49 +1757,16 @@ pub async fn rename_proxy_group_in_profile(
     // 1. 更新策略组本身
     config.proxy_groups[group_index] = group;
 
-    // 2. 如果名称改变，更新所有引用
+    // 2. 如果名称改变，更新其他策略组、规则和子规则中的引用
     if old_name != new_name {
-        // 2a. 更新其他策略组中的 proxies 引用
-        for g in &mut config.proxy_groups {
-            for proxy in g.proxies.iter_mut() {
-                if proxy == &old_name {
-                    *proxy = new_name.clone();
-                }
-            }
-        }
-
-        // 2b. 更新规则中的策略引用
-        // 规则格式: TYPE,payload,policy 或 MATCH,policy
-        for rule in &mut config.rules {
-            let parts: Vec<&str> = rule.split(',').collect();
-            if parts.is_empty() {
-                continue;
-            }
-
-            let rule_type = parts[0];
-
-            // MATCH 规则: MATCH,policy
-            if rule_type == "MATCH" && parts.len() >= 2 && parts[1] == old_name {
-                *rule = format!("MATCH,{}", new_name);
-                continue;
-            }
-
-            // 其他规则: TYPE,payload,policy[,extra...]
-            if parts.len() >= 3 && parts[2] == old_name {
-                let mut new_parts: Vec<&str> = parts.clone();
-                // 我们需要替换第三部分
-                let new_name_ref: &str = &new_name;
-                new_parts[2] = new_name_ref;
-                *rule = new_parts.join(",");
-            }
-        }
+        Composer::rename_group_references(&mut config, &old_name, &new_name);
     }
 
     workspace
         .update_config(&profile_id, &config)
         .map_err(|e| e.to_string())?;
 
-    on_profile_changed(Some(&state), metadata.active).await?;
+    on_profile_changed(Some(&state), metadata.in_use()).await?;
     log::info!(
         "Renamed proxy group '{}' to '{}' in profile '{}'",
         old_name,
@@ -1086,9 +1778,23 @@ pub async fn rename_proxy_group_in_profile(
 
 // ==================== 辅助函数 ====================
 
+/// 按当前 Profile 列表更新监听的源文件
+fn refresh_profile_watcher() {
+    if let Some(state) = crate::commands::try_get_app_state() {
+        state.profile_watcher.refresh();
+    }
+}
+
+/// 记录 Profile 修改历史（失败只记录日志，不影响主流程）
+fn record_profile_history(workspace: &Workspace, id: &str, description: &str) {
+    if let Err(e) = workspace.record_history(id, description) {
+        log::warn!("Failed to record history for profile {}: {}", id, e);
+    }
+}
+
 /// Profile 变更后的统一处理
 ///
-/// - 如果是活跃 Profile，重载配置
+/// - 如果是活跃 Profile，重载配置（经过防抖，连续编辑只重载一次）
 async fn on_profile_changed(
     state: Option<&State<'_, AppState>>,
     is_active: bool,
@@ -1096,35 +1802,38 @@ async fn on_profile_changed(
     // 如果是活跃 Profile 且提供了 state，重载配置
     if is_active {
         if let Some(state) = state {
-            reload_active_profile_internal(state).await?;
+            reload_active_profile(state).await?;
         }
     }
 
     Ok(())
 }
 
-/// 重载活跃 Profile 的内部实现
-async fn reload_active_profile_internal(state: &State<'_, AppState>) -> Result<(), String> {
-    use crate::commands::reload::{
-        build_base_config_from_settings_with_proxy_state, reload_config, ConfigBackup,
-        ReloadOptions,
+/// 重载活跃 Profile（经过防抖，窗口内的多次请求只重载一次）
+pub async fn reload_active_profile(state: &AppState) -> Result<(), String> {
+    let Some(_guard) = state.reload_debouncer.wait().await else {
+        return Ok(());
     };
+    reload_active_profile_internal(state).await
+}
+
+/// 根据活跃 Profile 重新生成并写入 config.yaml（不重载核心）
+///
+/// 没有活跃 Profile 时返回 false
+pub fn write_active_runtime_config(
+    state: &AppState,
+    system_proxy_enabled: bool,
+) -> Result<bool, String> {
+    use crate::commands::reload::build_base_config_from_settings_with_proxy_state;
 
     let workspace = Workspace::new().map_err(|e| e.to_string())?;
-    let active_id = match workspace
+    let Some(active_id) = workspace
         .get_active_profile_id()
         .map_err(|e| e.to_string())?
-    {
-        Some(id) => id,
-        None => return Ok(()),
+    else {
+        return Ok(false);
     };
 
-    // 获取系统代理状态，决定是否启用端口
-    let system_proxy_enabled = *state.system_proxy_enabled.lock().await;
-
-    // 创建配置备份
-    let backup = ConfigBackup::create(state).map_err(|e| e.to_string())?;
-
     // 从 settings.json 构建基础配置
     let app_settings = state
         .config_manager
@@ -1136,20 +1845,38 @@ async fn reload_active_profile_internal(state: &State<'_, AppState>) -> Result<(
     );
 
     // 使用 AppState 中的 api_secret，确保与 MihomoApi 客户端一致
-    base_config.secret = state.api_secret.clone();
+    base_config.secret = state.api_secret();
 
     let mut runtime_config = workspace
         .activate_profile(&active_id, &base_config, Some(app_settings.use_jsdelivr))
         .map_err(|e| e.to_string())?;
+    crate::commands::reload::apply_provider_overrides(&app_settings.mihomo, &mut runtime_config);
 
     // 确保 runtime_config 中的 secret 也是正确的
-    runtime_config.secret = state.api_secret.clone();
+    runtime_config.secret = state.api_secret();
 
     state
         .config_manager
         .save_mihomo_config(&runtime_config)
         .map_err(|e| e.to_string())?;
 
+    Ok(true)
+}
+
+/// 重载活跃 Profile 的内部实现
+async fn reload_active_profile_internal(state: &AppState) -> Result<(), String> {
+    use crate::commands::reload::{reload_config, ConfigBackup, ReloadOptions};
+
+    // 获取系统代理状态，决定是否启用端口
+    let system_proxy_enabled = *state.system_proxy_enabled.lock().await;
+
+    // 创建配置备份
+    let backup = ConfigBackup::create(state).map_err(|e| e.to_string())?;
+
+    if !write_active_runtime_config(state, system_proxy_enabled)? {
+        return Ok(());
+    }
+
     // 使用统一的重载机制
     let options = ReloadOptions::safe();
     if let Err(e) = reload_config(None, &options).await {
@@ -1,7 +1,20 @@
-use crate::commands::get_app_state_or_err;
+use crate::commands::{capture_crash_report, get_app_state_or_err, AppState, CommandError};
+use crate::config::{Composer, Workspace};
+use crate::mihomo::{
+    conflux_ports, parse_schedule_time, CoreConfigSchema, CoreState, ExternalCoreAction,
+    ExternalCoreReport, MihomoManager, ObservabilityStats, RuleHitStats, StandaloneNodeTest,
+    TemporaryDirectStatus, TraceEntry, TraceStatus, MAX_OBSERVABILITY_BUDGET_MB,
+    MAX_TRAFFIC_RETENTION_MINUTES, MIN_AUTO_SWITCH_INTERVAL_SECS, MIN_CONNECTION_LIMIT,
+    MIN_OBSERVABILITY_BUDGET_MB,
+};
 use crate::models::{
-    ConnectionsResponse, ProxyGroup, ProxyStatus, RuleItem, TrafficData, VersionInfo,
+    AutoSwitchSettings, ChainLatency, ConnectionLimitSettings, ConnectionSortKey, ConnectionsPage,
+    ConnectionsResponse, CorePriority, LanAccessSettings, ObservabilitySettings, ProxyConfig,
+    ProxyGroup, ProxyIntegration, ProxyStatus, RuleImpact, RuleItem, ScriptConfig, SelectionChange,
+    SelectionPreset, SelectionPresetResult, SelectionResetResult, SelectionScheduleSettings,
+    SkippedSelection, SnifferConfig, StateReconcile, TrafficData, VersionInfo,
 };
+use crate::system::{TunDiagnosis, TunDiagnosisStatus};
 use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 
@@ -38,10 +51,19 @@ pub async fn check_tun_consistency() -> Result<serde_json::Value, String> {
 }
 
 /// 启动代理
+///
+/// `core_only` 为 true 时仅启动核心：本次运行关闭 TUN、清除系统代理，
+/// 只在本地端口提供代理，供浏览器扩展或手动配置的应用使用。
+/// 只修改运行时的 config.yaml，不改变用户保存的 TUN 设置。
 #[tauri::command]
-pub async fn start_proxy(app: AppHandle) -> Result<ProxyStatus, String> {
+pub async fn start_proxy(app: AppHandle, core_only: Option<bool>) -> Result<ProxyStatus, String> {
     let state = get_app_state_or_err()?;
 
+    if core_only.unwrap_or(false) {
+        log::info!("Starting proxy in core-only mode (no system proxy, TUN disabled)...");
+        disable_system_integration(state, false).await?;
+    }
+
     state
         .mihomo_manager
         .start()
@@ -50,6 +72,9 @@ pub async fn start_proxy(app: AppHandle) -> Result<ProxyStatus, String> {
 
     log::info!("Proxy started successfully");
 
+    resume_auto_switch(&app).await;
+    apply_suggested_selection(&app).await;
+
     // 启动成功后，获取完整状态并返回
     let status = get_proxy_status().await?;
 
@@ -69,21 +94,48 @@ pub async fn start_proxy_normal_mode(app: AppHandle) -> Result<(), String> {
 
     log::info!("Starting proxy in normal mode (forced, TUN disabled)...");
 
-    // 1. 禁用配置文件中的 TUN 模式
+    // 1. 禁用 TUN、清除系统代理
+    disable_system_integration(state, true).await?;
+
+    // 2. 启动 mihomo（现在配置中 TUN 已禁用，会以普通模式启动）
+    state
+        .mihomo_manager
+        .start()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Proxy started in normal mode successfully");
+
+    resume_auto_switch(&app).await;
+    apply_suggested_selection(&app).await;
+
+    // 3. 同步状态到托盘菜单和前端
+    sync_proxy_status(&app).await;
+
+    Ok(())
+}
+
+/// 禁用 TUN 并清除系统代理（启动前调用）
+///
+/// `persist` 为 false 时只修改运行时的 config.yaml，settings.json 中的 TUN 设置保持不变。
+async fn disable_system_integration(state: &AppState, persist: bool) -> Result<(), String> {
+    // 禁用配置文件中的 TUN 模式
     if let Err(e) = state.config_manager.update_tun_mode(false) {
         log::error!("Failed to disable TUN in config: {}", e);
         return Err(format!("禁用增强模式配置失败: {}", e));
     }
 
-    // 2. 更新 settings.json
-    if let Ok(mut app_settings) = state.config_manager.load_app_settings() {
+    // 更新 settings.json
+    if !persist {
+        log::debug!("Skipping TUN setting sync for core-only start");
+    } else if let Ok(mut app_settings) = state.config_manager.load_app_settings() {
         app_settings.mihomo.tun.enable = false;
         if let Err(e) = state.config_manager.save_app_settings(&app_settings) {
             log::warn!("Failed to sync TUN setting to settings.json: {}", e);
         }
     }
 
-    // 3. 清除系统代理状态（如果有）
+    // 清除系统代理状态（如果有）
     {
         let mut system_proxy = state.system_proxy_enabled.lock().await;
         if *system_proxy {
@@ -92,24 +144,8 @@ pub async fn start_proxy_normal_mode(app: AppHandle) -> Result<(), String> {
         }
     }
 
-    // 4. 清除增强模式状态
-    {
-        let mut enhanced_mode = state.enhanced_mode.lock().await;
-        *enhanced_mode = false;
-    }
-
-    // 5. 启动 mihomo（现在配置中 TUN 已禁用，会以普通模式启动）
-    state
-        .mihomo_manager
-        .start()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    log::info!("Proxy started in normal mode successfully");
-
-    // 6. 同步状态到托盘菜单和前端
-    sync_proxy_status(&app).await;
-
+    // 清除增强模式状态
+    *state.enhanced_mode.lock().await = false;
     Ok(())
 }
 
@@ -131,6 +167,9 @@ pub async fn stop_proxy(app: AppHandle) -> Result<(), String> {
     *enhanced_mode = false;
     drop(enhanced_mode);
 
+    state.auto_switcher.stop();
+    state.temporary_direct.cancel();
+
     state
         .mihomo_manager
         .stop()
@@ -157,6 +196,14 @@ pub async fn restart_proxy(app: AppHandle) -> Result<(), String> {
         .await
         .map_err(|e| e.to_string())?;
 
+    // 重启后核心使用配置文件中的模式，临时直连随之失效
+    if state.temporary_direct.cancel() {
+        let _ = app.emit("temporary-direct-changed", TemporaryDirectStatus::default());
+    }
+
+    resume_auto_switch(&app).await;
+    apply_suggested_selection(&app).await;
+
     // 发送状态变更事件
     if let Ok(status) = get_proxy_status().await {
         let _ = app.emit("proxy-status-changed", status);
@@ -166,6 +213,77 @@ pub async fn restart_proxy(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// 获取核心运行状态（后续变化通过 core-state-changed 事件推送）
+#[tauri::command]
+pub async fn get_core_state() -> Result<CoreState, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.mihomo_manager.core_state().get())
+}
+
+/// 检测外部核心：占用 Conflux 端口的其他进程，以及不是由 Conflux 启动的 mihomo/Clash 进程
+#[tauri::command]
+pub async fn detect_external_core() -> Result<ExternalCoreReport, String> {
+    let state = get_app_state_or_err()?;
+
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    Ok(state
+        .mihomo_manager
+        .detect_external_core(&conflux_ports(&app_settings.mihomo))
+        .await)
+}
+
+/// 处理检测到的外部核心
+///
+/// `takeOver` 结束外部核心进程，之后可以正常启动核心；端口被其他程序占用时不会结束该程序，
+/// 需要先修改端口。`coexist` 保留外部进程，但外部进程占用了 Conflux 的端口时无法共存。
+#[tauri::command]
+pub async fn resolve_external_core(
+    action: ExternalCoreAction,
+) -> Result<ExternalCoreReport, String> {
+    let report = detect_external_core().await?;
+
+    match action {
+        ExternalCoreAction::TakeOver => {
+            let foreign = report.foreign_port_conflicts();
+            if !foreign.is_empty() {
+                let holders: Vec<String> = foreign
+                    .iter()
+                    .map(|c| {
+                        format!(
+                            "端口 {} 被 {} (PID: {}) 占用",
+                            c.port, c.process.name, c.process.pid
+                        )
+                    })
+                    .collect();
+                return Err(format!(
+                    "{}，这些程序不是代理核心，不会被结束，请修改端口后重试",
+                    holders.join("；")
+                ));
+            }
+            let remaining = MihomoManager::kill_external_processes(&report);
+            if !remaining.is_empty() {
+                return Err(format!(
+                    "无法结束外部进程 (PID: {:?})，请手动结束后重试",
+                    remaining
+                ));
+            }
+            detect_external_core().await
+        }
+        ExternalCoreAction::Coexist => {
+            if let Some(conflict) = report.port_conflicts.first() {
+                return Err(format!(
+                    "端口 {} 已被 {} (PID: {}) 占用，无法共存，请修改端口或选择接管",
+                    conflict.port, conflict.process.name, conflict.process.pid
+                ));
+            }
+            Ok(report)
+        }
+    }
+}
+
 /// 获取代理状态
 #[tauri::command]
 pub async fn get_proxy_status() -> Result<ProxyStatus, String> {
@@ -197,9 +315,16 @@ pub async fn get_proxy_status() -> Result<ProxyStatus, String> {
     // 检测运行模式
     let run_mode = detect_run_mode(running).await;
 
+    // 临时直连只修改了运行时模式
+    let mode = if running && state.temporary_direct.is_active() {
+        "direct".to_string()
+    } else {
+        config.mode
+    };
+
     Ok(ProxyStatus {
         running,
-        mode: config.mode,
+        mode,
         port: settings.mihomo.port.unwrap_or(7890),
         socks_port: settings.mihomo.socks_port.unwrap_or(7891),
         mixed_port: settings.mihomo.mixed_port.unwrap_or(0),
@@ -209,6 +334,137 @@ pub async fn get_proxy_status() -> Result<ProxyStatus, String> {
         ipv6: config.ipv6,
         tcp_concurrent: config.tcp_concurrent,
         run_mode,
+        integration: ProxyIntegration::detect(running, system_proxy, enhanced_mode),
+    })
+}
+
+/// 与核心的实际状态对齐
+///
+/// 其他面板或直接调用控制器 API 修改模式、TUN 等设置后，应用缓存的状态和托盘会过期。
+/// 重新读取核心的 `/configs`、策略组选择和系统代理状态，修正 config.yaml 与 AppState，
+/// 有差异时发送 `proxy-status-changed`。窗口获得焦点时自动调用。
+#[tauri::command]
+pub async fn reconcile_state(app: AppHandle) -> Result<StateReconcile, String> {
+    let state = get_app_state_or_err()?;
+    let mut changes = Vec::new();
+    let mut selections = std::collections::HashMap::new();
+
+    // 系统代理可能被其他程序关闭或修改
+    let actual = state
+        .config_manager
+        .load_app_settings()
+        .and_then(|settings| crate::commands::system::system_proxy_active(&settings.mihomo));
+    if let Ok(actual) = actual {
+        let mut system_proxy = state.system_proxy_enabled.lock().await;
+        if *system_proxy != actual {
+            changes.push(format!("system_proxy: {} -> {}", *system_proxy, actual));
+            *system_proxy = actual;
+        }
+    }
+
+    if state.mihomo_manager.is_running().await {
+        let runtime = state
+            .mihomo_api
+            .get_configs()
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut config = state
+            .config_manager
+            .load_mihomo_config()
+            .map_err(|e| e.to_string())?;
+        let mut config_changed = false;
+
+        if let Some(mode) = runtime.get("mode").and_then(|v| v.as_str()) {
+            let mode = mode.to_lowercase();
+            if state.temporary_direct.is_active() {
+                // 临时直连期间模式被外部改掉，视为临时直连已结束
+                if mode != "direct" && state.temporary_direct.cancel() {
+                    changes.push(format!("temporary_direct: ended ({})", mode));
+                    let _ = app.emit("temporary-direct-changed", TemporaryDirectStatus::default());
+                }
+            }
+            if !state.temporary_direct.is_active() && config.mode != mode {
+                changes.push(format!("mode: {} -> {}", config.mode, mode));
+                config.mode = mode.clone();
+                config_changed = true;
+                if let Ok(mut app_settings) = state.config_manager.load_app_settings() {
+                    app_settings.mihomo.mode = mode;
+                    if let Err(e) = state.config_manager.save_app_settings(&app_settings) {
+                        log::warn!("Failed to save mode to settings.json: {}", e);
+                    }
+                }
+            }
+        }
+
+        if let Some(allow_lan) = runtime.get("allow-lan").and_then(|v| v.as_bool()) {
+            if config.allow_lan != allow_lan {
+                changes.push(format!("allow_lan: {} -> {}", config.allow_lan, allow_lan));
+                config.allow_lan = allow_lan;
+                config_changed = true;
+            }
+        }
+        if let Some(ipv6) = runtime.get("ipv6").and_then(|v| v.as_bool()) {
+            if config.ipv6 != ipv6 {
+                changes.push(format!("ipv6: {} -> {}", config.ipv6, ipv6));
+                config.ipv6 = ipv6;
+                config_changed = true;
+            }
+        }
+        if let Some(tun_enabled) = runtime
+            .get("tun")
+            .and_then(|tun| tun.get("enable"))
+            .and_then(|v| v.as_bool())
+        {
+            let tun = config.tun.get_or_insert_with(Default::default);
+            if tun.enable != tun_enabled {
+                changes.push(format!("tun: {} -> {}", tun.enable, tun_enabled));
+                tun.enable = tun_enabled;
+                config_changed = true;
+            }
+            *state.enhanced_mode.lock().await = tun_enabled;
+        }
+
+        if config_changed {
+            state
+                .config_manager
+                .save_mihomo_config(&config)
+                .map_err(|e| e.to_string())?;
+
+            // settings.json 是 config.yaml 的来源，不同步的话下次重新生成配置会把外部修改还原
+            if let Ok(mut app_settings) = state.config_manager.load_app_settings() {
+                app_settings.mihomo.allow_lan = config.allow_lan;
+                app_settings.mihomo.ipv6 = config.ipv6;
+                if let Some(tun) = &config.tun {
+                    app_settings.mihomo.tun.enable = tun.enable;
+                }
+                if let Err(e) = state.config_manager.save_app_settings(&app_settings) {
+                    log::warn!("Failed to save reconciled settings to settings.json: {}", e);
+                }
+            }
+        }
+
+        match state.mihomo_api.get_proxies().await {
+            Ok(proxies) => {
+                selections = proxies
+                    .proxies
+                    .into_iter()
+                    .filter_map(|(name, info)| Some((name, info.now?)))
+                    .collect();
+            }
+            Err(e) => log::debug!("Failed to read proxy selections: {}", e),
+        }
+    }
+
+    let status = get_proxy_status().await?;
+    if !changes.is_empty() {
+        log::info!("Reconciled app state with core: {}", changes.join(", "));
+        crate::commands::reload::publish_proxy_status(&app, &status);
+    }
+
+    Ok(StateReconcile {
+        changes,
+        selections,
+        status,
     })
 }
 
@@ -268,18 +524,177 @@ pub async fn detect_run_mode(running: bool) -> crate::models::RunMode {
     }
 }
 
+/// 获取局域网共享设置
+#[tauri::command]
+pub async fn get_lan_access() -> Result<LanAccessSettings, String> {
+    let state = get_app_state_or_err()?;
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    Ok(LanAccessSettings {
+        allow_lan: settings.mihomo.allow_lan,
+        bind_address: settings.mihomo.bind_address,
+        allowed_ips: settings.mihomo.lan_allowed_ips,
+    })
+}
+
 /// 设置 LAN 访问开关
+///
+/// `bind_address`、`allowed_ips` 未传入时保持原设置；
+/// `bind_address` 传入空字符串时恢复默认（`*`），`allowed_ips` 传入空列表时不限制网段。
+/// `bind_address` 为回环地址（`127.0.0.1`、`::1` 等）时等同于关闭局域网共享。
 #[tauri::command]
-pub async fn set_allow_lan(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub async fn set_allow_lan(
+    app: AppHandle,
+    enabled: bool,
+    bind_address: Option<String>,
+    allowed_ips: Option<Vec<String>>,
+) -> Result<(), String> {
     use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
 
+    let bind_address = bind_address.map(|addr| addr.trim().to_string());
+    if let Some(addr) = bind_address.as_deref().filter(|addr| !addr.is_empty()) {
+        validate_bind_address(addr)?;
+    }
+    // 监听回环地址等同于关闭局域网共享
+    let loopback = bind_address.as_deref().is_some_and(is_loopback_address);
+    let allowed_ips = allowed_ips.map(|ips| {
+        ips.iter()
+            .map(|ip| ip.trim().to_string())
+            .filter(|ip| !ip.is_empty())
+            .collect::<Vec<_>>()
+    });
+    if let Some(ips) = &allowed_ips {
+        for cidr in ips {
+            validate_cidr(cidr)?;
+        }
+    }
+
     apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
-        settings.allow_lan = enabled;
+        settings.allow_lan = enabled && !loopback;
+        if let Some(addr) = bind_address.filter(|_| !loopback) {
+            settings.bind_address = Some(addr).filter(|addr| !addr.is_empty());
+        }
+        if let Some(ips) = allowed_ips {
+            settings.lan_allowed_ips = ips;
+        }
         Ok(())
     })
     .await
 }
 
+/// 设置核心代理端口的监听地址
+///
+/// - 回环地址（如 `127.0.0.1`、`::1`）：关闭局域网共享，只允许本机连接
+/// - 其他 IP：开启局域网共享并只监听该地址
+/// - `*`：开启局域网共享并监听所有地址
+///
+/// 监听地址变更需要重启核心进程。
+#[tauri::command]
+pub async fn set_bind_address(app: AppHandle, addr: String) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let addr = addr.trim().to_string();
+    validate_bind_address(&addr)?;
+    let loopback = is_loopback_address(&addr);
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.allow_lan = !loopback;
+        if !loopback {
+            settings.bind_address = Some(addr.clone()).filter(|addr| addr != "*");
+        }
+        Ok(())
+    })
+    .await?;
+
+    log::info!("Bind address set to: {}", addr);
+    Ok(())
+}
+
+/// 监听地址是否为回环地址（`127.0.0.0/8`、`::1`）
+fn is_loopback_address(addr: &str) -> bool {
+    addr.parse::<std::net::IpAddr>()
+        .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// 校验局域网共享监听地址（`*` 或 IP 地址）
+fn validate_bind_address(addr: &str) -> Result<(), String> {
+    if addr == "*" || addr.parse::<std::net::IpAddr>().is_ok() {
+        Ok(())
+    } else {
+        Err(format!("监听地址 '{}' 无效，应为 * 或 IP 地址", addr))
+    }
+}
+
+/// 校验 CIDR 网段（如 `192.168.1.0/24`、`fd00::/8`）
+fn validate_cidr(cidr: &str) -> Result<(), String> {
+    let invalid = || format!("网段 '{}' 不是有效的 CIDR", cidr);
+
+    let (ip, prefix) = cidr.split_once('/').ok_or_else(invalid)?;
+    let ip: std::net::IpAddr = ip.parse().map_err(|_| invalid())?;
+    let prefix: u8 = prefix.parse().map_err(|_| invalid())?;
+    let max_prefix = if ip.is_ipv4() { 32 } else { 128 };
+    if prefix > max_prefix {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// 校验 nameserver-policy 的域名匹配（如 `+.corp.example.com`、`geosite:cn`，可用逗号分隔多个）
+fn validate_policy_domain(pattern: &str) -> Result<(), String> {
+    let invalid = || format!("域名匹配 '{}' 无效", pattern);
+
+    for part in pattern.split(',').map(str::trim) {
+        if let Some(name) = part
+            .strip_prefix("geosite:")
+            .or_else(|| part.strip_prefix("rule-set:"))
+        {
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(invalid());
+            }
+            continue;
+        }
+
+        let domain = part
+            .strip_prefix("+.")
+            .or_else(|| part.strip_prefix("*."))
+            .unwrap_or(part);
+        let valid = !domain.is_empty()
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && domain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '*'));
+        if !valid {
+            return Err(invalid());
+        }
+    }
+    Ok(())
+}
+
+/// 校验 DNS 服务器地址（IP[:端口] 或 udp/tcp/tls/https URI）
+fn validate_dns_server(server: &str) -> Result<(), String> {
+    let invalid = || {
+        format!(
+            "DNS 服务器 '{}' 无效，应为 IP 地址或 udp/tcp/tls/https 地址",
+            server
+        )
+    };
+
+    if server.parse::<std::net::IpAddr>().is_ok() || server.parse::<std::net::SocketAddr>().is_ok()
+    {
+        return Ok(());
+    }
+
+    let url = reqwest::Url::parse(server).map_err(|_| invalid())?;
+    if !matches!(url.scheme(), "udp" | "tcp" | "tls" | "https") || url.host_str().is_none() {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
 /// 设置 HTTP/SOCKS 端口
 #[tauri::command]
 pub async fn set_ports(app: AppHandle, port: u16, socks_port: u16) -> Result<(), String> {
@@ -294,13 +709,25 @@ pub async fn set_ports(app: AppHandle, port: u16, socks_port: u16) -> Result<(),
     .await
 }
 
+/// 开启 IPv6 时 TUN 网卡使用的默认 IPv6 地址
+const DEFAULT_TUN_INET6_ADDRESS: &str = "fdfe:dcba:9876::1/126";
+
 /// 设置 IPv6 开关
+///
+/// 同时更新 DNS 的 `ipv6`（是否解析 AAAA）和 TUN 的 `inet6-address`，
+/// 避免只开一半。TUN 已开启时地址变更需要 API restart，其余可热重载。
 #[tauri::command]
 pub async fn set_ipv6(app: AppHandle, enabled: bool) -> Result<(), String> {
     use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
 
     apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
         settings.ipv6 = enabled;
+        settings.dns.ipv6 = Some(enabled);
+        if !enabled {
+            settings.tun.inet6_address.clear();
+        } else if settings.tun.inet6_address.is_empty() {
+            settings.tun.inet6_address = vec![DEFAULT_TUN_INET6_ADDRESS.to_string()];
+        }
         Ok(())
     })
     .await
@@ -321,42 +748,104 @@ pub async fn set_tcp_concurrent(app: AppHandle, enabled: bool) -> Result<(), Str
 /// 设置域名嗅探开关
 #[tauri::command]
 pub async fn set_sniffing(app: AppHandle, enabled: bool) -> Result<(), String> {
-    use crate::commands::reload::{apply_config_change, ReloadOptions};
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
 
-    apply_config_change(Some(&app), &ReloadOptions::default(), |config| {
-        if enabled {
-            // 启用 sniffer，使用默认配置
-            let mut sniffer = config.sniffer.clone().unwrap_or_default();
-            sniffer.enable = true;
-            config.sniffer = Some(sniffer);
-        } else {
-            // 禁用 sniffer
-            if let Some(ref mut sniffer) = config.sniffer {
-                sniffer.enable = false;
+    // 首次设置时以当前 config.yaml 中的 sniffer 为基础，保留已有的端口和域名配置
+    let current = get_app_state_or_err()?
+        .config_manager
+        .load_mihomo_config()
+        .ok()
+        .and_then(|config| config.sniffer);
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings
+            .sniffer
+            .get_or_insert_with(|| current.unwrap_or_default())
+            .enable = enabled;
+        Ok(())
+    })
+    .await
+}
+
+/// 设置域名嗅探配置
+#[tauri::command]
+pub async fn set_sniffer_config(app: AppHandle, sniffer: SnifferConfig) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    if let Some(sniff) = &sniffer.sniff {
+        for (protocol, config) in [("TLS", &sniff.tls), ("HTTP", &sniff.http)] {
+            if let Some(config) = config {
+                for ports in &config.ports {
+                    validate_sniff_ports(ports)
+                        .map_err(|e| format!("{} 嗅探端口无效: {}", protocol, e))?;
+                }
             }
         }
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.sniffer = Some(sniffer);
         Ok(())
     })
     .await
 }
 
+/// 校验嗅探端口（单个端口 `443` 或端口范围 `8080-8880`）
+fn validate_sniff_ports(ports: &str) -> Result<(), String> {
+    let parse_port = |value: &str| -> Result<u16, String> {
+        match value.trim().parse::<u16>() {
+            Ok(port) if port > 0 => Ok(port),
+            _ => Err(format!("'{}' 不是 1-65535 之间的端口", value.trim())),
+        }
+    };
+
+    match ports.split_once('-') {
+        Some((start, end)) => {
+            let start = parse_port(start)?;
+            let end = parse_port(end)?;
+            if start > end {
+                return Err(format!("端口范围 '{}' 起始端口大于结束端口", ports));
+            }
+            Ok(())
+        }
+        None => parse_port(ports).map(|_| ()),
+    }
+}
+
 /// 切换代理模式
 #[tauri::command]
 pub async fn switch_mode(app: AppHandle, mode: String) -> Result<(), String> {
     let state = get_app_state_or_err()?;
 
     // 验证模式
-    let valid_modes = ["rule", "global", "direct"];
+    let valid_modes = ["rule", "global", "direct", "script"];
     if !valid_modes.contains(&mode.as_str()) {
         return Err(format!("Invalid mode: {}", mode));
     }
+    // script 模式需要重启核心加载脚本，通过 set_script_mode 切换
+    if mode == "script" {
+        let app_settings = state
+            .config_manager
+            .load_app_settings()
+            .map_err(|e| e.to_string())?;
+        let Some(script) = app_settings.mihomo.script else {
+            return Err("尚未配置脚本，无法切换到 script 模式".to_string());
+        };
+        return set_script_mode(app, Some(script)).await;
+    }
+
+    // 手动切换模式会结束临时直连
+    let was_temporary_direct = state.temporary_direct.cancel();
+    if was_temporary_direct {
+        let _ = app.emit("temporary-direct-changed", TemporaryDirectStatus::default());
+    }
 
-    // 检查当前模式，如果相同则跳过
+    // 检查当前模式，如果相同则跳过（临时直连时运行时模式与配置文件不一致，仍需下发）
     let current_config = state
         .config_manager
         .load_mihomo_config()
         .map_err(|e| e.to_string())?;
-    if current_config.mode == mode {
+    if current_config.mode == mode && !was_temporary_direct {
         return Ok(());
     }
 
@@ -392,6 +881,63 @@ pub async fn switch_mode(app: AppHandle, mode: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 设置脚本并切换到 script 模式，`script` 为 None 时清除脚本并恢复为规则模式
+///
+/// 脚本出错可能导致核心无法加载配置，失败时回滚到之前的配置。
+#[tauri::command]
+pub async fn set_script_mode(app: AppHandle, script: Option<ScriptConfig>) -> Result<(), String> {
+    use crate::commands::reload::{apply_config_change, ReloadOptions};
+
+    let state = get_app_state_or_err()?;
+
+    if script.as_ref().is_some_and(|s| !s.has_code()) {
+        return Err("script 模式需要配置脚本代码".to_string());
+    }
+    let mode = if script.is_some() { "script" } else { "rule" };
+
+    if state.temporary_direct.cancel() {
+        let _ = app.emit("temporary-direct-changed", TemporaryDirectStatus::default());
+    }
+
+    let new_script = script.clone();
+    apply_config_change(Some(&app), &ReloadOptions::default(), move |config| {
+        if new_script.is_some() || config.mode == "script" {
+            config.mode = mode.to_string();
+        }
+        config.script = new_script;
+        Ok(())
+    })
+    .await?;
+
+    // 核心成功加载后再写入 settings.json，避免下次激活时带上出错的脚本
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    if script.is_some() || app_settings.mihomo.mode == "script" {
+        app_settings.mihomo.mode = mode.to_string();
+    }
+    app_settings.mihomo.script = script;
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    if let Ok(status) = get_proxy_status().await {
+        let _ = app.emit("proxy-status-changed", status);
+    }
+
+    log::info!(
+        "Script mode {}",
+        if mode == "script" {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    Ok(())
+}
+
 /// 获取代理节点列表
 ///
 /// 根据模式参数过滤返回的策略组：
@@ -438,6 +984,8 @@ pub async fn get_proxies(mode: Option<String>) -> Result<Vec<ProxyGroup>, String
                     group_type: info.proxy_type.clone(),
                     now: info.now.clone(),
                     all: info.all.clone(),
+                    hidden: info.hidden,
+                    icon: info.icon.clone(),
                 });
             }
         }
@@ -476,25 +1024,87 @@ pub async fn select_proxy(group: String, name: String) -> Result<(), String> {
     Ok(())
 }
 
-/// 测试代理延迟
+/// 全局模式使用的策略组名称
+const GLOBAL_GROUP: &str = "GLOBAL";
+
+/// 设置全局模式的出口节点
+///
+/// 在 GLOBAL 策略组中选择节点，并将代理模式切换为 global
 #[tauri::command]
-pub async fn test_proxy_delay(name: String) -> Result<u32, String> {
+pub async fn set_global_proxy(app: AppHandle, name: String) -> Result<(), String> {
     let state = get_app_state_or_err()?;
 
     if !state.mihomo_manager.is_running().await {
         return Err("Proxy is not running".to_string());
     }
 
-    let response = state
+    let proxies = state
         .mihomo_api
-        .test_delay(&name, 5000, "http://www.gstatic.com/generate_204")
+        .get_proxies()
         .await
         .map_err(|e| e.to_string())?;
+    let global = proxies
+        .proxies
+        .get(GLOBAL_GROUP)
+        .ok_or_else(|| "GLOBAL 策略组不存在".to_string())?;
+    if !global.all.contains(&name) {
+        return Err(format!("GLOBAL 策略组中不存在节点: {}", name));
+    }
 
-    Ok(response.delay)
-}
+    state
+        .mihomo_api
+        .select_proxy(GLOBAL_GROUP, &name)
+        .await
+        .map_err(|e| e.to_string())?;
 
-/// 获取流量数据
+    // switch_mode 会持久化模式并发送状态变更事件
+    let status = get_proxy_status().await?;
+    if status.mode == "global" {
+        let _ = app.emit("proxy-status-changed", status);
+    } else {
+        switch_mode(app, "global".to_string()).await?;
+    }
+
+    log::info!("Global proxy set to {}", name);
+    Ok(())
+}
+
+/// 测试代理延迟
+#[tauri::command]
+pub async fn test_proxy_delay(name: String) -> Result<u32, String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let response = state
+        .mihomo_api
+        .test_delay(&name, 5000, "http://www.gstatic.com/generate_204")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.delay)
+}
+
+/// 单独测试一个节点（不加入任何 Profile，核心未运行时也可用）
+///
+/// 用于导入前验证粘贴的节点是否可用。`url` 默认为 generate_204，`timeout` 单位为毫秒。
+#[tauri::command]
+pub async fn test_standalone_node(
+    proxy: ProxyConfig,
+    url: Option<String>,
+    timeout: Option<u32>,
+) -> Result<StandaloneNodeTest, String> {
+    let url = url.unwrap_or_else(|| "http://www.gstatic.com/generate_204".to_string());
+    let timeout = timeout.unwrap_or(5000).clamp(1000, 30000);
+
+    crate::mihomo::test_standalone_node(&proxy, &url, timeout)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取流量数据
 #[tauri::command]
 pub async fn get_traffic() -> Result<TrafficData, String> {
     let state = get_app_state_or_err()?;
@@ -541,6 +1151,159 @@ pub async fn get_connections() -> Result<ConnectionsResponse, String> {
     Ok(connections)
 }
 
+/// 单页连接数上限
+const MAX_CONNECTIONS_PAGE_SIZE: usize = 1000;
+
+/// 获取一页连接（后端过滤与排序，减少传给前端的数据量）
+#[tauri::command]
+pub async fn get_connections_page(
+    offset: usize,
+    limit: usize,
+    sort_by: Option<ConnectionSortKey>,
+    filter: Option<String>,
+) -> Result<ConnectionsPage, String> {
+    let state = get_app_state_or_err()?;
+    let limit = limit.clamp(1, MAX_CONNECTIONS_PAGE_SIZE);
+
+    if !state.mihomo_manager.is_running().await {
+        return Ok(ConnectionsPage {
+            connections: vec![],
+            total: 0,
+            download_total: 0,
+            upload_total: 0,
+        });
+    }
+
+    let connections = state
+        .mihomo_api
+        .get_connections()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(connections.into_page(
+        offset,
+        limit,
+        sort_by.unwrap_or_default(),
+        filter.as_deref(),
+    ))
+}
+
+/// 预估规则影响：用当前活跃连接模拟匹配该规则
+///
+/// 假定规则插入到规则列表最前面，不考虑已有规则的顺序；
+/// GEOIP、GEOSITE、RULE-SET 等依赖外部数据的规则无法模拟，返回错误。
+#[tauri::command]
+pub async fn simulate_rule_impact(rule: String) -> Result<RuleImpact, String> {
+    let state = get_app_state_or_err()?;
+    let matcher = crate::config::RuleMatcher::parse(rule.trim()).map_err(|e| e.to_string())?;
+
+    let connections = if state.mihomo_manager.is_running().await {
+        state
+            .mihomo_api
+            .get_connections()
+            .await
+            .map_err(|e| e.to_string())?
+            .connections
+    } else {
+        Vec::new()
+    };
+
+    let total = connections.len();
+    let matched: Vec<_> = connections
+        .into_iter()
+        .filter(|c| matcher.matches(&c.metadata))
+        .collect();
+
+    Ok(RuleImpact {
+        target: matcher.target,
+        matched,
+        total,
+    })
+}
+
+/// 导出 CSV 时保留的主机数量
+const MAX_EXPORT_HOSTS: usize = 100;
+
+/// 导出流量历史和主机流量统计为 CSV
+///
+/// 第一段为最近 `window_secs` 秒的每秒流量，第二段为当前连接按主机汇总的流量（按总量降序）。
+/// `window_secs` 限制在 1 秒到流量历史最长保留时长之间。
+#[tauri::command]
+pub async fn export_traffic_csv(target_path: String, window_secs: u64) -> Result<(), String> {
+    use std::collections::HashMap;
+    use std::fmt::Write as _;
+
+    let window_secs = window_secs.clamp(1, u64::from(MAX_TRAFFIC_RETENTION_MINUTES) * 60);
+    let state = get_app_state_or_err()?;
+
+    let samples = state
+        .traffic_history
+        .samples_since(std::time::Duration::from_secs(window_secs));
+
+    let connections = if state.mihomo_manager.is_running().await {
+        state
+            .mihomo_api
+            .get_connections()
+            .await
+            .map_err(|e| e.to_string())?
+            .connections
+    } else {
+        vec![]
+    };
+
+    // host -> (连接数, 上传, 下载)
+    let mut hosts: HashMap<String, (usize, u64, u64)> = HashMap::new();
+    for conn in &connections {
+        let entry = hosts.entry(conn.display_host().to_string()).or_default();
+        entry.0 += 1;
+        entry.1 += conn.upload;
+        entry.2 += conn.download;
+    }
+    let mut hosts: Vec<_> = hosts.into_iter().collect();
+    hosts.sort_by(|a, b| (b.1 .1 + b.1 .2).cmp(&(a.1 .1 + a.1 .2)));
+    hosts.truncate(MAX_EXPORT_HOSTS);
+
+    let mut csv = String::from("timestamp,upload_bytes,download_bytes\n");
+    for sample in &samples {
+        let time = chrono::DateTime::from_timestamp_millis(sample.timestamp)
+            .map(|t| t.with_timezone(&chrono::Local).to_rfc3339())
+            .unwrap_or_default();
+        let _ = writeln!(csv, "{},{},{}", time, sample.up, sample.down);
+    }
+
+    csv.push_str("\nhost,connections,upload_bytes,download_bytes,total_bytes\n");
+    for (host, (count, upload, download)) in &hosts {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{}",
+            csv_field(host),
+            count,
+            upload,
+            download,
+            upload + download
+        );
+    }
+
+    std::fs::write(&target_path, csv).map_err(|e| format!("写入文件失败: {}", e))?;
+
+    log::info!(
+        "Exported {} traffic samples and {} hosts to {}",
+        samples.len(),
+        hosts.len(),
+        target_path
+    );
+    Ok(())
+}
+
+/// CSV 字段转义（包含逗号、引号或换行时加引号）
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 /// 关闭单个连接
 #[tauri::command]
 pub async fn close_connection(id: String) -> Result<(), String> {
@@ -577,6 +1340,65 @@ pub async fn close_all_connections() -> Result<(), String> {
     Ok(())
 }
 
+/// 测量经当前代理链路访问 `url` 的分段延迟
+///
+/// 通过运行中的混合端口发起请求，分别统计核心 DNS 解析、CONNECT 建连、
+/// TLS 握手和首字节耗时，用于判断慢在 DNS、握手还是节点本身。
+#[tauri::command]
+pub async fn measure_chain_latency(url: String) -> Result<ChainLatency, String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let runtime_config = state
+        .mihomo_api
+        .get_configs()
+        .await
+        .map_err(|e| e.to_string())?;
+    let mixed_port = runtime_config
+        .get("mixed-port")
+        .and_then(|v| v.as_u64())
+        .and_then(|port| u16::try_from(port).ok())
+        .filter(|port| *port != 0)
+        .ok_or_else(|| "混合代理端口未开启，请先开启系统代理".to_string())?;
+
+    // 核心 DNS 单独计时（CONNECT 阶段核心会再次解析，通常命中缓存）
+    let host = reqwest::Url::parse(&url)
+        .map_err(|e| format!("无效的 URL: {}", e))?
+        .host_str()
+        .map(|h| h.trim_matches(['[', ']']).to_string())
+        .unwrap_or_default();
+    let dns_ms = if host.parse::<std::net::IpAddr>().is_ok() {
+        None
+    } else {
+        let start = std::time::Instant::now();
+        match state.mihomo_api.dns_query(&host, "A").await {
+            Ok(_) => Some(start.elapsed().as_millis() as u64),
+            Err(e) => {
+                log::warn!("DNS timing failed for {}: {}", host, e);
+                None
+            }
+        }
+    };
+
+    let mut latency = crate::utils::measure_through_proxy(mixed_port, &url)
+        .await
+        .map_err(|e| e.to_string())?;
+    latency.dns_ms = dns_ms;
+
+    log::info!(
+        "Chain latency for {}: dns={:?}ms connect={}ms tls={:?}ms ttfb={}ms",
+        url,
+        latency.dns_ms,
+        latency.connect_ms,
+        latency.tls_ms,
+        latency.ttfb_ms
+    );
+    Ok(latency)
+}
+
 /// 设置 TUN 模式（增强模式）
 ///
 /// 重构后的智能流程：
@@ -584,13 +1406,19 @@ pub async fn close_all_connections() -> Result<(), String> {
 /// - 普通模式启用 TUN：进行权限前置检查 + 进程级重启
 /// - 关闭 TUN：任意模式都使用 API restart
 #[tauri::command]
-pub async fn set_tun_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub async fn set_tun_mode(app: AppHandle, enabled: bool) -> Result<(), CommandError> {
+    set_tun_mode_inner(app, enabled)
+        .await
+        .inspect_err(|e| capture_crash_report("set_tun_mode", e))
+}
+
+async fn set_tun_mode_inner(app: AppHandle, enabled: bool) -> Result<(), CommandError> {
     use crate::commands::reload::{should_process_restart_for_tun_change, sync_proxy_status};
 
     let state = get_app_state_or_err()?;
 
     if !state.mihomo_manager.is_running().await {
-        return Err("代理核心未运行".to_string());
+        return Err(CommandError::core("代理核心未运行"));
     }
 
     // 记录当前状态
@@ -621,7 +1449,8 @@ pub async fn set_tun_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
         log::info!("Using process restart path for TUN mode change");
 
         // 前置检查：确保有激活的订阅
-        crate::commands::require_active_subscription_with_proxies()?;
+        crate::commands::require_active_subscription_with_proxies()
+            .map_err(CommandError::validation)?;
 
         // 平台特定的权限前置检查
         #[cfg(target_os = "windows")]
@@ -634,38 +1463,36 @@ pub async fn set_tun_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
 
             if !service_running && !is_admin {
                 log::error!("TUN mode enabled but not running as admin and service not running");
-                return Err("NEED_ADMIN:增强模式需要管理员权限。请选择以下方式之一：\n\
+                return Err(CommandError::permission(
+                    "NEED_ADMIN:增强模式需要管理员权限。请选择以下方式之一：\n\
                     1. 在设置中安装并启动 Conflux 服务（推荐）\n\
-                    2. 以管理员身份重新启动应用"
-                    .to_string());
+                    2. 以管理员身份重新启动应用",
+                ));
             }
         }
 
         #[cfg(target_os = "macos")]
         {
             // macOS 普通模式：检查并设置 helper 权限
-            let has_permission =
-                crate::system::TunPermission::check_permission().map_err(|e| e.to_string())?;
+            let has_permission = crate::system::TunPermission::check_permission()
+                .map_err(CommandError::permission)?;
             if !has_permission {
                 log::info!("TUN permission not set, requesting setup...");
-                crate::system::TunPermission::setup_permission()
-                    .map_err(|e| format!("增强模式需要额外系统权限/组件：{}", e))?;
+                crate::system::TunPermission::setup_permission().map_err(|e| {
+                    CommandError::permission(format!("增强模式需要额外系统权限/组件：{}", e))
+                })?;
             }
         }
 
         // 停止当前核心
-        state
-            .mihomo_manager
-            .stop()
-            .await
-            .map_err(|e| e.to_string())?;
+        state.mihomo_manager.stop().await?;
 
         // 更新配置文件
         if let Err(e) = state.config_manager.update_tun_mode(true) {
             log::error!("Failed to update TUN config: {}", e);
             // 尝试恢复核心（普通模式）
             let _ = state.mihomo_manager.start().await;
-            return Err(format!("更新配置失败: {}", e));
+            return Err(CommandError::core(format!("更新配置失败: {}", e)));
         }
 
         // 以新权限启动核心
@@ -682,7 +1509,7 @@ pub async fn set_tun_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
                 let _ = state.config_manager.update_tun_mode(false);
                 let _ = state.mihomo_manager.start().await;
                 sync_proxy_status(&app).await;
-                return Err(format!("启动增强模式失败: {}", e));
+                return Err(CommandError::core(format!("启动增强模式失败: {}", e)));
             }
         }
     }
@@ -693,17 +1520,14 @@ pub async fn set_tun_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
     // 更新配置文件
     if let Err(e) = state.config_manager.update_tun_mode(enabled) {
         log::error!("Failed to update TUN config: {}", e);
-        return Err(format!("更新配置失败: {}", e));
+        return Err(CommandError::core(format!("更新配置失败: {}", e)));
     }
 
     // 调用 API restart
-    match state.mihomo_api.restart().await {
+    match state.mihomo_manager.api_restart(&state.mihomo_api).await {
         Ok(_) => {
             log::info!("MiHomo API restart requested");
 
-            // 等待内核重启完成
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-
             update_tun_state(&state, &app, enabled).await;
             sync_proxy_status(&app).await;
             log::info!(
@@ -716,7 +1540,7 @@ pub async fn set_tun_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
             log::error!("MiHomo API restart failed: {}", e);
             // 回滚配置
             let _ = state.config_manager.update_tun_mode(previous_enabled);
-            Err(format!("内核重启失败: {}", e))
+            Err(CommandError::core(format!("内核重启失败: {}", e)))
         }
     }
 }
@@ -741,12 +1565,145 @@ async fn update_tun_state(state: &crate::commands::AppState, _app: &AppHandle, e
     }
 }
 
+/// 只在运行时配置中切换 TUN，不写入 settings.json
+///
+/// 用于临时关闭 TUN 的场景，应用中途退出后下次启动仍按保存的设置开启。
+pub(crate) async fn set_runtime_tun(
+    state: &crate::commands::AppState,
+    enabled: bool,
+) -> Result<(), String> {
+    use crate::commands::reload::should_process_restart_for_tun_change;
+
+    let previous_enabled = *state.enhanced_mode.lock().await;
+    if previous_enabled == enabled {
+        return Ok(());
+    }
+
+    let run_mode = detect_run_mode(true).await;
+    state
+        .config_manager
+        .update_tun_mode(enabled)
+        .map_err(|e| format!("更新配置失败: {}", e))?;
+    let result = if should_process_restart_for_tun_change(previous_enabled, enabled, &run_mode) {
+        state.mihomo_manager.restart().await
+    } else {
+        state.mihomo_manager.api_restart(&state.mihomo_api).await
+    };
+    result.map_err(|e| e.to_string())?;
+
+    *state.enhanced_mode.lock().await = enabled;
+    Ok(())
+}
+
 /// 检查 TUN 权限状态
 #[tauri::command]
 pub async fn check_tun_permission() -> Result<bool, String> {
     crate::system::TunPermission::check_permission().map_err(|e| e.to_string())
 }
 
+/// TUN 连通性测试地址（应被 TUN 捕获的外部 HTTP 地址）
+const TUN_TEST_HOST: &str = "www.gstatic.com";
+
+/// 检查 TUN 是否真正接管流量
+///
+/// 依次检查：核心运行时 TUN 已启用 → TUN 网卡存在 → 路由指向 TUN →
+/// 绕过系统代理直连测试地址，并确认该连接以 TUN 入站出现在核心连接列表中
+#[tauri::command]
+pub async fn verify_tun_active() -> Result<TunDiagnosis, String> {
+    use crate::system::{find_tun_interface, has_tun_routes};
+
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Ok(TunDiagnosis::new(
+            TunDiagnosisStatus::CoreNotRunning,
+            "代理核心未运行",
+        ));
+    }
+
+    let runtime_config = state
+        .mihomo_api
+        .get_configs()
+        .await
+        .map_err(|e| e.to_string())?;
+    let runtime_tun = runtime_config.get("tun");
+    let tun_enabled = runtime_tun
+        .and_then(|tun| tun.get("enable"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !tun_enabled {
+        return Ok(TunDiagnosis::new(
+            TunDiagnosisStatus::TunDisabled,
+            "核心运行时配置中增强模式未启用",
+        ));
+    }
+
+    let device = runtime_tun
+        .and_then(|tun| tun.get("device"))
+        .and_then(|v| v.as_str());
+    let Some(iface) = find_tun_interface(device).map_err(|e| e.to_string())? else {
+        return Ok(TunDiagnosis::new(
+            TunDiagnosisStatus::InterfaceMissing,
+            "未找到 TUN 网卡，请检查增强模式权限或重启核心",
+        ));
+    };
+
+    let routes_found = has_tun_routes(&iface).unwrap_or_else(|e| {
+        log::warn!("Failed to inspect routing table: {}", e);
+        false
+    });
+    let mut diagnosis = TunDiagnosis::new(TunDiagnosisStatus::Working, "");
+    diagnosis.interface_name = Some(iface.name.clone());
+    diagnosis.interface_address = Some(iface.address.to_string());
+    diagnosis.routes_found = routes_found;
+
+    if !routes_found {
+        diagnosis.status = TunDiagnosisStatus::RoutesMissing;
+        diagnosis.message = format!(
+            "TUN 网卡 {} 已创建，但系统路由未指向该网卡，请确认已开启自动路由",
+            iface.name
+        );
+        return Ok(diagnosis);
+    }
+
+    // 不走系统代理，只有被 TUN 捕获时才会出现在核心连接列表中；
+    // 保持 client 存活，使连接在查询期间仍处于打开状态
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let request_result = client
+        .get(format!("http://{}/generate_204", TUN_TEST_HOST))
+        .send()
+        .await;
+
+    let captured = match state.mihomo_api.get_connections().await {
+        Ok(connections) => connections.connections.iter().any(|conn| {
+            conn.metadata.conn_type.eq_ignore_ascii_case("tun")
+                && conn.metadata.host == TUN_TEST_HOST
+        }),
+        Err(e) => {
+            log::warn!("Failed to fetch connections for TUN check: {}", e);
+            false
+        }
+    };
+    drop(client);
+
+    diagnosis.test_captured = Some(captured);
+    if captured {
+        diagnosis.message = format!("增强模式工作正常（网卡 {}）", iface.name);
+    } else {
+        diagnosis.status = TunDiagnosisStatus::TrafficNotCaptured;
+        diagnosis.message = match request_result {
+            Ok(_) => "测试连接未经过 TUN，可能被其他 VPN 或路由排除规则绕过".to_string(),
+            Err(e) => format!("测试连接失败且未经过 TUN: {}", e),
+        };
+    }
+
+    Ok(diagnosis)
+}
+
 /// 设置 TUN Stack
 #[tauri::command]
 pub async fn set_tun_stack(app: AppHandle, stack: String) -> Result<(), String> {
@@ -784,6 +1741,10 @@ pub async fn set_strict_route(app: AppHandle, enabled: bool) -> Result<(), Strin
 pub async fn set_tun_route_exclude(app: AppHandle, addresses: Vec<String>) -> Result<(), String> {
     use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
 
+    for address in &addresses {
+        validate_cidr(address)?;
+    }
+
     apply_mihomo_settings_change(Some(&app), &ReloadOptions::safe(), |settings| {
         settings.tun.inet4_route_exclude_address = addresses.clone();
         Ok(())
@@ -794,6 +1755,75 @@ pub async fn set_tun_route_exclude(app: AppHandle, addresses: Vec<String>) -> Re
     Ok(())
 }
 
+/// 添加 TUN 路由排除网段（`route-exclude-address`）
+#[tauri::command]
+pub async fn add_tun_route_exclude(app: AppHandle, address: String) -> Result<(), String> {
+    update_tun_route_list(&app, true, address, true).await
+}
+
+/// 移除 TUN 路由排除网段
+#[tauri::command]
+pub async fn remove_tun_route_exclude(app: AppHandle, address: String) -> Result<(), String> {
+    update_tun_route_list(&app, true, address, false).await
+}
+
+/// 添加 TUN 路由包含网段（`route-address`）
+#[tauri::command]
+pub async fn add_tun_route_include(app: AppHandle, address: String) -> Result<(), String> {
+    update_tun_route_list(&app, false, address, true).await
+}
+
+/// 移除 TUN 路由包含网段
+#[tauri::command]
+pub async fn remove_tun_route_include(app: AppHandle, address: String) -> Result<(), String> {
+    update_tun_route_list(&app, false, address, false).await
+}
+
+/// 在 TUN 路由排除/包含列表中添加或移除网段
+async fn update_tun_route_list(
+    app: &AppHandle,
+    exclude: bool,
+    address: String,
+    add: bool,
+) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let address = address.trim().to_string();
+    if add {
+        validate_cidr(&address)?;
+    }
+
+    apply_mihomo_settings_change(Some(app), &ReloadOptions::safe(), |settings| {
+        let list = if exclude {
+            &mut settings.tun.route_exclude_address
+        } else {
+            &mut settings.tun.route_address
+        };
+        if add {
+            if list.contains(&address) {
+                return Err(format!("网段 '{}' 已存在", address));
+            }
+            list.push(address.clone());
+        } else {
+            let before = list.len();
+            list.retain(|a| a != &address);
+            if list.len() == before {
+                return Err(format!("网段 '{}' 不存在", address));
+            }
+        }
+        Ok(())
+    })
+    .await?;
+
+    log::info!(
+        "TUN route {} address {} {}",
+        if exclude { "exclude" } else { "include" },
+        if add { "added:" } else { "removed:" },
+        address
+    );
+    Ok(())
+}
+
 /// 手动设置 TUN 权限
 #[tauri::command]
 pub async fn setup_tun_permission() -> Result<(), String> {
@@ -826,13 +1856,91 @@ pub async fn get_rules_from_api() -> Result<Vec<RuleItem>, String> {
 pub async fn get_core_version() -> Result<VersionInfo, String> {
     let state = get_app_state_or_err()?;
 
-    state
+    let mut version = state
         .mihomo_api
         .get_version()
         .await
+        .map_err(|e| e.to_string())?;
+    version.pinned_version = load_pinned_core_version();
+    Ok(version)
+}
+
+/// 记录固定核心版本的文件，位于数据目录，不参与 WebDAV 同步
+///
+/// 核心二进制是每台设备各自安装的，固定版本只对本机有意义。
+const PINNED_CORE_VERSION_FILE: &str = "pinned_core_version";
+
+fn pinned_core_version_path() -> Result<std::path::PathBuf, String> {
+    crate::utils::get_app_data_dir()
+        .map(|dir| dir.join(PINNED_CORE_VERSION_FILE))
         .map_err(|e| e.to_string())
 }
 
+fn load_pinned_core_version() -> Option<String> {
+    let content = std::fs::read_to_string(pinned_core_version_path().ok()?).ok()?;
+    let version = content.trim();
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// 规范化固定的版本号，只接受 `v1.19` / `1.19.2` 这类数字版本，统一加上 `v` 前缀
+fn normalize_pinned_version(version: &str) -> Result<String, String> {
+    let digits = version.trim_start_matches(['v', 'V']);
+    let valid = !digits.is_empty()
+        && digits.split('.').count() >= 2
+        && digits
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    if !valid {
+        return Err(format!("无效的核心版本号: {}", version));
+    }
+    Ok(format!("v{}", digits))
+}
+
+/// 固定核心版本（传 None 取消固定）
+///
+/// 固定后检查更新时不再升级核心，避免新版本核心引入的问题。只对本机生效。
+#[tauri::command]
+pub async fn set_pinned_core_version(version: Option<String>) -> Result<(), String> {
+    let version = version
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .map(|v| normalize_pinned_version(&v))
+        .transpose()?;
+
+    let path = pinned_core_version_path()?;
+    match &version {
+        Some(version) => std::fs::write(&path, version).map_err(|e| e.to_string())?,
+        None if path.exists() => std::fs::remove_file(&path).map_err(|e| e.to_string())?,
+        None => {}
+    }
+
+    log::info!("Pinned core version: {:?}", version);
+    Ok(())
+}
+
+/// 获取核心配置能力
+///
+/// 根据核心版本和当前 /configs 推导可用的配置项，
+/// 前端据此隐藏当前核心不支持的选项
+#[tauri::command]
+pub async fn get_core_config_schema() -> Result<CoreConfigSchema, String> {
+    let state = get_app_state_or_err()?;
+
+    let version = state
+        .mihomo_api
+        .get_version()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = state
+        .mihomo_api
+        .get_configs()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(CoreConfigSchema::build(&version.version, config))
+}
+
 /// 升级核心（调用 mihomo /upgrade API）
 ///
 /// 触发 mihomo 核心自我更新，更新完成后返回新的版本信息
@@ -845,12 +1953,25 @@ pub async fn upgrade_core() -> Result<VersionInfo, String> {
     }
 
     // 获取当前版本
-    let current_version = state
+    let mut current_version = state
         .mihomo_api
         .get_version()
         .await
         .map_err(|e| format!("获取版本失败: {}", e))?;
 
+    // 固定了版本时不检查更新，但仍校验运行的核心是否就是固定的版本
+    if let Some(pinned) = load_pinned_core_version() {
+        if crate::utils::compare_versions(&current_version.version, &pinned) != 0 {
+            return Err(format!(
+                "当前核心版本 {} 与固定的版本 {} 不一致，请安装固定的版本或取消固定",
+                current_version.version, pinned
+            ));
+        }
+        log::info!("Core upgrade skipped, version pinned to {}", pinned);
+        current_version.pinned_version = Some(pinned);
+        return Ok(current_version);
+    }
+
     log::info!(
         "Checking for core upgrade, current version: {}",
         current_version.version
@@ -916,262 +2037,1756 @@ pub async fn get_run_mode() -> Result<crate::models::RunMode, String> {
     Ok(run_mode)
 }
 
-// ============= Provider 命令 =============
+// ============= 自动切换命令 =============
 
-/// 代理 Provider 返回给前端的结构
-#[derive(Debug, Clone, Serialize)]
-pub struct ProxyProviderFrontend {
-    pub name: String,
-    #[serde(rename = "type")]
-    pub provider_type: String,
-    #[serde(rename = "vehicleType")]
-    pub vehicle_type: String,
-    pub proxies: Vec<ProxyProviderProxyFrontend>,
-    #[serde(rename = "updatedAt")]
-    pub updated_at: Option<String>,
-    #[serde(rename = "subscriptionInfo")]
-    pub subscription_info: Option<SubscriptionInfoFrontend>,
-}
+/// 获取自动切换最快节点设置
+#[tauri::command]
+pub async fn get_auto_switch_settings() -> Result<AutoSwitchSettings, String> {
+    let state = get_app_state_or_err()?;
 
-#[derive(Debug, Clone, Serialize)]
-pub struct ProxyProviderProxyFrontend {
-    pub name: String,
-    #[serde(rename = "type")]
-    pub proxy_type: String,
-    pub udp: bool,
-    pub now: Option<String>,
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    Ok(app_settings.auto_switch)
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct SubscriptionInfoFrontend {
-    #[serde(rename = "Upload")]
-    pub upload: Option<u64>,
-    #[serde(rename = "Download")]
-    pub download: Option<u64>,
-    #[serde(rename = "Total")]
-    pub total: Option<u64>,
-    #[serde(rename = "Expire")]
-    pub expire: Option<u64>,
+/// 设置自动切换最快节点
+///
+/// 启用后定期对指定 select 策略组测速并选择最快节点，
+/// 仅当新节点比当前节点快出阈值时才切换
+#[tauri::command]
+pub async fn set_auto_switch(app: AppHandle, settings: AutoSwitchSettings) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    if settings.enabled && settings.group.trim().is_empty() {
+        return Err("请选择要自动切换的策略组".to_string());
+    }
+    if settings.interval_secs < MIN_AUTO_SWITCH_INTERVAL_SECS {
+        return Err(format!(
+            "测速间隔不能小于 {} 秒",
+            MIN_AUTO_SWITCH_INTERVAL_SECS
+        ));
+    }
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.auto_switch = settings.clone();
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    if settings.enabled && state.mihomo_manager.is_running().await {
+        state.auto_switcher.start(app, settings);
+    } else {
+        state.auto_switcher.stop();
+    }
+
+    Ok(())
 }
 
-/// 规则 Provider 返回给前端的结构
-#[derive(Debug, Clone, Serialize)]
-pub struct RuleProviderFrontend {
-    pub name: String,
-    #[serde(rename = "type")]
-    pub provider_type: String,
-    pub behavior: String,
-    #[serde(rename = "ruleCount")]
-    pub rule_count: u32,
-    #[serde(rename = "updatedAt")]
-    pub updated_at: Option<String>,
-    #[serde(rename = "vehicleType")]
-    pub vehicle_type: String,
+/// 核心启动后按设置恢复自动切换
+pub(crate) async fn resume_auto_switch(app: &AppHandle) {
+    let Some(state) = crate::commands::try_get_app_state() else {
+        return;
+    };
+
+    match state.config_manager.load_app_settings() {
+        Ok(app_settings) if app_settings.auto_switch.enabled => {
+            state
+                .auto_switcher
+                .start(app.clone(), app_settings.auto_switch);
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to load auto switch settings: {}", e),
+    }
 }
 
-/// 获取代理 Provider 列表
+/// 首次启用订阅时应用其建议的默认节点（核心运行后调用）
+///
+/// 至少选中一个节点后记录在 Profile 元数据中，之后重载或重启不会覆盖用户手动的选择。
+pub(crate) async fn apply_suggested_selection(app: &AppHandle) {
+    let Some(state) = crate::commands::try_get_app_state() else {
+        return;
+    };
+
+    match state.config_manager.load_app_settings() {
+        Ok(app_settings) if app_settings.apply_suggested_selection => {}
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("Failed to load settings for suggested selection: {}", e);
+            return;
+        }
+    }
+
+    match apply_suggested_selection_inner(state).await {
+        Ok(Some(result)) => {
+            log::info!(
+                "Applied suggested selection: {} applied, {} skipped",
+                result.applied.len(),
+                result.skipped.len()
+            );
+            let _ = app.emit("suggested-selection-applied", result);
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to apply suggested selection: {}", e),
+    }
+}
+
+async fn apply_suggested_selection_inner(
+    state: &AppState,
+) -> Result<Option<SelectionPresetResult>, String> {
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let Some(profile_id) = workspace
+        .get_active_profile_id()
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    let (mut metadata, config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+    if metadata.default_selection_applied {
+        return Ok(None);
+    }
+
+    let suggestions = Composer::suggested_selections(&config);
+    if suggestions.is_empty() {
+        return Ok(None);
+    }
+
+    let result = apply_selections(state, suggestions).await?;
+    // 全部跳过（如策略组尚未加载）时不记录，下次启动核心时重试
+    if !result.applied.is_empty() {
+        metadata.default_selection_applied = true;
+        workspace
+            .update_metadata(&profile_id, &metadata)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(Some(result))
+}
+
+/// 重新应用订阅建议的默认节点
+///
+/// 清除 Profile 的已应用标记；该 Profile 正在使用且核心运行时立即应用，否则在下次启用时应用。
 #[tauri::command]
-pub async fn get_proxy_providers() -> Result<Vec<ProxyProviderFrontend>, String> {
+pub async fn reapply_suggested_selection(app: AppHandle, profile_id: String) -> Result<(), String> {
     let state = get_app_state_or_err()?;
 
-    if !state.mihomo_manager.is_running().await {
-        return Err("Proxy is not running".to_string());
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    let mut metadata = workspace
+        .get_metadata(&profile_id)
+        .map_err(|e| e.to_string())?;
+    if metadata.default_selection_applied {
+        metadata.default_selection_applied = false;
+        workspace
+            .update_metadata(&profile_id, &metadata)
+            .map_err(|e| e.to_string())?;
     }
 
-    let response = state
-        .mihomo_api
-        .get_proxy_providers()
-        .await
+    let active = workspace
+        .get_active_profile_id()
         .map_err(|e| e.to_string())?;
+    if active.as_deref() == Some(profile_id.as_str()) && state.mihomo_manager.is_running().await {
+        apply_suggested_selection(&app).await;
+    }
+    Ok(())
+}
 
-    let mut providers: Vec<ProxyProviderFrontend> = response
-        .providers
-        .into_iter()
-        .filter(|(_, info)| info.vehicle_type != "Compatible")
-        .map(|(_, info)| ProxyProviderFrontend {
-            name: info.name,
-            provider_type: info.provider_type,
-            vehicle_type: info.vehicle_type,
-            proxies: info
-                .proxies
-                .into_iter()
-                .map(|p| ProxyProviderProxyFrontend {
-                    name: p.name,
-                    proxy_type: p.proxy_type,
-                    udp: p.udp,
-                    now: p.now,
-                })
-                .collect(),
-            updated_at: info.updated_at,
-            subscription_info: info.subscription_info.map(|s| SubscriptionInfoFrontend {
-                upload: s.upload,
-                download: s.download,
-                total: s.total,
-                expire: s.expire,
-            }),
-        })
-        .collect();
+// ============= 连接数上限命令 =============
 
-    providers.sort_by(|a, b| a.name.cmp(&b.name));
+/// 获取连接数上限设置
+#[tauri::command]
+pub async fn get_connection_limit() -> Result<ConnectionLimitSettings, String> {
+    let state = get_app_state_or_err()?;
 
-    Ok(providers)
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    Ok(app_settings.connection_limit)
 }
 
-/// 更新代理 Provider
+/// 设置连接数上限
+///
+/// 启用后定期检查连接总数，超过阈值时发送 `connection-limit-exceeded` 事件，
+/// 动作为 `close-idle` 时同时关闭最早建立的空闲连接
 #[tauri::command]
-pub async fn update_proxy_provider(name: String) -> Result<(), String> {
+pub async fn set_connection_limit(
+    app: AppHandle,
+    settings: ConnectionLimitSettings,
+) -> Result<(), String> {
     let state = get_app_state_or_err()?;
 
-    if !state.mihomo_manager.is_running().await {
-        return Err("Proxy is not running".to_string());
+    if settings.enabled && settings.threshold < MIN_CONNECTION_LIMIT {
+        return Err(format!("连接数阈值不能小于 {}", MIN_CONNECTION_LIMIT));
     }
 
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.connection_limit = settings.clone();
     state
-        .mihomo_api
-        .update_proxy_provider(&name)
-        .await
+        .config_manager
+        .save_app_settings(&app_settings)
         .map_err(|e| e.to_string())?;
 
-    log::info!("Updated proxy provider: {}", name);
+    apply_connection_limit(&app, state, &settings);
     Ok(())
 }
 
-/// 代理 Provider 健康检查
+/// 按设置启动或停止连接数看门狗（保存或同步设置后调用）
+pub(crate) fn apply_connection_limit(
+    app: &AppHandle,
+    state: &AppState,
+    settings: &ConnectionLimitSettings,
+) {
+    if !settings.enabled {
+        state.connection_watchdog.stop();
+    } else if settings.threshold < MIN_CONNECTION_LIMIT {
+        log::warn!(
+            "Connection limit {} is below {}, watchdog not started",
+            settings.threshold,
+            MIN_CONNECTION_LIMIT
+        );
+        state.connection_watchdog.stop();
+    } else {
+        state
+            .connection_watchdog
+            .start(app.clone(), settings.clone());
+    }
+}
+
+/// 获取核心进程优先级
 #[tauri::command]
-pub async fn health_check_proxy_provider(name: String) -> Result<(), String> {
+pub async fn get_core_priority() -> Result<CorePriority, String> {
     let state = get_app_state_or_err()?;
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    Ok(settings.core_priority)
+}
 
-    if !state.mihomo_manager.is_running().await {
-        return Err("Proxy is not running".to_string());
+/// 设置核心进程优先级（`low`、`normal` 或 `high`）
+///
+/// 保存后立即应用到正在运行的核心，之后每次启动或重启核心时重新应用。
+/// 提高优先级（以及在 Unix 上从 low 恢复）需要管理员权限，失败时在下次启动核心时生效。
+/// 核心由 helper（macOS）或 Windows 服务启动时只能设置为 normal。
+#[tauri::command]
+pub async fn set_core_priority(level: String) -> Result<(), String> {
+    let priority = CorePriority::parse(&level)
+        .ok_or_else(|| format!("无效的核心优先级: {}（可选 low、normal、high）", level))?;
+    let state = get_app_state_or_err()?;
+
+    // helper / Windows 服务以管理员权限运行核心，应用无法调整其优先级
+    let running = state.mihomo_manager.is_running().await;
+    let privileged = matches!(
+        detect_run_mode(running).await,
+        crate::models::RunMode::Service | crate::models::RunMode::HelperMac
+    );
+    if privileged && priority != CorePriority::Normal {
+        return Err("核心由系统服务或 helper 启动，无法调整优先级".to_string());
+    }
+    // 普通用户只能调低优先级，提前拒绝而不是在启动核心时静默失败
+    #[cfg(unix)]
+    if priority == CorePriority::High && unsafe { libc::geteuid() } != 0 {
+        return Err("提高核心优先级需要 root 权限".to_string());
     }
 
+    let mut settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    settings.core_priority = priority;
     state
-        .mihomo_api
-        .health_check_proxy_provider(&name)
-        .await
+        .config_manager
+        .save_app_settings(&settings)
         .map_err(|e| e.to_string())?;
+    state.mihomo_manager.set_priority(priority);
 
-    log::info!("Health checked proxy provider: {}", name);
+    if running && !privileged {
+        state
+            .mihomo_manager
+            .apply_priority()
+            .map_err(|e| format!("优先级已保存，将在下次启动核心时生效：{}", e))?;
+    }
     Ok(())
 }
 
-/// 获取规则 Provider 列表
+// ============= 临时直连命令 =============
+
+/// 临时直连最长时长（秒）
+const MAX_TEMPORARY_DIRECT_SECS: u64 = 24 * 60 * 60;
+
+/// 临时切换到直连模式，到期后自动恢复原模式
+///
+/// 重复调用会重新计时。仅修改运行时模式，不写入配置文件
 #[tauri::command]
-pub async fn get_rule_providers() -> Result<Vec<RuleProviderFrontend>, String> {
+pub async fn set_temporary_direct(
+    app: AppHandle,
+    duration_secs: u64,
+) -> Result<TemporaryDirectStatus, String> {
     let state = get_app_state_or_err()?;
 
+    if duration_secs == 0 || duration_secs > MAX_TEMPORARY_DIRECT_SECS {
+        return Err(format!(
+            "临时直连时长需在 1 到 {} 秒之间",
+            MAX_TEMPORARY_DIRECT_SECS
+        ));
+    }
     if !state.mihomo_manager.is_running().await {
-        return Err("Proxy is not running".to_string());
+        return Err("代理核心未运行".to_string());
     }
 
-    let response = state
-        .mihomo_api
-        .get_rule_providers()
+    let config = state
+        .config_manager
+        .load_mihomo_config()
+        .map_err(|e| e.to_string())?;
+    if config.mode == "direct" && !state.temporary_direct.is_active() {
+        return Err("当前已是直连模式".to_string());
+    }
+
+    let status = state
+        .temporary_direct
+        .start(
+            app.clone(),
+            config.mode,
+            std::time::Duration::from_secs(duration_secs),
+        )
         .await
         .map_err(|e| e.to_string())?;
 
-    let mut providers: Vec<RuleProviderFrontend> = response
-        .providers
-        .into_iter()
-        .map(|(_, info)| RuleProviderFrontend {
-            name: info.name,
-            provider_type: info.provider_type,
-            behavior: info.behavior,
-            rule_count: info.rule_count,
-            updated_at: info.updated_at,
-            vehicle_type: info.vehicle_type,
-        })
-        .collect();
+    if let Ok(proxy_status) = get_proxy_status().await {
+        let _ = app.emit("proxy-status-changed", proxy_status);
+    }
 
-    providers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(status)
+}
 
-    Ok(providers)
+/// 立即结束临时直连并恢复原模式
+#[tauri::command]
+pub async fn cancel_temporary_direct(app: AppHandle) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+    state
+        .temporary_direct
+        .restore(&app)
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// 更新规则 Provider
+/// 获取临时直连状态
 #[tauri::command]
-pub async fn update_rule_provider(name: String) -> Result<(), String> {
+pub async fn get_temporary_direct_status() -> Result<TemporaryDirectStatus, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.temporary_direct.status())
+}
+
+/// 连接追踪最长时长（秒）
+const MAX_TRACE_SECS: u64 = 10 * 60;
+
+/// 开始连接追踪：在指定时长内记录新连接的命中规则和代理链
+///
+/// 重复调用会清空记录并重新开始。
+#[tauri::command]
+pub async fn start_trace(duration_secs: u64) -> Result<TraceStatus, String> {
     let state = get_app_state_or_err()?;
 
+    if duration_secs == 0 || duration_secs > MAX_TRACE_SECS {
+        return Err(format!("追踪时长需在 1 到 {} 秒之间", MAX_TRACE_SECS));
+    }
     if !state.mihomo_manager.is_running().await {
-        return Err("Proxy is not running".to_string());
+        return Err("代理核心未运行".to_string());
     }
 
-    state
-        .mihomo_api
-        .update_rule_provider(&name)
-        .await
-        .map_err(|e| e.to_string())?;
+    Ok(state
+        .connection_tracer
+        .start(std::time::Duration::from_secs(duration_secs)))
+}
 
-    log::info!("Updated rule provider: {}", name);
-    Ok(())
+/// 停止连接追踪，返回已记录的连接
+#[tauri::command]
+pub async fn stop_trace() -> Result<Vec<TraceEntry>, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.connection_tracer.stop())
 }
 
-// ============= 设置命令 =============
+/// 获取追踪状态
+#[tauri::command]
+pub async fn get_trace_status() -> Result<TraceStatus, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.connection_tracer.status())
+}
 
-/// 设置混合端口
+/// 获取追踪记录（追踪自动结束后仍可读取，直到下次开始）
 #[tauri::command]
-pub async fn set_mixed_port(app: AppHandle, port: Option<u16>) -> Result<(), String> {
-    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+pub async fn get_trace_entries() -> Result<Vec<TraceEntry>, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.connection_tracer.entries())
+}
 
+/// 获取本次核心会话中各规则的命中次数
+#[tauri::command]
+pub async fn get_rule_hit_counts() -> Result<RuleHitStats, String> {
     let state = get_app_state_or_err()?;
+    Ok(state.rule_hits.stats())
+}
 
-    // 加载当前设置以检查端口冲突
-    let app_settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+/// 清零规则命中计数
+#[tauri::command]
+pub async fn reset_rule_hit_counts() -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+    state.rule_hits.reset();
+    Ok(())
+}
 
-    let port_value = port.unwrap_or(0); // 0 表示禁用混合端口
+/// 获取诊断数据（流量历史、日志缓存、连接追踪、规则命中）的内存占用
+#[tauri::command]
+pub async fn get_observability_stats() -> Result<ObservabilityStats, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.observability.stats())
+}
 
-    // 验证端口不与 HTTP/SOCKS 端口冲突
-    if port_value != 0 {
-        let http_port = app_settings.mihomo.port.unwrap_or(7890);
+/// 设置诊断数据的内存预算、流量历史保留时长和规则命中统计开关，超出部分立即丢弃
+#[tauri::command]
+pub async fn set_observability_settings(
+    settings: ObservabilitySettings,
+) -> Result<ObservabilityStats, String> {
+    if !(MIN_OBSERVABILITY_BUDGET_MB..=MAX_OBSERVABILITY_BUDGET_MB)
+        .contains(&settings.memory_budget_mb)
+    {
+        return Err(format!(
+            "内存预算应在 {}-{} MB 之间",
+            MIN_OBSERVABILITY_BUDGET_MB, MAX_OBSERVABILITY_BUDGET_MB
+        ));
+    }
+    if !(1..=MAX_TRAFFIC_RETENTION_MINUTES).contains(&settings.traffic_retention_minutes) {
+        return Err(format!(
+            "流量历史保留时长应在 1-{} 分钟之间",
+            MAX_TRAFFIC_RETENTION_MINUTES
+        ));
+    }
+    let state = get_app_state_or_err()?;
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.observability = settings.clone();
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    state.observability.apply_settings(&settings);
+    log::info!(
+        "Observability budget set to {} MB, traffic retention {} min",
+        settings.memory_budget_mb,
+        settings.traffic_retention_minutes
+    );
+    Ok(state.observability.stats())
+}
+
+// ============= 选择预设命令 =============
+
+/// 获取所有策略组选择预设
+#[tauri::command]
+pub async fn list_selection_presets() -> Result<Vec<SelectionPreset>, String> {
+    let state = get_app_state_or_err()?;
+
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    Ok(app_settings.selection_presets)
+}
+
+/// 保存当前所有 select 策略组的选择为预设（同名预设会被覆盖）
+#[tauri::command]
+pub async fn save_selection_preset(name: String) -> Result<SelectionPreset, String> {
+    let state = get_app_state_or_err()?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("预设名称不能为空".to_string());
+    }
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let proxies = state
+        .mihomo_api
+        .get_proxies()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 只有 select 策略组可以手动选择，其他类型的 now 由核心自动决定
+    let selections = proxies
+        .proxies
+        .into_iter()
+        .filter(|(_, info)| info.proxy_type == "Selector")
+        .filter_map(|(group, info)| info.now.map(|now| (group, now)))
+        .collect();
+
+    let preset = SelectionPreset {
+        name: name.clone(),
+        selections,
+        saved_at: chrono::Local::now().to_rfc3339(),
+    };
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.selection_presets.retain(|p| p.name != name);
+    app_settings.selection_presets.push(preset.clone());
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    log::info!(
+        "Saved selection preset '{}' ({} groups)",
+        name,
+        preset.selections.len()
+    );
+    Ok(preset)
+}
+
+/// 应用策略组选择预设
+///
+/// 已不存在的策略组或节点会被跳过并在结果中返回
+#[tauri::command]
+pub async fn apply_selection_preset(name: String) -> Result<SelectionPresetResult, String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    let preset = app_settings
+        .selection_presets
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("预设不存在: {}", name))?;
+
+    let mut selections: Vec<_> = preset.selections.into_iter().collect();
+    selections.sort();
+    let result = apply_selections(state, selections).await?;
+
+    log::info!(
+        "Applied selection preset '{}': {} applied, {} skipped",
+        name,
+        result.applied.len(),
+        result.skipped.len()
+    );
+    Ok(result)
+}
+
+/// 在核心中依次选择节点，策略组或节点不存在时跳过
+async fn apply_selections(
+    state: &AppState,
+    selections: Vec<(String, String)>,
+) -> Result<SelectionPresetResult, String> {
+    let proxies = state
+        .mihomo_api
+        .get_proxies()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut result = SelectionPresetResult {
+        applied: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for (group, proxy) in selections {
+        let skip_reason = match proxies.proxies.get(&group) {
+            None => Some("策略组不存在".to_string()),
+            Some(info) if info.proxy_type != "Selector" => Some("策略组不可手动选择".to_string()),
+            Some(info) if !info.all.contains(&proxy) => Some("节点不存在".to_string()),
+            Some(info) if info.now.as_deref() == Some(proxy.as_str()) => None,
+            Some(_) => state
+                .mihomo_api
+                .select_proxy(&group, &proxy)
+                .await
+                .err()
+                .map(|e| e.to_string()),
+        };
+
+        match skip_reason {
+            Some(reason) => result.skipped.push(SkippedSelection {
+                group,
+                proxy,
+                reason,
+            }),
+            None => result.applied.push(group),
+        }
+    }
+    Ok(result)
+}
+
+/// 删除策略组选择预设
+#[tauri::command]
+pub async fn delete_selection_preset(name: String) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.selection_presets.retain(|p| p.name != name);
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())
+}
+
+/// 将配置中所有 `select` 策略组恢复为订阅默认的节点
+///
+/// 默认节点为策略组列出的第一个节点，未列出节点（只使用代理提供者）时取核心中的第一个；
+/// 只能重置当前使用中的配置。已保存的选择预设是全局的，不会被清除。
+#[tauri::command]
+pub async fn reset_selections(profile_id: String) -> Result<SelectionResetResult, String> {
+    let state = get_app_state_or_err()?;
+
+    let workspace = Workspace::new().map_err(|e| e.to_string())?;
+    if workspace
+        .get_active_profile_id()
+        .map_err(|e| e.to_string())?
+        .as_deref()
+        != Some(profile_id.as_str())
+    {
+        return Err("只能重置当前使用中的配置".to_string());
+    }
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let (_, config) = workspace
+        .get_profile(&profile_id)
+        .map_err(|e| e.to_string())?;
+    let proxies = state
+        .mihomo_api
+        .get_proxies()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut result = SelectionResetResult {
+        changed: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for group in config
+        .proxy_groups
+        .iter()
+        .filter(|g| g.group_type == "select")
+    {
+        let Some(info) = proxies.proxies.get(&group.name) else {
+            continue;
+        };
+        let Some(default) = group.proxies.first().or_else(|| info.all.first()).cloned() else {
+            continue;
+        };
+        if info.now.as_deref() == Some(default.as_str()) {
+            continue;
+        }
+
+        let skip_reason = if info.proxy_type != "Selector" {
+            Some("策略组不可手动选择".to_string())
+        } else if !info.all.contains(&default) {
+            Some("节点不存在".to_string())
+        } else {
+            state
+                .mihomo_api
+                .select_proxy(&group.name, &default)
+                .await
+                .err()
+                .map(|e| e.to_string())
+        };
+
+        match skip_reason {
+            Some(reason) => result.skipped.push(SkippedSelection {
+                group: group.name.clone(),
+                proxy: default,
+                reason,
+            }),
+            None => result.changed.push(SelectionChange {
+                group: group.name.clone(),
+                from: info.now.clone(),
+                to: default,
+            }),
+        }
+    }
+
+    log::info!(
+        "Reset selections for profile '{}': {} changed, {} skipped",
+        profile_id,
+        result.changed.len(),
+        result.skipped.len()
+    );
+    Ok(result)
+}
+
+/// 获取按时段选择节点的设置
+#[tauri::command]
+pub async fn get_selection_schedule() -> Result<SelectionScheduleSettings, String> {
+    let state = get_app_state_or_err()?;
+
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    Ok(app_settings.selection_schedule)
+}
+
+/// 设置按时段选择节点
+///
+/// 每条规则为 `{start, end, group, proxy}`（本地时间 `HH:MM`，结束早于开始表示跨天）。
+/// 启用后进入新时段时自动切换，并发送 `selection-schedule-applied` 事件；
+/// 同一策略组的时段重叠时靠前的规则优先，策略组或节点不存在时跳过。
+#[tauri::command]
+pub async fn set_selection_schedule(
+    app: AppHandle,
+    settings: SelectionScheduleSettings,
+) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    for entry in &settings.entries {
+        for time in [&entry.start, &entry.end] {
+            if parse_schedule_time(time).is_none() {
+                return Err(format!("时间格式无效: {}（应为 HH:MM）", time));
+            }
+        }
+        if entry.group.trim().is_empty() || entry.proxy.trim().is_empty() {
+            return Err("策略组和节点不能为空".to_string());
+        }
+    }
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.selection_schedule = settings.clone();
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    if settings.enabled {
+        state.selection_scheduler.start(app, settings);
+    } else {
+        state.selection_scheduler.stop();
+    }
+
+    Ok(())
+}
+
+/// 开关核心的 `profile.store-selected`
+///
+/// 开启后核心把每个 select 策略组的当前选择写入缓存，重载/重启后自动恢复；
+/// 关闭后每次重载都会回到配置中的第一个节点。
+///
+/// 与选择预设互不冲突：预设只在用户主动应用时通过 API 切换节点，
+/// 切换结果随后由核心缓存接管。因此两者同时开启时，重启后恢复的是
+/// 最近一次的选择（可能来自预设），而不是预设本身。
+#[tauri::command]
+pub async fn set_store_selected(app: AppHandle, enabled: bool) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let state = get_app_state_or_err()?;
+    let current = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?
+        .mihomo
+        .store_selected;
+    if current == enabled {
+        return Ok(());
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.store_selected = enabled;
+        Ok(())
+    })
+    .await?;
+
+    log::info!("store-selected set to {}", enabled);
+    Ok(())
+}
+
+// ============= Provider 命令 =============
+
+/// 代理 Provider 返回给前端的结构
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyProviderFrontend {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub provider_type: String,
+    #[serde(rename = "vehicleType")]
+    pub vehicle_type: String,
+    pub proxies: Vec<ProxyProviderProxyFrontend>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<String>,
+    #[serde(rename = "subscriptionInfo")]
+    pub subscription_info: Option<SubscriptionInfoFrontend>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyProviderProxyFrontend {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub proxy_type: String,
+    pub udp: bool,
+    pub now: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionInfoFrontend {
+    #[serde(rename = "Upload")]
+    pub upload: Option<u64>,
+    #[serde(rename = "Download")]
+    pub download: Option<u64>,
+    #[serde(rename = "Total")]
+    pub total: Option<u64>,
+    #[serde(rename = "Expire")]
+    pub expire: Option<u64>,
+}
+
+/// 规则 Provider 返回给前端的结构
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleProviderFrontend {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub provider_type: String,
+    pub behavior: String,
+    #[serde(rename = "ruleCount")]
+    pub rule_count: u32,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<String>,
+    #[serde(rename = "vehicleType")]
+    pub vehicle_type: String,
+}
+
+/// 获取代理 Provider 列表
+#[tauri::command]
+pub async fn get_proxy_providers() -> Result<Vec<ProxyProviderFrontend>, String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let response = state
+        .mihomo_api
+        .get_proxy_providers()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut providers: Vec<ProxyProviderFrontend> = response
+        .providers
+        .into_iter()
+        .filter(|(_, info)| info.vehicle_type != "Compatible")
+        .map(|(_, info)| ProxyProviderFrontend {
+            name: info.name,
+            provider_type: info.provider_type,
+            vehicle_type: info.vehicle_type,
+            proxies: info
+                .proxies
+                .into_iter()
+                .map(|p| ProxyProviderProxyFrontend {
+                    name: p.name,
+                    proxy_type: p.proxy_type,
+                    udp: p.udp,
+                    now: p.now,
+                })
+                .collect(),
+            updated_at: info.updated_at,
+            subscription_info: info.subscription_info.map(|s| SubscriptionInfoFrontend {
+                upload: s.upload,
+                download: s.download,
+                total: s.total,
+                expire: s.expire,
+            }),
+        })
+        .collect();
+
+    providers.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(providers)
+}
+
+/// 更新代理 Provider
+#[tauri::command]
+pub async fn update_proxy_provider(name: String) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    state
+        .mihomo_api
+        .update_proxy_provider(&name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Updated proxy provider: {}", name);
+    Ok(())
+}
+
+/// 代理 Provider 健康检查
+#[tauri::command]
+pub async fn health_check_proxy_provider(name: String) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    state
+        .mihomo_api
+        .health_check_proxy_provider(&name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Health checked proxy provider: {}", name);
+    Ok(())
+}
+
+/// 获取规则 Provider 列表
+#[tauri::command]
+pub async fn get_rule_providers() -> Result<Vec<RuleProviderFrontend>, String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let response = state
+        .mihomo_api
+        .get_rule_providers()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut providers: Vec<RuleProviderFrontend> = response
+        .providers
+        .into_iter()
+        .map(|(_, info)| RuleProviderFrontend {
+            name: info.name,
+            provider_type: info.provider_type,
+            behavior: info.behavior,
+            rule_count: info.rule_count,
+            updated_at: info.updated_at,
+            vehicle_type: info.vehicle_type,
+        })
+        .collect();
+
+    providers.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(providers)
+}
+
+/// 更新规则 Provider
+#[tauri::command]
+pub async fn update_rule_provider(name: String) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    state
+        .mihomo_api
+        .update_rule_provider(&name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Updated rule provider: {}", name);
+    Ok(())
+}
+
+/// Provider 预热结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderWarmResult {
+    pub name: String,
+    /// "proxy" 或 "rule"
+    pub kind: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 预热当前配置：触发所有远程 Provider 下载，返回每个 Provider 的结果
+#[tauri::command]
+pub async fn warm_active_profile() -> Result<Vec<ProviderWarmResult>, String> {
+    let state = get_app_state_or_err()?;
+
+    if !state.mihomo_manager.is_running().await {
+        return Err("Proxy is not running".to_string());
+    }
+
+    warm_providers(&state.mihomo_api).await
+}
+
+/// 并发更新所有 HTTP 类型的代理/规则 Provider
+pub(crate) async fn warm_providers(
+    api: &crate::mihomo::MihomoApi,
+) -> Result<Vec<ProviderWarmResult>, String> {
+    let proxy_providers = api.get_proxy_providers().await.map_err(|e| e.to_string())?;
+    let rule_providers = api.get_rule_providers().await.map_err(|e| e.to_string())?;
+
+    let mut names: Vec<(&'static str, String)> = proxy_providers
+        .providers
+        .into_values()
+        .filter(|info| info.vehicle_type == "HTTP")
+        .map(|info| ("proxy", info.name))
+        .chain(
+            rule_providers
+                .providers
+                .into_values()
+                .filter(|info| info.vehicle_type == "HTTP")
+                .map(|info| ("rule", info.name)),
+        )
+        .collect();
+    names.sort();
+
+    let futures = names.into_iter().map(|(kind, name)| async move {
+        let result = if kind == "proxy" {
+            api.update_proxy_provider(&name).await
+        } else {
+            api.update_rule_provider(&name).await
+        };
+        let error = result.err().map(|e| e.to_string());
+        if let Some(e) = &error {
+            log::warn!("Failed to warm {} provider {}: {}", kind, name, e);
+        }
+        ProviderWarmResult {
+            name,
+            kind: kind.to_string(),
+            success: error.is_none(),
+            error,
+        }
+    });
+    let results = futures_util::future::join_all(futures).await;
+
+    log::info!(
+        "Warmed {}/{} providers",
+        results.iter().filter(|r| r.success).count(),
+        results.len()
+    );
+    Ok(results)
+}
+
+// ============= 设置命令 =============
+
+/// 设置混合端口
+#[tauri::command]
+pub async fn set_mixed_port(app: AppHandle, port: Option<u16>) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let state = get_app_state_or_err()?;
+
+    // 加载当前设置以检查端口冲突
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+
+    let port_value = port.unwrap_or(0); // 0 表示禁用混合端口
+
+    // 验证端口不与 HTTP/SOCKS 端口冲突
+    if port_value != 0 {
+        let http_port = app_settings.mihomo.port.unwrap_or(7890);
         let socks_port = app_settings.mihomo.socks_port.unwrap_or(7891);
 
-        if port_value == http_port {
+        if port_value == http_port {
+            return Err(format!(
+                "混合端口 {} 与 HTTP 端口冲突，请使用不同的端口",
+                port_value
+            ));
+        }
+        if port_value == socks_port {
+            return Err(format!(
+                "混合端口 {} 与 SOCKS 端口冲突，请使用不同的端口",
+                port_value
+            ));
+        }
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.mixed_port = if port_value == 0 {
+            None
+        } else {
+            Some(port_value)
+        };
+        Ok(())
+    })
+    .await
+}
+
+/// 设置进程查找模式
+#[tauri::command]
+pub async fn set_find_process_mode(app: AppHandle, mode: String) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    // 验证模式
+    let valid_modes = ["always", "strict", "off"];
+    if !valid_modes.contains(&mode.as_str()) {
+        return Err(format!("无效的进程查找模式: {}", mode));
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.find_process_mode = mode.clone();
+        Ok(())
+    })
+    .await?;
+
+    log::info!("Find process mode set to: {}", mode);
+    Ok(())
+}
+
+/// 设置出站绑定网卡（`interface-name`）和 `routing-mark`
+///
+/// `name` 为空字符串时恢复由核心自动选择；`routing_mark` 仅在 Linux 上生效。
+/// 两者都可以热重载，无需重启核心。
+#[tauri::command]
+pub async fn set_interface_name(
+    app: AppHandle,
+    name: String,
+    routing_mark: Option<u32>,
+) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let name = name.trim().to_string();
+    if !name.is_empty() {
+        let interfaces = crate::system::list_network_interfaces().map_err(|e| e.to_string())?;
+        let Some(iface) = interfaces.iter().find(|i| i.name == name) else {
+            return Err(format!("网卡不存在: {}", name));
+        };
+        if iface.is_loopback {
+            return Err(format!("不能绑定回环网卡: {}", name));
+        }
+    }
+    validate_routing_mark(routing_mark)?;
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.interface_name = Some(name.clone()).filter(|n| !n.is_empty());
+        settings.routing_mark = routing_mark;
+        Ok(())
+    })
+    .await?;
+
+    log::info!(
+        "Interface name set to: {} (routing-mark: {:?})",
+        if name.is_empty() { "auto" } else { &name },
+        routing_mark
+    );
+    Ok(())
+}
+
+/// 校验 `routing-mark`：仅 Linux 可用，0 表示不打标记，应传 None
+fn validate_routing_mark(routing_mark: Option<u32>) -> Result<(), String> {
+    match routing_mark {
+        None => Ok(()),
+        Some(_) if !cfg!(target_os = "linux") => Err("routing-mark 仅在 Linux 上可用".to_string()),
+        Some(0) => Err("routing-mark 不能为 0，清除请留空".to_string()),
+        Some(_) => Ok(()),
+    }
+}
+
+/// 设置核心出站流量的 `routing-mark`（fwmark，仅 Linux）
+///
+/// 核心自身的出站连接会带上该标记，配合 `ip rule add fwmark <mark> ...`
+/// 让它们绕过策略路由或 TUN 路由表。TUN auto-route 默认靠绑定出站网卡避免回环，
+/// 与 routing-mark 可以同时使用；标记需避开系统中已有的 fwmark 规则。
+/// 为 None 时清除。可以热重载，无需重启核心。
+#[tauri::command]
+pub async fn set_routing_mark(app: AppHandle, routing_mark: Option<u32>) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    validate_routing_mark(routing_mark)?;
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.routing_mark = routing_mark;
+        Ok(())
+    })
+    .await?;
+
+    log::info!("Routing mark set to: {:?}", routing_mark);
+    Ok(())
+}
+
+/// GEO 自动更新间隔的允许范围（小时）
+const GEO_UPDATE_INTERVAL_RANGE: std::ops::RangeInclusive<u32> = 1..=720;
+
+/// 设置由核心自动更新 GEO 数据（`geo-auto-update` / `geo-update-interval`）
+///
+/// 开启后关闭应用侧规则数据库的自动更新，避免两边同时下载（反之应用侧开启自动更新时
+/// `save_app_settings` 会关闭核心侧更新）；`interval_hours`
+/// 为 None 时使用核心默认值。可以热重载，无需重启核心。
+#[tauri::command]
+pub async fn set_geo_auto_update(
+    app: AppHandle,
+    enabled: bool,
+    interval_hours: Option<u32>,
+) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    if let Some(interval) = interval_hours {
+        if !GEO_UPDATE_INTERVAL_RANGE.contains(&interval) {
             return Err(format!(
-                "混合端口 {} 与 HTTP 端口冲突，请使用不同的端口",
-                port_value
+                "geo-update-interval 必须在 {}-{} 小时之间",
+                GEO_UPDATE_INTERVAL_RANGE.start(),
+                GEO_UPDATE_INTERVAL_RANGE.end()
             ));
         }
-        if port_value == socks_port {
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        if enabled && cfg!(target_os = "macos") && settings.tun.enable {
+            return Err("macOS 增强模式下 GEO 数据只能由应用更新".to_string());
+        }
+        settings.geo_auto_update = enabled;
+        settings.geo_update_interval = interval_hours;
+        Ok(())
+    })
+    .await?;
+
+    if enabled {
+        let state = get_app_state_or_err()?;
+        let mut app_settings = state
+            .config_manager
+            .load_app_settings()
+            .map_err(|e| e.to_string())?;
+        if app_settings.rule_databases.iter().any(|db| db.auto_update) {
+            for db in &mut app_settings.rule_databases {
+                db.auto_update = false;
+            }
+            state
+                .config_manager
+                .save_app_settings(&app_settings)
+                .map_err(|e| e.to_string())?;
+            log::info!("Disabled app-side rule database auto update (core owns GEO updates)");
+        }
+    }
+
+    log::info!(
+        "GEO auto update set to: {} (interval: {:?}h)",
+        enabled,
+        interval_hours
+    );
+    Ok(())
+}
+
+/// keep-alive 时间的允许范围（秒）
+const KEEP_ALIVE_RANGE: std::ops::RangeInclusive<u32> = 1..=3600;
+
+/// 移动网络预设：拉长探测间隔，减少计费流量和唤醒次数
+const MOBILE_KEEP_ALIVE_INTERVAL: u32 = 60;
+const MOBILE_KEEP_ALIVE_IDLE: u32 = 600;
+
+/// 设置 TCP keep-alive（`keep-alive-interval` / `keep-alive-idle` / `disable-keep-alive`）
+///
+/// `interval`、`idle` 为 None 时使用核心默认值。可以热重载，无需重启核心。
+#[tauri::command]
+pub async fn set_keep_alive(
+    app: AppHandle,
+    interval: Option<u32>,
+    idle: Option<u32>,
+    disabled: bool,
+) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    for (name, value) in [("keep-alive-interval", interval), ("keep-alive-idle", idle)] {
+        if let Some(value) = value {
+            if !KEEP_ALIVE_RANGE.contains(&value) {
+                return Err(format!(
+                    "{} 必须在 {}-{} 秒之间",
+                    name,
+                    KEEP_ALIVE_RANGE.start(),
+                    KEEP_ALIVE_RANGE.end()
+                ));
+            }
+        }
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        settings.keep_alive_interval = interval;
+        settings.keep_alive_idle = idle;
+        settings.disable_keep_alive = disabled;
+        Ok(())
+    })
+    .await?;
+
+    log::info!(
+        "Keep-alive set to: interval={:?}, idle={:?}, disabled={}",
+        interval,
+        idle,
+        disabled
+    );
+    Ok(())
+}
+
+/// 应用移动网络 keep-alive 预设（较长的探测间隔和空闲时间）
+#[tauri::command]
+pub async fn apply_mobile_keep_alive_preset(app: AppHandle) -> Result<(), String> {
+    set_keep_alive(
+        app,
+        Some(MOBILE_KEEP_ALIVE_INTERVAL),
+        Some(MOBILE_KEEP_ALIVE_IDLE),
+        false,
+    )
+    .await
+}
+
+/// User-Agent 最大长度
+const MAX_GLOBAL_UA_LEN: usize = 256;
+
+/// Provider 健康检查间隔范围（秒）
+const PROVIDER_HEALTH_CHECK_RANGE: std::ops::RangeInclusive<u32> = 60..=86400;
+
+/// 设置核心资源下载参数（`global-ua` / `etag-support` / Provider 健康检查间隔）
+///
+/// 为 None 时使用核心默认值（健康检查间隔为 None 时使用 Profile 中的值）。
+/// 开启 ETag 后核心下载 Provider 时会发送条件请求，服务器返回 304 则跳过下载。
+/// 可以热重载，无需重启核心。
+#[tauri::command]
+pub async fn set_provider_download_options(
+    app: AppHandle,
+    global_ua: Option<String>,
+    etag_support: Option<bool>,
+    health_check_interval: Option<u32>,
+) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, reload_config, ReloadOptions};
+
+    let global_ua = global_ua
+        .map(|ua| ua.trim().to_string())
+        .filter(|ua| !ua.is_empty());
+    if let Some(ua) = &global_ua {
+        if ua.len() > MAX_GLOBAL_UA_LEN {
+            return Err(format!("User-Agent 不能超过 {} 个字符", MAX_GLOBAL_UA_LEN));
+        }
+        if ua.chars().any(char::is_control) {
+            return Err("User-Agent 不能包含控制字符".to_string());
+        }
+    }
+
+    if let Some(interval) = health_check_interval {
+        if !PROVIDER_HEALTH_CHECK_RANGE.contains(&interval) {
             return Err(format!(
-                "混合端口 {} 与 SOCKS 端口冲突，请使用不同的端口",
-                port_value
+                "健康检查间隔必须在 {}-{} 秒之间",
+                PROVIDER_HEALTH_CHECK_RANGE.start(),
+                PROVIDER_HEALTH_CHECK_RANGE.end()
             ));
         }
     }
 
+    let state = get_app_state_or_err()?;
+    let previous_interval = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?
+        .mihomo
+        .provider_health_check_interval;
+
     apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
-        settings.mixed_port = if port_value == 0 {
-            None
-        } else {
-            Some(port_value)
-        };
+        settings.global_ua = global_ua.clone();
+        settings.etag_support = etag_support;
+        settings.provider_health_check_interval = health_check_interval;
         Ok(())
     })
-    .await
+    .await?;
+
+    // 取消覆盖时 config.yaml 中仍是覆盖后的间隔，按 Profile 重新生成运行时配置
+    if previous_interval.is_some() && health_check_interval.is_none() {
+        let system_proxy_enabled = *state.system_proxy_enabled.lock().await;
+        if crate::commands::profile::write_active_runtime_config(state, system_proxy_enabled)? {
+            reload_config(Some(&app), &ReloadOptions::default()).await?;
+        }
+    }
+
+    log::info!(
+        "Provider download options set to: global-ua={:?}, etag-support={:?}, health-check-interval={:?}",
+        global_ua,
+        etag_support,
+        health_check_interval
+    );
+    Ok(())
 }
 
-/// 设置进程查找模式
+/// 设置 DNS 增强模式（fake-ip / redir-host）
+///
+/// 切换增强模式需要核心 API restart（见 detect_config_change_type_with_mode），
+/// 重启后清除 FakeIP 缓存，避免残留的假 IP 映射导致连接失败。
 #[tauri::command]
-pub async fn set_find_process_mode(app: AppHandle, mode: String) -> Result<(), String> {
+pub async fn set_dns_enhanced_mode(app: AppHandle, mode: String) -> Result<(), String> {
     use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
 
-    // 验证模式
-    let valid_modes = ["always", "strict", "off"];
+    let valid_modes = ["fake-ip", "redir-host"];
     if !valid_modes.contains(&mode.as_str()) {
-        return Err(format!("无效的进程查找模式: {}", mode));
+        return Err(format!("无效的 DNS 增强模式: {}", mode));
+    }
+
+    let state = get_app_state_or_err()?;
+    let current = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?
+        .mihomo
+        .dns
+        .enhanced_mode;
+    if current.as_deref() == Some(mode.as_str()) {
+        return Ok(());
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::safe(), |settings| {
+        settings.dns.enhanced_mode = Some(mode.clone());
+        Ok(())
+    })
+    .await?;
+
+    if state.mihomo_manager.is_running().await {
+        if let Err(e) = state.mihomo_api.flush_fakeip().await {
+            log::warn!("Failed to flush FakeIP cache after DNS mode change: {}", e);
+        }
+    }
+
+    log::info!("DNS enhanced mode set to: {}", mode);
+    Ok(())
+}
+
+/// fake-ip 网段至少包含的地址数（前缀长度上限）
+const MAX_FAKE_IP_PREFIX: u8 = 20;
+
+/// 校验 fake-ip 网段：IPv4、足够大、位于私有/保留地址段内
+fn validate_fake_ip_range(cidr: &str) -> Result<crate::config::IpCidr, String> {
+    use crate::config::IpCidr;
+    use std::net::IpAddr;
+
+    let range = IpCidr::parse(cidr).map_err(|e| e.to_string())?;
+    let IpAddr::V4(addr) = range.addr else {
+        return Err("fake-ip-range 必须是 IPv4 网段".to_string());
+    };
+    if range.prefix > MAX_FAKE_IP_PREFIX {
+        return Err(format!(
+            "fake-ip 网段过小，前缀长度不能大于 /{}",
+            MAX_FAKE_IP_PREFIX
+        ));
+    }
+
+    // 私有地址、运营商级 NAT（100.64.0.0/10）和基准测试保留段（198.18.0.0/15）
+    let reserved = [
+        "10.0.0.0/8",
+        "172.16.0.0/12",
+        "192.168.0.0/16",
+        "100.64.0.0/10",
+        "198.18.0.0/15",
+    ];
+    let inside_reserved = reserved.iter().any(|block| {
+        IpCidr::parse(block)
+            .is_ok_and(|block| block.prefix <= range.prefix && block.contains(IpAddr::V4(addr)))
+    });
+    if !inside_reserved {
+        return Err(format!("{} 不在私有或保留地址段内", cidr));
+    }
+    Ok(range)
+}
+
+/// 检查 fake-ip 网段是否与局域网网段、TUN 路由排除网段重叠
+///
+/// 与网卡网段重叠时 fake-ip 会劫持局域网流量；落入路由排除网段时
+/// fake-ip 流量不经过 TUN。TUN 网卡的地址取自当前 fake-ip 网段，不参与比较。
+fn check_fake_ip_range_conflicts(
+    range: &crate::config::IpCidr,
+    current: Option<&str>,
+    tun: &crate::models::TunConfig,
+) -> Result<(), String> {
+    use crate::config::IpCidr;
+
+    let current = current.and_then(|c| IpCidr::parse(c).ok());
+    if let Ok(networks) = crate::system::list_interface_networks() {
+        for (name, network) in networks {
+            let Ok(network) = IpCidr::parse(&network) else {
+                continue;
+            };
+            if current.as_ref().is_some_and(|c| c.contains(network.addr)) {
+                continue;
+            }
+            if range.overlaps(&network) {
+                return Err(format!("fake-ip 网段与网卡 {} 的网段重叠", name));
+            }
+        }
+    }
+
+    let excluded = tun
+        .inet4_route_exclude_address
+        .iter()
+        .chain(&tun.route_exclude_address);
+    for network in excluded {
+        if IpCidr::parse(network).is_ok_and(|n| range.overlaps(&n)) {
+            return Err(format!("fake-ip 网段与 TUN 路由排除网段 {} 重叠", network));
+        }
+    }
+    Ok(())
+}
+
+/// 设置 DNS fake-ip 网段（空字符串恢复默认 `198.18.0.1/16`）
+///
+/// 用于避开与局域网重叠的默认网段。变更需要核心 API restart，
+/// 完成后清空 FakeIP 缓存，避免旧网段的映射残留。
+#[tauri::command]
+pub async fn set_fake_ip_range(app: AppHandle, cidr: String) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+    use crate::models::DnsConfig;
+
+    let cidr = match cidr.trim() {
+        "" => DnsConfig::default().fake_ip_range.unwrap_or_default(),
+        cidr => cidr.to_string(),
+    };
+    let range = validate_fake_ip_range(&cidr)?;
+
+    let state = get_app_state_or_err()?;
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?
+        .mihomo;
+    let current = settings.dns.fake_ip_range.as_deref();
+    if current == Some(cidr.as_str()) {
+        return Ok(());
+    }
+    check_fake_ip_range_conflicts(&range, current, &settings.tun)?;
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::safe(), |settings| {
+        settings.dns.fake_ip_range = Some(cidr.clone());
+        Ok(())
+    })
+    .await?;
+
+    if state.mihomo_manager.is_running().await {
+        if let Err(e) = state.mihomo_api.flush_fakeip().await {
+            log::warn!("Failed to flush FakeIP cache after range change: {}", e);
+        }
+    }
+
+    log::info!("Fake-IP range set to: {}", cidr);
+    Ok(())
+}
+
+/// 添加或更新 DNS nameserver-policy 条目（匹配的域名使用指定的 DNS 服务器）
+#[tauri::command]
+pub async fn add_nameserver_policy(
+    app: AppHandle,
+    domain: String,
+    servers: Vec<String>,
+) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let domain = domain.trim().to_string();
+    validate_policy_domain(&domain)?;
+
+    let servers: Vec<String> = servers
+        .iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if servers.is_empty() {
+        return Err("至少需要一个 DNS 服务器".to_string());
+    }
+    for server in &servers {
+        validate_dns_server(server)?;
     }
 
     apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
-        settings.find_process_mode = mode.clone();
+        settings
+            .dns
+            .nameserver_policy
+            .get_or_insert_with(|| {
+                // 未配置时以默认策略为基础，避免添加一条后丢失默认分流
+                crate::models::DnsConfig::default()
+                    .nameserver_policy
+                    .unwrap_or_default()
+            })
+            .insert(domain.clone(), servers.clone());
         Ok(())
     })
     .await?;
 
-    log::info!("Find process mode set to: {}", mode);
+    log::info!("Nameserver policy set: {} -> {:?}", domain, servers);
+    Ok(())
+}
+
+/// 删除 DNS nameserver-policy 条目
+#[tauri::command]
+pub async fn remove_nameserver_policy(app: AppHandle, domain: String) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        let removed = settings
+            .dns
+            .nameserver_policy
+            .as_mut()
+            .and_then(|policy| policy.remove(&domain));
+        if removed.is_none() {
+            return Err(format!("DNS 策略不存在: {}", domain));
+        }
+        Ok(())
+    })
+    .await?;
+
+    log::info!("Nameserver policy removed: {}", domain);
+    Ok(())
+}
+
+/// DoH 测试的默认域名
+const DEFAULT_DOH_TEST_DOMAIN: &str = "www.google.com";
+
+/// 测试 DNS-over-HTTPS 服务器是否可用
+///
+/// 不经过核心直接向 `url` 发送 RFC 8484 查询，返回解析到的地址和耗时，
+/// 用于确认加密 DNS 在当前网络下确实可用。
+#[tauri::command]
+pub async fn test_doh_resolver(
+    url: String,
+    test_domain: Option<String>,
+    timeout: Option<u32>,
+) -> Result<crate::utils::DohTestResult, String> {
+    let domain = test_domain
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| DEFAULT_DOH_TEST_DOMAIN.to_string());
+    let timeout = std::time::Duration::from_millis(u64::from(timeout.unwrap_or(5000)));
+
+    crate::utils::test_doh_resolver(&url, &domain, timeout)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 核心不提供 DNS 查询接口时，经代理查询所用的 DoH 服务器
+const PROXY_DNS_FALLBACK_DOH: &str = "https://1.1.1.1/dns-query";
+
+/// 对比核心 DNS 与系统 DNS 对同一域名的解析结果，用于排查 DNS 污染和泄漏
+///
+/// 核心一侧优先使用控制接口的 DNS 查询，核心不支持该接口时改为经代理端口
+/// 查询公共 DoH 服务器。两边都解析成功但没有相同地址时标记为不一致。
+#[tauri::command]
+pub async fn compare_dns(
+    domain: String,
+    timeout: Option<u32>,
+) -> Result<crate::utils::DnsComparison, String> {
+    use crate::utils::{resolve_with_system, DnsComparison, DnsLookup};
+
+    let domain = domain.trim().trim_end_matches('.').to_string();
+    if domain.is_empty() || domain.parse::<std::net::IpAddr>().is_ok() {
+        return Err("请输入要解析的域名".to_string());
+    }
+    let timeout = std::time::Duration::from_millis(u64::from(timeout.unwrap_or(5000)));
+    let state = get_app_state_or_err()?;
+
+    let system = async {
+        let start = std::time::Instant::now();
+        DnsLookup::new("system", start, resolve_with_system(&domain, timeout).await)
+    };
+    let (core, system) = tokio::join!(resolve_with_core(state, &domain, timeout), system);
+
+    let comparison = DnsComparison::new(domain, core, system);
+    if comparison.mismatch {
+        log::warn!(
+            "DNS mismatch for {}: core={:?} system={:?}",
+            comparison.domain,
+            comparison.core.ips,
+            comparison.system.ips
+        );
+    }
+    Ok(comparison)
+}
+
+/// 通过核心解析域名，核心不提供 `/dns/query` 时经代理端口查询 DoH
+async fn resolve_with_core(
+    state: &AppState,
+    domain: &str,
+    timeout: std::time::Duration,
+) -> crate::utils::DnsLookup {
+    use crate::utils::{parse_core_dns_answer, resolve_doh_through_proxy, DnsLookup};
+
+    let start = std::time::Instant::now();
+    if !state.mihomo_manager.is_running().await {
+        return DnsLookup::new("core", start, Err(anyhow::anyhow!("代理未运行")));
+    }
+
+    let query = |query_type| async move {
+        tokio::time::timeout(timeout, state.mihomo_api.dns_query(domain, query_type))
+            .await
+            .map_err(|_| anyhow::anyhow!("查询超时"))?
+    };
+    match query("A").await {
+        Ok(answer) => {
+            let result = match parse_core_dns_answer(&answer) {
+                Ok(mut ips) => {
+                    // 没有 IPv6 记录很常见，AAAA 查询失败不影响结果
+                    if let Ok(v6) = query("AAAA").await {
+                        ips.extend(parse_core_dns_answer(&v6).unwrap_or_default());
+                    }
+                    Ok(ips)
+                }
+                Err(e) => Err(e),
+            };
+            DnsLookup::new("core", start, result)
+        }
+        Err(e) => {
+            log::info!(
+                "Core DNS query unavailable ({}), resolving {} through proxy",
+                e,
+                domain
+            );
+            let port = match state.config_manager.load_mihomo_config() {
+                Ok(config) => config.mixed_port.unwrap_or(7892),
+                Err(e) => return DnsLookup::new("proxy", start, Err(e)),
+            };
+            let result = resolve_doh_through_proxy(port, PROXY_DNS_FALLBACK_DOH, domain, timeout)
+                .await
+                .map(|r| r.ips);
+            DnsLookup::new("proxy", start, result)
+        }
+    }
+}
+
+/// 将 DoH 服务器添加到 DNS 的 `nameserver` 或 `fallback` 列表（已存在时不重复添加）
+#[tauri::command]
+pub async fn add_doh_resolver(app: AppHandle, url: String, list: String) -> Result<(), String> {
+    use crate::commands::reload::{apply_mihomo_settings_change, ReloadOptions};
+
+    let url = url.trim().to_string();
+    crate::utils::validate_doh_url(&url).map_err(|e| e.to_string())?;
+    if !matches!(list.as_str(), "nameserver" | "fallback") {
+        return Err(format!("无效的 DNS 列表: {}", list));
+    }
+
+    apply_mihomo_settings_change(Some(&app), &ReloadOptions::default(), |settings| {
+        let servers = match list.as_str() {
+            "fallback" => &mut settings.dns.fallback,
+            _ => &mut settings.dns.nameserver,
+        };
+        if !servers.contains(&url) {
+            servers.push(url.clone());
+        }
+        Ok(())
+    })
+    .await?;
+
+    log::info!("DoH resolver added to {}: {}", list, url);
     Ok(())
 }
 
@@ -6,17 +6,22 @@
 //! - 健康检查
 //! - 状态同步
 //! - 智能重载策略（区分热重载和需要重启的配置变更）
+//! - 重载防抖（合并连续编辑触发的重载）
 
 use anyhow::Result;
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{Mutex, MutexGuard};
 use tokio::time::sleep;
 
 use crate::commands::proxy::get_proxy_status;
 use crate::commands::{get_app_state_or_err, try_get_app_state, AppState};
-use crate::models::{MihomoConfig, RunMode};
+use crate::models::{MihomoConfig, MihomoProfileConfig, ProxyStatus, RunMode};
+use crate::tray_menu::TrayMenuState;
 
 /// 配置变更类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -101,21 +106,25 @@ pub fn should_process_restart_for_tun_change(
 /// - TUN 模式变更：根据 RunMode 决定 ApiRestart 或 ProcessRestart
 /// - TUN 栈变更：ApiRestart
 /// - external-controller 变更：ProcessRestart
+/// - sniffer 变更：HotReload
 pub fn detect_config_change_type_with_mode(
     old: &MihomoConfig,
     new: &MihomoConfig,
     run_mode: &RunMode,
 ) -> ConfigChangeResult {
-    // 端口变更可以通过 PATCH /configs 热更新，不需要重启
-    if old.port != new.port || old.socks_port != new.socks_port || old.mixed_port != new.mixed_port
-    {
-        let reason = format!(
-            "端口变更 (HTTP: {:?}->{:?}, SOCKS: {:?}->{:?}, Mixed: {:?}->{:?})",
-            old.port, new.port, old.socks_port, new.socks_port, old.mixed_port, new.mixed_port
-        );
-        log::info!("[ConfigChange] {} - 可热重载", reason);
+    // 局域网开关或监听地址变更需要进程级重启，确保旧地址上的监听被彻底关闭
+    if old.allow_lan != new.allow_lan || old.bind_address != new.bind_address {
+        let listen = |config: &MihomoConfig| {
+            if config.allow_lan {
+                config.bind_address.as_deref().unwrap_or("*").to_string()
+            } else {
+                "127.0.0.1".to_string()
+            }
+        };
+        let reason = format!("监听地址变更 ({} -> {})", listen(old), listen(new));
+        log::warn!("[ConfigChange] {} - 需要进程级重启", reason);
         return ConfigChangeResult {
-            change_type: ConfigChangeType::HotReload,
+            change_type: ConfigChangeType::ProcessRestart,
             reason: Some(reason),
         };
     }
@@ -165,6 +174,28 @@ pub fn detect_config_change_type_with_mode(
         };
     }
 
+    // TUN 路由地址或 IPv6 地址变更：需要重建网卡和路由表，通过 API restart 生效
+    if old_tun_enabled {
+        let route_lists = |config: &MihomoConfig| {
+            config.tun.as_ref().map(|t| {
+                (
+                    t.inet4_route_exclude_address.clone(),
+                    t.route_exclude_address.clone(),
+                    t.route_address.clone(),
+                    t.inet6_address.clone(),
+                )
+            })
+        };
+        if route_lists(old) != route_lists(new) {
+            let reason = "TUN 路由地址变更".to_string();
+            log::info!("[ConfigChange] {} - 需要 API restart", reason);
+            return ConfigChangeResult {
+                change_type: ConfigChangeType::ApiRestart,
+                reason: Some(reason),
+            };
+        }
+    }
+
     // DNS 相关变更需要 API restart
     // 检查 enhanced-mode (fake-ip / redir-host / normal)
     let old_enhanced_mode = old.dns.as_ref().and_then(|d| d.enhanced_mode.as_ref());
@@ -252,6 +283,117 @@ pub fn detect_config_change_type_with_mode(
         };
     }
 
+    // HTTPS 控制器的监听地址或证书变更需要进程级重启
+    if old.external_controller_tls != new.external_controller_tls || old.tls != new.tls {
+        let reason = format!(
+            "External Controller TLS 变更 ({} -> {})",
+            old.external_controller_tls.as_deref().unwrap_or("无"),
+            new.external_controller_tls.as_deref().unwrap_or("无")
+        );
+        log::warn!("[ConfigChange] {} - 需要进程级重启", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::ProcessRestart,
+            reason: Some(reason),
+        };
+    }
+
+    // 脚本在核心启动时编译，进入/退出 script 模式或修改脚本需要 API restart
+    if old.script != new.script
+        || (old.mode != new.mode && (old.mode == "script" || new.mode == "script"))
+    {
+        let reason = "脚本模式配置变更".to_string();
+        log::info!("[ConfigChange] {} - 需要 API restart", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::ApiRestart,
+            reason: Some(reason),
+        };
+    }
+
+    // 以下变更均可热重载，需要重启的检查都必须放在前面，避免被提前返回跳过
+    // 端口变更可以通过 PATCH /configs 热更新，不需要重启
+    if old.port != new.port || old.socks_port != new.socks_port || old.mixed_port != new.mixed_port
+    {
+        let reason = format!(
+            "端口变更 (HTTP: {:?}->{:?}, SOCKS: {:?}->{:?}, Mixed: {:?}->{:?})",
+            old.port, new.port, old.socks_port, new.socks_port, old.mixed_port, new.mixed_port
+        );
+        log::info!("[ConfigChange] {} - 可热重载", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::HotReload,
+            reason: Some(reason),
+        };
+    }
+
+    // nameserver-policy 变更可以热重载（PUT /configs 会重建 DNS resolver）
+    let old_policy = old.dns.as_ref().map(|d| &d.nameserver_policy);
+    let new_policy = new.dns.as_ref().map(|d| &d.nameserver_policy);
+    if old_policy != new_policy {
+        let reason = "DNS nameserver-policy 变更".to_string();
+        log::info!("[ConfigChange] {} - 可热重载", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::HotReload,
+            reason: Some(reason),
+        };
+    }
+
+    // 出站网卡变更可以热重载（PUT /configs 会更新默认出站网卡和 routing-mark）
+    if old.interface_name != new.interface_name || old.routing_mark != new.routing_mark {
+        let reason = format!(
+            "出站网卡变更 ({:?} -> {:?}, routing-mark {:?} -> {:?})",
+            old.interface_name, new.interface_name, old.routing_mark, new.routing_mark
+        );
+        log::info!("[ConfigChange] {} - 可热重载", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::HotReload,
+            reason: Some(reason),
+        };
+    }
+
+    // keep-alive 变更可以热重载（PUT /configs 会更新全局 TCP keep-alive 参数）
+    if old.keep_alive_interval != new.keep_alive_interval
+        || old.keep_alive_idle != new.keep_alive_idle
+        || old.disable_keep_alive != new.disable_keep_alive
+    {
+        let reason = "TCP keep-alive 配置变更".to_string();
+        log::info!("[ConfigChange] {} - 可热重载", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::HotReload,
+            reason: Some(reason),
+        };
+    }
+
+    // 资源下载参数变更可以热重载（核心在应用配置时更新全局 UA 和 ETag 开关）
+    if old.global_ua != new.global_ua || old.etag_support != new.etag_support {
+        let reason = "资源下载配置变更".to_string();
+        log::info!("[ConfigChange] {} - 可热重载", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::HotReload,
+            reason: Some(reason),
+        };
+    }
+
+    // store-selected 变更可以热重载（核心在应用配置时按该开关恢复缓存中的选择）
+    if old.profile != new.profile {
+        let reason = "profile 缓存配置变更".to_string();
+        log::info!("[ConfigChange] {} - 可热重载", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::HotReload,
+            reason: Some(reason),
+        };
+    }
+
+    // sniffer 变更可以热重载（PUT /configs 会重新加载嗅探配置）
+    let old_sniffer = serde_json::to_value(&old.sniffer).ok();
+    let new_sniffer = serde_json::to_value(&new.sniffer).ok();
+    if old_sniffer != new_sniffer {
+        let reason = "Sniffer 配置变更".to_string();
+        log::info!("[ConfigChange] {} - 可热重载", reason);
+        return ConfigChangeResult {
+            change_type: ConfigChangeType::HotReload,
+            reason: Some(reason),
+        };
+    }
+
     // 其他变更可以热重载
     log::info!("[ConfigChange] 配置变更可以热重载，无需重启核心");
     ConfigChangeResult {
@@ -330,6 +472,23 @@ impl ReloadOptions {
     }
 }
 
+/// 当前存活的配置备份数，大于 0 时说明有配置变更正在进行
+static ACTIVE_BACKUPS: AtomicUsize = AtomicUsize::new(0);
+
+/// 遗留备份（`config.yaml.bak`）的检查结果
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleBackupStatus {
+    /// 没有遗留备份
+    None,
+    /// 当前配置可用，备份已无用
+    Orphaned,
+    /// 当前配置不可用而备份可用，可以恢复
+    Restorable,
+    /// 当前配置与备份都不可用
+    Unusable,
+}
+
 /// 配置备份
 pub struct ConfigBackup {
     config_path: PathBuf,
@@ -353,6 +512,7 @@ impl ConfigBackup {
             log::debug!("Config backup created at: {:?}", backup_path);
         }
 
+        ACTIVE_BACKUPS.fetch_add(1, Ordering::SeqCst);
         Ok(Self {
             config_path,
             backup_path,
@@ -389,6 +549,112 @@ impl ConfigBackup {
 impl Drop for ConfigBackup {
     fn drop(&mut self) {
         self.cleanup();
+        ACTIVE_BACKUPS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 检查异常退出（如配置变更途中被强制结束）遗留的备份
+///
+/// 配置变更正在进行时备份属于正常状态，返回 `None`。
+pub async fn inspect_stale_backup(config_path: &Path) -> StaleBackupStatus {
+    let backup_path = config_path.with_extension("yaml.bak");
+    if ACTIVE_BACKUPS.load(Ordering::SeqCst) > 0 || !backup_path.exists() {
+        return StaleBackupStatus::None;
+    }
+
+    if config_file_valid(config_path).await {
+        StaleBackupStatus::Orphaned
+    } else if config_file_valid(&backup_path).await {
+        StaleBackupStatus::Restorable
+    } else {
+        StaleBackupStatus::Unusable
+    }
+}
+
+async fn config_file_valid(path: &Path) -> bool {
+    match crate::mihomo::test_config_file(path).await {
+        Ok(result) => result.success,
+        Err(e) => {
+            log::warn!("Failed to test config {:?}: {}", path, e);
+            false
+        }
+    }
+}
+
+/// 处理遗留备份：可恢复时用备份覆盖当前配置，无用的备份直接删除
+///
+/// 两者都不可用时保留备份，交给用户处理。返回处理前的检查结果。
+pub async fn recover_stale_backup(state: &AppState) -> Result<StaleBackupStatus> {
+    let config_path = state.config_manager.mihomo_config_path().clone();
+    let backup_path = config_path.with_extension("yaml.bak");
+
+    let status = inspect_stale_backup(&config_path).await;
+    match status {
+        StaleBackupStatus::Restorable => {
+            fs::copy(&backup_path, &config_path)?;
+            fs::remove_file(&backup_path)?;
+            log::info!("Config restored from stale backup");
+        }
+        StaleBackupStatus::Orphaned => {
+            fs::remove_file(&backup_path)?;
+            log::info!("Removed orphaned config backup");
+        }
+        StaleBackupStatus::Unusable => {
+            log::warn!("Both config and stale backup failed validation, keeping backup");
+        }
+        StaleBackupStatus::None => {}
+    }
+    Ok(status)
+}
+
+/// 重载防抖窗口
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 合并短时间内的多次 Profile 重载请求
+///
+/// 每次请求都会递增代数并等待防抖窗口，只有窗口内最后一个请求执行重载，
+/// 因此一连串编辑只会重载一次，且重载的一定是最终状态。
+#[derive(Default)]
+pub struct ReloadDebouncer {
+    generation: AtomicU64,
+    /// 是否有尚未执行的重载
+    pending: AtomicBool,
+    /// 串行执行实际重载
+    reload_lock: Mutex<()>,
+}
+
+impl ReloadDebouncer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 等待防抖窗口
+    ///
+    /// 返回 `Some` 表示调用方应在持有该锁期间执行重载；
+    /// 返回 `None` 表示重载已交给更新的请求（或已在退出时处理）。
+    pub async fn wait(&self) -> Option<MutexGuard<'_, ()>> {
+        self.pending.store(true, Ordering::SeqCst);
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        sleep(RELOAD_DEBOUNCE).await;
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return None;
+        }
+
+        let guard = self.reload_lock.lock().await;
+        // 等待上一次重载期间可能又有新请求
+        if self.generation.load(Ordering::SeqCst) != generation
+            || !self.pending.swap(false, Ordering::SeqCst)
+        {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// 取出尚未执行的重载（应用退出时使用），正在等待的请求随之作废
+    pub fn take_pending(&self) -> bool {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.pending.swap(false, Ordering::SeqCst)
     }
 }
 
@@ -475,6 +741,17 @@ pub async fn reload_config(app: Option<&AppHandle>, options: &ReloadOptions) ->
         match state.mihomo_api.reload_configs(config_path_str, true).await {
             Ok(_) => {
                 log::info!("Config reloaded successfully");
+                // 规则可能已改变，旧的命中计数不再对应当前规则
+                state.rule_hits.reset();
+                // 重载会把运行时模式恢复为配置文件中的模式，临时直连期间重新切回直连
+                if state.temporary_direct.is_active() && !state.temporary_direct.reapply().await {
+                    if let Some(app) = app {
+                        let _ = app.emit(
+                            "temporary-direct-changed",
+                            crate::mihomo::TemporaryDirectStatus::default(),
+                        );
+                    }
+                }
 
                 // 等待配置生效并验证
                 if options.wait_for_healthy {
@@ -750,15 +1027,102 @@ pub fn apply_settings_to_config_with_proxy_state(
         config.mixed_port = Some(0);
     }
     config.allow_lan = settings.allow_lan;
+    // 只有开启局域网共享时才写入监听地址和网段限制
+    if settings.allow_lan {
+        config.bind_address = Some(
+            settings
+                .bind_address
+                .clone()
+                .unwrap_or_else(|| "*".to_string()),
+        );
+        config.lan_allowed_ips = settings.lan_allowed_ips.clone();
+    } else {
+        config.bind_address = None;
+        config.lan_allowed_ips = vec![];
+    }
     config.mode = settings.mode.clone();
+    config.script = settings.script.clone();
     config.ipv6 = settings.ipv6;
     config.tcp_concurrent = settings.tcp_concurrent;
     config.find_process_mode = settings.find_process_mode.clone();
-    config.tun = Some(settings.tun.clone());
-    config.dns = Some(settings.dns.clone());
+    config.interface_name = crate::system::runtime_interface_name(settings.interface_name.as_ref());
+    config.routing_mark = settings.routing_mark;
+    config.keep_alive_interval = settings.keep_alive_interval;
+    config.keep_alive_idle = settings.keep_alive_idle;
+    config.disable_keep_alive = Some(settings.disable_keep_alive).filter(|disabled| *disabled);
+    config.global_ua = settings.global_ua.clone();
+    config.etag_support = settings.etag_support;
+    apply_provider_overrides(settings, config);
+    // macOS TUN 模式下核心以 root 运行，自动下载会写出 root 文件，仍由应用更新
+    let geo_auto_update =
+        settings.geo_auto_update && !(cfg!(target_os = "macos") && settings.tun.enable);
+    config.geo_auto_update = geo_auto_update;
+    config.geo_update_interval = settings.geo_update_interval.filter(|_| geo_auto_update);
+    let mut tun = settings.tun.clone();
+    let mut dns = settings.dns.clone();
+    // 全局关闭 IPv6 时 DNS 和 TUN 也不处理 IPv6，避免只开一半导致 AAAA 记录无法连接
+    if !settings.ipv6 {
+        dns.ipv6 = Some(false);
+        tun.inet6_address.clear();
+    }
+    config.tun = Some(tun);
+    config.dns = Some(dns);
+    // settings.json 中没有 sniffer 时沿用现有配置，避免升级后覆盖用户在 config.yaml 中的选择；
+    // TUN 模式下必须启用 sniffer，否则无法从 fake-ip 流量中提取真实域名
+    let mut sniffer = settings
+        .sniffer
+        .clone()
+        .or_else(|| config.sniffer.take())
+        .unwrap_or_default();
+    if settings.tun.enable {
+        sniffer.enable = true;
+    }
+    config.sniffer = Some(sniffer);
+    config
+        .profile
+        .get_or_insert_with(MihomoProfileConfig::default)
+        .store_selected = settings.store_selected;
     // API 认证配置（应用层管理）
     config.secret = settings.secret.clone();
     config.external_controller = settings.external_controller.clone();
+    match (
+        &settings.external_controller_tls,
+        &settings.tls_certificate,
+        &settings.tls_private_key,
+    ) {
+        (Some(listen), Some(certificate), Some(private_key)) => {
+            config.external_controller_tls = Some(listen.clone());
+            config.tls = Some(crate::models::ControllerTlsConfig {
+                certificate: certificate.clone(),
+                private_key: private_key.clone(),
+            });
+        }
+        _ => {
+            config.external_controller_tls = None;
+            config.tls = None;
+        }
+    }
+    config.external_ui = settings.external_ui.clone();
+    config.external_ui_url = settings.external_ui_url.clone();
+}
+
+/// 将设置中的 Provider 健康检查间隔应用到所有开启了健康检查的 proxy-provider
+///
+/// Provider 来自 Profile，激活 Profile 生成运行时配置后也需要调用。
+pub fn apply_provider_overrides(
+    settings: &crate::models::MihomoSettings,
+    config: &mut MihomoConfig,
+) {
+    let Some(interval) = settings.provider_health_check_interval else {
+        return;
+    };
+    for health_check in config
+        .proxy_providers
+        .values_mut()
+        .filter_map(|provider| provider.health_check.as_mut())
+    {
+        health_check.interval = Some(interval);
+    }
 }
 
 /// 从 MihomoSettings 构建基础配置
@@ -782,11 +1146,19 @@ pub fn build_base_config_from_settings_with_proxy_state(
 }
 
 /// 同步代理状态（发送状态变更事件）
-/// 托盘菜单和前端通过事件监听器更新 UI
+/// 前端通过事件监听器更新 UI，托盘菜单在这里直接同步
 pub async fn sync_proxy_status(app: &AppHandle) {
     if let Ok(status) = get_proxy_status().await {
-        let _ = app.emit("proxy-status-changed", status);
+        publish_proxy_status(app, &status);
+    }
+}
+
+/// 发送 `proxy-status-changed` 并同步托盘菜单勾选状态
+pub fn publish_proxy_status(app: &AppHandle, status: &ProxyStatus) {
+    if let Some(tray) = app.try_state::<TrayMenuState>() {
+        tray.sync_from_status(status);
     }
+    let _ = app.emit("proxy-status-changed", status);
 }
 
 /// 检查 mihomo 是否健康
@@ -809,3 +1181,61 @@ pub async fn check_mihomo_healthy() -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listen_address_change_needs_process_restart() {
+        let old = MihomoConfig {
+            allow_lan: false,
+            ..Default::default()
+        };
+        let new = MihomoConfig {
+            allow_lan: true,
+            bind_address: Some("192.168.1.10".to_string()),
+            ..Default::default()
+        };
+
+        let result = detect_config_change_type_with_mode(&old, &new, &RunMode::Normal);
+        assert_eq!(result.change_type, ConfigChangeType::ProcessRestart);
+        assert_eq!(
+            result.reason.as_deref(),
+            Some("监听地址变更 (127.0.0.1 -> 192.168.1.10)")
+        );
+
+        let result = detect_config_change_type_with_mode(&new, &old, &RunMode::Normal);
+        assert_eq!(result.change_type, ConfigChangeType::ProcessRestart);
+
+        let result = detect_config_change_type_with_mode(&old, &old.clone(), &RunMode::Normal);
+        assert_ne!(result.change_type, ConfigChangeType::ProcessRestart);
+    }
+
+    #[test]
+    fn test_script_change_needs_api_restart() {
+        let old = MihomoConfig {
+            mode: "script".to_string(),
+            ..Default::default()
+        };
+        let new = MihomoConfig {
+            script: Some(crate::models::ScriptConfig {
+                code: Some("def main(ctx, metadata):\n  return \"DIRECT\"".to_string()),
+                ..Default::default()
+            }),
+            ..old.clone()
+        };
+
+        let result = detect_config_change_type_with_mode(&old, &new, &RunMode::Normal);
+        assert_eq!(result.change_type, ConfigChangeType::ApiRestart);
+
+        // 同时修改可热重载的字段时不能跳过重启
+        let new = MihomoConfig {
+            mixed_port: Some(7893),
+            interface_name: Some("en0".to_string()),
+            ..new
+        };
+        let result = detect_config_change_type_with_mode(&old, &new, &RunMode::Normal);
+        assert_eq!(result.change_type, ConfigChangeType::ApiRestart);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::commands::get_app_state_or_err;
+use crate::models::{AppProxyRules, MihomoSettings, NetworkChangePolicy, WindowBehavior};
 use crate::system::NetworkExtensionManager;
 use crate::system::NetworkExtensionStatus;
+use crate::system::PreviousSystemProxy;
 use crate::system::SystemProxy;
 use crate::utils;
 use serde::Serialize;
@@ -19,8 +21,7 @@ pub async fn set_system_proxy(app: AppHandle) -> Result<(), String> {
         .load_app_settings()
         .map_err(|e| e.to_string())?;
 
-    let port = settings.mihomo.port.unwrap_or(7890);
-    let socks_port = settings.mihomo.socks_port.unwrap_or(7891);
+    let (port, socks_port) = system_proxy_ports(&settings.mihomo);
 
     // 先通过 mihomo API 恢复端口监听
     state
@@ -29,11 +30,7 @@ pub async fn set_system_proxy(app: AppHandle) -> Result<(), String> {
         .await
         .map_err(|e| format!("Failed to enable mihomo ports: {}", e))?;
 
-    // 设置 HTTP 代理
-    SystemProxy::set_http_proxy("127.0.0.1", port).map_err(|e| e.to_string())?;
-
-    // 设置 SOCKS 代理
-    SystemProxy::set_socks_proxy("127.0.0.1", socks_port).map_err(|e| e.to_string())?;
+    apply_system_proxy(&settings.mihomo).map_err(|e| e.to_string())?;
 
     // 更新状态（注意：必须在调用 get_proxy_status 之前释放锁，否则会死锁）
     {
@@ -50,6 +47,31 @@ pub async fn set_system_proxy(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// 系统代理使用的 HTTP / SOCKS 端口
+fn system_proxy_ports(settings: &MihomoSettings) -> (u16, u16) {
+    (
+        settings.port.unwrap_or(7890),
+        settings.socks_port.unwrap_or(7891),
+    )
+}
+
+/// 将系统 HTTP / SOCKS 代理指向核心端口
+///
+/// 开启系统代理和网络切换后重新应用共用此逻辑，保证两者设置一致。
+pub(crate) fn apply_system_proxy(settings: &MihomoSettings) -> anyhow::Result<()> {
+    let (port, socks_port) = system_proxy_ports(settings);
+    SystemProxy::set_http_proxy("127.0.0.1", port)?;
+    SystemProxy::set_socks_proxy("127.0.0.1", socks_port)
+}
+
+/// 系统代理是否已开启并指向核心端口
+///
+/// 其他程序设置的系统代理不算作本应用开启。
+pub(crate) fn system_proxy_active(settings: &MihomoSettings) -> anyhow::Result<bool> {
+    let (port, _) = system_proxy_ports(settings);
+    SystemProxy::get_proxy_status("127.0.0.1", port)
+}
+
 /// 清除系统代理
 #[tauri::command]
 pub async fn clear_system_proxy(app: AppHandle) -> Result<(), String> {
@@ -87,6 +109,71 @@ pub async fn get_system_proxy_status() -> Result<bool, String> {
     Ok(enabled)
 }
 
+/// 获取开启系统代理前保存的原始系统代理配置
+///
+/// 关闭系统代理时会恢复为该配置，没有保存时返回 None。
+#[tauri::command]
+pub async fn get_previous_system_proxy() -> Result<Option<PreviousSystemProxy>, String> {
+    Ok(SystemProxy::get_previous_proxy())
+}
+
+/// 以安全模式启动的原因，正常启动时为 None
+#[tauri::command]
+pub async fn get_safe_mode() -> Result<Option<utils::SafeModeReason>, String> {
+    let state = get_app_state_or_err()?;
+    Ok(state.safe_mode)
+}
+
+/// 获取本次启动各阶段的耗时（Sub-Store 在后台启动，完成后才会出现）
+#[tauri::command]
+pub async fn get_startup_timings() -> Result<utils::StartupTimings, String> {
+    Ok(utils::startup_timings())
+}
+
+/// 数据目录信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirInfo {
+    /// 当前使用的数据目录
+    pub data_dir: String,
+    /// 覆盖目录（`CONFLUX_DATA_DIR` 或设置中指定），未覆盖时为空
+    pub override_dir: Option<String>,
+    /// 覆盖来自环境变量（此时设置中的目录不生效）
+    pub from_env: bool,
+}
+
+/// 获取当前数据目录及覆盖情况
+#[tauri::command]
+pub async fn get_data_dir_info() -> Result<DataDirInfo, String> {
+    let data_dir = utils::get_app_data_dir().map_err(|e| e.to_string())?;
+    Ok(DataDirInfo {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        override_dir: utils::get_data_dir_override().map(|d| d.to_string_lossy().to_string()),
+        from_env: std::env::var_os(utils::DATA_DIR_ENV).is_some_and(|v| !v.is_empty()),
+    })
+}
+
+/// 设置独立的数据目录（如测试用的 staging 目录），为 None 时恢复默认目录
+///
+/// 重启应用后生效；环境变量 `CONFLUX_DATA_DIR` 优先于这里的设置。
+#[tauri::command]
+pub async fn set_data_dir_override(path: Option<String>) -> Result<(), String> {
+    let dir = match path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let dir = std::path::PathBuf::from(&path);
+            if !dir.is_absolute() {
+                return Err(format!("请使用绝对路径: {}", path));
+            }
+            std::fs::create_dir_all(&dir).map_err(|e| format!("无法创建目录 {}: {}", path, e))?;
+            Some(dir)
+        }
+        None => None,
+    };
+    utils::set_data_dir_override(dir.as_deref()).map_err(|e| e.to_string())?;
+    log::info!("Data dir override set to {:?} (applies after restart)", dir);
+    Ok(())
+}
+
 /// 获取开机自启动状态
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 #[tauri::command]
@@ -125,6 +212,81 @@ pub async fn set_autostart_enabled(app: AppHandle, enabled: bool) -> Result<(),
     Ok(())
 }
 
+/// 将 settings.json 中的 autoStart 应用到系统（与系统当前状态一致时不做改动）
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub(crate) fn apply_autostart_to_system(app: &AppHandle, enabled: bool) {
+    use tauri_plugin_autostart::ManagerExt;
+
+    let autolaunch = app.autolaunch();
+    if autolaunch.is_enabled().ok() == Some(enabled) {
+        return;
+    }
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+
+    match result {
+        Ok(_) => log::info!("Applied autostart setting from config: {}", enabled),
+        Err(e) => log::warn!("Failed to apply autostart setting: {}", e),
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+pub(crate) fn apply_autostart_to_system(_app: &AppHandle, _enabled: bool) {
+    // 移动平台不支持开机自启动
+}
+
+/// 获取窗口与托盘的启动行为
+#[tauri::command]
+pub async fn get_window_behavior() -> Result<WindowBehavior, String> {
+    let state = get_app_state_or_err()?;
+    let settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    Ok(WindowBehavior::from_settings(&settings))
+}
+
+/// 设置窗口与托盘的启动行为
+///
+/// 开机自启动会同步注册或移除系统启动项，其余选项在下次启动或关闭窗口时生效。
+#[tauri::command]
+pub async fn set_window_behavior(app: AppHandle, behavior: WindowBehavior) -> Result<(), String> {
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    {
+        use tauri_plugin_autostart::ManagerExt;
+
+        let autolaunch = app.autolaunch();
+        if autolaunch.is_enabled().ok() != Some(behavior.auto_start) {
+            if behavior.auto_start {
+                autolaunch.enable().map_err(|e| e.to_string())?;
+            } else {
+                autolaunch.disable().map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    let _ = app;
+
+    let state = get_app_state_or_err()?;
+    let mut settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    settings.auto_start = behavior.auto_start;
+    settings.start_minimized = behavior.start_minimized;
+    settings.close_to_tray = behavior.close_to_tray;
+    state
+        .config_manager
+        .save_app_settings(&settings)
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Window behavior set to: {:?}", behavior);
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
 // macOS Network Extension (placeholder)
 // -----------------------------------------------------------------------------
@@ -141,6 +303,77 @@ pub async fn open_network_extension_settings() -> Result<(), String> {
     NetworkExtensionManager::open_settings().map_err(|e| e.to_string())
 }
 
+/// 设置网络切换（Wi-Fi/有线切换、VPN 连接或断开）后的处理策略
+///
+/// 切换后按策略重新应用系统代理、恢复已断开的绑定网卡、清除 DNS 缓存，
+/// 并发送 `network-changed` 事件。立即生效，无需重启。
+#[tauri::command]
+pub async fn set_network_change_policy(policy: NetworkChangePolicy) -> Result<(), String> {
+    let state = get_app_state_or_err()?;
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.network_change_policy = policy;
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    log::info!(
+        "Network change policy updated: {:?}",
+        app_settings.network_change_policy
+    );
+    Ok(())
+}
+
+/// 获取按应用分流规则
+#[tauri::command]
+pub async fn get_app_proxy_rules() -> Result<AppProxyRules, String> {
+    let state = get_app_state_or_err()?;
+    let app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    Ok(app_settings.app_proxy_rules)
+}
+
+/// 设置按应用分流规则（macOS，`included`/`excluded` 为应用 Bundle ID）
+///
+/// 规则由 Network Extension 按应用路由，返回规范化后的规则。Network Extension
+/// 不可用（平台不支持、未安装或未启用）时返回错误且不保存，避免保存不生效的规则。
+#[tauri::command]
+pub async fn set_app_proxy_rules(
+    included: Vec<String>,
+    excluded: Vec<String>,
+) -> Result<AppProxyRules, String> {
+    let rules = NetworkExtensionManager::normalize_app_rules(&included, &excluded)
+        .map_err(|e| e.to_string())?;
+    let extension = NetworkExtensionManager::status().map_err(|e| e.to_string())?;
+    if !(extension.supported && extension.installed && extension.enabled) {
+        return Err(format!("暂不支持按应用分流：{}", extension.message));
+    }
+    let state = get_app_state_or_err()?;
+
+    let mut app_settings = state
+        .config_manager
+        .load_app_settings()
+        .map_err(|e| e.to_string())?;
+    app_settings.app_proxy_rules = rules.clone();
+    state
+        .config_manager
+        .save_app_settings(&app_settings)
+        .map_err(|e| e.to_string())?;
+
+    log::info!(
+        "App proxy rules updated: {} included, {} excluded",
+        rules.included.len(),
+        rules.excluded.len()
+    );
+    Ok(rules)
+}
+
 // -----------------------------------------------------------------------------
 // Network info (Home cards)
 // -----------------------------------------------------------------------------
@@ -372,6 +605,195 @@ pub async fn get_local_ip_info() -> Result<LocalIpInfo, String> {
     })
 }
 
+/// 列出本机网卡（用于选择 `interface-name`）
+#[tauri::command]
+pub async fn list_network_interfaces() -> Result<Vec<crate::system::NetworkInterface>, String> {
+    crate::system::list_network_interfaces().map_err(|e| e.to_string())
+}
+
+/// 门户检测地址（未被劫持时返回包含 `Success` 的页面，不会重定向）
+const CAPTIVE_PROBE_URL: &str = "http://captive.apple.com/hotspot-detect.html";
+
+/// 绕过门户期间检查网络是否恢复的间隔
+const CAPTIVE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 绕过门户的最长时间，超时后无论是否认证都恢复代理
+const CAPTIVE_BYPASS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// 是否正在绕过门户
+static CAPTIVE_BYPASS_ACTIVE: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+/// 门户检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptiveProbeResult {
+    /// 正常联网
+    Online,
+    /// 被重定向或返回了其他页面（需要网页认证）
+    Portal,
+    /// 请求失败
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptivePortalStatus {
+    /// 不经过代理的检测结果（开启 TUN 时仍会被 TUN 接管）
+    pub direct: CaptiveProbeResult,
+    /// 经过核心代理端口的检测结果，核心未运行或端口未开启时为 None
+    pub proxied: Option<CaptiveProbeResult>,
+    pub portal_detected: bool,
+    /// 门户重定向地址
+    pub portal_url: Option<String>,
+    /// 是否正在绕过门户
+    pub bypass_active: bool,
+}
+
+/// 请求检测地址，返回结果和重定向地址
+async fn probe_captive_portal(proxy_url: Option<&str>) -> (CaptiveProbeResult, Option<String>) {
+    let mut builder = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(std::time::Duration::from_secs(5));
+    builder = match proxy_url.map(reqwest::Proxy::all) {
+        Some(Ok(proxy)) => builder.proxy(proxy),
+        Some(Err(_)) => return (CaptiveProbeResult::Unreachable, None),
+        None => builder.no_proxy(),
+    };
+    let Ok(client) = builder.build() else {
+        return (CaptiveProbeResult::Unreachable, None);
+    };
+
+    let response = match client.get(CAPTIVE_PROBE_URL).send().await {
+        Ok(response) => response,
+        Err(_) => return (CaptiveProbeResult::Unreachable, None),
+    };
+    if response.status().is_redirection() {
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        return (CaptiveProbeResult::Portal, location);
+    }
+    match response.text().await {
+        Ok(body) if body.contains("Success") => (CaptiveProbeResult::Online, None),
+        Ok(_) => (CaptiveProbeResult::Portal, None),
+        Err(_) => (CaptiveProbeResult::Unreachable, None),
+    }
+}
+
+/// 核心当前监听的 HTTP 代理地址（优先混合端口）
+async fn core_proxy_url() -> Option<String> {
+    let state = get_app_state_or_err().ok()?;
+    if !state.mihomo_manager.is_running().await {
+        return None;
+    }
+    let configs = state.mihomo_api.get_configs().await.ok()?;
+    ["mixed-port", "port"]
+        .iter()
+        .filter_map(|key| configs.get(*key).and_then(|v| v.as_u64()))
+        .find(|port| *port > 0)
+        .map(|port| format!("http://127.0.0.1:{}", port))
+}
+
+/// 检测是否处于需要网页认证的网络（酒店、机场 Wi-Fi 等）
+///
+/// 分别直连和经过代理请求检测地址：直连被重定向说明存在门户，
+/// 此时代理通常也无法连通，需要先调用 `bypass_for_captive_portal` 完成认证。
+#[tauri::command]
+pub async fn detect_captive_portal() -> Result<CaptivePortalStatus, String> {
+    let (direct, portal_url) = probe_captive_portal(None).await;
+    let proxied = match core_proxy_url().await {
+        Some(url) => Some(probe_captive_portal(Some(&url)).await.0),
+        None => None,
+    };
+
+    Ok(CaptivePortalStatus {
+        direct,
+        proxied,
+        portal_detected: direct == CaptiveProbeResult::Portal,
+        portal_url,
+        bypass_active: CAPTIVE_BYPASS_ACTIVE.load(std::sync::atomic::Ordering::SeqCst),
+    })
+}
+
+/// 临时关闭系统代理和 TUN，以便在浏览器中完成门户认证
+///
+/// 后台每隔几秒直连检测一次，网络恢复（或超时）后自动恢复之前开启的系统代理和 TUN。
+/// 只改动运行时状态，不写入 settings.json，应用中途退出后下次启动仍按保存的设置开启。
+/// 发送 `captive-portal-bypass` 事件（开始时为 true，恢复后为 false）。
+#[tauri::command]
+pub async fn bypass_for_captive_portal(app: AppHandle) -> Result<(), String> {
+    use std::sync::atomic::Ordering;
+
+    let state = get_app_state_or_err()?;
+    if CAPTIVE_BYPASS_ACTIVE.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let system_proxy = *state.system_proxy_enabled.lock().await;
+    let tun = *state.enhanced_mode.lock().await;
+
+    if system_proxy {
+        if let Err(e) = SystemProxy::clear_proxy() {
+            CAPTIVE_BYPASS_ACTIVE.store(false, Ordering::SeqCst);
+            return Err(e.to_string());
+        }
+        *state.system_proxy_enabled.lock().await = false;
+    }
+    if tun {
+        if let Err(e) = crate::commands::proxy::set_runtime_tun(state, false).await {
+            log::warn!("Failed to disable TUN for captive portal: {}", e);
+        }
+    }
+    crate::commands::reload::sync_proxy_status(&app).await;
+
+    log::info!(
+        "Captive portal bypass started (system proxy: {}, TUN: {})",
+        system_proxy,
+        tun
+    );
+    let _ = app.emit("captive-portal-bypass", true);
+
+    tokio::spawn(async move {
+        let deadline = tokio::time::Instant::now() + CAPTIVE_BYPASS_TIMEOUT;
+        loop {
+            tokio::time::sleep(CAPTIVE_CHECK_INTERVAL).await;
+            if probe_captive_portal(None).await.0 == CaptiveProbeResult::Online {
+                log::info!("Captive portal cleared, restoring proxy");
+                break;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                log::warn!("Captive portal bypass timed out, restoring proxy");
+                break;
+            }
+        }
+
+        if tun {
+            if let Err(e) = crate::commands::proxy::set_runtime_tun(state, true).await {
+                log::warn!("Failed to restore TUN after captive portal: {}", e);
+            }
+        }
+        if system_proxy {
+            let restored = state
+                .config_manager
+                .load_app_settings()
+                .and_then(|settings| apply_system_proxy(&settings.mihomo));
+            match restored {
+                Ok(()) => *state.system_proxy_enabled.lock().await = true,
+                Err(e) => log::warn!("Failed to restore system proxy after captive portal: {}", e),
+            }
+        }
+        crate::commands::reload::sync_proxy_status(&app).await;
+
+        CAPTIVE_BYPASS_ACTIVE.store(false, Ordering::SeqCst);
+        let _ = app.emit("captive-portal-bypass", false);
+    });
+
+    Ok(())
+}
+
 /// 获取终端代理命令（用于复制到剪贴板）
 #[tauri::command]
 pub async fn get_terminal_proxy_command() -> Result<String, String> {
@@ -716,6 +1138,136 @@ pub async fn reset_all_data(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// 将配置、Profile、规则集和 Sub-Store 数据备份为单个 zip
+///
+/// 默认不包含 GEO 数据库和核心二进制，`include_large` 为 true 时一并备份。
+#[tauri::command]
+pub async fn backup_data_dir(
+    target_zip: String,
+    include_large: Option<bool>,
+) -> Result<utils::DataBackupResult, String> {
+    let target = std::path::PathBuf::from(target_zip);
+    let include_large = include_large.unwrap_or(false);
+    tokio::task::spawn_blocking(move || utils::backup_data_dir(&target, include_large))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("备份失败: {}", e))
+}
+
+/// 从 [`backup_data_dir`] 生成的 zip 恢复数据，返回恢复的文件数
+///
+/// 会覆盖当前所有配置，`confirm` 必须为 true。恢复前停止核心和 Sub-Store，
+/// 恢复后按恢复的设置更新 API 客户端、按活跃 Profile 重新生成配置，并重新启动之前在运行的服务。
+#[tauri::command]
+pub async fn restore_data_dir(
+    app: AppHandle,
+    zip_path: String,
+    confirm: bool,
+) -> Result<usize, String> {
+    if !confirm {
+        return Err("恢复会覆盖当前所有配置，请确认后重试".to_string());
+    }
+    let state = get_app_state_or_err()?;
+
+    let mihomo_running = state.mihomo_manager.is_running().await;
+    if mihomo_running {
+        state.auto_switcher.stop();
+        state
+            .mihomo_manager
+            .stop()
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let mut substore = state.substore_manager.lock().await;
+    let substore_running = substore.is_running().await;
+    if substore_running {
+        substore.stop().await.map_err(|e| e.to_string())?;
+    }
+
+    let path = std::path::PathBuf::from(zip_path);
+    let restored = tokio::task::spawn_blocking(move || utils::restore_data_dir(&path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("恢复失败: {}", e));
+
+    // 无论恢复是否成功都恢复之前的运行状态
+    if substore_running {
+        if let Err(e) = substore.start(app.clone()).await {
+            log::warn!("Failed to restart Sub-Store after restore: {}", e);
+        }
+    }
+    drop(substore);
+
+    if restored.is_ok() {
+        if let Err(e) = reinit_api_clients(state).await {
+            log::warn!("Failed to re-init API clients after restore: {}", e);
+        }
+        let system_proxy_enabled = *state.system_proxy_enabled.lock().await;
+        if let Err(e) =
+            crate::commands::profile::write_active_runtime_config(state, system_proxy_enabled)
+        {
+            log::warn!("Failed to regenerate config after restore: {}", e);
+        }
+    }
+    if mihomo_running {
+        state
+            .mihomo_manager
+            .start()
+            .await
+            .map_err(|e| e.to_string())?;
+        crate::commands::proxy::resume_auto_switch(&app).await;
+    }
+    if let Ok(status) = crate::commands::proxy::get_proxy_status().await {
+        let _ = app.emit("proxy-status-changed", status);
+    }
+
+    restored
+}
+
+/// 按恢复后的 settings.json 更新 API 客户端的地址、密钥和核心优先级
+///
+/// 备份中没有密钥时沿用当前密钥并写回 settings.json。
+async fn reinit_api_clients(state: &crate::commands::AppState) -> anyhow::Result<()> {
+    let mut settings = state.config_manager.load_app_settings()?;
+    if settings.mihomo.secret.is_empty() {
+        settings.mihomo.secret = state.api_secret();
+        state.config_manager.save_app_settings(&settings)?;
+    }
+
+    let secret = settings.mihomo.secret.clone();
+    if let Ok(mut current) = state.api_secret.write() {
+        *current = secret.clone();
+    }
+    state.mihomo_api.set_secret(secret.clone());
+    state.mihomo_manager.set_secret(secret.clone());
+    state.log_streamer.set_secret(secret).await;
+
+    let (api_url, ca_cert) = crate::mihomo::controller_endpoint(&settings.mihomo);
+    state
+        .mihomo_api
+        .set_endpoint(api_url.clone(), ca_cert.as_deref())?;
+    state.log_streamer.set_endpoint(api_url, ca_cert).await;
+
+    state.mihomo_manager.set_priority(settings.core_priority);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Crash reports
+// -----------------------------------------------------------------------------
+
+/// 列出崩溃报告（最新的在前）
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<utils::CrashReportSummary>, String> {
+    utils::list_crash_reports().map_err(|e| e.to_string())
+}
+
+/// 读取崩溃报告内容
+#[tauri::command]
+pub async fn open_crash_report(id: String) -> Result<utils::CrashReport, String> {
+    utils::read_crash_report(&id).map_err(|e| e.to_string())
+}
+
 /// 让 Rust Analyzer / IDE 能追踪到通过 `tauri::generate_handler!` 注册的命令引用，
 /// 避免出现误报的 dead_code 警告（命令实际会在运行时被 Tauri 调用）。
 pub fn link_tauri_commands_for_ide() {
@@ -1,31 +1,19 @@
-use tauri::{AppHandle, State};
-
-use crate::commands::AppState;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::commands::proxy::apply_connection_limit;
+use crate::commands::system::apply_autostart_to_system;
+use crate::commands::{AppState, CommandError};
 use crate::models::WebDavConfig;
-use crate::webdav::{ConflictInfo, SyncManager, SyncResult, SyncState, WebDavClient};
-
-/// 将 settings.json 中的 autoStart 应用到系统
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
-fn apply_autostart_to_system(app: &AppHandle, enabled: bool) {
-    use tauri_plugin_autostart::ManagerExt;
-
-    let autolaunch = app.autolaunch();
-    let result = if enabled {
-        autolaunch.enable()
-    } else {
-        autolaunch.disable()
-    };
-
-    match result {
-        Ok(_) => log::info!("Applied autostart setting from config: {}", enabled),
-        Err(e) => log::warn!("Failed to apply autostart setting: {}", e),
-    }
-}
+use crate::webdav::{
+    ConflictInfo, SyncManager, SyncPreview, SyncResult, SyncState, UploadProgress, WebDavClient,
+    WebDavEndpoint,
+};
 
-#[cfg(any(target_os = "android", target_os = "ios"))]
-fn apply_autostart_to_system(_app: &AppHandle, _enabled: bool) {
-    // 移动平台不支持开机自启动
-}
+/// 强制上传的取消标记
+static UPLOAD_CANCEL: Lazy<Arc<AtomicBool>> = Lazy::new(|| Arc::new(AtomicBool::new(false)));
 
 /// 测试 WebDAV 连接
 #[tauri::command]
@@ -36,6 +24,17 @@ pub async fn test_webdav_connection(config: WebDavConfig) -> Result<bool, String
     client.test_connection().await.map_err(|e| e.to_string())
 }
 
+/// 探测可用的 WebDAV 地址
+///
+/// 依次尝试用户输入和常见路径（`/dav`、Nextcloud 的 `/remote.php/dav/files/<用户名>` 等），
+/// 返回可用的地址和识别到的服务商，用于连接测试失败时提示修正。
+#[tauri::command]
+pub async fn normalize_webdav_url(config: WebDavConfig) -> Result<WebDavEndpoint, String> {
+    crate::webdav::normalize_webdav_url(&config.url, &config.username, &config.password)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 获取 WebDAV 配置
 #[tauri::command]
 pub async fn get_webdav_config(state: State<'_, AppState>) -> Result<WebDavConfig, String> {
@@ -66,20 +65,45 @@ pub async fn save_webdav_config(
         .map_err(|e| e.to_string())
 }
 
-/// 上传配置到 WebDAV
+/// 上传配置到 WebDAV（全量覆盖远端快照）
+///
+/// 需要显式传入 `confirm = true`，防止误触覆盖唯一的远端备份。
+/// 进度通过 `webdav-upload-progress` 事件推送，可用 `cancel_webdav_upload` 取消。
 #[tauri::command]
-pub async fn webdav_upload(state: State<'_, AppState>) -> Result<SyncResult, String> {
-    let settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+pub async fn webdav_upload(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    confirm: bool,
+) -> Result<SyncResult, CommandError> {
+    if !confirm {
+        return Err(CommandError::validation(
+            "强制上传会覆盖远端快照，请确认后再执行",
+        ));
+    }
+
+    let settings = state.config_manager.load_app_settings()?;
 
     if !settings.webdav.enabled {
-        return Err("WebDAV 同步未启用".to_string());
+        return Err(CommandError::validation("WebDAV 同步未启用"));
     }
 
-    let sync_manager = SyncManager::new(settings.webdav);
-    sync_manager.upload_all().await.map_err(|e| e.to_string())
+    UPLOAD_CANCEL.store(false, Ordering::SeqCst);
+    let sync_manager = SyncManager::new(settings.webdav)
+        .with_progress(move |progress: &UploadProgress| {
+            let _ = app.emit("webdav-upload-progress", progress);
+        })
+        .with_cancel_flag(UPLOAD_CANCEL.clone());
+    sync_manager
+        .upload_all()
+        .await
+        .map_err(CommandError::network)
+}
+
+/// 取消正在进行的强制上传（快照替换远端之后不再生效）
+#[tauri::command]
+pub async fn cancel_webdav_upload() -> Result<(), String> {
+    UPLOAD_CANCEL.store(true, Ordering::SeqCst);
+    Ok(())
 }
 
 /// 从 WebDAV 下载配置
@@ -91,32 +115,27 @@ pub async fn webdav_download(
     app: AppHandle,
     state: State<'_, AppState>,
     force: bool,
-) -> Result<SyncResult, String> {
-    let settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+) -> Result<SyncResult, CommandError> {
+    let settings = state.config_manager.load_app_settings()?;
 
     if !settings.webdav.enabled {
-        return Err("WebDAV 同步未启用".to_string());
+        return Err(CommandError::validation("WebDAV 同步未启用"));
     }
 
     let sync_manager = SyncManager::new(settings.webdav);
     let result = sync_manager
         .download_all(force)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(CommandError::network)?;
 
     // 下载成功后，只应用系统级设置
     if result.success {
         // 重新加载 settings.json
-        let new_settings = state
-            .config_manager
-            .load_app_settings()
-            .map_err(|e| e.to_string())?;
+        let new_settings = state.config_manager.load_app_settings()?;
 
         // 应用开机自启动到系统
         apply_autostart_to_system(&app, new_settings.auto_start);
+        apply_connection_limit(&app, &state, &new_settings.connection_limit);
 
         // 注意：不自动重载 MiHomo
         // 用户需要手动激活 profile 才会从 settings.json + profile 生成完整运行时配置
@@ -142,11 +161,8 @@ pub async fn clear_sync_status() -> Result<(), String> {
 #[tauri::command]
 pub async fn check_webdav_conflict(
     state: State<'_, AppState>,
-) -> Result<Option<ConflictInfo>, String> {
-    let settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+) -> Result<Option<ConflictInfo>, CommandError> {
+    let settings = state.config_manager.load_app_settings()?;
 
     if !settings.webdav.enabled {
         return Ok(None);
@@ -156,7 +172,7 @@ pub async fn check_webdav_conflict(
     sync_manager
         .check_conflict()
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::network)
 }
 
 /// 增量同步
@@ -164,54 +180,64 @@ pub async fn check_webdav_conflict(
 /// 自动检测本地和远端的变化，执行双向增量同步。
 /// 只同步有变化的文件，提高效率。
 #[tauri::command]
-pub async fn webdav_sync(app: AppHandle, state: State<'_, AppState>) -> Result<SyncResult, String> {
-    let settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+pub async fn webdav_sync(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SyncResult, CommandError> {
+    let settings = state.config_manager.load_app_settings()?;
 
     if !settings.webdav.enabled {
-        return Err("WebDAV 同步未启用".to_string());
+        return Err(CommandError::validation("WebDAV 同步未启用"));
     }
 
     let sync_manager = SyncManager::new(settings.webdav);
-    let result = sync_manager.sync().await.map_err(|e| e.to_string())?;
+    let result = sync_manager.sync().await.map_err(CommandError::network)?;
 
     // 同步成功后，应用系统级设置
     if result.success && !result.downloaded_files.is_empty() {
-        let new_settings = state
-            .config_manager
-            .load_app_settings()
-            .map_err(|e| e.to_string())?;
+        let new_settings = state.config_manager.load_app_settings()?;
 
         apply_autostart_to_system(&app, new_settings.auto_start);
+        apply_connection_limit(&app, &state, &new_settings.connection_limit);
         log::info!("WebDAV sync completed. User needs to activate a profile to apply changes.");
     }
 
     Ok(result)
 }
 
+/// 预览增量同步（只比较不传输）
+///
+/// 返回同步方向以及将上传、下载、删除的文件，供用户在同步前确认。
+#[tauri::command]
+pub async fn webdav_sync_preview(state: State<'_, AppState>) -> Result<SyncPreview, CommandError> {
+    let settings = state.config_manager.load_app_settings()?;
+
+    if !settings.webdav.enabled {
+        return Err(CommandError::validation("WebDAV 同步未启用"));
+    }
+
+    let sync_manager = SyncManager::new(settings.webdav);
+    sync_manager.preview().await.map_err(CommandError::network)
+}
+
 /// 解决单个文件的冲突
 #[tauri::command]
 pub async fn resolve_file_conflict(
     state: State<'_, AppState>,
     path: String,
     choice: String,
-) -> Result<(), String> {
-    let settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+) -> Result<(), CommandError> {
+    let settings = state.config_manager.load_app_settings()?;
 
     if !settings.webdav.enabled {
-        return Err("WebDAV 同步未启用".to_string());
+        return Err(CommandError::validation("WebDAV 同步未启用"));
     }
 
     let sync_manager = SyncManager::new(settings.webdav);
     sync_manager
         .resolve_file_conflict(&path, &choice)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::network)
 }
 
 /// 解决所有冲突
@@ -221,14 +247,11 @@ pub async fn resolve_webdav_conflict(
     app: AppHandle,
     state: State<'_, AppState>,
     choice: String,
-) -> Result<SyncResult, String> {
-    let settings = state
-        .config_manager
-        .load_app_settings()
-        .map_err(|e| e.to_string())?;
+) -> Result<SyncResult, CommandError> {
+    let settings = state.config_manager.load_app_settings()?;
 
     if !settings.webdav.enabled {
-        return Err("WebDAV 同步未启用".to_string());
+        return Err(CommandError::validation("WebDAV 同步未启用"));
     }
 
     let sync_manager = SyncManager::new(settings.webdav);
@@ -236,16 +259,14 @@ pub async fn resolve_webdav_conflict(
     let result = sync_manager
         .resolve_all_conflicts(&choice)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(CommandError::network)?;
 
     // 同步成功后，应用系统级设置
     if result.success && !result.downloaded_files.is_empty() {
-        let new_settings = state
-            .config_manager
-            .load_app_settings()
-            .map_err(|e| e.to_string())?;
+        let new_settings = state.config_manager.load_app_settings()?;
 
         apply_autostart_to_system(&app, new_settings.auto_start);
+        apply_connection_limit(&app, &state, &new_settings.connection_limit);
         log::info!("Conflict resolved. User needs to activate a profile to apply changes.");
     }
 
@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 连续失败多少次后熔断
+const FAILURE_THRESHOLD: u32 = 3;
+
+/// 熔断后的冷却时间，期间对该主机的请求直接失败
+const COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// 探测请求的最长等待时间（大于请求超时），超过后允许重新探测
+const PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy)]
+enum BreakerState {
+    /// 正常请求，记录连续失败次数
+    Closed { failures: u32 },
+    /// 冷却中
+    Open { until: Instant },
+    /// 冷却结束，放行一次探测请求
+    HalfOpen { since: Instant },
+}
+
+/// 按主机记录的熔断状态
+static BREAKERS: Lazy<Mutex<HashMap<String, BreakerState>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 订阅拉取熔断器
+///
+/// 机场服务器故障时，频繁刷新会让用户 IP 被限流。同一主机连续失败
+/// `FAILURE_THRESHOLD` 次后进入冷却，冷却期间的请求直接失败；冷却结束后
+/// 放行一次探测请求，成功则恢复，失败则重新冷却。
+pub struct FetchBreaker;
+
+impl FetchBreaker {
+    /// 请求前检查，冷却中返回错误
+    pub fn check(host: &str) -> Result<()> {
+        let Ok(mut breakers) = BREAKERS.lock() else {
+            return Ok(());
+        };
+        match breakers.get(host).copied() {
+            Some(BreakerState::Open { until }) => {
+                let now = Instant::now();
+                if now < until {
+                    let secs = until.duration_since(now).as_secs().max(1);
+                    return Err(anyhow!(
+                        "订阅服务器 {} 连续请求失败，冷却中（约 {} 秒后重试）",
+                        host,
+                        secs
+                    ));
+                }
+                log::info!("Subscription host {} cooled down, probing", host);
+                breakers.insert(host.to_string(), BreakerState::HalfOpen { since: now });
+                Ok(())
+            }
+            Some(BreakerState::HalfOpen { since }) if since.elapsed() < PROBE_TIMEOUT => {
+                Err(anyhow!("订阅服务器 {} 正在恢复检测中，请稍后重试", host))
+            }
+            Some(BreakerState::HalfOpen { .. }) => {
+                // 上一次探测没有结果（请求被取消），重新放行
+                breakers.insert(
+                    host.to_string(),
+                    BreakerState::HalfOpen {
+                        since: Instant::now(),
+                    },
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 请求成功，重置状态
+    pub fn record_success(host: &str) {
+        if let Ok(mut breakers) = BREAKERS.lock() {
+            breakers.remove(host);
+        }
+    }
+
+    /// 请求失败，达到阈值或探测失败时进入冷却
+    pub fn record_failure(host: &str) {
+        let Ok(mut breakers) = BREAKERS.lock() else {
+            return;
+        };
+        let failures = match breakers.get(host) {
+            Some(BreakerState::Closed { failures }) => failures + 1,
+            Some(BreakerState::HalfOpen { .. }) => FAILURE_THRESHOLD,
+            Some(BreakerState::Open { .. }) => return,
+            None => 1,
+        };
+
+        let state = if failures >= FAILURE_THRESHOLD {
+            log::warn!(
+                "Subscription host {} failed {} times, cooling down for {:?}",
+                host,
+                failures,
+                COOLDOWN
+            );
+            BreakerState::Open {
+                until: Instant::now() + COOLDOWN,
+            }
+        } else {
+            BreakerState::Closed { failures }
+        };
+        breakers.insert(host.to_string(), state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let host = "breaker-test.example.com";
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            FetchBreaker::record_failure(host);
+            assert!(FetchBreaker::check(host).is_ok());
+        }
+        FetchBreaker::record_failure(host);
+        assert!(FetchBreaker::check(host).is_err());
+
+        FetchBreaker::record_success(host);
+        assert!(FetchBreaker::check(host).is_ok());
+    }
+}
@@ -1,23 +1,123 @@
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
+use super::{FetchBreaker, RuleMatcher};
 use crate::models::{
-    HealthCheck, ProfileConfig, ProxyConfig, ProxyGroupConfig, ProxyProvider, RuleProvider,
+    ConfigDiffItem, GroupCycle, HealthCheck, NodeRename, ProfileConfig, ProfileDiff, ProxyConfig,
+    ProxyGroupConfig, ProxyProvider, RegionGroup, RegionGroupsPlan, RuleConflict, RuleDedupReport,
+    RuleProvider, ShadowedRule, SubscriptionUsage, TemplateMergeMode, ValidationIssue,
 };
 
 /// 配置编排器
 /// 负责解析、验证和提取配置内容
 pub struct Composer;
 
+/// 远程地址的响应内容
+struct FetchedText {
+    content: String,
+    usage: Option<SubscriptionUsage>,
+    /// 跟随重定向后的最终地址
+    final_url: String,
+    status: reqwest::StatusCode,
+    content_type: Option<String>,
+}
+
+/// 托管配置头（ClashX / Surge 的 `#!MANAGED-CONFIG <url> interval=<秒>`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedConfigHeader {
+    pub url: String,
+    /// 更新间隔（秒）
+    pub interval: Option<u64>,
+}
+
 impl Composer {
-    /// 从 YAML 内容解析配置
+    /// 核心内置的策略（可直接作为规则目标）
+    pub const BUILTIN_TARGETS: &'static [&'static str] =
+        &["DIRECT", "REJECT", "REJECT-DROP", "PASS", "COMPATIBLE"];
+
+    /// 从 YAML 内容解析配置（自动去除 BOM 并将 CRLF 转为 LF）
     pub fn parse_yaml(content: &str) -> Result<ProfileConfig> {
+        let content = Self::normalize_line_endings(content);
         let raw: serde_yaml::Value =
-            serde_yaml::from_str(content).map_err(|e| anyhow!("Failed to parse YAML: {}", e))?;
+            serde_yaml::from_str(&content).map_err(|e| anyhow!("Failed to parse YAML: {}", e))?;
         Self::extract_config(&raw)
     }
 
+    /// 读取本地 YAML 文件，兼容 UTF-8 BOM 和 UTF-16（Windows 记事本另存的文件）
+    pub fn read_yaml_file(path: impl AsRef<std::path::Path>) -> Result<String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow!("Failed to read file '{}': {}", path.display(), e))?;
+        Self::decode_text(&bytes)
+            .map_err(|e| anyhow!("Failed to decode file '{}': {}", path.display(), e))
+    }
+
+    /// 按 BOM 识别编码并转为 UTF-8，没有 BOM 时按 UTF-8 处理
+    pub fn decode_text(bytes: &[u8]) -> Result<String> {
+        fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<String> {
+            if bytes.len() % 2 != 0 {
+                return Err(anyhow!("UTF-16 内容长度无效"));
+            }
+            let units = bytes
+                .chunks_exact(2)
+                .map(|pair| from_bytes([pair[0], pair[1]]));
+            char::decode_utf16(units)
+                .collect::<std::result::Result<String, _>>()
+                .map_err(|e| anyhow!("UTF-16 内容无效: {}", e))
+        }
+
+        if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+            return String::from_utf8(rest.to_vec()).map_err(|e| anyhow!("UTF-8 内容无效: {}", e));
+        }
+        if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+            return decode_utf16(rest, u16::from_le_bytes);
+        }
+        if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+            return decode_utf16(rest, u16::from_be_bytes);
+        }
+        String::from_utf8(bytes.to_vec()).map_err(|_| anyhow!("文件不是 UTF-8 编码"))
+    }
+
+    /// 去除 BOM 并统一换行符
+    fn normalize_line_endings(content: &str) -> std::borrow::Cow<'_, str> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        if content.contains('\r') {
+            std::borrow::Cow::Owned(content.replace("\r\n", "\n").replace('\r', "\n"))
+        } else {
+            std::borrow::Cow::Borrowed(content)
+        }
+    }
+
+    /// 解析文件开头注释中的托管配置头，没有时返回 None
+    pub fn managed_config_header(content: &str) -> Option<ManagedConfigHeader> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let header = content
+            .lines()
+            .map(str::trim)
+            .take_while(|line| line.is_empty() || line.starts_with('#'))
+            .filter_map(|line| line.strip_prefix("#!"))
+            .find(|line| {
+                line.trim_start()
+                    .to_ascii_lowercase()
+                    .starts_with("managed-config")
+            })?;
+
+        let mut parts = header.split_whitespace().skip(1);
+        let url = parts
+            .next()
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))?;
+        let interval = parts
+            .filter_map(|part| part.split_once('='))
+            .find(|(key, _)| key.eq_ignore_ascii_case("interval"))
+            .and_then(|(_, value)| value.parse().ok());
+
+        Some(ManagedConfigHeader {
+            url: url.to_string(),
+            interval,
+        })
+    }
+
     /// 从远程 URL 获取并解析配置
     pub async fn fetch_and_parse(url: &str) -> Result<ProfileConfig> {
         let (config, _) = Self::fetch_and_parse_with_flags(url).await?;
@@ -26,10 +126,88 @@ impl Composer {
 
     /// 从远程 URL 获取并解析配置，并返回是否自动生成默认规则
     pub async fn fetch_and_parse_with_flags(url: &str) -> Result<(ProfileConfig, bool)> {
+        let (config, default_rules_applied, _) = Self::fetch_and_parse_with_usage(url).await?;
+        Ok((config, default_rules_applied))
+    }
+
+    /// 从远程 URL 获取并解析配置，同时返回是否自动生成默认规则和订阅用量
+    pub async fn fetch_and_parse_with_usage(
+        url: &str,
+    ) -> Result<(ProfileConfig, bool, Option<SubscriptionUsage>)> {
+        let fetched = Self::fetch_remote(url).await?;
+
+        // 过期的订阅常重定向到 HTML 登录页，直接解析只会得到难以理解的 YAML 错误；
+        // 部分服务器会以 text/html 返回正常配置，因此 Content-Type 只在解析失败时作为依据
+        let login_error = || {
+            anyhow!(
+                "订阅需要登录或已过期：服务器返回的是网页而不是配置（HTTP {}，最终地址 {}）",
+                fetched.status.as_u16(),
+                fetched.final_url
+            )
+        };
+        if Self::looks_like_html(&fetched.content) {
+            return Err(login_error());
+        }
+        let is_html_response = fetched
+            .content_type
+            .as_deref()
+            .is_some_and(|t| t.to_ascii_lowercase().contains("text/html"));
+
+        let mut config = match Self::parse_yaml(&fetched.content) {
+            Ok(config) => config,
+            Err(_) if is_html_response => return Err(login_error()),
+            Err(e) => return Err(e),
+        };
+        let usage = fetched.usage;
+        let mut default_rules_applied = false;
+        if Self::should_apply_nodes_only_template(&config) {
+            log::info!("Remote subscription only contains proxies, applying template config");
+            config = Self::build_nodes_only_template(config.proxies);
+            default_rules_applied = true;
+        }
+        Ok((config, default_rules_applied, usage))
+    }
+
+    /// 获取远程 URL 的文本内容
+    pub async fn fetch_text(url: &str) -> Result<String> {
+        let (content, _) = Self::fetch_text_with_usage(url).await?;
+        Ok(content)
+    }
+
+    /// 获取远程 URL 的文本内容，并解析 `subscription-userinfo` 响应头
+    ///
+    /// 远程主机连续失败后会熔断一段时间（见 `FetchBreaker`），本地地址不受影响。
+    pub async fn fetch_text_with_usage(url: &str) -> Result<(String, Option<SubscriptionUsage>)> {
+        let fetched = Self::fetch_remote(url).await?;
+        Ok((fetched.content, fetched.usage))
+    }
+
+    /// 判断响应内容是否为 HTML 网页（登录页、错误页等），而不是配置或规则内容
+    fn looks_like_html(content: &str) -> bool {
+        let head: String = content
+            .trim_start_matches('\u{feff}')
+            .trim_start()
+            .chars()
+            .take(64)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        head.starts_with("<!doctype html") || head.starts_with("<html") || head.starts_with("<head")
+    }
+
+    async fn fetch_remote(url: &str) -> Result<FetchedText> {
+        let is_local = url.starts_with("http://127.0.0.1") || url.starts_with("http://localhost");
+        let breaker_host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .filter(|_| !is_local);
+        if let Some(host) = &breaker_host {
+            FetchBreaker::check(host)?;
+        }
+
         let mut builder = reqwest::Client::builder().timeout(Duration::from_secs(30));
 
         // 如果是本地地址，禁用代理，避免受系统代理影响导致连接失败
-        if url.starts_with("http://127.0.0.1") || url.starts_with("http://localhost") {
+        if is_local {
             log::debug!("Fetching local URL, disabling proxy: {}", url);
             builder = builder.no_proxy();
         }
@@ -40,26 +218,121 @@ impl Composer {
             .get(url)
             .header("User-Agent", "Conflux/0.1.0")
             .send()
-            .await
-            .map_err(|e| anyhow!("Failed to fetch URL: {}", e))?;
+            .await;
 
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to fetch: HTTP {}", response.status()));
+        // 只有连接失败、限流和服务端错误计入熔断，其他状态说明服务器可用
+        let server_failed = match &response {
+            Ok(response) => {
+                let status = response.status();
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(_) => true,
+        };
+        if let Some(host) = &breaker_host {
+            if server_failed {
+                FetchBreaker::record_failure(host);
+            } else {
+                FetchBreaker::record_success(host);
+            }
         }
 
+        let response = response.map_err(|e| anyhow!("Failed to fetch URL: {}", e))?;
+        let status = response.status();
+        let final_url = response.url().to_string();
+        if !status.is_success() {
+            if final_url != url {
+                return Err(anyhow!(
+                    "Failed to fetch: HTTP {} (redirected to {})",
+                    status,
+                    final_url
+                ));
+            }
+            return Err(anyhow!("Failed to fetch: HTTP {}", status));
+        }
+
+        let usage = response
+            .headers()
+            .get("subscription-userinfo")
+            .and_then(|value| value.to_str().ok())
+            .and_then(SubscriptionUsage::parse_header);
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
         let content = response
             .text()
             .await
             .map_err(|e| anyhow!("Failed to read response: {}", e))?;
+        Ok(FetchedText {
+            content,
+            usage,
+            final_url,
+            status,
+            content_type,
+        })
+    }
 
-        let mut config = Self::parse_yaml(&content)?;
-        let mut default_rules_applied = false;
-        if Self::should_apply_nodes_only_template(&config) {
-            log::info!("Remote subscription only contains proxies, applying template config");
-            config = Self::build_nodes_only_template(config.proxies);
-            default_rules_applied = true;
+    /// 解析纯文本规则列表（每行一条规则），返回 (规则, 跳过的行数)
+    ///
+    /// 忽略空行、注释（`#`、`//`、`;`）和 YAML `payload:` 头，兼容 `- RULE` 格式。
+    /// 指定 `target_override` 时将每条规则的目标改写为该策略组，
+    /// 也可为不带目标的规则（如 `DOMAIN-SUFFIX,example.com`）补上目标；
+    /// 未指定时不带目标的规则会被跳过。`MATCH` 规则总是跳过。
+    pub fn parse_rule_list(content: &str, target_override: Option<&str>) -> (Vec<String>, usize) {
+        let mut rules = Vec::new();
+        let mut skipped = 0;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with("//")
+                || line.starts_with(';')
+                || line == "payload:"
+            {
+                continue;
+            }
+
+            let line = line
+                .strip_prefix('-')
+                .map(str::trim)
+                .unwrap_or(line)
+                .trim_matches(|c| c == '\'' || c == '"');
+            let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+
+            // `no-resolve` 等参数位于目标之后
+            let option_count = parts
+                .iter()
+                .rev()
+                .take_while(|part| matches!(**part, "no-resolve" | "src"))
+                .count();
+            let (body, options) = parts.split_at(parts.len() - option_count);
+
+            if body.len() < 2
+                || body.iter().any(|part| part.is_empty())
+                || body[0].eq_ignore_ascii_case("MATCH")
+            {
+                skipped += 1;
+                continue;
+            }
+
+            let mut rule_parts = body.to_vec();
+            match (target_override, rule_parts.len()) {
+                (Some(target), 2) => rule_parts.push(target),
+                (Some(target), len) => rule_parts[len - 1] = target,
+                (None, 2) => {
+                    skipped += 1;
+                    continue;
+                }
+                (None, _) => {}
+            }
+            rule_parts.extend_from_slice(options);
+            rules.push(rule_parts.join(","));
         }
-        Ok((config, default_rules_applied))
+
+        (rules, skipped)
     }
 
     /// 从原始配置提取应用支持的内容
@@ -102,6 +375,17 @@ impl Composer {
             config.rules = Self::parse_rules(rules)?;
         }
 
+        // 提取 sub-rules
+        if let Some(sub_rules) = raw.get("sub-rules").and_then(|v| v.as_mapping()) {
+            for (name, rules) in sub_rules {
+                if let Some(name) = name.as_str() {
+                    config
+                        .sub_rules
+                        .insert(name.to_string(), Self::parse_rules(rules)?);
+                }
+            }
+        }
+
         log::debug!(
             "Extracted config: {} proxies, {} groups, {} rules",
             config.proxies.len(),
@@ -380,21 +664,917 @@ impl Composer {
         }
     }
 
-    /// 过滤掉引用无效 provider 的规则
+    /// 过滤掉引用无效 provider 的规则（含子规则）
     pub fn filter_invalid_rules(config: &mut ProfileConfig) {
-        let valid_providers: std::collections::HashSet<&String> =
-            config.rule_providers.keys().collect();
-
-        config.rules.retain(|rule| {
-            if rule.starts_with("RULE-SET,") {
-                let parts: Vec<&str> = rule.split(',').collect();
-                if parts.len() >= 2 {
-                    let provider_name = parts[1];
-                    return valid_providers.contains(&provider_name.to_string());
+        let valid_providers: HashSet<String> = config.rule_providers.keys().cloned().collect();
+        Self::retain_rule_sets(config, |provider| valid_providers.contains(provider));
+    }
+
+    /// 删除规则和子规则中 `keep` 返回 false 的规则源对应的 RULE-SET 规则
+    pub fn retain_rule_sets(config: &mut ProfileConfig, keep: impl Fn(&str) -> bool) {
+        let keep_rule = |rule: &String| {
+            let mut parts = rule.split(',').map(str::trim);
+            match (parts.next(), parts.next()) {
+                (Some(rule_type), Some(provider)) if rule_type.eq_ignore_ascii_case("RULE-SET") => {
+                    keep(provider)
+                }
+                _ => true,
+            }
+        };
+        config.rules.retain(|rule| keep_rule(rule));
+        for rules in config.sub_rules.values_mut() {
+            rules.retain(|rule| keep_rule(rule));
+        }
+    }
+
+    /// 检查配置中的引用关系，返回警告列表（不修改配置）
+    ///
+    /// 这些问题不会导致解析失败，但可能让核心启动报错或规则不生效。
+    pub fn validate(config: &ProfileConfig) -> Vec<String> {
+        Self::validation_issues(config)
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    /// 检查节点、策略组和规则的引用关系，返回带字段路径的问题列表
+    ///
+    /// 核心会拒绝加载的问题（节点重名、引用不存在的代理源或规则源）为错误，其余为警告。
+    /// `validate` 和 `ConfigManager::collect_validation_issues` 共用这些检查。
+    pub fn validation_issues(config: &ProfileConfig) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        if config.proxies.is_empty() && config.proxy_providers.is_empty() {
+            issues.push(ValidationIssue::warning(
+                "proxies",
+                "配置中没有代理节点或代理源",
+            ));
+        }
+
+        let mut proxy_names = HashSet::new();
+        for (index, proxy) in config.proxies.iter().enumerate() {
+            if !proxy_names.insert(proxy.name.as_str()) {
+                issues.push(ValidationIssue::error(
+                    format!("proxies[{}].name", index),
+                    format!("代理节点名称重复：{}", proxy.name),
+                ));
+            }
+        }
+
+        let mut group_names = HashSet::new();
+        for (index, group) in config.proxy_groups.iter().enumerate() {
+            let field = format!("proxy-groups[{}].name", index);
+            if !group_names.insert(group.name.as_str()) {
+                issues.push(ValidationIssue::warning(
+                    &field,
+                    format!("策略组名称重复：{}", group.name),
+                ));
+            }
+            if proxy_names.contains(group.name.as_str()) {
+                issues.push(ValidationIssue::warning(
+                    field,
+                    format!("策略组与代理节点同名：{}", group.name),
+                ));
+            }
+        }
+
+        let is_known_target = |name: &str| {
+            Self::BUILTIN_TARGETS.contains(&name)
+                || proxy_names.contains(name)
+                || group_names.contains(name)
+        };
+
+        for (index, group) in config.proxy_groups.iter().enumerate() {
+            let members_field = format!("proxy-groups[{}].proxies", index);
+            for member in &group.proxies {
+                if !is_known_target(member) {
+                    issues.push(ValidationIssue::warning(
+                        &members_field,
+                        format!(
+                            "策略组「{}」引用了不存在的节点或策略组：{}",
+                            group.name, member
+                        ),
+                    ));
+                }
+            }
+            for provider in &group.use_providers {
+                if !config.proxy_providers.contains_key(provider) {
+                    issues.push(ValidationIssue::error(
+                        format!("proxy-groups[{}].use", index),
+                        format!("策略组「{}」引用了不存在的代理源：{}", group.name, provider),
+                    ));
+                }
+            }
+
+            let includes_all = group.include_all.unwrap_or(false)
+                || group.include_all_proxies.unwrap_or(false)
+                || group.include_all_providers.unwrap_or(false);
+            if group.proxies.is_empty() && group.use_providers.is_empty() && !includes_all {
+                issues.push(ValidationIssue::warning(
+                    members_field,
+                    format!("策略组「{}」没有任何节点", group.name),
+                ));
+            }
+        }
+
+        for cycle in Self::find_group_cycles(config) {
+            let index = config
+                .proxy_groups
+                .iter()
+                .position(|g| cycle.groups.first() == Some(&g.name))
+                .unwrap_or_default();
+            issues.push(ValidationIssue::warning(
+                format!("proxy-groups[{}].proxies", index),
+                format!("策略组循环引用：{}", cycle.path()),
+            ));
+        }
+
+        let mut sub_rule_names: Vec<&String> = config.sub_rules.keys().collect();
+        sub_rule_names.sort();
+        let rule_sets = std::iter::once(("", &config.rules)).chain(
+            sub_rule_names
+                .into_iter()
+                .map(|name| (name.as_str(), &config.sub_rules[name])),
+        );
+        for (sub_rule, rules) in rule_sets {
+            // 子规则中的问题带上子规则名称
+            let label = |rule: &String| {
+                if sub_rule.is_empty() {
+                    rule.clone()
+                } else {
+                    format!("{}（子规则「{}」）", rule, sub_rule)
+                }
+            };
+            let field = |index: usize| {
+                if sub_rule.is_empty() {
+                    format!("rules[{}]", index)
+                } else {
+                    format!("sub-rules.{}[{}]", sub_rule, index)
+                }
+            };
+            for (index, rule) in rules.iter().enumerate() {
+                let parts: Vec<&str> = rule.split(',').map(str::trim).collect();
+                let rule_type = parts[0].to_ascii_uppercase();
+
+                let Some(target) = Self::rule_target_index(&parts).map(|i| parts[i]) else {
+                    issues.push(ValidationIssue::warning(
+                        field(index),
+                        format!("规则格式无效：{}", label(rule)),
+                    ));
+                    continue;
+                };
+                if rule_type != "MATCH" && parts.len() < 3 {
+                    issues.push(ValidationIssue::warning(
+                        field(index),
+                        format!("规则格式无效：{}", label(rule)),
+                    ));
+                    continue;
+                }
+
+                if rule_type == "RULE-SET" && !config.rule_providers.contains_key(parts[1]) {
+                    issues.push(ValidationIssue::error(
+                        field(index),
+                        format!("规则引用了不存在的规则源：{}", label(rule)),
+                    ));
+                }
+                if rule_type == "SUB-RULE" {
+                    // SUB-RULE 的目标是子规则名称
+                    if !config.sub_rules.contains_key(target) {
+                        issues.push(ValidationIssue::warning(
+                            field(index),
+                            format!("规则引用了不存在的子规则：{}", label(rule)),
+                        ));
+                    }
+                } else if !is_known_target(target) {
+                    issues.push(ValidationIssue::warning(
+                        field(index),
+                        format!("规则目标不存在：{}", label(rule)),
+                    ));
                 }
             }
-            true
+        }
+
+        issues
+    }
+
+    /// 比较两份配置的节点、策略组和规则
+    ///
+    /// 节点按名称匹配，名称相同但类型、地址或其他参数不同时视为修改；
+    /// 策略组按名称匹配，比较类型和成员；规则按内容比较。
+    pub fn diff(old: &ProfileConfig, new: &ProfileConfig) -> ProfileDiff {
+        fn endpoint(proxy: &ProxyConfig) -> String {
+            format!("{} {}:{}", proxy.proxy_type, proxy.server, proxy.port)
+        }
+        fn item(name: &str, detail: Option<String>) -> ConfigDiffItem {
+            ConfigDiffItem {
+                name: name.to_string(),
+                detail,
+            }
+        }
+
+        let mut diff = ProfileDiff::default();
+
+        let old_proxies: HashMap<&str, &ProxyConfig> =
+            old.proxies.iter().map(|p| (p.name.as_str(), p)).collect();
+        let new_proxies: HashMap<&str, &ProxyConfig> =
+            new.proxies.iter().map(|p| (p.name.as_str(), p)).collect();
+        for proxy in &new.proxies {
+            match old_proxies.get(proxy.name.as_str()) {
+                None => diff
+                    .proxies
+                    .added
+                    .push(item(&proxy.name, Some(endpoint(proxy)))),
+                Some(old_proxy) => {
+                    let (old_endpoint, new_endpoint) = (endpoint(old_proxy), endpoint(proxy));
+                    if old_endpoint != new_endpoint {
+                        diff.proxies.changed.push(item(
+                            &proxy.name,
+                            Some(format!("{} -> {}", old_endpoint, new_endpoint)),
+                        ));
+                    } else if serde_json::to_value(old_proxy).ok()
+                        != serde_json::to_value(proxy).ok()
+                    {
+                        diff.proxies
+                            .changed
+                            .push(item(&proxy.name, Some("参数变更".to_string())));
+                    }
+                }
+            }
+        }
+        for proxy in &old.proxies {
+            if !new_proxies.contains_key(proxy.name.as_str()) {
+                diff.proxies
+                    .removed
+                    .push(item(&proxy.name, Some(endpoint(proxy))));
+            }
+        }
+
+        let old_groups: HashMap<&str, &ProxyGroupConfig> = old
+            .proxy_groups
+            .iter()
+            .map(|g| (g.name.as_str(), g))
+            .collect();
+        let new_groups: HashSet<&str> = new.proxy_groups.iter().map(|g| g.name.as_str()).collect();
+        for group in &new.proxy_groups {
+            let Some(old_group) = old_groups.get(group.name.as_str()) else {
+                diff.proxy_groups
+                    .added
+                    .push(item(&group.name, Some(group.group_type.clone())));
+                continue;
+            };
+            let detail = if old_group.group_type != group.group_type {
+                Some(format!(
+                    "类型 {} -> {}",
+                    old_group.group_type, group.group_type
+                ))
+            } else if old_group.proxies != group.proxies
+                || old_group.use_providers != group.use_providers
+            {
+                Some(format!(
+                    "成员 {} -> {}",
+                    old_group.proxies.len() + old_group.use_providers.len(),
+                    group.proxies.len() + group.use_providers.len()
+                ))
+            } else {
+                None
+            };
+            if detail.is_some() {
+                diff.proxy_groups.changed.push(item(&group.name, detail));
+            }
+        }
+        for group in &old.proxy_groups {
+            if !new_groups.contains(group.name.as_str()) {
+                diff.proxy_groups
+                    .removed
+                    .push(item(&group.name, Some(group.group_type.clone())));
+            }
+        }
+
+        let old_rules: HashSet<&str> = old.rules.iter().map(String::as_str).collect();
+        let new_rules: HashSet<&str> = new.rules.iter().map(String::as_str).collect();
+        for rule in &new.rules {
+            if !old_rules.contains(rule.as_str()) {
+                diff.rules.added.push(item(rule, None));
+            }
+        }
+        for rule in &old.rules {
+            if !new_rules.contains(rule.as_str()) {
+                diff.rules.removed.push(item(rule, None));
+            }
+        }
+
+        diff
+    }
+
+    /// 合并主、副两个 Profile（多配置模式）
+    ///
+    /// 节点、策略组和代理源只取自主 Profile，规则和规则源只取自副 Profile，
+    /// 两侧不会出现同类定义重名的情况，因此不需要按优先级覆盖。
+    /// 合并结果中规则目标不存在、节点与策略组重名或策略组循环引用时返回错误。
+    pub fn merge_layered(
+        primary: ProfileConfig,
+        secondary: ProfileConfig,
+    ) -> Result<ProfileConfig> {
+        let merged = ProfileConfig {
+            proxies: primary.proxies,
+            proxy_groups: primary.proxy_groups,
+            proxy_providers: primary.proxy_providers,
+            rule_providers: secondary.rule_providers,
+            rules: secondary.rules,
+            sub_rules: secondary.sub_rules,
+        };
+
+        let mut errors = Vec::new();
+
+        let mut names = HashSet::new();
+        for name in merged
+            .proxies
+            .iter()
+            .map(|p| p.name.as_str())
+            .chain(merged.proxy_groups.iter().map(|g| g.name.as_str()))
+        {
+            if !names.insert(name) {
+                errors.push(format!("节点或策略组名称重复：{}", name));
+            }
+        }
+
+        for cycle in Self::find_group_cycles(&merged) {
+            errors.push(format!("策略组循环引用：{}", cycle.path()));
+        }
+
+        let mut missing_targets = HashSet::new();
+        for rule in merged
+            .rules
+            .iter()
+            .chain(merged.sub_rules.values().flatten())
+        {
+            let parts: Vec<&str> = rule.split(',').map(str::trim).collect();
+            let target = parts
+                .iter()
+                .skip(1)
+                .rev()
+                .find(|part| !matches!(**part, "no-resolve" | "src"));
+            if parts[0].eq_ignore_ascii_case("SUB-RULE") {
+                if let Some(target) = target.filter(|t| !merged.sub_rules.contains_key(**t)) {
+                    errors.push(format!("规则引用了不存在的子规则：{}", target));
+                }
+            } else if let Some(target) = target {
+                if !Self::BUILTIN_TARGETS.contains(target) && !names.contains(target) {
+                    missing_targets.insert(*target);
+                }
+            }
+            if parts[0].eq_ignore_ascii_case("RULE-SET")
+                && parts.len() > 1
+                && !merged.rule_providers.contains_key(parts[1])
+            {
+                errors.push(format!("规则引用了不存在的规则源：{}", rule));
+            }
+        }
+        let mut missing_targets: Vec<_> = missing_targets.into_iter().collect();
+        missing_targets.sort_unstable();
+        for target in missing_targets {
+            errors.push(format!("主配置中没有规则使用的策略组：{}", target));
+        }
+
+        if !errors.is_empty() {
+            return Err(anyhow!("合并配置失败：{}", errors.join("；")));
+        }
+        Ok(merged)
+    }
+
+    /// 将规则模板（规则、规则源、代理源、策略组）合并到配置中，节点不受影响
+    ///
+    /// - `Append`：新规则插入到 MATCH 之前（已存在的跳过，配置已有 MATCH 时忽略模板的 MATCH），
+    ///   同名的规则源和代理源被模板覆盖；与已有策略组或节点重名的模板策略组重命名后追加，
+    ///   模板规则中的引用同步更新
+    /// - `Replace`：模板中非空的部分整体替换配置中的对应部分
+    ///
+    /// 返回合并时重命名的策略组说明。
+    pub fn apply_template(
+        config: &mut ProfileConfig,
+        template: &ProfileConfig,
+        mode: TemplateMergeMode,
+    ) -> Vec<String> {
+        let mut notes = Vec::new();
+        match mode {
+            TemplateMergeMode::Replace => {
+                if !template.rules.is_empty() {
+                    config.rules = template.rules.clone();
+                }
+                if !template.sub_rules.is_empty() {
+                    config.sub_rules = template.sub_rules.clone();
+                }
+                if !template.rule_providers.is_empty() {
+                    config.rule_providers = template.rule_providers.clone();
+                }
+                if !template.proxy_providers.is_empty() {
+                    config.proxy_providers = template.proxy_providers.clone();
+                }
+                if !template.proxy_groups.is_empty() {
+                    config.proxy_groups = template.proxy_groups.clone();
+                }
+            }
+            TemplateMergeMode::Append => {
+                let mut template = template.clone();
+                let mut taken: HashSet<String> = config
+                    .proxy_groups
+                    .iter()
+                    .map(|g| g.name.clone())
+                    .chain(config.proxies.iter().map(|p| p.name.clone()))
+                    .chain(template.proxy_groups.iter().map(|g| g.name.clone()))
+                    .collect();
+                let conflicts: Vec<String> = template
+                    .proxy_groups
+                    .iter()
+                    .map(|g| g.name.clone())
+                    .filter(|name| {
+                        config.proxy_groups.iter().any(|g| &g.name == name)
+                            || config.proxies.iter().any(|p| &p.name == name)
+                    })
+                    .collect();
+                for old_name in conflicts {
+                    let mut new_name = format!("{} (模板)", old_name);
+                    let mut suffix = 2;
+                    while taken.contains(&new_name) {
+                        new_name = format!("{} (模板 {})", old_name, suffix);
+                        suffix += 1;
+                    }
+                    taken.insert(new_name.clone());
+                    for group in template
+                        .proxy_groups
+                        .iter_mut()
+                        .filter(|g| g.name == old_name)
+                    {
+                        group.name = new_name.clone();
+                    }
+                    Self::rename_group_references(&mut template, &old_name, &new_name);
+                    notes.push(format!(
+                        "策略组 {} 已存在，模板中的同名策略组已重命名为 {}",
+                        old_name, new_name
+                    ));
+                }
+                let template = &template;
+
+                let is_match = |rule: &String| rule.starts_with("MATCH,");
+                let has_match = config.rules.iter().any(is_match);
+                let mut existing: HashSet<String> = config.rules.iter().cloned().collect();
+                let new_rules: Vec<String> = template
+                    .rules
+                    .iter()
+                    .filter(|rule| !(has_match && is_match(rule)))
+                    .filter(|rule| existing.insert((*rule).clone()))
+                    .cloned()
+                    .collect();
+                let insert_pos = config
+                    .rules
+                    .iter()
+                    .position(is_match)
+                    .unwrap_or(config.rules.len());
+                config.rules.splice(insert_pos..insert_pos, new_rules);
+
+                config.sub_rules.extend(template.sub_rules.clone());
+                config
+                    .rule_providers
+                    .extend(template.rule_providers.clone());
+                config
+                    .proxy_providers
+                    .extend(template.proxy_providers.clone());
+
+                config
+                    .proxy_groups
+                    .extend(template.proxy_groups.iter().cloned());
+            }
+        }
+        notes
+    }
+
+    /// 用正则替换批量重命名节点，并同步更新策略组、规则和 `dialer-proxy` 中的引用
+    ///
+    /// 新名称为空、互相重复或与策略组、内置策略重名时返回错误，配置不做修改。
+    pub fn rename_proxies(
+        config: &mut ProfileConfig,
+        pattern: &regex::Regex,
+        replacement: &str,
+    ) -> Result<Vec<NodeRename>> {
+        let renames: Vec<NodeRename> = config
+            .proxies
+            .iter()
+            .map(|proxy| NodeRename {
+                old_name: proxy.name.clone(),
+                new_name: pattern
+                    .replace_all(&proxy.name, replacement)
+                    .trim()
+                    .to_string(),
+            })
+            .filter(|rename| rename.new_name != rename.old_name)
+            .collect();
+        if renames.is_empty() {
+            return Ok(renames);
+        }
+
+        let mapping: HashMap<&str, &str> = renames
+            .iter()
+            .map(|r| (r.old_name.as_str(), r.new_name.as_str()))
+            .collect();
+        let mut names = HashSet::new();
+        for proxy in &config.proxies {
+            let name = mapping
+                .get(proxy.name.as_str())
+                .copied()
+                .unwrap_or(&proxy.name);
+            if name.is_empty() {
+                return Err(anyhow!("节点 {} 重命名后名称为空", proxy.name));
+            }
+            if Self::BUILTIN_TARGETS.contains(&name)
+                || config.proxy_groups.iter().any(|g| g.name == name)
+            {
+                return Err(anyhow!("新名称与策略组或内置策略重名: {}", name));
+            }
+            if !names.insert(name) {
+                return Err(anyhow!("重命名后节点名称重复: {}", name));
+            }
+        }
+
+        let rename = |name: &mut String| {
+            if let Some(new_name) = mapping.get(name.as_str()) {
+                *name = (*new_name).to_string();
+            }
+        };
+        for proxy in &mut config.proxies {
+            rename(&mut proxy.name);
+            if let Some(serde_yaml::Value::String(dialer)) = proxy.extra.get_mut("dialer-proxy") {
+                rename(dialer);
+            }
+        }
+        for group in &mut config.proxy_groups {
+            group.proxies.iter_mut().for_each(rename);
+        }
+        Self::rename_rule_targets(config, |target| {
+            mapping.get(target).map(|name| (*name).to_string())
         });
+
+        Ok(renames)
+    }
+
+    /// 重命名策略组后更新其他策略组和规则（含子规则）中的引用
+    pub fn rename_group_references(config: &mut ProfileConfig, old_name: &str, new_name: &str) {
+        for group in &mut config.proxy_groups {
+            for member in group.proxies.iter_mut().filter(|m| *m == old_name) {
+                *member = new_name.to_string();
+            }
+        }
+        Self::rename_rule_targets(config, |target| {
+            (target == old_name).then(|| new_name.to_string())
+        });
+    }
+
+    /// 按 `rename` 返回的新名称替换规则和子规则的目标策略
+    fn rename_rule_targets(config: &mut ProfileConfig, rename: impl Fn(&str) -> Option<String>) {
+        let rules = config
+            .rules
+            .iter_mut()
+            .chain(config.sub_rules.values_mut().flatten());
+        for rule in rules {
+            let mut parts: Vec<String> = rule.split(',').map(|p| p.trim().to_string()).collect();
+            // SUB-RULE 的目标是子规则名称，不是策略
+            if parts[0].eq_ignore_ascii_case("SUB-RULE") {
+                continue;
+            }
+            let Some(index) = Self::rule_target_index(&parts) else {
+                continue;
+            };
+            if let Some(new_name) = rename(&parts[index]) {
+                parts[index] = new_name;
+                *rule = parts.join(",");
+            }
+        }
+    }
+
+    /// 按节点名称的正则表达式规划地区策略组，每个地区包含名称匹配的节点
+    ///
+    /// 一个节点可以属于多个地区；地区按传入顺序排列。
+    /// 代理源中的节点通过 `use` 所有代理源并以同一正则 `filter` 筛选。
+    pub fn plan_region_groups(
+        config: &ProfileConfig,
+        patterns: &[(String, regex::Regex)],
+        select_group: &str,
+    ) -> RegionGroupsPlan {
+        let mut providers: Vec<String> = config.proxy_providers.keys().cloned().collect();
+        providers.sort();
+        let groups: Vec<RegionGroup> = patterns
+            .iter()
+            .map(|(name, pattern)| RegionGroup {
+                name: name.clone(),
+                proxies: config
+                    .proxies
+                    .iter()
+                    .filter(|p| pattern.is_match(&p.name))
+                    .map(|p| p.name.clone())
+                    .collect(),
+                providers: providers.clone(),
+                filter: (!providers.is_empty()).then(|| pattern.as_str().to_string()),
+            })
+            .collect();
+        let unmatched = config
+            .proxies
+            .iter()
+            .filter(|p| !groups.iter().any(|g| g.proxies.contains(&p.name)))
+            .map(|p| p.name.clone())
+            .collect();
+        RegionGroupsPlan {
+            select_group: select_group.to_string(),
+            groups,
+            unmatched,
+        }
+    }
+
+    /// 将地区策略组写入配置：每个地区一个 `url-test` 策略组，
+    /// 另加一个列出所有地区组的 `select` 策略组
+    ///
+    /// 与之前生成的策略组（`generated`）同名时原位替换，否则追加到末尾；
+    /// 与手动创建的策略组同名时拒绝应用。
+    /// 有节点未归入任何地区或有地区没有匹配到节点时拒绝应用。
+    pub fn apply_region_groups(
+        config: &mut ProfileConfig,
+        plan: &RegionGroupsPlan,
+        generated: &[String],
+    ) -> Result<()> {
+        if !plan.unmatched.is_empty() {
+            return Err(anyhow!(
+                "以下节点没有匹配任何地区：{}",
+                plan.unmatched.join("、")
+            ));
+        }
+        let empty: Vec<&str> = plan
+            .groups
+            .iter()
+            .filter(|g| g.proxies.is_empty() && g.providers.is_empty())
+            .map(|g| g.name.as_str())
+            .collect();
+        if !empty.is_empty() {
+            return Err(anyhow!("以下地区没有匹配到节点：{}", empty.join("、")));
+        }
+
+        let names = plan
+            .groups
+            .iter()
+            .map(|g| g.name.as_str())
+            .chain(std::iter::once(plan.select_group.as_str()));
+        let mut seen = HashSet::new();
+        for name in names {
+            if name.trim().is_empty() {
+                return Err(anyhow!("策略组名称不能为空"));
+            }
+            if Self::BUILTIN_TARGETS.contains(&name)
+                || config.proxies.iter().any(|p| p.name == name)
+            {
+                return Err(anyhow!("策略组名称与节点或内置策略重名: {}", name));
+            }
+            if !seen.insert(name) {
+                return Err(anyhow!("策略组名称重复: {}", name));
+            }
+            if !generated.iter().any(|g| g == name)
+                && config.proxy_groups.iter().any(|g| g.name == name)
+            {
+                return Err(anyhow!("已存在同名的策略组，不会覆盖: {}", name));
+            }
+        }
+
+        let select = ProxyGroupConfig {
+            name: plan.select_group.clone(),
+            group_type: "select".to_string(),
+            proxies: plan.groups.iter().map(|g| g.name.clone()).collect(),
+            ..Default::default()
+        };
+        let regions = plan.groups.iter().map(|g| ProxyGroupConfig {
+            name: g.name.clone(),
+            group_type: "url-test".to_string(),
+            proxies: g.proxies.clone(),
+            use_providers: g.providers.clone(),
+            filter: g.filter.clone(),
+            url: Some("http://www.gstatic.com/generate_204".to_string()),
+            interval: Some(300),
+            ..Default::default()
+        });
+        for group in std::iter::once(select).chain(regions) {
+            match config
+                .proxy_groups
+                .iter_mut()
+                .find(|g| g.name == group.name)
+            {
+                Some(existing) => *existing = group,
+                None => config.proxy_groups.push(group),
+            }
+        }
+        Ok(())
+    }
+
+    /// 规则去重：移除完全相同的重复规则，并报告同一匹配条件指向不同策略的冲突
+    ///
+    /// 规则按顺序匹配，冲突中只有最先出现的规则生效；`resolve_conflicts` 为 true 时
+    /// 一并移除这些不会被命中的规则。保持原有顺序，`MATCH` 规则不会被移除。
+    pub fn dedup_rules(config: &mut ProfileConfig, resolve_conflicts: bool) -> RuleDedupReport {
+        let mut report = RuleDedupReport::default();
+        let mut seen = HashSet::new();
+        // 匹配条件 -> conflicts 中的下标（未冲突时为 None）及生效的规则
+        let mut matchers: HashMap<String, (Option<usize>, String)> = HashMap::new();
+
+        let rules = std::mem::take(&mut config.rules);
+        for rule in rules {
+            let normalized = Self::normalize_rule(&rule);
+            let is_match = normalized.starts_with("MATCH,");
+            if !is_match && !seen.insert(normalized.clone()) {
+                report.removed.push(rule);
+                continue;
+            }
+
+            if let Some(matcher) = Self::rule_matcher(&normalized) {
+                match matchers.get_mut(&matcher) {
+                    Some((index, effective)) => {
+                        let index = *index.get_or_insert_with(|| {
+                            report.conflicts.push(RuleConflict {
+                                matcher: matcher.clone(),
+                                effective: effective.clone(),
+                                shadowed: Vec::new(),
+                            });
+                            report.conflicts.len() - 1
+                        });
+                        report.conflicts[index].shadowed.push(rule.clone());
+                        if resolve_conflicts && !is_match {
+                            report.removed.push(rule);
+                            continue;
+                        }
+                    }
+                    None => {
+                        matchers.insert(matcher, (None, rule.clone()));
+                    }
+                }
+            }
+            config.rules.push(rule);
+        }
+
+        report
+    }
+
+    /// 找出被前面规则完全覆盖的规则（如 `DOMAIN-SUFFIX,google.com` 之后的
+    /// `DOMAIN,mail.google.com`），每条被覆盖的规则只报告最先覆盖它的规则
+    ///
+    /// 只分析能在本地解析的规则类型，GEOIP、RULE-SET、逻辑规则等会被忽略。
+    pub fn find_shadowed_rules(config: &ProfileConfig) -> Vec<ShadowedRule> {
+        let matchers: Vec<(usize, RuleMatcher)> = config
+            .rules
+            .iter()
+            .enumerate()
+            .filter_map(|(i, rule)| RuleMatcher::parse(rule).ok().map(|m| (i, m)))
+            .collect();
+
+        let mut shadowed = Vec::new();
+        for (pos, (index, matcher)) in matchers.iter().enumerate() {
+            if let Some((earlier, _)) = matchers[..pos]
+                .iter()
+                .find(|(_, earlier)| earlier.subsumes(matcher))
+            {
+                shadowed.push(ShadowedRule {
+                    shadowing_index: *earlier,
+                    shadowing_rule: config.rules[*earlier].clone(),
+                    shadowed_index: *index,
+                    shadowed_rule: config.rules[*index].clone(),
+                });
+            }
+        }
+        shadowed
+    }
+
+    /// 找出策略组之间的循环引用（包括引用自身）
+    ///
+    /// 对策略组引用关系做深度优先遍历，每条回边报告一个循环。
+    pub fn find_group_cycles(config: &ProfileConfig) -> Vec<GroupCycle> {
+        fn visit<'a>(
+            name: &'a str,
+            edges: &HashMap<&'a str, Vec<&'a str>>,
+            done: &mut HashSet<&'a str>,
+            stack: &mut Vec<&'a str>,
+            cycles: &mut Vec<GroupCycle>,
+        ) {
+            stack.push(name);
+            for &next in edges.get(name).into_iter().flatten() {
+                if let Some(pos) = stack.iter().position(|&n| n == next) {
+                    cycles.push(GroupCycle {
+                        groups: stack[pos..].iter().map(|&n| n.to_string()).collect(),
+                    });
+                } else if !done.contains(next) {
+                    visit(next, edges, done, stack, cycles);
+                }
+            }
+            stack.pop();
+            done.insert(name);
+        }
+
+        let group_names: HashSet<&str> = config
+            .proxy_groups
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect();
+        let mut edges: HashMap<&str, Vec<&str>> = HashMap::new();
+        for group in &config.proxy_groups {
+            let refs = edges.entry(group.name.as_str()).or_default();
+            for member in &group.proxies {
+                if group_names.contains(member.as_str()) && !refs.contains(&member.as_str()) {
+                    refs.push(member);
+                }
+            }
+        }
+
+        let mut done = HashSet::new();
+        let mut cycles = Vec::new();
+        for group in &config.proxy_groups {
+            if !done.contains(group.name.as_str()) {
+                visit(&group.name, &edges, &mut done, &mut Vec::new(), &mut cycles);
+            }
+        }
+        cycles
+    }
+
+    /// 打断策略组循环引用：从每个循环的最后一个策略组中移除对第一个策略组的引用
+    ///
+    /// 移除后没有任何成员的策略组会补上 `DIRECT`，避免核心拒绝空策略组。
+    /// 返回被打断的循环。
+    pub fn break_group_cycles(config: &mut ProfileConfig) -> Vec<GroupCycle> {
+        let cycles = Self::find_group_cycles(config);
+        for cycle in &cycles {
+            let (Some(target), Some(owner)) = (cycle.groups.first(), cycle.groups.last()) else {
+                continue;
+            };
+            let Some(group) = config.proxy_groups.iter_mut().find(|g| &g.name == owner) else {
+                continue;
+            };
+            group.proxies.retain(|p| p != target);
+            let includes_all = group.include_all.unwrap_or(false)
+                || group.include_all_proxies.unwrap_or(false)
+                || group.include_all_providers.unwrap_or(false);
+            if group.proxies.is_empty() && group.use_providers.is_empty() && !includes_all {
+                group.proxies.push("DIRECT".to_string());
+            }
+        }
+        cycles
+    }
+
+    /// 订阅建议的默认节点（`select` 策略组的 `default` 字段），返回 (策略组, 节点)
+    ///
+    /// 策略组列出了节点时，建议的节点必须在其中。
+    pub fn suggested_selections(config: &ProfileConfig) -> Vec<(String, String)> {
+        config
+            .proxy_groups
+            .iter()
+            .filter(|g| g.group_type == "select")
+            .filter_map(|g| {
+                let proxy = g.extra.get("default")?.as_str()?.trim();
+                let listed = g.proxies.is_empty() || g.proxies.iter().any(|p| p == proxy);
+                (!proxy.is_empty() && listed).then(|| (g.name.clone(), proxy.to_string()))
+            })
+            .collect()
+    }
+
+    /// 规范化规则文本：去掉逗号两侧空白，规则类型转为大写
+    ///
+    /// 域名不区分大小写，统一转为小写；DOMAIN-REGEX 的正则区分大小写，保持原样。
+    fn normalize_rule(rule: &str) -> String {
+        let mut parts: Vec<String> = rule.split(',').map(|p| p.trim().to_string()).collect();
+        parts[0] = parts[0].to_ascii_uppercase();
+        if parts[0].starts_with("DOMAIN") && parts[0] != "DOMAIN-REGEX" {
+            if let Some(payload) = parts.get_mut(1) {
+                *payload = payload.to_ascii_lowercase();
+            }
+        }
+        parts.join(",")
+    }
+
+    /// 规则按逗号拆分后目标（策略）所在的位置
+    ///
+    /// 目标位于最后，`no-resolve` 等参数除外；逻辑规则的条件中也含逗号，同样取最后一段。
+    pub(crate) fn rule_target_index<S: AsRef<str>>(parts: &[S]) -> Option<usize> {
+        (1..parts.len())
+            .rev()
+            .find(|&i| !matches!(parts[i].as_ref().trim(), "no-resolve" | "src"))
+    }
+
+    /// 规则的匹配条件（类型 + 内容）；逻辑规则和子规则不参与冲突检测
+    fn rule_matcher(normalized: &str) -> Option<String> {
+        let parts: Vec<&str> = normalized.split(',').collect();
+        match parts[0] {
+            "AND" | "OR" | "NOT" | "SUB-RULE" => None,
+            "MATCH" => Some("MATCH".to_string()),
+            _ if parts.len() >= 3 => {
+                // 附加参数（如 no-resolve）也属于匹配条件
+                let options = parts[3..].join(",");
+                Some(
+                    format!("{},{},{}", parts[0], parts[1], options)
+                        .trim_end_matches(',')
+                        .to_string(),
+                )
+            }
+            _ => None,
+        }
     }
 
     /// 修正 rule-providers 的路径
@@ -493,6 +1673,36 @@ impl Composer {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_looks_like_html() {
+        assert!(Composer::looks_like_html(
+            "\n  <!DOCTYPE html><html><body>Login</body></html>"
+        ));
+        assert!(Composer::looks_like_html("<html lang=\"en\">"));
+        assert!(!Composer::looks_like_html("proxies:\n  - name: a"));
+        assert!(!Composer::looks_like_html("c3M6Ly9hYmM="));
+    }
+
+    #[test]
+    fn test_read_yaml_file_with_bom_and_crlf() {
+        let yaml = "proxies:\r\n  - name: bom-ss\r\n    type: ss\r\n    server: example.com\r\n    port: 8388\r\n    cipher: aes-256-gcm\r\n    password: pass\r\n";
+        let path = std::env::temp_dir().join(format!("conflux-bom-{}.yaml", uuid::Uuid::new_v4()));
+
+        let mut bytes = b"\xEF\xBB\xBF".to_vec();
+        bytes.extend_from_slice(yaml.as_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        let config = Composer::parse_yaml(&Composer::read_yaml_file(&path).unwrap()).unwrap();
+        assert_eq!(config.proxies[0].name, "bom-ss");
+
+        let mut utf16 = b"\xFF\xFE".to_vec();
+        utf16.extend(yaml.encode_utf16().flat_map(u16::to_le_bytes));
+        std::fs::write(&path, &utf16).unwrap();
+        let config = Composer::parse_yaml(&Composer::read_yaml_file(&path).unwrap()).unwrap();
+        assert_eq!(config.proxies[0].port, 8388);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn test_parse_simple_yaml() {
         let yaml = r#"
@@ -522,6 +1732,423 @@ rules:
         assert_eq!(config.rules.len(), 2);
     }
 
+    #[test]
+    fn test_proxy_group_metadata_round_trip() {
+        let yaml = r"
+proxy-groups:
+  - name: PROXY
+    type: select
+    proxies:
+      - DIRECT
+    icon: https://example.com/proxy.png
+    hidden: true
+    x-custom: keep-me
+";
+
+        let config = Composer::parse_yaml(yaml).unwrap();
+        let reparsed = Composer::parse_yaml(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        let group = &reparsed.proxy_groups[0];
+        assert_eq!(group.icon.as_deref(), Some("https://example.com/proxy.png"));
+        assert_eq!(group.hidden, Some(true));
+        assert_eq!(
+            group.extra.get("x-custom").and_then(|v| v.as_str()),
+            Some("keep-me")
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_missing_references() {
+        let yaml = r"
+proxies:
+  - name: test-ss
+    type: ss
+    server: example.com
+    port: 8388
+    cipher: aes-256-gcm
+    password: password123
+
+proxy-groups:
+  - name: PROXY
+    type: select
+    proxies:
+      - test-ss
+      - missing-node
+      - DIRECT
+
+rules:
+  - RULE-SET,missing-set,PROXY
+  - DOMAIN-SUFFIX,example.com,NOWHERE
+  - IP-CIDR,10.0.0.0/8,DIRECT,no-resolve
+  - MATCH,PROXY
+";
+
+        let config = Composer::parse_yaml(yaml).unwrap();
+        let warnings = Composer::validate(&config);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings.iter().any(|w| w.contains("missing-node")));
+        assert!(warnings.iter().any(|w| w.contains("missing-set")));
+        assert!(warnings.iter().any(|w| w.contains("NOWHERE")));
+    }
+
+    #[test]
+    fn test_validation_issues_field_paths() {
+        use crate::models::ValidationSeverity;
+
+        let yaml = r"
+proxies:
+  - {name: a, type: ss, server: example.com, port: 8388, cipher: aes-256-gcm, password: p}
+  - {name: a, type: ss, server: example.com, port: 8389, cipher: aes-256-gcm, password: p}
+proxy-groups:
+  - {name: PROXY, type: select, proxies: [a], use: [missing]}
+rules:
+  - RULE-SET,missing-set,PROXY
+  - MATCH,PROXY
+";
+        let config = Composer::parse_yaml(yaml).unwrap();
+        let issues = Composer::validation_issues(&config);
+        let errors: Vec<(&str, ValidationSeverity)> = issues
+            .iter()
+            .filter(|i| i.severity == ValidationSeverity::Error)
+            .map(|i| (i.field_path.as_str(), i.severity))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                ("proxies[1].name", ValidationSeverity::Error),
+                ("proxy-groups[0].use", ValidationSeverity::Error),
+                ("rules[0]", ValidationSeverity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sub_rules_round_trip() {
+        let yaml = r"
+proxies:
+  - name: test-ss
+    type: ss
+    server: example.com
+    port: 8388
+    cipher: aes-256-gcm
+    password: password123
+
+rules:
+  - SUB-RULE,(NETWORK,udp),udp-rules
+  - SUB-RULE,(NETWORK,tcp),missing-rules
+  - MATCH,DIRECT
+
+sub-rules:
+  udp-rules:
+    - DST-PORT,443,REJECT
+    - MATCH,test-ss
+";
+
+        let config = Composer::parse_yaml(yaml).unwrap();
+        assert_eq!(config.sub_rules["udp-rules"].len(), 2);
+
+        let serialized = serde_yaml::to_string(&config).unwrap();
+        assert!(serialized.contains("sub-rules:"));
+        let reparsed = Composer::parse_yaml(&serialized).unwrap();
+        assert_eq!(reparsed.sub_rules, config.sub_rules);
+
+        let warnings = Composer::validate(&config);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("missing-rules"));
+
+        let mut runtime = crate::models::MihomoConfig::default();
+        runtime.sub_rules = reparsed.sub_rules;
+        let runtime_yaml = serde_yaml::to_string(&runtime).unwrap();
+        assert!(runtime_yaml.contains("udp-rules:"));
+    }
+
+    #[test]
+    fn test_sub_rules_follow_group_rename_and_rule_set_removal() {
+        let yaml = r"
+proxy-groups:
+  - name: Proxy
+    type: select
+    proxies: [DIRECT]
+rule-providers:
+  ads:
+    type: http
+    behavior: domain
+    url: https://example.com/ads.yaml
+rules:
+  - SUB-RULE,(NETWORK,udp),udp-rules
+  - RULE-SET,ads,REJECT
+  - MATCH,Proxy
+sub-rules:
+  udp-rules:
+    - RULE-SET,ads,REJECT
+    - RULE-SET,missing,REJECT
+    - MATCH,Proxy
+";
+        let mut config = Composer::parse_yaml(yaml).unwrap();
+        Composer::rename_group_references(&mut config, "Proxy", "Main");
+        assert_eq!(config.rules[2], "MATCH,Main");
+        assert_eq!(config.sub_rules["udp-rules"][2], "MATCH,Main");
+
+        Composer::filter_invalid_rules(&mut config);
+        assert_eq!(config.sub_rules["udp-rules"].len(), 2);
+
+        Composer::retain_rule_sets(&mut config, |provider| provider != "ads");
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.sub_rules["udp-rules"], vec!["MATCH,Main"]);
+    }
+
+    #[test]
+    fn test_parse_rule_list() {
+        let content = r"
+# comment
+payload:
+  - DOMAIN-SUFFIX,example.com
+  - 'IP-CIDR,10.0.0.0/8,DIRECT,no-resolve'
+DOMAIN,foo.com,Proxy
+MATCH,Proxy
+invalid
+";
+
+        let (rules, skipped) = Composer::parse_rule_list(content, None);
+        assert_eq!(
+            rules,
+            vec![
+                "IP-CIDR,10.0.0.0/8,DIRECT,no-resolve",
+                "DOMAIN,foo.com,Proxy"
+            ]
+        );
+        assert_eq!(skipped, 3);
+
+        let (rules, skipped) = Composer::parse_rule_list(content, Some("Media"));
+        assert_eq!(
+            rules,
+            vec![
+                "DOMAIN-SUFFIX,example.com,Media",
+                "IP-CIDR,10.0.0.0/8,Media,no-resolve",
+                "DOMAIN,foo.com,Media",
+            ]
+        );
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn test_managed_config_header() {
+        let content = "#!MANAGED-CONFIG https://example.com/sub?token=abc interval=43200 strict=false\n\nproxies: []\n";
+        assert_eq!(
+            Composer::managed_config_header(content),
+            Some(ManagedConfigHeader {
+                url: "https://example.com/sub?token=abc".to_string(),
+                interval: Some(43200),
+            })
+        );
+
+        assert_eq!(
+            Composer::managed_config_header("proxies: []\n#!MANAGED-CONFIG https://a.com\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_diff_profiles() {
+        let old = Composer::parse_yaml(
+            r"
+proxies:
+  - { name: a, type: ss, server: a.example.com, port: 443, cipher: aes-256-gcm, password: x }
+  - { name: b, type: ss, server: b.example.com, port: 443, cipher: aes-256-gcm, password: x }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [a, b] }
+rules:
+  - MATCH,PROXY
+",
+        )
+        .unwrap();
+        let new = Composer::parse_yaml(
+            r"
+proxies:
+  - { name: a, type: ss, server: a2.example.com, port: 443, cipher: aes-256-gcm, password: x }
+  - { name: c, type: ss, server: c.example.com, port: 443, cipher: aes-256-gcm, password: x }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [a, c] }
+rules:
+  - DOMAIN,example.com,DIRECT
+  - MATCH,PROXY
+",
+        )
+        .unwrap();
+
+        let diff = Composer::diff(&old, &new);
+        let names = |items: &[ConfigDiffItem]| -> Vec<String> {
+            items.iter().map(|i| i.name.clone()).collect()
+        };
+        assert_eq!(names(&diff.proxies.added), vec!["c"]);
+        assert_eq!(names(&diff.proxies.removed), vec!["b"]);
+        assert_eq!(names(&diff.proxies.changed), vec!["a"]);
+        assert_eq!(names(&diff.proxy_groups.changed), vec!["PROXY"]);
+        assert_eq!(names(&diff.rules.added), vec!["DOMAIN,example.com,DIRECT"]);
+        assert!(diff.rules.removed.is_empty());
+    }
+
+    #[test]
+    fn test_merge_layered() {
+        let primary = Composer::parse_yaml(
+            r"
+proxies:
+  - { name: a, type: ss, server: a.example.com, port: 443, cipher: aes-256-gcm, password: x }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [a, AUTO] }
+  - { name: AUTO, type: url-test, proxies: [a] }
+rules:
+  - MATCH,DIRECT
+",
+        )
+        .unwrap();
+        let secondary = Composer::parse_yaml(
+            r"
+proxies:
+  - { name: b, type: ss, server: b.example.com, port: 443, cipher: aes-256-gcm, password: x }
+rules:
+  - DOMAIN-SUFFIX,example.com,AUTO
+  - MATCH,PROXY
+",
+        )
+        .unwrap();
+
+        let merged = Composer::merge_layered(primary.clone(), secondary.clone()).unwrap();
+        assert_eq!(merged.proxies.len(), 1);
+        assert_eq!(merged.proxy_groups.len(), 2);
+        assert_eq!(merged.rules, secondary.rules);
+
+        let mut cyclic = primary;
+        cyclic.proxy_groups[1].proxies.push("PROXY".to_string());
+        let err = Composer::merge_layered(cyclic, secondary).unwrap_err();
+        assert!(err.to_string().contains("循环引用"));
+    }
+
+    #[test]
+    fn test_apply_template() {
+        let base = Composer::parse_yaml(
+            r"
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [DIRECT] }
+rules:
+  - DOMAIN,a.example.com,DIRECT
+  - MATCH,PROXY
+",
+        )
+        .unwrap();
+        let template = Composer::parse_yaml(
+            r"
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [REJECT, DIRECT] }
+  - { name: ADS, type: select, proxies: [REJECT] }
+rules:
+  - DOMAIN,a.example.com,DIRECT
+  - DOMAIN-SUFFIX,ads.example.com,ADS
+  - MATCH,DIRECT
+",
+        )
+        .unwrap();
+
+        let mut appended = base.clone();
+        let renamed = Composer::apply_template(&mut appended, &template, TemplateMergeMode::Append);
+        assert_eq!(
+            appended.rules,
+            vec![
+                "DOMAIN,a.example.com,DIRECT",
+                "DOMAIN-SUFFIX,ads.example.com,ADS",
+                "MATCH,PROXY"
+            ]
+        );
+        // 同名策略组不覆盖已有的，模板中的重命名后追加
+        assert_eq!(renamed.len(), 1);
+        assert_eq!(appended.proxy_groups.len(), 3);
+        assert_eq!(appended.proxy_groups[0].proxies, vec!["DIRECT"]);
+        assert_eq!(appended.proxy_groups[1].name, "PROXY (模板)");
+        assert_eq!(appended.proxy_groups[1].proxies, vec!["REJECT", "DIRECT"]);
+
+        let mut replaced = base;
+        Composer::apply_template(&mut replaced, &template, TemplateMergeMode::Replace);
+        assert_eq!(replaced.rules, template.rules);
+    }
+
+    #[test]
+    fn test_rename_proxies() {
+        let mut config = Composer::parse_yaml(
+            r"
+proxies:
+  - { name: 🇭🇰 HK 01, type: ss, server: a.com, port: 1, cipher: aes-128-gcm, password: x }
+  - { name: 🇯🇵 JP 01, type: ss, server: b.com, port: 1, cipher: aes-128-gcm, password: x, dialer-proxy: 🇭🇰 HK 01 }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [🇭🇰 HK 01, 🇯🇵 JP 01] }
+rules:
+  - DOMAIN,a.example.com,🇭🇰 HK 01
+  - IP-CIDR,1.1.1.1/32,🇭🇰 HK 01,no-resolve
+  - AND,((DOMAIN,b.example.com),(NETWORK,UDP)),🇯🇵 JP 01
+  - match,🇯🇵 JP 01
+",
+        )
+        .unwrap();
+
+        let pattern = regex::Regex::new(r"^\p{Regional_Indicator}+\s*").unwrap();
+        let renames = Composer::rename_proxies(&mut config, &pattern, "").unwrap();
+        assert_eq!(renames.len(), 2);
+        assert_eq!(config.proxy_groups[0].proxies, vec!["HK 01", "JP 01"]);
+        assert_eq!(config.rules[0], "DOMAIN,a.example.com,HK 01");
+        assert_eq!(config.rules[1], "IP-CIDR,1.1.1.1/32,HK 01,no-resolve");
+        assert_eq!(
+            config.rules[2],
+            "AND,((DOMAIN,b.example.com),(NETWORK,UDP)),JP 01"
+        );
+        assert_eq!(config.rules[3], "match,JP 01");
+        assert_eq!(
+            config.proxies[1].extra.get("dialer-proxy"),
+            Some(&serde_yaml::Value::String("HK 01".to_string()))
+        );
+
+        let pattern = regex::Regex::new(r"\d+").unwrap();
+        assert!(Composer::rename_proxies(&mut config, &pattern, "").is_err());
+        assert_eq!(config.proxies[0].name, "HK 01");
+    }
+
+    #[test]
+    fn test_dedup_rules() {
+        let yaml = r"
+rules:
+  - DOMAIN-SUFFIX,google.com,PROXY
+  - DOMAIN-SUFFIX,Google.com, PROXY
+  - DOMAIN-SUFFIX,google.com,DIRECT
+  - IP-CIDR,10.0.0.0/8,DIRECT,no-resolve
+  - IP-CIDR,10.0.0.0/8,PROXY
+  - MATCH,PROXY
+";
+        let mut config = Composer::parse_yaml(yaml).unwrap();
+        let report = Composer::dedup_rules(&mut config, false);
+        assert_eq!(report.removed, vec!["DOMAIN-SUFFIX,Google.com, PROXY"]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].matcher, "DOMAIN-SUFFIX,google.com");
+        assert_eq!(config.rules.len(), 5);
+
+        let mut config = Composer::parse_yaml(yaml).unwrap();
+        let report = Composer::dedup_rules(&mut config, true);
+        assert_eq!(report.removed.len(), 2);
+        assert_eq!(config.rules.last().unwrap(), "MATCH,PROXY");
+        assert_eq!(config.rules.len(), 4);
+    }
+
+    #[test]
+    fn test_dedup_rules_keeps_case_distinct_regex() {
+        let yaml = r"
+rules:
+  - DOMAIN-REGEX,^API\.,PROXY
+  - DOMAIN-REGEX,^api\.,DIRECT
+  - MATCH,PROXY
+";
+        let mut config = Composer::parse_yaml(yaml).unwrap();
+        let report = Composer::dedup_rules(&mut config, true);
+        assert!(report.removed.is_empty());
+        assert!(report.conflicts.is_empty());
+        assert_eq!(config.rules.len(), 3);
+    }
+
     #[test]
     fn test_guess_behavior() {
         assert_eq!(Composer::guess_behavior("ChinaIP"), "ipcidr");
@@ -577,4 +2204,149 @@ rules:
             .contains(&"node-1".to_string()));
         assert_eq!(config.rules.last().unwrap(), "MATCH,🚀 节点选择");
     }
+
+    #[test]
+    fn test_suggested_selections() {
+        let config: ProfileConfig = serde_yaml::from_str(
+            r"
+proxy-groups:
+  - name: Proxy
+    type: select
+    proxies: [HK, JP]
+    default: JP
+  - name: Missing
+    type: select
+    proxies: [HK]
+    default: US
+  - name: Auto
+    type: url-test
+    proxies: [HK, JP]
+    default: JP
+  - name: Provider
+    type: select
+    use: [sub]
+    default: SG
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Composer::suggested_selections(&config),
+            vec![
+                ("Proxy".to_string(), "JP".to_string()),
+                ("Provider".to_string(), "SG".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_cycles() {
+        let mut config: ProfileConfig = serde_yaml::from_str(
+            r"
+proxies:
+  - { name: HK, type: socks5, server: 127.0.0.1, port: 1080 }
+proxy-groups:
+  - name: A
+    type: select
+    proxies: [B, HK]
+  - name: B
+    type: select
+    proxies: [A]
+  - name: C
+    type: select
+    proxies: [A, B]
+",
+        )
+        .unwrap();
+
+        let cycles = Composer::find_group_cycles(&config);
+        assert_eq!(
+            cycles,
+            vec![GroupCycle {
+                groups: vec!["A".to_string(), "B".to_string()],
+            }]
+        );
+        assert!(Composer::validate(&config).contains(&"策略组循环引用：A → B → A".to_string()));
+
+        assert_eq!(Composer::break_group_cycles(&mut config), cycles);
+        assert!(Composer::find_group_cycles(&config).is_empty());
+        assert_eq!(config.proxy_groups[0].proxies, vec!["B", "HK"]);
+        assert_eq!(config.proxy_groups[1].proxies, vec!["DIRECT"]);
+        assert_eq!(config.proxy_groups[2].proxies, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_region_groups() {
+        let mut config: ProfileConfig = serde_yaml::from_str(
+            r"
+proxies:
+  - { name: 香港 01, type: socks5, server: 127.0.0.1, port: 1080 }
+  - { name: HK 02, type: socks5, server: 127.0.0.1, port: 1081 }
+  - { name: 日本 01, type: socks5, server: 127.0.0.1, port: 1082 }
+  - { name: 剩余流量：10GB, type: socks5, server: 127.0.0.1, port: 1083 }
+proxy-groups:
+  - name: 🇯🇵 JP
+    type: select
+    proxies: [DIRECT]
+",
+        )
+        .unwrap();
+        let patterns = vec![
+            (
+                "🇭🇰 HK".to_string(),
+                regex::Regex::new("(?i)香港|HK").unwrap(),
+            ),
+            ("🇯🇵 JP".to_string(), regex::Regex::new("日本|JP").unwrap()),
+        ];
+
+        let plan = Composer::plan_region_groups(&config, &patterns, "🌐 地区");
+        assert_eq!(plan.groups[0].proxies, vec!["香港 01", "HK 02"]);
+        assert_eq!(plan.groups[1].proxies, vec!["日本 01"]);
+        assert_eq!(plan.unmatched, vec!["剩余流量：10GB"]);
+        assert!(Composer::apply_region_groups(&mut config, &plan, &[]).is_err());
+
+        config.proxies.pop();
+        let plan = Composer::plan_region_groups(&config, &patterns, "🌐 地区");
+        // 手动创建的同名策略组不会被覆盖
+        assert!(Composer::apply_region_groups(&mut config, &plan, &[]).is_err());
+        assert_eq!(config.proxy_groups[0].group_type, "select");
+
+        // 之前生成的策略组原位替换
+        let generated = vec!["🇯🇵 JP".to_string()];
+        Composer::apply_region_groups(&mut config, &plan, &generated).unwrap();
+        let names: Vec<&str> = config
+            .proxy_groups
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["🇯🇵 JP", "🌐 地区", "🇭🇰 HK"]);
+        assert_eq!(config.proxy_groups[0].group_type, "url-test");
+        assert_eq!(config.proxy_groups[1].proxies, vec!["🇭🇰 HK", "🇯🇵 JP"]);
+        assert!(Composer::validate(&config).is_empty());
+    }
+
+    #[test]
+    fn test_region_groups_with_providers() {
+        let mut config: ProfileConfig = serde_yaml::from_str(
+            r"
+proxy-providers:
+  sub:
+    type: http
+    url: https://example.com/sub.yaml
+    path: ./providers/sub.yaml
+",
+        )
+        .unwrap();
+        let patterns = vec![("🇭🇰 HK".to_string(), regex::Regex::new("香港|HK").unwrap())];
+
+        let plan = Composer::plan_region_groups(&config, &patterns, "🌐 地区");
+        assert!(plan.groups[0].proxies.is_empty());
+        assert_eq!(plan.groups[0].providers, vec!["sub"]);
+        assert_eq!(plan.groups[0].filter.as_deref(), Some("香港|HK"));
+
+        Composer::apply_region_groups(&mut config, &plan, &[]).unwrap();
+        let hk = &config.proxy_groups[1];
+        assert_eq!(hk.use_providers, vec!["sub"]);
+        assert_eq!(hk.filter.as_deref(), Some("香港|HK"));
+    }
 }
@@ -0,0 +1,661 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::config::Composer;
+use crate::models::{ProfileConfig, ProxyConfig, ProxyGroupConfig};
+
+/// 外部客户端配置格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignFormat {
+    Surge,
+    QuantumultX,
+}
+
+/// 外部配置转换结果
+#[derive(Debug, Default)]
+pub struct ForeignConversion {
+    pub config: ProfileConfig,
+    /// 无法转换而跳过的行（带原因）
+    pub warnings: Vec<String>,
+}
+
+/// 测速地址（QuantumultX 策略组不单独配置）
+const DEFAULT_TEST_URL: &str = "https://www.gstatic.com/generate_204";
+
+/// mihomo 内置策略，不需要在节点或策略组中定义
+const BUILTIN_POLICIES: &[&str] = &["DIRECT", "REJECT", "REJECT-DROP", "PASS", "COMPATIBLE"];
+
+/// Surge 规则类型到 mihomo 规则类型的映射
+const SURGE_RULE_TYPES: &[(&str, &str)] = &[
+    ("DOMAIN", "DOMAIN"),
+    ("DOMAIN-SUFFIX", "DOMAIN-SUFFIX"),
+    ("DOMAIN-KEYWORD", "DOMAIN-KEYWORD"),
+    ("IP-CIDR", "IP-CIDR"),
+    ("IP-CIDR6", "IP-CIDR6"),
+    ("GEOIP", "GEOIP"),
+    ("IP-ASN", "IP-ASN"),
+    ("PROCESS-NAME", "PROCESS-NAME"),
+    ("DEST-PORT", "DST-PORT"),
+    ("DST-PORT", "DST-PORT"),
+    ("SRC-IP", "SRC-IP-CIDR"),
+    ("SRC-PORT", "SRC-PORT"),
+];
+
+/// QuantumultX 分流类型到 mihomo 规则类型的映射
+const QUANTUMULT_RULE_TYPES: &[(&str, &str)] = &[
+    ("host", "DOMAIN"),
+    ("host-suffix", "DOMAIN-SUFFIX"),
+    ("host-keyword", "DOMAIN-KEYWORD"),
+    ("ip-cidr", "IP-CIDR"),
+    ("ip6-cidr", "IP-CIDR6"),
+    ("geoip", "GEOIP"),
+    ("ip-asn", "IP-ASN"),
+];
+
+/// 根据小节标题判断配置格式，不是 Surge/QuantumultX 配置时返回 None
+pub fn detect_foreign_format(content: &str) -> Option<ForeignFormat> {
+    let sections: Vec<String> = content
+        .lines()
+        .filter_map(section_name)
+        .map(|s| s.to_ascii_lowercase())
+        .collect();
+    let has = |name: &str| sections.iter().any(|s| s == name);
+    if has("server_local") || has("filter_local") {
+        Some(ForeignFormat::QuantumultX)
+    } else if has("proxy") || has("proxy group") || has("rule") {
+        Some(ForeignFormat::Surge)
+    } else {
+        None
+    }
+}
+
+/// 转换 Surge 或 QuantumultX 配置（自动识别格式）
+pub fn convert_foreign_config(content: &str) -> Option<ForeignConversion> {
+    match detect_foreign_format(content)? {
+        ForeignFormat::Surge => Some(convert_surge(content)),
+        ForeignFormat::QuantumultX => Some(convert_quantumult(content)),
+    }
+}
+
+/// 转换 Surge 配置的 `[Proxy]`、`[Proxy Group]`、`[Rule]` 小节
+pub fn convert_surge(content: &str) -> ForeignConversion {
+    let mut result = ForeignConversion::default();
+    for (section, line) in section_lines(content) {
+        let converted = match section.as_str() {
+            "proxy" => surge_proxy(line).map(|p| result.config.proxies.push(p)),
+            "proxy group" => surge_group(line).map(|g| result.config.proxy_groups.push(g)),
+            "rule" => surge_rule(line).map(|r| result.config.rules.push(r)),
+            _ => continue,
+        };
+        if let Err(reason) = converted {
+            result.warnings.push(format!("{}（{}）", line, reason));
+        }
+    }
+    prune_dangling_references(&mut result);
+    result
+}
+
+/// 转换 QuantumultX 配置的 `[server_local]`、`[policy]`、`[filter_local]` 小节
+pub fn convert_quantumult(content: &str) -> ForeignConversion {
+    let mut result = ForeignConversion::default();
+    for (section, line) in section_lines(content) {
+        let converted = match section.as_str() {
+            "server_local" => {
+                quantumult_proxy(line, &mut result.warnings).map(|p| result.config.proxies.push(p))
+            }
+            "policy" => quantumult_group(line).map(|g| result.config.proxy_groups.push(g)),
+            "filter_local" => quantumult_rule(line).map(|r| result.config.rules.push(r)),
+            _ => continue,
+        };
+        if let Err(reason) = converted {
+            result.warnings.push(format!("{}（{}）", line, reason));
+        }
+    }
+    prune_dangling_references(&mut result);
+    result
+}
+
+/// 移除引用了未导入节点或策略组的成员和规则
+///
+/// 跳过的节点仍会被策略组和规则引用，不处理会导致核心拒绝加载配置。
+/// 成员全部被移除的策略组也一并移除，因此需要重复检查直到没有变化。
+fn prune_dangling_references(result: &mut ForeignConversion) {
+    let config = &mut result.config;
+    loop {
+        let defined: HashSet<String> = config
+            .proxies
+            .iter()
+            .map(|p| p.name.clone())
+            .chain(config.proxy_groups.iter().map(|g| g.name.clone()))
+            .chain(BUILTIN_POLICIES.iter().map(|p| (*p).to_string()))
+            .collect();
+
+        let mut changed = false;
+        for group in &mut config.proxy_groups {
+            group.proxies.retain(|member| {
+                let keep = defined.contains(member);
+                if !keep {
+                    result.warnings.push(format!(
+                        "策略组 {} 的成员 {} 未导入，已移除",
+                        group.name, member
+                    ));
+                }
+                keep
+            });
+        }
+        config.proxy_groups.retain(|group| {
+            let keep = !group.proxies.is_empty();
+            if !keep {
+                result
+                    .warnings
+                    .push(format!("策略组 {} 没有可用成员，已移除", group.name));
+                changed = true;
+            }
+            keep
+        });
+        if !changed {
+            break;
+        }
+    }
+
+    let defined: HashSet<&str> = config
+        .proxies
+        .iter()
+        .map(|p| p.name.as_str())
+        .chain(config.proxy_groups.iter().map(|g| g.name.as_str()))
+        .chain(BUILTIN_POLICIES.iter().copied())
+        .collect();
+    config.rules.retain(|rule| {
+        let parts: Vec<&str> = rule.split(',').collect();
+        let keep = Composer::rule_target_index(&parts).map_or(true, |i| defined.contains(parts[i]));
+        if !keep {
+            result
+                .warnings
+                .push(format!("{}（策略未导入，已移除）", rule));
+        }
+        keep
+    });
+}
+
+fn section_name(line: &str) -> Option<&str> {
+    line.trim().strip_prefix('[')?.strip_suffix(']')
+}
+
+/// 按小节遍历有效行，返回 (小写小节名, 行内容)，跳过空行和注释
+fn section_lines(content: &str) -> Vec<(String, &str)> {
+    let mut section = String::new();
+    let mut lines = Vec::new();
+    for raw in content.lines() {
+        let line = raw.trim();
+        if let Some(name) = section_name(line) {
+            section = name.trim().to_ascii_lowercase();
+            continue;
+        }
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with(';')
+            || line.starts_with("//")
+        {
+            continue;
+        }
+        lines.push((section.clone(), line));
+    }
+    lines
+}
+
+/// 拆分逗号分隔的参数，`key=value` 放入 map，其余按顺序放入位置参数
+fn split_params(text: &str) -> (Vec<String>, HashMap<String, String>) {
+    let mut positional = Vec::new();
+    let mut named = HashMap::new();
+    for part in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('=') {
+            Some((key, value)) => {
+                named.insert(
+                    key.trim().to_ascii_lowercase(),
+                    value.trim().trim_matches('"').to_string(),
+                );
+            }
+            None => positional.push(part.trim_matches('"').to_string()),
+        }
+    }
+    (positional, named)
+}
+
+fn is_true(value: Option<&String>) -> bool {
+    value.is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
+fn parse_port(port: &str) -> Result<u16, String> {
+    port.trim()
+        .parse()
+        .map_err(|_| format!("端口无效: {}", port))
+}
+
+fn new_proxy(name: &str, proxy_type: &str, server: &str, port: u16) -> ProxyConfig {
+    ProxyConfig {
+        name: name.to_string(),
+        proxy_type: proxy_type.to_string(),
+        server: server.to_string(),
+        port,
+        cipher: None,
+        password: None,
+        uuid: None,
+        alter_id: None,
+        network: None,
+        tls: None,
+        skip_cert_verify: None,
+        sni: None,
+        udp: false,
+        extra: HashMap::new(),
+    }
+}
+
+fn set_extra(proxy: &mut ProxyConfig, key: &str, value: impl Into<serde_yaml::Value>) {
+    proxy.extra.insert(key.to_string(), value.into());
+}
+
+/// 设置 TLS 服务器名称：vmess / vless 使用 `servername`，其他类型使用 `sni`
+fn set_server_name(proxy: &mut ProxyConfig, name: Option<&String>) {
+    let Some(name) = name else {
+        return;
+    };
+    if matches!(proxy.proxy_type.as_str(), "vmess" | "vless") {
+        set_extra(proxy, "servername", name.as_str());
+    } else {
+        proxy.sni = Some(name.clone());
+    }
+}
+
+/// 设置 WebSocket 传输参数
+fn set_ws_opts(proxy: &mut ProxyConfig, path: Option<&String>, host: Option<&String>) {
+    proxy.network = Some("ws".to_string());
+    let mut opts = serde_yaml::Mapping::new();
+    if let Some(path) = path {
+        opts.insert("path".into(), path.as_str().into());
+    }
+    if let Some(host) = host {
+        let mut headers = serde_yaml::Mapping::new();
+        headers.insert("Host".into(), host.as_str().into());
+        opts.insert("headers".into(), headers.into());
+    }
+    if !opts.is_empty() {
+        set_extra(proxy, "ws-opts", opts);
+    }
+}
+
+/// Surge 节点：`名称 = 类型, 服务器, 端口, 参数...`
+fn surge_proxy(line: &str) -> Result<ProxyConfig, String> {
+    let (name, rest) = line.split_once('=').ok_or("格式不正确")?;
+    let name = name.trim();
+    let (positional, params) = split_params(rest);
+    let kind = positional
+        .first()
+        .map(|k| k.to_ascii_lowercase())
+        .ok_or("缺少节点类型")?;
+    if matches!(kind.as_str(), "direct" | "reject" | "reject-tinygif") {
+        return Err("内置策略无需导入".to_string());
+    }
+    let server = positional.get(1).ok_or("缺少服务器地址")?;
+    let port = parse_port(positional.get(2).ok_or("缺少端口")?)?;
+    let user = params.get("username").or(positional.get(3));
+    let pass = params.get("password").or(positional.get(4));
+
+    let mut proxy = match kind.as_str() {
+        "ss" | "shadowsocks" => {
+            let mut proxy = new_proxy(name, "ss", server, port);
+            proxy.cipher = Some(params.get("encrypt-method").ok_or("缺少加密方式")?.clone());
+            proxy.password = Some(params.get("password").ok_or("缺少密码")?.clone());
+            if let Some(obfs) = params.get("obfs") {
+                let mut opts = serde_yaml::Mapping::new();
+                opts.insert("mode".into(), obfs.as_str().into());
+                if let Some(host) = params.get("obfs-host") {
+                    opts.insert("host".into(), host.as_str().into());
+                }
+                set_extra(&mut proxy, "plugin", "obfs");
+                set_extra(&mut proxy, "plugin-opts", opts);
+            }
+            proxy
+        }
+        "vmess" => {
+            let mut proxy = new_proxy(name, "vmess", server, port);
+            proxy.uuid = Some(user.ok_or("缺少 UUID")?.clone());
+            proxy.alter_id = Some(0);
+            proxy.cipher = Some("auto".to_string());
+            if is_true(params.get("ws")) {
+                let host = params
+                    .get("ws-headers")
+                    .and_then(|h| h.split('|').find_map(|kv| kv.strip_prefix("Host:")))
+                    .map(|h| h.trim().to_string());
+                set_ws_opts(&mut proxy, params.get("ws-path"), host.as_ref());
+            }
+            proxy.tls = is_true(params.get("tls")).then_some(true);
+            proxy
+        }
+        "trojan" => {
+            let mut proxy = new_proxy(name, "trojan", server, port);
+            proxy.password = Some(params.get("password").ok_or("缺少密码")?.clone());
+            proxy
+        }
+        "http" | "https" => {
+            let mut proxy = new_proxy(name, "http", server, port);
+            proxy.tls = (kind == "https").then_some(true);
+            proxy
+        }
+        "socks5" | "socks5-tls" => {
+            let mut proxy = new_proxy(name, "socks5", server, port);
+            proxy.tls = (kind == "socks5-tls").then_some(true);
+            proxy
+        }
+        other => return Err(format!("不支持的节点类型 {}", other)),
+    };
+
+    if matches!(proxy.proxy_type.as_str(), "http" | "socks5") {
+        if let (Some(user), Some(pass)) = (user, pass) {
+            set_extra(&mut proxy, "username", user.as_str());
+            set_extra(&mut proxy, "password", pass.as_str());
+        }
+    }
+    set_server_name(&mut proxy, params.get("sni"));
+    if is_true(params.get("skip-cert-verify")) {
+        proxy.skip_cert_verify = Some(true);
+    }
+    proxy.udp = is_true(params.get("udp-relay"));
+    Ok(proxy)
+}
+
+/// Surge 策略组：`名称 = 类型, 成员..., 参数...`
+fn surge_group(line: &str) -> Result<ProxyGroupConfig, String> {
+    let (name, rest) = line.split_once('=').ok_or("格式不正确")?;
+    let (mut positional, params) = split_params(rest);
+    if positional.is_empty() {
+        return Err("缺少策略组类型".to_string());
+    }
+    let kind = positional.remove(0).to_ascii_lowercase();
+    let group_type = match kind.as_str() {
+        "select" | "url-test" | "fallback" | "load-balance" => kind,
+        other => return Err(format!("不支持的策略组类型 {}", other)),
+    };
+    if params.contains_key("policy-path") {
+        return Err("不支持外部策略列表 policy-path".to_string());
+    }
+    if positional.is_empty() {
+        return Err("策略组没有成员".to_string());
+    }
+
+    let mut group = ProxyGroupConfig {
+        name: name.trim().to_string(),
+        group_type,
+        proxies: positional.iter().map(|p| map_policy(p)).collect(),
+        ..Default::default()
+    };
+    if group.group_type != "select" {
+        group.url = params.get("url").cloned();
+        group.interval = params.get("interval").and_then(|v| v.parse().ok());
+        group.tolerance = params.get("tolerance").and_then(|v| v.parse().ok());
+    }
+    Ok(group)
+}
+
+/// Surge 规则：`类型,值,策略[,no-resolve]`，`FINAL,策略` 转为 `MATCH`
+fn surge_rule(line: &str) -> Result<String, String> {
+    let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+    let kind = parts[0].to_ascii_uppercase();
+    if kind == "FINAL" {
+        let policy = parts.get(1).ok_or("缺少策略")?;
+        return Ok(format!("MATCH,{}", map_policy(policy)));
+    }
+    let mapped = SURGE_RULE_TYPES
+        .iter()
+        .find(|(surge, _)| *surge == kind)
+        .map(|(_, mihomo)| *mihomo)
+        .ok_or_else(|| format!("不支持的规则类型 {}", parts[0]))?;
+    if kind == "SRC-IP" {
+        // Surge 的 SRC-IP 是单个地址，mihomo 的 SRC-IP-CIDR 需要前缀长度
+        let mut parts = parts.clone();
+        let address = parts.get(1).copied().unwrap_or_default();
+        let cidr = match (address.contains('/'), address.contains(':')) {
+            (true, _) => address.to_string(),
+            (false, true) => format!("{}/128", address),
+            (false, false) => format!("{}/32", address),
+        };
+        if parts.len() > 1 {
+            parts[1] = &cidr;
+        }
+        return build_rule(mapped, &parts[1..]);
+    }
+    build_rule(mapped, &parts[1..])
+}
+
+/// QuantumultX 节点：`类型=服务器:端口, 参数..., tag=名称`
+///
+/// 不支持的混淆方式不会阻止导入，但会在 `warnings` 中记录，因为节点很可能无法连接。
+fn quantumult_proxy(line: &str, warnings: &mut Vec<String>) -> Result<ProxyConfig, String> {
+    let (kind, rest) = line.split_once('=').ok_or("格式不正确")?;
+    let kind = kind.trim().to_ascii_lowercase();
+    let (address, params) = rest.split_once(',').unwrap_or((rest, ""));
+    let (server, port) = address.trim().rsplit_once(':').ok_or("缺少端口")?;
+    let port = parse_port(port)?;
+    let (_, params) = split_params(params);
+    let name = params.get("tag").ok_or("缺少节点名称 tag")?;
+    let over_tls = is_true(params.get("over-tls"));
+    let obfs = params.get("obfs").map(|o| o.to_ascii_lowercase());
+
+    let mut proxy = match kind.as_str() {
+        "shadowsocks" => {
+            let mut proxy = new_proxy(name, "ss", server, port);
+            proxy.cipher = Some(params.get("method").ok_or("缺少加密方式")?.clone());
+            proxy.password = Some(params.get("password").ok_or("缺少密码")?.clone());
+            match obfs.as_deref() {
+                Some(mode @ ("http" | "tls")) => {
+                    let mut opts = serde_yaml::Mapping::new();
+                    opts.insert("mode".into(), mode.into());
+                    if let Some(host) = params.get("obfs-host") {
+                        opts.insert("host".into(), host.as_str().into());
+                    }
+                    set_extra(&mut proxy, "plugin", "obfs");
+                    set_extra(&mut proxy, "plugin-opts", opts);
+                }
+                Some(other) => {
+                    warnings.push(format!("节点 {} 的混淆方式 {} 不支持，已忽略", name, other));
+                }
+                None => {}
+            }
+            proxy
+        }
+        "vmess" => {
+            let mut proxy = new_proxy(name, "vmess", server, port);
+            proxy.uuid = Some(params.get("password").ok_or("缺少 UUID")?.clone());
+            proxy.alter_id = Some(0);
+            proxy.cipher = Some(
+                params
+                    .get("method")
+                    .cloned()
+                    .unwrap_or_else(|| "auto".to_string()),
+            );
+            match obfs.as_deref() {
+                Some(mode @ ("ws" | "wss")) => {
+                    set_ws_opts(&mut proxy, params.get("obfs-uri"), params.get("obfs-host"));
+                    proxy.tls = (mode == "wss").then_some(true);
+                }
+                Some("over-tls") | None => {}
+                Some(other) => {
+                    warnings.push(format!("节点 {} 的混淆方式 {} 不支持，已忽略", name, other));
+                }
+            }
+            if over_tls {
+                proxy.tls = Some(true);
+            }
+            proxy
+        }
+        "trojan" => {
+            let mut proxy = new_proxy(name, "trojan", server, port);
+            proxy.password = Some(params.get("password").ok_or("缺少密码")?.clone());
+            proxy
+        }
+        "http" | "socks5" => {
+            let mut proxy = new_proxy(name, &kind, server, port);
+            if let (Some(user), Some(pass)) = (params.get("username"), params.get("password")) {
+                set_extra(&mut proxy, "username", user.as_str());
+                set_extra(&mut proxy, "password", pass.as_str());
+            }
+            proxy.tls = over_tls.then_some(true);
+            proxy
+        }
+        other => return Err(format!("不支持的节点类型 {}", other)),
+    };
+
+    set_server_name(&mut proxy, params.get("tls-host"));
+    if params
+        .get("tls-verification")
+        .is_some_and(|v| v.eq_ignore_ascii_case("false"))
+    {
+        proxy.skip_cert_verify = Some(true);
+    }
+    proxy.udp = is_true(params.get("udp-relay"));
+    Ok(proxy)
+}
+
+/// QuantumultX 策略组：`类型=名称, 成员..., 参数...`
+fn quantumult_group(line: &str) -> Result<ProxyGroupConfig, String> {
+    let (kind, rest) = line.split_once('=').ok_or("格式不正确")?;
+    let group_type = match kind.trim().to_ascii_lowercase().as_str() {
+        "static" => "select",
+        "url-latency-benchmark" => "url-test",
+        "available" => "fallback",
+        "round-robin" => "load-balance",
+        other => return Err(format!("不支持的策略组类型 {}", other)),
+    };
+    let (mut positional, params) = split_params(rest);
+    if positional.len() < 2 {
+        return Err("策略组没有成员".to_string());
+    }
+    let name = positional.remove(0);
+
+    let mut group = ProxyGroupConfig {
+        name,
+        group_type: group_type.to_string(),
+        proxies: positional.iter().map(|p| map_policy(p)).collect(),
+        ..Default::default()
+    };
+    if group_type != "select" {
+        // QuantumultX 使用全局的测速地址，这里使用 mihomo 的默认地址
+        group.url = Some(DEFAULT_TEST_URL.to_string());
+        group.interval = params.get("check-interval").and_then(|v| v.parse().ok());
+        group.tolerance = params.get("tolerance").and_then(|v| v.parse().ok());
+    }
+    Ok(group)
+}
+
+/// QuantumultX 分流：`类型, 值, 策略`，`final, 策略` 转为 `MATCH`
+fn quantumult_rule(line: &str) -> Result<String, String> {
+    let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+    let kind = parts[0].to_ascii_lowercase();
+    if kind == "final" {
+        let policy = parts.get(1).ok_or("缺少策略")?;
+        return Ok(format!("MATCH,{}", map_policy(policy)));
+    }
+    let mapped = QUANTUMULT_RULE_TYPES
+        .iter()
+        .find(|(qx, _)| *qx == kind)
+        .map(|(_, mihomo)| *mihomo)
+        .ok_or_else(|| format!("不支持的规则类型 {}", parts[0]))?;
+    build_rule(mapped, &parts[1..])
+}
+
+/// 由 `值, 策略[, no-resolve]` 构造 mihomo 规则
+fn build_rule(kind: &str, parts: &[&str]) -> Result<String, String> {
+    let (value, policy) = match parts {
+        [value, policy, ..] if !value.is_empty() && !policy.is_empty() => (value, policy),
+        _ => return Err("缺少匹配值或策略".to_string()),
+    };
+    let mut rule = format!("{},{},{}", kind, value, map_policy(policy));
+    if parts[2..]
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case("no-resolve"))
+    {
+        rule.push_str(",no-resolve");
+    }
+    Ok(rule)
+}
+
+/// 内置策略名转换为 mihomo 的写法
+fn map_policy(policy: &str) -> String {
+    match policy.to_ascii_lowercase().as_str() {
+        "direct" => "DIRECT".to_string(),
+        "reject" | "reject-tinygif" | "reject-img" | "reject-dict" | "reject-array" => {
+            "REJECT".to_string()
+        }
+        "reject-drop" => "REJECT-DROP".to_string(),
+        _ => policy.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_foreign_config() {
+        let surge = "[General]\nloglevel = notify\n\n[Proxy]\n\
+            HK = ss, hk.example.com, 8388, encrypt-method=aes-128-gcm, password=pwd, udp-relay=true\n\
+            JP = vmess, jp.example.com, 443, username=uuid-1, ws=true, ws-path=/ws, tls=true, sni=cdn.example.com\n\
+            Snell = snell, 1.2.3.4, 1000, psk=x\n\n[Proxy Group]\n\
+            Proxy = select, HK, JP, Snell, DIRECT\n\
+            Auto = url-test, HK, JP, url=http://www.gstatic.com/generate_204, interval=600\n\
+            SnellOnly = select, Snell\n\n\
+            [Rule]\nDOMAIN-SUFFIX,google.com,Proxy\nIP-CIDR,10.0.0.0/8,DIRECT,no-resolve\n\
+            SRC-IP,192.168.1.2,DIRECT\nDOMAIN,snell.example.com,SnellOnly\n\
+            RULE-SET,https://example.com/list,Proxy\nFINAL,Proxy,dns-failed\n";
+        assert_eq!(detect_foreign_format(surge), Some(ForeignFormat::Surge));
+        let result = convert_foreign_config(surge).unwrap();
+        assert_eq!(result.config.proxies.len(), 2);
+        assert_eq!(
+            result.config.proxies[0].cipher.as_deref(),
+            Some("aes-128-gcm")
+        );
+        assert!(result.config.proxies[0].udp);
+        assert_eq!(result.config.proxies[1].network.as_deref(), Some("ws"));
+        assert_eq!(result.config.proxies[1].sni, None);
+        assert_eq!(
+            result.config.proxies[1].extra["servername"].as_str(),
+            Some("cdn.example.com")
+        );
+        // 引用未导入节点的成员、策略组和规则都被移除
+        assert_eq!(result.config.proxy_groups.len(), 2);
+        assert_eq!(
+            result.config.proxy_groups[0].proxies,
+            vec!["HK", "JP", "DIRECT"]
+        );
+        assert_eq!(result.config.proxy_groups[1].interval, Some(600));
+        assert_eq!(
+            result.config.rules,
+            vec![
+                "DOMAIN-SUFFIX,google.com,Proxy",
+                "IP-CIDR,10.0.0.0/8,DIRECT,no-resolve",
+                "SRC-IP-CIDR,192.168.1.2/32,DIRECT",
+                "MATCH,Proxy",
+            ]
+        );
+        assert_eq!(result.warnings.len(), 6);
+
+        let qx = "[server_local]\n\
+            trojan=tj.example.com:443, password=pwd, over-tls=true, tls-host=tj.example.com, tag=TJ\n\
+            shadowsocks=ss.example.com:8388, method=aes-128-gcm, password=pwd, obfs=ws, tag=SS\n\
+            [policy]\nstatic=Proxy, TJ, direct\n[filter_local]\n\
+            host-suffix, example.com, Proxy\nuser-agent, App*, direct\nfinal, Proxy\n";
+        assert_eq!(detect_foreign_format(qx), Some(ForeignFormat::QuantumultX));
+        let result = convert_foreign_config(qx).unwrap();
+        assert_eq!(
+            result.config.proxies[0].sni.as_deref(),
+            Some("tj.example.com")
+        );
+        assert_eq!(result.config.proxy_groups[0].proxies, vec!["TJ", "DIRECT"]);
+        assert_eq!(
+            result.config.rules,
+            vec!["DOMAIN-SUFFIX,example.com,Proxy", "MATCH,Proxy"]
+        );
+        // user-agent 规则和 ss 不支持的 obfs=ws
+        assert_eq!(result.warnings.len(), 2);
+
+        assert!(detect_foreign_format("proxies: []\n").is_none());
+    }
+}
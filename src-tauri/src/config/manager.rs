@@ -1,8 +1,13 @@
 use anyhow::Result;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-use crate::models::{AppSettings, DnsConfig, MihomoConfig, TunConfig};
+use super::Composer;
+use crate::models::{
+    AppSettings, DnsConfig, MihomoConfig, ProfileConfig, ScriptConfig, TunConfig, ValidationIssue,
+    ValidationSeverity,
+};
 use crate::utils::{get_app_settings_path, get_mihomo_config_path};
 
 /// 配置管理器
@@ -44,10 +49,9 @@ impl ConfigManager {
             log::info!("Applied default DNS config");
             changed = true;
         } else if let Some(dns) = &mut config.dns {
-            let default_dns = DnsConfig::default();
-            // 如果 nameserver-policy 为空，使用默认值
-            if dns.nameserver_policy.is_empty() && !default_dns.nameserver_policy.is_empty() {
-                dns.nameserver_policy = default_dns.nameserver_policy;
+            // 只在没有 nameserver-policy 时使用默认值，保留用户清空后的空策略
+            if dns.nameserver_policy.is_none() {
+                dns.nameserver_policy = DnsConfig::default().nameserver_policy;
                 log::info!("Applied default nameserver-policy to existing config");
                 changed = true;
             }
@@ -61,15 +65,20 @@ impl ConfigManager {
         }
 
         // ============================================================================
-        // 禁用 MiHomo 核心自动下载 GEO 资源（由 Conflux 统一管理）
+        // 禁用 MiHomo 核心自动下载 GEO 资源（默认由 Conflux 统一管理）
         //
         // 目标：
         // - 禁止核心在任何平台/模式下自行从网络拉取 GeoIP/GeoSite/MMDB/ASN
         // - 避免权限/路径差异导致核心写入失败或不可控更新
         //
         // Conflux 会在应用数据目录下载这些文件，并通过 reload_configs 触发核心重载。
+        // 用户在设置中选择由核心自动更新（geoAutoUpdate）时保留核心的配置。
         // ============================================================================
-        if config.geo_auto_update {
+        let core_owns_geo = self
+            .load_app_settings()
+            .map(|settings| settings.mihomo.geo_auto_update)
+            .unwrap_or(false);
+        if config.geo_auto_update && !core_owns_geo {
             log::info!("Disabling geo-auto-update (managed by Conflux)");
             config.geo_auto_update = false;
             changed = true;
@@ -79,7 +88,7 @@ impl ConfigManager {
             config.geox_url = None;
             changed = true;
         }
-        if config.geo_update_interval.is_some() {
+        if !config.geo_auto_update && config.geo_update_interval.is_some() {
             // 禁用自动更新时 interval 无意义，清空避免误解
             config.geo_update_interval = None;
             changed = true;
@@ -245,13 +254,101 @@ impl ConfigManager {
 
     /// 验证 MiHomo 配置
     ///
-    /// 注意：大部分验证由 mihomo 核心处理，这里只做基础检查
-    pub fn validate_mihomo_config(&self, _config: &MihomoConfig) -> Result<bool> {
-        // 端口、模式、日志级别等都由 UI 下拉选择控制，无需额外验证
-        // 即使配置文件被手动修改，mihomo 核心也会处理无效值
+    /// 只有 `Error` 级别的问题会导致失败，多个错误合并为一条消息。
+    /// 需要逐项定位时使用 `collect_validation_issues`。
+    pub fn validate_mihomo_config(&self, config: &MihomoConfig) -> Result<bool> {
+        let errors: Vec<String> = self
+            .collect_validation_issues(config)
+            .into_iter()
+            .filter(|issue| issue.severity == ValidationSeverity::Error)
+            .map(|issue| issue.message)
+            .collect();
+
+        if !errors.is_empty() {
+            anyhow::bail!(errors.join("；"));
+        }
         Ok(true)
     }
 
+    /// 逐项检查 MiHomo 配置，返回带字段路径的问题列表
+    ///
+    /// 注意：大部分验证由 mihomo 核心处理，这里只检查会导致核心拒绝加载
+    /// 或明显不符合预期的情况：端口冲突、脚本配置，以及 `Composer::validation_issues`
+    /// 中的节点、策略组和规则引用检查。
+    pub fn collect_validation_issues(&self, config: &MihomoConfig) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        // 端口：0 表示关闭该入站，不参与冲突检查
+        let ports = [
+            ("port", config.port),
+            ("socks-port", config.socks_port),
+            ("mixed-port", config.mixed_port),
+        ];
+        let mut used_ports: HashMap<u16, &str> = HashMap::new();
+        for (field, port) in ports {
+            let Some(port) = port.filter(|p| *p != 0) else {
+                continue;
+            };
+            if let Some(other) = used_ports.get(&port) {
+                issues.push(ValidationIssue::error(
+                    field,
+                    format!("{} 与 {} 使用了相同的端口 {}", field, other, port),
+                ));
+            } else {
+                used_ports.insert(port, field);
+            }
+            if port < 1024 && !cfg!(windows) {
+                issues.push(ValidationIssue::warning(
+                    field,
+                    format!("{} 使用特权端口 {}，可能需要管理员权限", field, port),
+                ));
+            }
+        }
+
+        let controller_port = config
+            .external_controller
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok());
+        if let Some(port) = controller_port {
+            if let Some(other) = used_ports.get(&port) {
+                issues.push(ValidationIssue::error(
+                    "external-controller",
+                    format!("external-controller 与 {} 使用了相同的端口 {}", other, port),
+                ));
+            }
+        }
+
+        // 节点、策略组和规则的引用检查与导入订阅时共用
+        let profile = ProfileConfig {
+            proxies: config.proxies.clone(),
+            proxy_groups: config.proxy_groups.clone(),
+            proxy_providers: config.proxy_providers.clone(),
+            rule_providers: config.rule_providers.clone(),
+            rules: config.rules.clone(),
+            sub_rules: config.sub_rules.clone(),
+        };
+        issues.extend(Composer::validation_issues(&profile));
+
+        if config.mode == "script" && !config.script.as_ref().is_some_and(ScriptConfig::has_code) {
+            issues.push(ValidationIssue::error(
+                "script.code",
+                "script 模式需要配置脚本代码",
+            ));
+        }
+        if let Some(script) = &config.script {
+            for (name, expr) in &script.shortcuts {
+                if name.trim().is_empty() || expr.trim().is_empty() {
+                    issues.push(ValidationIssue::error(
+                        format!("script.shortcuts.{}", name),
+                        "脚本快捷方式的名称和表达式不能为空",
+                    ));
+                }
+            }
+        }
+
+        issues
+    }
+
     /// 加载应用设置
     pub fn load_app_settings(&self) -> Result<AppSettings> {
         if !self.app_settings_path.exists() {
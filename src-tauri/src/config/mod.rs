@@ -1,7 +1,23 @@
+mod circuit_breaker;
 mod composer;
+mod foreign_import;
 mod manager;
+mod rule_matcher;
+mod rule_templates;
+mod security_audit;
+mod share_link;
+mod subscription_alert;
+mod watcher;
 mod workspace;
 
+pub use circuit_breaker::*;
 pub use composer::*;
+pub use foreign_import::*;
 pub use manager::*;
+pub use rule_matcher::*;
+pub use rule_templates::*;
+pub use security_audit::*;
+pub use share_link::*;
+pub use subscription_alert::*;
+pub use watcher::*;
 pub use workspace::*;
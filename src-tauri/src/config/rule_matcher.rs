@@ -0,0 +1,331 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::net::IpAddr;
+
+use crate::models::ConnectionMetadata;
+
+/// 规则的匹配条件（只包含能根据连接元数据在本地判断的类型）
+#[derive(Debug, Clone)]
+enum RuleCondition {
+    Domain(String),
+    DomainSuffix(String),
+    DomainKeyword(String),
+    DomainRegex(Regex),
+    IpCidr {
+        source: bool,
+        cidr: IpCidr,
+    },
+    Port {
+        source: bool,
+        ranges: Vec<(u16, u16)>,
+    },
+    ProcessName(String),
+    ProcessPath(String),
+    Network(String),
+    Match,
+}
+
+/// CIDR 网段，如 `198.18.0.1/16`（地址部分不要求是网络地址）
+#[derive(Debug, Clone, Copy)]
+pub struct IpCidr {
+    pub addr: IpAddr,
+    pub prefix: u8,
+}
+
+impl IpCidr {
+    pub fn parse(value: &str) -> Result<Self> {
+        let (addr, prefix) = value
+            .split_once('/')
+            .ok_or_else(|| anyhow!("无效的 CIDR: {}", value))?;
+        let addr: IpAddr = addr.parse().map_err(|_| anyhow!("无效的 IP: {}", addr))?;
+        let prefix: u8 = prefix
+            .parse()
+            .map_err(|_| anyhow!("无效的前缀长度: {}", prefix))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(anyhow!("无效的前缀长度: {}", prefix));
+        }
+        Ok(Self { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+
+    /// 两个网段是否有重叠（同一地址族下，较大的网段包含较小网段的网络地址）
+    pub fn overlaps(&self, other: &IpCidr) -> bool {
+        let (wide, narrow) = if self.prefix <= other.prefix {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        wide.contains(narrow.addr)
+    }
+}
+
+/// 解析后的单条规则
+#[derive(Debug, Clone)]
+pub struct RuleMatcher {
+    condition: RuleCondition,
+    /// 规则目标（策略组或内置策略），规则未写目标时为空
+    pub target: String,
+}
+
+impl RuleMatcher {
+    /// 解析规则文本，如 `DOMAIN-SUFFIX,example.com,REJECT`
+    ///
+    /// 依赖外部数据的规则（GEOIP、GEOSITE、RULE-SET、逻辑规则等）无法在本地判断，返回错误。
+    pub fn parse(rule: &str) -> Result<Self> {
+        let parts: Vec<&str> = rule.split(',').map(str::trim).collect();
+        let rule_type = parts[0].to_ascii_uppercase();
+
+        if rule_type == "MATCH" {
+            return Ok(Self {
+                condition: RuleCondition::Match,
+                target: parts.get(1).copied().unwrap_or_default().to_string(),
+            });
+        }
+
+        let payload = parts
+            .get(1)
+            .copied()
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow!("规则缺少匹配内容: {}", rule))?;
+        let target = parts
+            .get(2)
+            .copied()
+            .filter(|t| !matches!(*t, "no-resolve" | "src"))
+            .unwrap_or_default()
+            .to_string();
+
+        let condition = match rule_type.as_str() {
+            "DOMAIN" => RuleCondition::Domain(payload.to_ascii_lowercase()),
+            "DOMAIN-SUFFIX" => RuleCondition::DomainSuffix(payload.to_ascii_lowercase()),
+            "DOMAIN-KEYWORD" => RuleCondition::DomainKeyword(payload.to_ascii_lowercase()),
+            "DOMAIN-REGEX" => RuleCondition::DomainRegex(
+                Regex::new(payload).map_err(|e| anyhow!("无效的正则表达式: {}", e))?,
+            ),
+            "IP-CIDR" | "IP-CIDR6" | "SRC-IP-CIDR" => RuleCondition::IpCidr {
+                source: rule_type == "SRC-IP-CIDR",
+                cidr: IpCidr::parse(payload)?,
+            },
+            "DST-PORT" | "SRC-PORT" => RuleCondition::Port {
+                source: rule_type == "SRC-PORT",
+                ranges: parse_port_ranges(payload)?,
+            },
+            "PROCESS-NAME" => RuleCondition::ProcessName(payload.to_string()),
+            "PROCESS-PATH" => RuleCondition::ProcessPath(payload.to_string()),
+            "NETWORK" => RuleCondition::Network(payload.to_ascii_lowercase()),
+            other => return Err(anyhow!("无法在本地模拟 {} 规则", other)),
+        };
+
+        Ok(Self { condition, target })
+    }
+
+    /// 连接是否匹配该规则
+    pub fn matches(&self, meta: &ConnectionMetadata) -> bool {
+        let host = meta.host.to_ascii_lowercase();
+        match &self.condition {
+            RuleCondition::Domain(domain) => host == *domain,
+            RuleCondition::DomainSuffix(suffix) => {
+                host == *suffix
+                    || host
+                        .strip_suffix(suffix.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            }
+            RuleCondition::DomainKeyword(keyword) => host.contains(keyword.as_str()),
+            RuleCondition::DomainRegex(regex) => !host.is_empty() && regex.is_match(&host),
+            RuleCondition::IpCidr { source, cidr } => {
+                let ip = if *source {
+                    &meta.source_ip
+                } else {
+                    &meta.destination_ip
+                };
+                ip.parse().is_ok_and(|ip| cidr.contains(ip))
+            }
+            RuleCondition::Port { source, ranges } => {
+                let port = if *source {
+                    &meta.source_port
+                } else {
+                    &meta.destination_port
+                };
+                port.parse::<u16>()
+                    .is_ok_and(|port| ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&port)))
+            }
+            RuleCondition::ProcessName(name) => meta.process.as_deref() == Some(name.as_str()),
+            RuleCondition::ProcessPath(path) => meta.process_path.as_deref() == Some(path.as_str()),
+            RuleCondition::Network(network) => meta.network.eq_ignore_ascii_case(network),
+            RuleCondition::Match => true,
+        }
+    }
+
+    /// 该规则能否命中 `other` 能命中的所有连接（同类匹配条件且范围更大或相同）
+    ///
+    /// 为 true 时排在后面的 `other` 永远不会被命中。只比较同一类匹配条件，
+    /// 正则规则无法静态比较，不视为覆盖。
+    pub fn subsumes(&self, other: &RuleMatcher) -> bool {
+        let covers_domain = |suffix: &str, domain: &str| {
+            domain == suffix
+                || domain
+                    .strip_suffix(suffix)
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        };
+        match (&self.condition, &other.condition) {
+            (RuleCondition::Match, _) => true,
+            (RuleCondition::Domain(a), RuleCondition::Domain(b)) => a == b,
+            (
+                RuleCondition::DomainSuffix(a),
+                RuleCondition::Domain(b) | RuleCondition::DomainSuffix(b),
+            ) => covers_domain(a, b),
+            // 后缀规则命中的域名都以后缀结尾，后缀包含关键字即被覆盖
+            (
+                RuleCondition::DomainKeyword(a),
+                RuleCondition::Domain(b)
+                | RuleCondition::DomainSuffix(b)
+                | RuleCondition::DomainKeyword(b),
+            ) => b.contains(a.as_str()),
+            (
+                RuleCondition::IpCidr {
+                    source: sa,
+                    cidr: a,
+                },
+                RuleCondition::IpCidr {
+                    source: sb,
+                    cidr: b,
+                },
+            ) => sa == sb && a.prefix <= b.prefix && a.contains(b.addr),
+            (
+                RuleCondition::Port {
+                    source: sa,
+                    ranges: a,
+                },
+                RuleCondition::Port {
+                    source: sb,
+                    ranges: b,
+                },
+            ) => {
+                sa == sb
+                    && b.iter()
+                        .all(|(lo, hi)| a.iter().any(|(alo, ahi)| alo <= lo && hi <= ahi))
+            }
+            (RuleCondition::ProcessName(a), RuleCondition::ProcessName(b))
+            | (RuleCondition::ProcessPath(a), RuleCondition::ProcessPath(b)) => a == b,
+            (RuleCondition::Network(a), RuleCondition::Network(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// 解析端口列表，如 `443`、`8000-9000`、`80/443`
+fn parse_port_ranges(value: &str) -> Result<Vec<(u16, u16)>> {
+    let parse = |s: &str| {
+        s.trim()
+            .parse::<u16>()
+            .map_err(|_| anyhow!("无效的端口: {}", s))
+    };
+    value
+        .split('/')
+        .map(|part| match part.split_once('-') {
+            Some((lo, hi)) => Ok((parse(lo)?, parse(hi)?)),
+            None => parse(part).map(|port| (port, port)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(host: &str, ip: &str, port: &str) -> ConnectionMetadata {
+        ConnectionMetadata {
+            network: "tcp".to_string(),
+            conn_type: "HTTP".to_string(),
+            source_ip: "192.168.1.2".to_string(),
+            destination_ip: ip.to_string(),
+            source_port: "50000".to_string(),
+            destination_port: port.to_string(),
+            host: host.to_string(),
+            dns_mode: "normal".to_string(),
+            process: None,
+            process_path: None,
+        }
+    }
+
+    #[test]
+    fn test_rule_matcher() {
+        let conn = meta("www.Example.com", "93.184.216.34", "443");
+
+        let rule = RuleMatcher::parse("DOMAIN-SUFFIX,example.com,REJECT").unwrap();
+        assert_eq!(rule.target, "REJECT");
+        assert!(rule.matches(&conn));
+        assert!(!RuleMatcher::parse("DOMAIN-SUFFIX,ample.com,REJECT")
+            .unwrap()
+            .matches(&conn));
+        assert!(
+            RuleMatcher::parse("IP-CIDR,93.184.0.0/16,DIRECT,no-resolve")
+                .unwrap()
+                .matches(&conn)
+        );
+        assert!(RuleMatcher::parse("DST-PORT,80/400-500,DIRECT")
+            .unwrap()
+            .matches(&conn));
+        assert!(RuleMatcher::parse("GEOIP,CN,DIRECT").is_err());
+
+        let subsumes = |a: &str, b: &str| {
+            RuleMatcher::parse(a)
+                .unwrap()
+                .subsumes(&RuleMatcher::parse(b).unwrap())
+        };
+        assert!(subsumes(
+            "DOMAIN-SUFFIX,google.com,A",
+            "DOMAIN,mail.google.com,B"
+        ));
+        assert!(subsumes(
+            "DOMAIN-SUFFIX,google.com,A",
+            "DOMAIN-SUFFIX,google.com,B"
+        ));
+        assert!(!subsumes(
+            "DOMAIN-SUFFIX,gle.com,A",
+            "DOMAIN-SUFFIX,google.com,B"
+        ));
+        assert!(!subsumes(
+            "DOMAIN,google.com,A",
+            "DOMAIN-SUFFIX,google.com,B"
+        ));
+        assert!(subsumes(
+            "DOMAIN-KEYWORD,goog,A",
+            "DOMAIN-SUFFIX,google.com,B"
+        ));
+        assert!(subsumes("IP-CIDR,10.0.0.0/8,A", "IP-CIDR,10.1.0.0/16,B"));
+        assert!(!subsumes("IP-CIDR,10.1.0.0/16,A", "IP-CIDR,10.0.0.0/8,B"));
+        assert!(!subsumes(
+            "SRC-IP-CIDR,10.0.0.0/8,A",
+            "IP-CIDR,10.1.0.0/16,B"
+        ));
+        assert!(subsumes("DST-PORT,400-500,A", "DST-PORT,443,B"));
+        assert!(subsumes("MATCH,A", "DOMAIN,example.com,B"));
+    }
+
+    #[test]
+    fn test_ip_cidr_overlaps() {
+        let cidr = |v: &str| IpCidr::parse(v).unwrap();
+        assert!(cidr("198.18.0.1/16").overlaps(&cidr("198.18.4.2/24")));
+        assert!(cidr("192.168.1.20/24").overlaps(&cidr("192.168.0.0/16")));
+        assert!(!cidr("198.18.0.1/16").overlaps(&cidr("198.19.0.1/16")));
+        assert!(!cidr("10.0.0.0/8").overlaps(&cidr("::1/128")));
+    }
+}
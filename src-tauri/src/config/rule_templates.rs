@@ -0,0 +1,92 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use super::Composer;
+use crate::models::ProfileConfig;
+
+/// 内置规则模板（YAML 编译进程序，位于 `resources/rule-templates`）
+struct BundledTemplate {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    yaml: &'static str,
+}
+
+const BUNDLED_TEMPLATES: &[BundledTemplate] = &[
+    BundledTemplate {
+        id: "global",
+        name: "全局代理",
+        description: "除局域网外的流量都走代理",
+        yaml: include_str!("../../resources/rule-templates/global.yaml"),
+    },
+    BundledTemplate {
+        id: "china-direct",
+        name: "国内直连",
+        description: "中国大陆域名和 IP 直连，其余走代理",
+        yaml: include_str!("../../resources/rule-templates/china-direct.yaml"),
+    },
+    BundledTemplate {
+        id: "adblock-china-direct",
+        name: "广告拦截 + 国内直连",
+        description: "拦截广告域名，中国大陆域名和 IP 直连，其余走代理",
+        yaml: include_str!("../../resources/rule-templates/adblock-china-direct.yaml"),
+    },
+];
+
+/// 内置规则模板信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTemplateInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub rule_count: usize,
+    pub rule_provider_count: usize,
+}
+
+/// 列出内置规则模板
+pub fn list_rule_templates() -> Vec<RuleTemplateInfo> {
+    BUNDLED_TEMPLATES
+        .iter()
+        .filter_map(|template| {
+            let config = Composer::parse_yaml(template.yaml).ok()?;
+            Some(RuleTemplateInfo {
+                id: template.id,
+                name: template.name,
+                description: template.description,
+                rule_count: config.rules.len(),
+                rule_provider_count: config.rule_providers.len(),
+            })
+        })
+        .collect()
+}
+
+/// 加载内置规则模板（规则、规则源和策略组）
+pub fn load_rule_template(id: &str) -> Result<ProfileConfig> {
+    let template = BUNDLED_TEMPLATES
+        .iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| anyhow!("规则模板不存在: {}", id))?;
+    Composer::parse_yaml(template.yaml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_templates_parse() {
+        assert_eq!(list_rule_templates().len(), BUNDLED_TEMPLATES.len());
+        for template in BUNDLED_TEMPLATES {
+            let config = load_rule_template(template.id).unwrap();
+            assert!(config.rules.last().unwrap().starts_with("MATCH,"));
+            assert_eq!(
+                Composer::validate(&config),
+                vec!["配置中没有代理节点或代理源".to_string()],
+                "{}",
+                template.id
+            );
+        }
+        assert!(load_rule_template("missing").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::net::IpAddr;
+
+use crate::models::{ProxyConfig, SecurityFinding, SecuritySeverity};
+
+/// 已不推荐使用的 Shadowsocks 流加密（非 AEAD，可被主动探测）
+const WEAK_SS_CIPHERS: &[&str] = &[
+    "rc4",
+    "rc4-md5",
+    "aes-128-cfb",
+    "aes-192-cfb",
+    "aes-256-cfb",
+    "aes-128-ctr",
+    "aes-192-ctr",
+    "aes-256-ctr",
+    "bf-cfb",
+    "camellia-128-cfb",
+    "camellia-192-cfb",
+    "camellia-256-cfb",
+    "chacha20",
+    "chacha20-ietf",
+    "salsa20",
+    "xchacha20",
+];
+
+/// 总是使用 TLS 的协议
+const TLS_PROTOCOLS: &[&str] = &["trojan", "hysteria", "hysteria2", "tuic", "anytls"];
+
+/// 检查节点中的不安全配置（只读，不修改配置）
+///
+/// - `skip-cert-verify: true`：不校验证书，可被中间人攻击
+/// - 弱加密：Shadowsocks 流加密、`none` / `plain`
+/// - 明文传输：未启用 TLS 的 http / socks5 / vless，以及不加密的 vmess
+/// - TLS 节点服务器为 IP 且未设置 SNI：证书通常无法匹配，往往只能跳过校验
+pub fn audit_proxies(proxies: &[ProxyConfig]) -> Vec<SecurityFinding> {
+    let mut findings = Vec::new();
+    for proxy in proxies {
+        audit_proxy(proxy, &mut findings);
+    }
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    findings
+}
+
+fn audit_proxy(proxy: &ProxyConfig, findings: &mut Vec<SecurityFinding>) {
+    let mut push = |severity: SecuritySeverity, kind: &str, message: String| {
+        findings.push(SecurityFinding {
+            proxy: proxy.name.clone(),
+            severity,
+            kind: kind.to_string(),
+            message,
+        });
+    };
+
+    let proxy_type = proxy.proxy_type.to_ascii_lowercase();
+    let cipher = proxy.cipher.as_deref().map(str::to_ascii_lowercase);
+    let uses_tls = proxy.tls == Some(true) || TLS_PROTOCOLS.contains(&proxy_type.as_str());
+    let uses_reality = proxy.extra.contains_key("reality-opts");
+
+    if proxy.skip_cert_verify == Some(true) {
+        push(
+            SecuritySeverity::High,
+            "skip-cert-verify",
+            "已关闭证书校验（skip-cert-verify），流量可能被中间人解密".to_string(),
+        );
+    }
+
+    match (proxy_type.as_str(), cipher.as_deref()) {
+        ("ss", Some("none" | "plain")) => push(
+            SecuritySeverity::High,
+            "plaintext",
+            "Shadowsocks 未加密，流量以明文传输".to_string(),
+        ),
+        ("ss", Some(c)) if WEAK_SS_CIPHERS.contains(&c) => push(
+            SecuritySeverity::Medium,
+            "weak-cipher",
+            format!("加密方式 {} 已不推荐使用，建议改用 AEAD 或 2022 加密", c),
+        ),
+        ("ssr", _) => push(
+            SecuritySeverity::Medium,
+            "weak-cipher",
+            "ShadowsocksR 已停止维护，加密强度不足".to_string(),
+        ),
+        ("vmess", Some("none" | "zero")) if !uses_tls => push(
+            SecuritySeverity::High,
+            "plaintext",
+            "VMess 未加密且未启用 TLS，流量以明文传输".to_string(),
+        ),
+        ("http" | "socks5" | "vless", _) if !uses_tls && !uses_reality => push(
+            SecuritySeverity::High,
+            "plaintext",
+            format!("{} 节点未启用 TLS，流量以明文传输", proxy.proxy_type),
+        ),
+        _ => {}
+    }
+
+    let has_sni = proxy.sni.is_some()
+        || proxy.extra.contains_key("servername")
+        || proxy.extra.contains_key("peer");
+    if uses_tls && !uses_reality && !has_sni && proxy.server.parse::<IpAddr>().is_ok() {
+        push(
+            SecuritySeverity::Low,
+            "missing-sni",
+            "TLS 节点服务器为 IP 地址且未设置 SNI，证书校验通常无法通过".to_string(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Composer;
+
+    #[test]
+    fn test_audit_proxies() {
+        let config = Composer::parse_yaml(
+            r"
+proxies:
+  - { name: ok, type: trojan, server: a.example.com, port: 443, password: x }
+  - { name: insecure, type: trojan, server: 1.2.3.4, port: 443, password: x, skip-cert-verify: true }
+  - { name: weak, type: ss, server: b.example.com, port: 1, cipher: aes-256-cfb, password: x }
+  - { name: plain, type: vless, server: c.example.com, port: 80, uuid: x }
+",
+        )
+        .unwrap();
+
+        let findings = audit_proxies(&config.proxies);
+        let kinds: Vec<(&str, &str)> = findings
+            .iter()
+            .map(|f| (f.proxy.as_str(), f.kind.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("insecure", "skip-cert-verify"),
+                ("plain", "plaintext"),
+                ("weak", "weak-cipher"),
+                ("insecure", "missing-sni"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use serde_json::json;
+
+use crate::models::ProxyConfig;
+
+/// 将节点转换为分享链接（`ss://`、`vmess://`、`vless://`、`trojan://`），不支持的类型返回 None
+///
+/// 生成的链接与前端 `parseProxyLink` 的解析规则对应，导出后可以再次导入。
+pub fn to_share_link(proxy: &ProxyConfig) -> Option<String> {
+    match proxy.proxy_type.to_ascii_lowercase().as_str() {
+        "ss" => ss_link(proxy),
+        "vmess" => vmess_link(proxy),
+        "vless" => vless_link(proxy),
+        "trojan" => trojan_link(proxy),
+        _ => None,
+    }
+}
+
+/// IPv6 地址需要加方括号
+fn host_port(proxy: &ProxyConfig) -> String {
+    if proxy.server.contains(':') {
+        format!("[{}]:{}", proxy.server, proxy.port)
+    } else {
+        format!("{}:{}", proxy.server, proxy.port)
+    }
+}
+
+/// 从 `ws-opts` 等传输配置中读取字段
+fn transport_opt<'a>(proxy: &'a ProxyConfig, key: &str) -> Option<&'a serde_yaml::Value> {
+    let network = proxy.network.as_deref()?;
+    proxy.extra.get(&format!("{}-opts", network))?.get(key)
+}
+
+/// TLS 服务器名称：vmess / vless 使用 `servername`，其他类型使用 `sni`
+fn server_name(proxy: &ProxyConfig) -> Option<&str> {
+    proxy
+        .extra
+        .get("servername")
+        .and_then(|v| v.as_str())
+        .or(proxy.sni.as_deref())
+        .filter(|s| !s.is_empty())
+}
+
+/// 传输层参数：(path, host, serviceName)
+///
+/// ws 读取 `path` 和 `headers.Host`，h2 读取 `path` 和 `host` 列表的第一项，
+/// grpc 读取 `grpc-service-name`。
+fn transport_params(proxy: &ProxyConfig) -> (&str, &str, &str) {
+    let str_opt = |key: &str| {
+        transport_opt(proxy, key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+    };
+    let host = transport_opt(proxy, "headers")
+        .and_then(|headers| headers.get("Host"))
+        .or_else(|| transport_opt(proxy, "host").and_then(|hosts| hosts.get(0)))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    (str_opt("path"), host, str_opt("grpc-service-name"))
+}
+
+/// URI 格式链接（vless / trojan）的传输层查询参数
+fn transport_query(proxy: &ProxyConfig, params: &mut Vec<String>) {
+    let Some(network) = proxy.network.as_deref().filter(|n| *n != "tcp") else {
+        return;
+    };
+    params.push(format!("type={}", urlencoding::encode(network)));
+
+    let (path, host, service_name) = transport_params(proxy);
+    for (key, value) in [
+        ("path", path),
+        ("host", host),
+        ("serviceName", service_name),
+    ] {
+        if !value.is_empty() {
+            params.push(format!("{}={}", key, urlencoding::encode(value)));
+        }
+    }
+}
+
+/// 将 `plugin` / `plugin-opts` 转换为 SIP002 的 `plugin` 参数（如 `obfs-local;obfs=http;obfs-host=a.com`）
+fn ss_plugin(proxy: &ProxyConfig) -> Option<String> {
+    let plugin = proxy.extra.get("plugin")?.as_str()?;
+    let opts = proxy.extra.get("plugin-opts").and_then(|v| v.as_mapping());
+    let opt = |key: &str| opts.and_then(|m| m.get(key));
+
+    let mut parts = Vec::new();
+    match plugin {
+        "obfs" => {
+            parts.push("obfs-local".to_string());
+            if let Some(mode) = opt("mode").and_then(|v| v.as_str()) {
+                parts.push(format!("obfs={}", mode));
+            }
+            if let Some(host) = opt("host").and_then(|v| v.as_str()) {
+                parts.push(format!("obfs-host={}", host));
+            }
+        }
+        _ => {
+            parts.push(plugin.to_string());
+            for (key, value) in opts.into_iter().flatten() {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                match value {
+                    serde_yaml::Value::Bool(true) => parts.push(key.to_string()),
+                    serde_yaml::Value::String(s) => parts.push(format!("{}={}", key, s)),
+                    serde_yaml::Value::Number(n) => parts.push(format!("{}={}", key, n)),
+                    _ => {}
+                }
+            }
+        }
+    }
+    Some(parts.join(";"))
+}
+
+/// SIP002 格式：`ss://base64url(method:password)@host:port/?plugin=...#name`
+fn ss_link(proxy: &ProxyConfig) -> Option<String> {
+    let cipher = proxy.cipher.as_deref()?;
+    let password = proxy.password.as_deref()?;
+    let user_info = URL_SAFE_NO_PAD.encode(format!("{}:{}", cipher, password));
+    let query = ss_plugin(proxy)
+        .map(|plugin| format!("/?plugin={}", urlencoding::encode(&plugin)))
+        .unwrap_or_default();
+    Some(format!(
+        "ss://{}@{}{}#{}",
+        user_info,
+        host_port(proxy),
+        query,
+        urlencoding::encode(&proxy.name)
+    ))
+}
+
+/// v2rayN 格式：`vmess://base64(json)`
+fn vmess_link(proxy: &ProxyConfig) -> Option<String> {
+    let uuid = proxy.uuid.as_deref()?;
+    let (path, host, service_name) = transport_params(proxy);
+    // v2rayN 的 grpc 节点在 path 中保存 serviceName
+    let path = if path.is_empty() { service_name } else { path };
+
+    let payload = json!({
+        "v": "2",
+        "ps": proxy.name,
+        "add": proxy.server,
+        "port": proxy.port.to_string(),
+        "id": uuid,
+        "aid": proxy.alter_id.unwrap_or(0).to_string(),
+        "scy": proxy.cipher.as_deref().unwrap_or("auto"),
+        "net": proxy.network.as_deref().unwrap_or("tcp"),
+        "type": "none",
+        "host": host,
+        "path": path,
+        "tls": if proxy.tls.unwrap_or(false) { "tls" } else { "" },
+        "sni": server_name(proxy).unwrap_or_default(),
+        "allowInsecure": if proxy.skip_cert_verify.unwrap_or(false) { "1" } else { "0" },
+    });
+
+    Some(format!("vmess://{}", STANDARD.encode(payload.to_string())))
+}
+
+/// `vless://uuid@host:port?encryption=none&security=tls&type=ws&path=...#name`
+fn vless_link(proxy: &ProxyConfig) -> Option<String> {
+    let uuid = proxy.uuid.as_deref()?;
+
+    let mut params = vec!["encryption=none".to_string()];
+    if proxy.tls.unwrap_or(false) {
+        params.push("security=tls".to_string());
+    }
+    if let Some(sni) = server_name(proxy) {
+        params.push(format!("sni={}", urlencoding::encode(sni)));
+    }
+    if let Some(flow) = proxy.extra.get("flow").and_then(|v| v.as_str()) {
+        params.push(format!("flow={}", urlencoding::encode(flow)));
+    }
+    if proxy.skip_cert_verify.unwrap_or(false) {
+        params.push("allowInsecure=1".to_string());
+    }
+    transport_query(proxy, &mut params);
+    if proxy.udp {
+        params.push("udp=1".to_string());
+    }
+
+    Some(format!(
+        "vless://{}@{}?{}#{}",
+        urlencoding::encode(uuid),
+        host_port(proxy),
+        params.join("&"),
+        urlencoding::encode(&proxy.name)
+    ))
+}
+
+/// `trojan://password@host:port?sni=...#name`
+fn trojan_link(proxy: &ProxyConfig) -> Option<String> {
+    let password = proxy.password.as_deref()?;
+
+    let mut params = Vec::new();
+    if let Some(sni) = proxy.sni.as_deref().filter(|s| !s.is_empty()) {
+        params.push(format!("sni={}", urlencoding::encode(sni)));
+    }
+    if proxy.skip_cert_verify.unwrap_or(false) {
+        params.push("allowInsecure=1".to_string());
+    }
+    transport_query(proxy, &mut params);
+    if proxy.udp {
+        params.push("udp=1".to_string());
+    }
+    let query = if params.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", params.join("&"))
+    };
+
+    Some(format!(
+        "trojan://{}@{}{}#{}",
+        urlencoding::encode(password),
+        host_port(proxy),
+        query,
+        urlencoding::encode(&proxy.name)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(proxy_type: &str) -> ProxyConfig {
+        ProxyConfig {
+            name: "香港 01".to_string(),
+            proxy_type: proxy_type.to_string(),
+            server: "hk.example.com".to_string(),
+            port: 443,
+            cipher: None,
+            password: None,
+            uuid: None,
+            alter_id: None,
+            network: None,
+            tls: None,
+            skip_cert_verify: None,
+            sni: None,
+            udp: false,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_share_links() {
+        let mut ss = proxy("ss");
+        ss.cipher = Some("aes-256-gcm".to_string());
+        ss.password = Some("pass".to_string());
+        let link = to_share_link(&ss).unwrap();
+        let user_info = URL_SAFE_NO_PAD.encode("aes-256-gcm:pass");
+        assert_eq!(
+            link,
+            format!(
+                "ss://{}@hk.example.com:443#%E9%A6%99%E6%B8%AF%2001",
+                user_info
+            )
+        );
+
+        let mut vmess = proxy("vmess");
+        vmess.uuid = Some("b831381d-6324-4d53-ad4f-8cda48b30811".to_string());
+        vmess.tls = Some(true);
+        let link = to_share_link(&vmess).unwrap();
+        let decoded = STANDARD
+            .decode(link.strip_prefix("vmess://").unwrap())
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&decoded).unwrap();
+        assert_eq!(payload["ps"], "香港 01");
+        assert_eq!(payload["port"], "443");
+        assert_eq!(payload["tls"], "tls");
+
+        let mut trojan = proxy("trojan");
+        trojan.password = Some("p@ss".to_string());
+        trojan.sni = Some("hk.example.com".to_string());
+        assert_eq!(
+            to_share_link(&trojan).unwrap(),
+            "trojan://p%40ss@hk.example.com:443?sni=hk.example.com#%E9%A6%99%E6%B8%AF%2001"
+        );
+
+        assert!(to_share_link(&proxy("wireguard")).is_none());
+    }
+
+    #[test]
+    fn test_share_link_transport_and_plugin() {
+        let yaml = |s: &str| serde_yaml::from_str::<serde_yaml::Value>(s).unwrap();
+
+        let mut ss = proxy("ss");
+        ss.cipher = Some("aes-256-gcm".to_string());
+        ss.password = Some("pass".to_string());
+        ss.extra.insert("plugin".to_string(), yaml("obfs"));
+        ss.extra.insert(
+            "plugin-opts".to_string(),
+            yaml("{mode: http, host: bing.com}"),
+        );
+        let link = to_share_link(&ss).unwrap();
+        assert!(link.contains("/?plugin=obfs-local%3Bobfs%3Dhttp%3Bobfs-host%3Dbing.com#"));
+
+        let mut vmess = proxy("vmess");
+        vmess.uuid = Some("b831381d-6324-4d53-ad4f-8cda48b30811".to_string());
+        vmess.network = Some("ws".to_string());
+        vmess
+            .extra
+            .insert("servername".to_string(), yaml("cdn.example.com"));
+        vmess.extra.insert(
+            "ws-opts".to_string(),
+            yaml("{path: /ws, headers: {Host: cdn.example.com}}"),
+        );
+        let link = to_share_link(&vmess).unwrap();
+        let decoded = STANDARD
+            .decode(link.strip_prefix("vmess://").unwrap())
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&decoded).unwrap();
+        assert_eq!(payload["sni"], "cdn.example.com");
+        assert_eq!(payload["path"], "/ws");
+        assert_eq!(payload["host"], "cdn.example.com");
+
+        let mut vless = proxy("vless");
+        vless.uuid = Some("b831381d-6324-4d53-ad4f-8cda48b30811".to_string());
+        vless.tls = Some(true);
+        vless.network = Some("grpc".to_string());
+        vless
+            .extra
+            .insert("grpc-opts".to_string(), yaml("{grpc-service-name: gun}"));
+        assert_eq!(
+            to_share_link(&vless).unwrap(),
+            "vless://b831381d-6324-4d53-ad4f-8cda48b30811@hk.example.com:443\
+             ?encryption=none&security=tls&type=grpc&serviceName=gun#%E9%A6%99%E6%B8%AF%2001"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::Workspace;
+use crate::models::{ProfileMetadata, ProfileType, SubscriptionAlertSettings};
+use crate::utils::get_app_data_dir;
+
+/// 检查间隔
+const SUBSCRIPTION_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 启动后首次检查前的等待时间（避开启动阶段的资源竞争）
+const SUBSCRIPTION_CHECK_DELAY: Duration = Duration::from_secs(30);
+
+/// 已提醒记录文件（跨重启保留，避免重复提醒）
+const ALERT_STATE_FILE: &str = "subscription_alerts.json";
+
+/// 提醒类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubscriptionWarningKind {
+    /// 即将到期或已到期
+    Expire,
+    /// 流量即将用尽
+    Usage,
+}
+
+/// 订阅提醒（`subscription-warning` 事件）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionWarning {
+    pub profile_id: String,
+    pub profile_name: String,
+    pub kind: SubscriptionWarningKind,
+    pub message: String,
+    /// 到期时间（Unix 秒时间戳）
+    pub expire: Option<u64>,
+    pub usage_percent: Option<f64>,
+}
+
+/// 每个 Profile 已提醒的阈值
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlertRecord {
+    /// 已针对该到期时间提醒过（续费后到期时间变化会再次提醒）
+    expire_warned: Option<u64>,
+    /// 已提醒过流量超限（用量回落到阈值以下后重置）
+    usage_warned: bool,
+}
+
+/// 检查所有远程订阅，返回当前满足提醒条件的项（不去重）
+pub fn collect_subscription_warnings(
+    settings: &SubscriptionAlertSettings,
+) -> Vec<SubscriptionWarning> {
+    let profiles = Workspace::new()
+        .and_then(|workspace| workspace.list_profiles())
+        .unwrap_or_default();
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+
+    profiles
+        .iter()
+        .filter(|p| p.profile_type == ProfileType::Remote)
+        .flat_map(|p| profile_warnings(p, settings, now))
+        .collect()
+}
+
+fn profile_warnings(
+    profile: &ProfileMetadata,
+    settings: &SubscriptionAlertSettings,
+    now: u64,
+) -> Vec<SubscriptionWarning> {
+    let Some(usage) = profile.subscription else {
+        return Vec::new();
+    };
+    let mut warnings = Vec::new();
+
+    if let Some(expire) = usage.expire {
+        let threshold = u64::from(settings.expire_days) * 24 * 60 * 60;
+        if expire <= now + threshold {
+            let message = if expire <= now {
+                format!("订阅「{}」已到期", profile.name)
+            } else {
+                let days = (expire - now).div_ceil(24 * 60 * 60);
+                format!("订阅「{}」将在 {} 天内到期", profile.name, days)
+            };
+            warnings.push(SubscriptionWarning {
+                profile_id: profile.id.clone(),
+                profile_name: profile.name.clone(),
+                kind: SubscriptionWarningKind::Expire,
+                message,
+                expire: Some(expire),
+                usage_percent: usage.usage_percent(),
+            });
+        }
+    }
+
+    if let Some(percent) = usage.usage_percent() {
+        if percent >= f64::from(settings.usage_percent) {
+            warnings.push(SubscriptionWarning {
+                profile_id: profile.id.clone(),
+                profile_name: profile.name.clone(),
+                kind: SubscriptionWarningKind::Usage,
+                message: format!("订阅「{}」已使用 {:.0}% 流量", profile.name, percent),
+                expire: usage.expire,
+                usage_percent: Some(percent),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// 启动后台检查（每天一次，应用运行期间只需启动一次）
+pub fn start_subscription_monitor(app: AppHandle) {
+    tokio::spawn(async move {
+        tokio::time::sleep(SUBSCRIPTION_CHECK_DELAY).await;
+        loop {
+            check_and_notify(&app);
+            tokio::time::sleep(SUBSCRIPTION_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// 检查订阅并发送尚未提醒过的警告
+fn check_and_notify(app: &AppHandle) {
+    let Some(state) = crate::commands::try_get_app_state() else {
+        return;
+    };
+    let settings = match state.config_manager.load_app_settings() {
+        Ok(settings) => settings.subscription_alert,
+        Err(e) => {
+            log::warn!("Failed to load settings for subscription check: {}", e);
+            return;
+        }
+    };
+    if !settings.enabled {
+        return;
+    }
+
+    let warnings = collect_subscription_warnings(&settings);
+    let previous = load_alert_records();
+
+    // 只保留当前仍满足条件的阈值，不再满足的自动重置，下次越过阈值时重新提醒
+    let mut records: HashMap<String, AlertRecord> = HashMap::new();
+    let mut fresh = Vec::new();
+    for warning in warnings {
+        let old = previous.get(&warning.profile_id);
+        let record = records.entry(warning.profile_id.clone()).or_default();
+        let is_new = match warning.kind {
+            SubscriptionWarningKind::Expire => {
+                record.expire_warned = warning.expire;
+                old.and_then(|r| r.expire_warned) != warning.expire
+            }
+            SubscriptionWarningKind::Usage => {
+                record.usage_warned = true;
+                !old.is_some_and(|r| r.usage_warned)
+            }
+        };
+        if is_new {
+            fresh.push(warning);
+        }
+    }
+    save_alert_records(&records);
+
+    for warning in fresh {
+        log::info!("Subscription warning: {}", warning.message);
+        let _ = app.emit("subscription-warning", &warning);
+    }
+}
+
+fn alert_state_path() -> Option<PathBuf> {
+    get_app_data_dir()
+        .ok()
+        .map(|dir| dir.join(ALERT_STATE_FILE))
+}
+
+fn load_alert_records() -> HashMap<String, AlertRecord> {
+    alert_state_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_alert_records(records: &HashMap<String, AlertRecord>) {
+    let Some(path) = alert_state_path() else {
+        return;
+    };
+    match serde_json::to_string_pretty(records) {
+        Ok(content) => {
+            if let Err(e) = std::fs::write(path, content) {
+                log::warn!("Failed to save subscription alert state: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize subscription alert state: {}", e),
+    }
+}
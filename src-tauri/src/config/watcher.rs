@@ -0,0 +1,209 @@
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc;
+
+use super::Workspace;
+use crate::models::ProfileType;
+
+/// 源文件变化后的静默时间，窗口内的多次写入只重新导入一次
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 原子写入（先删除/重命名再写入）期间源文件可能暂时不存在，等待它重新出现
+const MISSING_FILE_RETRIES: u32 = 10;
+const MISSING_FILE_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// 监听 LocalWatch 类型 Profile 的源文件
+///
+/// 监听的是源文件所在目录（非递归），这样编辑器或生成工具用
+/// “写临时文件再重命名”的方式替换文件时也能收到事件。
+pub struct ProfileWatcher {
+    inner: Mutex<Option<WatcherInner>>,
+    /// 每个源文件的防抖代数，只有最后一次事件对应的任务会执行重新导入
+    generations: Arc<Mutex<HashMap<PathBuf, u64>>>,
+}
+
+struct WatcherInner {
+    watcher: RecommendedWatcher,
+    watched_dirs: HashSet<PathBuf>,
+}
+
+impl ProfileWatcher {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(None),
+            generations: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 启动监听（应用运行期间只需启动一次）
+    pub fn start(&self, app: AppHandle) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+
+        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+            Ok(event) => {
+                if event.kind.is_access() {
+                    return;
+                }
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+            Err(e) => log::warn!("Profile watcher error: {}", e),
+        });
+
+        let watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                log::warn!("Failed to create profile watcher: {}", e);
+                return;
+            }
+        };
+
+        if let Ok(mut inner) = self.inner.lock() {
+            *inner = Some(WatcherInner {
+                watcher,
+                watched_dirs: HashSet::new(),
+            });
+        }
+        self.refresh();
+
+        let generations = self.generations.clone();
+        tokio::spawn(async move {
+            while let Some(path) = rx.recv().await {
+                let Some(generation) = bump_generation(&generations, &path) else {
+                    continue;
+                };
+
+                let generations = generations.clone();
+                let app = app.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(WATCH_DEBOUNCE).await;
+                    let latest = generations.lock().ok().and_then(|g| g.get(&path).copied());
+                    if latest == Some(generation) {
+                        handle_source_changed(&app, &path).await;
+                    }
+                });
+            }
+        });
+    }
+
+    /// 按当前 Profile 列表更新监听的目录
+    pub fn refresh(&self) {
+        let sources = watched_sources();
+
+        let Ok(mut guard) = self.inner.lock() else {
+            return;
+        };
+        let Some(inner) = guard.as_mut() else {
+            return;
+        };
+
+        let wanted: HashSet<PathBuf> = sources
+            .iter()
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect();
+
+        for dir in inner
+            .watched_dirs
+            .difference(&wanted)
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            let _ = inner.watcher.unwatch(&dir);
+            inner.watched_dirs.remove(&dir);
+        }
+        for dir in wanted {
+            if inner.watched_dirs.contains(&dir) {
+                continue;
+            }
+            match inner.watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    log::info!("Watching profile sources in {:?}", dir);
+                    inner.watched_dirs.insert(dir);
+                }
+                Err(e) => log::warn!("Failed to watch {:?}: {}", dir, e),
+            }
+        }
+
+        // 保留仍在监听的文件的代数，避免打断正在防抖的任务
+        if let Ok(mut generations) = self.generations.lock() {
+            generations.retain(|path, _| sources.contains(path));
+            for path in sources {
+                generations.entry(path).or_insert(0);
+            }
+        }
+    }
+}
+
+impl Default for ProfileWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 所有 LocalWatch Profile 的源文件路径
+fn watched_sources() -> Vec<PathBuf> {
+    let profiles = Workspace::new()
+        .and_then(|workspace| workspace.list_profiles())
+        .unwrap_or_default();
+
+    profiles
+        .into_iter()
+        .filter(|p| p.profile_type == ProfileType::LocalWatch)
+        .filter_map(|p| p.source_path.map(PathBuf::from))
+        .collect()
+}
+
+/// 递增源文件的防抖代数；不是被监听的源文件时返回 None
+fn bump_generation(generations: &Mutex<HashMap<PathBuf, u64>>, path: &Path) -> Option<u64> {
+    let mut generations = generations.lock().ok()?;
+    let generation = generations.get_mut(path)?;
+    *generation += 1;
+    Some(*generation)
+}
+
+/// 源文件变化后重新导入对应的 Profile，活跃 Profile 会重新加载
+async fn handle_source_changed(app: &AppHandle, path: &Path) {
+    let mut retries = 0;
+    while !path.exists() {
+        if retries >= MISSING_FILE_RETRIES {
+            // 文件被删除或移走：保留现有配置，重新出现时会再次收到事件
+            log::warn!("Watched profile source {:?} is missing, skipping", path);
+            return;
+        }
+        retries += 1;
+        tokio::time::sleep(MISSING_FILE_RETRY_DELAY).await;
+    }
+
+    let Ok(workspace) = Workspace::new() else {
+        return;
+    };
+    let path_str = path.to_string_lossy();
+    let profiles = workspace.list_profiles().unwrap_or_default();
+
+    for profile in profiles.into_iter().filter(|p| {
+        p.profile_type == ProfileType::LocalWatch && p.source_path.as_deref() == Some(&path_str)
+    }) {
+        let metadata = match workspace.reimport_watched(&profile.id) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                log::warn!("Failed to reimport watched profile {}: {}", profile.id, e);
+                continue;
+            }
+        };
+
+        if metadata.in_use() {
+            if let Some(state) = crate::commands::try_get_app_state() {
+                if let Err(e) = crate::commands::profile::reload_active_profile(state).await {
+                    log::warn!("Failed to reload watched profile {}: {}", profile.id, e);
+                }
+            }
+        }
+
+        let _ = app.emit("profile-source-changed", &metadata);
+    }
+}
@@ -1,10 +1,26 @@
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
-
-use crate::config::Composer;
-use crate::models::{MihomoConfig, ProfileConfig, ProfileMetadata, ProfileType};
+use std::path::{Path, PathBuf};
+
+use crate::config::{convert_foreign_config, Composer};
+use crate::models::{
+    ExternalImportResult, ForeignImportResult, MihomoConfig, ProfileConfig, ProfileDiff,
+    ProfileMetadata, ProfileType, ProviderCheck, ProviderCheckStatus, ProxyConfig,
+    RulesetRefreshResult, SkippedImportFile,
+};
 use crate::utils::get_app_data_dir;
 
+/// 导入外部目录时的最大递归深度
+const MAX_IMPORT_DEPTH: usize = 3;
+
+/// 规则集 ETag 缓存文件（位于 ruleset 目录，按文件名记录）
+const RULESET_ETAG_FILE: &str = ".etags.json";
+
+/// Profile 名称的最大长度（字符数）
+const MAX_PROFILE_NAME_CHARS: usize = 64;
+
+/// 单个规则集下载超时
+const RULESET_DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// 工作区管理器
 /// 负责管理 Profile 目录结构、读写配置和激活 Profile
 pub struct Workspace {
@@ -14,6 +30,15 @@ pub struct Workspace {
     ruleset_dir: PathBuf,
 }
 
+/// Profile 不存在的错误，以 `io::ErrorKind::NotFound` 承载便于调用方区分
+fn profile_not_found(id: &str) -> anyhow::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("Profile not found: {}", id),
+    )
+    .into()
+}
+
 impl Workspace {
     /// 创建新的工作区管理器
     pub fn new() -> Result<Self> {
@@ -79,7 +104,7 @@ impl Workspace {
         let config_path = profile_dir.join("profile.yaml");
 
         if !metadata_path.exists() {
-            return Err(anyhow!("Profile not found: {}", id));
+            return Err(profile_not_found(id));
         }
 
         let metadata: ProfileMetadata =
@@ -98,7 +123,7 @@ impl Workspace {
     pub fn get_metadata(&self, id: &str) -> Result<ProfileMetadata> {
         let metadata_path = self.profile_dir(id).join("metadata.json");
         if !metadata_path.exists() {
-            return Err(anyhow!("Profile not found: {}", id));
+            return Err(profile_not_found(id));
         }
         let metadata: ProfileMetadata =
             serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?;
@@ -107,9 +132,11 @@ impl Workspace {
 
     /// 创建新 Profile（从远程 URL）
     pub async fn create_from_remote(&self, name: &str, url: &str) -> Result<ProfileMetadata> {
+        let name = &*sanitize_profile_name(name)?;
         log::info!("Creating remote profile '{}' from URL: {}", name, url);
 
-        let (mut config, default_rules_applied) = Composer::fetch_and_parse_with_flags(url).await?;
+        let (mut config, default_rules_applied, usage) =
+            Composer::fetch_and_parse_with_usage(url).await?;
 
         let id = uuid::Uuid::new_v4().to_string();
 
@@ -119,6 +146,16 @@ impl Workspace {
         // 过滤无效规则
         Composer::filter_invalid_rules(&mut config);
 
+        // 移除订阅中完全重复的规则（冲突只记录，不做处理）
+        let dedup = Composer::dedup_rules(&mut config, false);
+        if !dedup.removed.is_empty() || !dedup.conflicts.is_empty() {
+            log::info!(
+                "Subscription rules: removed {} duplicates, found {} conflicting matchers",
+                dedup.removed.len(),
+                dedup.conflicts.len()
+            );
+        }
+
         let mut metadata =
             ProfileMetadata::new_remote(id.clone(), name.to_string(), url.to_string());
         metadata.update_stats(
@@ -128,6 +165,7 @@ impl Workspace {
             config.provider_count(),
         );
         metadata.default_rules_applied = Some(default_rules_applied);
+        metadata.subscription = usage;
 
         self.save_profile(&id, &metadata, &config)?;
 
@@ -142,12 +180,15 @@ impl Workspace {
         Ok(metadata)
     }
 
-    /// 创建新 Profile（从本地文件复制）
-    pub fn create_from_local(&self, name: &str, file_path: &str) -> Result<ProfileMetadata> {
-        let content = std::fs::read_to_string(file_path)
-            .map_err(|e| anyhow!("Failed to read file '{}': {}", file_path, e))?;
+    /// 读取并解析本地配置文件（修正 rule-provider 路径并过滤无效规则）
+    fn load_local_config(&self, file_path: &str) -> Result<ProfileConfig> {
+        let content = Composer::read_yaml_file(file_path)?;
+        self.prepare_local_config(&content)
+    }
 
-        let mut config = Composer::parse_yaml(&content)?;
+    /// 解析本地配置内容（修正 rule-provider 路径并过滤无效规则）
+    fn prepare_local_config(&self, content: &str) -> Result<ProfileConfig> {
+        let mut config = Composer::parse_yaml(content)?;
 
         // 修正 rule-provider 路径
         Composer::fix_provider_paths(&mut config, &self.ruleset_dir)?;
@@ -155,8 +196,35 @@ impl Workspace {
         // 过滤无效规则
         Composer::filter_invalid_rules(&mut config);
 
+        Ok(config)
+    }
+
+    /// 创建新 Profile（从本地文件复制）
+    ///
+    /// 文件开头有 ClashX 的 `#!MANAGED-CONFIG <url>` 时创建远程 Profile，
+    /// 以文件内容作为首次快照，之后可以照常更新订阅。
+    pub fn create_from_local(&self, name: &str, file_path: &str) -> Result<ProfileMetadata> {
+        let name = &*sanitize_profile_name(name)?;
+        let content = Composer::read_yaml_file(file_path)?;
+        let config = self.prepare_local_config(&content)?;
+
         let id = uuid::Uuid::new_v4().to_string();
-        let mut metadata = ProfileMetadata::new_local(id.clone(), name.to_string());
+        let mut metadata = match Composer::managed_config_header(&content) {
+            Some(header) => {
+                log::info!(
+                    "Found managed-config header in '{}', creating remote profile",
+                    file_path
+                );
+                let mut metadata =
+                    ProfileMetadata::new_remote(id.clone(), name.to_string(), header.url);
+                if let Some(interval) = header.interval {
+                    // 托管配置的间隔以秒为单位，Profile 以小时为单位
+                    metadata.update_interval = Some((interval / 3600).clamp(1, 24 * 7) as u32);
+                }
+                metadata
+            }
+            None => ProfileMetadata::new_local(id.clone(), name.to_string()),
+        };
         metadata.update_stats(
             config.proxy_count(),
             config.group_count(),
@@ -167,7 +235,8 @@ impl Workspace {
         self.save_profile(&id, &metadata, &config)?;
 
         log::info!(
-            "Created local profile '{}' from '{}' with {} proxies, {} groups, {} rules",
+            "Created {:?} profile '{}' from '{}' with {} proxies, {} groups, {} rules",
+            metadata.profile_type,
             name,
             file_path,
             config.proxy_count(),
@@ -178,8 +247,225 @@ impl Workspace {
         Ok(metadata)
     }
 
+    /// 创建监听本地文件的 Profile（源文件变化时自动重新导入）
+    pub fn create_from_watch(&self, name: &str, file_path: &str) -> Result<ProfileMetadata> {
+        let name = &*sanitize_profile_name(name)?;
+        // 保存绝对路径，便于与文件系统事件中的路径比较
+        let source_path = std::fs::canonicalize(file_path)
+            .map_err(|e| anyhow!("Failed to resolve file '{}': {}", file_path, e))?
+            .to_string_lossy()
+            .to_string();
+        let config = self.load_local_config(&source_path)?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut metadata =
+            ProfileMetadata::new_local_watch(id.clone(), name.to_string(), source_path.clone());
+        metadata.update_stats(
+            config.proxy_count(),
+            config.group_count(),
+            config.rule_count(),
+            config.provider_count(),
+        );
+
+        self.save_profile(&id, &metadata, &config)?;
+
+        log::info!(
+            "Created watched profile '{}' from '{}' with {} proxies",
+            name,
+            source_path,
+            config.proxy_count()
+        );
+
+        Ok(metadata)
+    }
+
+    /// 从源文件重新导入监听的 Profile（保留备注和修改记录）
+    pub fn reimport_watched(&self, id: &str) -> Result<ProfileMetadata> {
+        let mut metadata = self.get_metadata(id)?;
+        if metadata.profile_type != ProfileType::LocalWatch {
+            return Err(anyhow!("Profile is not watching a local file"));
+        }
+        let source_path = metadata
+            .source_path
+            .clone()
+            .ok_or_else(|| anyhow!("Watched profile has no source path"))?;
+
+        let config = self.load_local_config(&source_path)?;
+
+        metadata.update_stats(
+            config.proxy_count(),
+            config.group_count(),
+            config.rule_count(),
+            config.provider_count(),
+        );
+        metadata.record_history(format!("源文件变更：{} 个节点", config.proxy_count()));
+
+        self.save_profile(id, &metadata, &config)?;
+
+        log::info!(
+            "Reimported watched profile '{}' from '{}'",
+            metadata.name,
+            source_path
+        );
+
+        Ok(metadata)
+    }
+
+    /// 从其他 Clash 客户端的数据目录批量导入 Profile
+    ///
+    /// 递归扫描目录中的 YAML 文件，以原文件名作为 Profile 名称；
+    /// 非 YAML 文件、解析失败或不含代理的文件会被跳过并记录原因
+    pub fn import_from_dir(&self, dir: &str) -> Result<ExternalImportResult> {
+        let root = Path::new(dir);
+        if !root.is_dir() {
+            return Err(anyhow!("Directory not found: {}", dir));
+        }
+
+        let mut result = ExternalImportResult::default();
+        let mut yaml_files = Vec::new();
+        Self::collect_yaml_files(root, 0, &mut yaml_files, &mut result.skipped)?;
+        yaml_files.sort();
+
+        for path in yaml_files {
+            let path_str = path.to_string_lossy().to_string();
+            let skip = |reason: String| SkippedImportFile {
+                path: path_str.clone(),
+                reason,
+            };
+
+            let config = match Composer::read_yaml_file(&path)
+                .and_then(|content| Composer::parse_yaml(&content))
+            {
+                Ok(config) => config,
+                Err(e) => {
+                    result.skipped.push(skip(format!("解析失败: {}", e)));
+                    continue;
+                }
+            };
+
+            // 客户端自身的索引/运行时文件（如 Clash Verge 的 profiles.yaml）不含代理
+            if config.proxies.is_empty() && config.proxy_providers.is_empty() {
+                result.skipped.push(skip("不包含代理节点".to_string()));
+                continue;
+            }
+
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| path_str.clone());
+
+            match self.create_from_local(&name, &path_str) {
+                Ok(metadata) => result.imported.push(metadata),
+                Err(e) => result.skipped.push(skip(e.to_string())),
+            }
+        }
+
+        log::info!(
+            "Imported {} profiles from '{}', skipped {} files",
+            result.imported.len(),
+            dir,
+            result.skipped.len()
+        );
+
+        Ok(result)
+    }
+
+    /// 递归收集目录中的 YAML 文件
+    fn collect_yaml_files(
+        dir: &Path,
+        depth: usize,
+        files: &mut Vec<PathBuf>,
+        skipped: &mut Vec<SkippedImportFile>,
+    ) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = match entry {
+                Ok(entry) => entry.path(),
+                Err(e) => {
+                    skipped.push(SkippedImportFile {
+                        path: dir.to_string_lossy().to_string(),
+                        reason: format!("读取目录项失败: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            if path.is_dir() {
+                // 子目录不可读（如权限不足）时只跳过该目录，不中断整个导入
+                if depth < MAX_IMPORT_DEPTH {
+                    if let Err(e) = Self::collect_yaml_files(&path, depth + 1, files, skipped) {
+                        skipped.push(SkippedImportFile {
+                            path: path.to_string_lossy().to_string(),
+                            reason: format!("读取目录失败: {}", e),
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let is_yaml = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml")
+                });
+
+            if is_yaml {
+                files.push(path);
+            } else {
+                skipped.push(SkippedImportFile {
+                    path: path.to_string_lossy().to_string(),
+                    reason: "非 YAML 文件".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 从 Surge / QuantumultX 配置创建本地 Profile
+    ///
+    /// 节点、策略组和规则转换为 mihomo 格式，无法转换的行放入返回的警告列表
+    pub fn create_from_foreign(&self, name: &str, file_path: &str) -> Result<ForeignImportResult> {
+        let name = &*sanitize_profile_name(name)?;
+        let content = Composer::read_yaml_file(file_path)?;
+        let conversion = convert_foreign_config(&content)
+            .ok_or_else(|| anyhow!("不是 Surge 或 QuantumultX 配置文件"))?;
+        let mut config = conversion.config;
+        if config.proxies.is_empty() {
+            return Err(anyhow!("配置中没有可导入的节点"));
+        }
+        Composer::filter_invalid_rules(&mut config);
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut metadata = ProfileMetadata::new_local(id.clone(), name.to_string());
+        metadata.update_stats(
+            config.proxy_count(),
+            config.group_count(),
+            config.rule_count(),
+            config.provider_count(),
+        );
+
+        self.save_profile(&id, &metadata, &config)?;
+
+        log::info!(
+            "Imported profile '{}' from '{}' with {} proxies, {} groups, {} rules ({} lines skipped)",
+            name,
+            file_path,
+            config.proxy_count(),
+            config.group_count(),
+            config.rule_count(),
+            conversion.warnings.len()
+        );
+
+        Ok(ForeignImportResult {
+            profile: metadata,
+            warnings: conversion.warnings,
+        })
+    }
+
     /// 创建空白 Profile
     pub fn create_blank(&self, name: &str) -> Result<ProfileMetadata> {
+        let name = &*sanitize_profile_name(name)?;
         let id = uuid::Uuid::new_v4().to_string();
         let config = ProfileConfig::with_default_group();
         let mut metadata = ProfileMetadata::new_blank(id.clone(), name.to_string());
@@ -265,8 +551,17 @@ impl Workspace {
         base_config: &MihomoConfig,
         use_jsdelivr: Option<bool>,
     ) -> Result<MihomoConfig> {
-        let (_metadata, mut config) = self.get_profile(id)?;
+        let (_metadata, config) = self.get_profile(id)?;
+        self.build_runtime_config(config, base_config, use_jsdelivr)
+    }
 
+    /// 将 Profile 内容合并到基础配置
+    fn build_runtime_config(
+        &self,
+        mut config: ProfileConfig,
+        base_config: &MihomoConfig,
+        use_jsdelivr: Option<bool>,
+    ) -> Result<MihomoConfig> {
         // 修正 rule-provider 路径
         Composer::fix_provider_paths(&mut config, &self.ruleset_dir)?;
 
@@ -311,12 +606,14 @@ impl Workspace {
         runtime_config.proxy_providers = config.proxy_providers;
         runtime_config.rule_providers = config.rule_providers;
         runtime_config.rules = config.rules;
+        runtime_config.sub_rules = config.sub_rules;
 
         Ok(runtime_config)
     }
 
     /// 激活 Profile（生成运行时配置）
     ///
+    /// 保持当前的副 Profile（多配置模式），未设置时为单配置模式。
     /// `use_jsdelivr`: 是否使用 JsDelivr 加速 GitHub 资源 URL，传入 None 则不转换
     pub fn activate_profile(
         &self,
@@ -324,19 +621,55 @@ impl Workspace {
         base_config: &MihomoConfig,
         use_jsdelivr: Option<bool>,
     ) -> Result<MihomoConfig> {
-        let metadata = self.get_metadata(id)?;
-        let runtime_config = self.generate_runtime_config(id, base_config, use_jsdelivr)?;
+        let secondary_id = self.get_secondary_profile_id()?.filter(|s| s != id);
+        self.activate_profiles(id, secondary_id.as_deref(), base_config, use_jsdelivr)
+    }
+
+    /// 激活主 Profile，并指定提供规则和规则源的副 Profile（None 为单配置模式）
+    ///
+    /// 合并规则见 `Composer::merge_layered`，合并失败时不修改激活状态。
+    pub fn activate_profiles(
+        &self,
+        id: &str,
+        secondary_id: Option<&str>,
+        base_config: &MihomoConfig,
+        use_jsdelivr: Option<bool>,
+    ) -> Result<MihomoConfig> {
+        if secondary_id == Some(id) {
+            return Err(anyhow!("主 Profile 和副 Profile 不能相同"));
+        }
+
+        let (metadata, mut config) = self.get_profile(id)?;
+        if let Some(secondary_id) = secondary_id {
+            let (secondary_metadata, secondary) = self.get_profile(secondary_id)?;
+            config = Composer::merge_layered(config, secondary)?;
+            log::info!(
+                "Merged rules from secondary profile '{}' ({})",
+                secondary_metadata.name,
+                secondary_id
+            );
+        }
+        let runtime_config = self.build_runtime_config(config, base_config, use_jsdelivr)?;
 
         // 更新所有 Profile 的 active 状态
-        self.set_active_profile(id)?;
+        self.set_active_profile(id, secondary_id)?;
 
         log::info!("Activated profile '{}' ({})", metadata.name, id);
 
         Ok(runtime_config)
     }
 
-    /// 设置活跃 Profile
-    fn set_active_profile(&self, active_id: &str) -> Result<()> {
+    /// 获取副 Profile ID
+    pub fn get_secondary_profile_id(&self) -> Result<Option<String>> {
+        Ok(self
+            .list_profiles()?
+            .into_iter()
+            .find(|p| p.secondary_active)
+            .map(|p| p.id))
+    }
+
+    /// 设置活跃 Profile（以及多配置模式下的副 Profile）
+    fn set_active_profile(&self, active_id: &str, secondary_id: Option<&str>) -> Result<()> {
         for entry in std::fs::read_dir(&self.profiles_dir)? {
             let entry = entry?;
             if entry.file_type()?.is_dir() {
@@ -345,9 +678,14 @@ impl Workspace {
                     let content = std::fs::read_to_string(&metadata_path)?;
                     let mut metadata: ProfileMetadata = serde_json::from_str(&content)?;
 
-                    let should_be_active = entry.file_name().to_string_lossy() == active_id;
-                    if metadata.active != should_be_active {
+                    let dir_name = entry.file_name().to_string_lossy().to_string();
+                    let should_be_active = dir_name == active_id;
+                    let should_be_secondary = Some(dir_name.as_str()) == secondary_id;
+                    if metadata.active != should_be_active
+                        || metadata.secondary_active != should_be_secondary
+                    {
                         metadata.active = should_be_active;
+                        metadata.secondary_active = should_be_secondary;
                         std::fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
                     }
                 }
@@ -379,7 +717,10 @@ impl Workspace {
     }
 
     /// 刷新远程 Profile
-    pub async fn refresh_remote(&self, id: &str) -> Result<ProfileMetadata> {
+    ///
+    /// 订阅返回 0 个节点（例如机场维护页被解析成了 YAML）且原订阅有节点时，
+    /// 除非 `allow_empty` 为 true，否则拒绝覆盖，保留上次的节点。
+    pub async fn refresh_remote(&self, id: &str, allow_empty: bool) -> Result<ProfileMetadata> {
         // 1. 获取现有的 Profile 和配置
         // 我们需要保留现有的非 Proxy 配置（如规则、代理组等）
         let (metadata, old_config) = self.get_profile(id)?;
@@ -394,28 +735,288 @@ impl Workspace {
             .ok_or_else(|| anyhow!("Remote profile has no URL"))?;
 
         // 2. 获取新的远程配置
-        // 注意：fetch_and_parse_with_flags 可能会应用模板，但这不影响我们获取代理列表
-        // 因为 fetch_and_parse_with_flags 也会返回解析出的 proxies
-        let (new_fetched_config, _default_rules_applied) =
-            Composer::fetch_and_parse_with_flags(url).await?;
+        // 注意：fetch_and_parse_with_usage 可能会应用模板，但这不影响我们获取代理列表
+        // 因为 fetch_and_parse_with_usage 也会返回解析出的 proxies
+        let (new_fetched_config, _default_rules_applied, usage) =
+            Composer::fetch_and_parse_with_usage(url).await?;
+
+        if !allow_empty && is_empty_subscription(&new_fetched_config) {
+            let previous = remote_proxy_count(&old_config);
+            if previous > 0 {
+                log::warn!(
+                    "Remote profile '{}' returned no proxies, keeping {} previous proxies",
+                    metadata.name,
+                    previous
+                );
+                return Err(anyhow!(
+                    "订阅返回了 0 个节点，已保留上次的 {} 个节点（可在设置中允许空订阅更新）",
+                    previous
+                ));
+            }
+        }
 
         // 3. 合并配置：保留本地配置，仅用远程的代理列表覆盖
+        let final_config = self.merge_remote_config(old_config, new_fetched_config)?;
+
+        // 4. 更新元数据
+        let mut new_metadata = metadata.clone();
+        new_metadata.update_stats(
+            final_config.proxy_count(),
+            final_config.group_count(),
+            final_config.rule_count(),
+            final_config.provider_count(),
+        );
+        // 这里我们不更新 default_rules_applied，因为我们没有重新应用规则模板
+        if usage.is_some() {
+            new_metadata.subscription = usage;
+        }
+        new_metadata.record_history(format!("更新订阅：{} 个节点", final_config.proxy_count()));
+
+        // 5. 保存结果
+        self.save_profile(id, &new_metadata, &final_config)?;
+
+        log::info!(
+            "Refreshed remote profile '{}'. Proxies: {}, Groups: {}, Rules: {}",
+            new_metadata.name,
+            final_config.proxy_count(),
+            final_config.group_count(),
+            final_config.rule_count()
+        );
+
+        Ok(new_metadata)
+    }
+
+    /// 只刷新 Profile 中 http 类型的规则源，不重新下载订阅
+    ///
+    /// 并发下载到 ruleset 目录，带上次的 ETag 发送条件请求，返回 304 时跳过。
+    pub async fn refresh_rulesets(&self, id: &str) -> Result<Vec<RulesetRefreshResult>> {
+        let (_metadata, config) = self.get_profile(id)?;
+
+        let etag_path = self.ruleset_dir.join(RULESET_ETAG_FILE);
+        let etags: std::collections::HashMap<String, String> = std::fs::read_to_string(&etag_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let client = reqwest::Client::builder()
+            .timeout(RULESET_DOWNLOAD_TIMEOUT)
+            .build()?;
+
+        let futures = config
+            .rule_providers
+            .iter()
+            .filter(|(_, provider)| provider.provider_type == "http")
+            .map(|(name, provider)| {
+                let client = &client;
+                let etags = &etags;
+                async move {
+                    let result = match &provider.url {
+                        Some(url) => {
+                            let path = self.ruleset_file(name, provider.path.as_ref());
+                            let key = path
+                                .file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_else(|| name.clone());
+                            Self::download_ruleset(client, url, &path, etags.get(&key))
+                                .await
+                                .map(|etag| (key, etag))
+                        }
+                        None => Err(anyhow!("规则源缺少 url")),
+                    };
+                    (name.clone(), result)
+                }
+            });
+        let downloads = futures_util::future::join_all(futures).await;
+
+        let mut etags = etags;
+        let mut results = Vec::with_capacity(downloads.len());
+        for (name, result) in downloads {
+            match result {
+                Ok((key, Some(etag))) => {
+                    if etag.is_empty() {
+                        etags.remove(&key);
+                    } else {
+                        etags.insert(key, etag);
+                    }
+                    results.push(RulesetRefreshResult {
+                        name,
+                        updated: true,
+                        error: None,
+                    });
+                }
+                Ok((_, None)) => results.push(RulesetRefreshResult {
+                    name,
+                    updated: false,
+                    error: None,
+                }),
+                Err(e) => {
+                    log::warn!("Failed to refresh ruleset '{}': {}", name, e);
+                    results.push(RulesetRefreshResult {
+                        name,
+                        updated: false,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+        std::fs::write(&etag_path, serde_json::to_string_pretty(&etags)?)?;
+
+        log::info!(
+            "Refreshed rulesets of profile '{}': {}/{} updated",
+            id,
+            results.iter().filter(|r| r.updated).count(),
+            results.len()
+        );
+        Ok(results)
+    }
+
+    /// 检查 Profile 引用的规则源/代理源本地文件是否存在，只返回有问题的项
+    ///
+    /// 与激活时一样先修正路径（file 转 http、占位文件等），再检查 file/http 类型的本地文件；
+    /// `download` 为 true 时下载缺失的 http 规则源。返回空列表表示全部可用。
+    pub async fn check_providers(&self, id: &str, download: bool) -> Result<Vec<ProviderCheck>> {
+        let (_metadata, mut config) = self.get_profile(id)?;
+        Composer::fix_provider_paths(&mut config, &self.ruleset_dir)?;
+
+        let mut checks = Vec::new();
+        let rule_providers = config
+            .rule_providers
+            .iter()
+            .map(|(name, p)| ("rule", name, &p.provider_type, &p.url, &p.path));
+        let proxy_providers = config
+            .proxy_providers
+            .iter()
+            .map(|(name, p)| ("proxy", name, &p.provider_type, &p.url, &p.path));
+        for (kind, name, provider_type, url, path) in rule_providers.chain(proxy_providers) {
+            let message = match provider_type.as_str() {
+                "file" if path.is_none() => "缺少 path，请补充本地文件路径或删除此项",
+                "file" => "本地文件不存在且没有 URL，请重新导入文件、补充 URL 或删除此项",
+                "http" if url.is_none() => "缺少 URL，请补充下载地址或删除此项",
+                // 代理源由核心自行下载
+                "http" if kind == "proxy" => continue,
+                "http" => "尚未下载，核心启动时会尝试下载，网络不可用时将加载失败",
+                _ => continue,
+            };
+            let exists = path
+                .as_ref()
+                .is_some_and(|p| self.resolve_provider_path(p).exists());
+            if exists {
+                continue;
+            }
+            checks.push(ProviderCheck {
+                name: name.clone(),
+                kind: kind.to_string(),
+                provider_type: provider_type.clone(),
+                path: path.clone(),
+                status: ProviderCheckStatus::Missing,
+                message: message.to_string(),
+            });
+        }
+
+        if download {
+            let client = reqwest::Client::builder()
+                .timeout(RULESET_DOWNLOAD_TIMEOUT)
+                .build()?;
+            for check in checks
+                .iter_mut()
+                .filter(|c| c.kind == "rule" && c.provider_type == "http")
+            {
+                let provider = &config.rule_providers[&check.name];
+                let Some(url) = &provider.url else {
+                    continue;
+                };
+                let path = self.ruleset_file(&check.name, provider.path.as_ref());
+                match Self::download_ruleset(&client, url, &path, None).await {
+                    Ok(_) => {
+                        check.status = ProviderCheckStatus::Downloaded;
+                        check.message = "已下载".to_string();
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to download ruleset '{}': {}", check.name, e);
+                        check.status = ProviderCheckStatus::DownloadFailed;
+                        check.message = format!("下载失败: {}", e);
+                    }
+                }
+            }
+        }
+
+        checks.sort_by(|a, b| (&a.kind, &a.name).cmp(&(&b.kind, &b.name)));
+        Ok(checks)
+    }
+
+    /// 相对路径按核心工作目录（数据目录）解析
+    fn resolve_provider_path(&self, path: &str) -> PathBuf {
+        let path = Path::new(path);
+        match self.profiles_dir.parent() {
+            Some(data_dir) if path.is_relative() => data_dir.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    /// http 规则源在 ruleset 目录中的本地文件（与激活时 `Composer::fix_provider_paths` 一致）
+    fn ruleset_file(&self, name: &str, path: Option<&String>) -> PathBuf {
+        let file_name = path
+            .and_then(|p| Path::new(p).file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{}.yaml", name));
+        self.ruleset_dir.join(file_name)
+    }
+
+    /// 下载单个规则集，未变化时返回 Ok(None)，否则返回新的 ETag（服务器未提供时为空）
+    async fn download_ruleset(
+        client: &reqwest::Client,
+        url: &str,
+        path: &Path,
+        etag: Option<&String>,
+    ) -> Result<Option<String>> {
+        let mut request = client
+            .get(url)
+            .header("User-Agent", concat!("Conflux/", env!("CARGO_PKG_VERSION")));
+        if let Some(etag) = etag.filter(|_| path.exists()) {
+            request = request.header("If-None-Match", etag.as_str());
+        }
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("HTTP {}", response.status()));
+        }
+
+        let new_etag = response
+            .headers()
+            .get("etag")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let bytes = response.bytes().await?;
+
+        // 先写临时文件再替换，避免核心读到写了一半的文件
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("download");
+        std::fs::write(&tmp_path, &bytes)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(Some(new_etag))
+    }
+
+    /// 合并刷新得到的远程配置：以远程配置为基础，保留本地节点、Provider、策略组和规则
+    fn merge_remote_config(
+        &self,
+        old_config: ProfileConfig,
+        fetched: ProfileConfig,
+    ) -> Result<ProfileConfig> {
         // 但我们需要保留那些被标记为 "local" 的代理节点
         let local_proxies: Vec<_> = old_config
             .proxies
             .iter()
-            .filter(|p| {
-                p.extra
-                    .get("x-conflux-managed")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s == "local")
-                    .unwrap_or(false)
-            })
+            .filter(|p| is_local_proxy(p))
             .cloned()
             .collect();
 
         // 使用新的远程配置作为基础
-        let mut final_config = new_fetched_config;
+        let mut final_config = fetched;
 
         // 3.1 保留用户自定义的 Proxy Providers (远程配置中不存在的)
         for (name, provider) in old_config.proxy_providers {
@@ -456,6 +1057,8 @@ impl Workspace {
             old_config.rules.len()
         );
         final_config.rules = old_config.rules;
+        // 子规则由规则引用，随规则一起保留
+        final_config.sub_rules = old_config.sub_rules;
 
         // 修正 rule-provider 路径 (确保新下载的配置路径正确)
         Composer::fix_provider_paths(&mut final_config, &self.ruleset_dir)?;
@@ -472,34 +1075,45 @@ impl Workspace {
             final_config.proxies.extend(local_proxies);
         }
 
-        // 4. 更新元数据
-        let mut new_metadata = metadata.clone();
-        new_metadata.update_stats(
-            final_config.proxy_count(),
-            final_config.group_count(),
-            final_config.rule_count(),
-            final_config.provider_count(),
-        );
-        // 这里我们不更新 default_rules_applied，因为我们没有重新应用规则模板
+        Ok(final_config)
+    }
 
-        // 5. 保存结果
-        self.save_profile(id, &new_metadata, &final_config)?;
+    /// 预览刷新远程订阅的结果（不保存），返回当前配置与刷新后配置的差异
+    pub async fn preview_refresh(&self, id: &str) -> Result<ProfileDiff> {
+        let (metadata, old_config) = self.get_profile(id)?;
+        if metadata.profile_type != ProfileType::Remote {
+            return Err(anyhow!("Profile is not a remote subscription"));
+        }
+        let url = metadata
+            .url
+            .as_ref()
+            .ok_or_else(|| anyhow!("Remote profile has no URL"))?;
 
-        log::info!(
-            "Refreshed remote profile '{}'. Proxies: {}, Groups: {}, Rules: {}",
-            new_metadata.name,
-            final_config.proxy_count(),
-            final_config.group_count(),
-            final_config.rule_count()
-        );
+        let (fetched, _) = Composer::fetch_and_parse_with_flags(url).await?;
+        let merged = self.merge_remote_config(old_config.clone(), fetched)?;
+        Ok(Composer::diff(&old_config, &merged))
+    }
 
-        Ok(new_metadata)
+    /// 追加一条修改记录
+    pub fn record_history(&self, id: &str, description: &str) -> Result<()> {
+        let mut metadata = self.get_metadata(id)?;
+        metadata.record_history(description);
+        self.update_metadata(id, &metadata)
+    }
+
+    /// 设置 Profile 备注
+    pub fn set_notes(&self, id: &str, notes: &str) -> Result<ProfileMetadata> {
+        let mut metadata = self.get_metadata(id)?;
+        metadata.notes = notes.to_string();
+        self.update_metadata(id, &metadata)?;
+        Ok(metadata)
     }
 
     /// 重命名 Profile
     pub fn rename_profile(&self, id: &str, new_name: &str) -> Result<ProfileMetadata> {
+        let new_name = sanitize_profile_name(new_name)?;
         let mut metadata = self.get_metadata(id)?;
-        metadata.name = new_name.to_string();
+        metadata.name = new_name;
         metadata.updated_at = chrono::Local::now().to_rfc3339();
         self.update_metadata(id, &metadata)?;
         Ok(metadata)
@@ -507,8 +1121,7 @@ impl Workspace {
 
     /// 解析配置文件（预览，不保存）
     pub fn parse_config_file(&self, path: &str) -> Result<ProfileConfig> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| anyhow!("Failed to read file '{}': {}", path, e))?;
+        let content = Composer::read_yaml_file(path)?;
         Composer::parse_yaml(&content)
     }
 
@@ -518,6 +1131,54 @@ impl Workspace {
     }
 }
 
+/// 用户手动添加的本地节点（刷新订阅时保留）
+fn is_local_proxy(proxy: &ProxyConfig) -> bool {
+    proxy
+        .extra
+        .get("x-conflux-managed")
+        .and_then(|v| v.as_str())
+        .map(|s| s == "local")
+        .unwrap_or(false)
+}
+
+/// 整理 Profile 名称：去掉控制字符和文字方向控制符，合并空白，截断过长的名称
+///
+/// 名称会显示在托盘菜单中，部分平台遇到这些字符时菜单会显示异常；
+/// 普通 Unicode 字符和 emoji（包括零宽连接符组合）保持不变。
+pub fn sanitize_profile_name(name: &str) -> Result<String> {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'))
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    let mut sanitized: String = cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_PROFILE_NAME_CHARS)
+        .collect();
+
+    // 截断可能留下不完整的 emoji 组合
+    while sanitized.ends_with(['\u{200D}', '\u{FE0F}', ' ']) {
+        sanitized.pop();
+    }
+    if sanitized.is_empty() {
+        return Err(anyhow!("配置名称不能为空"));
+    }
+    Ok(sanitized)
+}
+
+/// 来自订阅的节点数量（不含本地节点）
+fn remote_proxy_count(config: &ProfileConfig) -> usize {
+    config.proxies.iter().filter(|p| !is_local_proxy(p)).count()
+}
+
+/// 订阅没有任何节点，也没有通过 proxy-providers 提供节点
+fn is_empty_subscription(config: &ProfileConfig) -> bool {
+    config.proxies.is_empty() && config.proxy_providers.is_empty()
+}
+
 impl Default for Workspace {
     fn default() -> Self {
         Self::new().expect("Failed to create Workspace")
@@ -533,4 +1194,22 @@ mod tests {
         let workspace = Workspace::new();
         assert!(workspace.is_ok());
     }
+
+    #[test]
+    fn test_sanitize_profile_name() {
+        assert_eq!(
+            sanitize_profile_name("  机场\t订阅\n ").unwrap(),
+            "机场 订阅"
+        );
+        assert_eq!(sanitize_profile_name("🇭🇰 HK\u{202E}").unwrap(), "🇭🇰 HK");
+        assert_eq!(sanitize_profile_name("👨‍👩‍👧").unwrap(), "👨‍👩‍👧");
+        assert_eq!(
+            sanitize_profile_name(&"a".repeat(100))
+                .unwrap()
+                .chars()
+                .count(),
+            MAX_PROFILE_NAME_CHARS
+        );
+        assert!(sanitize_profile_name(" \u{0007} ").is_err());
+    }
 }
@@ -277,7 +277,7 @@ async fn run_tray_traffic_loop(_app_handle: AppHandle) {}
 
 fn main() {
     // 设置默认日志级别为 info，可通过 RUST_LOG 环境变量覆盖
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    utils::init_logger();
 
     // Windows: 设置 AppUserModelId，让所有 WebView2 进程在任务管理器中显示为应用子进程
     #[cfg(windows)]
@@ -488,6 +488,19 @@ fn main() {
                 })
                 .build(app)?;
 
+            // 主窗口默认隐藏（tauri.conf.json），按设置决定是否在启动时显示
+            let settings = config::ConfigManager::new()
+                .and_then(|manager| manager.load_app_settings())
+                .unwrap_or_default();
+            if settings.start_minimized {
+                log::info!("Starting minimized to tray");
+            } else if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            // 系统启动项可能被清理或随应用移动失效，按设置重新注册
+            commands::system::apply_autostart_to_system(app.handle(), settings.auto_start);
+
             // 初始化应用状态
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -514,6 +527,24 @@ fn main() {
                             let _ = app_handle.emit("proxy-status-changed", &status);
                         }
 
+                        // 检查上次异常退出遗留的配置备份
+                        let backup_app = app_handle.clone();
+                        tauri::async_runtime::spawn(async move {
+                            use commands::reload::StaleBackupStatus;
+
+                            let state = backup_app.state::<commands::AppState>();
+                            match state.startup_backup {
+                                StaleBackupStatus::Orphaned => {
+                                    let _ = commands::reload::recover_stale_backup(&state).await;
+                                }
+                                StaleBackupStatus::Restorable => {
+                                    log::warn!("Current config is invalid, backup is restorable");
+                                    let _ = backup_app.emit("config-backup-available", ());
+                                }
+                                _ => {}
+                            }
+                        });
+
                         run_tray_traffic_loop(app_handle).await;
                     }
                     Err(e) => {
@@ -533,33 +564,89 @@ fn main() {
             commands::proxy::stop_proxy,
             commands::proxy::restart_proxy,
             commands::proxy::get_proxy_status,
+            commands::proxy::reconcile_state,
+            commands::proxy::get_core_state,
+            commands::proxy::detect_external_core,
+            commands::proxy::resolve_external_core,
             commands::proxy::switch_mode,
+            commands::proxy::set_script_mode,
             commands::proxy::get_run_mode,
             // 节点命令
             commands::proxy::get_proxies,
             commands::proxy::select_proxy,
+            commands::proxy::set_global_proxy,
             commands::proxy::test_proxy_delay,
+            commands::proxy::test_standalone_node,
+            // 自动切换最快节点
+            commands::proxy::get_auto_switch_settings,
+            commands::proxy::set_auto_switch,
+            commands::proxy::get_connection_limit,
+            commands::proxy::set_connection_limit,
+            commands::proxy::get_core_priority,
+            commands::proxy::set_core_priority,
+            // 策略组选择预设
+            commands::proxy::list_selection_presets,
+            commands::proxy::save_selection_preset,
+            commands::proxy::apply_selection_preset,
+            commands::proxy::delete_selection_preset,
+            commands::proxy::reset_selections,
+            commands::proxy::reapply_suggested_selection,
+            commands::proxy::get_selection_schedule,
+            commands::proxy::set_selection_schedule,
+            commands::proxy::set_store_selected,
+            // 临时直连
+            commands::proxy::set_temporary_direct,
+            commands::proxy::cancel_temporary_direct,
+            commands::proxy::get_temporary_direct_status,
+            commands::proxy::start_trace,
+            commands::proxy::stop_trace,
+            commands::proxy::get_trace_status,
+            commands::proxy::get_trace_entries,
+            commands::proxy::get_rule_hit_counts,
+            commands::proxy::reset_rule_hit_counts,
+            commands::proxy::get_observability_stats,
+            commands::proxy::set_observability_settings,
             // 配置命令
             commands::config::get_config,
             commands::config::get_config_proxies,
             commands::config::save_config,
+            commands::config::validate_config,
+            commands::config::test_config_file,
+            commands::config::test_config_content,
+            commands::config::recover_from_backup,
             commands::config::get_app_settings,
+            commands::config::set_latency_thresholds,
             commands::config::save_app_settings,
+            commands::config::check_port_conflicts,
+            commands::config::rotate_api_secret,
+            commands::config::set_controller_tls,
             commands::config::get_rules,
             commands::config::save_rules,
             commands::config::download_resource,
+            commands::config::download_geo_with_progress,
             commands::config::check_resource_files,
             commands::config::check_resource_updates,
             commands::config::reload_geo_database,
+            commands::config::install_dashboard,
             // 系统命令
             commands::system::set_system_proxy,
             commands::system::clear_system_proxy,
             commands::system::get_system_proxy_status,
+            commands::system::get_previous_system_proxy,
+            commands::system::get_safe_mode,
+            commands::system::get_startup_timings,
+            commands::system::get_data_dir_info,
+            commands::system::set_data_dir_override,
             commands::system::get_autostart_enabled,
             commands::system::set_autostart_enabled,
+            commands::system::get_window_behavior,
+            commands::system::set_window_behavior,
             // 首页网络信息
             commands::system::get_public_ip_info,
             commands::system::get_local_ip_info,
+            commands::system::list_network_interfaces,
+            commands::system::detect_captive_portal,
+            commands::system::bypass_for_captive_portal,
             commands::system::get_terminal_proxy_command,
             commands::system::copy_to_clipboard,
             commands::system::copy_terminal_proxy_command,
@@ -568,15 +655,26 @@ fn main() {
             commands::system::restart_as_admin,
             // 重置数据
             commands::system::reset_all_data,
+            commands::system::backup_data_dir,
+            commands::system::restore_data_dir,
+            // 崩溃报告
+            commands::system::list_crash_reports,
+            commands::system::open_crash_report,
             // 进程图标（连接/请求列表）
             commands::system::get_process_icon,
             // macOS Network Extension（占位，用于增强模式引导）
             commands::system::get_network_extension_status,
             commands::system::open_network_extension_settings,
+            commands::system::get_app_proxy_rules,
+            commands::system::set_app_proxy_rules,
+            commands::system::set_network_change_policy,
             // 流量命令
             commands::proxy::get_traffic,
             // 连接命令
             commands::proxy::get_connections,
+            commands::proxy::get_connections_page,
+            commands::proxy::simulate_rule_impact,
+            commands::proxy::export_traffic_csv,
             commands::proxy::close_connection,
             commands::proxy::close_all_connections,
             // TUN 模式命令
@@ -584,35 +682,61 @@ fn main() {
             commands::proxy::set_tun_stack,
             commands::proxy::set_strict_route,
             commands::proxy::set_tun_route_exclude,
+            commands::proxy::add_tun_route_exclude,
+            commands::proxy::remove_tun_route_exclude,
+            commands::proxy::add_tun_route_include,
+            commands::proxy::remove_tun_route_include,
             commands::proxy::check_tun_permission,
+            commands::proxy::verify_tun_active,
             commands::proxy::setup_tun_permission,
             commands::proxy::check_tun_consistency,
             // 规则命令
             commands::proxy::get_rules_from_api,
             // 版本信息
             commands::proxy::get_core_version,
+            commands::proxy::get_core_config_schema,
             commands::proxy::upgrade_core,
+            commands::proxy::set_pinned_core_version,
             // 局域网共享
             commands::proxy::set_allow_lan,
+            commands::proxy::set_bind_address,
+            commands::proxy::get_lan_access,
             // 端口与网络选项
             commands::proxy::set_ports,
             commands::proxy::set_ipv6,
             commands::proxy::set_tcp_concurrent,
             commands::proxy::set_sniffing,
+            commands::proxy::set_sniffer_config,
             commands::proxy::set_mixed_port,
             commands::proxy::set_find_process_mode,
+            commands::proxy::set_interface_name,
+            commands::proxy::set_routing_mark,
+            commands::proxy::set_geo_auto_update,
+            commands::proxy::set_keep_alive,
+            commands::proxy::apply_mobile_keep_alive_preset,
+            commands::proxy::set_provider_download_options,
             commands::proxy::get_app_version,
             commands::proxy::flush_fakeip_cache,
+            commands::proxy::set_dns_enhanced_mode,
+            commands::proxy::set_fake_ip_range,
+            commands::proxy::add_nameserver_policy,
+            commands::proxy::remove_nameserver_policy,
+            commands::proxy::test_doh_resolver,
+            commands::proxy::compare_dns,
+            commands::proxy::add_doh_resolver,
             // Provider 命令
             commands::proxy::get_proxy_providers,
             commands::proxy::update_proxy_provider,
             commands::proxy::health_check_proxy_provider,
             commands::proxy::get_rule_providers,
             commands::proxy::update_rule_provider,
+            commands::proxy::warm_active_profile,
             // 日志命令
             commands::logs::start_log_stream,
             commands::logs::stop_log_stream,
             commands::logs::set_log_level,
+            commands::logs::set_stream_reconnect_interval,
+            commands::logs::export_logs,
             // Sub-Store 命令
             commands::substore::start_substore,
             commands::substore::stop_substore,
@@ -633,26 +757,50 @@ fn main() {
             commands::profile::get_active_profile_id,
             commands::profile::create_remote_profile,
             commands::profile::create_local_profile,
+            commands::profile::create_watch_profile,
+            commands::profile::import_external_profiles,
+            commands::profile::import_foreign_profile,
             commands::profile::create_blank_profile,
+            commands::profile::validate_profile_name,
             commands::profile::delete_profile,
             commands::profile::rename_profile,
+            commands::profile::get_profile_notes,
+            commands::profile::set_profile_notes,
+            commands::profile::get_profile_history,
             commands::profile::activate_profile,
             commands::profile::refresh_profile,
+            commands::profile::refresh_profile_rulesets,
+            commands::profile::check_profile_providers,
             commands::profile::parse_config_file,
+            commands::profile::validate_subscription_yaml,
             commands::profile::preview_remote_config,
+            commands::profile::diff_profiles,
+            commands::profile::preview_profile_refresh,
+            commands::profile::check_subscription_warnings,
             commands::profile::export_profile_config,
             // Profile 代理 CRUD 命令
             commands::profile::add_proxy,
             commands::profile::update_proxy,
             commands::profile::delete_proxy,
+            commands::profile::rename_nodes,
+            commands::profile::build_region_groups,
+            commands::profile::dedup_rules,
+            commands::profile::find_shadowed_rules,
+            commands::profile::find_group_cycles,
+            commands::profile::audit_profile_security,
+            commands::profile::export_share_links,
             // Profile 规则命令
             commands::profile::add_rule_to_profile,
             commands::profile::delete_rule_from_profile,
+            commands::profile::import_rules_from_url,
             commands::profile::add_rule_provider_to_profile,
             commands::profile::delete_rule_provider_from_profile,
             commands::profile::update_rule_provider_in_profile,
             commands::profile::rename_rule_provider_in_profile,
             commands::profile::update_profile_config,
+            commands::profile::apply_template_to_profiles,
+            commands::profile::list_rule_templates,
+            commands::profile::apply_rule_template,
             // Profile Proxy Provider 命令
             commands::profile::add_proxy_provider_to_profile,
             commands::profile::update_proxy_provider_in_profile,
@@ -665,13 +813,17 @@ fn main() {
             // URL 延迟测试命令
             commands::proxy::test_url_delay,
             commands::proxy::test_urls_delay,
+            commands::proxy::measure_chain_latency,
             // WebDAV 同步命令
             commands::webdav::test_webdav_connection,
+            commands::webdav::normalize_webdav_url,
             commands::webdav::get_webdav_config,
             commands::webdav::save_webdav_config,
             commands::webdav::webdav_upload,
+            commands::webdav::cancel_webdav_upload,
             commands::webdav::webdav_download,
             commands::webdav::webdav_sync,
+            commands::webdav::webdav_sync_preview,
             commands::webdav::get_sync_status,
             commands::webdav::clear_sync_status,
             commands::webdav::check_webdav_conflict,
@@ -750,11 +902,57 @@ fn main() {
                         }
                     });
                 }
+                RunEvent::WindowEvent {
+                    label,
+                    event: tauri::WindowEvent::Focused(true),
+                    ..
+                } if label == "main" => {
+                    // 窗口获得焦点时与核心对齐状态（核心可能被其他面板修改过）
+                    let app = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = commands::proxy::reconcile_state(app).await {
+                            log::debug!("Failed to reconcile state on focus: {}", e);
+                        }
+                    });
+                }
+                RunEvent::WindowEvent {
+                    label,
+                    event: tauri::WindowEvent::CloseRequested { api, .. },
+                    ..
+                } if label == "main" => {
+                    // 开启「关闭到托盘」时隐藏主窗口，核心继续运行
+                    let close_to_tray = app_handle
+                        .try_state::<commands::AppState>()
+                        .and_then(|state| state.config_manager.load_app_settings().ok())
+                        .unwrap_or_default()
+                        .close_to_tray;
+                    if close_to_tray {
+                        api.prevent_close();
+                        if let Some(window) = app_handle.get_webview_window("main") {
+                            let _ = window.hide();
+                        }
+                    }
+                }
                 RunEvent::Exit => {
                     log::info!("Application is exiting, cleaning up...");
 
                     // 清理子进程和系统设置 - 使用同步方式，避免异步锁导致卡死
                     if let Some(app_state) = app_handle.try_state::<commands::AppState>() {
+                        // 0. 写入防抖中尚未执行的重载，保证 config.yaml 为最终编辑结果
+                        if app_state.reload_debouncer.take_pending() {
+                            let system_proxy_enabled = app_state
+                                .system_proxy_enabled
+                                .try_lock()
+                                .map(|enabled| *enabled)
+                                .unwrap_or(false);
+                            if let Err(e) = commands::profile::write_active_runtime_config(
+                                &app_state,
+                                system_proxy_enabled,
+                            ) {
+                                log::warn!("Failed to write pending profile reload: {}", e);
+                            }
+                        }
+
                         // 1. 清理系统代理设置
                         log::info!("Clearing system proxy settings...");
                         if let Ok(enabled) = app_state.system_proxy_enabled.try_lock() {
@@ -1,48 +1,124 @@
 use anyhow::Result;
 use reqwest::Client;
 use serde_json::json;
+use std::collections::HashMap;
+use std::sync::RwLock;
 use std::time::Duration;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::{
+    connect_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream,
+};
 
 use crate::models::{
     ConnectionsResponse, DelayResponse, ProxiesResponse, ProxyProvidersResponse,
     RuleProvidersResponse, RulesResponse, TrafficData, VersionInfo,
 };
 
+/// 核心 WebSocket 连接
+pub type MihomoWebSocket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Provider 更新请求超时（核心下载远程文件的时间）
+const PROVIDER_UPDATE_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// MiHomo REST API 客户端
 pub struct MihomoApi {
-    client: Client,
-    base_url: String,
-    secret: String,
+    client: RwLock<Client>,
+    base_url: RwLock<String>,
+    /// HTTPS 控制器需要信任的证书（PEM）
+    ca_cert: RwLock<Option<Vec<u8>>>,
+    secret: RwLock<String>,
 }
 
 impl MihomoApi {
     /// 创建新的 API 客户端
     pub fn new(base_url: String, secret: String) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(2))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = Self::build_client(None).expect("Failed to create HTTP client");
 
         Self {
-            client,
-            base_url,
-            secret,
+            client: RwLock::new(client),
+            base_url: RwLock::new(base_url),
+            ca_cert: RwLock::new(None),
+            secret: RwLock::new(secret),
+        }
+    }
+
+    /// 构建 HTTP 客户端，`ca_cert` 为 HTTPS 控制器需要信任的证书（PEM）
+    fn build_client(ca_cert: Option<&[u8]>) -> Result<Client> {
+        let mut builder = Client::builder().timeout(Duration::from_secs(2));
+        if let Some(cert) = ca_cert {
+            // 证书通常签发给局域网地址，应用经回环地址连接，不校验主机名
+            builder = builder
+                .add_root_certificate(reqwest::Certificate::from_pem(cert)?)
+                .danger_accept_invalid_hostnames(true);
+        }
+        Ok(builder.build()?)
+    }
+
+    fn client(&self) -> Client {
+        self.client.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    fn base_url(&self) -> String {
+        self.base_url.read().map(|u| u.clone()).unwrap_or_default()
+    }
+
+    /// 切换控制器地址（HTTP / HTTPS）
+    pub fn set_endpoint(&self, base_url: String, ca_cert: Option<&[u8]>) -> Result<()> {
+        let client = Self::build_client(ca_cert)?;
+        if let Ok(mut current) = self.client.write() {
+            *current = client;
         }
+        if let Ok(mut current) = self.base_url.write() {
+            *current = base_url;
+        }
+        if let Ok(mut current) = self.ca_cert.write() {
+            *current = ca_cert.map(<[u8]>::to_vec);
+        }
+        Ok(())
+    }
+
+    /// 连接核心的 WebSocket 接口（如 `/connections`），使用当前地址和密钥
+    pub async fn connect_websocket(&self, path: &str) -> Result<MihomoWebSocket> {
+        let url = format!("{}{}", self.base_url(), path)
+            .replacen("http://", "ws://", 1)
+            .replacen("https://", "wss://", 1);
+        let mut request = url.into_client_request()?;
+        let secret = self.secret.read().map(|s| s.clone()).unwrap_or_default();
+        if !secret.is_empty() {
+            request
+                .headers_mut()
+                .insert("Authorization", format!("Bearer {}", secret).parse()?);
+        }
+        let ca_cert = self.ca_cert.read().map(|c| c.clone()).unwrap_or_default();
+        let connector = match ca_cert {
+            Some(cert) => Some(Connector::NativeTls(super::trusted_tls_connector(&cert)?)),
+            None => None,
+        };
+        let (stream, _) = connect_async_tls_with_config(request, None, false, connector).await?;
+        Ok(stream)
     }
 
     /// 添加认证头
     fn auth_header(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
-        if self.secret.is_empty() {
+        let secret = self.secret.read().map(|s| s.clone()).unwrap_or_default();
+        if secret.is_empty() {
             request
         } else {
-            request.header("Authorization", format!("Bearer {}", self.secret))
+            request.header("Authorization", format!("Bearer {}", secret))
+        }
+    }
+
+    /// 更新 API 密钥
+    pub fn set_secret(&self, secret: String) {
+        if let Ok(mut current) = self.secret.write() {
+            *current = secret;
         }
     }
 
     /// 获取版本信息
     pub async fn get_version(&self) -> Result<VersionInfo> {
-        let url = format!("{}/version", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/version", self.base_url());
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
         let version = response.json().await?;
         Ok(version)
@@ -50,8 +126,8 @@ impl MihomoApi {
 
     /// 获取代理列表
     pub async fn get_proxies(&self) -> Result<ProxiesResponse> {
-        let url = format!("{}/proxies", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/proxies", self.base_url());
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
         let proxies = response.json().await?;
         Ok(proxies)
@@ -59,8 +135,8 @@ impl MihomoApi {
 
     /// 切换代理节点
     pub async fn select_proxy(&self, group: &str, name: &str) -> Result<()> {
-        let url = format!("{}/proxies/{}", self.base_url, urlencoding::encode(group));
-        let request = self.client.put(&url).json(&json!({ "name": name }));
+        let url = format!("{}/proxies/{}", self.base_url(), urlencoding::encode(group));
+        let request = self.client().put(&url).json(&json!({ "name": name }));
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -75,11 +151,11 @@ impl MihomoApi {
     pub async fn test_delay(&self, proxy: &str, timeout: u32, url: &str) -> Result<DelayResponse> {
         let api_url = format!(
             "{}/proxies/{}/delay",
-            self.base_url,
+            self.base_url(),
             urlencoding::encode(proxy)
         );
         let request = self
-            .client
+            .client()
             .get(&api_url)
             .query(&[("timeout", timeout.to_string()), ("url", url.to_string())]);
         let response = self.auth_header(request).send().await?;
@@ -92,10 +168,41 @@ impl MihomoApi {
         }
     }
 
+    /// 测试策略组内所有节点的延迟（GET /group/{name}/delay）
+    ///
+    /// 返回节点名到延迟（毫秒）的映射，超时或失败的节点不会出现在结果中
+    pub async fn test_group_delay(
+        &self,
+        group: &str,
+        timeout: u32,
+        url: &str,
+    ) -> Result<HashMap<String, u32>> {
+        let api_url = format!(
+            "{}/group/{}/delay",
+            self.base_url(),
+            urlencoding::encode(group)
+        );
+        // 组测速耗时取决于 timeout 参数，需要覆盖默认的 2 秒超时
+        let request = self
+            .client()
+            .get(&api_url)
+            .query(&[("timeout", timeout.to_string()), ("url", url.to_string())])
+            .timeout(Duration::from_millis(u64::from(timeout) + 2000));
+        let response = self.auth_header(request).send().await?;
+
+        if response.status().is_success() {
+            let delays = response.json().await?;
+            Ok(delays)
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!("Group delay test failed: {}", error_text))
+        }
+    }
+
     /// 获取连接列表
     pub async fn get_connections(&self) -> Result<ConnectionsResponse> {
-        let url = format!("{}/connections", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/connections", self.base_url());
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
 
         if !response.status().is_success() {
@@ -129,8 +236,8 @@ impl MihomoApi {
 
     /// 关闭指定连接
     pub async fn close_connection(&self, id: &str) -> Result<()> {
-        let url = format!("{}/connections/{}", self.base_url, id);
-        let request = self.client.delete(&url);
+        let url = format!("{}/connections/{}", self.base_url(), id);
+        let request = self.client().delete(&url);
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -142,8 +249,8 @@ impl MihomoApi {
 
     /// 关闭所有连接
     pub async fn close_all_connections(&self) -> Result<()> {
-        let url = format!("{}/connections", self.base_url);
-        let request = self.client.delete(&url);
+        let url = format!("{}/connections", self.base_url());
+        let request = self.client().delete(&url);
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -155,8 +262,8 @@ impl MihomoApi {
 
     /// 获取实时流量数据
     pub async fn get_traffic(&self) -> Result<TrafficData> {
-        let url = format!("{}/traffic", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/traffic", self.base_url());
+        let request = self.client().get(&url);
         let mut response = self.auth_header(request).send().await?;
 
         // traffic 端点返回的是流式数据
@@ -176,8 +283,8 @@ impl MihomoApi {
 
     /// 切换代理模式
     pub async fn patch_configs(&self, mode: &str) -> Result<()> {
-        let url = format!("{}/configs", self.base_url);
-        let request = self.client.patch(&url).json(&json!({ "mode": mode }));
+        let url = format!("{}/configs", self.base_url());
+        let request = self.client().patch(&url).json(&json!({ "mode": mode }));
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -190,8 +297,8 @@ impl MihomoApi {
 
     /// 设置代理端口（设为 0 可禁用对应端口）
     pub async fn set_ports(&self, port: u16, socks_port: u16) -> Result<()> {
-        let url = format!("{}/configs", self.base_url);
-        let request = self.client.patch(&url).json(&json!({
+        let url = format!("{}/configs", self.base_url());
+        let request = self.client().patch(&url).json(&json!({
             "port": port,
             "socks-port": socks_port
         }));
@@ -207,8 +314,8 @@ impl MihomoApi {
 
     /// 重载配置
     pub async fn reload_configs(&self, path: &str, force: bool) -> Result<()> {
-        let url = format!("{}/configs", self.base_url);
-        let request = self.client.put(&url).json(&json!({
+        let url = format!("{}/configs", self.base_url());
+        let request = self.client().put(&url).json(&json!({
             "path": path,
             "payload": ""
         }));
@@ -228,8 +335,8 @@ impl MihomoApi {
 
     /// 获取配置
     pub async fn get_configs(&self) -> Result<serde_json::Value> {
-        let url = format!("{}/configs", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/configs", self.base_url());
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
         let configs = response.json().await?;
         Ok(configs)
@@ -237,8 +344,8 @@ impl MihomoApi {
 
     /// 获取规则列表
     pub async fn get_rules(&self) -> Result<RulesResponse> {
-        let url = format!("{}/rules", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/rules", self.base_url());
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
         let rules = response.json().await?;
         Ok(rules)
@@ -246,8 +353,8 @@ impl MihomoApi {
 
     /// 获取代理 Provider 列表
     pub async fn get_proxy_providers(&self) -> Result<ProxyProvidersResponse> {
-        let url = format!("{}/providers/proxies", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/providers/proxies", self.base_url());
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
         let providers = response.json().await?;
         Ok(providers)
@@ -257,10 +364,11 @@ impl MihomoApi {
     pub async fn update_proxy_provider(&self, name: &str) -> Result<()> {
         let url = format!(
             "{}/providers/proxies/{}",
-            self.base_url,
+            self.base_url(),
             urlencoding::encode(name)
         );
-        let request = self.client.put(&url);
+        // 更新会等待核心下载完成，需要覆盖默认的 2 秒超时
+        let request = self.client().put(&url).timeout(PROVIDER_UPDATE_TIMEOUT);
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -278,10 +386,10 @@ impl MihomoApi {
     pub async fn health_check_proxy_provider(&self, name: &str) -> Result<()> {
         let url = format!(
             "{}/providers/proxies/{}/healthcheck",
-            self.base_url,
+            self.base_url(),
             urlencoding::encode(name)
         );
-        let request = self.client.get(&url);
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -297,8 +405,8 @@ impl MihomoApi {
 
     /// 获取规则 Provider 列表
     pub async fn get_rule_providers(&self) -> Result<RuleProvidersResponse> {
-        let url = format!("{}/providers/rules", self.base_url);
-        let request = self.client.get(&url);
+        let url = format!("{}/providers/rules", self.base_url());
+        let request = self.client().get(&url);
         let response = self.auth_header(request).send().await?;
         let providers = response.json().await?;
         Ok(providers)
@@ -308,10 +416,11 @@ impl MihomoApi {
     pub async fn update_rule_provider(&self, name: &str) -> Result<()> {
         let url = format!(
             "{}/providers/rules/{}",
-            self.base_url,
+            self.base_url(),
             urlencoding::encode(name)
         );
-        let request = self.client.put(&url);
+        // 更新会等待核心下载完成，需要覆盖默认的 2 秒超时
+        let request = self.client().put(&url).timeout(PROVIDER_UPDATE_TIMEOUT);
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -327,8 +436,8 @@ impl MihomoApi {
 
     /// 清除 FakeIP 缓存
     pub async fn flush_fakeip(&self) -> Result<()> {
-        let url = format!("{}/cache/fakeip/flush", self.base_url);
-        let request = self.client.post(&url).json(&serde_json::json!({}));
+        let url = format!("{}/cache/fakeip/flush", self.base_url());
+        let request = self.client().post(&url).json(&serde_json::json!({}));
         let response = self.auth_header(request).send().await?;
         if response.status().is_success() {
             Ok(())
@@ -341,13 +450,42 @@ impl MihomoApi {
         }
     }
 
+    /// 清除核心 DNS 缓存
+    pub async fn flush_dns_cache(&self) -> Result<()> {
+        let url = format!("{}/cache/dns/flush", self.base_url());
+        let request = self.client().post(&url).json(&serde_json::json!({}));
+        let response = self.auth_header(request).send().await?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!("Failed to flush DNS cache: {}", error_text))
+        }
+    }
+
+    /// 通过核心 DNS 解析域名（GET /dns/query）
+    pub async fn dns_query(&self, name: &str, query_type: &str) -> Result<serde_json::Value> {
+        let url = format!("{}/dns/query", self.base_url());
+        let request = self
+            .client()
+            .get(&url)
+            .query(&[("name", name), ("type", query_type)]);
+        let response = self.auth_header(request).send().await?;
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!("DNS query failed: {}", error_text))
+        }
+    }
+
     /// 重启内核（POST /restart）
     ///
     /// 让 mihomo 内核自己重启，用于有权限模式下应用配置变更。
     /// 与进程级重启（stop + start）不同，这不会改变进程权限。
     pub async fn restart(&self) -> Result<()> {
-        let url = format!("{}/restart", self.base_url);
-        let request = self.client.post(&url).json(&serde_json::json!({}));
+        let url = format!("{}/restart", self.base_url());
+        let request = self.client().post(&url).json(&serde_json::json!({}));
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -363,12 +501,13 @@ impl MihomoApi {
     /// 触发 mihomo 核心自我更新。更新可能需要较长时间，建议设置较长的超时时间。
     /// 返回 true 表示有更新并已升级，false 表示已是最新版本无需更新。
     pub async fn upgrade(&self) -> Result<bool> {
-        let url = format!("{}/upgrade", self.base_url);
-        // 升级操作可能需要较长时间，使用单独的 client 并设置更长的超时
-        let client = reqwest::Client::builder()
+        let url = format!("{}/upgrade", self.base_url());
+        // 升级操作可能需要较长时间，单独设置更长的超时（client 中包含 HTTPS 控制器的 CA）
+        let request = self
+            .client()
+            .post(&url)
             .timeout(Duration::from_secs(300)) // 5 分钟超时
-            .build()?;
-        let request = client.post(&url).json(&serde_json::json!({}));
+            .json(&serde_json::json!({}));
         let response = self.auth_header(request).send().await?;
 
         if response.status().is_success() {
@@ -0,0 +1,214 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::{MihomoApi, MihomoManager};
+use crate::models::AutoSwitchSettings;
+
+/// 单次组测速超时（毫秒）
+const GROUP_DELAY_TIMEOUT_MS: u32 = 5000;
+
+/// 最小测速间隔（秒），避免过于频繁地测速
+pub const MIN_AUTO_SWITCH_INTERVAL_SECS: u64 = 30;
+
+/// 自动切换事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSwitchEvent {
+    pub group: String,
+    pub from: Option<String>,
+    pub to: String,
+    pub delay: u32,
+    pub previous_delay: Option<u32>,
+}
+
+/// 自动切换最快节点
+///
+/// 后台定期对 select 策略组测速，并选择延迟最低的节点。
+/// 核心停止或功能关闭时任务自动退出。
+pub struct AutoSwitcher {
+    /// 任务代数，每次启动/停止都会递增，旧任务检测到代数变化后退出
+    generation: Arc<AtomicU64>,
+    mihomo_api: Arc<MihomoApi>,
+    mihomo_manager: Arc<MihomoManager>,
+}
+
+impl AutoSwitcher {
+    pub fn new(mihomo_api: Arc<MihomoApi>, mihomo_manager: Arc<MihomoManager>) -> Self {
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            mihomo_api,
+            mihomo_manager,
+        }
+    }
+
+    /// 启动后台任务（已有任务会被替换）
+    pub fn start(&self, app: AppHandle, settings: AutoSwitchSettings) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current_generation = self.generation.clone();
+        let mihomo_api = self.mihomo_api.clone();
+        let mihomo_manager = self.mihomo_manager.clone();
+
+        log::info!(
+            "Auto switch started for group '{}' (interval: {}s, threshold: {}ms)",
+            settings.group,
+            settings.interval_secs,
+            settings.threshold_ms
+        );
+
+        tokio::spawn(async move {
+            let interval =
+                Duration::from_secs(settings.interval_secs.max(MIN_AUTO_SWITCH_INTERVAL_SECS));
+
+            loop {
+                if current_generation.load(Ordering::SeqCst) != generation {
+                    break;
+                }
+
+                if !mihomo_manager.is_running().await {
+                    log::info!("Core is not running, auto switch stopped");
+                    break;
+                }
+
+                match check_and_switch(&mihomo_api, &settings).await {
+                    Ok(Some(event)) => {
+                        log::info!(
+                            "Auto switched group '{}': {:?} -> {} ({}ms)",
+                            event.group,
+                            event.from,
+                            event.to,
+                            event.delay
+                        );
+                        let _ = app.emit("auto-switch-changed", &event);
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Auto switch check failed: {}", e),
+                }
+
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
+    /// 停止后台任务
+    pub fn stop(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// 对策略组测速，必要时切换到最快节点
+async fn check_and_switch(
+    api: &MihomoApi,
+    settings: &AutoSwitchSettings,
+) -> anyhow::Result<Option<AutoSwitchEvent>> {
+    let proxies = api.get_proxies().await?;
+    let group = proxies
+        .proxies
+        .get(&settings.group)
+        .ok_or_else(|| anyhow::anyhow!("Proxy group not found: {}", settings.group))?;
+
+    if group.proxy_type != "Selector" {
+        return Err(anyhow::anyhow!(
+            "Proxy group '{}' is not a select group",
+            settings.group
+        ));
+    }
+
+    let delays = api
+        .test_group_delay(&settings.group, GROUP_DELAY_TIMEOUT_MS, &settings.test_url)
+        .await?;
+
+    let current = group.now.clone();
+    let Some((best_name, best_delay, current_delay)) =
+        pick_switch_target(&delays, current.as_deref(), settings.threshold_ms)
+    else {
+        return Ok(None);
+    };
+
+    api.select_proxy(&settings.group, &best_name).await?;
+
+    Ok(Some(AutoSwitchEvent {
+        group: settings.group.clone(),
+        from: current,
+        to: best_name,
+        delay: best_delay,
+        previous_delay: current_delay,
+    }))
+}
+
+/// 根据测速结果选出要切换到的节点，返回 `(节点, 延迟, 当前节点延迟)`
+///
+/// 延迟为 0 表示测速失败。当前节点已是最快或仍可用且与最快节点相差不超过阈值时不切换。
+fn pick_switch_target(
+    delays: &HashMap<String, u32>,
+    current: Option<&str>,
+    threshold_ms: u32,
+) -> Option<(String, u32, Option<u32>)> {
+    let (best_name, best_delay) = delays
+        .iter()
+        .filter(|(_, delay)| **delay > 0)
+        .min_by_key(|(_, delay)| **delay)?;
+
+    if current == Some(best_name.as_str()) {
+        return None;
+    }
+
+    let current_delay = current
+        .and_then(|name| delays.get(name))
+        .copied()
+        .filter(|delay| *delay > 0);
+
+    // 当前节点可用时，只有新节点快出阈值才切换
+    if current_delay.is_some_and(|delay| delay <= best_delay.saturating_add(threshold_ms)) {
+        return None;
+    }
+
+    Some((best_name.clone(), *best_delay, current_delay))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delays(entries: &[(&str, u32)]) -> HashMap<String, u32> {
+        entries
+            .iter()
+            .map(|&(name, delay)| (name.to_string(), delay))
+            .collect()
+    }
+
+    #[test]
+    fn test_pick_switch_target_respects_threshold() {
+        let delays = delays(&[("HK", 180), ("JP", 120), ("SG", 0)]);
+
+        assert_eq!(
+            pick_switch_target(&delays, Some("HK"), 50),
+            Some(("JP".to_string(), 120, Some(180)))
+        );
+        assert_eq!(pick_switch_target(&delays, Some("HK"), 60), None);
+        assert_eq!(pick_switch_target(&delays, Some("JP"), 0), None);
+    }
+
+    #[test]
+    fn test_pick_switch_target_leaves_failed_current_node() {
+        let delays = delays(&[("HK", 180), ("SG", 0)]);
+
+        assert_eq!(
+            pick_switch_target(&delays, Some("SG"), 1000),
+            Some(("HK".to_string(), 180, None))
+        );
+        assert_eq!(
+            pick_switch_target(&delays, None, 1000),
+            Some(("HK".to_string(), 180, None))
+        );
+    }
+
+    #[test]
+    fn test_pick_switch_target_all_failed() {
+        let delays = delays(&[("HK", 0), ("SG", 0)]);
+        assert_eq!(pick_switch_target(&delays, Some("HK"), 0), None);
+    }
+}
@@ -0,0 +1,170 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 核心功能及其最低支持版本
+///
+/// `config_key` 为 `/configs` 中对应的字段，核心返回该字段时直接视为支持；
+/// 未返回时回退到版本表判断。
+struct CapabilityGate {
+    name: &'static str,
+    config_key: Option<&'static str>,
+    min_version: (u32, u32, u32),
+}
+
+/// 版本表（按 mihomo 发布记录维护）
+const CAPABILITY_GATES: &[CapabilityGate] = &[
+    CapabilityGate {
+        name: "tun",
+        config_key: Some("tun"),
+        min_version: (1, 10, 0),
+    },
+    CapabilityGate {
+        name: "tun-stack-gvisor",
+        config_key: None,
+        min_version: (1, 10, 0),
+    },
+    CapabilityGate {
+        name: "tun-stack-mixed",
+        config_key: None,
+        min_version: (1, 17, 0),
+    },
+    CapabilityGate {
+        name: "tun-route-exclude-address",
+        config_key: None,
+        min_version: (1, 18, 0),
+    },
+    CapabilityGate {
+        name: "unified-delay",
+        config_key: Some("unified-delay"),
+        min_version: (1, 10, 0),
+    },
+    CapabilityGate {
+        name: "tcp-concurrent",
+        config_key: Some("tcp-concurrent"),
+        min_version: (1, 10, 0),
+    },
+    CapabilityGate {
+        name: "sniffer",
+        config_key: Some("sniffing"),
+        min_version: (1, 10, 0),
+    },
+    CapabilityGate {
+        name: "find-process-mode",
+        config_key: Some("find-process-mode"),
+        min_version: (1, 13, 0),
+    },
+    CapabilityGate {
+        name: "global-client-fingerprint",
+        config_key: None,
+        min_version: (1, 14, 0),
+    },
+    CapabilityGate {
+        name: "keep-alive-interval",
+        config_key: None,
+        min_version: (1, 16, 0),
+    },
+    CapabilityGate {
+        name: "geo-auto-update",
+        config_key: None,
+        min_version: (1, 16, 0),
+    },
+    CapabilityGate {
+        name: "rule-provider-mrs",
+        config_key: None,
+        min_version: (1, 18, 0),
+    },
+];
+
+/// 核心配置能力
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreConfigSchema {
+    /// 核心版本
+    pub version: String,
+    /// 功能名 -> 是否支持
+    pub capabilities: BTreeMap<String, bool>,
+    /// 当前运行时配置（`/configs`）
+    pub config: serde_json::Value,
+}
+
+impl CoreConfigSchema {
+    /// 根据核心版本和 `/configs` 返回值推导能力表
+    pub fn build(version: &str, config: serde_json::Value) -> Self {
+        let parsed = parse_core_version(version);
+
+        let capabilities = CAPABILITY_GATES
+            .iter()
+            .map(|gate| {
+                let exposed = gate
+                    .config_key
+                    .is_some_and(|key| config.get(key).is_some_and(|v| !v.is_null()));
+                // 无法解析的版本（如 alpha 构建）视为最新版本
+                let supported =
+                    exposed || parsed.map_or(true, |version| version >= gate.min_version);
+                (gate.name.to_string(), supported)
+            })
+            .collect();
+
+        Self {
+            version: version.to_string(),
+            capabilities,
+            config,
+        }
+    }
+}
+
+/// 解析核心版本号，如 `v1.18.5`、`Meta v1.19.0 linux amd64`
+fn parse_core_version(version: &str) -> Option<(u32, u32, u32)> {
+    let token = version.split_whitespace().find(|part| {
+        part.trim_start_matches('v')
+            .starts_with(|c: char| c.is_ascii_digit())
+    })?;
+
+    let mut numbers = token
+        .trim_start_matches('v')
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse::<u32>().ok());
+
+    let major = numbers.next()??;
+    let minor = numbers.next().flatten().unwrap_or(0);
+    let patch = numbers.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_core_version() {
+        assert_eq!(parse_core_version("v1.18.5"), Some((1, 18, 5)));
+        assert_eq!(
+            parse_core_version("Meta v1.19.0 linux amd64"),
+            Some((1, 19, 0))
+        );
+        assert_eq!(parse_core_version("1.17"), Some((1, 17, 0)));
+        assert_eq!(parse_core_version("alpha-e0c6b4f"), None);
+    }
+
+    #[test]
+    fn test_capabilities_gated_by_version() {
+        let schema = CoreConfigSchema::build("v1.17.0", serde_json::json!({}));
+        assert!(schema.capabilities["tun-stack-mixed"]);
+        assert!(!schema.capabilities["tun-route-exclude-address"]);
+        assert!(!schema.capabilities["rule-provider-mrs"]);
+    }
+
+    #[test]
+    fn test_capabilities_from_config_keys_and_unknown_version() {
+        let schema = CoreConfigSchema::build(
+            "v1.12.0",
+            serde_json::json!({ "find-process-mode": "strict", "tun": null }),
+        );
+        assert!(schema.capabilities["find-process-mode"]);
+        assert!(schema.capabilities["tun"]);
+        assert!(!schema.capabilities["keep-alive-interval"]);
+
+        let schema = CoreConfigSchema::build("alpha-e0c6b4f", serde_json::json!({}));
+        assert!(schema.capabilities.values().all(|supported| *supported));
+    }
+}
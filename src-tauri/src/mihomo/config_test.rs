@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::Composer;
+use crate::models::ValidationSeverity;
+use crate::utils::{ensure_mihomo_in_data_dir, get_app_data_dir};
+
+/// `mihomo -t` 的最长等待时间
+const CONFIG_TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 日志行中的 `msg="..."`
+static MSG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"msg="((?:[^"\\]|\\.)*)""#).unwrap());
+
+/// YAML 错误中的 `line 12` / `line 12, column 3`
+static LINE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"line (\d+)(?:,? column (\d+))?").unwrap());
+
+/// 核心报告的字段位置，如 `proxy 3`、`proxy group[2]`、`rules[10]`
+static FIELD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(proxy group|proxy-group|proxy|rule-provider|proxy-provider|rules?)\s*\[?(\d+)\]?",
+    )
+    .unwrap()
+});
+
+/// 核心配置测试中的一条错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreConfigError {
+    pub message: String,
+    /// 出错的行号（从 1 开始），核心未给出时为空
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    /// 出错的字段，如 `proxies[3]`、`proxy-groups[2]`
+    pub field: Option<String>,
+}
+
+/// 核心配置测试结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreConfigTestResult {
+    pub success: bool,
+    pub errors: Vec<CoreConfigError>,
+    /// 核心不支持 `-t`，结果来自应用内的 YAML 解析
+    pub fallback: bool,
+}
+
+impl CoreConfigError {
+    /// 从错误文本中提取行号和字段位置
+    fn from_message(message: &str) -> Self {
+        let (line, column) = LINE_RE
+            .captures(message)
+            .map(|caps| {
+                (
+                    caps.get(1).and_then(|m| m.as_str().parse().ok()),
+                    caps.get(2).and_then(|m| m.as_str().parse().ok()),
+                )
+            })
+            .unwrap_or((None, None));
+        let field = FIELD_RE.captures(message).map(|caps| {
+            let section = match caps[1].to_ascii_lowercase().as_str() {
+                "proxy" => "proxies",
+                "proxy group" | "proxy-group" => "proxy-groups",
+                "rule-provider" => "rule-providers",
+                "proxy-provider" => "proxy-providers",
+                _ => "rules",
+            };
+            format!("{}[{}]", section, &caps[2])
+        });
+        Self {
+            message: message.to_string(),
+            line,
+            column,
+            field,
+        }
+    }
+}
+
+/// 解析 `mihomo -t` 的输出，提取 error/fatal 级别的日志
+pub fn parse_config_test_output(output: &str) -> Vec<CoreConfigError> {
+    output
+        .lines()
+        .filter(|line| line.contains("level=error") || line.contains("level=fatal"))
+        .map(|line| {
+            let message = MSG_RE
+                .captures(line)
+                .map(|caps| caps[1].replace("\\\"", "\""))
+                .unwrap_or_else(|| line.trim().to_string());
+            CoreConfigError::from_message(&message)
+        })
+        .collect()
+}
+
+/// 使用核心测试配置文件（`mihomo -t -d <data_dir> -f <path>`）
+///
+/// 核心不支持 `-t` 时退回到应用内的 YAML 解析，只能发现语法错误。
+pub async fn test_config_file(path: &Path) -> Result<CoreConfigTestResult> {
+    if !path.is_file() {
+        return Err(anyhow!("配置文件不存在: {}", path.display()));
+    }
+
+    let mihomo_path = ensure_mihomo_in_data_dir()?;
+    let data_dir = get_app_data_dir()?;
+
+    let mut command = tokio::process::Command::new(&mihomo_path);
+    command
+        .arg("-t")
+        .arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg(path)
+        .kill_on_drop(true);
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = tokio::time::timeout(CONFIG_TEST_TIMEOUT, command.output())
+        .await
+        .map_err(|_| anyhow!("核心配置测试超时"))??;
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    if text.contains("flag provided but not defined") {
+        log::info!("Core does not support -t, falling back to YAML parsing");
+        return Ok(test_config_with_parser(path));
+    }
+
+    let mut errors = parse_config_test_output(&text);
+    if !output.status.success() && errors.is_empty() {
+        errors.push(CoreConfigError::from_message(text.trim()));
+    }
+
+    Ok(CoreConfigTestResult {
+        success: output.status.success() && errors.is_empty(),
+        errors,
+        fallback: false,
+    })
+}
+
+impl CoreConfigTestResult {
+    /// 未交给核心测试就已失败（如内容无法解析）
+    pub fn failed(message: &str) -> Self {
+        Self {
+            success: false,
+            errors: vec![CoreConfigError::from_message(message)],
+            fallback: false,
+        }
+    }
+}
+
+/// 应用内检查（核心不支持 `-t` 时使用）
+///
+/// 除 YAML 语法外，还检查核心会拒绝加载的引用错误（节点重名、引用不存在的代理源或规则源等）。
+fn test_config_with_parser(path: &Path) -> CoreConfigTestResult {
+    let errors = match Composer::read_yaml_file(path).and_then(|content| {
+        serde_yaml::from_str::<serde_yaml::Value>(&content)?;
+        Composer::parse_yaml(&content)
+    }) {
+        Ok(config) => Composer::validation_issues(&config)
+            .into_iter()
+            .filter(|issue| issue.severity == ValidationSeverity::Error)
+            .map(|issue| CoreConfigError {
+                message: issue.message,
+                line: None,
+                column: None,
+                field: Some(issue.field_path),
+            })
+            .collect(),
+        Err(e) => vec![CoreConfigError::from_message(&e.to_string())],
+    };
+    CoreConfigTestResult {
+        success: errors.is_empty(),
+        errors,
+        fallback: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_test_output() {
+        let output = r#"time="2024-01-01T00:00:00+08:00" level=info msg="Start initial configuration in progress"
+time="2024-01-01T00:00:00+08:00" level=error msg="proxy group[2]: 'proxies' or 'use' is missing"
+time="2024-01-01T00:00:00+08:00" level=fatal msg="yaml: line 12: mapping values are not allowed in this context"
+configuration file config.yaml test failed"#;
+
+        let errors = parse_config_test_output(output);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].field.as_deref(), Some("proxy-groups[2]"));
+        assert_eq!(errors[0].line, None);
+        assert_eq!(errors[1].line, Some(12));
+        assert_eq!(
+            errors[1].message,
+            "yaml: line 12: mapping values are not allowed in this context"
+        );
+    }
+}
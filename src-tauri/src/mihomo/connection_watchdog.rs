@@ -0,0 +1,151 @@
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::{MihomoApi, MihomoManager};
+use crate::models::{ConnectionLimitAction, ConnectionLimitSettings};
+
+/// 检查间隔
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 最小阈值，避免误关正常连接
+pub const MIN_CONNECTION_LIMIT: usize = 100;
+
+/// 连接数超限事件（`connection-limit-exceeded`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionLimitEvent {
+    pub total: usize,
+    pub threshold: usize,
+    /// 已关闭的空闲连接数（仅提醒时为 0）
+    pub closed: usize,
+}
+
+/// 连接数看门狗
+///
+/// 定期检查核心的连接总数，超过阈值时发送 `connection-limit-exceeded` 事件，
+/// 并按设置关闭最早建立的空闲连接（两次检查之间没有流量的连接）。
+/// 没有关闭连接时每次超限只提醒一次，回到阈值以下后才会再次提醒。
+pub struct ConnectionWatchdog {
+    /// 任务代数，每次启动/停止都会递增，旧任务检测到代数变化后退出
+    generation: Arc<AtomicU64>,
+    mihomo_api: Arc<MihomoApi>,
+    mihomo_manager: Arc<MihomoManager>,
+}
+
+impl ConnectionWatchdog {
+    pub fn new(mihomo_api: Arc<MihomoApi>, mihomo_manager: Arc<MihomoManager>) -> Self {
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            mihomo_api,
+            mihomo_manager,
+        }
+    }
+
+    /// 启动后台任务（已有任务会被替换）
+    pub fn start(&self, app: AppHandle, settings: ConnectionLimitSettings) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current_generation = self.generation.clone();
+        let mihomo_api = self.mihomo_api.clone();
+        let mihomo_manager = self.mihomo_manager.clone();
+
+        log::info!(
+            "Connection watchdog started (threshold: {}, action: {:?})",
+            settings.threshold,
+            settings.action
+        );
+
+        tokio::spawn(async move {
+            // 上一次检查时各连接的流量，用于判断空闲
+            let mut last_traffic: HashMap<String, u64> = HashMap::new();
+            let mut warned = false;
+
+            loop {
+                tokio::time::sleep(WATCHDOG_INTERVAL).await;
+                if current_generation.load(Ordering::SeqCst) != generation {
+                    break;
+                }
+                if !mihomo_manager.is_running().await {
+                    last_traffic.clear();
+                    continue;
+                }
+
+                let connections = match mihomo_api.get_connections().await {
+                    Ok(response) => response.connections,
+                    Err(e) => {
+                        log::debug!("Connection watchdog poll failed: {}", e);
+                        continue;
+                    }
+                };
+                let total = connections.len();
+
+                // 无法解析开始时间的连接排在最后，优先关闭确定最早建立的连接
+                let mut idle: Vec<(Option<DateTime<FixedOffset>>, &str)> = connections
+                    .iter()
+                    .filter(|conn| {
+                        last_traffic.get(&conn.id) == Some(&(conn.upload + conn.download))
+                    })
+                    .map(|conn| {
+                        (
+                            DateTime::parse_from_rfc3339(&conn.start).ok(),
+                            conn.id.as_str(),
+                        )
+                    })
+                    .collect();
+                last_traffic = connections
+                    .iter()
+                    .map(|conn| (conn.id.clone(), conn.upload + conn.download))
+                    .collect();
+
+                if total <= settings.threshold {
+                    warned = false;
+                    continue;
+                }
+
+                let mut closed = 0;
+                if settings.action == ConnectionLimitAction::CloseIdle {
+                    // 按时间点比较，不受时区和小数秒位数影响
+                    idle.sort_unstable_by_key(|(start, id)| (start.is_none(), *start, *id));
+                    for (_, id) in idle.into_iter().take(total - settings.threshold) {
+                        if current_generation.load(Ordering::SeqCst) != generation {
+                            return;
+                        }
+                        match mihomo_api.close_connection(id).await {
+                            Ok(()) => closed += 1,
+                            Err(e) => log::debug!("Failed to close connection {}: {}", id, e),
+                        }
+                    }
+                }
+                // 没有可关闭的连接时同样只提醒一次
+                if closed == 0 && warned {
+                    continue;
+                }
+                warned = true;
+
+                log::warn!(
+                    "Connection count {} exceeds limit {}, closed {} idle connections",
+                    total,
+                    settings.threshold,
+                    closed
+                );
+                let _ = app.emit(
+                    "connection-limit-exceeded",
+                    ConnectionLimitEvent {
+                        total,
+                        threshold: settings.threshold,
+                        closed,
+                    },
+                );
+            }
+        });
+    }
+
+    /// 停止后台任务
+    pub fn stop(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,121 @@
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::MihomoManager;
+
+/// 崩溃检测间隔
+const CORE_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 核心运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoreState {
+    #[default]
+    Stopped,
+    Starting,
+    Running,
+    Restarting,
+    /// 核心在未请求停止的情况下退出
+    Crashed,
+}
+
+/// 核心状态（唯一数据源）
+///
+/// 由 MihomoManager 在启动/停止/重启时更新，每次变化都会发送
+/// `core-state-changed` 事件，前端订阅事件而不是轮询 `get_proxy_status`。
+pub struct CoreStateTracker {
+    state: Mutex<CoreState>,
+    /// 正在进行的启动/停止/重启操作数，期间不做崩溃检测
+    operations: AtomicUsize,
+    app: OnceCell<AppHandle>,
+}
+
+/// 启动/停止/重启操作期间持有，释放后恢复崩溃检测
+pub struct CoreOperationGuard<'a> {
+    operations: &'a AtomicUsize,
+}
+
+impl Drop for CoreOperationGuard<'_> {
+    fn drop(&mut self) {
+        self.operations.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl CoreStateTracker {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(CoreState::Stopped),
+            operations: AtomicUsize::new(0),
+            app: OnceCell::new(),
+        }
+    }
+
+    /// 当前状态
+    pub fn get(&self) -> CoreState {
+        self.state.lock().map(|state| *state).unwrap_or_default()
+    }
+
+    /// 更新状态，状态变化时发送事件
+    pub fn set(&self, new_state: CoreState) {
+        let changed = match self.state.lock() {
+            Ok(mut state) if *state != new_state => {
+                log::debug!("Core state: {:?} -> {:?}", *state, new_state);
+                *state = new_state;
+                true
+            }
+            _ => false,
+        };
+
+        if changed {
+            if let Some(app) = self.app.get() {
+                let _ = app.emit("core-state-changed", new_state);
+            }
+        }
+    }
+
+    /// 标记一次启动/停止/重启操作开始
+    pub fn begin_operation(&self) -> CoreOperationGuard<'_> {
+        self.operations.fetch_add(1, Ordering::SeqCst);
+        CoreOperationGuard {
+            operations: &self.operations,
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        self.operations.load(Ordering::SeqCst) > 0
+    }
+
+    /// 绑定事件发送目标并启动崩溃检测（应用运行期间只需启动一次）
+    pub fn start(&self, app: AppHandle, mihomo_manager: Arc<MihomoManager>) {
+        let _ = self.app.set(app);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(CORE_WATCH_INTERVAL).await;
+
+                let tracker = mihomo_manager.core_state();
+                if tracker.is_busy() || tracker.get() != CoreState::Running {
+                    continue;
+                }
+
+                if !mihomo_manager.is_running().await
+                    && !tracker.is_busy()
+                    && tracker.get() == CoreState::Running
+                {
+                    log::warn!("MiHomo exited unexpectedly");
+                    tracker.set(CoreState::Crashed);
+                }
+            }
+        });
+    }
+}
+
+impl Default for CoreStateTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use crate::models::MihomoSettings;
+
+/// 外部进程
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalProcess {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// 被外部进程占用的端口
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConflict {
+    pub port: u16,
+    /// `controller`、`mixed`、`http` 或 `socks`
+    pub purpose: &'static str,
+    pub process: ExternalProcess,
+}
+
+/// 外部核心检测结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalCoreReport {
+    /// 被其他进程占用的 Conflux 端口
+    pub port_conflicts: Vec<PortConflict>,
+    /// 不是由 Conflux 启动的 mihomo/Clash 核心进程
+    pub processes: Vec<ExternalProcess>,
+}
+
+impl ExternalCoreReport {
+    pub fn is_empty(&self) -> bool {
+        self.port_conflicts.is_empty() && self.processes.is_empty()
+    }
+
+    /// 接管时需要结束的核心进程（去重）
+    ///
+    /// 只包含 mihomo/Clash 核心，占用端口的其他程序不会被结束。
+    pub fn pids(&self) -> Vec<u32> {
+        let mut pids: Vec<u32> = self
+            .port_conflicts
+            .iter()
+            .map(|c| &c.process)
+            .chain(self.processes.iter())
+            .filter(|p| is_core_process_name(&p.name))
+            .map(|p| p.pid)
+            .collect();
+        pids.sort_unstable();
+        pids.dedup();
+        pids
+    }
+
+    /// 占用端口但不是核心的进程，接管无法解决，需要修改端口
+    pub fn foreign_port_conflicts(&self) -> Vec<&PortConflict> {
+        self.port_conflicts
+            .iter()
+            .filter(|c| !is_core_process_name(&c.process.name))
+            .collect()
+    }
+}
+
+/// 发现外部核心时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExternalCoreAction {
+    /// 结束外部进程，由 Conflux 接管
+    TakeOver,
+    /// 保留外部进程，要求其不占用 Conflux 的端口
+    Coexist,
+}
+
+/// Conflux 使用的端口及用途
+pub fn conflux_ports(settings: &MihomoSettings) -> Vec<(u16, &'static str)> {
+    let controller = settings
+        .external_controller
+        .rsplit_once(':')
+        .and_then(|(_, port)| port.parse().ok());
+    [
+        (controller, "controller"),
+        (settings.mixed_port, "mixed"),
+        (Some(settings.port.unwrap_or(7890)), "http"),
+        (Some(settings.socks_port.unwrap_or(7891)), "socks"),
+    ]
+    .into_iter()
+    .filter_map(|(port, purpose)| port.filter(|&p| p != 0).map(|p| (p, purpose)))
+    .collect()
+}
+
+/// 是否为 mihomo/Clash 核心进程名（不含 Clash Verge 等图形界面程序本身）
+fn is_core_process_name(name: &str) -> bool {
+    let base = name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(name)
+        .to_lowercase();
+    let base = base.strip_suffix(".exe").unwrap_or(&base);
+    base.contains("mihomo")
+        || base == "clash"
+        || base.starts_with("clash-meta")
+        || base.starts_with("clash-premium")
+        || base.starts_with("clash-linux")
+        || base.starts_with("clash-darwin")
+        || base.starts_with("clash-win")
+}
+
+/// 解析 `ps -axo pid=,comm=` 的一行
+#[cfg(unix)]
+fn parse_ps_line(line: &str) -> Option<ExternalProcess> {
+    let (pid, name) = line.trim().split_once(char::is_whitespace)?;
+    Some(ExternalProcess {
+        pid: pid.parse().ok()?,
+        name: name.trim().to_string(),
+    })
+}
+
+/// 解析 `tasklist /FO CSV /NH` 的一行（`"name","pid",...`）
+#[cfg(windows)]
+fn parse_tasklist_line(line: &str) -> Option<ExternalProcess> {
+    let mut fields = line.split("\",\"").map(|f| f.trim_matches('"'));
+    let name = fields.next()?.to_string();
+    Some(ExternalProcess {
+        pid: fields.next()?.parse().ok()?,
+        name,
+    })
+}
+
+/// 列出系统中的进程
+fn list_processes() -> Vec<ExternalProcess> {
+    #[cfg(unix)]
+    {
+        let output = match Command::new("ps").args(["-axo", "pid=,comm="]).output() {
+            Ok(output) => output,
+            Err(e) => {
+                log::debug!("Failed to list processes: {}", e);
+                return Vec::new();
+            }
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(parse_ps_line)
+            .collect()
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        let output = match Command::new("tasklist")
+            .args(["/FO", "CSV", "/NH"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                log::debug!("Failed to list processes: {}", e);
+                return Vec::new();
+            }
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(parse_tasklist_line)
+            .collect()
+    }
+}
+
+/// 检测外部核心
+///
+/// `owned` 为 Conflux 启动的核心进程，`ports` 为 Conflux 使用的端口及用途，
+/// `find_pid` 查找监听指定端口的进程。
+pub(crate) fn detect_external_core(
+    owned: &[u32],
+    ports: &[(u16, &'static str)],
+    find_pid: impl Fn(u16) -> Option<u32>,
+) -> ExternalCoreReport {
+    let processes = list_processes();
+    let own_pid = std::process::id();
+    let is_foreign = |pid: u32| pid != own_pid && !owned.contains(&pid);
+    let name_of = |pid: u32| {
+        processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.name.clone())
+            .unwrap_or_default()
+    };
+
+    let mut report = ExternalCoreReport::default();
+    for &(port, purpose) in ports {
+        if report.port_conflicts.iter().any(|c| c.port == port) {
+            continue;
+        }
+        if let Some(pid) = find_pid(port).filter(|&pid| is_foreign(pid)) {
+            report.port_conflicts.push(PortConflict {
+                port,
+                purpose,
+                process: ExternalProcess {
+                    pid,
+                    name: name_of(pid),
+                },
+            });
+        }
+    }
+    report.processes = processes
+        .into_iter()
+        .filter(|p| is_foreign(p.pid) && is_core_process_name(&p.name))
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_pids_only_include_cores() {
+        let process = |pid, name: &str| ExternalProcess {
+            pid,
+            name: name.to_string(),
+        };
+        let report = ExternalCoreReport {
+            port_conflicts: vec![
+                PortConflict {
+                    port: 7890,
+                    purpose: "http",
+                    process: process(100, "mihomo"),
+                },
+                PortConflict {
+                    port: 9090,
+                    purpose: "controller",
+                    process: process(200, "nginx"),
+                },
+            ],
+            processes: vec![process(100, "mihomo"), process(300, "clash-meta")],
+        };
+
+        assert_eq!(report.pids(), vec![100, 300]);
+        let foreign = report.foreign_port_conflicts();
+        assert_eq!(foreign.len(), 1);
+        assert_eq!(foreign[0].port, 9090);
+    }
+
+    #[test]
+    fn test_is_core_process_name() {
+        assert!(is_core_process_name("mihomo"));
+        assert!(is_core_process_name(
+            "/Applications/Clash Verge.app/Contents/MacOS/verge-mihomo"
+        ));
+        assert!(is_core_process_name("mihomo-x86_64-pc-windows-msvc.exe"));
+        assert!(is_core_process_name("clash-meta"));
+        assert!(is_core_process_name("Clash.exe"));
+        assert!(!is_core_process_name("clash-verge"));
+        assert!(!is_core_process_name("conflux"));
+    }
+}
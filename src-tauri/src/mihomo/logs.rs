@@ -1,11 +1,18 @@
 use anyhow::Result;
-use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
-use tokio_tungstenite::{connect_async, tungstenite::client::IntoClientRequest};
+use tokio_tungstenite::{
+    connect_async_tls_with_config, tungstenite::client::IntoClientRequest, Connector,
+};
+
+use super::{next_with_heartbeat, ReconnectBackoff, StreamReconnectedEvent};
+
+/// 保留的最近日志条数（用于崩溃报告和日志导出）
+const MAX_RECENT_LOGS: usize = 1000;
 
 /// 日志条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,10 +20,13 @@ pub struct LogEntry {
     #[serde(rename = "type")]
     pub log_type: String,
     pub payload: String,
+    /// 收到日志的时间（Unix 毫秒时间戳）
+    #[serde(default)]
+    pub time: i64,
 }
 
-/// 日志级别
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// 日志级别（按严重程度排序）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
     Debug,
     Info,
@@ -55,8 +65,14 @@ pub struct LogStreamer {
     running: Arc<AtomicBool>,
     current_level: Arc<Mutex<LogLevel>>,
     level_changed: Arc<AtomicBool>,
-    base_url: String,
-    secret: String,
+    /// 密钥已轮换或控制器地址已切换，需要重连
+    secret_changed: Arc<AtomicBool>,
+    base_url: Arc<Mutex<String>>,
+    /// HTTPS 控制器需要信任的证书（PEM）
+    ca_cert: Arc<Mutex<Option<Vec<u8>>>>,
+    secret: Arc<Mutex<String>>,
+    /// 最近收到的日志
+    recent: Arc<std::sync::Mutex<VecDeque<LogEntry>>>,
 }
 
 impl LogStreamer {
@@ -65,8 +81,11 @@ impl LogStreamer {
             running: Arc::new(AtomicBool::new(false)),
             current_level: Arc::new(Mutex::new(LogLevel::Info)),
             level_changed: Arc::new(AtomicBool::new(false)),
-            base_url,
-            secret,
+            secret_changed: Arc::new(AtomicBool::new(false)),
+            base_url: Arc::new(Mutex::new(base_url)),
+            ca_cert: Arc::new(Mutex::new(None)),
+            secret: Arc::new(Mutex::new(secret)),
+            recent: Arc::new(std::sync::Mutex::new(VecDeque::new())),
         }
     }
 
@@ -91,11 +110,17 @@ impl LogStreamer {
         let running = self.running.clone();
         let current_level = self.current_level.clone();
         let level_changed = self.level_changed.clone();
-        let base_url = self.base_url.clone();
-        let secret = self.secret.clone();
+        let secret_changed = self.secret_changed.clone();
+        let current_base_url = self.base_url.clone();
+        let current_ca_cert = self.ca_cert.clone();
+        let current_secret = self.secret.clone();
+        let recent = self.recent.clone();
 
         tokio::spawn(async move {
             let mut current_ws_level = LogLevel::Info;
+            let mut backoff = ReconnectBackoff::new();
+            // 是否曾经连接过，用于区分首次连接和断线重连
+            let mut connected_before = false;
 
             loop {
                 if !running.load(Ordering::SeqCst) {
@@ -112,6 +137,19 @@ impl LogStreamer {
                     current_ws_level = level;
                     level_changed.store(false, Ordering::SeqCst);
                 }
+                secret_changed.store(false, Ordering::SeqCst);
+                let secret = current_secret.lock().await.clone();
+                let base_url = current_base_url.lock().await.clone();
+                let connector = match current_ca_cert.lock().await.as_deref() {
+                    Some(cert) => match crate::mihomo::trusted_tls_connector(cert) {
+                        Ok(connector) => Some(Connector::NativeTls(connector)),
+                        Err(e) => {
+                            log::error!("Failed to create TLS connector: {}", e);
+                            None
+                        }
+                    },
+                    None => None,
+                };
 
                 // 构建 WebSocket URL
                 let ws_url = base_url
@@ -124,7 +162,7 @@ impl LogStreamer {
                     Ok(r) => r,
                     Err(e) => {
                         log::error!("Failed to create WebSocket request: {}", e);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+                        tokio::time::sleep(backoff.next_delay()).await;
                         continue;
                     }
                 };
@@ -137,14 +175,25 @@ impl LogStreamer {
                 }
 
                 // 连接 WebSocket
-                match connect_async(request).await {
-                    Ok((ws_stream, _)) => {
+                // 主动断开（级别、密钥或地址变化）后立即重连，不计入失败
+                let mut reconnect_now = false;
+                match connect_async_tls_with_config(request, None, false, connector).await {
+                    Ok((mut ws_stream, _)) => {
                         log::info!("Log WebSocket connected");
                         let _ = app.emit("log-connected", true);
+                        let attempts = backoff.reset();
+                        if connected_before {
+                            let _ = app.emit(
+                                "stream-reconnected",
+                                StreamReconnectedEvent {
+                                    stream: "logs".to_string(),
+                                    attempts,
+                                },
+                            );
+                        }
+                        connected_before = true;
 
-                        let (_, mut read) = ws_stream.split();
-
-                        while let Some(message) = read.next().await {
+                        while let Some(message) = next_with_heartbeat(&mut ws_stream).await {
                             if !running.load(Ordering::SeqCst) {
                                 break;
                             }
@@ -152,25 +201,36 @@ impl LogStreamer {
                             // 检查级别是否改变，如果改变则断开以触发重连
                             if level_changed.load(Ordering::SeqCst) {
                                 log::info!("Log level changed, reconnecting...");
+                                reconnect_now = true;
+                                break;
+                            }
+                            if secret_changed.load(Ordering::SeqCst) {
+                                log::info!("API secret or endpoint changed, reconnecting...");
+                                reconnect_now = true;
                                 break;
                             }
 
                             match message {
-                                Ok(msg) => {
+                                Ok(msg) if msg.is_text() => {
                                     if let Ok(text) = msg.into_text() {
-                                        // 尝试解析为 LogEntry
-                                        if let Ok(entry) = serde_json::from_str::<LogEntry>(&text) {
-                                            let _ = app.emit("log-entry", entry);
-                                        } else {
-                                            // 如果解析失败，作为普通文本发送
-                                            let entry = LogEntry {
+                                        // 尝试解析为 LogEntry，失败时作为普通文本发送
+                                        let mut entry = serde_json::from_str::<LogEntry>(&text)
+                                            .unwrap_or_else(|_| LogEntry {
                                                 log_type: "info".to_string(),
                                                 payload: text.to_string(),
-                                            };
-                                            let _ = app.emit("log-entry", entry);
+                                                time: 0,
+                                            });
+                                        entry.time = chrono::Utc::now().timestamp_millis();
+                                        if let Ok(mut recent) = recent.lock() {
+                                            if recent.len() >= MAX_RECENT_LOGS {
+                                                recent.pop_front();
+                                            }
+                                            recent.push_back(entry.clone());
                                         }
+                                        let _ = app.emit("log-entry", entry);
                                     }
                                 }
+                                Ok(_) => {}
                                 Err(e) => {
                                     log::error!("WebSocket error: {}", e);
                                     break;
@@ -187,9 +247,9 @@ impl LogStreamer {
                 // 断开连接通知
                 let _ = app.emit("log-connected", false);
 
-                // 如果仍在运行，等待后重连
-                if running.load(Ordering::SeqCst) {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+                // 如果仍在运行，等待后重连（连续失败时逐步延长等待）
+                if running.load(Ordering::SeqCst) && !reconnect_now {
+                    tokio::time::sleep(backoff.next_delay()).await;
                 }
             }
 
@@ -204,6 +264,54 @@ impl LogStreamer {
         self.running.store(false, Ordering::SeqCst);
     }
 
+    /// 更新 API 密钥（运行中的日志流会用新密钥重连）
+    pub async fn set_secret(&self, secret: String) {
+        *self.secret.lock().await = secret;
+        self.secret_changed.store(true, Ordering::SeqCst);
+    }
+
+    /// 切换控制器地址（运行中的日志流会重连）
+    pub async fn set_endpoint(&self, base_url: String, ca_cert: Option<Vec<u8>>) {
+        *self.base_url.lock().await = base_url;
+        *self.ca_cert.lock().await = ca_cert;
+        self.secret_changed.store(true, Ordering::SeqCst);
+    }
+
+    /// 最近的日志（最多 `limit` 条，按时间顺序）
+    pub fn recent(&self, limit: usize) -> Vec<LogEntry> {
+        self.recent
+            .lock()
+            .map(|recent| {
+                let skip = recent.len().saturating_sub(limit);
+                recent.iter().skip(skip).cloned().collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 缓存的日志条数和估算的内存占用（字节）
+    pub fn memory_usage(&self) -> (usize, usize) {
+        self.recent
+            .lock()
+            .map(|recent| {
+                let bytes = recent
+                    .iter()
+                    .map(|e| std::mem::size_of::<LogEntry>() + e.log_type.len() + e.payload.len())
+                    .sum();
+                (recent.len(), bytes)
+            })
+            .unwrap_or_default()
+    }
+
+    /// 丢弃最早的 `count` 条缓存日志，返回实际丢弃的数量
+    pub fn evict_oldest(&self, count: usize) -> usize {
+        let Ok(mut recent) = self.recent.lock() else {
+            return 0;
+        };
+        let count = count.min(recent.len());
+        recent.drain(..count);
+        count
+    }
+
     /// 更新日志级别（会触发重新连接）
     pub async fn set_level(&self, level: LogLevel) {
         let mut current = self.current_level.lock().await;
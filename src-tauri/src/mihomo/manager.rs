@@ -2,11 +2,13 @@ use anyhow::Result;
 use std::fs;
 use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, Command};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration};
 
+use super::{detect_external_core, CoreState, CoreStateTracker, ExternalCoreReport, MihomoApi};
+use crate::models::CorePriority;
 use crate::utils::{ensure_mihomo_in_data_dir, get_app_data_dir, get_mihomo_config_path};
 
 /// 检查配置文件中 TUN 模式是否启用
@@ -101,12 +103,21 @@ async fn wait_for_service_ipc(max_wait: Duration) -> bool {
     false
 }
 
+/// 缺少权限或用户取消授权导致无法启动核心
+///
+/// 不是核心本身的故障，不计入安全模式的失败次数。
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct LaunchPermissionError(pub String);
+
 /// MiHomo 进程管理器
 pub struct MihomoManager {
     process: Arc<Mutex<Option<Child>>>,
     config_path: PathBuf,
     api_url: String,
-    api_secret: String,
+    api_secret: std::sync::RwLock<String>,
+    core_state: CoreStateTracker,
+    priority: std::sync::RwLock<CorePriority>,
 }
 
 impl MihomoManager {
@@ -118,10 +129,132 @@ impl MihomoManager {
             process: Arc::new(Mutex::new(None)),
             config_path,
             api_url: "http://127.0.0.1:9191".to_string(),
-            api_secret: secret,
+            api_secret: std::sync::RwLock::new(secret),
+            core_state: CoreStateTracker::new(),
+            priority: std::sync::RwLock::new(CorePriority::default()),
         })
     }
 
+    /// 核心运行状态
+    pub fn core_state(&self) -> &CoreStateTracker {
+        &self.core_state
+    }
+
+    /// 更新健康检查使用的 API 密钥
+    pub fn set_secret(&self, secret: String) {
+        if let Ok(mut current) = self.api_secret.write() {
+            *current = secret;
+        }
+    }
+
+    /// 设置核心进程优先级（下次启动或调用 `apply_priority` 时生效）
+    pub fn set_priority(&self, priority: CorePriority) {
+        if let Ok(mut current) = self.priority.write() {
+            *current = priority;
+        }
+    }
+
+    fn priority(&self) -> CorePriority {
+        self.priority.read().map(|p| *p).unwrap_or_default()
+    }
+
+    /// 将设置的优先级应用到正在运行的核心进程
+    ///
+    /// 只能调整由应用直接启动的核心；helper / Windows 服务启动的核心没有 PID 文件，返回错误
+    pub fn apply_priority(&self) -> Result<()> {
+        let pid = Self::load_pid().ok_or_else(|| anyhow::anyhow!("未找到核心进程 PID"))?;
+        let priority = self.priority();
+        Self::set_process_priority(pid, priority)?;
+        log::info!("Set MiHomo (PID {}) priority to {:?}", pid, priority);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn nice_value(priority: CorePriority) -> libc::c_int {
+        match priority {
+            CorePriority::Low => 10,
+            CorePriority::Normal => 0,
+            CorePriority::High => -5,
+        }
+    }
+
+    /// 在创建核心进程时设置优先级，避免启动和加载规则期间以默认优先级运行
+    ///
+    /// 调高优先级需要 root，失败时以默认优先级继续启动（设置时已拒绝非 root 的 high）。
+    #[cfg(unix)]
+    fn set_spawn_priority(&self, command: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        let priority = self.priority();
+        if priority == CorePriority::Normal {
+            return;
+        }
+        let nice = Self::nice_value(priority);
+        // SAFETY: pre_exec 中只调用 async-signal-safe 的 setpriority
+        unsafe {
+            command.pre_exec(move || {
+                libc::setpriority(libc::PRIO_PROCESS, 0, nice);
+                Ok(())
+            });
+        }
+    }
+
+    /// 创建核心进程时使用的优先级标志
+    #[cfg(windows)]
+    fn spawn_priority_flags(&self) -> u32 {
+        use windows_sys::Win32::System::Threading::{
+            ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+        };
+
+        match self.priority() {
+            CorePriority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+            CorePriority::Normal => 0,
+            CorePriority::High => ABOVE_NORMAL_PRIORITY_CLASS,
+        }
+    }
+
+    #[cfg(unix)]
+    fn set_process_priority(pid: u32, priority: CorePriority) -> Result<()> {
+        let nice = Self::nice_value(priority);
+        // 普通用户只能调低优先级（增大 nice），调高需要 root
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) } != 0 {
+            return Err(anyhow::anyhow!(
+                "setpriority failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn set_process_priority(pid: u32, priority: CorePriority) -> Result<()> {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::Threading::{
+            OpenProcess, SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS,
+            BELOW_NORMAL_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, PROCESS_SET_INFORMATION,
+        };
+
+        let class = match priority {
+            CorePriority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+            CorePriority::Normal => NORMAL_PRIORITY_CLASS,
+            CorePriority::High => ABOVE_NORMAL_PRIORITY_CLASS,
+        };
+        let handle = unsafe { OpenProcess(PROCESS_SET_INFORMATION, 0, pid) };
+        if handle.is_null() {
+            return Err(anyhow::anyhow!(
+                "OpenProcess failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let ok = unsafe { SetPriorityClass(handle, class) };
+        let error = std::io::Error::last_os_error();
+        unsafe { CloseHandle(handle) };
+        if ok == 0 {
+            return Err(anyhow::anyhow!("SetPriorityClass failed: {}", error));
+        }
+        Ok(())
+    }
+
     /// 获取 PID 文件路径
     fn get_pid_file_path() -> Result<PathBuf> {
         let data_dir = get_app_data_dir()?;
@@ -233,6 +366,68 @@ impl MihomoManager {
         }
     }
 
+    /// 由 Conflux 启动的核心进程（子进程、PID 文件、helper 和 Windows 服务）
+    async fn owned_pids(&self) -> Vec<u32> {
+        let mut pids: Vec<u32> = self
+            .process
+            .lock()
+            .await
+            .as_ref()
+            .map(Child::id)
+            .into_iter()
+            .collect();
+        pids.extend(Self::load_pid());
+
+        #[cfg(target_os = "macos")]
+        {
+            use crate::utils::HELPER_PID_FILE;
+            if let Ok(content) = fs::read_to_string(HELPER_PID_FILE) {
+                pids.extend(content.trim().parse::<u32>().ok());
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use crate::system::WinServiceManager;
+            if WinServiceManager::is_running().unwrap_or(false) {
+                if let Ok(status) = WinServiceManager::get_status().await {
+                    pids.extend(status.mihomo_pid);
+                }
+            }
+        }
+
+        pids
+    }
+
+    /// 检测不由 Conflux 管理的核心进程，以及占用 `ports` 的其他进程
+    pub async fn detect_external_core(&self, ports: &[(u16, &'static str)]) -> ExternalCoreReport {
+        let owned = self.owned_pids().await;
+        let ports = ports.to_vec();
+        tokio::task::spawn_blocking(move || {
+            detect_external_core(&owned, &ports, Self::find_pid_by_port)
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    /// 结束检测到的外部进程，返回仍在运行的进程
+    pub fn kill_external_processes(report: &ExternalCoreReport) -> Vec<u32> {
+        let mut remaining = Vec::new();
+        for pid in report.pids() {
+            log::info!("Taking over from external process {}", pid);
+            Self::kill_process_by_pid(pid);
+            // 其他应用以 root 启动的核心（如 TUN 模式）需要通过 helper 结束
+            #[cfg(target_os = "macos")]
+            if Self::is_pid_running(pid) {
+                Self::kill_process_via_helper(pid);
+            }
+            if Self::is_pid_running(pid) {
+                remaining.push(pid);
+            }
+        }
+        remaining
+    }
+
     /// 清理残留的 MiHomo 进程
     /// 在启动新进程前调用，确保没有僵尸进程
     pub fn cleanup_stale_processes() {
@@ -534,8 +729,32 @@ impl MihomoManager {
         ))
     }
 
+    /// 启动（包括服务、helper 等所有方式）结束后更新安全模式的失败计数
+    ///
+    /// 用户拒绝授权等权限问题不是核心本身的故障，不计入失败次数。
+    fn finish_launch_attempt(&self, result: &Result<()>) {
+        match result {
+            Ok(()) => crate::utils::clear_launch_failures(),
+            Err(e) if e.downcast_ref::<LaunchPermissionError>().is_some() => {
+                crate::utils::forget_launch_attempt();
+            }
+            Err(_) => {}
+        }
+    }
+
     /// 启动 MiHomo 进程
     pub async fn start(&self) -> Result<()> {
+        let _operation = self.core_state.begin_operation();
+        self.core_state.set(CoreState::Starting);
+
+        crate::utils::record_launch_attempt();
+        let result = self.start_process().await;
+        self.finish_launch_attempt(&result);
+        self.settle_core_state().await;
+        result
+    }
+
+    async fn start_process(&self) -> Result<()> {
         let mut process_guard = self.process.lock().await;
 
         if process_guard.is_some() {
@@ -604,9 +823,11 @@ impl MihomoManager {
         let config_path_str = self.config_path.to_string_lossy().to_string();
 
         // 启动 mihomo 进程
-        // 注意：使用 Stdio::null() 丢弃 stdout/stderr 输出
+        // 注意：stdout/stderr 写入核心日志文件，供崩溃报告读取
         // 不能使用 Stdio::piped() 因为如果不读取管道，缓冲区会满导致进程阻塞
-        // mihomo 的日志已经通过 WebSocket API (/logs) 获取，无需从 stdout/stderr 读取
+        // 实时日志通过 WebSocket API (/logs) 获取
+        #[cfg(not(target_os = "macos"))]
+        let (core_stdout, core_stderr) = crate::utils::core_log_stdio();
         #[cfg(windows)]
         let child = {
             use std::os::windows::process::CommandExt;
@@ -659,20 +880,22 @@ impl MihomoManager {
                     .current_dir(config_dir)
                     .env("SAFE_PATHS", &config_dir_str)
                     .args(["-d", &config_dir_str, "-f", &config_path_str])
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
+                    .creation_flags(CREATE_NO_WINDOW | self.spawn_priority_flags())
+                    .stdout(core_stdout)
+                    .stderr(core_stderr)
                     .spawn()
                     .map_err(|e| anyhow::anyhow!("Failed to spawn mihomo: {}", e))?
             }
             // 优先级 3：TUN 启用但没有权限，返回错误
             else if tun_enabled {
                 log::error!("TUN mode enabled but not running as admin and service not running");
-                return Err(anyhow::anyhow!(
+                return Err(LaunchPermissionError(
                     "NEED_ADMIN:增强模式需要管理员权限。请选择以下方式之一：\n\
                     1. 在设置中安装并启动 Conflux 服务（推荐）\n\
                     2. 以管理员身份重新启动应用"
-                ));
+                        .to_string(),
+                )
+                .into());
             }
             // 优先级 4：普通模式，直接启动
             else {
@@ -682,9 +905,9 @@ impl MihomoManager {
                     .current_dir(config_dir)
                     .env("SAFE_PATHS", &config_dir_str)
                     .args(["-d", &config_dir_str, "-f", &config_path_str])
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
+                    .creation_flags(CREATE_NO_WINDOW | self.spawn_priority_flags())
+                    .stdout(core_stdout)
+                    .stderr(core_stderr)
                     .spawn()
                     .map_err(|e| {
                         log::error!("Failed to spawn mihomo: {}", e);
@@ -759,12 +982,15 @@ impl MihomoManager {
             } else {
                 // 没有 helper 权限：普通模式启动
                 log::info!("Starting mihomo in normal mode (no helper permission)...");
-                let child = Command::new(&mihomo_path)
+                let (core_stdout, core_stderr) = crate::utils::core_log_stdio();
+                let mut command = Command::new(&mihomo_path);
+                self.set_spawn_priority(&mut command);
+                let child = command
                     .current_dir(config_dir)
                     .env("SAFE_PATHS", &config_dir_str)
                     .args(["-d", &config_dir_str, "-f", &config_path_str])
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
+                    .stdout(core_stdout)
+                    .stderr(core_stderr)
                     .spawn()
                     .map_err(|e| anyhow::anyhow!("Failed to spawn mihomo: {}", e))?;
 
@@ -775,12 +1001,14 @@ impl MihomoManager {
         // Linux: 直接启动
         #[cfg(target_os = "linux")]
         let (child, started_via_helper) = {
-            let child = Command::new(&mihomo_path)
+            let mut command = Command::new(&mihomo_path);
+            self.set_spawn_priority(&mut command);
+            let child = command
                 .current_dir(config_dir)
                 .env("SAFE_PATHS", &config_dir_str)
                 .args(["-d", &config_dir_str, "-f", &config_path_str])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
+                .stdout(core_stdout)
+                .stderr(core_stderr)
                 .spawn()
                 .map_err(|e| anyhow::anyhow!("Failed to spawn mihomo: {}", e))?;
 
@@ -862,7 +1090,7 @@ impl MihomoManager {
                             e
                         );
                         // 尝试清理进程
-                        let _ = self.stop().await;
+                        let _ = self.stop_process().await;
                         return Err(anyhow::anyhow!("MiHomo failed to start: {}", e));
                     }
                     log::debug!(
@@ -891,6 +1119,14 @@ impl MihomoManager {
     /// - macOS: 检查 helper PID 文件判断是否为 TUN 模式
     /// - 普通模式: 直接 kill
     pub async fn stop(&self) -> Result<()> {
+        let _operation = self.core_state.begin_operation();
+
+        let result = self.stop_process().await;
+        self.settle_core_state().await;
+        result
+    }
+
+    async fn stop_process(&self) -> Result<()> {
         // Windows: 动态检测是否通过服务启动
         #[cfg(target_os = "windows")]
         {
@@ -1121,6 +1357,12 @@ impl MihomoManager {
     ///
     /// 停止逻辑根据当前运行状态选择方式
     pub fn stop_sync(&self) {
+        let _operation = self.core_state.begin_operation();
+        self.stop_sync_process();
+        self.core_state.set(CoreState::Stopped);
+    }
+
+    fn stop_sync_process(&self) {
         log::info!("Synchronously stopping MiHomo...");
 
         // Windows: 动态检测是否需要通过服务停止
@@ -1241,6 +1483,31 @@ impl MihomoManager {
         Self::remove_pid_file();
     }
 
+    /// 操作结束后按进程实际情况确定核心状态
+    async fn settle_core_state(&self) {
+        let state = if self.is_running().await {
+            CoreState::Running
+        } else {
+            CoreState::Stopped
+        };
+        self.core_state.set(state);
+    }
+
+    /// 通过 API 重启核心（POST /restart），进程不变
+    ///
+    /// 期间状态为 `Restarting`，等待 API 重新就绪后再更新状态。
+    pub async fn api_restart(&self, mihomo_api: &MihomoApi) -> Result<()> {
+        let _operation = self.core_state.begin_operation();
+        self.core_state.set(CoreState::Restarting);
+
+        let result = mihomo_api.restart().await;
+        if result.is_ok() {
+            sleep(Duration::from_secs(1)).await;
+        }
+        self.settle_core_state().await;
+        result
+    }
+
     /// 重启 MiHomo 进程
     ///
     /// 优化后的重启流程：
@@ -1249,10 +1516,21 @@ impl MihomoManager {
     /// 3. 必要时清理残留进程
     /// 4. 确认进程已停止后再启动新进程
     pub async fn restart(&self) -> Result<()> {
+        let _operation = self.core_state.begin_operation();
+        self.core_state.set(CoreState::Restarting);
+
+        crate::utils::record_launch_attempt();
+        let result = self.restart_process().await;
+        self.finish_launch_attempt(&result);
+        self.settle_core_state().await;
+        result
+    }
+
+    async fn restart_process(&self) -> Result<()> {
         log::info!("Restarting MiHomo...");
 
         // 停止当前进程
-        self.stop().await?;
+        self.stop_process().await?;
 
         // 使用指数退避等待进程停止
         let max_wait = Duration::from_secs(5);
@@ -1298,7 +1576,7 @@ impl MihomoManager {
         }
 
         // 启动新进程
-        self.start().await?;
+        self.start_process().await?;
 
         log::info!("MiHomo restarted successfully");
         Ok(())
@@ -1389,9 +1667,14 @@ impl MihomoManager {
 
         let url = format!("{}/version", self.api_url);
 
+        let secret = self
+            .api_secret
+            .read()
+            .map(|s| s.clone())
+            .unwrap_or_default();
         let mut request = client.get(&url);
-        if !self.api_secret.is_empty() {
-            request = request.header("Authorization", format!("Bearer {}", self.api_secret));
+        if !secret.is_empty() {
+            request = request.header("Authorization", format!("Bearer {}", secret));
         }
 
         let response = request.send().await?;
@@ -1,7 +1,37 @@
 mod api;
+mod auto_switch;
+mod capabilities;
+mod config_test;
+mod connection_watchdog;
+mod controller_tls;
+mod core_state;
+mod external_core;
 mod logs;
 mod manager;
+mod node_test;
+mod observability;
+mod rule_hits;
+mod selection_schedule;
+mod stream_reconnect;
+mod temporary_direct;
+mod trace;
+mod traffic_history;
 
 pub use api::*;
+pub use auto_switch::*;
+pub use capabilities::*;
+pub use config_test::*;
+pub use connection_watchdog::*;
+pub use controller_tls::*;
+pub use core_state::*;
+pub use external_core::*;
 pub use logs::*;
 pub use manager::*;
+pub use node_test::*;
+pub use observability::*;
+pub use rule_hits::*;
+pub use selection_schedule::*;
+pub use stream_reconnect::*;
+pub use temporary_direct::*;
+pub use trace::*;
+pub use traffic_history::*;
@@ -0,0 +1,163 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::process::Child;
+
+use crate::models::{DelayResponse, ProxyConfig};
+use crate::utils::{ensure_mihomo_in_data_dir, get_app_data_dir};
+
+/// 等待临时核心控制器就绪的最长时间
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 轮询控制器的间隔
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 单个节点的测试结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StandaloneNodeTest {
+    pub reachable: bool,
+    /// 延迟（毫秒），不可达时为空
+    pub delay: Option<u32>,
+    /// 不可达的原因
+    pub error: Option<String>,
+}
+
+/// 在不修改任何 Profile 的情况下测试节点
+///
+/// 使用只包含该节点的最小配置启动一个临时核心（独立目录、随机控制器端口、不监听代理端口），
+/// 通过控制器的延迟测试接口测试后立即结束进程并删除临时目录，不影响正在运行的核心。
+pub async fn test_standalone_node(
+    proxy: &ProxyConfig,
+    url: &str,
+    timeout_ms: u32,
+) -> Result<StandaloneNodeTest> {
+    let home = get_app_data_dir()?.join(format!("node-test-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home)?;
+
+    let mut child = None;
+    let result = run_test(&home, &mut child, proxy, url, timeout_ms).await;
+
+    // 无论成功与否都清理临时核心和目录
+    if let Some(mut child) = child {
+        let _ = child.kill().await;
+    }
+    if let Err(e) = std::fs::remove_dir_all(&home) {
+        log::warn!("Failed to remove node test directory {:?}: {}", home, e);
+    }
+    result
+}
+
+async fn run_test(
+    home: &Path,
+    child: &mut Option<Child>,
+    proxy: &ProxyConfig,
+    url: &str,
+    timeout_ms: u32,
+) -> Result<StandaloneNodeTest> {
+    let port = TcpListener::bind(("127.0.0.1", 0))
+        .await?
+        .local_addr()?
+        .port();
+    let secret = uuid::Uuid::new_v4().simple().to_string();
+
+    let config = serde_json::json!({
+        "mixed-port": 0,
+        "external-controller": format!("127.0.0.1:{}", port),
+        "secret": secret,
+        "mode": "global",
+        "log-level": "silent",
+        "ipv6": true,
+        "geo-auto-update": false,
+        "profile": { "store-selected": false, "store-fake-ip": false },
+        "proxies": [proxy],
+        "rules": ["MATCH,DIRECT"],
+    });
+    let config_path = home.join("config.yaml");
+    std::fs::write(&config_path, serde_yaml::to_string(&config)?)?;
+
+    let mut command = tokio::process::Command::new(ensure_mihomo_in_data_dir()?);
+    command
+        .arg("-d")
+        .arg(home)
+        .arg("-f")
+        .arg(&config_path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let process = child.insert(
+        command
+            .spawn()
+            .map_err(|e| anyhow!("启动测试核心失败: {}", e))?,
+    );
+
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = Client::builder()
+        .timeout(Duration::from_millis(u64::from(timeout_ms)) + Duration::from_secs(2))
+        .build()?;
+
+    // 等待控制器就绪；核心提前退出说明节点配置无效
+    let started = Instant::now();
+    loop {
+        if let Some(status) = process.try_wait()? {
+            return Err(anyhow!("节点配置无效，测试核心已退出（{}）", status));
+        }
+        let ready = client
+            .get(format!("{}/version", base_url))
+            .bearer_auth(&secret)
+            .timeout(Duration::from_secs(1))
+            .send()
+            .await
+            .is_ok_and(|resp| resp.status().is_success());
+        if ready {
+            break;
+        }
+        if started.elapsed() > STARTUP_TIMEOUT {
+            return Err(anyhow!("测试核心启动超时"));
+        }
+        tokio::time::sleep(STARTUP_POLL_INTERVAL).await;
+    }
+
+    let response = client
+        .get(format!(
+            "{}/proxies/{}/delay",
+            base_url,
+            urlencoding::encode(&proxy.name)
+        ))
+        .bearer_auth(&secret)
+        .query(&[
+            ("timeout", timeout_ms.to_string()),
+            ("url", url.to_string()),
+        ])
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        let delay: DelayResponse = response.json().await?;
+        Ok(StandaloneNodeTest {
+            reachable: true,
+            delay: Some(delay.delay),
+            error: None,
+        })
+    } else {
+        let message = response
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|body| body.get("message")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| "连接超时或节点不可用".to_string());
+        Ok(StandaloneNodeTest {
+            reachable: false,
+            delay: None,
+            error: Some(message),
+        })
+    }
+}
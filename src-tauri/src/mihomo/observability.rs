@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{ConnectionTracer, LogStreamer, RuleHitCounter, TrafficHistory};
+use crate::models::ObservabilitySettings;
+
+/// 检查内存占用的间隔
+const BUDGET_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 每次从占用最多的缓冲区中丢弃的比例（1/N）
+const EVICT_FRACTION: usize = 10;
+
+/// 内存预算的取值范围（MB）
+pub const MIN_OBSERVABILITY_BUDGET_MB: u32 = 8;
+pub const MAX_OBSERVABILITY_BUDGET_MB: u32 = 1024;
+
+/// 流量历史保留时长的上限（分钟）
+pub const MAX_TRAFFIC_RETENTION_MINUTES: u32 = 24 * 60;
+
+/// 单个诊断数据缓冲区的占用
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferUsage {
+    /// `traffic`、`logs`、`trace` 或 `rule-hits`
+    pub name: &'static str,
+    pub entries: usize,
+    /// 估算的内存占用（字节）
+    pub bytes: usize,
+}
+
+/// 诊断数据的内存占用
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservabilityStats {
+    pub budget_bytes: usize,
+    pub total_bytes: usize,
+    pub buffers: Vec<BufferUsage>,
+}
+
+/// 诊断数据内存预算
+///
+/// 统一限制流量历史、日志缓存、连接追踪和规则命中计数的总内存占用。
+/// 后台定期检查，超出预算时从占用最多的缓冲区丢弃最早的数据（规则命中丢弃命中最少的规则）。
+pub struct ObservabilityBudget {
+    budget_bytes: Arc<AtomicUsize>,
+    traffic_history: Arc<TrafficHistory>,
+    log_streamer: Arc<LogStreamer>,
+    connection_tracer: Arc<ConnectionTracer>,
+    rule_hits: Arc<RuleHitCounter>,
+}
+
+impl ObservabilityBudget {
+    pub fn new(
+        traffic_history: Arc<TrafficHistory>,
+        log_streamer: Arc<LogStreamer>,
+        connection_tracer: Arc<ConnectionTracer>,
+        rule_hits: Arc<RuleHitCounter>,
+    ) -> Self {
+        let settings = ObservabilitySettings::default();
+        Self {
+            budget_bytes: Arc::new(AtomicUsize::new(budget_bytes(&settings))),
+            traffic_history,
+            log_streamer,
+            connection_tracer,
+            rule_hits,
+        }
+    }
+
+    /// 应用内存预算、保留时长和规则命中统计开关，超出预算的部分立即丢弃
+    pub fn apply_settings(&self, settings: &ObservabilitySettings) {
+        self.budget_bytes
+            .store(budget_bytes(settings), Ordering::SeqCst);
+        self.traffic_history.set_retention(Duration::from_secs(
+            u64::from(settings.traffic_retention_minutes) * 60,
+        ));
+        self.rule_hits.set_enabled(settings.rule_hit_counting);
+        self.enforce();
+    }
+
+    /// 启动后台检查任务（应用运行期间只需启动一次）
+    pub fn start(self: &Arc<Self>) {
+        let budget = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(BUDGET_CHECK_INTERVAL).await;
+                budget.enforce();
+            }
+        });
+    }
+
+    pub fn stats(&self) -> ObservabilityStats {
+        let buffers = self.usage();
+        ObservabilityStats {
+            budget_bytes: self.budget_bytes.load(Ordering::SeqCst),
+            total_bytes: buffers.iter().map(|b| b.bytes).sum(),
+            buffers,
+        }
+    }
+
+    fn usage(&self) -> Vec<BufferUsage> {
+        let usage = |name, (entries, bytes)| BufferUsage {
+            name,
+            entries,
+            bytes,
+        };
+        vec![
+            usage("traffic", self.traffic_history.memory_usage()),
+            usage("logs", self.log_streamer.memory_usage()),
+            usage("trace", self.connection_tracer.memory_usage()),
+            usage("rule-hits", self.rule_hits.memory_usage()),
+        ]
+    }
+
+    /// 超出预算时逐步丢弃数据，返回丢弃的条目数
+    fn enforce(&self) -> usize {
+        let budget = self.budget_bytes.load(Ordering::SeqCst);
+        let mut evicted = 0;
+        while let Some((name, count)) = pick_eviction(&self.usage(), budget) {
+            let removed = match name {
+                "traffic" => self.traffic_history.evict_oldest(count),
+                "logs" => self.log_streamer.evict_oldest(count),
+                "trace" => self.connection_tracer.evict_oldest(count),
+                _ => self.rule_hits.evict_least_hit(count),
+            };
+            if removed == 0 {
+                break;
+            }
+            evicted += removed;
+        }
+        if evicted > 0 {
+            log::info!(
+                "Evicted {} diagnostic entries to stay within {} bytes",
+                evicted,
+                budget
+            );
+        }
+        evicted
+    }
+}
+
+fn budget_bytes(settings: &ObservabilitySettings) -> usize {
+    settings.memory_budget_mb as usize * 1024 * 1024
+}
+
+/// 超出预算时选择占用最多的缓冲区及要丢弃的条目数，未超出时返回 None
+fn pick_eviction(buffers: &[BufferUsage], budget: usize) -> Option<(&'static str, usize)> {
+    let total: usize = buffers.iter().map(|b| b.bytes).sum();
+    if total <= budget {
+        return None;
+    }
+    let largest = buffers
+        .iter()
+        .filter(|b| b.entries > 0)
+        .max_by_key(|b| b.bytes)?;
+    Some((largest.name, (largest.entries / EVICT_FRACTION).max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_eviction() {
+        let buffers = vec![
+            BufferUsage {
+                name: "traffic",
+                entries: 3600,
+                bytes: 86_400,
+            },
+            BufferUsage {
+                name: "logs",
+                entries: 5,
+                bytes: 1_000,
+            },
+            BufferUsage {
+                name: "trace",
+                entries: 0,
+                bytes: 0,
+            },
+        ];
+        assert_eq!(pick_eviction(&buffers, 100_000), None);
+        assert_eq!(pick_eviction(&buffers, 50_000), Some(("traffic", 360)));
+
+        let small = &buffers[1..];
+        assert_eq!(pick_eviction(small, 500), Some(("logs", 1)));
+        assert_eq!(pick_eviction(&small[1..], 0), None);
+    }
+}
@@ -0,0 +1,271 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::{
+    next_with_heartbeat, CoreState, MihomoApi, MihomoManager, ReconnectBackoff,
+    StreamReconnectedEvent,
+};
+use crate::models::{Connection, ConnectionsResponse};
+
+/// 核心推送连接快照的间隔（毫秒），短于该间隔的连接可能被漏记
+const CONNECTIONS_PUSH_INTERVAL_MS: u64 = 500;
+
+/// 等待核心启动的轮询间隔
+const CORE_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 单条规则的命中次数
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleHitCount {
+    /// 规则，如 `DomainSuffix,google.com`、`Match`
+    pub rule: String,
+    pub hits: u64,
+}
+
+/// 规则命中统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleHitStats {
+    /// 是否正在统计
+    pub enabled: bool,
+    /// 统计开始时间（Unix 毫秒时间戳）
+    pub since: i64,
+    /// 统计到的连接总数
+    pub total: u64,
+    /// 按命中次数降序
+    pub rules: Vec<RuleHitCount>,
+}
+
+struct RuleHitBuffer {
+    since: i64,
+    counts: HashMap<String, u64>,
+    /// 上一次快照中的连接 ID
+    seen: HashSet<String>,
+}
+
+impl RuleHitBuffer {
+    fn new() -> Self {
+        Self {
+            since: chrono::Utc::now().timestamp_millis(),
+            counts: HashMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// 记录一次连接快照，只统计新出现的连接
+    fn record(&mut self, connections: &[Connection]) {
+        let mut current = HashSet::with_capacity(connections.len());
+        for conn in connections {
+            if !self.seen.contains(&conn.id) {
+                *self.counts.entry(rule_key(conn)).or_default() += 1;
+            }
+            current.insert(conn.id.clone());
+        }
+        // 连接关闭后 ID 不会再出现，只保留当前快照即可
+        self.seen = current;
+    }
+}
+
+fn rule_key(conn: &Connection) -> String {
+    if conn.rule_payload.is_empty() {
+        conn.rule.clone()
+    } else {
+        format!("{},{}", conn.rule, conn.rule_payload)
+    }
+}
+
+/// 规则命中计数
+///
+/// 启用后订阅核心的 `/connections` WebSocket，按每条新连接命中的规则计数，
+/// 用于找出从不生效的规则。核心重启或配置重载后自动清零；关闭时断开订阅。
+pub struct RuleHitCounter {
+    buffer: Arc<Mutex<RuleHitBuffer>>,
+    enabled: Arc<AtomicBool>,
+}
+
+impl RuleHitCounter {
+    pub fn new() -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(RuleHitBuffer::new())),
+            enabled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 开启或关闭统计；重新开启时从新的会话开始计数
+    pub fn set_enabled(&self, enabled: bool) {
+        if self.enabled.swap(enabled, Ordering::SeqCst) == enabled {
+            return;
+        }
+        if enabled {
+            if let Ok(mut buffer) = self.buffer.lock() {
+                *buffer = RuleHitBuffer::new();
+            }
+        }
+        log::info!(
+            "Rule hit counting {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    /// 启动后台订阅任务（应用运行期间只需启动一次）
+    pub fn start(
+        &self,
+        app: AppHandle,
+        mihomo_api: Arc<MihomoApi>,
+        mihomo_manager: Arc<MihomoManager>,
+    ) {
+        let buffer = self.buffer.clone();
+        let enabled = self.enabled.clone();
+        let path = format!("/connections?interval={}", CONNECTIONS_PUSH_INTERVAL_MS);
+
+        tokio::spawn(async move {
+            let mut backoff = ReconnectBackoff::new();
+            // 上次断开前是否已连接（核心重启不算断线重连）
+            let mut dropped = false;
+
+            loop {
+                // 等待开启统计且核心运行；期间核心停止或重启过则从新的会话开始计数
+                let mut restarted = false;
+                loop {
+                    let running = mihomo_manager.core_state().get() == CoreState::Running;
+                    if running && enabled.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    restarted |= !running;
+                    tokio::time::sleep(CORE_WAIT_INTERVAL).await;
+                }
+                if restarted {
+                    dropped = false;
+                    backoff.reset();
+                    if let Ok(mut buffer) = buffer.lock() {
+                        *buffer = RuleHitBuffer::new();
+                    }
+                }
+
+                match mihomo_api.connect_websocket(&path).await {
+                    Ok(mut stream) => {
+                        let attempts = backoff.reset();
+                        if dropped {
+                            log::info!("Connections WebSocket reconnected");
+                            let _ = app.emit(
+                                "stream-reconnected",
+                                StreamReconnectedEvent {
+                                    stream: "connections".to_string(),
+                                    attempts,
+                                },
+                            );
+                        }
+                        dropped = true;
+
+                        while let Some(message) = next_with_heartbeat(&mut stream).await {
+                            if !enabled.load(Ordering::SeqCst) {
+                                break;
+                            }
+                            let text = match message {
+                                Ok(msg) if msg.is_text() => match msg.into_text() {
+                                    Ok(text) => text,
+                                    Err(_) => continue,
+                                },
+                                Ok(_) => continue,
+                                Err(e) => {
+                                    log::debug!("Connections WebSocket error: {}", e);
+                                    break;
+                                }
+                            };
+                            match serde_json::from_str::<ConnectionsResponse>(&text) {
+                                Ok(snapshot) => {
+                                    if let Ok(mut buffer) = buffer.lock() {
+                                        buffer.record(&snapshot.connections);
+                                    }
+                                }
+                                Err(e) => log::debug!("Invalid connections snapshot: {}", e),
+                            }
+                        }
+                    }
+                    Err(e) => log::debug!("Failed to connect to connections WebSocket: {}", e),
+                }
+
+                if !enabled.load(Ordering::SeqCst) {
+                    // 主动关闭不算断线，重新开启后直接连接
+                    dropped = false;
+                    backoff.reset();
+                    continue;
+                }
+                tokio::time::sleep(backoff.next_delay()).await;
+            }
+        });
+    }
+
+    /// 当前会话的命中统计
+    pub fn stats(&self) -> RuleHitStats {
+        let Ok(buffer) = self.buffer.lock() else {
+            return RuleHitStats {
+                enabled: false,
+                since: 0,
+                total: 0,
+                rules: Vec::new(),
+            };
+        };
+        let mut rules: Vec<RuleHitCount> = buffer
+            .counts
+            .iter()
+            .map(|(rule, hits)| RuleHitCount {
+                rule: rule.clone(),
+                hits: *hits,
+            })
+            .collect();
+        rules.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.rule.cmp(&b.rule)));
+        RuleHitStats {
+            enabled: self.enabled.load(Ordering::SeqCst),
+            since: buffer.since,
+            total: rules.iter().map(|r| r.hits).sum(),
+            rules,
+        }
+    }
+
+    /// 统计的规则数和估算的内存占用（字节）
+    pub fn memory_usage(&self) -> (usize, usize) {
+        self.buffer
+            .lock()
+            .map(|b| {
+                let counts: usize = b.counts.keys().map(|rule| rule.len() + 32).sum();
+                let seen: usize = b.seen.iter().map(|id| id.len() + 24).sum();
+                (b.counts.len(), counts + seen)
+            })
+            .unwrap_or_default()
+    }
+
+    /// 丢弃命中次数最少的 `count` 条规则统计，返回实际丢弃的数量
+    ///
+    /// 计数没有时间顺序，命中最少的规则对分析价值最低。
+    pub fn evict_least_hit(&self, count: usize) -> usize {
+        let Ok(mut buffer) = self.buffer.lock() else {
+            return 0;
+        };
+        let mut counts: Vec<(String, u64)> = buffer.counts.drain().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let count = count.min(counts.len());
+        counts.truncate(counts.len() - count);
+        buffer.counts = counts.into_iter().collect();
+        count
+    }
+
+    /// 清零（配置重载后也会调用）；当前已存在的连接不会再被计入
+    pub fn reset(&self) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            let seen = std::mem::take(&mut buffer.seen);
+            *buffer = RuleHitBuffer::new();
+            buffer.seen = seen;
+        }
+    }
+}
+
+impl Default for RuleHitCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::Timelike;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::{MihomoApi, MihomoManager};
+use crate::models::{SelectionScheduleEntry, SelectionScheduleSettings};
+
+/// 检查间隔
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 按时段切换节点事件（`selection-schedule-applied`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionScheduleEvent {
+    pub group: String,
+    pub proxy: String,
+    /// 命中的规则时段，如 `09:00-18:00`
+    pub window: String,
+    /// 是否已切换；策略组或节点不存在时为 false
+    pub applied: bool,
+    pub error: Option<String>,
+}
+
+/// 解析 `HH:MM`，返回当天的分钟数
+pub fn parse_schedule_time(time: &str) -> Option<u32> {
+    let (hour, minute) = time.trim().split_once(':')?;
+    let hour: u32 = hour.parse().ok()?;
+    let minute: u32 = minute.parse().ok()?;
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+fn entry_contains(entry: &SelectionScheduleEntry, minute: u32) -> bool {
+    let (Some(start), Some(end)) = (
+        parse_schedule_time(&entry.start),
+        parse_schedule_time(&entry.end),
+    ) else {
+        return false;
+    };
+    match start.cmp(&end) {
+        std::cmp::Ordering::Equal => true,
+        std::cmp::Ordering::Less => (start..end).contains(&minute),
+        // 跨天，如 22:00-06:00
+        std::cmp::Ordering::Greater => minute >= start || minute < end,
+    }
+}
+
+/// 当前时刻各策略组生效的规则（策略组 -> 规则下标），同组靠前的规则优先
+pub fn active_schedule_entries(
+    entries: &[SelectionScheduleEntry],
+    minute: u32,
+) -> HashMap<&str, usize> {
+    let mut active = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if entry_contains(entry, minute) {
+            active.entry(entry.group.as_str()).or_insert(index);
+        }
+    }
+    active
+}
+
+/// 按时段自动选择节点
+///
+/// 每 30 秒检查一次当前时段，策略组命中的规则变化时（进入新的时段）调用 `select_proxy`
+/// 切换，并发送 `selection-schedule-applied` 事件。同一时段内只切换一次，
+/// 用户在时段内的手动选择不会被覆盖；核心重启后重新应用。
+pub struct SelectionScheduler {
+    /// 任务代数，每次启动/停止都会递增，旧任务检测到代数变化后退出
+    generation: Arc<AtomicU64>,
+    mihomo_api: Arc<MihomoApi>,
+    mihomo_manager: Arc<MihomoManager>,
+}
+
+impl SelectionScheduler {
+    pub fn new(mihomo_api: Arc<MihomoApi>, mihomo_manager: Arc<MihomoManager>) -> Self {
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            mihomo_api,
+            mihomo_manager,
+        }
+    }
+
+    /// 启动后台任务（已有任务会被替换）
+    pub fn start(&self, app: AppHandle, settings: SelectionScheduleSettings) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current_generation = self.generation.clone();
+        let mihomo_api = self.mihomo_api.clone();
+        let mihomo_manager = self.mihomo_manager.clone();
+
+        log::info!(
+            "Selection scheduler started ({} entries)",
+            settings.entries.len()
+        );
+
+        tokio::spawn(async move {
+            // 各策略组上一次应用的规则下标
+            let mut applied: HashMap<String, usize> = HashMap::new();
+
+            loop {
+                if current_generation.load(Ordering::SeqCst) != generation {
+                    break;
+                }
+                if !mihomo_manager.is_running().await {
+                    applied.clear();
+                    tokio::time::sleep(SCHEDULE_INTERVAL).await;
+                    continue;
+                }
+
+                let now = chrono::Local::now();
+                let minute = now.hour() * 60 + now.minute();
+                let active = active_schedule_entries(&settings.entries, minute);
+                // 离开时段的策略组下次进入时重新切换
+                applied.retain(|group, _| active.contains_key(group.as_str()));
+
+                let pending: Vec<(&str, usize)> = active
+                    .into_iter()
+                    .filter(|(group, index)| applied.get(*group) != Some(index))
+                    .collect();
+                if !pending.is_empty() {
+                    match mihomo_api.get_proxies().await {
+                        Ok(proxies) => {
+                            for (group, index) in pending {
+                                let entry = &settings.entries[index];
+                                let result = match proxies.proxies.get(group) {
+                                    None => Err("策略组不存在".to_string()),
+                                    Some(info) if !info.all.contains(&entry.proxy) => {
+                                        Err("节点不存在".to_string())
+                                    }
+                                    Some(info) if info.now.as_deref() == Some(&entry.proxy) => {
+                                        Ok(())
+                                    }
+                                    Some(_) => mihomo_api
+                                        .select_proxy(group, &entry.proxy)
+                                        .await
+                                        .map_err(|e| e.to_string()),
+                                };
+
+                                match &result {
+                                    Ok(()) => log::info!(
+                                        "Scheduled selection applied: {} -> {}",
+                                        group,
+                                        entry.proxy
+                                    ),
+                                    Err(e) => log::warn!(
+                                        "Scheduled selection {} -> {} skipped: {}",
+                                        group,
+                                        entry.proxy,
+                                        e
+                                    ),
+                                }
+                                let _ = app.emit(
+                                    "selection-schedule-applied",
+                                    SelectionScheduleEvent {
+                                        group: group.to_string(),
+                                        proxy: entry.proxy.clone(),
+                                        window: format!("{}-{}", entry.start, entry.end),
+                                        applied: result.is_ok(),
+                                        error: result.err(),
+                                    },
+                                );
+                                applied.insert(group.to_string(), index);
+                            }
+                        }
+                        Err(e) => log::debug!("Selection scheduler poll failed: {}", e),
+                    }
+                }
+
+                tokio::time::sleep(SCHEDULE_INTERVAL).await;
+            }
+        });
+    }
+
+    /// 停止后台任务
+    pub fn stop(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(start: &str, end: &str, group: &str, proxy: &str) -> SelectionScheduleEntry {
+        SelectionScheduleEntry {
+            start: start.to_string(),
+            end: end.to_string(),
+            group: group.to_string(),
+            proxy: proxy.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_active_schedule_entries() {
+        let entries = vec![
+            entry("09:00", "18:00", "PROXY", "work"),
+            entry("18:00", "09:00", "PROXY", "streaming"),
+            entry("00:00", "00:00", "PROXY", "fallback"),
+            entry("08:00", "10:00", "AI", "us"),
+        ];
+
+        let at = |h: u32, m: u32| active_schedule_entries(&entries, h * 60 + m);
+        assert_eq!(at(9, 0).get("PROXY"), Some(&0));
+        assert_eq!(at(8, 59).get("PROXY"), Some(&1));
+        assert_eq!(at(23, 30).get("PROXY"), Some(&1));
+        assert_eq!(at(9, 30).get("AI"), Some(&3));
+        assert_eq!(at(10, 0).get("AI"), None);
+
+        assert_eq!(parse_schedule_time("24:00"), None);
+        assert_eq!(parse_schedule_time("7:05"), Some(425));
+    }
+}
@@ -0,0 +1,111 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+/// 默认重连间隔（秒）
+pub const DEFAULT_STREAM_RECONNECT_SECS: u64 = 3;
+
+/// 重连间隔的上限（秒）
+pub const MAX_STREAM_RECONNECT_SECS: u64 = 60;
+
+/// 连续失败时退避的最长等待
+const MAX_BACKOFF: Duration = Duration::from_secs(MAX_STREAM_RECONNECT_SECS);
+
+/// 超过该时间没有收到任何消息时发送 ping 探测连接
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 发送 ping 后等待任意消息的时间，超时视为连接已失效
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 当前重连间隔（秒），由设置更新
+static RECONNECT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_STREAM_RECONNECT_SECS);
+
+/// 设置 WebSocket 断开后的重连间隔（连续失败时在此基础上翻倍退避）
+pub fn set_stream_reconnect_interval(secs: u64) {
+    RECONNECT_SECS.store(secs.clamp(1, MAX_STREAM_RECONNECT_SECS), Ordering::SeqCst);
+}
+
+/// 数据流重连事件（`stream-reconnected`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamReconnectedEvent {
+    /// `logs` 或 `connections`
+    pub stream: String,
+    /// 本次恢复前失败的重连次数
+    pub attempts: u32,
+}
+
+/// 重连退避：从设置的间隔开始，每次失败翻倍，最长 60 秒
+#[derive(Debug, Default)]
+pub struct ReconnectBackoff {
+    failures: u32,
+}
+
+impl ReconnectBackoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次失败并返回下次重连前的等待时间
+    pub fn next_delay(&mut self) -> Duration {
+        let base = Duration::from_secs(RECONNECT_SECS.load(Ordering::SeqCst));
+        let delay = base
+            .checked_mul(1 << self.failures.min(6))
+            .map_or(MAX_BACKOFF, |d| d.min(MAX_BACKOFF));
+        self.failures += 1;
+        delay
+    }
+
+    /// 连接成功，返回之前连续失败的次数
+    pub fn reset(&mut self) -> u32 {
+        std::mem::take(&mut self.failures)
+    }
+}
+
+/// 读取下一条消息，空闲时发送 ping 检测连接是否仍然可用
+///
+/// 空闲的网络上连接可能已被中间设备断开而没有任何通知，
+/// 因此空闲超过 30 秒时发送 ping，10 秒内没有收到任何消息（包括 pong）则返回 None 触发重连。
+pub async fn next_with_heartbeat<S>(stream: &mut S) -> Option<Result<Message, WsError>>
+where
+    S: StreamExt<Item = Result<Message, WsError>> + SinkExt<Message> + Unpin,
+{
+    if let Ok(message) = tokio::time::timeout(IDLE_TIMEOUT, stream.next()).await {
+        return message;
+    }
+    if stream
+        .send(Message::Ping(Default::default()))
+        .await
+        .is_err()
+    {
+        return None;
+    }
+    match tokio::time::timeout(PING_TIMEOUT, stream.next()).await {
+        Ok(message) => message,
+        Err(_) => {
+            log::debug!("WebSocket heartbeat timed out");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let mut backoff = ReconnectBackoff::new();
+        let base = DEFAULT_STREAM_RECONNECT_SECS;
+        assert_eq!(backoff.next_delay(), Duration::from_secs(base));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(base * 2));
+        for _ in 0..10 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), MAX_BACKOFF);
+        assert_eq!(backoff.reset(), 13);
+        assert_eq!(backoff.next_delay(), Duration::from_secs(base));
+    }
+}
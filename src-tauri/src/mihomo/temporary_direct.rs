@@ -0,0 +1,166 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::{MihomoApi, MihomoManager};
+
+/// 临时直连状态
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemporaryDirectStatus {
+    pub active: bool,
+    /// 到期后恢复的模式
+    pub previous_mode: Option<String>,
+    /// 到期时间（Unix 毫秒时间戳）
+    pub expires_at: Option<i64>,
+}
+
+/// 临时切换到直连模式
+///
+/// 只通过 API 修改运行时模式，不写入 config.yaml / settings.json，
+/// 因此应用退出或核心重启后自然回到原模式，不会停留在直连。
+pub struct TemporaryDirect {
+    /// 计时任务代数，每次开始/取消都会递增，旧任务检测到代数变化后放弃恢复
+    generation: Arc<AtomicU64>,
+    status: Arc<Mutex<TemporaryDirectStatus>>,
+    mihomo_api: Arc<MihomoApi>,
+    mihomo_manager: Arc<MihomoManager>,
+}
+
+impl TemporaryDirect {
+    pub fn new(mihomo_api: Arc<MihomoApi>, mihomo_manager: Arc<MihomoManager>) -> Self {
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            status: Arc::new(Mutex::new(TemporaryDirectStatus::default())),
+            mihomo_api,
+            mihomo_manager,
+        }
+    }
+
+    pub fn status(&self) -> TemporaryDirectStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status().active
+    }
+
+    /// 开始临时直连；已处于临时直连时替换计时（保留最初的模式）
+    pub async fn start(
+        &self,
+        app: AppHandle,
+        current_mode: String,
+        duration: Duration,
+    ) -> Result<TemporaryDirectStatus> {
+        self.mihomo_api.patch_configs("direct").await?;
+
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let status = {
+            let mut status = self
+                .status
+                .lock()
+                .map_err(|_| anyhow::anyhow!("Temporary direct state poisoned"))?;
+            let previous_mode = status.previous_mode.clone().unwrap_or(current_mode);
+            *status = TemporaryDirectStatus {
+                active: true,
+                previous_mode: Some(previous_mode),
+                expires_at: Some(
+                    chrono::Utc::now().timestamp_millis() + duration.as_millis() as i64,
+                ),
+            };
+            status.clone()
+        };
+
+        log::info!(
+            "Temporary direct mode enabled for {}s (restore to: {:?})",
+            duration.as_secs(),
+            status.previous_mode
+        );
+        let _ = app.emit("temporary-direct-changed", &status);
+
+        let current_generation = self.generation.clone();
+        let state = self.status.clone();
+        let mihomo_api = self.mihomo_api.clone();
+        let mihomo_manager = self.mihomo_manager.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            if current_generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            current_generation.fetch_add(1, Ordering::SeqCst);
+
+            if let Err(e) = restore_mode(&app, &state, &mihomo_api, &mihomo_manager).await {
+                log::warn!("Failed to restore mode after temporary direct: {}", e);
+            }
+        });
+
+        Ok(status)
+    }
+
+    /// 立即结束临时直连并恢复原模式
+    pub async fn restore(&self, app: &AppHandle) -> Result<()> {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        restore_mode(app, &self.status, &self.mihomo_api, &self.mihomo_manager).await
+    }
+
+    /// 配置重载后核心回到 config.yaml 中的模式，临时直连期间需要重新下发 direct
+    ///
+    /// 下发失败时结束临时直连，返回是否仍处于临时直连
+    pub async fn reapply(&self) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        match self.mihomo_api.patch_configs("direct").await {
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("Failed to re-apply temporary direct mode: {}", e);
+                self.cancel();
+                false
+            }
+        }
+    }
+
+    /// 取消计时但不恢复模式（核心已重启或用户手动切换了模式）
+    ///
+    /// 返回取消前是否处于临时直连
+    pub fn cancel(&self) -> bool {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        match self.status.lock() {
+            Ok(mut status) => std::mem::take(&mut *status).active,
+            Err(_) => false,
+        }
+    }
+}
+
+async fn restore_mode(
+    app: &AppHandle,
+    status: &Mutex<TemporaryDirectStatus>,
+    mihomo_api: &MihomoApi,
+    mihomo_manager: &MihomoManager,
+) -> Result<()> {
+    let previous = match status.lock() {
+        Ok(mut status) => std::mem::take(&mut *status),
+        Err(_) => return Ok(()),
+    };
+    let Some(previous_mode) = previous.previous_mode.filter(|_| previous.active) else {
+        return Ok(());
+    };
+
+    // 核心未运行时，下次启动会直接使用配置文件中的模式
+    if mihomo_manager.is_running().await {
+        mihomo_api.patch_configs(&previous_mode).await?;
+    }
+
+    log::info!(
+        "Temporary direct mode ended, restored to: {}",
+        previous_mode
+    );
+    let _ = app.emit("temporary-direct-changed", TemporaryDirectStatus::default());
+    if let Ok(status) = crate::commands::proxy::get_proxy_status().await {
+        let _ = app.emit("proxy-status-changed", status);
+    }
+    Ok(())
+}
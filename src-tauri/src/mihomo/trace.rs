@@ -0,0 +1,274 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{next_with_heartbeat, MihomoApi, MihomoManager, ReconnectBackoff};
+use crate::models::{Connection, ConnectionsResponse};
+
+/// 核心推送连接快照的间隔（毫秒），短于该间隔的连接可能被漏记
+const TRACE_PUSH_INTERVAL_MS: u64 = 200;
+
+/// 等待核心启动的轮询间隔
+const CORE_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 单次追踪最多记录的连接数
+const MAX_TRACE_ENTRIES: usize = 5000;
+
+/// 追踪记录的一条新连接
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEntry {
+    pub id: String,
+    /// 首次观察到的时间（Unix 毫秒时间戳）
+    pub seen_at: i64,
+    pub host: String,
+    pub destination_ip: String,
+    pub destination_port: String,
+    pub network: String,
+    /// 入站类型（HTTP / Socks5 / Tun 等）
+    pub conn_type: String,
+    pub process: Option<String>,
+    pub rule: String,
+    pub rule_payload: String,
+    /// 代理链（从最终出站到策略组）
+    pub chains: Vec<String>,
+}
+
+impl TraceEntry {
+    fn from_connection(conn: &Connection, seen_at: i64) -> Self {
+        let meta = &conn.metadata;
+        Self {
+            id: conn.id.clone(),
+            seen_at,
+            host: meta.host.clone(),
+            destination_ip: meta.destination_ip.clone(),
+            destination_port: meta.destination_port.clone(),
+            network: meta.network.clone(),
+            conn_type: meta.conn_type.clone(),
+            process: meta.process.clone(),
+            rule: conn.rule.clone(),
+            rule_payload: conn.rule_payload.clone(),
+            chains: conn.chains.clone(),
+        }
+    }
+
+    /// 估算的内存占用（字节）
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.id.len()
+            + self.host.len()
+            + self.destination_ip.len()
+            + self.destination_port.len()
+            + self.network.len()
+            + self.conn_type.len()
+            + self.process.as_ref().map_or(0, String::len)
+            + self.rule.len()
+            + self.rule_payload.len()
+            + self.chains.iter().map(|c| c.len() + 24).sum::<usize>()
+    }
+}
+
+/// 追踪状态
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStatus {
+    pub active: bool,
+    /// 开始时间（Unix 毫秒时间戳）
+    pub started_at: Option<i64>,
+    /// 自动结束时间（Unix 毫秒时间戳）
+    pub expires_at: Option<i64>,
+    /// 已记录的连接数
+    pub entries: usize,
+}
+
+#[derive(Default)]
+struct TraceBuffer {
+    status: TraceStatus,
+    entries: Vec<TraceEntry>,
+    seen: HashSet<String>,
+}
+
+/// 连接追踪
+///
+/// 在指定时间窗口内订阅 `/connections` WebSocket，记录新出现连接的路由信息（命中规则、代理链），
+/// 用于排查「为什么走了直连」之类的问题。开始时已存在的连接不计入。
+/// 记录保存在内存中，直到下次开始追踪。
+pub struct ConnectionTracer {
+    /// 追踪任务代数，每次开始/停止都会递增，旧任务检测到代数变化后退出
+    generation: Arc<AtomicU64>,
+    buffer: Arc<Mutex<TraceBuffer>>,
+    mihomo_api: Arc<MihomoApi>,
+    mihomo_manager: Arc<MihomoManager>,
+}
+
+impl ConnectionTracer {
+    pub fn new(mihomo_api: Arc<MihomoApi>, mihomo_manager: Arc<MihomoManager>) -> Self {
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            buffer: Arc::new(Mutex::new(TraceBuffer::default())),
+            mihomo_api,
+            mihomo_manager,
+        }
+    }
+
+    pub fn status(&self) -> TraceStatus {
+        self.buffer
+            .lock()
+            .map(|b| b.status.clone())
+            .unwrap_or_default()
+    }
+
+    /// 已记录的连接（按首次观察时间排序）
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.buffer
+            .lock()
+            .map(|b| b.entries.clone())
+            .unwrap_or_default()
+    }
+
+    /// 已记录的连接数和估算的内存占用（字节）
+    pub fn memory_usage(&self) -> (usize, usize) {
+        self.buffer
+            .lock()
+            .map(|b| {
+                let entries: usize = b.entries.iter().map(TraceEntry::estimated_size).sum();
+                let seen: usize = b.seen.iter().map(|id| id.len() + 24).sum();
+                (b.entries.len(), entries + seen)
+            })
+            .unwrap_or_default()
+    }
+
+    /// 丢弃最早记录的 `count` 条连接，返回实际丢弃的数量
+    ///
+    /// 连接 ID 仍保留，被丢弃的连接不会被重新记录。
+    pub fn evict_oldest(&self, count: usize) -> usize {
+        let Ok(mut buffer) = self.buffer.lock() else {
+            return 0;
+        };
+        let count = count.min(buffer.entries.len());
+        buffer.entries.drain(..count);
+        buffer.status.entries = buffer.entries.len();
+        count
+    }
+
+    /// 开始追踪，清空上一次的记录；已在追踪时重新开始
+    pub fn start(&self, duration: Duration) -> TraceStatus {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let now = chrono::Utc::now().timestamp_millis();
+        let status = TraceStatus {
+            active: true,
+            started_at: Some(now),
+            expires_at: Some(now + duration.as_millis() as i64),
+            entries: 0,
+        };
+        if let Ok(mut buffer) = self.buffer.lock() {
+            *buffer = TraceBuffer {
+                status: status.clone(),
+                ..Default::default()
+            };
+        }
+
+        let current_generation = self.generation.clone();
+        let buffer = self.buffer.clone();
+        let mihomo_api = self.mihomo_api.clone();
+        let mihomo_manager = self.mihomo_manager.clone();
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + duration;
+            let path = format!("/connections?interval={}", TRACE_PUSH_INTERVAL_MS);
+            let mut backoff = ReconnectBackoff::new();
+            // 第一个快照只记下已有连接，不计入追踪结果
+            let mut baseline = true;
+
+            while tokio::time::Instant::now() < deadline {
+                if current_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                if !mihomo_manager.is_running().await {
+                    tokio::time::sleep(CORE_WAIT_INTERVAL).await;
+                    continue;
+                }
+
+                match mihomo_api.connect_websocket(&path).await {
+                    Ok(mut stream) => {
+                        backoff.reset();
+                        while let Ok(Some(message)) =
+                            tokio::time::timeout_at(deadline, next_with_heartbeat(&mut stream))
+                                .await
+                        {
+                            if current_generation.load(Ordering::SeqCst) != generation {
+                                return;
+                            }
+                            let text = match message {
+                                Ok(msg) if msg.is_text() => match msg.into_text() {
+                                    Ok(text) => text,
+                                    Err(_) => continue,
+                                },
+                                Ok(_) => continue,
+                                Err(e) => {
+                                    log::debug!("Trace WebSocket error: {}", e);
+                                    break;
+                                }
+                            };
+                            match serde_json::from_str::<ConnectionsResponse>(&text) {
+                                Ok(snapshot) => {
+                                    record_connections(&buffer, &snapshot.connections, baseline);
+                                    baseline = false;
+                                }
+                                Err(e) => log::debug!("Invalid connections snapshot: {}", e),
+                            }
+                        }
+                    }
+                    Err(e) => log::debug!("Failed to connect to connections WebSocket: {}", e),
+                }
+
+                if tokio::time::Instant::now() < deadline {
+                    tokio::time::sleep(backoff.next_delay()).await;
+                }
+            }
+
+            if current_generation.load(Ordering::SeqCst) == generation {
+                if let Ok(mut buffer) = buffer.lock() {
+                    buffer.status.active = false;
+                    log::info!(
+                        "Connection trace finished, {} connections recorded",
+                        buffer.entries.len()
+                    );
+                }
+            }
+        });
+
+        log::info!("Connection trace started for {}s", duration.as_secs());
+        status
+    }
+
+    /// 停止追踪，返回已记录的连接
+    pub fn stop(&self) -> Vec<TraceEntry> {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        match self.buffer.lock() {
+            Ok(mut buffer) => {
+                buffer.status.active = false;
+                buffer.entries.clone()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+fn record_connections(buffer: &Mutex<TraceBuffer>, connections: &[Connection], baseline: bool) {
+    let Ok(mut buffer) = buffer.lock() else {
+        return;
+    };
+    let now = chrono::Utc::now().timestamp_millis();
+    for conn in connections {
+        if !buffer.seen.insert(conn.id.clone()) || baseline {
+            continue;
+        }
+        if buffer.entries.len() >= MAX_TRACE_ENTRIES {
+            break;
+        }
+        buffer.entries.push(TraceEntry::from_connection(conn, now));
+    }
+    buffer.status.entries = buffer.entries.len();
+}
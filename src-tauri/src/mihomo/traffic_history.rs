@@ -0,0 +1,158 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{next_with_heartbeat, CoreState, MihomoApi, MihomoManager, ReconnectBackoff};
+use crate::models::TrafficData;
+
+/// 采样间隔（核心每秒推送一次 `/traffic`）
+const TRAFFIC_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 等待核心启动的轮询间隔
+const CORE_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 默认保留的采样点（1 秒一个，约 1 小时）
+const DEFAULT_TRAFFIC_SAMPLES: usize = 3600;
+
+/// 流量采样点
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficSample {
+    /// 采样时间（Unix 毫秒时间戳）
+    pub timestamp: i64,
+    /// 该秒上传字节数
+    pub up: u64,
+    /// 该秒下载字节数
+    pub down: u64,
+}
+
+/// 流量历史
+///
+/// 后台保持一条 `/traffic` WebSocket 连接，核心每秒推送的数据即为一个采样点，
+/// 保存在固定容量的环形缓冲区中，与前端是否显示图表无关。核心未运行时不采样，
+/// 断线后按流重连设置重连。
+pub struct TrafficHistory {
+    samples: Arc<Mutex<VecDeque<TrafficSample>>>,
+    /// 最多保留的采样点，由保留时长设置决定
+    max_samples: Arc<AtomicUsize>,
+}
+
+impl TrafficHistory {
+    pub fn new() -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(VecDeque::with_capacity(DEFAULT_TRAFFIC_SAMPLES))),
+            max_samples: Arc::new(AtomicUsize::new(DEFAULT_TRAFFIC_SAMPLES)),
+        }
+    }
+
+    /// 设置保留时长，超出部分立即丢弃
+    pub fn set_retention(&self, retention: Duration) {
+        let max = (retention.as_secs() / TRAFFIC_SAMPLE_INTERVAL.as_secs()).max(1) as usize;
+        self.max_samples.store(max, Ordering::SeqCst);
+        if let Ok(mut samples) = self.samples.lock() {
+            let excess = samples.len().saturating_sub(max);
+            samples.drain(..excess);
+        }
+    }
+
+    /// 采样点数量和估算的内存占用（字节）
+    pub fn memory_usage(&self) -> (usize, usize) {
+        let len = self.samples.lock().map(|s| s.len()).unwrap_or(0);
+        (len, len * std::mem::size_of::<TrafficSample>())
+    }
+
+    /// 丢弃最早的 `count` 个采样点，返回实际丢弃的数量
+    pub fn evict_oldest(&self, count: usize) -> usize {
+        let Ok(mut samples) = self.samples.lock() else {
+            return 0;
+        };
+        let count = count.min(samples.len());
+        samples.drain(..count);
+        count
+    }
+
+    /// 启动后台采样任务（应用运行期间只需启动一次）
+    pub fn start(&self, mihomo_api: Arc<MihomoApi>, mihomo_manager: Arc<MihomoManager>) {
+        let samples = self.samples.clone();
+        let max_samples = self.max_samples.clone();
+
+        tokio::spawn(async move {
+            let mut backoff = ReconnectBackoff::new();
+
+            loop {
+                let mut restarted = false;
+                while mihomo_manager.core_state().get() != CoreState::Running {
+                    restarted = true;
+                    tokio::time::sleep(CORE_WAIT_INTERVAL).await;
+                }
+                if restarted {
+                    backoff.reset();
+                }
+
+                match mihomo_api.connect_websocket("/traffic").await {
+                    Ok(mut stream) => {
+                        backoff.reset();
+                        while let Some(message) = next_with_heartbeat(&mut stream).await {
+                            let text = match message {
+                                Ok(msg) if msg.is_text() => match msg.into_text() {
+                                    Ok(text) => text,
+                                    Err(_) => continue,
+                                },
+                                Ok(_) => continue,
+                                Err(e) => {
+                                    log::debug!("Traffic WebSocket error: {}", e);
+                                    break;
+                                }
+                            };
+                            let traffic = match serde_json::from_str::<TrafficData>(&text) {
+                                Ok(traffic) => traffic,
+                                Err(e) => {
+                                    log::debug!("Invalid traffic sample: {}", e);
+                                    continue;
+                                }
+                            };
+
+                            if let Ok(mut samples) = samples.lock() {
+                                let max = max_samples.load(Ordering::SeqCst);
+                                while samples.len() >= max {
+                                    samples.pop_front();
+                                }
+                                samples.push_back(TrafficSample {
+                                    timestamp: chrono::Utc::now().timestamp_millis(),
+                                    up: traffic.up,
+                                    down: traffic.down,
+                                });
+                            }
+                        }
+                    }
+                    Err(e) => log::debug!("Failed to connect to traffic WebSocket: {}", e),
+                }
+
+                tokio::time::sleep(backoff.next_delay()).await;
+            }
+        });
+    }
+
+    /// 获取最近 `window` 内的采样点（按时间升序）
+    pub fn samples_since(&self, window: Duration) -> Vec<TrafficSample> {
+        let since = chrono::Utc::now().timestamp_millis() - window.as_millis() as i64;
+        self.samples
+            .lock()
+            .map(|samples| {
+                samples
+                    .iter()
+                    .filter(|sample| sample.timestamp >= since)
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Default for TrafficHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -16,6 +16,18 @@ pub struct MihomoConfig {
     #[serde(rename = "allow-lan", default)]
     pub allow_lan: bool,
 
+    /// 局域网共享时监听的地址（`*` 为全部地址）
+    #[serde(rename = "bind-address", skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<String>,
+
+    /// 允许访问的局域网网段（CIDR），为空时不限制
+    #[serde(
+        rename = "lan-allowed-ips",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub lan_allowed_ips: Vec<String>,
+
     #[serde(default = "default_mode")]
     pub mode: String,
 
@@ -31,10 +43,60 @@ pub struct MihomoConfig {
     #[serde(default)]
     pub secret: String,
 
+    /// HTTPS 外部控制器监听地址，需配合 `tls` 证书
+    #[serde(
+        rename = "external-controller-tls",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub external_controller_tls: Option<String>,
+
+    /// 外部控制器使用的 TLS 证书
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<ControllerTlsConfig>,
+
+    /// 面板目录（相对于核心工作目录），由核心在 `/ui` 路径下提供
+    #[serde(rename = "external-ui", skip_serializing_if = "Option::is_none")]
+    pub external_ui: Option<String>,
+
+    /// 面板下载地址（核心 `/upgrade/ui` 接口使用）
+    #[serde(rename = "external-ui-url", skip_serializing_if = "Option::is_none")]
+    pub external_ui_url: Option<String>,
+
     // 启用进程查找
     #[serde(rename = "find-process-mode", default = "default_find_process_mode")]
     pub find_process_mode: String,
 
+    /// 出站流量绑定的网卡
+    #[serde(rename = "interface-name", skip_serializing_if = "Option::is_none")]
+    pub interface_name: Option<String>,
+
+    /// 出站流量的 SO_MARK（仅 Linux）
+    #[serde(rename = "routing-mark", skip_serializing_if = "Option::is_none")]
+    pub routing_mark: Option<u32>,
+
+    /// TCP keep-alive 探测间隔（秒）
+    #[serde(
+        rename = "keep-alive-interval",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub keep_alive_interval: Option<u32>,
+
+    /// TCP 连接空闲多久后开始 keep-alive 探测（秒）
+    #[serde(rename = "keep-alive-idle", skip_serializing_if = "Option::is_none")]
+    pub keep_alive_idle: Option<u32>,
+
+    /// 关闭 TCP keep-alive
+    #[serde(rename = "disable-keep-alive", skip_serializing_if = "Option::is_none")]
+    pub disable_keep_alive: Option<bool>,
+
+    /// 核心下载外部资源（Provider、GEO 数据）时使用的 User-Agent
+    #[serde(rename = "global-ua", skip_serializing_if = "Option::is_none")]
+    pub global_ua: Option<String>,
+
+    /// 下载 Provider 时使用 ETag 条件请求，未变化的文件不重复下载（核心默认开启）
+    #[serde(rename = "etag-support", skip_serializing_if = "Option::is_none")]
+    pub etag_support: Option<bool>,
+
     // GeoData 相关配置
     #[serde(rename = "geodata-mode", default)]
     pub geodata_mode: bool,
@@ -70,6 +132,14 @@ pub struct MihomoConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dns: Option<DnsConfig>,
 
+    /// 核心缓存配置（选中节点、fake-ip 映射的持久化）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<MihomoProfileConfig>,
+
+    /// `mode: script` 使用的脚本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<ScriptConfig>,
+
     #[serde(
         rename = "proxy-providers",
         default,
@@ -94,6 +164,102 @@ pub struct MihomoConfig {
 
     #[serde(default, alias = "Rule")]
     pub rules: Vec<String>,
+
+    #[serde(
+        rename = "sub-rules",
+        default,
+        skip_serializing_if = "std::collections::HashMap::is_empty"
+    )]
+    pub sub_rules: std::collections::HashMap<String, Vec<String>>,
+}
+
+/// 核心 `script` 配置（`mode: script` 时由脚本决定每个连接的策略）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptConfig {
+    /// 脚本代码，需定义 `main(ctx, metadata)` 并返回策略名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+
+    /// 可在规则中以 `SCRIPT,名称,策略` 引用的表达式
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub shortcuts: std::collections::HashMap<String, String>,
+}
+
+impl ScriptConfig {
+    /// 是否有可执行的脚本代码
+    pub fn has_code(&self) -> bool {
+        self.code
+            .as_deref()
+            .is_some_and(|code| !code.trim().is_empty())
+    }
+}
+
+/// 核心 `profile` 配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MihomoProfileConfig {
+    /// 将策略组选中的节点写入核心缓存，重载/重启后自动恢复
+    #[serde(rename = "store-selected", default = "default_true")]
+    pub store_selected: bool,
+
+    #[serde(rename = "store-fake-ip", skip_serializing_if = "Option::is_none")]
+    pub store_fake_ip: Option<bool>,
+}
+
+impl Default for MihomoProfileConfig {
+    fn default() -> Self {
+        Self {
+            store_selected: true,
+            store_fake_ip: None,
+        }
+    }
+}
+
+/// 配置校验问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationSeverity {
+    /// 核心会拒绝加载，保存时直接报错
+    Error,
+    /// 可以加载，但可能不符合预期
+    Warning,
+}
+
+/// 配置校验问题
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    /// 出问题的字段路径（如 `mixed-port`、`proxy-groups[0].use`），前端据此定位输入框
+    pub field_path: String,
+    pub severity: ValidationSeverity,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn error(field_path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field_path: field_path.into(),
+            severity: ValidationSeverity::Error,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(field_path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field_path: field_path.into(),
+            severity: ValidationSeverity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// 外部控制器 TLS 证书（`tls` 块）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ControllerTlsConfig {
+    /// 证书路径或 PEM 内容
+    pub certificate: String,
+    /// 私钥路径或 PEM 内容
+    #[serde(rename = "private-key")]
+    pub private_key: String,
 }
 
 /// Sniffer 配置（域名嗅探）
@@ -210,6 +376,10 @@ pub struct DnsConfig {
     #[serde(rename = "fake-ip-range", skip_serializing_if = "Option::is_none")]
     pub fake_ip_range: Option<String>,
 
+    /// 是否解析 AAAA 记录，应与全局 `ipv6` 保持一致
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv6: Option<bool>,
+
     /// Fake IP 过滤模式: blacklist, whitelist
     #[serde(
         rename = "fake-ip-filter-mode",
@@ -274,12 +444,14 @@ pub struct DnsConfig {
 
     /// 域名策略：根据域名分流到不同 DNS 服务器
     /// 例如: {"geosite:cn": ["223.5.5.5"], "geosite:gfw": ["https://dns.cloudflare.com/dns-query"]}
+    ///
+    /// None 表示未配置（加载时补上默认策略），空表为用户清空了所有策略
     #[serde(
         rename = "nameserver-policy",
         default,
-        skip_serializing_if = "HashMap::is_empty"
+        skip_serializing_if = "Option::is_none"
     )]
-    pub nameserver_policy: HashMap<String, Vec<String>>,
+    pub nameserver_policy: Option<HashMap<String, Vec<String>>>,
 }
 
 impl Default for DnsConfig {
@@ -310,6 +482,7 @@ impl Default for DnsConfig {
             listen: Some("0.0.0.0:1053".to_string()),
             enhanced_mode: Some("fake-ip".to_string()),
             fake_ip_range: Some("198.18.0.1/16".to_string()),
+            ipv6: None,
             fake_ip_filter_mode: Some("blacklist".to_string()),
             fake_ip_filter: vec![
                 "+.lan".to_string(),
@@ -334,7 +507,7 @@ impl Default for DnsConfig {
             use_system_hosts: true,
             respect_rules: true,
             cache_algorithm: Some("arc".to_string()),
-            nameserver_policy,
+            nameserver_policy: Some(nameserver_policy),
         }
     }
 }
@@ -384,6 +557,30 @@ pub struct TunConfig {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub inet4_route_exclude_address: Vec<String>,
+
+    /// 路由排除地址列表（IPv4/IPv6 CIDR），如 VPN 网段、局域网打印机
+    #[serde(
+        rename = "route-exclude-address",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub route_exclude_address: Vec<String>,
+
+    /// 路由包含地址列表（CIDR），非空时只有这些网段经过 TUN
+    #[serde(
+        rename = "route-address",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub route_address: Vec<String>,
+
+    /// TUN 网卡的 IPv6 地址，为空时 TUN 不处理 IPv6 流量
+    #[serde(
+        rename = "inet6-address",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub inet6_address: Vec<String>,
 }
 
 impl Default for TunConfig {
@@ -396,6 +593,9 @@ impl Default for TunConfig {
             strict_route: Some(false),
             dns_hijack: vec!["any:53".to_string(), "tcp://any:53".to_string()],
             inet4_route_exclude_address: default_inet4_route_exclude_address(),
+            route_exclude_address: vec![],
+            route_address: vec![],
+            inet6_address: vec![],
         }
     }
 }
@@ -455,11 +655,24 @@ impl Default for MihomoConfig {
             socks_port: default_socks_port(),
             mixed_port: default_mixed_port(),
             allow_lan: false,
+            bind_address: None,
+            lan_allowed_ips: vec![],
             mode: default_mode(),
             log_level: default_log_level(),
             external_controller: default_external_controller(),
             secret: String::new(),
+            external_controller_tls: None,
+            tls: None,
+            external_ui: None,
+            external_ui_url: None,
             find_process_mode: default_find_process_mode(),
+            interface_name: None,
+            routing_mark: None,
+            keep_alive_interval: None,
+            keep_alive_idle: None,
+            disable_keep_alive: None,
+            global_ua: None,
+            etag_support: None,
             geodata_mode: true,
             geodata_loader: Some("memconservative".to_string()),
             geo_auto_update: false,
@@ -475,11 +688,14 @@ impl Default for MihomoConfig {
             proxy_providers: std::collections::HashMap::new(),
             rule_providers: std::collections::HashMap::new(),
             rules: vec!["GEOIP,CN,DIRECT".to_string(), "MATCH,PROXY".to_string()],
+            sub_rules: std::collections::HashMap::new(),
             ipv6: false,
             tcp_concurrent: false,
             sniffer: Some(SnifferConfig::default()),
             tun: None,
             dns: None,
+            profile: None,
+            script: None,
         }
     }
 }
@@ -596,6 +812,14 @@ pub struct ProxyGroupConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tolerance: Option<u32>,
+
+    /// 未建模的字段原样保留，避免保存时丢失
+    #[serde(
+        flatten,
+        default,
+        skip_serializing_if = "std::collections::HashMap::is_empty"
+    )]
+    pub extra: std::collections::HashMap<String, serde_yaml::Value>,
 }
 
 /// 代理提供者配置
@@ -715,6 +939,284 @@ pub struct WebDavConfig {
     pub last_sync_time: Option<String>,
 }
 
+/// 自动切换最快节点配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSwitchSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// 目标策略组（必须是 select 类型）
+    #[serde(default)]
+    pub group: String,
+
+    /// 测速间隔（秒）
+    #[serde(default = "default_auto_switch_interval")]
+    pub interval_secs: u64,
+
+    /// 切换阈值（毫秒），新节点需比当前节点快超过该值才切换，避免频繁抖动
+    #[serde(default = "default_auto_switch_threshold")]
+    pub threshold_ms: u32,
+
+    /// 测速地址
+    #[serde(default = "default_auto_switch_test_url")]
+    pub test_url: String,
+}
+
+fn default_auto_switch_interval() -> u64 {
+    300
+}
+
+fn default_auto_switch_threshold() -> u32 {
+    50
+}
+
+fn default_auto_switch_test_url() -> String {
+    "http://www.gstatic.com/generate_204".to_string()
+}
+
+impl Default for AutoSwitchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            group: String::new(),
+            interval_secs: default_auto_switch_interval(),
+            threshold_ms: default_auto_switch_threshold(),
+            test_url: default_auto_switch_test_url(),
+        }
+    }
+}
+
+/// 核心进程优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CorePriority {
+    /// 让出 CPU，重载大型规则集时避免界面卡顿
+    Low,
+    #[default]
+    Normal,
+    /// 需要管理员权限
+    High,
+}
+
+impl CorePriority {
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_ascii_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "normal" => Some(Self::Normal),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+
+/// 连接数超限时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConnectionLimitAction {
+    /// 只发送提醒
+    #[default]
+    Warn,
+    /// 关闭最早建立的空闲连接，直到回到阈值以下
+    CloseIdle,
+}
+
+/// 连接数上限配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionLimitSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// 连接总数阈值
+    #[serde(default = "default_connection_limit_threshold")]
+    pub threshold: usize,
+
+    #[serde(default)]
+    pub action: ConnectionLimitAction,
+}
+
+fn default_connection_limit_threshold() -> usize {
+    2000
+}
+
+impl Default for ConnectionLimitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: default_connection_limit_threshold(),
+            action: ConnectionLimitAction::default(),
+        }
+    }
+}
+
+/// 网络切换（Wi-Fi/有线切换、VPN 连接或断开）后的处理策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkChangePolicy {
+    /// 是否在网络切换后自动处理
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// 系统代理已开启时重新应用到当前网络服务
+    #[serde(default = "default_true")]
+    pub reapply_system_proxy: bool,
+
+    /// 绑定的出站网卡（`interface-name`）不可用时恢复为自动选择
+    #[serde(default = "default_true")]
+    pub redetect_interface: bool,
+
+    /// 清除核心 DNS 缓存
+    #[serde(default)]
+    pub flush_dns: bool,
+}
+
+impl Default for NetworkChangePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reapply_system_proxy: true,
+            redetect_interface: true,
+            flush_dns: false,
+        }
+    }
+}
+
+/// 诊断数据（流量历史、规则命中、连接追踪、日志缓存）的内存限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservabilitySettings {
+    /// 诊断数据的总内存预算（MB），超出时丢弃最早的数据
+    #[serde(default = "default_observability_budget_mb")]
+    pub memory_budget_mb: u32,
+
+    /// 流量历史保留时长（分钟）
+    #[serde(default = "default_traffic_retention_minutes")]
+    pub traffic_retention_minutes: u32,
+
+    /// 是否统计规则命中次数（需要持续订阅 `/connections`，默认关闭）
+    #[serde(default)]
+    pub rule_hit_counting: bool,
+}
+
+fn default_observability_budget_mb() -> u32 {
+    64
+}
+
+fn default_traffic_retention_minutes() -> u32 {
+    60
+}
+
+impl Default for ObservabilitySettings {
+    fn default() -> Self {
+        Self {
+            memory_budget_mb: default_observability_budget_mb(),
+            traffic_retention_minutes: default_traffic_retention_minutes(),
+            rule_hit_counting: false,
+        }
+    }
+}
+
+/// 延迟着色阈值（毫秒）
+///
+/// 低于 `good` 为绿色，低于 `medium` 为黄色，其余为红色
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyThresholds {
+    #[serde(default = "default_latency_good")]
+    pub good: u32,
+    #[serde(default = "default_latency_medium")]
+    pub medium: u32,
+}
+
+fn default_latency_good() -> u32 {
+    200
+}
+
+fn default_latency_medium() -> u32 {
+    500
+}
+
+impl Default for LatencyThresholds {
+    fn default() -> Self {
+        Self {
+            good: default_latency_good(),
+            medium: default_latency_medium(),
+        }
+    }
+}
+
+/// 订阅到期/流量提醒配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionAlertSettings {
+    /// 是否启用
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// 距到期不足该天数时提醒
+    #[serde(default = "default_subscription_expire_days")]
+    pub expire_days: u32,
+
+    /// 已用流量超过该百分比时提醒
+    #[serde(default = "default_subscription_usage_percent")]
+    pub usage_percent: u8,
+}
+
+fn default_subscription_expire_days() -> u32 {
+    3
+}
+
+fn default_subscription_usage_percent() -> u8 {
+    90
+}
+
+impl Default for SubscriptionAlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            expire_days: default_subscription_expire_days(),
+            usage_percent: default_subscription_usage_percent(),
+        }
+    }
+}
+
+/// 策略组选择预设（保存一组策略组的当前选择，可一键恢复）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionPreset {
+    pub name: String,
+    /// 策略组名称 -> 选中的节点
+    pub selections: HashMap<String, String>,
+    /// 保存时间 (ISO 8601)
+    pub saved_at: String,
+}
+
+/// 按时段选择节点的一条规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionScheduleEntry {
+    /// 开始时间（本地时间 `HH:MM`，含）
+    pub start: String,
+    /// 结束时间（本地时间 `HH:MM`，不含）；早于开始时间表示跨天，等于开始时间表示全天
+    pub end: String,
+    pub group: String,
+    pub proxy: String,
+}
+
+/// 按时段自动选择节点
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionScheduleSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 同一策略组有多条规则命中时，靠前的优先
+    #[serde(default)]
+    pub entries: Vec<SelectionScheduleEntry>,
+}
+
 /// MiHomo 用户设置（存储在 settings.json 中，用于生成运行时 config.yaml）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -735,10 +1237,22 @@ pub struct MihomoSettings {
     #[serde(default)]
     pub allow_lan: bool,
 
-    /// 代理模式 (rule/global/direct)
+    /// 局域网共享监听地址，未设置时为 `*`
+    #[serde(default)]
+    pub bind_address: Option<String>,
+
+    /// 允许访问的局域网网段（CIDR），为空时不限制
+    #[serde(default)]
+    pub lan_allowed_ips: Vec<String>,
+
+    /// 代理模式 (rule/global/direct/script)
     #[serde(default = "default_mode")]
     pub mode: String,
 
+    /// `script` 模式使用的脚本
+    #[serde(default)]
+    pub script: Option<ScriptConfig>,
+
     /// 启用 IPv6
     #[serde(default)]
     pub ipv6: bool,
@@ -751,6 +1265,46 @@ pub struct MihomoSettings {
     #[serde(default = "default_find_process_mode")]
     pub find_process_mode: String,
 
+    /// 出站绑定网卡，未设置时由核心自动选择
+    #[serde(default)]
+    pub interface_name: Option<String>,
+
+    /// 出站 routing-mark（仅 Linux）
+    #[serde(default)]
+    pub routing_mark: Option<u32>,
+
+    /// TCP keep-alive 探测间隔（秒），未设置时使用核心默认值
+    #[serde(default)]
+    pub keep_alive_interval: Option<u32>,
+
+    /// TCP keep-alive 空闲时间（秒），未设置时使用核心默认值
+    #[serde(default)]
+    pub keep_alive_idle: Option<u32>,
+
+    /// 关闭 TCP keep-alive
+    #[serde(default)]
+    pub disable_keep_alive: bool,
+
+    /// 核心下载资源的 User-Agent，未设置时使用核心默认值
+    #[serde(default)]
+    pub global_ua: Option<String>,
+
+    /// Provider 下载的 ETag 支持，未设置时使用核心默认值（开启）
+    #[serde(default)]
+    pub etag_support: Option<bool>,
+
+    /// 覆盖所有 proxy-provider 的健康检查间隔（秒），未设置时使用 Profile 中的值
+    #[serde(default)]
+    pub provider_health_check_interval: Option<u32>,
+
+    /// 由核心自动更新 GEO 数据（开启后应用不再自动更新规则数据库）
+    #[serde(default)]
+    pub geo_auto_update: bool,
+
+    /// 核心自动更新 GEO 数据的间隔（小时），未设置时使用核心默认值
+    #[serde(default)]
+    pub geo_update_interval: Option<u32>,
+
     /// TUN 模式配置
     #[serde(default)]
     pub tun: TunConfig,
@@ -766,6 +1320,34 @@ pub struct MihomoSettings {
     /// 外部控制器地址（应用层管理）
     #[serde(default = "default_external_controller")]
     pub external_controller: String,
+
+    /// HTTPS 外部控制器监听地址，设置后应用通过 HTTPS 连接核心
+    #[serde(default)]
+    pub external_controller_tls: Option<String>,
+
+    /// 控制器 TLS 证书路径（PEM）
+    #[serde(default)]
+    pub tls_certificate: Option<String>,
+
+    /// 控制器 TLS 私钥路径（PKCS#8 PEM）
+    #[serde(default)]
+    pub tls_private_key: Option<String>,
+
+    /// 面板目录，安装面板后设置
+    #[serde(default)]
+    pub external_ui: Option<String>,
+
+    /// 面板下载地址
+    #[serde(default)]
+    pub external_ui_url: Option<String>,
+
+    /// 域名嗅探配置，未设置时沿用 config.yaml 中的配置（不存在则使用默认配置）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sniffer: Option<SnifferConfig>,
+
+    /// 由核心持久化策略组选中的节点（`profile.store-selected`）
+    #[serde(default = "default_true")]
+    pub store_selected: bool,
 }
 
 impl Default for MihomoSettings {
@@ -775,18 +1357,47 @@ impl Default for MihomoSettings {
             socks_port: default_socks_port(),
             mixed_port: default_mixed_port(),
             allow_lan: true,
+            bind_address: None,
+            lan_allowed_ips: vec![],
             mode: default_mode(),
+            script: None,
             ipv6: false,
             tcp_concurrent: true,
             find_process_mode: default_find_process_mode(),
+            interface_name: None,
+            routing_mark: None,
+            keep_alive_interval: None,
+            keep_alive_idle: None,
+            disable_keep_alive: false,
+            global_ua: None,
+            etag_support: None,
+            provider_health_check_interval: None,
+            geo_auto_update: false,
+            geo_update_interval: None,
             tun: TunConfig::default(),
             dns: DnsConfig::default(),
             secret: String::new(),
             external_controller: default_external_controller(),
+            external_controller_tls: None,
+            tls_certificate: None,
+            tls_private_key: None,
+            external_ui: None,
+            external_ui_url: None,
+            sniffer: None,
+            store_selected: true,
         }
     }
 }
 
+/// 局域网共享设置
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanAccessSettings {
+    pub allow_lan: bool,
+    pub bind_address: Option<String>,
+    pub allowed_ips: Vec<String>,
+}
+
 /// 应用设置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
@@ -802,6 +1413,10 @@ pub struct AppSettings {
     #[serde(rename = "closeToTray", default = "default_close_to_tray")]
     pub close_to_tray: bool,
 
+    /// 启动时不显示主窗口，只显示托盘图标
+    #[serde(rename = "startMinimized", default)]
+    pub start_minimized: bool,
+
     /// 使用 JsDelivr 加速 GitHub 资源
     #[serde(rename = "useJsdelivr", default)]
     pub use_jsdelivr: bool,
@@ -813,6 +1428,69 @@ pub struct AppSettings {
     #[serde(default)]
     pub webdav: WebDavConfig,
 
+    /// 自动切换最快节点
+    #[serde(rename = "autoSwitch", default)]
+    pub auto_switch: AutoSwitchSettings,
+
+    /// 连接数上限（防止异常应用建立大量连接）
+    #[serde(rename = "connectionLimit", default)]
+    pub connection_limit: ConnectionLimitSettings,
+
+    /// 延迟着色阈值（窗口与托盘共用）
+    #[serde(rename = "latencyThresholds", default)]
+    pub latency_thresholds: LatencyThresholds,
+
+    /// 策略组选择预设
+    #[serde(rename = "selectionPresets", default)]
+    pub selection_presets: Vec<SelectionPreset>,
+
+    /// 按时段自动选择节点
+    #[serde(rename = "selectionSchedule", default)]
+    pub selection_schedule: SelectionScheduleSettings,
+
+    /// 订阅到期/流量提醒
+    #[serde(rename = "subscriptionAlert", default)]
+    pub subscription_alert: SubscriptionAlertSettings,
+
+    /// 允许订阅更新为空（默认拒绝用 0 个节点覆盖已有节点）
+    #[serde(rename = "allowEmptySubscription", default)]
+    pub allow_empty_subscription: bool,
+
+    /// 切换配置后自动预热远程 Provider
+    #[serde(rename = "warmProvidersOnActivate", default)]
+    pub warm_providers_on_activate: bool,
+
+    /// 首次启用订阅时应用其建议的默认节点
+    #[serde(rename = "applySuggestedSelection", default = "default_true")]
+    pub apply_suggested_selection: bool,
+
+    /// 激活配置、保存配置或切换 TUN 模式出现内部错误时生成崩溃报告（已脱敏）
+    #[serde(rename = "crashReportsEnabled", default)]
+    pub crash_reports_enabled: bool,
+
+    /// 日志/连接数据流断开后的重连间隔（秒），连续失败时翻倍退避
+    #[serde(
+        rename = "streamReconnectInterval",
+        default = "default_stream_reconnect_interval"
+    )]
+    pub stream_reconnect_interval: u64,
+
+    /// 核心进程优先级（启动和重启核心时应用）
+    #[serde(rename = "corePriority", default)]
+    pub core_priority: CorePriority,
+
+    /// macOS 按应用分流规则（Network Extension）
+    #[serde(rename = "appProxyRules", default)]
+    pub app_proxy_rules: AppProxyRules,
+
+    /// 网络切换后的处理策略
+    #[serde(rename = "networkChangePolicy", default)]
+    pub network_change_policy: NetworkChangePolicy,
+
+    /// 诊断数据的内存限制
+    #[serde(default)]
+    pub observability: ObservabilitySettings,
+
     /// MiHomo 用户设置（端口、DNS、TUN 等）
     #[serde(default)]
     pub mihomo: MihomoSettings,
@@ -825,6 +1503,44 @@ fn default_close_to_tray() -> bool {
     true
 }
 
+fn default_stream_reconnect_interval() -> u64 {
+    crate::mihomo::DEFAULT_STREAM_RECONNECT_SECS
+}
+
+/// 窗口与托盘的启动行为
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowBehavior {
+    /// 开机自启动
+    pub auto_start: bool,
+    /// 启动时最小化到托盘
+    pub start_minimized: bool,
+    /// 关闭主窗口时隐藏到托盘而不是退出
+    pub close_to_tray: bool,
+}
+
+impl WindowBehavior {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            auto_start: settings.auto_start,
+            start_minimized: settings.start_minimized,
+            close_to_tray: settings.close_to_tray,
+        }
+    }
+}
+
+/// 按应用分流规则（以 Bundle ID 标识应用）
+///
+/// `included` 非空时只有其中的应用走代理；`excluded` 中的应用始终直连。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppProxyRules {
+    #[serde(default)]
+    pub included: Vec<String>,
+    #[serde(default)]
+    pub excluded: Vec<String>,
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
@@ -832,6 +1548,7 @@ impl Default for AppSettings {
             auto_start: false,
             system_proxy: false,
             close_to_tray: default_close_to_tray(),
+            start_minimized: false,
             use_jsdelivr: false,
             // A 体系（geodata .dat）：必需资源
             // - geoip.dat (GeoIP)
@@ -883,6 +1600,21 @@ impl Default for AppSettings {
                 },
             ],
             webdav: WebDavConfig::default(),
+            auto_switch: AutoSwitchSettings::default(),
+            connection_limit: ConnectionLimitSettings::default(),
+            latency_thresholds: LatencyThresholds::default(),
+            selection_presets: Vec::new(),
+            selection_schedule: SelectionScheduleSettings::default(),
+            subscription_alert: SubscriptionAlertSettings::default(),
+            allow_empty_subscription: false,
+            warm_providers_on_activate: false,
+            apply_suggested_selection: true,
+            crash_reports_enabled: false,
+            stream_reconnect_interval: default_stream_reconnect_interval(),
+            core_priority: CorePriority::default(),
+            app_proxy_rules: AppProxyRules::default(),
+            network_change_policy: NetworkChangePolicy::default(),
+            observability: ObservabilitySettings::default(),
             mihomo: MihomoSettings::default(),
         }
     }
@@ -3,6 +3,12 @@ use std::collections::HashMap;
 
 use super::config::{ProxyConfig, ProxyGroupConfig, ProxyProvider, RuleProvider};
 
+/// 每个 Profile 保留的修改记录条数
+pub const MAX_PROFILE_HISTORY: usize = 50;
+
+/// 备注最大长度（字符数）
+pub const MAX_PROFILE_NOTES_CHARS: usize = 10_000;
+
 /// Profile 类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -13,6 +19,9 @@ pub enum ProfileType {
     Local,
     /// 空白配置（新建）
     Blank,
+    /// 监听本地文件（源文件变化时自动重新导入）
+    #[serde(rename = "local_watch")]
+    LocalWatch,
 }
 
 impl Default for ProfileType {
@@ -34,6 +43,9 @@ pub struct ProfileMetadata {
     /// 远程订阅 URL（仅 Remote 类型）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    /// 监听的源文件路径（仅 LocalWatch 类型）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
     /// 创建时间 (ISO 8601)
     pub created_at: String,
     /// 最后更新时间 (ISO 8601)
@@ -53,14 +65,87 @@ pub struct ProfileMetadata {
     /// 是否自动生成默认规则（远程订阅且无规则时）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_rules_applied: Option<bool>,
+    /// 是否已应用订阅建议的默认节点（只在首次启用时应用，之后保留用户的选择）
+    #[serde(default)]
+    pub default_selection_applied: bool,
+    /// 按地区生成的策略组名称，再次生成时只覆盖这些策略组
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub region_groups: Vec<String>,
     /// 是否当前激活
     pub active: bool,
+    /// 多配置模式下是否作为副 Profile 激活（提供规则和规则源）
+    #[serde(default)]
+    pub secondary_active: bool,
     /// 自动更新（仅 Remote 类型）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_update: Option<bool>,
     /// 更新间隔（小时，仅 Remote 类型）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub update_interval: Option<u32>,
+    /// 用户备注
+    #[serde(default)]
+    pub notes: String,
+    /// 修改记录（按时间顺序追加，超出上限时丢弃最早的记录）
+    #[serde(default)]
+    pub history: Vec<ProfileHistoryEntry>,
+    /// 订阅用量（远程订阅响应头 `subscription-userinfo`，仅 Remote 类型）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<SubscriptionUsage>,
+}
+
+/// 订阅用量与到期时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUsage {
+    pub upload: u64,
+    pub download: u64,
+    pub total: u64,
+    /// 到期时间（Unix 秒时间戳）
+    pub expire: Option<u64>,
+}
+
+impl SubscriptionUsage {
+    /// 解析 `subscription-userinfo` 响应头（`upload=1; download=2; total=3; expire=4`）
+    pub fn parse_header(value: &str) -> Option<Self> {
+        let mut usage = Self::default();
+        let mut found = false;
+
+        for pair in value.split(';') {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            // 部分机场返回浮点数（如 `1.2e10`）
+            let Ok(number) = value.trim().parse::<f64>() else {
+                continue;
+            };
+            let number = number.max(0.0) as u64;
+            match key.trim().to_ascii_lowercase().as_str() {
+                "upload" => usage.upload = number,
+                "download" => usage.download = number,
+                "total" => usage.total = number,
+                "expire" => usage.expire = (number > 0).then_some(number),
+                _ => continue,
+            }
+            found = true;
+        }
+
+        found.then_some(usage)
+    }
+
+    /// 已用流量百分比（总量未知时为 None）
+    pub fn usage_percent(&self) -> Option<f64> {
+        (self.total > 0).then(|| (self.upload + self.download) as f64 / self.total as f64 * 100.0)
+    }
+}
+
+/// Profile 修改记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileHistoryEntry {
+    /// 记录时间 (ISO 8601)
+    pub timestamp: String,
+    /// 操作描述
+    pub description: String,
 }
 
 impl ProfileMetadata {
@@ -72,6 +157,7 @@ impl ProfileMetadata {
             name,
             profile_type: ProfileType::Remote,
             url: Some(url),
+            source_path: None,
             created_at: now.clone(),
             updated_at: now,
             proxy_count: 0,
@@ -80,9 +166,15 @@ impl ProfileMetadata {
             provider_count: 0,
             provider_proxy_counts: HashMap::new(),
             default_rules_applied: None,
+            default_selection_applied: false,
+            region_groups: Vec::new(),
             active: false,
+            secondary_active: false,
             auto_update: Some(true),
             update_interval: Some(24),
+            notes: String::new(),
+            history: Vec::new(),
+            subscription: None,
         }
     }
 
@@ -94,6 +186,7 @@ impl ProfileMetadata {
             name,
             profile_type: ProfileType::Local,
             url: None,
+            source_path: None,
             created_at: now.clone(),
             updated_at: now,
             proxy_count: 0,
@@ -102,9 +195,24 @@ impl ProfileMetadata {
             provider_count: 0,
             provider_proxy_counts: HashMap::new(),
             default_rules_applied: None,
+            default_selection_applied: false,
+            region_groups: Vec::new(),
             active: false,
+            secondary_active: false,
             auto_update: None,
             update_interval: None,
+            notes: String::new(),
+            history: Vec::new(),
+            subscription: None,
+        }
+    }
+
+    /// 创建新的本地文件监听元数据
+    pub fn new_local_watch(id: String, name: String, source_path: String) -> Self {
+        Self {
+            profile_type: ProfileType::LocalWatch,
+            source_path: Some(source_path),
+            ..Self::new_local(id, name)
         }
     }
 
@@ -116,6 +224,7 @@ impl ProfileMetadata {
             name,
             profile_type: ProfileType::Blank,
             url: None,
+            source_path: None,
             created_at: now.clone(),
             updated_at: now,
             proxy_count: 0,
@@ -124,12 +233,23 @@ impl ProfileMetadata {
             provider_count: 0,
             provider_proxy_counts: HashMap::new(),
             default_rules_applied: None,
+            default_selection_applied: false,
+            region_groups: Vec::new(),
             active: false,
+            secondary_active: false,
             auto_update: None,
             update_interval: None,
+            notes: String::new(),
+            history: Vec::new(),
+            subscription: None,
         }
     }
 
+    /// 是否正在被运行时配置使用（主 Profile 或副 Profile）
+    pub fn in_use(&self) -> bool {
+        self.active || self.secondary_active
+    }
+
     /// 更新统计信息
     pub fn update_stats(
         &mut self,
@@ -145,6 +265,18 @@ impl ProfileMetadata {
         self.updated_at = chrono::Local::now().to_rfc3339();
     }
 
+    /// 追加一条修改记录
+    pub fn record_history(&mut self, description: impl Into<String>) {
+        self.history.push(ProfileHistoryEntry {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            description: description.into(),
+        });
+        if self.history.len() > MAX_PROFILE_HISTORY {
+            let overflow = self.history.len() - MAX_PROFILE_HISTORY;
+            self.history.drain(..overflow);
+        }
+    }
+
     /// 更新提供者节点数量统计
     pub fn update_provider_proxy_counts(&mut self, counts: HashMap<String, u32>) {
         self.provider_proxy_counts = counts;
@@ -183,6 +315,10 @@ pub struct ProfileConfig {
     /// 规则列表
     #[serde(default, alias = "Rule")]
     pub rules: Vec<String>,
+
+    /// 子规则集合，由 `SUB-RULE,(条件),名称` 规则引用
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sub_rules: HashMap<String, Vec<String>>,
 }
 
 impl ProfileConfig {
@@ -199,6 +335,7 @@ impl ProfileConfig {
             proxy_providers: HashMap::new(),
             rule_providers: HashMap::new(),
             rules: vec!["MATCH,PROXY".to_string()],
+            sub_rules: HashMap::new(),
         }
     }
 
@@ -227,3 +364,296 @@ impl ProfileConfig {
         self.proxies.iter().any(|p| p.name == name)
     }
 }
+
+/// 外部目录导入结果
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalImportResult {
+    /// 成功导入的 Profile
+    pub imported: Vec<ProfileMetadata>,
+    /// 跳过的文件
+    pub skipped: Vec<SkippedImportFile>,
+}
+
+/// Surge / QuantumultX 配置导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignImportResult {
+    /// 创建的 Profile
+    pub profile: ProfileMetadata,
+    /// 无法转换而跳过的行
+    pub warnings: Vec<String>,
+}
+
+/// 导入时跳过的文件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedImportFile {
+    /// 文件路径
+    pub path: String,
+    /// 跳过原因
+    pub reason: String,
+}
+
+/// 订阅文件校验结果
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionValidation {
+    /// 没有解析错误（警告不影响导入）
+    pub valid: bool,
+    /// 解析错误（无法导入）
+    pub errors: Vec<String>,
+    /// 引用问题等警告（可以导入，但可能不生效）
+    pub warnings: Vec<String>,
+    pub proxy_count: usize,
+    pub group_count: usize,
+    pub rule_count: usize,
+}
+
+/// 从规则列表 URL 导入规则的结果
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleImportResult {
+    /// 新增的规则数
+    pub added: usize,
+    /// 无法解析或缺少目标而跳过的行数
+    pub skipped: usize,
+    /// 与已有规则重复而跳过的规则数
+    pub duplicates: usize,
+    /// 导入后新出现的校验警告
+    pub warnings: Vec<String>,
+}
+
+/// 规则模板的合并方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateMergeMode {
+    /// 规则插入到 MATCH 之前，同名规则源被模板覆盖，重名的模板策略组重命名后追加
+    Append,
+    /// 模板中非空的部分整体替换 Profile 中的对应部分
+    Replace,
+}
+
+/// 规则模板应用到单个 Profile 的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateApplyResult {
+    pub profile_id: String,
+    /// 是否已保存（出现新的校验警告时不保存）
+    pub applied: bool,
+    /// 应用后新出现的校验警告
+    pub warnings: Vec<String>,
+    /// 与已有策略组重名而被重命名的模板策略组
+    pub renamed_groups: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// 节点重命名
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeRename {
+    pub old_name: String,
+    pub new_name: String,
+}
+
+/// 按地区生成的 `url-test` 策略组
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionGroup {
+    pub name: String,
+    /// 名称匹配该地区的节点
+    pub proxies: Vec<String>,
+    /// 引用的代理源（节点在运行时由核心按 `filter` 筛选）
+    pub providers: Vec<String>,
+    /// 代理源节点的名称过滤，没有代理源时为 None
+    pub filter: Option<String>,
+}
+
+/// 按地区生成策略组的方案
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionGroupsPlan {
+    /// 列出所有地区组的 `select` 策略组
+    pub select_group: String,
+    pub groups: Vec<RegionGroup>,
+    /// 没有匹配任何地区的节点（代理源中的节点在运行时才知道，不在此列）
+    pub unmatched: Vec<String>,
+}
+
+/// 同一匹配条件指向不同策略的规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleConflict {
+    /// 匹配条件，如 `DOMAIN-SUFFIX,google.com`
+    pub matcher: String,
+    /// 最先出现、实际生效的规则
+    pub effective: String,
+    /// 之后出现的冲突规则（不会被命中）
+    pub shadowed: Vec<String>,
+}
+
+/// 规则去重报告
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDedupReport {
+    /// 已移除的规则
+    pub removed: Vec<String>,
+    pub conflicts: Vec<RuleConflict>,
+}
+
+/// 被前面更宽泛的规则完全覆盖、永远不会命中的规则
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowedRule {
+    /// 覆盖规则在 `rules` 中的下标
+    pub shadowing_index: usize,
+    pub shadowing_rule: String,
+    /// 被覆盖规则在 `rules` 中的下标
+    pub shadowed_index: usize,
+    pub shadowed_rule: String,
+}
+
+/// 策略组循环引用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupCycle {
+    /// 循环中的策略组，依次引用下一个，最后一个引用第一个
+    pub groups: Vec<String>,
+}
+
+impl GroupCycle {
+    /// 展示用的循环路径，首尾相同，如 `A → B → A`
+    pub fn path(&self) -> String {
+        let mut path = self.groups.clone();
+        path.extend(self.groups.first().cloned());
+        path.join(" → ")
+    }
+}
+
+/// 配置差异中的一项
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDiffItem {
+    pub name: String,
+    /// 节点为 `类型 地址:端口`，策略组为变更说明
+    pub detail: Option<String>,
+}
+
+/// 某一类配置项的差异
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ConfigDiffSection {
+    pub added: Vec<ConfigDiffItem>,
+    pub removed: Vec<ConfigDiffItem>,
+    pub changed: Vec<ConfigDiffItem>,
+}
+
+/// 两份 Profile 配置的差异（节点、策略组、规则）
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileDiff {
+    pub proxies: ConfigDiffSection,
+    pub proxy_groups: ConfigDiffSection,
+    /// 规则没有名称，按内容比较，只有新增和删除
+    pub rules: ConfigDiffSection,
+}
+
+/// 节点分享链接
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareLink {
+    pub name: String,
+    pub link: String,
+}
+
+/// 单个规则源的刷新结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesetRefreshResult {
+    pub name: String,
+    /// 是否下载了新内容（false 表示未变化或失败）
+    pub updated: bool,
+    pub error: Option<String>,
+}
+
+/// 规则源/代理源本地文件的检查状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderCheckStatus {
+    /// 本地文件缺失
+    Missing,
+    /// 原本缺失，已下载
+    Downloaded,
+    /// 缺失且下载失败
+    DownloadFailed,
+}
+
+/// 单个有问题的规则源/代理源
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCheck {
+    pub name: String,
+    /// `rule` 或 `proxy`
+    pub kind: String,
+    /// `file` 或 `http`
+    pub provider_type: String,
+    /// 修正后的本地路径
+    pub path: Option<String>,
+    pub status: ProviderCheckStatus,
+    /// 处理建议或下载失败原因
+    pub message: String,
+}
+
+/// 导出分享链接的结果
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLinkExport {
+    pub links: Vec<ShareLink>,
+    /// 所有链接按行拼接，便于一次性复制
+    pub text: String,
+    /// 类型不支持或缺少必要字段而跳过的节点名称
+    pub skipped: Vec<String>,
+}
+
+/// 节点安全问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecuritySeverity {
+    Low,
+    Medium,
+    High,
+}
+
+/// 节点安全问题
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityFinding {
+    /// 节点名称
+    pub proxy: String,
+    pub severity: SecuritySeverity,
+    /// 问题类型：`skip-cert-verify`、`weak-cipher`、`missing-sni`、`plaintext`
+    pub kind: String,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subscription_userinfo() {
+        let usage = SubscriptionUsage::parse_header(
+            "upload=1073741824; download=2147483648; total=10737418240; expire=1735689600",
+        )
+        .unwrap();
+        assert_eq!(usage.upload, 1_073_741_824);
+        assert_eq!(usage.total, 10_737_418_240);
+        assert_eq!(usage.expire, Some(1_735_689_600));
+        assert_eq!(usage.usage_percent(), Some(30.0));
+
+        let usage =
+            SubscriptionUsage::parse_header("upload=0; download=1.5e9; total=0; expire=").unwrap();
+        assert_eq!(usage.download, 1_500_000_000);
+        assert_eq!(usage.expire, None);
+        assert_eq!(usage.usage_percent(), None);
+
+        assert!(SubscriptionUsage::parse_header("invalid").is_none());
+    }
+}
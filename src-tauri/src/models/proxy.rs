@@ -25,6 +25,29 @@ impl Default for RunMode {
     }
 }
 
+/// 代理接入状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyIntegration {
+    /// 核心未运行
+    #[default]
+    Off,
+    /// 核心运行中，但未设置系统代理、未启用 TUN（仅提供代理端口）
+    ProxyOnly,
+    /// 已接管系统流量（系统代理或 TUN）
+    SystemIntegrated,
+}
+
+impl ProxyIntegration {
+    pub fn detect(running: bool, system_proxy: bool, enhanced_mode: bool) -> Self {
+        match (running, system_proxy || enhanced_mode) {
+            (false, _) => Self::Off,
+            (true, false) => Self::ProxyOnly,
+            (true, true) => Self::SystemIntegrated,
+        }
+    }
+}
+
 /// 代理状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyStatus {
@@ -40,6 +63,20 @@ pub struct ProxyStatus {
     pub tcp_concurrent: bool,
     /// 运行模式（普通/服务/提权等）
     pub run_mode: RunMode,
+    /// 代理接入状态
+    #[serde(default)]
+    pub integration: ProxyIntegration,
+}
+
+/// 与核心运行时状态对齐的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateReconcile {
+    /// 发现并修正的差异，如 `mode: rule -> global`
+    pub changes: Vec<String>,
+    /// 核心当前各策略组选中的节点
+    pub selections: HashMap<String, String>,
+    pub status: ProxyStatus,
 }
 
 impl Default for ProxyStatus {
@@ -56,6 +93,7 @@ impl Default for ProxyStatus {
             ipv6: false,
             tcp_concurrent: true,
             run_mode: RunMode::default(),
+            integration: ProxyIntegration::default(),
         }
     }
 }
@@ -74,6 +112,10 @@ pub struct ProxyInfo {
     pub all: Vec<String>,
     #[serde(default)]
     pub now: Option<String>,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub icon: Option<String>,
 }
 
 /// 延迟历史记录
@@ -181,6 +223,137 @@ pub struct ConnectionsResponse {
     pub upload_total: u64,
 }
 
+/// 连接分页排序字段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionSortKey {
+    /// 上传量（从大到小）
+    Upload,
+    /// 下载量（从大到小）
+    Download,
+    /// 持续时间（从长到短）
+    #[default]
+    Duration,
+    /// 主机名（字母序）
+    Host,
+}
+
+/// 分页后的连接列表
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionsPage {
+    pub connections: Vec<Connection>,
+    /// 过滤后的连接总数
+    pub total: usize,
+    pub download_total: u64,
+    pub upload_total: u64,
+}
+
+/// 规则影响预估：当前活跃连接中会被该规则命中的连接
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleImpact {
+    /// 规则目标，规则未写目标时为空
+    pub target: String,
+    pub matched: Vec<Connection>,
+    /// 当前活跃连接总数
+    pub total: usize,
+}
+
+impl Connection {
+    /// 主机名，缺失时使用目标 IP
+    pub fn display_host(&self) -> &str {
+        if self.metadata.host.is_empty() {
+            &self.metadata.destination_ip
+        } else {
+            &self.metadata.host
+        }
+    }
+
+    /// 按主机、IP、进程、规则、代理链匹配关键字（keyword 需为小写）
+    fn matches(&self, keyword: &str) -> bool {
+        let meta = &self.metadata;
+        [
+            meta.host.as_str(),
+            meta.destination_ip.as_str(),
+            meta.source_ip.as_str(),
+            meta.process.as_deref().unwrap_or_default(),
+            self.rule.as_str(),
+            self.rule_payload.as_str(),
+        ]
+        .into_iter()
+        .chain(self.chains.iter().map(String::as_str))
+        .any(|field| field.to_lowercase().contains(keyword))
+    }
+}
+
+impl ConnectionsResponse {
+    /// 过滤、排序并截取一页连接
+    pub fn into_page(
+        self,
+        offset: usize,
+        limit: usize,
+        sort_by: ConnectionSortKey,
+        filter: Option<&str>,
+    ) -> ConnectionsPage {
+        let keyword = filter
+            .map(|f| f.trim().to_lowercase())
+            .filter(|f| !f.is_empty());
+        let mut connections: Vec<Connection> = match keyword {
+            Some(keyword) => self
+                .connections
+                .into_iter()
+                .filter(|c| c.matches(&keyword))
+                .collect(),
+            None => self.connections,
+        };
+
+        match sort_by {
+            ConnectionSortKey::Upload => connections.sort_by(|a, b| b.upload.cmp(&a.upload)),
+            ConnectionSortKey::Download => {
+                connections.sort_by(|a, b| b.download.cmp(&a.download));
+            }
+            // 开始时间越早持续越久；解析失败的排在最后
+            ConnectionSortKey::Duration => connections.sort_by_cached_key(|c| {
+                chrono::DateTime::parse_from_rfc3339(&c.start)
+                    .map_or(i64::MAX, |t| t.timestamp_millis())
+            }),
+            ConnectionSortKey::Host => {
+                connections.sort_by_cached_key(|c| c.display_host().to_lowercase());
+            }
+        }
+
+        let total = connections.len();
+        let connections = connections.into_iter().skip(offset).take(limit).collect();
+
+        ConnectionsPage {
+            connections,
+            total,
+            download_total: self.download_total,
+            upload_total: self.upload_total,
+        }
+    }
+}
+
+/// 经代理链路的分段延迟（毫秒）
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainLatency {
+    pub url: String,
+    /// 核心 DNS 解析耗时（目标为 IP 或查询失败时为空）
+    pub dns_ms: Option<u64>,
+    /// 经混合端口 CONNECT 到目标的耗时（包含节点建连及中转）
+    pub connect_ms: u64,
+    /// TLS 握手耗时（仅 https）
+    pub tls_ms: Option<u64>,
+    /// 发出请求到收到首字节的耗时
+    pub ttfb_ms: u64,
+    /// connect + tls + ttfb
+    pub total_ms: u64,
+    /// HTTP 状态码
+    pub status: u16,
+}
+
 /// 流量数据
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TrafficData {
@@ -194,6 +367,9 @@ pub struct VersionInfo {
     pub version: String,
     #[serde(default)]
     pub meta: bool,
+    /// 固定的核心版本（本机设置，核心 API 不返回该字段）
+    #[serde(rename = "pinnedVersion", default)]
+    pub pinned_version: Option<String>,
 }
 
 /// 代理组
@@ -204,6 +380,10 @@ pub struct ProxyGroup {
     pub group_type: String,
     pub now: Option<String>,
     pub all: Vec<String>,
+    /// 策略组 `hidden` 字段，前端据此隐藏
+    pub hidden: bool,
+    /// 策略组 `icon` 字段
+    pub icon: Option<String>,
 }
 
 /// 规则项
@@ -287,3 +467,38 @@ pub struct RuleProviderInfo {
 pub struct RuleProvidersResponse {
     pub providers: std::collections::HashMap<String, RuleProviderInfo>,
 }
+
+/// 应用选择预设时被跳过的项
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedSelection {
+    pub group: String,
+    pub proxy: String,
+    pub reason: String,
+}
+
+/// 应用选择预设的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionPresetResult {
+    /// 已恢复选择的策略组
+    pub applied: Vec<String>,
+    pub skipped: Vec<SkippedSelection>,
+}
+
+/// 重置节点选择时改变的策略组
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionChange {
+    pub group: String,
+    pub from: Option<String>,
+    pub to: String,
+}
+
+/// 重置节点选择的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionResetResult {
+    pub changed: Vec<SelectionChange>,
+    pub skipped: Vec<SkippedSelection>,
+}
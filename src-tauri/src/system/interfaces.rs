@@ -0,0 +1,134 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// 本机网卡
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInterface {
+    pub name: String,
+    /// 网卡上的 IPv4/IPv6 地址
+    pub addresses: Vec<String>,
+    /// 网卡是否已启用并连接，无法获取时为 None
+    pub up: Option<bool>,
+    pub is_loopback: bool,
+}
+
+/// 列出本机已分配地址的网卡（按名称排序）
+pub fn list_network_interfaces() -> Result<Vec<NetworkInterface>> {
+    let mut interfaces: BTreeMap<String, NetworkInterface> = BTreeMap::new();
+    let states = interface_states();
+
+    for iface in get_if_addrs::get_if_addrs()? {
+        let entry = interfaces
+            .entry(iface.name.clone())
+            .or_insert_with(|| NetworkInterface {
+                up: states.get(&iface.name).copied(),
+                name: iface.name.clone(),
+                addresses: Vec::new(),
+                is_loopback: false,
+            });
+        entry.is_loopback |= iface.is_loopback();
+        entry.addresses.push(iface.ip().to_string());
+    }
+
+    Ok(interfaces.into_values().collect())
+}
+
+/// 列出本机网卡所在的网段（网卡名, `地址/前缀长度`）
+pub fn list_interface_networks() -> Result<Vec<(String, String)>> {
+    use get_if_addrs::IfAddr;
+
+    Ok(get_if_addrs::get_if_addrs()?
+        .into_iter()
+        .map(|iface| {
+            let prefix = match &iface.addr {
+                IfAddr::V4(v4) => u32::from(v4.netmask).count_ones(),
+                IfAddr::V6(v6) => u128::from(v6.netmask).count_ones(),
+            };
+            let network = format!("{}/{}", iface.ip(), prefix);
+            (iface.name, network)
+        })
+        .collect())
+}
+
+/// 各网卡是否处于连接状态（网卡名 -> 是否已启用并连接），一次系统调用取得全部网卡
+///
+/// Unix 上要求 UP 且 RUNNING；Windows 上以适配器名（与 `get_if_addrs` 一致）匹配，要求 OperStatus 为 Up。
+#[cfg(unix)]
+fn interface_states() -> HashMap<String, bool> {
+    let mut states = HashMap::new();
+    let mut addrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut addrs) } != 0 {
+        return states;
+    }
+
+    let required = (libc::IFF_UP | libc::IFF_RUNNING) as libc::c_uint;
+    let mut cursor = addrs;
+    while !cursor.is_null() {
+        let ifa = unsafe { &*cursor };
+        let name = unsafe { std::ffi::CStr::from_ptr(ifa.ifa_name) };
+        states
+            .entry(name.to_string_lossy().into_owned())
+            .or_insert(ifa.ifa_flags & required == required);
+        cursor = ifa.ifa_next;
+    }
+
+    unsafe { libc::freeifaddrs(addrs) };
+    states
+}
+
+#[cfg(windows)]
+fn interface_states() -> HashMap<String, bool> {
+    use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
+        GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows_sys::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+    use windows_sys::Win32::Networking::WinSock::AF_UNSPEC;
+
+    let mut states = HashMap::new();
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    let mut size: u32 = 16 * 1024;
+    // 按 u64 分配，保证结构体对齐；缓冲区不足时按返回的大小重试
+    let mut buffer: Vec<u64> = Vec::new();
+    let mut result = ERROR_BUFFER_OVERFLOW;
+    for _ in 0..3 {
+        buffer.resize((size as usize).div_ceil(8), 0);
+        result = unsafe {
+            GetAdaptersAddresses(
+                u32::from(AF_UNSPEC),
+                flags,
+                std::ptr::null(),
+                buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH,
+                &mut size,
+            )
+        };
+        if result != ERROR_BUFFER_OVERFLOW {
+            break;
+        }
+    }
+    if result != NO_ERROR {
+        return states;
+    }
+
+    let mut cursor = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while !cursor.is_null() {
+        let adapter = unsafe { &*cursor };
+        if !adapter.AdapterName.is_null() {
+            let name = unsafe { std::ffi::CStr::from_ptr(adapter.AdapterName as *const _) };
+            states.insert(
+                name.to_string_lossy().into_owned(),
+                adapter.OperStatus == IfOperStatusUp,
+            );
+        }
+        cursor = adapter.Next;
+    }
+    states
+}
+
+#[cfg(not(any(unix, windows)))]
+fn interface_states() -> HashMap<String, bool> {
+    HashMap::new()
+}
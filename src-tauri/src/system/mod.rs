@@ -1,5 +1,7 @@
 mod app_icon;
+mod interfaces;
 mod network_extension;
+mod network_monitor;
 mod proxy;
 mod tun;
 
@@ -7,7 +9,9 @@ mod tun;
 mod win_service;
 
 pub use app_icon::*;
+pub use interfaces::*;
 pub use network_extension::*;
+pub use network_monitor::*;
 pub use proxy::*;
 pub use tun::*;
 
@@ -1,6 +1,11 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::Serialize;
 
+use crate::models::AppProxyRules;
+
+/// Bundle ID 的最大长度
+const MAX_BUNDLE_ID_LEN: usize = 255;
+
 /// macOS Network Extension 状态（占位实现）
 ///
 /// 目标：替代旧的 setuid(root) TUN 方案，通过 Packet Tunnel Provider 等正规方式实现增强模式。
@@ -43,6 +48,39 @@ impl NetworkExtensionManager {
         }
     }
 
+    /// 校验并规范化按应用分流规则（去除空白与重复项）
+    ///
+    /// 同一应用不能同时出现在包含和排除列表中。规则由 NE 的 App Proxy Provider 按应用路由。
+    pub fn normalize_app_rules(included: &[String], excluded: &[String]) -> Result<AppProxyRules> {
+        let normalize = |ids: &[String]| -> Result<Vec<String>> {
+            let mut result: Vec<String> = Vec::new();
+            for id in ids.iter().map(|id| id.trim()) {
+                if !is_valid_bundle_id(id) {
+                    return Err(anyhow!("无效的 Bundle ID: {}", id));
+                }
+                if !result
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(id))
+                {
+                    result.push(id.to_string());
+                }
+            }
+            Ok(result)
+        };
+        let rules = AppProxyRules {
+            included: normalize(included)?,
+            excluded: normalize(excluded)?,
+        };
+        if let Some(id) = rules
+            .included
+            .iter()
+            .find(|id| rules.excluded.iter().any(|e| e.eq_ignore_ascii_case(id)))
+        {
+            return Err(anyhow!("{} 不能同时包含和排除", id));
+        }
+        Ok(rules)
+    }
+
     /// 打开系统设置中“网络扩展”相关面板（尽力而为）
     pub fn open_settings() -> Result<()> {
         #[cfg(target_os = "macos")]
@@ -60,3 +98,42 @@ impl NetworkExtensionManager {
         }
     }
 }
+
+/// Bundle ID 为反向域名格式（如 `com.apple.Safari`），只允许字母、数字、`-` 和 `.`
+fn is_valid_bundle_id(id: &str) -> bool {
+    let segments: Vec<&str> = id.split('.').collect();
+    id.len() <= MAX_BUNDLE_ID_LEN
+        && segments.len() >= 2
+        && segments
+            .iter()
+            .all(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_app_rules() {
+        let rules = NetworkExtensionManager::normalize_app_rules(
+            &[
+                " com.apple.Safari ".to_string(),
+                "com.apple.safari".to_string(),
+            ],
+            &["com.tencent.xinWeChat".to_string()],
+        )
+        .unwrap();
+        assert_eq!(rules.included, vec!["com.apple.Safari"]);
+        assert_eq!(rules.excluded, vec!["com.tencent.xinWeChat"]);
+
+        let invalid = |id: &str| {
+            NetworkExtensionManager::normalize_app_rules(&[id.to_string()], &[]).is_err()
+        };
+        assert!(invalid("Safari"));
+        assert!(invalid("com..apple"));
+        assert!(invalid("com.apple.Safari app"));
+
+        let overlap = ["com.apple.Safari".to_string()];
+        assert!(NetworkExtensionManager::normalize_app_rules(&overlap, &overlap).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::list_network_interfaces;
+
+/// 检查网络状态的间隔
+const NETWORK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 检测到变化后等待网络稳定的时间（切换过程中地址可能多次变化）
+const NETWORK_SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+/// 已断开、暂时改为自动选择的绑定网卡（只影响运行时配置，不修改设置）
+static UNAVAILABLE_INTERFACE: Mutex<Option<String>> = Mutex::new(None);
+
+/// 网络切换事件（`network-changed`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkChangedEvent {
+    /// 切换后可用的网卡
+    pub interfaces: Vec<String>,
+    /// 是否重新应用了系统代理
+    pub proxy_reapplied: bool,
+    /// 因不可用而临时恢复为自动选择的绑定网卡
+    pub interface_reset: Option<String>,
+    /// 重新连接后恢复绑定的网卡
+    pub interface_restored: Option<String>,
+    /// 是否清除了核心 DNS 缓存
+    pub dns_flushed: bool,
+}
+
+/// 当前网络状态：已连接网卡及其地址
+type NetworkSnapshot = BTreeMap<String, Vec<String>>;
+
+/// 获取网络状态快照
+///
+/// 忽略回环网卡、IPv6 链路本地地址和核心 TUN 使用的 198.18.0.0/15 地址，
+/// 避免开关增强模式或地址自动配置被误判为网络切换。
+fn network_snapshot() -> Option<NetworkSnapshot> {
+    let interfaces = match list_network_interfaces() {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            log::debug!("Failed to list network interfaces: {}", e);
+            return None;
+        }
+    };
+
+    let snapshot = interfaces
+        .into_iter()
+        .filter(|iface| !iface.is_loopback && iface.up != Some(false))
+        .filter_map(|iface| {
+            let mut addresses: Vec<String> = iface
+                .addresses
+                .into_iter()
+                .filter(|addr| addr.parse().is_ok_and(|ip| !is_ignored_address(ip)))
+                .collect();
+            addresses.sort();
+            (!addresses.is_empty()).then_some((iface.name, addresses))
+        })
+        .collect();
+    Some(snapshot)
+}
+
+fn is_ignored_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let octets = v4.octets();
+            octets[0] == 198 && (octets[1] & 0xFE) == 18
+        }
+        IpAddr::V6(v6) => (v6.segments()[0] & 0xFFC0) == 0xFE80,
+    }
+}
+
+/// 启动网络切换监听
+///
+/// 定期比较网卡和地址，变化稳定后按设置中的网络切换策略处理，
+/// 并发送 `network-changed` 事件。
+pub fn start_network_monitor(app: AppHandle) {
+    tokio::spawn(async move {
+        let mut last = network_snapshot();
+        loop {
+            tokio::time::sleep(NETWORK_POLL_INTERVAL).await;
+            let current = network_snapshot();
+            if current.is_none() || current == last {
+                continue;
+            }
+
+            tokio::time::sleep(NETWORK_SETTLE_DELAY).await;
+            let Some(settled) = network_snapshot() else {
+                continue;
+            };
+            if Some(&settled) == last.as_ref() {
+                // 短暂抖动后恢复原状
+                continue;
+            }
+
+            log::info!(
+                "Network changed: {:?} -> {:?}",
+                last.as_ref().map(|s| s.keys().collect::<Vec<_>>()),
+                settled.keys().collect::<Vec<_>>()
+            );
+            last = Some(settled.clone());
+            handle_network_change(&app, &settled).await;
+        }
+    });
+}
+
+/// 按设置处理网络切换
+async fn handle_network_change(app: &AppHandle, snapshot: &NetworkSnapshot) {
+    let Some(state) = crate::commands::try_get_app_state() else {
+        return;
+    };
+    let settings = match state.config_manager.load_app_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            log::warn!("Failed to load settings for network change: {}", e);
+            return;
+        }
+    };
+    let policy = settings.network_change_policy;
+
+    let mut event = NetworkChangedEvent {
+        interfaces: snapshot.keys().cloned().collect(),
+        proxy_reapplied: false,
+        interface_reset: None,
+        interface_restored: None,
+        dns_flushed: false,
+    };
+    if !policy.enabled {
+        let _ = app.emit("network-changed", event);
+        return;
+    }
+
+    // 新的网络服务不会继承之前的代理设置（如 macOS 上从 Wi-Fi 切换到有线）
+    if policy.reapply_system_proxy && *state.system_proxy_enabled.lock().await {
+        match crate::commands::system::apply_system_proxy(&settings.mihomo) {
+            Ok(()) => event.proxy_reapplied = true,
+            Err(e) => log::warn!("Failed to reapply system proxy after network change: {}", e),
+        }
+    }
+
+    if policy.redetect_interface {
+        match sync_bound_interface(app, snapshot).await {
+            Some((name, true)) => event.interface_restored = Some(name),
+            Some((name, false)) => event.interface_reset = Some(name),
+            None => {}
+        }
+    }
+
+    if policy.flush_dns && state.mihomo_manager.is_running().await {
+        match state.mihomo_api.flush_dns_cache().await {
+            Ok(()) => event.dns_flushed = true,
+            Err(e) => log::warn!("Failed to flush DNS cache after network change: {}", e),
+        }
+    }
+
+    let _ = app.emit("network-changed", event);
+}
+
+/// 运行时使用的绑定网卡：设置中的网卡已断开时为 None，由核心自动选择
+pub(crate) fn runtime_interface_name(configured: Option<&String>) -> Option<String> {
+    let unavailable = UNAVAILABLE_INTERFACE.lock().ok()?.clone();
+    configured
+        .filter(|name| unavailable.as_ref() != Some(*name))
+        .cloned()
+}
+
+/// 绑定的出站网卡断开时临时改为由核心自动选择，重新连接后恢复绑定
+///
+/// 只修改运行时的 config.yaml，settings.json 中的设置保持不变。
+/// 返回 (网卡名, 是否为恢复)，没有变化时返回 None。
+async fn sync_bound_interface(
+    app: &AppHandle,
+    snapshot: &NetworkSnapshot,
+) -> Option<(String, bool)> {
+    let state = crate::commands::try_get_app_state()?;
+    let bound = state
+        .config_manager
+        .load_app_settings()
+        .ok()?
+        .mihomo
+        .interface_name?;
+    let available = snapshot.contains_key(&bound);
+    let suppressed = UNAVAILABLE_INTERFACE.lock().ok()?.as_ref() == Some(&bound);
+    if available != suppressed {
+        return None;
+    }
+
+    *UNAVAILABLE_INTERFACE.lock().ok()? = (!available).then(|| bound.clone());
+    match apply_runtime_interface(app, available.then(|| bound.clone())).await {
+        Ok(()) => {
+            if available {
+                log::info!("Bound interface '{}' is back, restored binding", bound);
+            } else {
+                log::info!(
+                    "Bound interface '{}' is gone, switched to automatic selection",
+                    bound
+                );
+            }
+            Some((bound, available))
+        }
+        Err(e) => {
+            log::warn!("Failed to update interface-name '{}': {}", bound, e);
+            None
+        }
+    }
+}
+
+/// 修改运行时配置中的绑定网卡并重载
+async fn apply_runtime_interface(app: &AppHandle, name: Option<String>) -> Result<(), String> {
+    use crate::commands::reload::{reload_config, ReloadOptions};
+
+    let state = crate::commands::get_app_state_or_err()?;
+    let mut config = state
+        .config_manager
+        .load_mihomo_config()
+        .map_err(|e| e.to_string())?;
+    config.interface_name = name;
+    state
+        .config_manager
+        .save_mihomo_config(&config)
+        .map_err(|e| e.to_string())?;
+    reload_config(Some(app), &ReloadOptions::default()).await
+}
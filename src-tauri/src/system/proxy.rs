@@ -1,13 +1,137 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::process::Command;
 
+use crate::utils::get_app_data_dir;
+
+/// 原始系统代理配置文件名
+const PREVIOUS_PROXY_FILE: &str = "previous_system_proxy.json";
+
+/// 开启系统代理前的系统代理配置
+///
+/// 开启时保存，关闭时原样恢复，避免覆盖用户原本使用的代理。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousSystemProxy {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub bypass: Vec<String>,
+    /// SOCKS 代理（Windows 的 SOCKS 设置包含在 `server` 中）
+    #[serde(default)]
+    pub socks_enabled: bool,
+    #[serde(default)]
+    pub socks_host: String,
+    #[serde(default)]
+    pub socks_port: u16,
+    /// Windows 原始 `ProxyServer` 值（可能为 `http=a:1;socks=b:2` 按协议设置的格式）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+}
+
+impl PreviousSystemProxy {
+    /// HTTP 代理是否已开启并指向指定地址
+    fn points_to(&self, host: &str, port: u16) -> bool {
+        self.enabled && self.host == host && self.port == port
+    }
+}
+
+fn previous_proxy_path() -> Result<PathBuf> {
+    Ok(get_app_data_dir()?.join(PREVIOUS_PROXY_FILE))
+}
+
 /// 系统代理管理
 pub struct SystemProxy;
 
 impl SystemProxy {
     /// 设置系统 HTTP 代理
-    #[cfg(target_os = "macos")]
     pub fn set_http_proxy(host: &str, port: u16) -> Result<()> {
+        Self::save_previous_proxy(host, port);
+        Self::enable_http_proxy(host, port)
+    }
+
+    /// 设置系统 SOCKS 代理
+    pub fn set_socks_proxy(host: &str, port: u16) -> Result<()> {
+        Self::save_previous_proxy(host, port);
+        Self::enable_socks_proxy(host, port)
+    }
+
+    /// 清除系统代理
+    ///
+    /// 有保存的原始配置时恢复原始配置，否则直接关闭系统代理。
+    pub fn clear_proxy() -> Result<()> {
+        let Some(previous) = Self::get_previous_proxy() else {
+            return Self::disable_proxy();
+        };
+
+        let result = Self::restore_proxy(&previous);
+        if let Ok(path) = previous_proxy_path() {
+            let _ = std::fs::remove_file(path);
+        }
+
+        match result {
+            Ok(()) => {
+                log::info!(
+                    "System proxy restored to previous state (enabled: {}, {}:{})",
+                    previous.enabled,
+                    previous.host,
+                    previous.port
+                );
+                Ok(())
+            }
+            Err(e) => {
+                log::warn!("Failed to restore previous system proxy: {}", e);
+                Self::disable_proxy()
+            }
+        }
+    }
+
+    /// 检查系统代理是否已开启并指向指定地址
+    ///
+    /// 系统代理可能由其他程序开启，只有 HTTP 代理指向本应用端口时才视为已开启。
+    pub fn get_proxy_status(host: &str, port: u16) -> Result<bool> {
+        Ok(Self::read_proxy()?.points_to(host, port))
+    }
+
+    /// 获取开启系统代理前保存的原始配置
+    pub fn get_previous_proxy() -> Option<PreviousSystemProxy> {
+        let content = std::fs::read_to_string(previous_proxy_path().ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 保存当前系统代理配置（已有快照时不覆盖）
+    fn save_previous_proxy(host: &str, port: u16) {
+        if Self::get_previous_proxy().is_some() {
+            return;
+        }
+
+        let current = match Self::read_proxy() {
+            Ok(current) => current,
+            Err(e) => {
+                log::warn!("Failed to read current system proxy: {}", e);
+                return;
+            }
+        };
+
+        // 已经指向本应用（如上次异常退出未清理），不作为原始配置
+        if current.points_to(host, port) {
+            return;
+        }
+
+        let result = previous_proxy_path().and_then(|path| {
+            std::fs::write(path, serde_json::to_string_pretty(&current)?)?;
+            Ok(())
+        });
+        match result {
+            Ok(()) => log::info!("Saved previous system proxy: {:?}", current),
+            Err(e) => log::warn!("Failed to save previous system proxy: {}", e),
+        }
+    }
+
+    // macOS 实现
+    #[cfg(target_os = "macos")]
+    fn enable_http_proxy(host: &str, port: u16) -> Result<()> {
         let services = Self::get_network_services()?;
 
         for service in services {
@@ -36,9 +160,8 @@ impl SystemProxy {
         Ok(())
     }
 
-    /// 设置系统 SOCKS 代理
     #[cfg(target_os = "macos")]
-    pub fn set_socks_proxy(host: &str, port: u16) -> Result<()> {
+    fn enable_socks_proxy(host: &str, port: u16) -> Result<()> {
         let services = Self::get_network_services()?;
 
         for service in services {
@@ -55,9 +178,8 @@ impl SystemProxy {
         Ok(())
     }
 
-    /// 清除系统代理
     #[cfg(target_os = "macos")]
-    pub fn clear_proxy() -> Result<()> {
+    fn disable_proxy() -> Result<()> {
         let services = Self::get_network_services()?;
 
         for service in services {
@@ -99,26 +221,120 @@ impl SystemProxy {
         Ok(services)
     }
 
-    /// 检查系统代理状态
+    /// 读取 `networksetup -getwebproxy` 等命令的输出，返回 (开启, 地址, 端口)
+    #[cfg(target_os = "macos")]
+    fn read_service_proxy(flag: &str, service: &str) -> Result<(bool, String, u16)> {
+        let output = Command::new("networksetup")
+            .args([flag, service])
+            .output()?;
+        let output_str = String::from_utf8_lossy(&output.stdout);
+
+        let mut proxy = (false, String::new(), 0);
+        for line in output_str.lines() {
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                match key.trim() {
+                    "Enabled" => proxy.0 = value == "Yes",
+                    "Server" => proxy.1 = value.to_string(),
+                    "Port" => proxy.2 = value.parse().unwrap_or(0),
+                    _ => {}
+                }
+            }
+        }
+        Ok(proxy)
+    }
+
+    /// 读取当前系统代理配置（以第一个网络服务为准）
+    #[cfg(target_os = "macos")]
+    fn read_proxy() -> Result<PreviousSystemProxy> {
+        let services = Self::get_network_services()?;
+        let Some(service) = services.first() else {
+            return Ok(PreviousSystemProxy::default());
+        };
+
+        let (enabled, host, port) = Self::read_service_proxy("-getwebproxy", service)?;
+        let (socks_enabled, socks_host, socks_port) =
+            Self::read_service_proxy("-getsocksfirewallproxy", service)?;
+        let mut previous = PreviousSystemProxy {
+            enabled,
+            host,
+            port,
+            socks_enabled,
+            socks_host,
+            socks_port,
+            ..Default::default()
+        };
+
+        let output = Command::new("networksetup")
+            .args(["-getproxybypassdomains", service])
+            .output()?;
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        // 没有例外域名时输出 "There aren't any bypass domains set on ..."
+        if !output_str.contains("aren't any") {
+            previous.bypass = output_str
+                .lines()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+
+        Ok(previous)
+    }
+
+    /// 恢复系统代理配置
     #[cfg(target_os = "macos")]
-    pub fn get_proxy_status() -> Result<bool> {
+    fn restore_proxy(previous: &PreviousSystemProxy) -> Result<()> {
         let services = Self::get_network_services()?;
+        let state = if previous.enabled { "on" } else { "off" };
+        let socks_state = if previous.socks_enabled { "on" } else { "off" };
+        let port = previous.port.to_string();
+        let socks_port = previous.socks_port.to_string();
+
+        for service in services {
+            if !previous.host.is_empty() {
+                Command::new("networksetup")
+                    .args(["-setwebproxy", &service, &previous.host, &port])
+                    .output()?;
+                Command::new("networksetup")
+                    .args(["-setsecurewebproxy", &service, &previous.host, &port])
+                    .output()?;
+            }
 
-        if let Some(service) = services.first() {
-            let output = Command::new("networksetup")
-                .args(["-getwebproxy", service])
+            Command::new("networksetup")
+                .args(["-setwebproxystate", &service, state])
+                .output()?;
+            Command::new("networksetup")
+                .args(["-setsecurewebproxystate", &service, state])
+                .output()?;
+            if !previous.socks_host.is_empty() {
+                Command::new("networksetup")
+                    .args([
+                        "-setsocksfirewallproxy",
+                        &service,
+                        &previous.socks_host,
+                        &socks_port,
+                    ])
+                    .output()?;
+            }
+            Command::new("networksetup")
+                .args(["-setsocksfirewallproxystate", &service, socks_state])
                 .output()?;
 
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            Ok(output_str.contains("Enabled: Yes"))
-        } else {
-            Ok(false)
+            let mut args = vec!["-setproxybypassdomains".to_string(), service.clone()];
+            if previous.bypass.is_empty() {
+                args.push("Empty".to_string());
+            } else {
+                args.extend(previous.bypass.iter().cloned());
+            }
+            Command::new("networksetup").args(&args).output()?;
         }
+
+        Ok(())
     }
 
     // Windows 实现
     #[cfg(target_os = "windows")]
-    pub fn set_http_proxy(host: &str, port: u16) -> Result<()> {
+    fn enable_http_proxy(host: &str, port: u16) -> Result<()> {
         use std::os::windows::process::CommandExt;
         const CREATE_NO_WINDOW: u32 = 0x08000000;
 
@@ -159,14 +375,14 @@ impl SystemProxy {
     }
 
     #[cfg(target_os = "windows")]
-    pub fn set_socks_proxy(_host: &str, _port: u16) -> Result<()> {
+    fn enable_socks_proxy(_host: &str, _port: u16) -> Result<()> {
         // Windows 系统代理设置不直接支持 SOCKS，需要通过第三方工具或 PAC 脚本
         log::warn!("SOCKS proxy is not directly supported on Windows system proxy");
         Ok(())
     }
 
     #[cfg(target_os = "windows")]
-    pub fn clear_proxy() -> Result<()> {
+    fn disable_proxy() -> Result<()> {
         use std::os::windows::process::CommandExt;
         const CREATE_NO_WINDOW: u32 = 0x08000000;
 
@@ -189,8 +405,70 @@ impl SystemProxy {
         Ok(())
     }
 
+    /// 读取当前系统代理配置
+    #[cfg(target_os = "windows")]
+    fn read_proxy() -> Result<PreviousSystemProxy> {
+        let enabled = Self::query_internet_setting("ProxyEnable")?;
+        let server = Self::query_internet_setting("ProxyServer")?;
+        let bypass = Self::query_internet_setting("ProxyOverride")?.unwrap_or_default();
+
+        // 按协议分别设置的格式（如 `http=a:1;https=b:2`）只解析 HTTP 部分，
+        // 恢复时使用 `server` 中的原始值
+        let raw = server.as_deref().unwrap_or_default();
+        let http = if raw.contains('=') {
+            raw.split(';')
+                .find_map(|part| part.trim().strip_prefix("http="))
+                .unwrap_or_default()
+        } else {
+            raw
+        };
+        let (host, port) = http
+            .rsplit_once(':')
+            .map(|(host, port)| (host.to_string(), port.parse().unwrap_or(0)))
+            .unwrap_or_default();
+
+        Ok(PreviousSystemProxy {
+            enabled: enabled.is_some_and(|v| v == "0x1"),
+            host,
+            port,
+            server,
+            bypass: bypass
+                .split(';')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// 恢复系统代理配置
+    ///
+    /// 优先写回原始 `ProxyServer` 值，保留按协议设置和 SOCKS 部分；
+    /// 旧版本保存的快照没有原始值，按 host:port 恢复。
+    #[cfg(target_os = "windows")]
+    fn restore_proxy(previous: &PreviousSystemProxy) -> Result<()> {
+        let server = match &previous.server {
+            Some(server) => Some(server.clone()),
+            None if previous.host.is_empty() => None,
+            None if previous.port == 0 => Some(previous.host.clone()),
+            None => Some(format!("{}:{}", previous.host, previous.port)),
+        };
+        match server {
+            Some(server) => Self::set_internet_setting("ProxyServer", "REG_SZ", &server)?,
+            None => Self::delete_internet_setting("ProxyServer")?,
+        }
+        Self::set_internet_setting("ProxyOverride", "REG_SZ", &previous.bypass.join(";"))?;
+        Self::set_internet_setting(
+            "ProxyEnable",
+            "REG_DWORD",
+            if previous.enabled { "1" } else { "0" },
+        )?;
+        Ok(())
+    }
+
+    /// 查询 Internet Settings 注册表项，不存在时返回 None
     #[cfg(target_os = "windows")]
-    pub fn get_proxy_status() -> Result<bool> {
+    fn query_internet_setting(name: &str) -> Result<Option<String>> {
         use std::os::windows::process::CommandExt;
         const CREATE_NO_WINDOW: u32 = 0x08000000;
 
@@ -199,18 +477,68 @@ impl SystemProxy {
                 "query",
                 r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings",
                 "/v",
-                "ProxyEnable",
+                name,
             ])
             .creation_flags(CREATE_NO_WINDOW)
             .output()?;
 
+        // 输出格式：`    ProxyServer    REG_SZ    127.0.0.1:7890`
         let output_str = String::from_utf8_lossy(&output.stdout);
-        Ok(output_str.contains("0x1"))
+        Ok(output_str
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with(name))
+            .find_map(|line| {
+                let (_, rest) = line.split_once("REG_")?;
+                let value = rest.split_once(char::is_whitespace).map_or("", |(_, v)| v);
+                Some(value.trim().to_string())
+            }))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn delete_internet_setting(name: &str) -> Result<()> {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        // 值不存在时 reg delete 返回错误，忽略即可
+        Command::new("reg")
+            .args([
+                "delete",
+                r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings",
+                "/v",
+                name,
+                "/f",
+            ])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()?;
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn set_internet_setting(name: &str, value_type: &str, data: &str) -> Result<()> {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        Command::new("reg")
+            .args([
+                "add",
+                r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings",
+                "/v",
+                name,
+                "/t",
+                value_type,
+                "/d",
+                data,
+                "/f",
+            ])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()?;
+        Ok(())
     }
 
     // Linux 实现
     #[cfg(target_os = "linux")]
-    pub fn set_http_proxy(host: &str, port: u16) -> Result<()> {
+    fn enable_http_proxy(host: &str, port: u16) -> Result<()> {
         let proxy_url = format!("http://{}:{}", host, port);
 
         // 使用 gsettings 设置 GNOME 代理
@@ -249,7 +577,7 @@ impl SystemProxy {
     }
 
     #[cfg(target_os = "linux")]
-    pub fn set_socks_proxy(host: &str, port: u16) -> Result<()> {
+    fn enable_socks_proxy(host: &str, port: u16) -> Result<()> {
         Command::new("gsettings")
             .args(["set", "org.gnome.system.proxy.socks", "host", host])
             .output()?;
@@ -268,7 +596,7 @@ impl SystemProxy {
     }
 
     #[cfg(target_os = "linux")]
-    pub fn clear_proxy() -> Result<()> {
+    fn disable_proxy() -> Result<()> {
         Command::new("gsettings")
             .args(["set", "org.gnome.system.proxy", "mode", "none"])
             .output()?;
@@ -277,13 +605,103 @@ impl SystemProxy {
         Ok(())
     }
 
+    /// 读取当前系统代理配置
+    #[cfg(target_os = "linux")]
+    fn read_proxy() -> Result<PreviousSystemProxy> {
+        let get = |schema: &str, key: &str| -> Result<String> {
+            let output = Command::new("gsettings")
+                .args(["get", schema, key])
+                .output()?;
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+
+        let mode = get("org.gnome.system.proxy", "mode")?;
+        let host = get("org.gnome.system.proxy.http", "host")?;
+        let port = get("org.gnome.system.proxy.http", "port")?;
+        let socks_host = get("org.gnome.system.proxy.socks", "host")?;
+        let socks_port = get("org.gnome.system.proxy.socks", "port")?;
+        // 格式：`['localhost', '127.0.0.0/8']`，为空时为 `@as []`
+        let ignore_hosts = get("org.gnome.system.proxy", "ignore-hosts")?;
+
+        // GNOME 只有一个 mode，手动模式下设置了地址的协议即为开启
+        let socks_host = socks_host.trim_matches('\'').to_string();
+        Ok(PreviousSystemProxy {
+            enabled: mode.contains("manual"),
+            host: host.trim_matches('\'').to_string(),
+            port: port.parse().unwrap_or(0),
+            socks_enabled: mode.contains("manual") && !socks_host.is_empty(),
+            socks_host,
+            socks_port: socks_port.parse().unwrap_or(0),
+            bypass: ignore_hosts
+                .trim_start_matches("@as")
+                .trim()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split(',')
+                .map(|s| s.trim().trim_matches('\'').to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// 恢复系统代理配置
     #[cfg(target_os = "linux")]
-    pub fn get_proxy_status() -> Result<bool> {
-        let output = Command::new("gsettings")
-            .args(["get", "org.gnome.system.proxy", "mode"])
+    fn restore_proxy(previous: &PreviousSystemProxy) -> Result<()> {
+        let port = previous.port.to_string();
+        let ignore_hosts = format!(
+            "[{}]",
+            previous
+                .bypass
+                .iter()
+                .map(|h| format!("'{}'", h))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        for schema in [
+            "org.gnome.system.proxy.http",
+            "org.gnome.system.proxy.https",
+        ] {
+            Command::new("gsettings")
+                .args(["set", schema, "host", &previous.host])
+                .output()?;
+            Command::new("gsettings")
+                .args(["set", schema, "port", &port])
+                .output()?;
+        }
+
+        Command::new("gsettings")
+            .args([
+                "set",
+                "org.gnome.system.proxy.socks",
+                "host",
+                &previous.socks_host,
+            ])
+            .output()?;
+        Command::new("gsettings")
+            .args([
+                "set",
+                "org.gnome.system.proxy.socks",
+                "port",
+                &previous.socks_port.to_string(),
+            ])
             .output()?;
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        Ok(output_str.contains("manual"))
+        Command::new("gsettings")
+            .args([
+                "set",
+                "org.gnome.system.proxy",
+                "ignore-hosts",
+                &ignore_hosts,
+            ])
+            .output()?;
+
+        let mode = if previous.enabled { "manual" } else { "none" };
+        Command::new("gsettings")
+            .args(["set", "org.gnome.system.proxy", "mode", mode])
+            .output()?;
+
+        Ok(())
     }
 }
@@ -4,6 +4,8 @@ use anyhow::Result;
 #[cfg(not(target_os = "windows"))]
 use std::process::Command;
 
+#[cfg(not(target_os = "windows"))]
+use crate::mihomo::LaunchPermissionError;
 #[cfg(target_os = "linux")]
 use crate::utils::get_mihomo_binary_path;
 #[cfg(target_os = "macos")]
@@ -81,7 +83,7 @@ impl TunPermission {
 
             // 用户取消了授权
             if stderr.contains("User canceled") || stderr.contains("-128") {
-                return Err(anyhow!("用户取消了授权"));
+                return Err(LaunchPermissionError("用户取消了授权".to_string()).into());
             }
 
             Err(anyhow!("设置权限失败: {}", stderr))
@@ -140,7 +142,150 @@ impl TunPermission {
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
             log::error!("Failed to setup TUN permission: {}", stderr);
+
+            // pkexec 在用户关闭授权对话框时返回 126
+            if output.status.code() == Some(126) {
+                return Err(LaunchPermissionError("用户取消了授权".to_string()).into());
+            }
+
             Err(anyhow!("设置权限失败: {}", stderr))
         }
     }
 }
+
+/// TUN 网卡
+#[derive(Debug, Clone)]
+pub struct TunInterface {
+    pub name: String,
+    pub address: std::net::Ipv4Addr,
+}
+
+/// TUN 诊断结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunDiagnosisStatus {
+    /// 核心未运行
+    CoreNotRunning,
+    /// 运行时配置中 TUN 未启用
+    TunDisabled,
+    /// 找不到 TUN 网卡
+    InterfaceMissing,
+    /// 网卡存在但系统路由未指向 TUN
+    RoutesMissing,
+    /// 路由存在但测试连接未经过 TUN
+    TrafficNotCaptured,
+    /// 正常工作
+    Working,
+}
+
+/// TUN 诊断报告
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TunDiagnosis {
+    pub status: TunDiagnosisStatus,
+    /// TUN 网卡名称
+    pub interface_name: Option<String>,
+    /// TUN 网卡地址
+    pub interface_address: Option<String>,
+    /// 是否存在指向 TUN 的路由
+    pub routes_found: bool,
+    /// 测试连接是否经过 TUN（未执行测试时为 None）
+    pub test_captured: Option<bool>,
+    /// 诊断说明
+    pub message: String,
+}
+
+impl TunDiagnosis {
+    pub fn new(status: TunDiagnosisStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            interface_name: None,
+            interface_address: None,
+            routes_found: false,
+            test_captured: None,
+            message: message.into(),
+        }
+    }
+}
+
+/// mihomo TUN 默认使用 198.18.0.0/15 网段的地址
+fn is_tun_address(ip: std::net::Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    octets[0] == 198 && (octets[1] & 0xfe) == 18
+}
+
+/// 查找 TUN 网卡
+///
+/// 优先按运行时配置中的 `device` 名称匹配，否则按 TUN 网段地址匹配。
+pub fn find_tun_interface(device: Option<&str>) -> Result<Option<TunInterface>> {
+    use get_if_addrs::{get_if_addrs, IfAddr};
+
+    let interfaces: Vec<TunInterface> = get_if_addrs()?
+        .into_iter()
+        .filter_map(|iface| match iface.addr {
+            IfAddr::V4(v4) => Some(TunInterface {
+                name: iface.name,
+                address: v4.ip,
+            }),
+            IfAddr::V6(_) => None,
+        })
+        .collect();
+
+    if let Some(device) = device.filter(|d| !d.is_empty()) {
+        if let Some(found) = interfaces.iter().find(|i| i.name == device) {
+            return Ok(Some(found.clone()));
+        }
+    }
+
+    Ok(interfaces.into_iter().find(|i| is_tun_address(i.address)))
+}
+
+/// 检查系统路由表中是否存在经过 TUN 网卡的路由（排除网卡自身网段）
+#[cfg(target_os = "macos")]
+pub fn has_tun_routes(iface: &TunInterface) -> Result<bool> {
+    let output = Command::new("netstat")
+        .args(["-rn", "-f", "inet"])
+        .output()?;
+    let output_str = String::from_utf8_lossy(&output.stdout);
+
+    // 格式：Destination  Gateway  Flags  Netif  Expire
+    Ok(output_str.lines().any(|line| {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        parts.len() >= 4 && parts[3] == iface.name && !parts[0].starts_with("198.18")
+    }))
+}
+
+/// 检查系统路由表中是否存在经过 TUN 网卡的路由（排除网卡自身网段）
+#[cfg(target_os = "windows")]
+pub fn has_tun_routes(iface: &TunInterface) -> Result<bool> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = std::process::Command::new("route")
+        .args(["print", "-4"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()?;
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    let address = iface.address.to_string();
+
+    // 格式：Network Destination  Netmask  Gateway  Interface  Metric
+    Ok(output_str.lines().any(|line| {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        parts.len() >= 5 && parts[3] == address && !parts[0].starts_with("198.18")
+    }))
+}
+
+/// 检查系统路由表中是否存在经过 TUN 网卡的默认路由（mihomo 使用独立路由表）
+#[cfg(target_os = "linux")]
+pub fn has_tun_routes(iface: &TunInterface) -> Result<bool> {
+    let output = Command::new("ip")
+        .args(["route", "show", "table", "all"])
+        .output()?;
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    let dev = format!("dev {} ", iface.name);
+
+    Ok(output_str.lines().any(|line| {
+        let line = format!("{} ", line.trim());
+        line.contains(&dev) && (line.starts_with("default") || line.starts_with("0.0.0.0/"))
+    }))
+}
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Mutex;
+
+use super::get_app_data_dir;
+
+/// 保留的最近应用日志条数
+const MAX_APP_LOGS: usize = 1000;
+
+/// 核心 stdout/stderr 输出文件（相对于应用数据目录），每次启动核心时清空
+const CORE_LOG_FILE: &str = "logs/mihomo.log";
+
+/// 读取日志文件末尾时最多读取的字节数
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+
+static APP_LOGS: Lazy<Mutex<VecDeque<AppLogEntry>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// 应用自身的日志条目
+#[derive(Debug, Clone)]
+pub struct AppLogEntry {
+    /// Unix 毫秒时间戳
+    pub time: i64,
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// 输出到终端的同时保留最近的日志，供导出使用
+struct AppLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for AppLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+
+        if let Ok(mut logs) = APP_LOGS.lock() {
+            if logs.len() >= MAX_APP_LOGS {
+                logs.pop_front();
+            }
+            logs.push_back(AppLogEntry {
+                time: chrono::Utc::now().timestamp_millis(),
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// 初始化日志（默认级别 info，可通过 RUST_LOG 环境变量覆盖）
+pub fn init_logger() {
+    let inner =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let max_level = inner.filter();
+    if log::set_boxed_logger(Box::new(AppLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// 最近的应用日志（按时间顺序）
+pub fn recent_app_logs() -> Vec<AppLogEntry> {
+    APP_LOGS
+        .lock()
+        .map(|logs| logs.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn core_log_path() -> Result<PathBuf> {
+    let path = get_app_data_dir()?.join(CORE_LOG_FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(path)
+}
+
+/// 核心进程的 stdout 和 stderr，写入核心日志文件
+///
+/// 不能使用管道：不读取时缓冲区写满会阻塞核心。无法创建日志文件时丢弃输出。
+pub fn core_log_stdio() -> (Stdio, Stdio) {
+    let open = || -> Result<(Stdio, Stdio)> {
+        let file = File::create(core_log_path()?)?;
+        let stderr = file.try_clone()?;
+        Ok((Stdio::from(file), Stdio::from(stderr)))
+    };
+    open().unwrap_or_else(|e| {
+        log::warn!("Failed to open core log file: {}", e);
+        (Stdio::null(), Stdio::null())
+    })
+}
+
+/// 核心日志文件的最后 `lines` 行（由 helper 或服务启动的核心没有日志文件）
+pub fn core_log_tail(lines: usize) -> Vec<String> {
+    let read_tail = || -> Result<String> {
+        let mut file = File::open(core_log_path()?)?;
+        let len = file.metadata()?.len();
+        file.seek(SeekFrom::Start(len.saturating_sub(LOG_TAIL_BYTES)))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    };
+    let Ok(content) = read_tail() else {
+        return Vec::new();
+    };
+    let all: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    // 从中间开始读取时第一行可能不完整
+    let skip = all.len().saturating_sub(lines);
+    all[skip..].iter().map(|l| (*l).to_string()).collect()
+}
@@ -0,0 +1,181 @@
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::get_app_data_dir;
+
+/// 崩溃报告目录（相对于应用数据目录）
+const CRASH_REPORT_DIR: &str = "logs/crash";
+
+/// 最多保留的崩溃报告数量，超出后删除最旧的
+const MAX_CRASH_REPORTS: usize = 10;
+
+/// 替换敏感内容的占位符
+const REDACTED: &str = "<redacted>";
+
+/// URL 只保留协议和主机，路径和参数中常含订阅 token
+static URL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\b([a-z][a-z0-9+.-]*://)([^/\s"'?#]+)[^\s"']*"#).unwrap());
+
+/// `secret: xxx`、`password=xxx`、`Bearer xxx` 之类的键值
+static SECRET_KV_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\b(secret|password|passwd|token|authorization|bearer|uuid)(["']?\s*[:=]?\s*["']?)[^\s"',}]+"#)
+        .unwrap()
+});
+
+/// 崩溃报告（写入前已脱敏）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    /// 生成时间（Unix 毫秒时间戳）
+    pub created_at: i64,
+    /// 出错的命令
+    pub command: String,
+    pub error: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub core_state: String,
+    pub active_profile_id: Option<String>,
+    /// 最近的核心日志和应用日志
+    pub logs: Vec<String>,
+}
+
+/// 崩溃报告列表项
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub created_at: i64,
+    pub command: String,
+    pub error: String,
+}
+
+impl CrashReport {
+    /// 创建报告，`id` 由生成时间决定
+    pub fn new(command: &str, error: &str) -> Self {
+        let now = chrono::Local::now();
+        Self {
+            id: now.format("%Y%m%d-%H%M%S-%3f").to_string(),
+            created_at: now.timestamp_millis(),
+            command: command.to_string(),
+            error: error.to_string(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            core_state: String::new(),
+            active_profile_id: None,
+            logs: Vec::new(),
+        }
+    }
+
+    /// 脱敏：去掉 URL 路径和参数、键值形式的密钥，以及 `secrets` 中的原文
+    pub fn redact(&mut self, secrets: &[String]) {
+        self.error = redact_text(&self.error, secrets);
+        for line in &mut self.logs {
+            *line = redact_text(line, secrets);
+        }
+    }
+}
+
+/// 对一段文本脱敏
+pub fn redact_text(text: &str, secrets: &[String]) -> String {
+    let mut text = text.to_string();
+    for secret in secrets.iter().filter(|s| s.len() >= 4) {
+        text = text.replace(secret.as_str(), REDACTED);
+    }
+    let text = URL_RE.replace_all(&text, format!("${{1}}${{2}}/{}", REDACTED));
+    SECRET_KV_RE
+        .replace_all(&text, format!("${{1}}${{2}}{}", REDACTED))
+        .into_owned()
+}
+
+fn crash_report_dir() -> Result<PathBuf> {
+    let dir = get_app_data_dir()?.join(CRASH_REPORT_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 报告 id 只能由数字和 `-` 组成，防止路径穿越
+fn crash_report_path(id: &str) -> Result<PathBuf> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(anyhow!("无效的崩溃报告 ID: {}", id));
+    }
+    Ok(crash_report_dir()?.join(format!("{}.json", id)))
+}
+
+/// 保存报告，并只保留最近 `MAX_CRASH_REPORTS` 份
+pub fn save_crash_report(report: &CrashReport) -> Result<PathBuf> {
+    let path = crash_report_path(&report.id)?;
+    fs::write(&path, serde_json::to_string_pretty(report)?)?;
+
+    let mut ids = crash_report_ids()?;
+    if ids.len() > MAX_CRASH_REPORTS {
+        ids.sort();
+        for id in &ids[..ids.len() - MAX_CRASH_REPORTS] {
+            let _ = fs::remove_file(crash_report_path(id)?);
+        }
+    }
+
+    log::info!("Crash report saved to {:?}", path);
+    Ok(path)
+}
+
+fn crash_report_ids() -> Result<Vec<String>> {
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(crash_report_dir()?)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                ids.push(stem.to_string());
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// 列出崩溃报告（最新的在前）
+pub fn list_crash_reports() -> Result<Vec<CrashReportSummary>> {
+    let mut reports: Vec<CrashReportSummary> = crash_report_ids()?
+        .iter()
+        .filter_map(|id| read_crash_report(id).ok())
+        .map(|report| CrashReportSummary {
+            id: report.id,
+            created_at: report.created_at,
+            command: report.command,
+            error: report.error,
+        })
+        .collect();
+    reports.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(reports)
+}
+
+/// 读取崩溃报告
+pub fn read_crash_report(id: &str) -> Result<CrashReport> {
+    let content = fs::read_to_string(crash_report_path(id)?)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_text() {
+        let text =
+            "fetch https://sub.example.com/api/v1/client?token=abc123 failed, secret: s3cr3t-key";
+        let redacted = redact_text(text, &["s3cr3t-key".to_string()]);
+        assert!(redacted.contains("https://sub.example.com/<redacted>"));
+        assert!(!redacted.contains("abc123"));
+        assert!(!redacted.contains("s3cr3t-key"));
+
+        let redacted = redact_text("password=hunter2 ok", &[]);
+        assert_eq!(redacted, "password=<redacted> ok");
+
+        assert!(crash_report_path("../settings").is_err());
+    }
+}
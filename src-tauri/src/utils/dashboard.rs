@@ -0,0 +1,126 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+
+use super::get_app_data_dir;
+
+/// 面板默认下载地址（metacubexd 构建产物）
+pub const DEFAULT_DASHBOARD_URL: &str =
+    "https://github.com/MetaCubeX/metacubexd/archive/refs/heads/gh-pages.zip";
+
+/// 面板目录名（相对于核心工作目录，即 `external-ui` 的值）
+pub const DASHBOARD_DIR: &str = "ui";
+
+/// 解压后的最大总大小，防止压缩炸弹
+const DASHBOARD_MAX_UNPACKED_BYTES: u64 = 200 * 1024 * 1024;
+
+fn is_safe_entry_path(p: &Path) -> bool {
+    !p.is_absolute()
+        && !p.components().any(|c| {
+            matches!(
+                c,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        })
+}
+
+/// 校验面板压缩包，返回 `index.html` 所在的目录前缀
+///
+/// GitHub 分支压缩包会多包一层 `<repo>-<branch>/` 目录，解压时需要去掉。
+fn validate_dashboard_archive(zip_bytes: &[u8]) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(zip_bytes))
+        .map_err(|e| anyhow!("面板压缩包无效: {}", e))?;
+
+    let mut total_size = 0u64;
+    let mut index_prefix: Option<String> = None;
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        let name = file.name().to_string();
+        if !is_safe_entry_path(Path::new(&name)) {
+            return Err(anyhow!("面板压缩包包含非法路径: {}", name));
+        }
+        total_size = total_size.saturating_add(file.size());
+        if total_size > DASHBOARD_MAX_UNPACKED_BYTES {
+            return Err(anyhow!("面板压缩包解压后过大"));
+        }
+
+        if let Some(prefix) = name.strip_suffix("index.html") {
+            if !prefix.is_empty() && !prefix.ends_with('/') {
+                continue;
+            }
+            // 取最浅的一层 index.html
+            if index_prefix
+                .as_ref()
+                .map_or(true, |current| prefix.len() < current.len())
+            {
+                index_prefix = Some(prefix.to_string());
+            }
+        }
+    }
+
+    index_prefix.ok_or_else(|| anyhow!("面板压缩包中没有 index.html"))
+}
+
+/// 校验并解压面板到 `<data_dir>/ui`，返回安装目录
+///
+/// 先解压到临时目录，成功后再替换旧版本，避免半安装状态。
+pub fn install_dashboard_archive(zip_bytes: &[u8]) -> Result<PathBuf> {
+    let prefix = validate_dashboard_archive(zip_bytes)?;
+
+    let data_dir = get_app_data_dir()?;
+    let target_dir = data_dir.join(DASHBOARD_DIR);
+    let tmp_dir = data_dir.join(format!("{}_{}", DASHBOARD_DIR, uuid::Uuid::new_v4()));
+    fs::create_dir_all(&tmp_dir)?;
+
+    let extract = || -> Result<()> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(zip_bytes))?;
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let Some(rel) = file
+                .name()
+                .strip_prefix(prefix.as_str())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            if rel.is_empty() || file.is_dir() {
+                continue;
+            }
+
+            let out_path = tmp_dir.join(&rel);
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            fs::write(&out_path, &buf)?;
+        }
+        Ok(())
+    };
+
+    if let Err(e) = extract() {
+        let _ = fs::remove_dir_all(&tmp_dir);
+        return Err(e);
+    }
+
+    if target_dir.exists() {
+        fs::remove_dir_all(&target_dir)?;
+    }
+    fs::rename(&tmp_dir, &target_dir)?;
+
+    log::info!("Dashboard installed to {:?}", target_dir);
+    Ok(target_dir)
+}
+
+/// 面板访问地址（由核心的 external-controller 提供）
+pub fn dashboard_url(external_controller: &str) -> String {
+    let (host, port) = external_controller
+        .rsplit_once(':')
+        .unwrap_or((external_controller, "9090"));
+    let host = match host {
+        "" | "0.0.0.0" | "[::]" | "::" => "127.0.0.1",
+        other => other,
+    };
+    format!("http://{}:{}/{}/", host, port, DASHBOARD_DIR)
+}
@@ -0,0 +1,352 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{get_app_config_dir, get_app_data_dir, get_mihomo_binary_name};
+
+/// 备份清单文件名，恢复时据此识别备份
+const BACKUP_MANIFEST: &str = "conflux-backup.json";
+
+/// 当前备份格式版本
+const BACKUP_VERSION: u32 = 1;
+
+/// 整体替换的数据目录
+const DATA_DIRS: &[&str] = &["profiles", "ruleset"];
+
+/// Sub-Store 数据文件（相对数据目录）
+const SUBSTORE_DATA_FILE: &str = "sub-store/sub-store.json";
+
+/// 体积较大、默认不备份的 GEO 数据库扩展名
+const GEO_EXTENSIONS: &[&str] = &["dat", "mmdb", "metadb"];
+
+/// 备份清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupManifest {
+    version: u32,
+    created_at: String,
+    include_large: bool,
+}
+
+/// 本地备份结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBackupResult {
+    pub path: String,
+    pub files: usize,
+    /// 备份文件大小（字节）
+    pub size: u64,
+}
+
+/// 备份条目的恢复位置
+#[derive(Debug, PartialEq, Eq)]
+enum EntryTarget {
+    /// settings.json，恢复到配置目录
+    Settings,
+    /// 数据目录下的普通数据
+    Data,
+    /// GEO 数据库或核心二进制
+    Large,
+}
+
+/// 根据备份内的路径判断恢复位置，不认识的条目返回 None
+fn classify_entry(name: &str) -> Option<EntryTarget> {
+    if name == "settings.json" {
+        return Some(EntryTarget::Settings);
+    }
+    if name == SUBSTORE_DATA_FILE
+        || DATA_DIRS
+            .iter()
+            .any(|dir| name.strip_prefix(dir).is_some_and(|r| r.starts_with('/')))
+    {
+        return Some(EntryTarget::Data);
+    }
+    let is_geo = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| GEO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+    if !name.contains('/') && (is_geo || name == get_mihomo_binary_name()) {
+        return Some(EntryTarget::Large);
+    }
+    None
+}
+
+fn is_safe_entry_path(path: &Path) -> bool {
+    !path.is_absolute()
+        && !path.components().any(|c| {
+            matches!(
+                c,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        })
+}
+
+/// 递归收集目录下的文件（跳过隐藏文件），返回 (备份内路径, 文件路径)
+fn collect_dir(dir: &Path, prefix: &str, files: &mut Vec<(String, std::path::PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let rel = format!("{}/{}", prefix, name);
+        if path.is_dir() {
+            collect_dir(&path, &rel, files);
+        } else if path.is_file() {
+            files.push((rel, path));
+        }
+    }
+}
+
+/// 将配置、Profile、规则集和 Sub-Store 数据打包为单个 zip
+///
+/// `include_large` 为 true 时同时备份 GEO 数据库和核心二进制。
+pub fn backup_data_dir(target: &Path, include_large: bool) -> Result<DataBackupResult> {
+    let data_dir = get_app_data_dir()?;
+    let mut files = Vec::new();
+
+    let settings_path = get_app_config_dir()?.join("settings.json");
+    if settings_path.is_file() {
+        files.push(("settings.json".to_string(), settings_path));
+    }
+    for dir in DATA_DIRS {
+        collect_dir(&data_dir.join(dir), dir, &mut files);
+    }
+    let substore_path = data_dir.join(SUBSTORE_DATA_FILE);
+    if substore_path.is_file() {
+        files.push((SUBSTORE_DATA_FILE.to_string(), substore_path));
+    }
+    if include_large {
+        for entry in fs::read_dir(&data_dir)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_file() && classify_entry(&name) == Some(EntryTarget::Large) {
+                files.push((name, entry.path()));
+            }
+        }
+    }
+    files.sort();
+
+    // 先写临时文件，完成后再替换，避免留下不完整的备份
+    let tmp_path = target.with_extension("zip.tmp");
+    let mut zip = ZipWriter::new(fs::File::create(&tmp_path)?);
+    let options = FileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(include_large);
+
+    let manifest = BackupManifest {
+        version: BACKUP_VERSION,
+        created_at: chrono::Local::now().to_rfc3339(),
+        include_large,
+    };
+    zip.start_file(BACKUP_MANIFEST, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+    for (name, path) in &files {
+        zip.start_file(name.as_str(), options)?;
+        std::io::copy(&mut fs::File::open(path)?, &mut zip)?;
+    }
+    zip.finish()?;
+    fs::rename(&tmp_path, target)?;
+
+    let size = fs::metadata(target)?.len();
+    log::info!(
+        "Backed up {} files to {:?} ({} bytes)",
+        files.len(),
+        target,
+        size
+    );
+    Ok(DataBackupResult {
+        path: target.to_string_lossy().to_string(),
+        files: files.len(),
+        size,
+    })
+}
+
+/// 校验并恢复 [`backup_data_dir`] 生成的备份，返回恢复的文件数
+///
+/// 先完整解包到临时目录，再整体替换 Profile、规则集和 Sub-Store 数据；
+/// 替换过程中出错时回滚到恢复前的数据。调用前需停止核心和 Sub-Store。
+pub fn restore_data_dir(zip_path: &Path) -> Result<usize> {
+    let mut archive =
+        ZipArchive::new(fs::File::open(zip_path)?).map_err(|e| anyhow!("备份文件已损坏: {}", e))?;
+
+    let manifest: BackupManifest = {
+        let file = archive
+            .by_name(BACKUP_MANIFEST)
+            .map_err(|_| anyhow!("不是 Conflux 数据备份"))?;
+        serde_json::from_reader(file).map_err(|e| anyhow!("备份清单无效: {}", e))?
+    };
+    if manifest.version > BACKUP_VERSION {
+        return Err(anyhow!("备份由更新版本的 Conflux 创建，请升级后再恢复"));
+    }
+    for name in archive.file_names() {
+        if !is_safe_entry_path(Path::new(name)) {
+            return Err(anyhow!("非法备份路径: {}", name));
+        }
+    }
+
+    let data_dir = get_app_data_dir()?;
+    let tmp_dir = data_dir.join(format!("restore-{}", uuid::Uuid::new_v4()));
+    let result = extract_and_apply(&mut archive, &tmp_dir, &data_dir);
+    let _ = fs::remove_dir_all(&tmp_dir);
+
+    let restored = result?;
+    log::info!(
+        "Restored {} files from backup {:?} (created {})",
+        restored,
+        zip_path,
+        manifest.created_at
+    );
+    Ok(restored)
+}
+
+fn extract_and_apply(
+    archive: &mut ZipArchive<fs::File>,
+    tmp_dir: &Path,
+    data_dir: &Path,
+) -> Result<usize> {
+    let mut restored = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() || file.name() == BACKUP_MANIFEST {
+            continue;
+        }
+        let name = file.name().to_string();
+        let Some(target) = classify_entry(&name) else {
+            log::warn!("Skipping unknown backup entry: {}", name);
+            continue;
+        };
+        let out_path = tmp_dir.join(&name);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut file, &mut fs::File::create(&out_path)?)?;
+        restored.push((name, target));
+    }
+
+    // 解包全部成功后才替换本地数据：先把会被覆盖的数据移到一旁，失败时整体移回
+    let config_dir = get_app_config_dir()?;
+    let substore_path = data_dir.join(SUBSTORE_DATA_FILE);
+    let mut replaced: Vec<PathBuf> = DATA_DIRS.iter().map(|dir| data_dir.join(dir)).collect();
+    replaced.push(substore_path.clone());
+    let mut copies = Vec::new();
+    for (name, target) in &restored {
+        let dest = match target {
+            EntryTarget::Settings => config_dir.join(name),
+            EntryTarget::Data if name == SUBSTORE_DATA_FILE => substore_path.clone(),
+            EntryTarget::Data => continue,
+            EntryTarget::Large => data_dir.join(name),
+        };
+        if !replaced.contains(&dest) {
+            replaced.push(dest.clone());
+        }
+        copies.push((name.as_str(), dest));
+    }
+
+    // 恢复前不存在的路径，回滚时直接删除
+    let absent: Vec<PathBuf> = replaced.iter().filter(|p| !p.exists()).cloned().collect();
+    let mut moved_aside = Vec::new();
+    let result = move_aside(&replaced, &mut moved_aside)
+        .and_then(|_| apply_restored(tmp_dir, data_dir, &copies));
+    match result {
+        Ok(()) => {
+            for (_, aside) in moved_aside {
+                let _ = remove_path(&aside);
+            }
+            Ok(restored.len())
+        }
+        Err(e) => {
+            log::warn!("Restore failed, rolling back: {}", e);
+            for path in &absent {
+                let _ = remove_path(path);
+            }
+            for (original, aside) in moved_aside {
+                let _ = remove_path(&original);
+                if let Err(e) = fs::rename(&aside, &original) {
+                    log::error!("Failed to roll back {:?}: {}", original, e);
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 把已存在的路径重命名到同目录下的临时名称，记录 (原路径, 临时路径)
+fn move_aside(paths: &[PathBuf], moved: &mut Vec<(PathBuf, PathBuf)>) -> Result<()> {
+    for path in paths {
+        if !path.exists() {
+            continue;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let aside = path.with_file_name(format!(".{}.restore-old", file_name));
+        let _ = remove_path(&aside);
+        fs::rename(path, &aside)?;
+        moved.push((path.clone(), aside));
+    }
+    Ok(())
+}
+
+/// 将临时目录中的备份内容放到最终位置
+fn apply_restored(tmp_dir: &Path, data_dir: &Path, copies: &[(&str, PathBuf)]) -> Result<()> {
+    for dir in DATA_DIRS {
+        let src = tmp_dir.join(dir);
+        if src.exists() {
+            fs::rename(&src, data_dir.join(dir))?;
+        }
+    }
+
+    for (name, dest) in copies {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(tmp_dir.join(name), dest)?;
+
+        #[cfg(unix)]
+        if *name == get_mihomo_binary_name() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(dest, fs::Permissions::from_mode(0o755))?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_entry() {
+        assert_eq!(classify_entry("settings.json"), Some(EntryTarget::Settings));
+        assert_eq!(
+            classify_entry("profiles/abc/profile.yaml"),
+            Some(EntryTarget::Data)
+        );
+        assert_eq!(
+            classify_entry("sub-store/sub-store.json"),
+            Some(EntryTarget::Data)
+        );
+        assert_eq!(classify_entry("GeoIP.dat"), Some(EntryTarget::Large));
+        assert_eq!(
+            classify_entry(get_mihomo_binary_name()),
+            Some(EntryTarget::Large)
+        );
+        assert_eq!(classify_entry("profiles-old/a.yaml"), None);
+        assert_eq!(classify_entry("config.yaml"), None);
+        assert!(!is_safe_entry_path(Path::new("ruleset/../../etc/passwd")));
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// 一种解析方式的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsLookup {
+    /// `core`（核心 DNS 查询接口）、`proxy`（经代理的 DoH 查询）或 `system`（系统解析器）
+    pub source: &'static str,
+    pub ips: Vec<String>,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+impl DnsLookup {
+    pub fn new(source: &'static str, start: Instant, result: Result<Vec<String>>) -> Self {
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        match result {
+            Ok(ips) => Self {
+                source,
+                ips,
+                elapsed_ms,
+                error: None,
+            },
+            Err(e) => Self {
+                source,
+                ips: Vec::new(),
+                elapsed_ms,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// 核心 DNS 与系统 DNS 的解析结果对比
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsComparison {
+    pub domain: String,
+    pub core: DnsLookup,
+    pub system: DnsLookup,
+    /// 两边都解析成功但没有任何相同地址（可能是 DNS 污染或分区解析）
+    pub mismatch: bool,
+}
+
+impl DnsComparison {
+    pub fn new(domain: String, core: DnsLookup, system: DnsLookup) -> Self {
+        let core_ips: HashSet<&String> = core.ips.iter().collect();
+        let mismatch = !core.ips.is_empty()
+            && !system.ips.is_empty()
+            && !system.ips.iter().any(|ip| core_ips.contains(ip));
+        Self {
+            domain,
+            core,
+            system,
+            mismatch,
+        }
+    }
+}
+
+/// 通过系统解析器查询域名
+pub async fn resolve_with_system(domain: &str, timeout: Duration) -> Result<Vec<String>> {
+    let addrs = tokio::time::timeout(timeout, tokio::net::lookup_host((domain, 0)))
+        .await
+        .map_err(|_| anyhow!("查询超时"))??;
+    let mut ips: Vec<String> = Vec::new();
+    for addr in addrs {
+        let ip = addr.ip().to_string();
+        if !ips.contains(&ip) {
+            ips.push(ip);
+        }
+    }
+    Ok(ips)
+}
+
+/// 解析核心 `/dns/query` 的响应，返回 A/AAAA 记录中的地址
+pub fn parse_core_dns_answer(response: &serde_json::Value) -> Result<Vec<String>> {
+    match response.get("Status").and_then(|s| s.as_u64()).unwrap_or(0) {
+        0 => {}
+        2 => return Err(anyhow!("核心解析失败（SERVFAIL）")),
+        3 => return Err(anyhow!("域名不存在（NXDOMAIN）")),
+        5 => return Err(anyhow!("服务器拒绝查询（REFUSED）")),
+        rcode => return Err(anyhow!("核心返回错误码 {}", rcode)),
+    }
+    Ok(response
+        .get("Answer")
+        .and_then(|a| a.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|answer| matches!(answer.get("type").and_then(|t| t.as_u64()), Some(1 | 28)))
+        .filter_map(|answer| answer.get("data")?.as_str().map(str::to_string))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_core_dns_answer() {
+        let response = serde_json::json!({
+            "Status": 0,
+            "Answer": [
+                { "name": "www.example.com.", "type": 5, "TTL": 60, "data": "example.com." },
+                { "name": "example.com.", "type": 1, "TTL": 60, "data": "93.184.216.34" },
+                { "name": "example.com.", "type": 28, "TTL": 60, "data": "2606:2800::1" }
+            ]
+        });
+        assert_eq!(
+            parse_core_dns_answer(&response).unwrap(),
+            vec!["93.184.216.34", "2606:2800::1"]
+        );
+        assert!(parse_core_dns_answer(&serde_json::json!({ "Status": 3 })).is_err());
+
+        let lookup = |source, ips: &[&str]| DnsLookup {
+            source,
+            ips: ips.iter().map(|&ip| ip.to_string()).collect(),
+            elapsed_ms: 0,
+            error: None,
+        };
+        let compare = |core, system| {
+            DnsComparison::new(
+                "example.com".to_string(),
+                lookup("core", core),
+                lookup("system", system),
+            )
+            .mismatch
+        };
+        assert!(!compare(&["1.1.1.1", "1.0.0.1"], &["1.0.0.1"]));
+        assert!(compare(&["1.1.1.1"], &["10.0.0.1"]));
+        assert!(!compare(&["1.1.1.1"], &[]));
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::{anyhow, Result};
+use reqwest::Url;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, Instant};
+
+/// DNS 记录类型
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+/// DoH 测试结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DohTestResult {
+    /// 解析到的 IPv4/IPv6 地址
+    pub ips: Vec<String>,
+    /// 查询耗时（毫秒，A 与 AAAA 并发查询中较慢的一个）
+    pub elapsed_ms: u64,
+}
+
+/// 校验 DoH 地址（https://host/path，允许 mihomo 的 `#代理` 后缀），返回去掉片段的地址
+pub fn validate_doh_url(url: &str) -> Result<Url> {
+    let mut parsed = Url::parse(url.trim()).map_err(|_| anyhow!("DoH 地址格式不正确: {}", url))?;
+    if parsed.scheme() != "https" || parsed.host_str().is_none() {
+        return Err(anyhow!("DoH 地址必须是 https:// 开头的完整地址"));
+    }
+    parsed.set_fragment(None);
+    Ok(parsed)
+}
+
+/// 直接（不经过核心）向 DoH 服务器查询 `domain` 的 A/AAAA 记录
+pub async fn test_doh_resolver(
+    url: &str,
+    domain: &str,
+    timeout: Duration,
+) -> Result<DohTestResult> {
+    let endpoint = validate_doh_url(url)?;
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .no_proxy()
+        .build()?;
+    resolve(&client, &endpoint, domain).await
+}
+
+/// 经本地代理端口向 DoH 服务器查询 `domain` 的 A/AAAA 记录（请求由核心按规则转发）
+pub async fn resolve_doh_through_proxy(
+    proxy_port: u16,
+    url: &str,
+    domain: &str,
+    timeout: Duration,
+) -> Result<DohTestResult> {
+    let endpoint = validate_doh_url(url)?;
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .proxy(reqwest::Proxy::all(format!(
+            "http://127.0.0.1:{}",
+            proxy_port
+        ))?)
+        .build()?;
+    resolve(&client, &endpoint, domain).await
+}
+
+async fn resolve(client: &reqwest::Client, endpoint: &Url, domain: &str) -> Result<DohTestResult> {
+    let start = Instant::now();
+    let (v4, v6) = tokio::join!(
+        query(client, endpoint, domain, TYPE_A),
+        query(client, endpoint, domain, TYPE_AAAA)
+    );
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+
+    // A 记录查询失败说明服务器不可用；没有 IPv6 记录很常见，AAAA 失败只在 A 也为空时报错
+    let mut ips = v4?;
+    match v6 {
+        Ok(addrs) => ips.extend(addrs),
+        Err(e) if ips.is_empty() => return Err(e),
+        Err(e) => log::debug!("DoH AAAA query for {} failed: {}", domain, e),
+    }
+    if ips.is_empty() {
+        return Err(anyhow!("服务器未返回 {} 的地址记录", domain));
+    }
+
+    Ok(DohTestResult {
+        ips: ips.iter().map(IpAddr::to_string).collect(),
+        elapsed_ms,
+    })
+}
+
+async fn query(
+    client: &reqwest::Client,
+    endpoint: &Url,
+    domain: &str,
+    qtype: u16,
+) -> Result<Vec<IpAddr>> {
+    let response = client
+        .post(endpoint.clone())
+        .header("Content-Type", "application/dns-message")
+        .header("Accept", "application/dns-message")
+        .body(build_query(domain, qtype)?)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                anyhow!("查询超时")
+            } else {
+                anyhow!("请求失败: {}", e)
+            }
+        })?;
+    if !response.status().is_success() {
+        return Err(anyhow!("服务器返回 HTTP {}", response.status()));
+    }
+    parse_answers(&response.bytes().await?)
+}
+
+/// 构造 DNS 查询报文（RFC 8484 建议 ID 为 0）
+fn build_query(domain: &str, qtype: u16) -> Result<Vec<u8>> {
+    // ID=0, RD=1, QDCOUNT=1
+    let mut packet = vec![0, 0, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in domain.trim().trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(anyhow!("域名格式不正确: {}", domain));
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes()); // IN
+    Ok(packet)
+}
+
+/// 跳过报文中的域名（支持压缩指针），返回之后的偏移
+fn skip_name(packet: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(pos)?;
+        match len {
+            0 => return Some(pos + 1),
+            l if l & 0xC0 == 0xC0 => return Some(pos + 2),
+            l => pos += l as usize + 1,
+        }
+    }
+}
+
+/// 解析响应中的 A/AAAA 记录
+fn parse_answers(packet: &[u8]) -> Result<Vec<IpAddr>> {
+    let truncated = || anyhow!("DNS 响应不完整");
+    let read_u16 = |pos: usize| -> Option<u16> {
+        Some(u16::from_be_bytes([
+            *packet.get(pos)?,
+            *packet.get(pos + 1)?,
+        ]))
+    };
+
+    let flags = read_u16(2).ok_or_else(truncated)?;
+    match flags & 0x000F {
+        0 => {}
+        2 => return Err(anyhow!("服务器解析失败（SERVFAIL）")),
+        3 => return Err(anyhow!("域名不存在（NXDOMAIN）")),
+        5 => return Err(anyhow!("服务器拒绝查询（REFUSED）")),
+        rcode => return Err(anyhow!("服务器返回错误码 {}", rcode)),
+    }
+    let qdcount = read_u16(4).ok_or_else(truncated)?;
+    let ancount = read_u16(6).ok_or_else(truncated)?;
+
+    let mut pos = 12;
+    for _ in 0..qdcount {
+        pos = skip_name(packet, pos).ok_or_else(truncated)? + 4;
+    }
+
+    let mut ips = Vec::new();
+    for _ in 0..ancount {
+        pos = skip_name(packet, pos).ok_or_else(truncated)?;
+        let rtype = read_u16(pos).ok_or_else(truncated)?;
+        let rdlength = read_u16(pos + 8).ok_or_else(truncated)? as usize;
+        let rdata = packet
+            .get(pos + 10..pos + 10 + rdlength)
+            .ok_or_else(truncated)?;
+        match (rtype, rdata.len()) {
+            (TYPE_A, 4) => {
+                let octets: [u8; 4] = rdata.try_into()?;
+                ips.push(IpAddr::V4(Ipv4Addr::from(octets)));
+            }
+            (TYPE_AAAA, 16) => {
+                let octets: [u8; 16] = rdata.try_into()?;
+                ips.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            // CNAME 等其他记录
+            _ => {}
+        }
+        pos += 10 + rdlength;
+    }
+    Ok(ips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let mut packet = build_query("example.com", TYPE_A).unwrap();
+        // QR=1, ANCOUNT=2
+        packet[2] = 0x81;
+        packet[3] = 0x80;
+        packet[7] = 2;
+        // CNAME 记录（压缩指针指向问题中的域名）
+        packet.extend_from_slice(&[0xC0, 12, 0, 5, 0, 1, 0, 0, 0, 60, 0, 2, 0xC0, 12]);
+        // A 记录 93.184.216.34
+        packet.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 93, 184, 216, 34]);
+
+        let ips = parse_answers(&packet).unwrap();
+        assert_eq!(ips, vec!["93.184.216.34".parse::<IpAddr>().unwrap()]);
+
+        assert!(parse_answers(&packet[..40]).is_err());
+        packet[3] = 0x83;
+        assert!(parse_answers(&packet).is_err());
+
+        assert!(validate_doh_url("https://1.1.1.1/dns-query#DIRECT").is_ok());
+        assert!(validate_doh_url("tls://1.1.1.1").is_err());
+        assert!(build_query("bad..domain", TYPE_A).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use reqwest::{header, Client, StatusCode};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 单个地址的最大重试次数（每次重试都会从已下载的位置续传）
+const DOWNLOAD_MAX_ATTEMPTS: usize = 3;
+/// 重试间隔
+const DOWNLOAD_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// 下载 `url` 到 `target` 时使用的临时文件（按地址区分，不同镜像的数据不会拼接在一起）
+pub fn part_path_for(target: &Path, url: &str) -> PathBuf {
+    let digest = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.part", &digest[..8]));
+    target.with_file_name(name)
+}
+
+/// 记录临时文件对应的服务端版本（ETag 或 Last-Modified），续传时用于 `If-Range`
+fn validator_path(part_path: &Path) -> PathBuf {
+    let mut path = part_path.as_os_str().to_os_string();
+    path.push(".validator");
+    PathBuf::from(path)
+}
+
+/// 删除临时文件及其版本记录
+pub fn discard_partial(part_path: &Path) {
+    let _ = std::fs::remove_file(part_path);
+    let _ = std::fs::remove_file(validator_path(part_path));
+}
+
+/// 流式下载到 `part_path`，支持 HTTP Range 断点续传
+///
+/// `part_path` 已存在时带 `If-Range` 从其末尾继续下载，服务端文件已变化或不支持 Range 时
+/// 自动从头下载；没有记录服务端版本的临时文件不会续传。
+/// `on_progress(downloaded, total)` 在每次写入后回调。
+/// 返回响应头中的 (ETag, Last-Modified)。
+pub async fn download_resumable<F>(
+    client: &Client,
+    url: &str,
+    part_path: &Path,
+    mut on_progress: F,
+) -> Result<(Option<String>, Option<String>)>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let mut last_error = None;
+
+    for attempt in 1..=DOWNLOAD_MAX_ATTEMPTS {
+        match download_once(client, url, part_path, &mut on_progress).await {
+            Ok(meta) => return Ok(meta),
+            Err(e) => {
+                log::warn!(
+                    "Download attempt {}/{} failed for {}: {}",
+                    attempt,
+                    DOWNLOAD_MAX_ATTEMPTS,
+                    url,
+                    e
+                );
+                last_error = Some(e);
+                if attempt < DOWNLOAD_MAX_ATTEMPTS {
+                    tokio::time::sleep(DOWNLOAD_RETRY_DELAY).await;
+                }
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow!("Download failed: {}", url)))
+}
+
+async fn download_once<F>(
+    client: &Client,
+    url: &str,
+    part_path: &Path,
+    on_progress: &mut F,
+) -> Result<(Option<String>, Option<String>)>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let mut existing = std::fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+    let validator = std::fs::read_to_string(validator_path(part_path)).ok();
+    if existing > 0 && validator.is_none() {
+        // 无法确认服务端文件未变化，不能续传
+        existing = 0;
+    }
+
+    let mut request = client.get(url).header("User-Agent", "Conflux/0.1.0");
+    if let (true, Some(validator)) = (existing > 0, &validator) {
+        request = request
+            .header(header::RANGE, format!("bytes={}-", existing))
+            .header(header::IF_RANGE, validator.trim());
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    let (range_start, range_total) = content_range(response.headers());
+
+    // 416：已下载部分与文件大小一致时视为下载完成，否则丢弃后重新下载
+    if status == StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 {
+        if range_total == Some(existing) {
+            on_progress(existing, Some(existing));
+            return Ok(extract_version_headers(response.headers()));
+        }
+        discard_partial(part_path);
+        return Err(anyhow!(
+            "Partial download does not match remote size ({:?} vs {} bytes)",
+            range_total,
+            existing
+        ));
+    }
+
+    if !status.is_success() {
+        return Err(anyhow!("Request failed with status: {}", status));
+    }
+
+    let resumed = status == StatusCode::PARTIAL_CONTENT && existing > 0;
+    if resumed && range_start != Some(existing) {
+        discard_partial(part_path);
+        return Err(anyhow!(
+            "Server resumed from {:?} instead of {} bytes",
+            range_start,
+            existing
+        ));
+    }
+    let mut downloaded = if resumed { existing } else { 0 };
+    let total = response.content_length().map(|len| len + downloaded);
+    let version = extract_version_headers(response.headers());
+
+    if existing > 0 {
+        if resumed {
+            log::info!("Resuming download of {} from {} bytes", url, existing);
+        } else {
+            log::info!("Server does not support range requests, restarting {}", url);
+        }
+    }
+
+    if !resumed {
+        // 从头下载：记录本次响应的版本，没有可用于 If-Range 的强校验值时不记录
+        let (etag, last_modified) = &version;
+        let strong_etag = etag.as_ref().filter(|e| !e.starts_with("W/"));
+        match strong_etag.or(last_modified.as_ref()) {
+            Some(value) => std::fs::write(validator_path(part_path), value)?,
+            None => {
+                let _ = std::fs::remove_file(validator_path(part_path));
+            }
+        }
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part_path)?;
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+        on_progress(downloaded, total);
+    }
+    file.flush()?;
+
+    if let Some(total) = total {
+        if downloaded != total {
+            return Err(anyhow!(
+                "Incomplete download: {} of {} bytes",
+                downloaded,
+                total
+            ));
+        }
+    }
+
+    Ok(version)
+}
+
+/// 解析 `Content-Range`（`bytes 100-199/200` 或 `bytes */200`），返回 (起始位置, 总大小)
+fn content_range(headers: &header::HeaderMap) -> (Option<u64>, Option<u64>) {
+    let Some(value) = headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return (None, None);
+    };
+    let Some((range, total)) = value
+        .trim()
+        .trim_start_matches("bytes")
+        .trim()
+        .split_once('/')
+    else {
+        return (None, None);
+    };
+    let start = range
+        .split_once('-')
+        .and_then(|(s, _)| s.trim().parse().ok());
+    (start, total.trim().parse().ok())
+}
+
+fn extract_version_headers(headers: &header::HeaderMap) -> (Option<String>, Option<String>) {
+    let get = |name: header::HeaderName| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    (get(header::ETAG), get(header::LAST_MODIFIED))
+}
+
+/// 计算文件的 SHA-256
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 获取发布方提供的校验值（`<url>.sha256sum`），不存在时返回 None
+pub async fn fetch_sha256sum(client: &Client, url: &str) -> Option<String> {
+    let response = client
+        .get(format!("{}.sha256sum", url))
+        .header("User-Agent", "Conflux/0.1.0")
+        .send()
+        .await
+        .ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+
+    // 格式：`<hex>  <filename>` 或仅 `<hex>`
+    let text = response.text().await.ok()?;
+    let digest = text.split_whitespace().next()?.to_ascii_lowercase();
+    (digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())).then_some(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_range_and_part_path() {
+        let range = |value: &str| {
+            let mut headers = header::HeaderMap::new();
+            headers.insert(header::CONTENT_RANGE, value.parse().unwrap());
+            content_range(&headers)
+        };
+        assert_eq!(range("bytes 100-199/200"), (Some(100), Some(200)));
+        assert_eq!(range("bytes */200"), (None, Some(200)));
+        assert_eq!(range("bytes 0-99/*"), (Some(0), None));
+
+        let target = Path::new("/data/geoip.dat");
+        let a = part_path_for(target, "https://github.com/geoip.dat");
+        let b = part_path_for(target, "https://cdn.jsdelivr.net/geoip.dat");
+        assert_ne!(a, b);
+        assert_eq!(a.parent(), target.parent());
+        assert!(a.to_string_lossy().ends_with(".part"));
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Result};
+use reqwest::Url;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::models::ChainLatency;
+
+/// 单次测量的超时时间
+const CHAIN_LATENCY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 响应头最大长度
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// 经本地 HTTP 代理端口请求 `url`，分段统计建连、TLS 握手和首字节耗时
+///
+/// 不使用 reqwest，以便在 CONNECT、TLS 握手和请求之间分别计时。
+/// 返回结果中 `dns_ms` 为空，由调用方单独测量。
+pub async fn measure_through_proxy(proxy_port: u16, url: &str) -> Result<ChainLatency> {
+    tokio::time::timeout(CHAIN_LATENCY_TIMEOUT, measure(proxy_port, url))
+        .await
+        .map_err(|_| anyhow!("测量超时（{} 秒）", CHAIN_LATENCY_TIMEOUT.as_secs()))?
+}
+
+async fn measure(proxy_port: u16, url: &str) -> Result<ChainLatency> {
+    let parsed = Url::parse(url).map_err(|e| anyhow!("无效的 URL: {}", e))?;
+    let https = match parsed.scheme() {
+        "https" => true,
+        "http" => false,
+        scheme => return Err(anyhow!("不支持的协议: {}", scheme)),
+    };
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| anyhow!("URL 缺少主机名"))?
+        .to_string();
+    let port = parsed
+        .port_or_known_default()
+        .ok_or_else(|| anyhow!("URL 缺少端口"))?;
+    let path = match parsed.query() {
+        Some(query) => format!("{}?{}", parsed.path(), query),
+        None => parsed.path().to_string(),
+    };
+
+    // 1. CONNECT：核心解析目标并经代理链路建立连接后才会返回 200
+    let start = Instant::now();
+    let mut stream = TcpStream::connect(("127.0.0.1", proxy_port))
+        .await
+        .map_err(|e| anyhow!("无法连接本地代理端口 {}: {}", proxy_port, e))?;
+    let authority = format!("{}:{}", host, port);
+    stream
+        .write_all(
+            format!(
+                "CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\nProxy-Connection: keep-alive\r\n\r\n"
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let connect_response = read_head(&mut stream).await?;
+    let connect_status = parse_status(&connect_response)?;
+    if connect_status != 200 {
+        return Err(anyhow!("代理 CONNECT 失败，状态码 {}", connect_status));
+    }
+    let connect_ms = elapsed_ms(start);
+
+    // 2. TLS 握手 + 3. 首字节
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: {host}\r\nUser-Agent: Conflux/0.1.0\r\nAccept: */*\r\nConnection: close\r\n\r\n"
+    );
+    let (tls_ms, ttfb_ms, status) = if https {
+        let start = Instant::now();
+        let connector = tokio_native_tls::native_tls::TlsConnector::new()?;
+        let mut tls_stream = tokio_native_tls::TlsConnector::from(connector)
+            .connect(&host, stream)
+            .await
+            .map_err(|e| anyhow!("TLS 握手失败: {}", e))?;
+        let tls_ms = elapsed_ms(start);
+        let (ttfb_ms, status) = request_first_byte(&mut tls_stream, &request).await?;
+        (Some(tls_ms), ttfb_ms, status)
+    } else {
+        let (ttfb_ms, status) = request_first_byte(&mut stream, &request).await?;
+        (None, ttfb_ms, status)
+    };
+
+    Ok(ChainLatency {
+        url: url.to_string(),
+        dns_ms: None,
+        connect_ms,
+        tls_ms,
+        ttfb_ms,
+        total_ms: connect_ms + tls_ms.unwrap_or(0) + ttfb_ms,
+        status,
+    })
+}
+
+/// 发送请求，返回 (首字节耗时, 状态码)
+async fn request_first_byte<S>(stream: &mut S, request: &str) -> Result<(u64, u16)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let start = Instant::now();
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut first = [0u8; 1];
+    if stream.read(&mut first).await? == 0 {
+        return Err(anyhow!("服务器未返回数据即关闭连接"));
+    }
+    let ttfb_ms = elapsed_ms(start);
+
+    let mut head = first.to_vec();
+    head.extend(read_head(stream).await?);
+    Ok((ttfb_ms, parse_status(&head)?))
+}
+
+/// 读取到响应头结束（`\r\n\r\n`）为止
+async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte).await? == 0 {
+            break;
+        }
+        head.push(byte[0]);
+        if head.len() > MAX_HEADER_BYTES {
+            return Err(anyhow!("响应头过长"));
+        }
+    }
+    Ok(head)
+}
+
+/// 解析状态行中的状态码（`HTTP/1.1 200 OK`）
+fn parse_status(head: &[u8]) -> Result<u16> {
+    let text = String::from_utf8_lossy(head);
+    text.lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| anyhow!("无法解析响应状态行"))
+}
+
+fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
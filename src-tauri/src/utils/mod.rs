@@ -1,9 +1,31 @@
+mod app_log;
 mod clipboard;
+mod crash_report;
+mod dashboard;
+mod data_backup;
+mod dns_compare;
+mod doh;
+mod download;
 mod jsdelivr;
+mod latency;
 mod paths;
+mod ports;
+mod safe_mode;
+mod startup_timings;
 mod terminal_proxy;
 
+pub use app_log::*;
 pub use clipboard::*;
+pub use crash_report::*;
+pub use dashboard::*;
+pub use data_backup::*;
+pub use dns_compare::*;
+pub use doh::*;
+pub use download::*;
 pub use jsdelivr::*;
+pub use latency::*;
 pub use paths::*;
+pub use ports::*;
+pub use safe_mode::*;
+pub use startup_timings::*;
 pub use terminal_proxy::*;
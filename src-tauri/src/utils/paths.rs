@@ -1,4 +1,6 @@
 use anyhow::Result;
+use once_cell::sync::OnceCell;
+use std::ffi::OsStr;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
@@ -7,14 +9,72 @@ use std::path::PathBuf;
 // 应用目录路径
 // ============================================================================
 
+/// 覆盖数据目录的环境变量（测试或复现问题时使用独立目录）
+pub const DATA_DIR_ENV: &str = "CONFLUX_DATA_DIR";
+
+/// 记录覆盖目录的文件，位于默认配置目录中，重启后生效
+const DATA_DIR_OVERRIDE_FILE: &str = "data-dir";
+
+/// 启动时读取的覆盖目录（来自 `data-dir` 文件）
+static DATA_DIR_OVERRIDE: OnceCell<Option<PathBuf>> = OnceCell::new();
+
+fn default_config_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Cannot find config directory"))?
+        .join("Conflux"))
+}
+
+/// 数据目录覆盖：`CONFLUX_DATA_DIR` 优先，其次是默认配置目录中的 `data-dir` 文件
+///
+/// 设置后数据和配置（settings.json）都放在该目录下。
+pub fn get_data_dir_override() -> Option<PathBuf> {
+    if let Some(dir) = resolve_data_dir(std::env::var_os(DATA_DIR_ENV).as_deref(), None) {
+        return Some(dir);
+    }
+    DATA_DIR_OVERRIDE
+        .get_or_init(|| {
+            let path = default_config_dir().ok()?.join(DATA_DIR_OVERRIDE_FILE);
+            let content = std::fs::read_to_string(path).ok()?;
+            resolve_data_dir(None, Some(&content))
+        })
+        .clone()
+}
+
+/// 根据环境变量值和 `data-dir` 文件内容决定覆盖目录，空值视为未设置
+fn resolve_data_dir(env: Option<&OsStr>, file_content: Option<&str>) -> Option<PathBuf> {
+    if let Some(dir) = env.filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    let dir = file_content?.trim();
+    (!dir.is_empty()).then(|| PathBuf::from(dir))
+}
+
+/// 设置或清除 `data-dir` 文件中的覆盖目录（重启后生效）
+pub fn set_data_dir_override(dir: Option<&std::path::Path>) -> Result<()> {
+    let config_dir = default_config_dir()?;
+    std::fs::create_dir_all(&config_dir)?;
+    let path = config_dir.join(DATA_DIR_OVERRIDE_FILE);
+    match dir {
+        Some(dir) => std::fs::write(path, dir.to_string_lossy().as_bytes())?,
+        None if path.exists() => std::fs::remove_file(path)?,
+        None => {}
+    }
+    Ok(())
+}
+
 /// 获取应用数据目录
 /// - macOS: ~/Library/Application Support/Conflux
 /// - Windows: C:\Users\<User>\AppData\Roaming\Conflux
 /// - Linux: ~/.local/share/Conflux
+///
+/// 设置了覆盖目录时使用覆盖目录（见 [`get_data_dir_override`]）。
 pub fn get_app_data_dir() -> Result<PathBuf> {
-    let path = dirs::data_dir()
-        .ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?
-        .join("Conflux");
+    let path = match get_data_dir_override() {
+        Some(dir) => dir,
+        None => dirs::data_dir()
+            .ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?
+            .join("Conflux"),
+    };
 
     std::fs::create_dir_all(&path)?;
     Ok(path)
@@ -24,10 +84,13 @@ pub fn get_app_data_dir() -> Result<PathBuf> {
 /// - macOS: ~/Library/Application Support/Conflux
 /// - Windows: C:\Users\<User>\AppData\Roaming\Conflux
 /// - Linux: ~/.config/Conflux
+///
+/// 设置了覆盖目录时与数据目录相同。
 pub fn get_app_config_dir() -> Result<PathBuf> {
-    let path = dirs::config_dir()
-        .ok_or_else(|| anyhow::anyhow!("Cannot find config directory"))?
-        .join("Conflux");
+    let path = match get_data_dir_override() {
+        Some(dir) => dir,
+        None => default_config_dir()?,
+    };
 
     std::fs::create_dir_all(&path)?;
     Ok(path)
@@ -261,7 +324,7 @@ fn get_mihomo_version(path: &PathBuf) -> Option<String> {
 }
 
 /// 比较版本号，返回: >0 表示 a 更新, <0 表示 b 更新, =0 表示相同
-fn compare_versions(a: &str, b: &str) -> i32 {
+pub(crate) fn compare_versions(a: &str, b: &str) -> i32 {
     let parse_version = |v: &str| -> Vec<u32> {
         v.trim_start_matches('v')
             .split(|c: char| c == '.' || c == '-')
@@ -790,6 +853,21 @@ pub fn generate_api_secret() -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_data_dir() {
+        let env = OsStr::new("/tmp/conflux-env");
+        assert_eq!(
+            resolve_data_dir(Some(env), Some("/tmp/conflux-file")),
+            Some(PathBuf::from("/tmp/conflux-env"))
+        );
+        assert_eq!(
+            resolve_data_dir(Some(OsStr::new("")), Some("  /tmp/conflux-file\n")),
+            Some(PathBuf::from("/tmp/conflux-file"))
+        );
+        assert_eq!(resolve_data_dir(None, Some(" \n")), None);
+        assert_eq!(resolve_data_dir(None, None), None);
+    }
+
     #[test]
     fn test_get_mihomo_binary_name() {
         let name = get_mihomo_binary_name();
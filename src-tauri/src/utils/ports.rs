@@ -0,0 +1,203 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::BuildHasher;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, TcpListener, UdpSocket};
+
+use crate::models::MihomoSettings;
+
+/// 端口冲突类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortConflictKind {
+    /// 多个服务配置了同一端口
+    Duplicate,
+    /// 端口已被其他程序占用
+    InUse,
+    /// 无法检测端口（如权限不足、地址不可用）
+    Unavailable,
+}
+
+/// 本地服务监听的端口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenPort {
+    pub name: String,
+    pub port: u16,
+    /// 同时监听 UDP（DNS）
+    pub udp: bool,
+}
+
+/// 端口冲突
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConflict {
+    pub port: u16,
+    pub kind: PortConflictKind,
+    /// 使用该端口的服务
+    pub services: Vec<String>,
+    pub message: String,
+}
+
+/// 从地址字符串（如 `127.0.0.1:9191`、`0.0.0.0:1053`）中解析端口
+fn parse_listen_port(addr: &str) -> Option<u16> {
+    addr.rsplit_once(':')?.1.parse().ok()
+}
+
+/// 收集本地服务监听的端口（端口为 0 或未配置表示不监听）
+pub fn collect_listen_ports(settings: &MihomoSettings, substore_port: u16) -> Vec<ListenPort> {
+    let candidates = [
+        ("HTTP 代理端口", settings.port, false),
+        ("SOCKS5 代理端口", settings.socks_port, false),
+        ("混合代理端口", settings.mixed_port, false),
+        (
+            "外部控制器端口",
+            parse_listen_port(&settings.external_controller),
+            false,
+        ),
+        (
+            "HTTPS 外部控制器端口",
+            settings
+                .external_controller_tls
+                .as_deref()
+                .and_then(parse_listen_port),
+            false,
+        ),
+        (
+            "DNS 监听端口",
+            settings.dns.listen.as_deref().and_then(parse_listen_port),
+            true,
+        ),
+        ("Sub-Store 端口", Some(substore_port), false),
+    ];
+
+    candidates
+        .into_iter()
+        .filter_map(|(name, port, udp)| {
+            port.filter(|p| *p != 0).map(|port| ListenPort {
+                name: name.to_string(),
+                port,
+                udp,
+            })
+        })
+        .collect()
+}
+
+/// 检查端口是否已被占用（同时尝试回环地址和通配地址）
+///
+/// 只有 `AddrInUse` 视为被占用，其他绑定错误原样返回，由调用方报告。
+/// `udp` 为 true 时同时检查 UDP 端口。
+pub fn is_port_in_use(port: u16, udp: bool) -> std::io::Result<bool> {
+    for ip in [Ipv4Addr::LOCALHOST, Ipv4Addr::UNSPECIFIED] {
+        let tcp = TcpListener::bind((ip, port)).map(drop);
+        let udp = if udp {
+            UdpSocket::bind((ip, port)).map(drop)
+        } else {
+            Ok(())
+        };
+        match tcp.and(udp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::AddrInUse => return Ok(true),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(false)
+}
+
+/// 检查端口冲突
+///
+/// `owned_ports` 为本应用正在监听的端口（核心、Sub-Store 运行中），不视为被占用。
+pub fn check_port_conflicts<S: BuildHasher>(
+    ports: &[ListenPort],
+    owned_ports: &HashSet<u16, S>,
+) -> Vec<PortConflict> {
+    let mut by_port: BTreeMap<u16, (Vec<String>, bool)> = BTreeMap::new();
+    for listen in ports {
+        let entry = by_port.entry(listen.port).or_default();
+        entry.0.push(listen.name.clone());
+        entry.1 |= listen.udp;
+    }
+
+    let mut conflicts = Vec::new();
+    for (port, (services, udp)) in by_port {
+        if services.len() > 1 {
+            conflicts.push(PortConflict {
+                port,
+                kind: PortConflictKind::Duplicate,
+                message: format!("端口 {} 同时被 {} 使用", port, services.join("、")),
+                services,
+            });
+            continue;
+        }
+        if owned_ports.contains(&port) {
+            continue;
+        }
+        match is_port_in_use(port, udp) {
+            Ok(false) => {}
+            Ok(true) => conflicts.push(PortConflict {
+                port,
+                kind: PortConflictKind::InUse,
+                message: format!("{} {} 已被其他程序占用", services[0], port),
+                services,
+            }),
+            Err(e) => conflicts.push(PortConflict {
+                port,
+                kind: PortConflictKind::Unavailable,
+                message: format!("{} {} 无法使用：{}", services[0], port, e),
+                services,
+            }),
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listen_port(name: &str, port: u16, udp: bool) -> ListenPort {
+        ListenPort {
+            name: name.to_string(),
+            port,
+            udp,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_ports_detected() {
+        let ports = vec![
+            listen_port("HTTP 代理端口", 7890, false),
+            listen_port("混合代理端口", 7890, false),
+        ];
+        let owned: HashSet<u16> = [7890].into_iter().collect();
+        let conflicts = check_port_conflicts(&ports, &owned);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, PortConflictKind::Duplicate);
+        assert_eq!(conflicts[0].services.len(), 2);
+    }
+
+    #[test]
+    fn test_bound_port_detected() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let ports = vec![listen_port("HTTP 代理端口", port, false)];
+
+        let conflicts = check_port_conflicts(&ports, &HashSet::new());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, PortConflictKind::InUse);
+
+        let owned: HashSet<u16> = [port].into_iter().collect();
+        assert!(check_port_conflicts(&ports, &owned).is_empty());
+    }
+
+    #[test]
+    fn test_bound_udp_port_detected() {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = socket.local_addr().unwrap().port();
+
+        let dns = vec![listen_port("DNS 监听端口", port, true)];
+        let conflicts = check_port_conflicts(&dns, &HashSet::new());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, PortConflictKind::InUse);
+    }
+}
@@ -0,0 +1,90 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::get_app_data_dir;
+
+/// 强制以安全模式启动的环境变量（值为 `1` 时生效）
+pub const SAFE_MODE_ENV: &str = "CONFLUX_SAFE_MODE";
+
+/// 核心连续启动失败多少次后，下次启动应用时进入安全模式
+pub const LAUNCH_FAILURE_THRESHOLD: u32 = 3;
+
+/// 记录连续启动失败次数的标记文件
+const LAUNCH_FAILURES_FILE: &str = "launch_failures";
+
+fn launch_failures_path() -> Option<PathBuf> {
+    get_app_data_dir()
+        .ok()
+        .map(|dir| dir.join(LAUNCH_FAILURES_FILE))
+}
+
+/// 当前连续启动失败次数
+pub fn launch_failures() -> u32 {
+    launch_failures_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| content.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn write_launch_failures(count: u32) {
+    if count == 0 {
+        clear_launch_failures();
+        return;
+    }
+    if let Some(path) = launch_failures_path() {
+        if let Err(e) = std::fs::write(path, count.to_string()) {
+            log::warn!("Failed to record launch attempt: {}", e);
+        }
+    }
+}
+
+/// 核心启动前记录一次启动尝试，启动成功后由 `clear_launch_failures` 清零
+///
+/// 若核心在启动时崩溃（甚至导致应用退出），计数不会被清除，
+/// 连续达到阈值后下次启动进入安全模式。
+pub fn record_launch_attempt() {
+    write_launch_failures(launch_failures().saturating_add(1));
+}
+
+/// 撤销一次启动尝试（因缺少权限等非核心原因未能启动时不计入失败）
+pub fn forget_launch_attempt() {
+    write_launch_failures(launch_failures().saturating_sub(1));
+}
+
+/// 核心成功启动，清除失败计数
+pub fn clear_launch_failures() {
+    if let Some(path) = launch_failures_path() {
+        if path.exists() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 进入安全模式的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SafeModeReason {
+    /// 通过 `CONFLUX_SAFE_MODE=1` 手动要求
+    Requested,
+    /// 核心连续多次启动失败
+    LaunchFailures,
+}
+
+/// 本次启动是否应进入安全模式（不自动启动核心、不应用上次的 Profile），返回原因
+pub fn should_enter_safe_mode() -> Option<SafeModeReason> {
+    if std::env::var(SAFE_MODE_ENV).is_ok_and(|value| value == "1") {
+        log::warn!("Safe mode requested via {}", SAFE_MODE_ENV);
+        return Some(SafeModeReason::Requested);
+    }
+
+    let failures = launch_failures();
+    if failures >= LAUNCH_FAILURE_THRESHOLD {
+        log::warn!(
+            "Core failed to start {} consecutive times, entering safe mode",
+            failures
+        );
+        return Some(SafeModeReason::LaunchFailures);
+    }
+
+    None
+}
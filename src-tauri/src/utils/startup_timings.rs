@@ -0,0 +1,61 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static STARTUP_TIMINGS: Lazy<Mutex<StartupTimings>> =
+    Lazy::new(|| Mutex::new(StartupTimings::default()));
+
+/// 启动阶段耗时
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupPhase {
+    /// `geodata`、`config`、`secret`、`core-status` 或 `substore`
+    pub name: &'static str,
+    pub duration_ms: u64,
+}
+
+/// 本次启动各阶段的耗时
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupTimings {
+    /// 初始化应用状态的总耗时（不含后台启动的 Sub-Store），完成前为 None
+    pub init_ms: Option<u64>,
+    pub phases: Vec<StartupPhase>,
+}
+
+/// 记录一个启动阶段的耗时
+pub fn record_startup_phase(name: &'static str, duration: Duration) {
+    if let Ok(mut timings) = STARTUP_TIMINGS.lock() {
+        timings.phases.push(StartupPhase {
+            name,
+            duration_ms: duration.as_millis() as u64,
+        });
+    }
+}
+
+/// 记录初始化总耗时，并输出各阶段耗时摘要
+pub fn finish_startup_timings(total: Duration) {
+    let Ok(mut timings) = STARTUP_TIMINGS.lock() else {
+        return;
+    };
+    timings.init_ms = Some(total.as_millis() as u64);
+    let summary: Vec<String> = timings
+        .phases
+        .iter()
+        .map(|p| format!("{}={}ms", p.name, p.duration_ms))
+        .collect();
+    log::info!(
+        "Startup took {}ms ({})",
+        total.as_millis(),
+        summary.join(", ")
+    );
+}
+
+/// 本次启动的耗时记录
+pub fn startup_timings() -> StartupTimings {
+    STARTUP_TIMINGS
+        .lock()
+        .map(|timings| timings.clone())
+        .unwrap_or_default()
+}
@@ -1,13 +1,28 @@
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use futures_util::StreamExt;
 use reqwest::{header, Client, StatusCode};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::time::{sleep, Duration};
 
 const RETRY_MAX_ATTEMPTS: usize = 5;
 const RETRY_BASE_DELAY_MS: u64 = 250;
 const RETRY_MAX_DELAY_MS: u64 = 4000;
 
+/// 压缩载荷的魔数头（后接 gzip 数据）
+const COMPRESSED_MAGIC: &[u8] = b"CFXGZ\x01";
+/// zip 文件头，已压缩的快照不再重复压缩
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+/// 超过该大小的载荷才尝试压缩
+const COMPRESS_THRESHOLD_BYTES: usize = 4 * 1024;
+/// 可取消上传时每次发送的块大小
+const UPLOAD_CHUNK_BYTES: usize = 256 * 1024;
+
 /// WebDAV 文件信息
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +39,8 @@ pub struct WebDavClient {
     client: Client,
     base_url: String,
     auth_header: String,
+    /// 已确认存在的目录（仅在当前实例生命周期内有效）
+    ensured_dirs: Mutex<HashSet<String>>,
 }
 
 impl WebDavClient {
@@ -44,6 +61,7 @@ impl WebDavClient {
             client,
             base_url,
             auth_header,
+            ensured_dirs: Mutex::new(HashSet::new()),
         })
     }
 
@@ -68,6 +86,8 @@ impl WebDavClient {
     }
 
     /// 确保目录存在（递归创建）
+    ///
+    /// 已确认存在的目录会被缓存，同一实例内重复调用不再发起 PROPFIND/MKCOL
     pub async fn ensure_dir(&self, path: &str) -> Result<()> {
         log::debug!("确保目录存在: {}", path);
         let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
@@ -78,6 +98,9 @@ impl WebDavClient {
                 continue;
             }
             current_path = format!("{}/{}", current_path, part);
+            if self.is_dir_ensured(&current_path) {
+                continue;
+            }
             let url = format!("{}{}/", self.base_url, current_path);
 
             // 先检查目录是否存在
@@ -115,16 +138,64 @@ impl WebDavClient {
                         mkcol_status
                     ));
                 }
+            } else if !status.is_success() {
+                // 认证失败等情况不缓存，交给后续请求报错
+                continue;
+            }
+
+            if let Ok(mut ensured) = self.ensured_dirs.lock() {
+                ensured.insert(current_path.clone());
             }
         }
 
         Ok(())
     }
 
-    /// 上传文件
+    fn is_dir_ensured(&self, path: &str) -> bool {
+        self.ensured_dirs
+            .lock()
+            .map(|ensured| ensured.contains(path))
+            .unwrap_or(false)
+    }
+
+    /// 上传文件（原样上传，不压缩）
+    ///
+    /// 用于元信息、同步锁等旧版客户端也需要读取的小文件。
     pub async fn upload_file(&self, remote_path: &str, content: &[u8]) -> Result<()> {
         log::debug!("上传文件: {} ({} bytes)", remote_path, content.len());
+        self.put_payload(remote_path, content, None)
+            .await
+            .map(|_| ())
+    }
+
+    /// 上传数据文件，按块发送并在每块之前检查取消标记
+    ///
+    /// 超过阈值的内容会以 gzip 压缩后上传（带魔数头），下载时自动解压；
+    /// zip 等已压缩的内容原样上传。
+    /// 返回 `false` 表示已取消：请求被中断，远端文件可能残缺，调用方应上传到临时路径。
+    pub async fn upload_file_cancellable(
+        &self,
+        remote_path: &str,
+        content: &[u8],
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<bool> {
+        let payload = compress_payload(content)?;
+        log::debug!(
+            "上传文件: {} ({} bytes, 传输 {} bytes)",
+            remote_path,
+            content.len(),
+            payload.len()
+        );
+        self.put_payload(remote_path, &payload, cancel).await
+    }
 
+    /// PUT 上传载荷，失败时按退避重试；返回 `false` 表示已取消
+    async fn put_payload(
+        &self,
+        remote_path: &str,
+        payload: &[u8],
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<bool> {
         // 确保父目录存在
         if let Some(parent) = std::path::Path::new(remote_path).parent() {
             if let Some(parent_str) = parent.to_str() {
@@ -138,12 +209,16 @@ impl WebDavClient {
         let url = format!("{}{}", self.base_url, remote_path);
         log::debug!("PUT {}", url);
         for attempt in 1..=RETRY_MAX_ATTEMPTS {
+            if is_cancelled(cancel.as_ref()) {
+                return Ok(false);
+            }
             let response = self
                 .client
                 .put(&url)
                 .header(header::AUTHORIZATION, &self.auth_header)
                 .header(header::CONTENT_TYPE, "application/octet-stream")
-                .body(content.to_vec())
+                .header(header::CONTENT_LENGTH, payload.len())
+                .body(chunked_body(payload, cancel.clone()))
                 .send()
                 .await;
 
@@ -160,7 +235,7 @@ impl WebDavClient {
                     match status {
                         StatusCode::OK | StatusCode::CREATED | StatusCode::NO_CONTENT => {
                             log::debug!("上传成功: {}", remote_path);
-                            return Ok(());
+                            return Ok(true);
                         }
                         StatusCode::UNAUTHORIZED => return Err(anyhow!("认证失败")),
                         _ if should_retry_status(status) && attempt < RETRY_MAX_ATTEMPTS => {
@@ -175,6 +250,10 @@ impl WebDavClient {
                         }
                     }
                 }
+                Err(_) if is_cancelled(cancel.as_ref()) => {
+                    log::info!("上传已取消: {}", remote_path);
+                    return Ok(false);
+                }
                 Err(e) => {
                     log::warn!(
                         "上传请求失败: {} (attempt {}/{})",
@@ -209,7 +288,7 @@ impl WebDavClient {
                 Ok(resp) => {
                     let status = resp.status();
                     match status {
-                        StatusCode::OK => return Ok(resp.bytes().await?.to_vec()),
+                        StatusCode::OK => return decompress_payload(&resp.bytes().await?),
                         StatusCode::NOT_FOUND => {
                             return Err(anyhow!("文件不存在：{}", remote_path))
                         }
@@ -241,6 +320,53 @@ impl WebDavClient {
         Err(anyhow!("下载失败：超过最大重试次数"))
     }
 
+    /// 复制远端文件（覆盖目标），源文件不存在时返回 `false`
+    pub async fn copy_file(&self, from: &str, to: &str) -> Result<bool> {
+        self.transfer_file("COPY", from, to).await
+    }
+
+    /// 移动远端文件（覆盖目标），源文件不存在时返回 `false`
+    pub async fn move_file(&self, from: &str, to: &str) -> Result<bool> {
+        self.transfer_file("MOVE", from, to).await
+    }
+
+    async fn transfer_file(&self, method: &str, from: &str, to: &str) -> Result<bool> {
+        let url = format!("{}{}", self.base_url, from);
+        let response = self
+            .client
+            .request(reqwest::Method::from_bytes(method.as_bytes())?, &url)
+            .header(header::AUTHORIZATION, &self.auth_header)
+            .header("Destination", format!("{}{}", self.base_url, to))
+            .header("Overwrite", "T")
+            .send()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            StatusCode::UNAUTHORIZED => Err(anyhow!("认证失败")),
+            status => Err(anyhow!("{} {} 失败：HTTP {}", method, from, status)),
+        }
+    }
+
+    /// 删除文件（文件不存在视为成功）
+    pub async fn delete_file(&self, remote_path: &str) -> Result<()> {
+        let url = format!("{}{}", self.base_url, remote_path);
+        let response = self
+            .client
+            .delete(&url)
+            .header(header::AUTHORIZATION, &self.auth_header)
+            .send()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::NOT_FOUND => Ok(()),
+            StatusCode::UNAUTHORIZED => Err(anyhow!("认证失败")),
+            status => Err(anyhow!("删除失败：HTTP {}", status)),
+        }
+    }
+
     /// 获取文件信息（Last-Modified, ETag 等）
     #[allow(dead_code)]
     pub async fn get_file_info(&self, remote_path: &str) -> Result<Option<WebDavFileInfo>> {
@@ -314,6 +440,59 @@ impl WebDavClient {
     }
 }
 
+/// 压缩载荷：超过阈值且压缩后更小时，返回 `魔数头 + gzip 数据`，否则原样返回
+fn compress_payload(content: &[u8]) -> Result<Vec<u8>> {
+    if content.len() < COMPRESS_THRESHOLD_BYTES || content.starts_with(ZIP_MAGIC) {
+        return Ok(content.to_vec());
+    }
+
+    let mut encoder = GzEncoder::new(COMPRESSED_MAGIC.to_vec(), Compression::default());
+    encoder.write_all(content)?;
+    let compressed = encoder.finish()?;
+
+    // 压缩没有收益时保持原样
+    if compressed.len() >= content.len() {
+        return Ok(content.to_vec());
+    }
+
+    Ok(compressed)
+}
+
+/// 解压载荷：没有魔数头的视为旧版未压缩文件，原样返回
+fn decompress_payload(payload: &[u8]) -> Result<Vec<u8>> {
+    let Some(compressed) = payload.strip_prefix(COMPRESSED_MAGIC) else {
+        return Ok(payload.to_vec());
+    };
+
+    let mut content = Vec::new();
+    GzDecoder::new(compressed)
+        .read_to_end(&mut content)
+        .map_err(|e| anyhow!("解压远端文件失败：{}", e))?;
+    Ok(content)
+}
+
+fn is_cancelled(cancel: Option<&Arc<AtomicBool>>) -> bool {
+    cancel.is_some_and(|cancel| cancel.load(Ordering::SeqCst))
+}
+
+/// 把载荷切成块作为请求体，取消标记置位后以错误结束，中断请求
+fn chunked_body(payload: &[u8], cancel: Option<Arc<AtomicBool>>) -> reqwest::Body {
+    let chunks: Vec<Vec<u8>> = payload
+        .chunks(UPLOAD_CHUNK_BYTES)
+        .map(<[u8]>::to_vec)
+        .collect();
+    let stream = futures_util::stream::iter(chunks).map(move |chunk| {
+        if is_cancelled(cancel.as_ref()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "上传已取消",
+            ));
+        }
+        Ok(chunk)
+    });
+    reqwest::Body::wrap_stream(stream)
+}
+
 fn should_retry_status(status: StatusCode) -> bool {
     matches!(
         status,
@@ -351,4 +530,27 @@ mod tests {
             Some("Mon, 01 Jan 2024 00:00:00 GMT".to_string())
         );
     }
+
+    #[test]
+    fn test_payload_compression_roundtrip() {
+        let content = "proxies: []\n".repeat(1024).into_bytes();
+        let payload = compress_payload(&content).unwrap();
+        assert!(payload.starts_with(COMPRESSED_MAGIC));
+        assert!(payload.len() < content.len());
+        assert_eq!(decompress_payload(&payload).unwrap(), content);
+    }
+
+    #[test]
+    fn test_small_or_legacy_payload_kept_plain() {
+        let content = br#"{"version":1}"#.to_vec();
+        assert_eq!(compress_payload(&content).unwrap(), content);
+        assert_eq!(decompress_payload(&content).unwrap(), content);
+    }
+
+    #[test]
+    fn test_zip_payload_not_compressed() {
+        let mut content = ZIP_MAGIC.to_vec();
+        content.resize(COMPRESS_THRESHOLD_BYTES * 4, 0);
+        assert_eq!(compress_payload(&content).unwrap(), content);
+    }
 }
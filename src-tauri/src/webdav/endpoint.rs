@@ -0,0 +1,179 @@
+use anyhow::{anyhow, Result};
+use reqwest::{Client, Method, StatusCode, Url};
+use serde::Serialize;
+use std::time::Duration;
+
+/// 单次探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 网页端路径标记，出现时截取之前的部分作为站点根路径
+const WEB_UI_MARKERS: &[&str] = &["/index.php/", "/apps/files", "/#/", "/@manage"];
+
+/// 探测到的可用 WebDAV 地址
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebDavEndpoint {
+    /// 可用的 WebDAV 地址（无末尾斜杠）
+    pub url: String,
+    /// 识别到的服务商，无法识别时为空
+    pub provider: Option<String>,
+    /// 是否与用户输入的地址不同
+    pub corrected: bool,
+}
+
+/// 探测并修正 WebDAV 地址
+///
+/// 依次对用户输入和常见 WebDAV 路径发送 PROPFIND，返回第一个可用的地址。
+/// 只认 207 Multi-Status：网页、登录页等普通 HTTP 服务对 PROPFIND 也可能返回 200。
+pub async fn normalize_webdav_url(
+    input: &str,
+    username: &str,
+    password: &str,
+) -> Result<WebDavEndpoint> {
+    let candidates = candidate_urls(input, username)?;
+    let client = Client::builder().timeout(PROBE_TIMEOUT).build()?;
+    let original = input.trim().trim_end_matches('/');
+
+    let mut auth_failed = false;
+    for (index, candidate) in candidates.iter().enumerate() {
+        let response = client
+            .request(
+                Method::from_bytes(b"PROPFIND").unwrap(),
+                format!("{}/", candidate),
+            )
+            .basic_auth(username, Some(password))
+            .header("Depth", "0")
+            .send()
+            .await;
+
+        let status = match response {
+            Ok(resp) => resp.status(),
+            // 首个地址连不上说明主机不可达，其余路径不必再试
+            Err(e) if index == 0 && (e.is_connect() || e.is_timeout()) => {
+                return Err(anyhow!("无法连接服务器: {}", e));
+            }
+            Err(e) => {
+                log::debug!("WebDAV probe {} failed: {}", candidate, e);
+                continue;
+            }
+        };
+        log::debug!("WebDAV probe {}: HTTP {}", candidate, status);
+
+        match status {
+            StatusCode::MULTI_STATUS => {
+                return Ok(WebDavEndpoint {
+                    url: candidate.clone(),
+                    provider: detect_provider(candidate).map(str::to_string),
+                    corrected: candidate != original,
+                });
+            }
+            StatusCode::UNAUTHORIZED => auth_failed = true,
+            _ => {}
+        }
+    }
+
+    if auth_failed {
+        Err(anyhow!("认证失败：用户名或密码错误"))
+    } else {
+        Err(anyhow!("未找到可用的 WebDAV 地址，请检查服务器地址"))
+    }
+}
+
+/// 待探测的地址列表，用户输入（补全协议、去掉末尾斜杠）排在首位
+fn candidate_urls(input: &str, username: &str) -> Result<Vec<String>> {
+    let input = input.trim();
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("https://{}", input)
+    };
+    let url = Url::parse(&with_scheme).map_err(|_| anyhow!("服务器地址格式不正确"))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(anyhow!("服务器地址格式不正确"));
+    }
+
+    let origin = url.origin().ascii_serialization();
+    // 带 # 的网页地址（如 Alist 前端路由）片段不在 path 中
+    let full_path = match url.fragment() {
+        Some(fragment) => format!("{}#{}", url.path(), fragment),
+        None => url.path().to_string(),
+    };
+    let path = url.path().trim_end_matches('/');
+    let root = WEB_UI_MARKERS
+        .iter()
+        .filter_map(|marker| full_path.find(marker))
+        .min()
+        .map(|pos| full_path[..pos].trim_end_matches('/'))
+        .unwrap_or(path);
+
+    let mut candidates = vec![format!("{}{}", origin, path)];
+    let host = url.host_str().unwrap_or_default();
+    let known = match host {
+        h if h.ends_with("jianguoyun.com") => Some("/dav"),
+        h if h.ends_with("koofr.net") => Some("/dav/Koofr"),
+        h if h.ends_with("teracloud.jp") || h.ends_with("infini-cloud.net") => Some("/dav"),
+        _ => None,
+    };
+    if let Some(suffix) = known {
+        candidates.push(format!("{}{}", origin, suffix));
+    }
+
+    let user = urlencoding::encode(username);
+    for suffix in [
+        format!("/remote.php/dav/files/{}", user),
+        "/remote.php/webdav".to_string(),
+        "/dav".to_string(),
+        "/webdav".to_string(),
+    ] {
+        candidates.push(format!("{}{}{}", origin, root, suffix));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    candidates.retain(|c| seen.insert(c.clone()));
+    Ok(candidates)
+}
+
+/// 根据地址识别服务商
+fn detect_provider(url: &str) -> Option<&'static str> {
+    let host = Url::parse(url).ok()?.host_str()?.to_string();
+    if host.ends_with("jianguoyun.com") {
+        Some("坚果云")
+    } else if host.ends_with("koofr.net") {
+        Some("Koofr")
+    } else if host.ends_with("teracloud.jp") || host.ends_with("infini-cloud.net") {
+        Some("InfiniCLOUD")
+    } else if url.contains("/remote.php/") {
+        Some("Nextcloud / ownCloud")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_urls() {
+        let candidates = candidate_urls("dav.jianguoyun.com/", "me@example.com").unwrap();
+        assert_eq!(candidates[0], "https://dav.jianguoyun.com");
+        assert_eq!(candidates[1], "https://dav.jianguoyun.com/dav");
+
+        let candidates =
+            candidate_urls("https://cloud.example.com/index.php/apps/files/", "alice").unwrap();
+        assert_eq!(
+            candidates[0],
+            "https://cloud.example.com/index.php/apps/files"
+        );
+        assert_eq!(
+            candidates[1],
+            "https://cloud.example.com/remote.php/dav/files/alice"
+        );
+        assert_eq!(
+            detect_provider(&candidates[1]),
+            Some("Nextcloud / ownCloud")
+        );
+
+        assert!(candidate_urls("ftp://example.com", "a").is_err());
+    }
+}
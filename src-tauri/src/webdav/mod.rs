@@ -1,5 +1,7 @@
 mod client;
+mod endpoint;
 mod sync;
 
 pub use client::WebDavClient;
-pub use sync::{ConflictInfo, SyncManager, SyncResult, SyncState};
+pub use endpoint::{normalize_webdav_url, WebDavEndpoint};
+pub use sync::{ConflictInfo, SyncManager, SyncPreview, SyncResult, SyncState, UploadProgress};
@@ -3,8 +3,11 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
+use std::future::Future;
 use std::io::{Cursor, Read, Write};
 use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use super::client::WebDavClient;
 use crate::models::{AppSettings, WebDavConfig};
@@ -22,9 +25,30 @@ const SNAPSHOT_FILE: &str = "snapshot.zip";
 /// 远端快照元信息文件名
 const SNAPSHOT_META_FILE: &str = "snapshot.json";
 
+/// 快照元信息格式版本
+///
+/// 2：数据文件可能带 gzip 压缩信封（快照 zip 和元信息本身始终原样存储，1 版客户端仍可读取）。
+/// 远端版本高于此值时拒绝同步，避免旧客户端误读新格式。
+const SNAPSHOT_META_VERSION: u32 = 2;
+
+/// 上传中的快照临时文件名，上传完成后移动为正式快照
+const SNAPSHOT_UPLOADING_FILE: &str = "snapshot.zip.uploading";
+
+/// 强制覆盖前保留的上一份远端快照后缀
+const SNAPSHOT_BACKUP_SUFFIX: &str = ".bak";
+
 /// 本地同步状态里用于存储“快照”的 key
 const SNAPSHOT_STATE_KEY: &str = "__snapshot__";
 
+/// 远端同步锁文件名
+const LOCK_FILE: &str = ".lock";
+
+/// 本机设备 ID 文件名（用于标识同步锁的持有者）
+const DEVICE_ID_FILE: &str = "device_id";
+
+/// 同步锁过期时间（秒），超过后视为持有者异常退出，可直接覆盖
+const LOCK_STALE_SECS: i64 = 10 * 60;
+
 // ============================================================================
 // 数据结构定义（保持与前端 types/config.ts 一致）
 // ============================================================================
@@ -94,6 +118,39 @@ pub struct SyncResult {
     pub conflict_info: Option<ConflictInfo>,
 }
 
+/// 上传进度
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadProgress {
+    /// 阶段：pack（打包本地文件）、upload（上传远端文件）
+    pub stage: String,
+    /// 当前处理的文件（相对路径或远端文件名）
+    pub file: String,
+    pub current: usize,
+    pub total: usize,
+}
+
+/// 同步预览（只比较不传输）
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPreview {
+    /// 同步方向：none / upload / download / conflict
+    pub action: String,
+    pub message: String,
+    /// 上传后远端新增或修改的文件
+    pub to_upload: Vec<String>,
+    /// 下载后本地新增或修改的文件
+    pub to_download: Vec<String>,
+    /// 下载时会被删除的本地文件
+    pub to_delete_local: Vec<String>,
+    /// 上传时会从远端快照中移除的文件
+    pub to_delete_remote: Vec<String>,
+    pub has_conflict: bool,
+    pub conflict_info: Option<ConflictInfo>,
+}
+
+type ProgressCallback = Box<dyn Fn(&UploadProgress) + Send + Sync>;
+
 /// 本地文件信息（用于构建快照）
 #[derive(Debug, Clone)]
 struct LocalFileInfo {
@@ -111,17 +168,80 @@ struct SnapshotMeta {
     file_count: usize,
 }
 
+/// 远端同步锁（尽力而为的建议锁，防止多设备同时同步）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncLock {
+    device_id: String,
+    /// 每次加锁随机生成，释放时用于确认锁仍归自己所有
+    lock_id: String,
+    created_at: String,
+}
+
+impl SyncLock {
+    fn is_stale(&self) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.created_at)
+            .map(|created| {
+                chrono::Local::now()
+                    .signed_duration_since(created)
+                    .num_seconds()
+                    > LOCK_STALE_SECS
+            })
+            .unwrap_or(true)
+    }
+}
+
 // ============================================================================
 // 同步管理器实现（单包快照协议）
 // ============================================================================
 
 pub struct SyncManager {
     config: WebDavConfig,
+    progress: Option<ProgressCallback>,
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 impl SyncManager {
     pub fn new(config: WebDavConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            progress: None,
+            cancel: None,
+        }
+    }
+
+    /// 上传快照时报告每个文件的进度
+    #[must_use]
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&UploadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// 上传快照时检查取消标记（快照替换远端之后不再响应取消）
+    #[must_use]
+    pub fn with_cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    fn report_progress(&self, stage: &str, file: &str, current: usize, total: usize) {
+        if let Some(callback) = &self.progress {
+            callback(&UploadProgress {
+                stage: stage.to_string(),
+                file: file.to_string(),
+                current,
+                total,
+            });
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::SeqCst))
     }
 
     fn create_client(&self) -> Result<WebDavClient> {
@@ -162,21 +282,53 @@ impl SyncManager {
 
     /// 计算本地文件集合的“清单 hash”（与打包格式无关）
     fn compute_manifest_hash(local_files: &HashMap<String, LocalFileInfo>) -> String {
-        let mut keys: Vec<&String> = local_files.keys().collect();
+        Self::hash_manifest(&Self::file_hashes(local_files))
+    }
+
+    fn file_hashes(local_files: &HashMap<String, LocalFileInfo>) -> HashMap<String, String> {
+        local_files
+            .iter()
+            .map(|(path, info)| (path.clone(), info.hash.clone()))
+            .collect()
+    }
+
+    /// 按相对路径排序后对 `路径 + hash` 计算清单 hash
+    fn hash_manifest(file_hashes: &HashMap<String, String>) -> String {
+        let mut keys: Vec<&String> = file_hashes.keys().collect();
         keys.sort();
 
         let mut hasher = Sha256::new();
         for k in keys {
-            if let Some(info) = local_files.get(k) {
+            if let Some(hash) = file_hashes.get(k) {
                 hasher.update(k.as_bytes());
                 hasher.update(b"\n");
-                hasher.update(info.hash.as_bytes());
+                hasher.update(hash.as_bytes());
                 hasher.update(b"\n");
             }
         }
         format!("{:x}", hasher.finalize())
     }
 
+    /// 比较两份文件清单：返回 `source` 中新增或内容不同的文件，以及只存在于 `target` 的文件
+    fn diff_files(
+        source: &HashMap<String, String>,
+        target: &HashMap<String, String>,
+    ) -> (Vec<String>, Vec<String>) {
+        let mut changed: Vec<String> = source
+            .iter()
+            .filter(|(path, hash)| target.get(*path) != Some(*hash))
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut removed: Vec<String> = target
+            .keys()
+            .filter(|path| !source.contains_key(*path))
+            .cloned()
+            .collect();
+        changed.sort();
+        removed.sort();
+        (changed, removed)
+    }
+
     pub fn get_sync_status() -> Result<SyncState> {
         Self::load_sync_state()
     }
@@ -190,6 +342,102 @@ impl SyncManager {
         Ok(())
     }
 
+    /// 获取本机设备 ID（首次调用时生成并持久化）
+    fn get_device_id() -> Result<String> {
+        let path = get_app_data_dir()?.join(DEVICE_ID_FILE);
+        if let Ok(id) = fs::read_to_string(&path) {
+            let id = id.trim();
+            if !id.is_empty() {
+                return Ok(id.to_string());
+            }
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        fs::write(&path, &id)?;
+        Ok(id)
+    }
+
+    async fn fetch_remote_lock(&self, client: &WebDavClient) -> Result<Option<SyncLock>> {
+        let remote_lock_path = format!("{}/{}", REMOTE_BASE_PATH, LOCK_FILE);
+        match client.download_file(&remote_lock_path).await {
+            // 无法解析的锁文件视为过期锁
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes).ok()),
+            Err(e) => {
+                if e.to_string().contains("HTTP 404") || e.to_string().contains("文件不存在") {
+                    return Ok(None);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// 获取远端同步锁
+    ///
+    /// 其他设备持有未过期的锁时返回错误；写入后回读确认，降低两端同时加锁的概率。
+    async fn acquire_lock(&self, client: &WebDavClient) -> Result<SyncLock> {
+        let device_id = Self::get_device_id()?;
+
+        if let Some(existing) = self.fetch_remote_lock(client).await? {
+            if existing.device_id != device_id && !existing.is_stale() {
+                return Err(anyhow!(
+                    "其他设备正在同步（开始于 {}），请稍后再试",
+                    existing.created_at
+                ));
+            }
+            if existing.device_id != device_id {
+                log::warn!(
+                    "覆盖过期的同步锁: device={}, created_at={}",
+                    existing.device_id,
+                    existing.created_at
+                );
+            }
+        }
+
+        let lock = SyncLock {
+            device_id,
+            lock_id: uuid::Uuid::new_v4().to_string(),
+            created_at: chrono::Local::now().to_rfc3339(),
+        };
+        let remote_lock_path = format!("{}/{}", REMOTE_BASE_PATH, LOCK_FILE);
+        client
+            .upload_file(&remote_lock_path, &serde_json::to_vec_pretty(&lock)?)
+            .await?;
+
+        match self.fetch_remote_lock(client).await? {
+            Some(current) if current.lock_id == lock.lock_id => Ok(lock),
+            _ => Err(anyhow!("其他设备正在同步，请稍后再试")),
+        }
+    }
+
+    /// 释放远端同步锁（仅删除自己持有的锁，失败只记录日志）
+    async fn release_lock(&self, client: &WebDavClient, lock: &SyncLock) {
+        match self.fetch_remote_lock(client).await {
+            Ok(Some(current)) if current.lock_id == lock.lock_id => {
+                let remote_lock_path = format!("{}/{}", REMOTE_BASE_PATH, LOCK_FILE);
+                if let Err(e) = client.delete_file(&remote_lock_path).await {
+                    log::warn!("释放同步锁失败: {}", e);
+                }
+            }
+            Ok(_) => log::warn!("同步锁已被其他设备接管，跳过释放"),
+            Err(e) => log::warn!("读取同步锁失败: {}", e),
+        }
+    }
+
+    /// 在持有远端同步锁期间执行操作，无论成功与否都会释放锁
+    async fn with_lock<T, F, Fut>(&self, op: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let client = self.create_client()?;
+        client.ensure_dir(REMOTE_BASE_PATH).await?;
+
+        let lock = self.acquire_lock(&client).await?;
+        let result = op().await;
+        self.release_lock(&client, &lock).await;
+        result
+    }
+
     /// 扫描本地文件并计算 hash（用于快照）
     fn scan_local_files(&self) -> Result<HashMap<String, LocalFileInfo>> {
         let mut files = HashMap::new();
@@ -294,7 +542,12 @@ impl SyncManager {
         Ok(files)
     }
 
-    fn build_snapshot_zip(&self, local_files: &HashMap<String, LocalFileInfo>) -> Result<Vec<u8>> {
+    /// 打包快照；取消时返回 None
+    fn build_snapshot_zip(
+        &self,
+        local_files: &HashMap<String, LocalFileInfo>,
+        total_steps: usize,
+    ) -> Result<Option<Vec<u8>>> {
         use zip::write::FileOptions;
         use zip::CompressionMethod;
         use zip::ZipWriter;
@@ -308,18 +561,22 @@ impl SyncManager {
             let mut keys: Vec<&String> = local_files.keys().collect();
             keys.sort();
 
-            for rel in keys {
+            for (index, rel) in keys.into_iter().enumerate() {
+                if self.is_cancelled() {
+                    return Ok(None);
+                }
                 let Some(info) = local_files.get(rel) else {
                     continue;
                 };
                 let content = fs::read(&info.full_path)?;
                 zip.start_file(rel, options)?;
                 zip.write_all(&content)?;
+                self.report_progress("pack", rel, index + 1, total_steps);
             }
 
             zip.finish()?;
         }
-        Ok(buf)
+        Ok(Some(buf))
     }
 
     fn is_safe_zip_entry_path(p: &Path) -> bool {
@@ -428,6 +685,12 @@ impl SyncManager {
         match client.download_file(&remote_meta_path).await {
             Ok(bytes) => {
                 let meta: SnapshotMeta = serde_json::from_slice(&bytes)?;
+                if meta.version > SNAPSHOT_META_VERSION {
+                    return Err(anyhow!(
+                        "远端快照由更新版本的 Conflux 创建（格式版本 {}），请升级后再同步",
+                        meta.version
+                    ));
+                }
                 Ok(Some(meta))
             }
             Err(e) => {
@@ -440,7 +703,46 @@ impl SyncManager {
         }
     }
 
-    async fn upload_snapshot(&self) -> Result<SyncResult> {
+    /// 下载远端快照并校验与元信息一致，返回压缩包和其中每个文件的 hash
+    ///
+    /// 快照损坏或与元信息不符时直接报错，避免用不完整的远端数据覆盖本地。
+    async fn fetch_remote_snapshot(
+        &self,
+        client: &WebDavClient,
+        meta: &SnapshotMeta,
+    ) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        use zip::ZipArchive;
+
+        let remote_snapshot_path = format!("{}/{}", REMOTE_BASE_PATH, SNAPSHOT_FILE);
+        let zip_bytes = client.download_file(&remote_snapshot_path).await?;
+
+        let mut archive = ZipArchive::new(Cursor::new(zip_bytes.as_slice()))
+            .map_err(|e| anyhow!("远端快照已损坏，已中止同步: {}", e))?;
+        let mut files = HashMap::new();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            if file.is_dir() {
+                continue;
+            }
+            let name = file.name().to_string();
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            files.insert(name, Self::compute_hash(&buf));
+        }
+
+        if files.len() != meta.file_count || Self::hash_manifest(&files) != meta.snapshot_hash {
+            return Err(anyhow!(
+                "远端快照与元信息不一致（{} 个文件，元信息记录 {} 个），已中止同步",
+                files.len(),
+                meta.file_count
+            ));
+        }
+
+        Ok((zip_bytes, files))
+    }
+
+    /// 上传快照；`backup_previous` 为 true 时先把远端已有快照和元信息复制为 `.bak`
+    async fn upload_snapshot(&self, backup_previous: bool) -> Result<SyncResult> {
         let client = self.create_client()?;
         client.ensure_dir(REMOTE_BASE_PATH).await?;
 
@@ -458,22 +760,58 @@ impl SyncManager {
             });
         }
 
+        // 本地文件逐个打包，最后上传快照和元信息两个远端文件
+        let total_steps = local_files.len() + 2;
         let snapshot_hash = Self::compute_manifest_hash(&local_files);
-        let zip_bytes = self.build_snapshot_zip(&local_files)?;
+        let Some(zip_bytes) = self.build_snapshot_zip(&local_files, total_steps)? else {
+            return Ok(Self::make_cancelled_result());
+        };
+
+        // 先上传到临时文件，上传过程中可以取消；取消或失败时正式快照保持不变
+        let remote_uploading_path = format!("{}/{}", REMOTE_BASE_PATH, SNAPSHOT_UPLOADING_FILE);
+        self.report_progress("upload", SNAPSHOT_FILE, local_files.len() + 1, total_steps);
+        let uploaded = client
+            .upload_file_cancellable(&remote_uploading_path, &zip_bytes, self.cancel.clone())
+            .await;
+        if !matches!(uploaded, Ok(true)) {
+            if let Err(e) = client.delete_file(&remote_uploading_path).await {
+                log::warn!("Failed to remove partial snapshot upload: {}", e);
+            }
+            return uploaded.map(|_| Self::make_cancelled_result());
+        }
+
+        // 取消只在替换远端快照之前生效：快照一旦替换就必须写入元信息和同步状态，
+        // 否则远端快照与元信息、本地同步状态会不一致
+        if self.is_cancelled() {
+            let _ = client.delete_file(&remote_uploading_path).await;
+            return Ok(Self::make_cancelled_result());
+        }
 
         let remote_snapshot_path = format!("{}/{}", REMOTE_BASE_PATH, SNAPSHOT_FILE);
-        client
-            .upload_file(&remote_snapshot_path, zip_bytes.as_slice())
-            .await?;
+        let remote_meta_path = format!("{}/{}", REMOTE_BASE_PATH, SNAPSHOT_META_FILE);
+        if backup_previous {
+            for path in [&remote_snapshot_path, &remote_meta_path] {
+                let backup_path = format!("{}{}", path, SNAPSHOT_BACKUP_SUFFIX);
+                if client.copy_file(path, &backup_path).await? {
+                    log::info!("Backed up remote {} to {}", path, backup_path);
+                }
+            }
+        }
+        if !client
+            .move_file(&remote_uploading_path, &remote_snapshot_path)
+            .await?
+        {
+            return Err(anyhow!("上传的快照在远端不存在，无法替换"));
+        }
 
         let meta = SnapshotMeta {
-            version: 1,
+            version: SNAPSHOT_META_VERSION,
             updated_at: chrono::Local::now().to_rfc3339(),
             snapshot_hash: snapshot_hash.clone(),
             file_count: local_files.len(),
         };
         let meta_bytes = serde_json::to_vec_pretty(&meta)?;
-        let remote_meta_path = format!("{}/{}", REMOTE_BASE_PATH, SNAPSHOT_META_FILE);
+        self.report_progress("upload", SNAPSHOT_META_FILE, total_steps, total_steps);
         client
             .upload_file(&remote_meta_path, meta_bytes.as_slice())
             .await?;
@@ -534,8 +872,7 @@ impl SyncManager {
             }
         }
 
-        let remote_snapshot_path = format!("{}/{}", REMOTE_BASE_PATH, SNAPSHOT_FILE);
-        let zip_bytes = client.download_file(&remote_snapshot_path).await?;
+        let (zip_bytes, _) = self.fetch_remote_snapshot(&client, &remote_meta).await?;
 
         // 保留当前本地 WebDAV 配置（写回 settings 时合并）
         let current_webdav_config = self.config.clone();
@@ -568,6 +905,19 @@ impl SyncManager {
         })
     }
 
+    fn make_cancelled_result() -> SyncResult {
+        SyncResult {
+            success: false,
+            message: "上传已取消，远端快照未修改".to_string(),
+            uploaded_files: vec![],
+            downloaded_files: vec![],
+            deleted_local_files: vec![],
+            deleted_remote_files: vec![],
+            has_conflict: false,
+            conflict_info: None,
+        }
+    }
+
     fn make_conflict_result(message: &str) -> SyncResult {
         let now = chrono::Local::now().to_rfc3339();
         let item = ConflictItem {
@@ -595,6 +945,10 @@ impl SyncManager {
 
     /// 增量同步（新语义：基于快照 hash/时间戳 的方向选择）
     pub async fn sync(&self) -> Result<SyncResult> {
+        self.with_lock(|| self.sync_snapshot()).await
+    }
+
+    async fn sync_snapshot(&self) -> Result<SyncResult> {
         let client = self.create_client()?;
         client.ensure_dir(REMOTE_BASE_PATH).await?;
 
@@ -623,7 +977,7 @@ impl SyncManager {
                     conflict_info: None,
                 });
             }
-            return self.upload_snapshot().await;
+            return self.upload_snapshot(false).await;
         };
 
         let remote_hash = remote_meta.snapshot_hash.clone();
@@ -643,7 +997,7 @@ impl SyncManager {
         }
 
         if local_changed && !remote_changed {
-            return self.upload_snapshot().await;
+            return self.upload_snapshot(false).await;
         }
 
         if !local_changed && remote_changed {
@@ -680,14 +1034,131 @@ impl SyncManager {
         Ok(Self::make_conflict_result("检测到快照冲突，请选择处理方式"))
     }
 
-    /// 强制上传（全量覆盖远端快照）
+    /// 预览同步：与 `sync` 相同的方向判断，但不加锁、不传输、不修改同步状态
+    ///
+    /// 需要比较文件时会下载远端快照（只读），远端快照损坏时与真实同步一样中止。
+    pub async fn preview(&self) -> Result<SyncPreview> {
+        let client = self.create_client()?;
+
+        let base_state = Self::load_sync_state()?;
+        let base_hash = base_state
+            .files
+            .get(SNAPSHOT_STATE_KEY)
+            .map(|s| s.local_hash.clone());
+
+        let local_files = Self::file_hashes(&self.scan_local_files()?);
+        let local_current_hash = Self::hash_manifest(&local_files);
+        let local_changed = base_hash.as_deref() != Some(local_current_hash.as_str());
+
+        let up_to_date = SyncPreview {
+            action: "none".to_string(),
+            message: "已是最新，无需同步".to_string(),
+            ..Default::default()
+        };
+
+        let Some(remote_meta) = self.fetch_remote_meta(&client).await? else {
+            if local_files.is_empty() {
+                return Ok(up_to_date);
+            }
+            let (to_upload, _) = Self::diff_files(&local_files, &HashMap::new());
+            return Ok(SyncPreview {
+                action: "upload".to_string(),
+                message: format!("远端没有快照，将上传 {} 个文件", to_upload.len()),
+                to_upload,
+                ..Default::default()
+            });
+        };
+
+        let remote_changed = base_hash.as_deref() != Some(remote_meta.snapshot_hash.as_str());
+        if (!local_changed && !remote_changed) || local_current_hash == remote_meta.snapshot_hash {
+            return Ok(up_to_date);
+        }
+
+        let (_, remote_files) = self.fetch_remote_snapshot(&client, &remote_meta).await?;
+        let (to_upload, to_delete_remote) = Self::diff_files(&local_files, &remote_files);
+        let (to_download, mut to_delete_local) = Self::diff_files(&remote_files, &local_files);
+        // settings.json 下载时合并而不是删除
+        to_delete_local.retain(|path| path != "settings.json");
+
+        if local_changed && !remote_changed {
+            return Ok(SyncPreview {
+                action: "upload".to_string(),
+                message: format!(
+                    "将上传 {} 个文件，从远端快照移除 {} 个文件",
+                    to_upload.len(),
+                    to_delete_remote.len()
+                ),
+                to_upload,
+                to_delete_remote,
+                ..Default::default()
+            });
+        }
+
+        if !local_changed && remote_changed {
+            return Ok(SyncPreview {
+                action: "download".to_string(),
+                message: format!(
+                    "将下载 {} 个文件，删除本地 {} 个文件",
+                    to_download.len(),
+                    to_delete_local.len()
+                ),
+                to_download,
+                to_delete_local,
+                ..Default::default()
+            });
+        }
+
+        let mut conflict_paths: Vec<String> = to_upload.clone();
+        conflict_paths.extend(to_delete_remote.iter().cloned());
+        conflict_paths.sort();
+        let conflict_items = conflict_paths
+            .iter()
+            .map(|path| ConflictItem {
+                path: path.clone(),
+                conflict_type: "内容不同".to_string(),
+                local_status: if local_files.contains_key(path) {
+                    "已修改"
+                } else {
+                    "不存在"
+                }
+                .to_string(),
+                remote_status: if remote_files.contains_key(path) {
+                    "已修改"
+                } else {
+                    "不存在"
+                }
+                .to_string(),
+            })
+            .collect();
+        let message = "本地与远端都有修改，同步时需要选择保留哪一方".to_string();
+        let conflict_info = Self::make_conflict_result(&message)
+            .conflict_info
+            .map(|info| ConflictInfo {
+                conflicting_files: conflict_paths,
+                conflict_items,
+                ..info
+            });
+
+        Ok(SyncPreview {
+            action: "conflict".to_string(),
+            message,
+            to_upload,
+            to_download,
+            to_delete_local,
+            to_delete_remote,
+            has_conflict: true,
+            conflict_info,
+        })
+    }
+
+    /// 强制上传（全量覆盖远端快照，覆盖前备份远端已有快照）
     pub async fn upload_all(&self) -> Result<SyncResult> {
-        self.upload_snapshot().await
+        self.with_lock(|| self.upload_snapshot(true)).await
     }
 
     /// 强制下载（全量覆盖本地）；force=false 时会做冲突检查
     pub async fn download_all(&self, force: bool) -> Result<SyncResult> {
-        self.download_snapshot(force).await
+        self.with_lock(|| self.download_snapshot(force)).await
     }
 
     pub async fn check_conflict(&self) -> Result<Option<ConflictInfo>> {
@@ -716,24 +1187,15 @@ impl SyncManager {
 
     /// 解决单个“冲突项”（新逻辑：忽略 path，只按 choice 决定上传或下载）
     pub async fn resolve_file_conflict(&self, _path: &str, choice: &str) -> Result<()> {
-        match choice {
-            "local" => {
-                let _ = self.upload_snapshot().await?;
-                Ok(())
-            }
-            "remote" => {
-                let _ = self.download_snapshot(true).await?;
-                Ok(())
-            }
-            _ => Err(anyhow!("无效的选择: {}", choice)),
-        }
+        let _ = self.resolve_all_conflicts(choice).await?;
+        Ok(())
     }
 
     /// 批量解决冲突（新逻辑：直接按 choice 执行一次）
     pub async fn resolve_all_conflicts(&self, choice: &str) -> Result<SyncResult> {
         match choice {
-            "local" => self.upload_snapshot().await,
-            "remote" => self.download_snapshot(true).await,
+            "local" => self.with_lock(|| self.upload_snapshot(true)).await,
+            "remote" => self.with_lock(|| self.download_snapshot(true)).await,
             _ => Err(anyhow!("无效的选择: {}", choice)),
         }
     }
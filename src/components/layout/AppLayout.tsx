@@ -9,14 +9,18 @@ import Header from './Header';
 import { useProxyStore } from '@/stores/proxyStore';
 import { useAppStore } from '@/stores/appStore';
 import { useToast } from '@/hooks/useToast';
+import { ToastAction } from '@/components/ui/toast';
 import { DRAG_IGNORE_SELECTOR } from '@/utils/dragUtils';
 import logger from '@/utils/logger';
 import { ipc } from '@/services/ipc';
-import type { ProxyStatus } from '@/types/proxy';
+import type { CoreState, ProxyStatus } from '@/types/proxy';
+import type { SafeModeReason, SubscriptionWarning } from '@/types/config';
 
 export default function AppLayout() {
   const {
     applyStatus,
+    applyCoreState,
+    coreState,
     fetchStatus,
     fetchTraffic,
     fetchConnections,
@@ -28,6 +32,8 @@ export default function AppLayout() {
   } = useProxyStore(
     useShallow((state) => ({
       applyStatus: state.applyStatus,
+      applyCoreState: state.applyCoreState,
+      coreState: state.coreState,
       fetchStatus: state.fetchStatus,
       fetchTraffic: state.fetchTraffic,
       fetchConnections: state.fetchConnections,
@@ -41,10 +47,10 @@ export default function AppLayout() {
   const { fetchSettings, checkRuleDatabaseUpdates } = useAppStore();
   const { toast } = useToast();
   const initStarted = useRef(false);
-  /** 记录上次的运行状态，用于检测状态变化 */
-  const prevRunningRef = useRef<boolean | null>(null);
   /** 标记后端是否已初始化完成 */
   const [backendReady, setBackendReady] = useState(false);
+  /** 安全模式的原因：手动要求或多次启动失败后不自动启动核心 */
+  const [safeMode, setSafeMode] = useState<SafeModeReason | null>(null);
 
   useEffect(() => {
     // 监听 Profile 重载完成事件（处理全局通知）
@@ -99,10 +105,15 @@ export default function AppLayout() {
         await fetchStatus();
         logger.log('AppLayout: Status fetched');
 
-        // 如果 mihomo 未运行，自动启动
+        const inSafeMode = await ipc.getSafeMode();
+        setSafeMode(inSafeMode);
+
+        // 如果 mihomo 未运行，自动启动（安全模式下由用户手动启动）
         const currentStatus = useProxyStore.getState().status;
         logger.log('AppLayout: Current status:', currentStatus);
-        if (!currentStatus.running) {
+        if (inSafeMode) {
+          logger.warn('AppLayout: Safe mode, skipping auto start');
+        } else if (!currentStatus.running) {
           logger.log('AppLayout: Starting MiHomo...');
           await start();
           logger.log('MiHomo started automatically');
@@ -190,6 +201,79 @@ export default function AppLayout() {
     };
   }, [applyStatus]);
 
+  useEffect(() => {
+    // 订阅即将到期或流量即将用尽（后端每天检查，每个阈值只提醒一次）
+    const unlisten = listen<SubscriptionWarning>('subscription-warning', (event) => {
+      toast({
+        title: '订阅提醒',
+        description: event.payload.message,
+        variant: 'destructive',
+      });
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [toast]);
+
+  useEffect(() => {
+    // 上次重载配置时异常退出，当前配置无效但备份可用
+    const recover = async () => {
+      try {
+        await ipc.recoverFromBackup();
+        toast({ title: '配置已恢复', description: '已使用上次可用的配置备份' });
+      } catch (error) {
+        toast({
+          title: '恢复配置失败',
+          description: String(error),
+          variant: 'destructive',
+        });
+      }
+    };
+
+    const unlisten = listen('config-backup-available', () => {
+      toast({
+        title: '配置文件异常',
+        description: '上次重载配置时应用异常退出，当前配置无法使用，可以从备份恢复',
+        variant: 'destructive',
+        action: (
+          <ToastAction altText="从备份恢复配置" onClick={recover}>
+            恢复
+          </ToastAction>
+        ),
+      });
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [toast]);
+
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+
+    const setup = async () => {
+      try {
+        unlisten = await listen<CoreState>('core-state-changed', (event) => {
+          logger.log('AppLayout: Received core-state-changed event:', event.payload);
+          applyCoreState(event.payload);
+        });
+        // 监听注册前的状态变化不会收到事件，主动拉取一次
+        applyCoreState(await ipc.getCoreState());
+      } catch (error) {
+        logger.error('Failed to listen core state events:', error);
+      }
+    };
+
+    setup();
+
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, [applyCoreState]);
+
   useEffect(() => {
     // 判断是否是可编辑元素
     const isEditableElement = (target: EventTarget | null): boolean => {
@@ -225,62 +309,23 @@ export default function AppLayout() {
     };
   }, []);
 
-  // 核心状态统一探测：动态调整轮询频率
-  // - 只在后端初始化完成后才开始轮询（避免"应用正在初始化中"错误）
-  // - 状态稳定后降低频率（1秒 -> 3秒），减少不必要的 API 调用
-  // - 状态变化时恢复高频率
+  // 核心状态由 core-state-changed 事件驱动，不再轮询
+  // - 只在后端初始化完成后才探测（避免"应用正在初始化中"错误）
+  // - 进入运行或停止状态时探测一次版本号，进入运行状态时获取运行模式
   useEffect(() => {
-    // 后端未就绪时不启动轮询
-    if (!backendReady) {
+    if (!backendReady || coreState === 'starting' || coreState === 'restarting') {
       return;
     }
 
-    // 稳定计数器：记录连续多少次状态未变化
-    let stableCount = 0;
-    const STABLE_THRESHOLD = 5; // 连续 5 次稳定后降低频率
-    const FAST_INTERVAL = 1000; // 1 秒
-    const SLOW_INTERVAL = 3000; // 3 秒
-    let currentInterval = FAST_INTERVAL;
-    let timeoutId: NodeJS.Timeout | null = null;
-
-    const doProbe = async () => {
+    const refresh = async () => {
       const running = await probeStatus();
-      const prevRunning = prevRunningRef.current;
-
-      // 状态变化时获取运行模式
-      if (prevRunning !== null && prevRunning !== running) {
-        logger.log(`AppLayout: Core status changed: ${prevRunning} -> ${running}`);
-        if (running) {
-          // 从停止变为运行，获取运行模式
-          fetchRunMode();
-        }
-        // 状态变化，重置稳定计数器并恢复高频轮询
-        stableCount = 0;
-        currentInterval = FAST_INTERVAL;
-      } else {
-        // 状态未变化，增加稳定计数
-        stableCount++;
-        if (stableCount >= STABLE_THRESHOLD && currentInterval !== SLOW_INTERVAL) {
-          currentInterval = SLOW_INTERVAL;
-          logger.debug('AppLayout: Status stable, switching to slow polling');
-        }
+      if (running) {
+        fetchRunMode();
       }
-
-      prevRunningRef.current = running;
-
-      // 调度下一次探测
-      timeoutId = setTimeout(doProbe, currentInterval);
     };
 
-    // 立即执行第一次探测
-    doProbe();
-
-    return () => {
-      if (timeoutId) {
-        clearTimeout(timeoutId);
-      }
-    };
-  }, [backendReady, probeStatus, fetchRunMode]);
+    refresh();
+  }, [backendReady, coreState, probeStatus, fetchRunMode]);
 
   // 定时刷新流量数据和连接数据
   useEffect(() => {
@@ -345,6 +390,14 @@ export default function AppLayout() {
       {/* 顶部栏 - 全宽 */}
       <Header />
 
+      {safeMode && (
+        <div className="mx-3 mt-2 rounded-lg border border-amber-300 bg-amber-50 px-3 py-2 text-xs text-amber-800 dark:border-amber-700 dark:bg-amber-950/40 dark:text-amber-300">
+          {safeMode === 'requested'
+            ? '安全模式：已通过 CONFLUX_SAFE_MODE 环境变量要求以安全模式启动，本次未自动启动核心，也未应用上次的配置。'
+            : '安全模式：核心连续多次启动失败，本次未自动启动核心，也未应用上次的配置。请修复或删除出错的配置后手动启动。'}
+        </div>
+      )}
+
       {/* 下方区域：侧边栏 + 内容 */}
       <div className="flex flex-1 overflow-hidden">
         {/* 侧边栏 */}
@@ -0,0 +1,57 @@
+import { describe, it, expect, beforeEach, vi } from 'vitest';
+import { act, render, screen } from '@testing-library/react';
+import { useAppStore } from '@/stores/appStore';
+import { formatDelay, getDelayColorClass } from '@/utils/format';
+import { useLatencyThresholds } from './useLatencyThresholds';
+
+vi.mock('@/services/ipc', () => ({
+  ipc: {
+    getAppSettings: vi.fn(),
+    saveAppSettings: vi.fn(),
+    checkResourceUpdates: vi.fn(),
+  },
+}));
+
+function DelayLabel({ delay }: { delay: number }) {
+  const thresholds = useLatencyThresholds();
+  return <span className={getDelayColorClass(delay, thresholds)}>{formatDelay(delay)}</span>;
+}
+
+describe('useLatencyThresholds', () => {
+  beforeEach(() => {
+    useAppStore.setState({
+      settings: {
+        language: 'zh-CN',
+        autoStart: false,
+        systemProxy: false,
+        closeToTray: false,
+        ruleDatabases: [],
+      },
+    });
+  });
+
+  it('未配置阈值时应该使用默认值', () => {
+    render(<DelayLabel delay={300} />);
+    expect(screen.getByText('300 ms').className).toContain('amber');
+  });
+
+  it('应该按设置中的阈值着色', () => {
+    useAppStore.setState((state) => ({
+      settings: { ...state.settings, latencyThresholds: { good: 400, medium: 800 } },
+    }));
+    render(<DelayLabel delay={300} />);
+    expect(screen.getByText('300 ms').className).toContain('emerald');
+  });
+
+  it('阈值修改后应该重新着色', () => {
+    render(<DelayLabel delay={300} />);
+    expect(screen.getByText('300 ms').className).toContain('amber');
+
+    act(() => {
+      useAppStore.setState((state) => ({
+        settings: { ...state.settings, latencyThresholds: { good: 100, medium: 200 } },
+      }));
+    });
+    expect(screen.getByText('300 ms').className).toContain('red');
+  });
+});
@@ -0,0 +1,10 @@
+import { useAppStore } from '@/stores/appStore';
+import { DEFAULT_LATENCY_THRESHOLDS } from '@/utils/format';
+import type { LatencyThresholds } from '@/types/config';
+
+/**
+ * 读取设置中的延迟着色阈值，未配置时使用默认值
+ */
+export function useLatencyThresholds(): LatencyThresholds {
+  return useAppStore((state) => state.settings.latencyThresholds) ?? DEFAULT_LATENCY_THRESHOLDS;
+}
@@ -12,12 +12,13 @@ import {
   Terminal,
 } from 'lucide-react';
 import { useProxyStore } from '@/stores/proxyStore';
-import { formatSpeed, formatBytes } from '@/utils/format';
+import { formatSpeed, formatBytes, getDelayLevel } from '@/utils/format';
 import { cn } from '@/utils/cn';
 import { AreaChart, Area, ResponsiveContainer } from 'recharts';
 import { ipc } from '@/services/ipc';
 import type { LocalIpInfo, PublicIpInfo } from '@/types/network';
 import { useToast } from '@/hooks/useToast';
+import { useLatencyThresholds } from '@/hooks/useLatencyThresholds';
 import {
   GoogleIcon,
   YouTubeIcon,
@@ -189,6 +190,7 @@ function createInitialResults(): Map<string, SiteDelayResult> {
 function DiagnosticCard({ className }: { className?: string }) {
   const [results, setResults] = useState<Map<string, SiteDelayResult>>(createInitialResults);
   const [isTestingAll, setIsTestingAll] = useState(false);
+  const latencyThresholds = useLatencyThresholds();
 
   const testAllSites = useCallback(async () => {
     if (isTestingAll) return;
@@ -226,15 +228,17 @@ function DiagnosticCard({ className }: { className?: string }) {
 
   const getDelayColor = (delay: number | null, error: string | null) => {
     if (error || delay === null) return 'text-gray-400 dark:text-gray-500';
-    if (delay < 200) return 'text-emerald-500';
-    if (delay < 500) return 'text-amber-500';
+    const level = getDelayLevel(delay, latencyThresholds);
+    if (level === 'good') return 'text-emerald-500';
+    if (level === 'medium') return 'text-amber-500';
     return 'text-red-500';
   };
 
   const getDelayBgColor = (delay: number | null, error: string | null) => {
     if (error || delay === null) return 'bg-gray-100 dark:bg-zinc-800';
-    if (delay < 200) return 'bg-emerald-50 dark:bg-emerald-500/10';
-    if (delay < 500) return 'bg-amber-50 dark:bg-amber-500/10';
+    const level = getDelayLevel(delay, latencyThresholds);
+    if (level === 'good') return 'bg-emerald-50 dark:bg-emerald-500/10';
+    if (level === 'medium') return 'bg-amber-50 dark:bg-amber-500/10';
     return 'bg-red-50 dark:bg-red-500/10';
   };
 
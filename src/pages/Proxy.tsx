@@ -22,7 +22,8 @@ import {
 import { Tabs, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { useProxyStore } from '@/stores/proxyStore';
 import { useToast } from '@/hooks/useToast';
-import { formatDelay } from '@/utils/format';
+import { useLatencyThresholds } from '@/hooks/useLatencyThresholds';
+import { formatDelay, getDelayLevel } from '@/utils/format';
 import { cn } from '@/utils/cn';
 import { ipc } from '@/services/ipc';
 import type { ProxyMode, ProxyServerInfo, ProxyGroup } from '@/types/proxy';
@@ -55,8 +56,16 @@ const getGroupTypeLabel = (group: ProxyGroup) => {
 // Main Component
 // -----------------------------------------------------------------------------
 
+const DELAY_TEXT_CLASSES = {
+  none: 'text-gray-400',
+  good: 'text-emerald-500',
+  medium: 'text-amber-500',
+  bad: 'text-red-500',
+} as const;
+
 export default function Proxy() {
   const navigate = useNavigate();
+  const latencyThresholds = useLatencyThresholds();
   const { status, pendingMode, groups, fetchGroups, selectProxy, testDelay, switchMode, loading } =
     useProxyStore(
       useShallow((state) => ({
@@ -282,13 +291,7 @@ export default function Proxy() {
                   <span
                     className={cn(
                       'font-medium tabular-nums',
-                      delay < 0
-                        ? 'text-gray-400'
-                        : delay < 200
-                          ? 'text-emerald-500'
-                          : delay < 500
-                            ? 'text-amber-500'
-                            : 'text-red-500'
+                      DELAY_TEXT_CLASSES[getDelayLevel(delay, latencyThresholds)]
                     )}
                   >
                     {formatDelay(delay)}
@@ -86,6 +86,8 @@ function getProfileTypeInfo(type: ProfileType) {
       return { label: '本地配置', icon: FileText, color: 'text-orange-500' };
     case 'blank':
       return { label: '空白配置', icon: File, color: 'text-green-500' };
+    case 'local_watch':
+      return { label: '监听文件', icon: FileText, color: 'text-purple-500' };
   }
 }
 
@@ -395,6 +397,18 @@ export default function SubscriptionPage() {
   const handleActivate = async (id: string) => {
     setApplyingId(id);
     try {
+      // 激活前补全缺失的规则集，无法自动解决的问题提前提示（不阻止激活）
+      const issues = await ipc
+        .checkProfileProviders(id, true)
+        .then((checks) => checks.filter((c) => c.status !== 'downloaded'))
+        .catch(() => []);
+      if (issues.length > 0) {
+        toast({
+          title: `${issues.length} 个规则源/代理源不可用`,
+          description: issues.map((c) => `${c.name}：${c.message}`).join('\n'),
+          variant: 'destructive',
+        });
+      }
       // 调用激活（后端会异步执行重载）
       await ipc.activateProfile(id);
       // 立即刷新 profile 列表以显示新的激活状态
@@ -592,6 +606,7 @@ export default function SubscriptionPage() {
                       {profile.profileType === 'remote' && profile.url}
                       {profile.profileType === 'local' && '本地文件导入'}
                       {profile.profileType === 'blank' && '手动创建的空白配置'}
+                      {profile.profileType === 'local_watch' && profile.sourcePath}
                     </div>
                   </div>
 
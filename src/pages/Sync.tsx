@@ -1,4 +1,5 @@
 import { useState, useEffect, useCallback } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import {
   Cloud,
   CloudUpload,
@@ -32,7 +33,13 @@ import {
   DialogHeader,
   DialogTitle,
 } from '@/components/ui/dialog';
-import type { WebDavConfig, SyncState, ConflictInfo, ConflictItem } from '@/types/config';
+import type {
+  WebDavConfig,
+  SyncState,
+  ConflictInfo,
+  ConflictItem,
+  UploadProgress,
+} from '@/types/config';
 
 // 卡片组件
 function Card({ className, children }: { className?: string; children: React.ReactNode }) {
@@ -166,6 +173,10 @@ export default function Sync() {
   const [syncState, setSyncState] = useState<SyncState | null>(null);
   const [showAdvanced, setShowAdvanced] = useState(false);
 
+  // 强制上传确认与进度
+  const [uploadConfirmDialog, setUploadConfirmDialog] = useState(false);
+  const [uploadProgress, setUploadProgress] = useState<UploadProgress | null>(null);
+
   // 冲突对话框
   const [conflictDialog, setConflictDialog] = useState(false);
   const [conflictInfo, setConflictInfo] = useState<ConflictInfo | null>(null);
@@ -223,6 +234,17 @@ export default function Sync() {
         description: 'WebDAV 服务器连接正常',
       });
     } catch (error) {
+      // 地址填写有误时尝试常见路径，找到可用地址则自动修正
+      const endpoint = await ipc.normalizeWebDavUrl(config).catch(() => null);
+      if (endpoint?.corrected) {
+        await saveConfig({ ...config, url: endpoint.url });
+        const provider = endpoint.provider ? `识别为 ${endpoint.provider}，` : '';
+        toast({
+          title: '已修正服务器地址',
+          description: `${provider}已改为 ${endpoint.url}`,
+        });
+        return;
+      }
       toast({
         variant: 'destructive',
         title: '连接失败',
@@ -276,8 +298,8 @@ export default function Sync() {
     }
   };
 
-  // 强制上传（全量）
-  const handleForceUpload = async () => {
+  // 强制上传（全量）：先确认，避免误触覆盖远端快照
+  const handleForceUpload = () => {
     if (!config.enabled) {
       toast({
         variant: 'destructive',
@@ -287,9 +309,17 @@ export default function Sync() {
       return;
     }
 
+    setUploadConfirmDialog(true);
+  };
+
+  const handleConfirmForceUpload = async () => {
+    setUploadConfirmDialog(false);
     setSyncing(true);
+    const unlisten = await listen<UploadProgress>('webdav-upload-progress', (event) => {
+      setUploadProgress(event.payload);
+    });
     try {
-      const result = await ipc.webDavUpload();
+      const result = await ipc.webDavUpload(true);
       if (result.success) {
         toast({
           title: '上传成功',
@@ -311,10 +341,20 @@ export default function Sync() {
         description: String(error),
       });
     } finally {
+      unlisten();
+      setUploadProgress(null);
       setSyncing(false);
     }
   };
 
+  const handleCancelUpload = async () => {
+    try {
+      await ipc.cancelWebDavUpload();
+    } catch (error) {
+      console.error('取消上传失败:', error);
+    }
+  };
+
   // 强制下载（全量）
   const handleForceDownload = async () => {
     if (!config.enabled) {
@@ -696,8 +736,21 @@ export default function Sync() {
                     className="h-7 text-xs"
                   >
                     <CloudUpload className="w-3 h-3 mr-1" />
-                    强制上传
+                    {uploadProgress
+                      ? `上传中 ${uploadProgress.current}/${uploadProgress.total}`
+                      : '强制上传'}
                   </Button>
+                  {/* 元信息上传时快照已替换远端，不能再取消 */}
+                  {uploadProgress && uploadProgress.file !== 'snapshot.json' && (
+                    <Button
+                      variant="outline"
+                      size="sm"
+                      onClick={handleCancelUpload}
+                      className="h-7 text-xs text-red-500 hover:text-red-600"
+                    >
+                      取消上传
+                    </Button>
+                  )}
                   <Button
                     variant="outline"
                     size="sm"
@@ -732,6 +785,27 @@ export default function Sync() {
         </div>
       </div>
 
+      {/* 强制上传确认对话框 */}
+      <Dialog open={uploadConfirmDialog} onOpenChange={setUploadConfirmDialog}>
+        <DialogContent className="max-w-md">
+          <DialogHeader>
+            <DialogTitle>确认强制上传</DialogTitle>
+            <DialogDescription>
+              强制上传会用本地配置覆盖远端快照，原有快照会在远端保留一份 snapshot.zip.bak。确定继续吗？
+            </DialogDescription>
+          </DialogHeader>
+          <DialogFooter className="gap-2 sm:gap-0 mt-4">
+            <Button variant="outline" onClick={() => setUploadConfirmDialog(false)}>
+              取消
+            </Button>
+            <Button variant="destructive" onClick={handleConfirmForceUpload}>
+              <CloudUpload className="w-4 h-4 mr-2" />
+              覆盖远端
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+
       {/* 冲突解决对话框 */}
       <Dialog open={conflictDialog} onOpenChange={setConflictDialog}>
         <DialogContent className="max-w-lg">
@@ -21,7 +21,8 @@ import {
 import { ipc } from '@/services/ipc';
 import { useToast } from '@/hooks/useToast';
 import { useProxyStore } from '@/stores/proxyStore';
-import { formatDelay, getDelayColorClass } from '@/utils/format';
+import { useLatencyThresholds } from '@/hooks/useLatencyThresholds';
+import { formatDelay, getDelayColorClass, getDelayLevel } from '@/utils/format';
 import { cn } from '@/utils/cn';
 import type { ProxyConfig } from '@/types/config';
 import { BentoCard } from './components/BentoCard';
@@ -118,6 +119,7 @@ const AddServerMenu = ({
 export default function ProxyServers() {
   const status = useProxyStore((state) => state.status);
   const { toast } = useToast();
+  const latencyThresholds = useLatencyThresholds();
   const [activeProfileId, setActiveProfileId] = useState<string | null>(null);
   const [profileConfig, setProfileConfig] = useState<ProfileConfig | null>(null);
 
@@ -523,7 +525,7 @@ export default function ProxyServers() {
                               'w-1.5 h-1.5 rounded-full',
                               delay < 0
                                 ? 'bg-red-400'
-                                : delay < 200
+                                : getDelayLevel(delay, latencyThresholds) === 'good'
                                   ? 'bg-emerald-400'
                                   : 'bg-amber-400'
                             )}
@@ -531,7 +533,9 @@ export default function ProxyServers() {
                           <span
                             className={cn(
                               'text-[10px] font-bold',
-                              getDelayColorClass(delay).replace('bg-', 'text-').replace('/10', '')
+                              getDelayColorClass(delay, latencyThresholds)
+                                .replace('bg-', 'text-')
+                                .replace('/10', '')
                             )}
                           >
                             {formatDelay(delay)}
@@ -59,7 +59,8 @@ export const parseSsLink = (link: string): ProxyConfig => {
   const raw = link.replace(/^ss:\/\//i, '');
   const [beforeHash, hashPart] = raw.split('#');
   const name = parseName(hashPart);
-  const [basePart] = beforeHash.split('?');
+  // SIP002 带插件参数时为 `host:port/?plugin=...`
+  const basePart = beforeHash.split('?')[0].replace(/\/$/, '');
 
   const atIndex = basePart.lastIndexOf('@');
   let cipher = '';
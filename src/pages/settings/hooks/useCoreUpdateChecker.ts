@@ -37,6 +37,15 @@ export function useCoreUpdateChecker(
       // 调用后端升级 API
       const newVersion = await ipc.upgradeCore();
 
+      if (newVersion.pinnedVersion) {
+        setCoreUpdateStatus('idle');
+        toast({
+          title: '核心版本已固定',
+          description: `已固定为 ${newVersion.pinnedVersion}，取消固定后才会检查更新`,
+        });
+        return;
+      }
+
       setNewCoreVersion(newVersion.version);
       setCoreUpdateStatus('success');
 
@@ -102,6 +102,18 @@ export function NetworkSection({
     }
   };
 
+  const handleStoreSelectedChange = async (checked: boolean) => {
+    try {
+      await ipc.setStoreSelected(checked);
+      setConfig((prev) =>
+        prev ? { ...prev, profile: { ...prev.profile, 'store-selected': checked } } : prev
+      );
+      toast({ title: checked ? '将记住节点选择' : '已关闭节点选择记忆' });
+    } catch (error) {
+      toast({ title: '更新失败', description: String(error), variant: 'destructive' });
+    }
+  };
+
   const handleTunEnableChange = async (checked: boolean) => {
     try {
       await ipc.setTunMode(checked);
@@ -380,6 +392,21 @@ export function NetworkSection({
               </Select>
             }
           />
+          <Divider />
+          <SettingItem
+            icon={Layers}
+            iconBgColor="bg-teal-50 dark:bg-teal-500/10"
+            iconColor="text-teal-500"
+            title="记住节点选择"
+            description="重载或重启后恢复策略组选择"
+            action={
+              <Switch
+                checked={config?.profile?.['store-selected'] ?? true}
+                onCheckedChange={handleStoreSelectedChange}
+                className="scale-90"
+              />
+            }
+          />
         </BentoCard>
       </div>
     </div>
@@ -1,33 +1,142 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke, type InvokeArgs } from '@tauri-apps/api/core';
 import type { NetworkExtensionStatus } from '@/types/network';
 import type {
   ProxyStatus,
+  StateReconcile,
+  CoreState,
+  ExternalCoreAction,
+  ExternalCoreReport,
   ProxyGroup,
   TrafficData,
   ConnectionsResponse,
+  ConnectionsPage,
+  ConnectionSortKey,
+  RuleImpact,
   RuleItem,
+  ProviderWarmResult,
   VersionInfo,
+  CoreConfigSchema,
   ProxyServerInfo,
+  TemporaryDirectStatus,
+  TraceEntry,
+  TraceStatus,
+  RuleHitStats,
+  ObservabilityStats,
+  TunDiagnosis,
+  ChainLatency,
+  StandaloneNodeTest,
 } from '@/types/proxy';
-import type { LocalIpInfo, PublicIpInfo } from '@/types/network';
+import type {
+  CaptivePortalStatus,
+  LocalIpInfo,
+  NetworkInterface,
+  PreviousSystemProxy,
+  PublicIpInfo,
+} from '@/types/network';
 import type {
   MihomoConfig,
+  ValidationIssue,
+  CoreConfigTestResult,
+  StaleBackupStatus,
   AppSettings,
+  AutoSwitchSettings,
+  ConnectionLimitSettings,
+  CorePriority,
+  NetworkChangePolicy,
+  ObservabilitySettings,
+  SelectionPreset,
+  SelectionPresetResult,
+  SelectionResetResult,
+  SelectionScheduleSettings,
   DownloadResourceResult,
   ResourceUpdateCheckRequest,
   ResourceUpdateCheckResult,
   ProfileMetadata,
+  ProfileHistoryEntry,
+  ProfileDiff,
+  PortConflict,
+  LanAccessSettings,
+  ExternalImportResult,
+  AppProxyRules,
+  ForeignImportResult,
+  SubscriptionValidation,
+  RuleImportResult,
+  ShareLinkExport,
+  RulesetRefreshResult,
+  ProviderCheck,
+  SecurityFinding,
+  SubscriptionWarning,
   ProfileConfig,
   ProxyConfig,
   ProxyProvider,
   RuleProvider,
   ProxyGroupConfig,
   WebDavConfig,
+  WebDavEndpoint,
   SyncState,
   SyncResult,
+  SyncPreview,
   ConflictInfo,
+  SnifferConfig,
+  ScriptConfig,
+  DohTestResult,
+  DnsComparison,
+  CrashReport,
+  CrashReportSummary,
+  DataDirInfo,
+  StartupTimings,
+  SafeModeReason,
+  WindowBehavior,
+  DataBackupResult,
+  TemplateMergeMode,
+  TemplateApplyResult,
+  RuleTemplateInfo,
+  NodeRename,
+  RegionGroupsPlan,
+  RuleDedupReport,
+  ShadowedRule,
+  GroupCycle,
+  LatencyThresholds,
 } from '@/types/config';
 
+/**
+ * 后端 CommandError 的错误码
+ */
+export type CommandErrorCode = 'NETWORK' | 'VALIDATION' | 'PERMISSION' | 'NOT_FOUND' | 'CORE_ERROR';
+
+/**
+ * 结构化命令错误
+ * toString() 只返回原始错误信息，兼容现有的 String(error) 用法
+ */
+export class CommandError extends Error {
+  readonly code: CommandErrorCode;
+
+  constructor(code: CommandErrorCode, message: string) {
+    super(message);
+    this.name = 'CommandError';
+    this.code = code;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+/**
+ * 调用返回 CommandError 的命令，将 { code, message } 转换为 CommandError
+ */
+async function invokeCommand<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await invoke<T>(cmd, args);
+  } catch (error) {
+    if (error && typeof error === 'object' && 'code' in error && 'message' in error) {
+      const { code, message } = error as { code: CommandErrorCode; message: string };
+      throw new CommandError(code, message);
+    }
+    throw error;
+  }
+}
+
 /**
  * IPC 服务 - 与 Tauri 后端通信
  */
@@ -36,10 +145,11 @@ export const ipc = {
 
   /**
    * 启动代理
+   * @param coreOnly 仅启动核心，不设置系统代理、不启用 TUN
    * @returns 启动成功后的代理状态
    */
-  async startProxy(): Promise<ProxyStatus> {
-    return invoke('start_proxy');
+  async startProxy(coreOnly?: boolean): Promise<ProxyStatus> {
+    return invoke('start_proxy', { coreOnly });
   },
 
   /**
@@ -71,6 +181,34 @@ export const ipc = {
     return invoke('get_proxy_status');
   },
 
+  /**
+   * 与核心的实际状态对齐（其他面板修改模式/TUN 等之后），有差异时发送 proxy-status-changed
+   */
+  async reconcileState(): Promise<StateReconcile> {
+    return invoke('reconcile_state');
+  },
+
+  /**
+   * 获取核心运行状态
+   */
+  async getCoreState(): Promise<CoreState> {
+    return invoke('get_core_state');
+  },
+
+  /**
+   * 检测其他 Clash/mihomo 实例
+   */
+  async detectExternalCore(): Promise<ExternalCoreReport> {
+    return invoke('detect_external_core');
+  },
+
+  /**
+   * 处理检测到的外部核心
+   */
+  async resolveExternalCore(action: ExternalCoreAction): Promise<ExternalCoreReport> {
+    return invoke('resolve_external_core', { action });
+  },
+
   /**
    * 切换代理模式
    */
@@ -78,6 +216,13 @@ export const ipc = {
     return invoke('switch_mode', { mode });
   },
 
+  /**
+   * 设置脚本并切换到 script 模式，传入 null 时清除脚本并恢复为规则模式
+   */
+  async setScriptMode(script: ScriptConfig | null): Promise<void> {
+    return invoke('set_script_mode', { script });
+  },
+
   /**
    * 获取当前运行模式
    * 返回核心的运行模式（普通/服务/管理员/助手）
@@ -101,6 +246,13 @@ export const ipc = {
     return invoke('select_proxy', { group, name });
   },
 
+  /**
+   * 设置全局模式的出口节点（同时切换到 global 模式）
+   */
+  async setGlobalProxy(name: string): Promise<void> {
+    return invoke('set_global_proxy', { name });
+  },
+
   /**
    * 测试代理延迟
    */
@@ -108,6 +260,200 @@ export const ipc = {
     return invoke('test_proxy_delay', { name });
   },
 
+  /**
+   * 单独测试节点（不加入 Profile，用于导入前验证）
+   * @param timeout 超时时间（毫秒）
+   */
+  async testStandaloneNode(
+    proxy: ProxyConfig,
+    url?: string,
+    timeout?: number
+  ): Promise<StandaloneNodeTest> {
+    return invoke('test_standalone_node', { proxy, url, timeout });
+  },
+
+  /**
+   * 获取自动切换最快节点设置
+   */
+  async getAutoSwitchSettings(): Promise<AutoSwitchSettings> {
+    return invoke('get_auto_switch_settings');
+  },
+
+  /**
+   * 设置自动切换最快节点
+   */
+  async setAutoSwitch(settings: AutoSwitchSettings): Promise<void> {
+    return invoke('set_auto_switch', { settings });
+  },
+
+  /**
+   * 获取连接数上限设置
+   */
+  async getConnectionLimit(): Promise<ConnectionLimitSettings> {
+    return invoke('get_connection_limit');
+  },
+
+  /**
+   * 设置连接数上限（超限时发送 connection-limit-exceeded 事件）
+   */
+  async setConnectionLimit(settings: ConnectionLimitSettings): Promise<void> {
+    return invoke('set_connection_limit', { settings });
+  },
+
+  /**
+   * 获取核心进程优先级
+   */
+  async getCorePriority(): Promise<CorePriority> {
+    return invoke('get_core_priority');
+  },
+
+  /**
+   * 设置核心进程优先级，保存后立即应用到运行中的核心
+   */
+  async setCorePriority(level: CorePriority): Promise<void> {
+    return invoke('set_core_priority', { level });
+  },
+
+  /**
+   * 获取策略组选择预设
+   */
+  async listSelectionPresets(): Promise<SelectionPreset[]> {
+    return invoke('list_selection_presets');
+  },
+
+  /**
+   * 保存当前策略组选择为预设
+   */
+  async saveSelectionPreset(name: string): Promise<SelectionPreset> {
+    return invoke('save_selection_preset', { name });
+  },
+
+  /**
+   * 应用策略组选择预设
+   */
+  async applySelectionPreset(name: string): Promise<SelectionPresetResult> {
+    return invoke('apply_selection_preset', { name });
+  },
+
+  /**
+   * 删除策略组选择预设
+   */
+  async deleteSelectionPreset(name: string): Promise<void> {
+    return invoke('delete_selection_preset', { name });
+  },
+
+  /**
+   * 将 select 策略组恢复为订阅默认的节点（仅限使用中的配置）
+   */
+  async resetSelections(profileId: string): Promise<SelectionResetResult> {
+    return invoke('reset_selections', { profileId });
+  },
+
+  /**
+   * 重新应用订阅建议的默认节点（配置使用中时立即应用，否则在下次启用时应用）
+   */
+  async reapplySuggestedSelection(profileId: string): Promise<void> {
+    return invoke('reapply_suggested_selection', { profileId });
+  },
+
+  /**
+   * 获取按时段选择节点的设置
+   */
+  async getSelectionSchedule(): Promise<SelectionScheduleSettings> {
+    return invoke('get_selection_schedule');
+  },
+
+  /**
+   * 设置按时段选择节点（进入新时段时自动切换）
+   */
+  async setSelectionSchedule(settings: SelectionScheduleSettings): Promise<void> {
+    return invoke('set_selection_schedule', { settings });
+  },
+
+  /**
+   * 开关核心的 store-selected（重载/重启后恢复策略组选择）
+   */
+  async setStoreSelected(enabled: boolean): Promise<void> {
+    return invoke('set_store_selected', { enabled });
+  },
+
+  /**
+   * 临时切换到直连模式，到期后自动恢复（重复调用会重新计时）
+   */
+  async setTemporaryDirect(durationSecs: number): Promise<TemporaryDirectStatus> {
+    return invoke('set_temporary_direct', { durationSecs });
+  },
+
+  /**
+   * 立即结束临时直连
+   */
+  async cancelTemporaryDirect(): Promise<void> {
+    return invoke('cancel_temporary_direct');
+  },
+
+  /**
+   * 获取临时直连状态
+   */
+  async getTemporaryDirectStatus(): Promise<TemporaryDirectStatus> {
+    return invoke('get_temporary_direct_status');
+  },
+
+  /**
+   * 开始连接追踪：在指定时长内记录新连接的命中规则和代理链（重复调用会重新开始）
+   */
+  async startTrace(durationSecs: number): Promise<TraceStatus> {
+    return invoke('start_trace', { durationSecs });
+  },
+
+  /**
+   * 停止连接追踪，返回已记录的连接
+   */
+  async stopTrace(): Promise<TraceEntry[]> {
+    return invoke('stop_trace');
+  },
+
+  /**
+   * 获取连接追踪状态
+   */
+  async getTraceStatus(): Promise<TraceStatus> {
+    return invoke('get_trace_status');
+  },
+
+  /**
+   * 获取连接追踪记录（追踪结束后仍可读取，直到下次开始）
+   */
+  async getTraceEntries(): Promise<TraceEntry[]> {
+    return invoke('get_trace_entries');
+  },
+
+  /**
+   * 获取本次核心会话中各规则的命中次数
+   */
+  async getRuleHitCounts(): Promise<RuleHitStats> {
+    return invoke('get_rule_hit_counts');
+  },
+
+  /**
+   * 清零规则命中计数
+   */
+  async resetRuleHitCounts(): Promise<void> {
+    return invoke('reset_rule_hit_counts');
+  },
+
+  /**
+   * 获取诊断数据（流量历史、日志缓存、连接追踪、规则命中）的内存占用
+   */
+  async getObservabilityStats(): Promise<ObservabilityStats> {
+    return invoke('get_observability_stats');
+  },
+
+  /**
+   * 设置诊断数据的内存预算、流量历史保留时长和规则命中统计开关
+   */
+  async setObservabilitySettings(settings: ObservabilitySettings): Promise<ObservabilityStats> {
+    return invoke('set_observability_settings', { settings });
+  },
+
   /**
    * 获取流量数据
    */
@@ -122,6 +468,28 @@ export const ipc = {
     return invoke('get_connections');
   },
 
+  /** 获取一页连接（后端过滤与排序） */
+  async getConnectionsPage(
+    offset: number,
+    limit: number,
+    sortBy?: ConnectionSortKey,
+    filter?: string
+  ): Promise<ConnectionsPage> {
+    return invoke('get_connections_page', { offset, limit, sortBy, filter });
+  },
+
+  /**
+   * 预估规则影响：当前活跃连接中会被该规则命中的连接（假定规则置顶）
+   */
+  async simulateRuleImpact(rule: string): Promise<RuleImpact> {
+    return invoke('simulate_rule_impact', { rule });
+  },
+
+  /** 导出最近一段时间的流量和主机流量统计为 CSV（windowSecs 最长为流量历史保留上限 24 小时） */
+  async exportTrafficCsv(targetPath: string, windowSecs: number): Promise<void> {
+    return invoke('export_traffic_csv', { targetPath, windowSecs });
+  },
+
   /**
    * 关闭单个连接
    */
@@ -141,7 +509,7 @@ export const ipc = {
    * 在 macOS 上，如果没有权限会自动请求设置
    */
   async setTunMode(enabled: boolean): Promise<void> {
-    return invoke('set_tun_mode', { enabled });
+    return invokeCommand('set_tun_mode', { enabled });
   },
 
   /**
@@ -166,11 +534,47 @@ export const ipc = {
     return invoke('set_tun_route_exclude', { addresses });
   },
 
+  /** 添加 TUN 路由排除网段 */
+  async addTunRouteExclude(address: string): Promise<void> {
+    return invoke('add_tun_route_exclude', { address });
+  },
+
+  /** 移除 TUN 路由排除网段 */
+  async removeTunRouteExclude(address: string): Promise<void> {
+    return invoke('remove_tun_route_exclude', { address });
+  },
+
+  /** 添加 TUN 路由包含网段 */
+  async addTunRouteInclude(address: string): Promise<void> {
+    return invoke('add_tun_route_include', { address });
+  },
+
+  /** 移除 TUN 路由包含网段 */
+  async removeTunRouteInclude(address: string): Promise<void> {
+    return invoke('remove_tun_route_include', { address });
+  },
+
   /**
    * 设置 LAN 访问开关
    */
-  async setAllowLan(enabled: boolean): Promise<void> {
-    return invoke('set_allow_lan', { enabled });
+  async setAllowLan(
+    enabled: boolean,
+    bindAddress?: string,
+    allowedIps?: string[]
+  ): Promise<void> {
+    return invoke('set_allow_lan', { enabled, bindAddress, allowedIps });
+  },
+
+  /**
+   * 设置代理端口监听地址：回环地址仅本机可用，其他 IP 或 * 开启局域网共享（需重启核心）
+   */
+  async setBindAddress(addr: string): Promise<void> {
+    return invoke('set_bind_address', { addr });
+  },
+
+  /** 获取局域网共享设置（监听地址、允许的网段） */
+  async getLanAccess(): Promise<LanAccessSettings> {
+    return invoke('get_lan_access');
   },
 
   /**
@@ -201,6 +605,13 @@ export const ipc = {
     return invoke('set_sniffing', { enabled });
   },
 
+  /**
+   * 设置域名嗅探配置（协议端口、强制/跳过域名）
+   */
+  async setSnifferConfig(sniffer: SnifferConfig): Promise<void> {
+    return invoke('set_sniffer_config', { sniffer });
+  },
+
   /**
    * 检查 TUN 权限状态
    */
@@ -208,6 +619,13 @@ export const ipc = {
     return invoke('check_tun_permission');
   },
 
+  /**
+   * 检查 TUN 是否真正接管流量（网卡、路由、测试连接）
+   */
+  async verifyTunActive(): Promise<TunDiagnosis> {
+    return invoke('verify_tun_active');
+  },
+
   /**
    * 手动设置 TUN 权限
    */
@@ -229,6 +647,28 @@ export const ipc = {
     return invoke('open_network_extension_settings');
   },
 
+  /**
+   * 设置网络切换后的处理策略（切换后发送 network-changed 事件）
+   */
+  async setNetworkChangePolicy(policy: NetworkChangePolicy): Promise<void> {
+    return invoke('set_network_change_policy', { policy });
+  },
+
+  /**
+   * 获取按应用分流规则
+   */
+  async getAppProxyRules(): Promise<AppProxyRules> {
+    return invoke('get_app_proxy_rules');
+  },
+
+  /**
+   * 设置按应用分流规则（macOS，Bundle ID），返回规范化后的规则
+   * Network Extension 未安装或未启用时报错，规则不会保存
+   */
+  async setAppProxyRules(included: string[], excluded: string[]): Promise<AppProxyRules> {
+    return invoke('set_app_proxy_rules', { included, excluded });
+  },
+
   // ============= 配置命令 =============
 
   /**
@@ -242,7 +682,37 @@ export const ipc = {
    * 保存 MiHomo 配置
    */
   async saveConfig(config: MihomoConfig): Promise<void> {
-    return invoke('save_config', { config });
+    return invokeCommand('save_config', { config });
+  },
+
+  /**
+   * 校验 MiHomo 配置，返回带字段路径的问题列表（不保存）
+   */
+  async validateConfig(config: MihomoConfig): Promise<ValidationIssue[]> {
+    return invoke('validate_config', { config });
+  },
+
+  /**
+   * 使用核心测试配置文件（mihomo -t），返回带行号/字段位置的错误
+   */
+  async testConfigFile(path: string): Promise<CoreConfigTestResult> {
+    return invoke('test_config_file', { path });
+  },
+
+  /**
+   * 使用核心测试配置内容（原始配置编辑器粘贴的 YAML）
+   * 内容与当前设置合并成运行时配置后测试，行号对应生成的配置
+   */
+  async testConfigContent(content: string): Promise<CoreConfigTestResult> {
+    return invoke('test_config_content', { content });
+  },
+
+  /**
+   * 从异常退出遗留的备份恢复配置（启动时发现可恢复备份会发送 config-backup-available 事件）
+   * @returns 处理前的检查结果
+   */
+  async recoverFromBackup(): Promise<StaleBackupStatus> {
+    return invoke('recover_from_backup');
   },
 
   /**
@@ -252,6 +722,13 @@ export const ipc = {
     return invoke('get_app_settings');
   },
 
+  /**
+   * 设置延迟着色阈值（需满足 good < medium）
+   */
+  async setLatencyThresholds(thresholds: LatencyThresholds): Promise<void> {
+    return invoke('set_latency_thresholds', { thresholds });
+  },
+
   /**
    * 保存应用设置
    */
@@ -259,6 +736,28 @@ export const ipc = {
     return invoke('save_app_settings', { settings });
   },
 
+  /**
+   * 检查本地服务端口冲突
+   */
+  async checkPortConflicts(): Promise<PortConflict[]> {
+    return invoke('check_port_conflicts');
+  },
+
+  /** 轮换 API 密钥（核心运行中会重启） */
+  async rotateApiSecret(): Promise<void> {
+    return invoke('rotate_api_secret');
+  },
+
+  /** 启用或关闭 HTTPS 外部控制器（会校验证书与私钥是否配对，需要重启核心） */
+  async setControllerTls(
+    enabled: boolean,
+    listen: string | null,
+    certPath: string | null,
+    keyPath: string | null
+  ): Promise<void> {
+    return invoke('set_controller_tls', { enabled, listen, certPath, keyPath });
+  },
+
   /**
    * 获取配置文件中的规则
    */
@@ -313,6 +812,15 @@ export const ipc = {
     });
   },
 
+  /**
+   * 下载规则数据库（支持断点续传与完整性校验）
+   * 进度通过 geo-download-progress 事件报告
+   * @param which 规则数据库 ID（如 geoip / geosite）
+   */
+  async downloadGeoWithProgress(which: string): Promise<DownloadResourceResult> {
+    return invoke('download_geo_with_progress', { which });
+  },
+
   /**
    * 检查外部资源文件状态
    */
@@ -343,6 +851,13 @@ export const ipc = {
     return invoke('reload_geo_database');
   },
 
+  /**
+   * 下载并安装面板（由核心提供），url 为空时使用 metacubexd，返回面板访问地址
+   */
+  async installDashboard(url?: string): Promise<string> {
+    return invoke('install_dashboard', { url });
+  },
+
   /**
    * 获取核心版本信息
    */
@@ -350,14 +865,31 @@ export const ipc = {
     return invoke('get_core_version');
   },
 
+  /**
+   * 获取核心配置能力（按版本推导支持的配置项，并附带当前 /configs）
+   */
+  async getCoreConfigSchema(): Promise<CoreConfigSchema> {
+    return invoke('get_core_config_schema');
+  },
+
   /**
    * 升级核心（调用 mihomo /upgrade API）
-   * 触发 mihomo 核心自我更新，更新完成后返回新的版本信息
+   * 触发 mihomo 核心自我更新，更新完成后返回新的版本信息；
+   * 核心版本已固定时不升级，返回当前版本并带上 pinnedVersion；
+   * 运行的核心与固定的版本不一致时报错
    */
   async upgradeCore(): Promise<VersionInfo> {
     return invoke('upgrade_core');
   },
 
+  /**
+   * 固定核心版本（传 null 取消固定），只对本机生效，固定后检查更新时不升级核心
+   * 版本号需为 `v1.19.2` 这类数字版本
+   */
+  async setPinnedCoreVersion(version: string | null): Promise<void> {
+    return invoke('set_pinned_core_version', { version });
+  },
+
   /**
    * 获取配置文件中的代理服务器列表
    */
@@ -388,6 +920,13 @@ export const ipc = {
     return invoke('get_system_proxy_status');
   },
 
+  /**
+   * 获取开启系统代理前的原始系统代理配置（关闭时会恢复为该配置）
+   */
+  async getPreviousSystemProxy(): Promise<PreviousSystemProxy | null> {
+    return invoke('get_previous_system_proxy');
+  },
+
   /**
    * 获取公网 IP 信息（IP + 国家代码）
    */
@@ -402,6 +941,28 @@ export const ipc = {
     return invoke('get_local_ip_info');
   },
 
+  /**
+   * 列出本机网卡（用于选择出站网卡）
+   */
+  async listNetworkInterfaces(): Promise<NetworkInterface[]> {
+    return invoke('list_network_interfaces');
+  },
+
+  /**
+   * 检测是否处于需要网页认证的网络（酒店、机场 Wi-Fi 等）
+   */
+  async detectCaptivePortal(): Promise<CaptivePortalStatus> {
+    return invoke('detect_captive_portal');
+  },
+
+  /**
+   * 临时关闭系统代理和 TUN 以完成门户认证，网络恢复后自动还原
+   * 通过 captive-portal-bypass 事件通知开始（true）和恢复（false）
+   */
+  async bypassForCaptivePortal(): Promise<void> {
+    return invoke('bypass_for_captive_portal');
+  },
+
   /**
    * 获取终端代理命令（export / PowerShell env）
    */
@@ -496,6 +1057,13 @@ export const ipc = {
     return invoke('update_rule_provider', { name });
   },
 
+  /**
+   * 预热当前配置：触发所有远程 Provider 下载
+   */
+  async warmActiveProfile(): Promise<ProviderWarmResult[]> {
+    return invoke('warm_active_profile');
+  },
+
   // ============= 日志命令 =============
 
   /**
@@ -519,6 +1087,27 @@ export const ipc = {
     return invoke('set_log_level', { level });
   },
 
+  /**
+   * 设置日志/连接数据流断开后的重连间隔（秒，1-60），恢复后发送 stream-reconnected 事件
+   */
+  async setStreamReconnectInterval(secs: number): Promise<void> {
+    return invoke('set_stream_reconnect_interval', { secs });
+  },
+
+  /**
+   * 导出日志到文件（核心日志与应用日志，敏感信息已脱敏）
+   * @param levelFilter 最低日志级别
+   * @param sinceTimestamp 起始时间（Unix 毫秒时间戳）
+   * @returns 导出的条数
+   */
+  async exportLogs(
+    targetPath: string,
+    levelFilter?: string,
+    sinceTimestamp?: number
+  ): Promise<number> {
+    return invoke('export_logs', { targetPath, levelFilter, sinceTimestamp });
+  },
+
   // ============= 设置命令 =============
 
   /**
@@ -535,6 +1124,115 @@ export const ipc = {
     return invoke('set_find_process_mode', { mode });
   },
 
+  /**
+   * 设置出站绑定网卡，name 为空时恢复自动选择（routingMark 仅 Linux）
+   */
+  async setInterfaceName(name: string, routingMark?: number): Promise<void> {
+    return invoke('set_interface_name', { name, routingMark });
+  },
+
+  /**
+   * 设置核心出站流量的 routing-mark（fwmark，仅 Linux），null 为清除
+   */
+  async setRoutingMark(routingMark: number | null): Promise<void> {
+    return invoke('set_routing_mark', { routingMark });
+  },
+
+  /**
+   * 设置由核心自动更新 GEO 数据，开启后应用不再自动更新规则数据库
+   * @param intervalHours 更新间隔（小时，1-720），null 为核心默认值
+   */
+  async setGeoAutoUpdate(enabled: boolean, intervalHours?: number | null): Promise<void> {
+    return invoke('set_geo_auto_update', { enabled, intervalHours });
+  },
+
+  /**
+   * 设置 TCP keep-alive（interval / idle 单位为秒，不传时使用核心默认值）
+   */
+  async setKeepAlive(
+    interval: number | null,
+    idle: number | null,
+    disabled: boolean
+  ): Promise<void> {
+    return invoke('set_keep_alive', { interval, idle, disabled });
+  },
+
+  /**
+   * 应用移动网络 keep-alive 预设
+   */
+  async applyMobileKeepAlivePreset(): Promise<void> {
+    return invoke('apply_mobile_keep_alive_preset');
+  },
+
+  /**
+   * 设置核心资源下载参数（global-ua / etag-support，不传时使用核心默认值）
+   * healthCheckInterval 覆盖所有 proxy-provider 的健康检查间隔（60-86400 秒），不传时使用 Profile 中的值
+   */
+  async setProviderDownloadOptions(
+    globalUa: string | null,
+    etagSupport: boolean | null,
+    healthCheckInterval: number | null = null
+  ): Promise<void> {
+    return invoke('set_provider_download_options', {
+      globalUa,
+      etagSupport,
+      healthCheckInterval,
+    });
+  },
+
+  /**
+   * 设置 DNS 增强模式
+   */
+  async setDnsEnhancedMode(mode: 'fake-ip' | 'redir-host'): Promise<void> {
+    return invoke('set_dns_enhanced_mode', { mode });
+  },
+
+  /**
+   * 设置 DNS fake-ip 网段（空字符串恢复默认 198.18.0.1/16）
+   */
+  async setFakeIpRange(cidr: string): Promise<void> {
+    return invoke('set_fake_ip_range', { cidr });
+  },
+
+  /**
+   * 添加或更新 DNS 域名策略（nameserver-policy）
+   */
+  async addNameserverPolicy(domain: string, servers: string[]): Promise<void> {
+    return invoke('add_nameserver_policy', { domain, servers });
+  },
+
+  /**
+   * 删除 DNS 域名策略
+   */
+  async removeNameserverPolicy(domain: string): Promise<void> {
+    return invoke('remove_nameserver_policy', { domain });
+  },
+
+  /**
+   * 测试 DoH 服务器（不经过核心直接查询 testDomain，返回解析结果和耗时）
+   */
+  async testDohResolver(
+    url: string,
+    testDomain?: string,
+    timeout?: number
+  ): Promise<DohTestResult> {
+    return invoke('test_doh_resolver', { url, testDomain, timeout });
+  },
+
+  /**
+   * 将 DoH 服务器添加到 DNS 的 nameserver 或 fallback 列表
+   */
+  async addDohResolver(url: string, list: 'nameserver' | 'fallback'): Promise<void> {
+    return invoke('add_doh_resolver', { url, list });
+  },
+
+  /**
+   * 对比核心 DNS 与系统 DNS 对同一域名的解析结果
+   */
+  async compareDns(domain: string, timeout?: number): Promise<DnsComparison> {
+    return invoke('compare_dns', { domain, timeout });
+  },
+
   /**
    * 获取应用版本
    */
@@ -549,6 +1247,34 @@ export const ipc = {
     return invoke('flush_fakeip_cache');
   },
 
+  /**
+   * 以安全模式启动的原因（不自动启动核心、未应用上次的 Profile），正常启动时为 null
+   */
+  async getSafeMode(): Promise<SafeModeReason | null> {
+    return invoke('get_safe_mode');
+  },
+
+  /**
+   * 获取本次启动各阶段的耗时
+   */
+  async getStartupTimings(): Promise<StartupTimings> {
+    return invoke('get_startup_timings');
+  },
+
+  /**
+   * 获取当前数据目录及覆盖情况
+   */
+  async getDataDirInfo(): Promise<DataDirInfo> {
+    return invoke('get_data_dir_info');
+  },
+
+  /**
+   * 设置独立的数据目录，null 恢复默认（重启后生效，CONFLUX_DATA_DIR 优先）
+   */
+  async setDataDirOverride(path: string | null): Promise<void> {
+    return invoke('set_data_dir_override', { path });
+  },
+
   /**
    * 获取开机自启动状态
    */
@@ -563,6 +1289,20 @@ export const ipc = {
     return invoke('set_autostart_enabled', { enabled });
   },
 
+  /**
+   * 获取窗口与托盘的启动行为
+   */
+  async getWindowBehavior(): Promise<WindowBehavior> {
+    return invoke('get_window_behavior');
+  },
+
+  /**
+   * 设置窗口与托盘的启动行为（开机自启动会同步注册或移除系统启动项）
+   */
+  async setWindowBehavior(behavior: WindowBehavior): Promise<void> {
+    return invoke('set_window_behavior', { behavior });
+  },
+
   // ============= Sub-Store 命令 =============
 
   /**
@@ -641,6 +1381,27 @@ export const ipc = {
     return invoke('create_local_profile', { name, filePath });
   },
 
+  /**
+   * 创建监听本地文件的 Profile（源文件变化时自动重新导入）
+   */
+  async createWatchProfile(name: string, filePath: string): Promise<ProfileMetadata> {
+    return invoke('create_watch_profile', { name, filePath });
+  },
+
+  /**
+   * 从其他 Clash 客户端的数据目录批量导入 Profile
+   */
+  async importExternalProfiles(dir: string): Promise<ExternalImportResult> {
+    return invoke('import_external_profiles', { dir });
+  },
+
+  /**
+   * 导入 Surge / QuantumultX 配置为本地 Profile
+   */
+  async importForeignProfile(name: string, filePath: string): Promise<ForeignImportResult> {
+    return invoke('import_foreign_profile', { name, filePath });
+  },
+
   /**
    * 创建空白 Profile
    */
@@ -648,6 +1409,13 @@ export const ipc = {
     return invoke('create_blank_profile', { name });
   },
 
+  /**
+   * 校验 Profile 名称，返回实际保存时使用的名称（去掉控制字符、限制长度）
+   */
+  async validateProfileName(name: string): Promise<string> {
+    return invoke('validate_profile_name', { name });
+  },
+
   /**
    * 删除 Profile
    */
@@ -662,11 +1430,33 @@ export const ipc = {
     return invoke('rename_profile', { id, newName });
   },
 
+  /**
+   * 获取 Profile 备注
+   */
+  async getProfileNotes(id: string): Promise<string> {
+    return invoke('get_profile_notes', { id });
+  },
+
+  /**
+   * 设置 Profile 备注
+   */
+  async setProfileNotes(id: string, notes: string): Promise<ProfileMetadata> {
+    return invoke('set_profile_notes', { id, notes });
+  },
+
+  /**
+   * 获取 Profile 修改记录
+   */
+  async getProfileHistory(id: string): Promise<ProfileHistoryEntry[]> {
+    return invoke('get_profile_history', { id });
+  },
+
   /**
    * 激活 Profile
+   * 指定 secondaryId 时进入多配置模式：节点和策略组来自 id，规则和规则源来自 secondaryId
    */
-  async activateProfile(id: string): Promise<void> {
-    return invoke('activate_profile', { id });
+  async activateProfile(id: string, secondaryId?: string): Promise<void> {
+    return invokeCommand('activate_profile', { id, secondaryId });
   },
 
   /**
@@ -676,6 +1466,20 @@ export const ipc = {
     return invoke('refresh_profile', { id });
   },
 
+  /**
+   * 只刷新 Profile 的规则源（不重新下载订阅）
+   */
+  async refreshProfileRulesets(id: string): Promise<RulesetRefreshResult[]> {
+    return invoke('refresh_profile_rulesets', { id });
+  },
+
+  /**
+   * 检查 Profile 引用的规则源/代理源本地文件，download 为 true 时下载缺失的 http 规则源
+   */
+  async checkProfileProviders(id: string, download?: boolean): Promise<ProviderCheck[]> {
+    return invoke('check_profile_providers', { id, download });
+  },
+
   /**
    * 解析配置文件（预览，不保存）
    */
@@ -683,6 +1487,11 @@ export const ipc = {
     return invoke('parse_config_file', { path });
   },
 
+  /** 校验本地订阅文件（不导入） */
+  async validateSubscriptionYaml(path: string): Promise<SubscriptionValidation> {
+    return invoke('validate_subscription_yaml', { path });
+  },
+
   /**
    * 预览远程配置（不保存）
    */
@@ -690,6 +1499,27 @@ export const ipc = {
     return invoke('preview_remote_config', { url });
   },
 
+  /**
+   * 比较两个 Profile 的节点、策略组和规则（aId 为基准）
+   */
+  async diffProfiles(aId: string, bId: string): Promise<ProfileDiff> {
+    return invoke('diff_profiles', { aId, bId });
+  },
+
+  /**
+   * 预览刷新远程订阅后的变化（不保存）
+   */
+  async previewProfileRefresh(id: string): Promise<ProfileDiff> {
+    return invoke('preview_profile_refresh', { id });
+  },
+
+  /**
+   * 检查远程订阅的到期时间和流量
+   */
+  async checkSubscriptionWarnings(): Promise<SubscriptionWarning[]> {
+    return invoke('check_subscription_warnings');
+  },
+
   /**
    * 导出 Profile 配置到指定路径
    */
@@ -732,6 +1562,66 @@ export const ipc = {
     return invoke('delete_proxy', { profileId, proxyName });
   },
 
+  /**
+   * 用正则替换批量重命名节点，preview 为 true 时只返回将要进行的重命名
+   */
+  async renameNodes(
+    profileId: string,
+    pattern: string,
+    replacement: string,
+    preview: boolean
+  ): Promise<NodeRename[]> {
+    return invoke('rename_nodes', { profileId, pattern, replacement, preview });
+  },
+
+  /**
+   * 按节点名称的正则表达式生成地区策略组（patterns 为策略组名称到正则的映射），
+   * preview 为 true 时只返回方案
+   */
+  async buildRegionGroups(
+    profileId: string,
+    patterns: Record<string, string>,
+    preview: boolean,
+    selectGroup?: string
+  ): Promise<RegionGroupsPlan> {
+    return invoke('build_region_groups', { profileId, patterns, selectGroup, preview });
+  },
+
+  /**
+   * 规则去重，resolveConflicts 为 true 时同时移除冲突中不会被命中的规则
+   */
+  async dedupRules(profileId: string, resolveConflicts?: boolean): Promise<RuleDedupReport> {
+    return invoke('dedup_rules', { profileId, resolveConflicts });
+  },
+
+  /**
+   * 检查被前面更宽泛的规则覆盖、永远不会命中的规则
+   */
+  async findShadowedRules(profileId: string): Promise<ShadowedRule[]> {
+    return invoke('find_shadowed_rules', { profileId });
+  },
+
+  /**
+   * 检查策略组循环引用，fix 为 true 时移除一条引用以打断循环并保存
+   */
+  async findGroupCycles(profileId: string, fix?: boolean): Promise<GroupCycle[]> {
+    return invoke('find_group_cycles', { profileId, fix });
+  },
+
+  /**
+   * 导出节点分享链接（ss / vmess / vless / trojan），proxyNames 为空时导出全部
+   */
+  async exportShareLinks(profileId: string, proxyNames?: string[]): Promise<ShareLinkExport> {
+    return invoke('export_share_links', { profileId, proxyNames });
+  },
+
+  /**
+   * 检查节点的不安全配置（跳过证书校验、弱加密、明文传输等），只读
+   */
+  async auditProfileSecurity(id: string): Promise<SecurityFinding[]> {
+    return invoke('audit_profile_security', { id });
+  },
+
   // ============= Profile 规则命令 =============
 
   /**
@@ -748,6 +1638,17 @@ export const ipc = {
     return invoke('delete_rule_from_profile', { profileId, index });
   },
 
+  /**
+   * 从远程规则列表导入规则（插入到 MATCH 之前）
+   */
+  async importRulesFromUrl(
+    profileId: string,
+    url: string,
+    targetOverride?: string
+  ): Promise<RuleImportResult> {
+    return invoke('import_rules_from_url', { profileId, url, targetOverride });
+  },
+
   /**
    * 添加 rule-provider 到 Profile
    */
@@ -796,6 +1697,35 @@ export const ipc = {
     return invoke('update_profile_config', { profileId, config });
   },
 
+  /**
+   * 将规则模板（规则、规则源、代理源、策略组）应用到多个 Profile
+   */
+  async applyTemplateToProfiles(
+    template: Partial<ProfileConfig>,
+    profileIds: string[],
+    mergeMode: TemplateMergeMode
+  ): Promise<TemplateApplyResult[]> {
+    return invoke('apply_template_to_profiles', { template, profileIds, mergeMode });
+  },
+
+  /**
+   * 列出内置规则模板
+   */
+  async listRuleTemplates(): Promise<RuleTemplateInfo[]> {
+    return invoke('list_rule_templates');
+  },
+
+  /**
+   * 将内置规则模板应用到 Profile（默认追加合并，重名的模板策略组会被重命名）
+   */
+  async applyRuleTemplate(
+    profileId: string,
+    templateId: string,
+    mergeMode?: TemplateMergeMode
+  ): Promise<TemplateApplyResult> {
+    return invoke('apply_rule_template', { profileId, templateId, mergeMode });
+  },
+
   // ============= Proxy Provider CRUD =============
 
   /**
@@ -886,6 +1816,13 @@ export const ipc = {
     return invoke('test_urls_delay', { urls, timeoutMs });
   },
 
+  /**
+   * 测量经当前代理链路访问 URL 的分段延迟（DNS / 建连 / TLS / 首字节）
+   */
+  async measureChainLatency(url: string): Promise<ChainLatency> {
+    return invoke('measure_chain_latency', { url });
+  },
+
   /**
    * 更新当前活跃 Profile 中的策略组
    */
@@ -998,6 +1935,36 @@ export const ipc = {
     return invoke('reset_all_data');
   },
 
+  /**
+   * 将配置、Profile、规则集和 Sub-Store 数据备份为单个 zip
+   * includeLarge 为 true 时同时备份 GEO 数据库和核心二进制
+   */
+  async backupDataDir(targetZip: string, includeLarge?: boolean): Promise<DataBackupResult> {
+    return invoke('backup_data_dir', { targetZip, includeLarge });
+  },
+
+  /**
+   * 从本地备份恢复数据（覆盖当前所有配置，confirm 必须为 true）
+   * 返回恢复的文件数
+   */
+  async restoreDataDir(zipPath: string, confirm: boolean): Promise<number> {
+    return invoke('restore_data_dir', { zipPath, confirm });
+  },
+
+  /**
+   * 列出崩溃报告（最新的在前）
+   */
+  async listCrashReports(): Promise<CrashReportSummary[]> {
+    return invoke('list_crash_reports');
+  },
+
+  /**
+   * 读取崩溃报告内容
+   */
+  async openCrashReport(id: string): Promise<CrashReport> {
+    return invoke('open_crash_report', { id });
+  },
+
   // ============= WebDAV 同步命令 =============
 
   /**
@@ -1007,6 +1974,13 @@ export const ipc = {
     return invoke('test_webdav_connection', { config });
   },
 
+  /**
+   * 探测可用的 WebDAV 地址（尝试 /dav、Nextcloud 等常见路径）
+   */
+  async normalizeWebDavUrl(config: WebDavConfig): Promise<WebDavEndpoint> {
+    return invoke('normalize_webdav_url', { config });
+  },
+
   /**
    * 获取 WebDAV 配置
    */
@@ -1023,9 +1997,17 @@ export const ipc = {
 
   /**
    * 上传配置到 WebDAV（强制全量上传）
+   * @param confirm 必须为 true，防止误触覆盖远端快照
+   */
+  async webDavUpload(confirm: boolean): Promise<SyncResult> {
+    return invokeCommand('webdav_upload', { confirm });
+  },
+
+  /**
+   * 取消正在进行的强制上传
    */
-  async webDavUpload(): Promise<SyncResult> {
-    return invoke('webdav_upload');
+  async cancelWebDavUpload(): Promise<void> {
+    return invoke('cancel_webdav_upload');
   },
 
   /**
@@ -1033,7 +2015,7 @@ export const ipc = {
    * @param force 是否强制下载（忽略冲突）
    */
   async webDavDownload(force: boolean = false): Promise<SyncResult> {
-    return invoke('webdav_download', { force });
+    return invokeCommand('webdav_download', { force });
   },
 
   /**
@@ -1042,7 +2024,14 @@ export const ipc = {
    * 只同步有变化的文件，提高效率。
    */
   async webDavSync(): Promise<SyncResult> {
-    return invoke('webdav_sync');
+    return invokeCommand('webdav_sync');
+  },
+
+  /**
+   * 预览增量同步（只比较不传输），返回将上传、下载、删除的文件
+   */
+  async webDavSyncPreview(): Promise<SyncPreview> {
+    return invokeCommand('webdav_sync_preview');
   },
 
   /**
@@ -1063,7 +2052,7 @@ export const ipc = {
    * 检查是否有冲突
    */
   async checkWebDavConflict(): Promise<ConflictInfo | null> {
-    return invoke('check_webdav_conflict');
+    return invokeCommand('check_webdav_conflict');
   },
 
   /**
@@ -1071,7 +2060,7 @@ export const ipc = {
    * @param choice 'local' 保留本地，'remote' 使用远端
    */
   async resolveWebDavConflict(choice: 'local' | 'remote'): Promise<SyncResult> {
-    return invoke('resolve_webdav_conflict', { choice });
+    return invokeCommand('resolve_webdav_conflict', { choice });
   },
 
   /**
@@ -1080,7 +2069,7 @@ export const ipc = {
    * @param choice 'local' 保留本地，'remote' 使用远端
    */
   async resolveFileConflict(path: string, choice: 'local' | 'remote'): Promise<void> {
-    return invoke('resolve_file_conflict', { path, choice });
+    return invokeCommand('resolve_file_conflict', { path, choice });
   },
 };
 
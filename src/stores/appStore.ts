@@ -105,6 +105,10 @@ export const useAppStore = create<AppState>((set, get) => ({
       db.id === id ? { ...db, ...updates } : db
     );
     await updateSettings({ ruleDatabases: newDatabases });
+    // 开启应用侧自动更新时后端会关闭核心 GEO 自动更新，同步本地状态
+    if (updates.autoUpdate && settings.mihomo?.geoAutoUpdate) {
+      await get().fetchSettings();
+    }
   },
 
   // 检查规则数据库更新（应用启动时调用一次）
@@ -112,8 +116,8 @@ export const useAppStore = create<AppState>((set, get) => ({
     const { settings, ruleDatabaseUpdateChecked } = get();
     const databases = settings.ruleDatabases || [];
 
-    // 如果已经检查过或没有数据库，跳过
-    if (ruleDatabaseUpdateChecked || databases.length === 0) {
+    // 如果已经检查过或没有数据库，跳过；由核心自动更新时也不再检查
+    if (ruleDatabaseUpdateChecked || databases.length === 0 || settings.mihomo?.geoAutoUpdate) {
       return;
     }
 
@@ -3,6 +3,7 @@ import { ipc } from '@/services/ipc';
 import logger from '@/utils/logger';
 import type {
   ProxyStatus,
+  CoreState,
   ProxyGroup,
   TrafficData,
   ProxyMode,
@@ -27,6 +28,8 @@ export interface ConnectionStats {
 interface ProxyState {
   // 状态
   status: ProxyStatus;
+  /** 核心运行状态（由 core-state-changed 事件推送） */
+  coreState: CoreState;
   /** 当前正在切换的模式（等待事件回执） */
   pendingMode: ProxyMode | null;
   groups: ProxyGroup[];
@@ -53,6 +56,7 @@ interface ProxyState {
 
   // 动作
   applyStatus: (status: ProxyStatus) => void;
+  applyCoreState: (coreState: CoreState) => void;
   fetchStatus: () => Promise<void>;
   start: () => Promise<void>;
   stop: () => Promise<void>;
@@ -113,6 +117,7 @@ const MAX_REQUEST_HISTORY = 500;
 
 export const useProxyStore = create<ProxyState>((set, get) => ({
   status: initialStatus,
+  coreState: 'stopped',
   pendingMode: null,
   groups: [],
   traffic: { up: 0, down: 0 },
@@ -135,6 +140,16 @@ export const useProxyStore = create<ProxyState>((set, get) => ({
     }));
   },
 
+  applyCoreState: (coreState) => {
+    set((state) => {
+      // 过渡状态保持原 running 值，避免重启期间闪烁
+      if (coreState === 'starting' || coreState === 'restarting') {
+        return { coreState };
+      }
+      return { coreState, status: { ...state.status, running: coreState === 'running' } };
+    });
+  },
+
   fetchStatus: async () => {
     try {
       const status = await ipc.getProxyStatus();
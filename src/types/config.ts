@@ -1,3 +1,12 @@
+/**
+ * macOS 按应用分流规则（Bundle ID）
+ * included 非空时只有其中的应用走代理；excluded 中的应用始终直连
+ */
+export interface AppProxyRules {
+  included: string[];
+  excluded: string[];
+}
+
 /**
  * GeoX URL 配置
  */
@@ -31,6 +40,8 @@ export interface DnsConfig {
   'enhanced-mode'?: 'normal' | 'fake-ip' | 'redir-host';
   /** Fake IP 范围 */
   'fake-ip-range'?: string;
+  /** 是否解析 AAAA 记录，应与全局 ipv6 保持一致 */
+  ipv6?: boolean;
   /** Fake IP 过滤模式: blacklist, whitelist */
   'fake-ip-filter-mode'?: 'blacklist' | 'whitelist';
   /** Fake IP 过滤列表 */
@@ -59,6 +70,38 @@ export interface DnsConfig {
   'nameserver-policy'?: Record<string, string[]>;
 }
 
+/**
+ * DoH 服务器测试结果
+ */
+export interface DohTestResult {
+  /** 解析到的 IPv4/IPv6 地址 */
+  ips: string[];
+  /** 查询耗时（毫秒） */
+  elapsedMs: number;
+}
+
+/**
+ * 一种解析方式的结果
+ */
+export interface DnsLookup {
+  /** core：核心 DNS 查询接口；proxy：经代理的 DoH 查询；system：系统解析器 */
+  source: 'core' | 'proxy' | 'system';
+  ips: string[];
+  elapsedMs: number;
+  error?: string;
+}
+
+/**
+ * 核心 DNS 与系统 DNS 的解析结果对比
+ */
+export interface DnsComparison {
+  domain: string;
+  core: DnsLookup;
+  system: DnsLookup;
+  /** 两边都解析成功但没有任何相同地址（可能是 DNS 污染或分区解析） */
+  mismatch: boolean;
+}
+
 /**
  * TUN 配置
  */
@@ -74,6 +117,12 @@ export interface TunConfig {
    * 显式排除内网网段，即使在全局模式下这些 IP 也不经过代理
    */
   'inet4-route-exclude-address'?: string[];
+  /** 路由排除地址列表（IPv4/IPv6 CIDR），如 VPN 网段、局域网设备 */
+  'route-exclude-address'?: string[];
+  /** 路由包含地址列表（CIDR），非空时只有这些网段经过 TUN */
+  'route-address'?: string[];
+  /** TUN 网卡的 IPv6 地址，为空时 TUN 不处理 IPv6 流量 */
+  'inet6-address'?: string[];
 }
 
 /**
@@ -102,20 +151,43 @@ export interface SnifferConfig {
   'skip-domain'?: string[];
 }
 
+/**
+ * 核心 script 配置（mode: script 时由脚本决定每个连接的策略）
+ */
+export interface ScriptConfig {
+  /** 脚本代码，需定义 main(ctx, metadata) 并返回策略名称 */
+  code?: string;
+  /** 可在规则中以 SCRIPT,名称,策略 引用的表达式 */
+  shortcuts?: Record<string, string>;
+}
+
 export interface MihomoConfig {
   port: number;
   'socks-port': number;
   'mixed-port': number;
   'allow-lan': boolean;
+  'bind-address'?: string;
+  'lan-allowed-ips'?: string[];
   ipv6?: boolean;
   'tcp-concurrent'?: boolean;
   /** 域名嗅探配置（mihomo 使用 sniffer 配置块） */
   sniffer?: SnifferConfig;
   mode: string;
+  /** mode: script 使用的脚本 */
+  script?: ScriptConfig;
   'log-level': string;
   'external-controller': string;
   secret: string;
   'find-process-mode'?: string;
+  'interface-name'?: string;
+  'routing-mark'?: number;
+  'keep-alive-interval'?: number;
+  'keep-alive-idle'?: number;
+  'disable-keep-alive'?: boolean;
+  'global-ua'?: string;
+  'etag-support'?: boolean;
+  'external-ui'?: string;
+  'external-ui-url'?: string;
   // GeoData 相关配置
   'geodata-mode'?: boolean;
   'geodata-loader'?: string;
@@ -124,11 +196,56 @@ export interface MihomoConfig {
   'geox-url'?: GeoxUrl;
   tun?: TunConfig;
   dns?: DnsConfig;
+  profile?: {
+    'store-selected'?: boolean;
+    'store-fake-ip'?: boolean;
+  };
   proxies: ProxyConfig[];
   'proxy-groups': ProxyGroupConfig[];
   rules: string[];
 }
 
+/**
+ * 配置校验问题
+ */
+export interface ValidationIssue {
+  /** 字段路径，如 `mixed-port`、`proxy-groups[0].use` */
+  fieldPath: string;
+  severity: 'error' | 'warning';
+  message: string;
+}
+
+/**
+ * 核心配置测试中的一条错误
+ */
+export interface CoreConfigError {
+  message: string;
+  /** 出错的行号（从 1 开始），核心未给出时为 null */
+  line: number | null;
+  column: number | null;
+  /** 出错的字段，如 `proxies[3]`、`proxy-groups[2]` */
+  field: string | null;
+}
+
+/**
+ * 核心配置测试结果（mihomo -t）
+ */
+export interface CoreConfigTestResult {
+  success: boolean;
+  errors: CoreConfigError[];
+  /** 核心不支持 -t，结果来自应用内的 YAML 解析 */
+  fallback: boolean;
+}
+
+/**
+ * 遗留配置备份（config.yaml.bak）的检查结果
+ * - none: 没有遗留备份
+ * - orphaned: 当前配置可用，备份已无用
+ * - restorable: 当前配置不可用而备份可用
+ * - unusable: 当前配置与备份都不可用
+ */
+export type StaleBackupStatus = 'none' | 'orphaned' | 'restorable' | 'unusable';
+
 /**
  * 代理节点配置
  */
@@ -280,7 +397,7 @@ export function buildRule(type: RuleType, payload: string, policy: string): stri
 /**
  * Profile 类型
  */
-export type ProfileType = 'remote' | 'local' | 'blank';
+export type ProfileType = 'remote' | 'local' | 'blank' | 'local_watch';
 
 /**
  * Profile 元数据
@@ -291,6 +408,8 @@ export interface ProfileMetadata {
   profileType: ProfileType;
   /** 远程订阅 URL（仅 remote 类型） */
   url?: string;
+  /** 监听的源文件路径（仅 local_watch 类型） */
+  sourcePath?: string;
   createdAt: string;
   updatedAt: string;
   proxyCount: number;
@@ -302,11 +421,271 @@ export interface ProfileMetadata {
   providerProxyCounts?: Record<string, number>;
   /** 是否自动生成默认规则（远程订阅且无规则时） */
   defaultRulesApplied?: boolean;
+  /** 是否已应用订阅建议的默认节点（只在首次启用时应用） */
+  defaultSelectionApplied?: boolean;
+  /** 按地区生成的策略组名称，再次生成时只覆盖这些策略组 */
+  regionGroups?: string[];
   active: boolean;
+  /** 多配置模式下是否作为副 Profile 激活（提供规则和规则源） */
+  secondaryActive?: boolean;
   /** 自动更新（仅 remote 类型） */
   autoUpdate?: boolean;
   /** 更新间隔（小时，仅 remote 类型） */
   updateInterval?: number;
+  /** 用户备注 */
+  notes: string;
+  /** 修改记录（按时间顺序） */
+  history: ProfileHistoryEntry[];
+  /** 订阅用量（远程订阅响应头 subscription-userinfo） */
+  subscription?: SubscriptionUsage;
+}
+
+/**
+ * 订阅用量与到期时间
+ */
+export interface SubscriptionUsage {
+  upload: number;
+  download: number;
+  total: number;
+  /** 到期时间（Unix 秒时间戳） */
+  expire?: number;
+}
+
+/**
+ * 订阅提醒（subscription-warning 事件）
+ */
+export interface SubscriptionWarning {
+  profileId: string;
+  profileName: string;
+  kind: 'expire' | 'usage';
+  message: string;
+  expire?: number;
+  usagePercent?: number;
+}
+
+/**
+ * Profile 修改记录
+ */
+export interface ProfileHistoryEntry {
+  timestamp: string;
+  description: string;
+}
+
+/**
+ * 外部目录导入结果
+ */
+export interface ExternalImportResult {
+  imported: ProfileMetadata[];
+  skipped: { path: string; reason: string }[];
+}
+
+/**
+ * Surge / QuantumultX 配置导入结果
+ * warnings 为无法转换而跳过的行
+ */
+export interface ForeignImportResult {
+  profile: ProfileMetadata;
+  warnings: string[];
+}
+
+/**
+ * 订阅文件校验结果
+ * errors 为无法导入的解析错误，warnings 为不影响导入的引用问题
+ */
+export interface SubscriptionValidation {
+  valid: boolean;
+  errors: string[];
+  warnings: string[];
+  proxyCount: number;
+  groupCount: number;
+  ruleCount: number;
+}
+
+/**
+ * 从规则列表 URL 导入规则的结果
+ */
+export interface RuleImportResult {
+  added: number;
+  skipped: number;
+  duplicates: number;
+  warnings: string[];
+}
+
+/**
+ * 规则模板合并方式：append 插入到 MATCH 之前，replace 整体替换模板中非空的部分
+ */
+export type TemplateMergeMode = 'append' | 'replace';
+
+/**
+ * 规则模板应用到单个 Profile 的结果
+ */
+export interface TemplateApplyResult {
+  profileId: string;
+  /** 是否已保存（出现新的校验警告时不保存） */
+  applied: boolean;
+  /** 应用后新出现的校验警告 */
+  warnings: string[];
+  /** 与已有策略组重名而被重命名的模板策略组 */
+  renamedGroups: string[];
+  error: string | null;
+}
+
+/**
+ * 内置规则模板
+ */
+export interface RuleTemplateInfo {
+  id: string;
+  name: string;
+  description: string;
+  ruleCount: number;
+  ruleProviderCount: number;
+}
+
+/**
+ * 节点重命名
+ */
+export interface NodeRename {
+  oldName: string;
+  newName: string;
+}
+
+/**
+ * 按地区生成的 url-test 策略组
+ */
+export interface RegionGroup {
+  name: string;
+  /** 名称匹配该地区的节点 */
+  proxies: string[];
+  /** 引用的代理源（节点在运行时由核心按 filter 筛选） */
+  providers: string[];
+  /** 代理源节点的名称过滤，没有代理源时为 null */
+  filter: string | null;
+}
+
+/**
+ * 按地区生成策略组的方案
+ */
+export interface RegionGroupsPlan {
+  /** 列出所有地区组的 select 策略组 */
+  selectGroup: string;
+  groups: RegionGroup[];
+  /** 没有匹配任何地区的节点（代理源中的节点在运行时才知道，不在此列） */
+  unmatched: string[];
+}
+
+/**
+ * 同一匹配条件指向不同策略的规则
+ */
+export interface RuleConflict {
+  /** 匹配条件，如 DOMAIN-SUFFIX,google.com */
+  matcher: string;
+  /** 最先出现、实际生效的规则 */
+  effective: string;
+  /** 之后出现的冲突规则（不会被命中） */
+  shadowed: string[];
+}
+
+/**
+ * 规则去重报告
+ */
+export interface RuleDedupReport {
+  /** 已移除的规则 */
+  removed: string[];
+  conflicts: RuleConflict[];
+}
+
+/**
+ * 被前面更宽泛的规则完全覆盖、永远不会命中的规则
+ */
+export interface ShadowedRule {
+  shadowingIndex: number;
+  shadowingRule: string;
+  shadowedIndex: number;
+  shadowedRule: string;
+}
+
+/**
+ * 策略组循环引用
+ */
+export interface GroupCycle {
+  /** 循环中的策略组，依次引用下一个，最后一个引用第一个 */
+  groups: string[];
+}
+
+/**
+ * 配置差异中的一项
+ */
+export interface ConfigDiffItem {
+  name: string;
+  /** 节点为「类型 地址:端口」，策略组为变更说明 */
+  detail: string | null;
+}
+
+export interface ConfigDiffSection {
+  added: ConfigDiffItem[];
+  removed: ConfigDiffItem[];
+  changed: ConfigDiffItem[];
+}
+
+/**
+ * 两个 Profile 的差异
+ */
+export interface ProfileDiff {
+  proxies: ConfigDiffSection;
+  proxyGroups: ConfigDiffSection;
+  rules: ConfigDiffSection;
+}
+
+/**
+ * 单个规则源的刷新结果
+ */
+export interface RulesetRefreshResult {
+  name: string;
+  /** 是否下载了新内容（false 表示未变化或失败） */
+  updated: boolean;
+  error: string | null;
+}
+
+/**
+ * 规则源/代理源本地文件的检查结果（只包含有问题的项）
+ */
+export interface ProviderCheck {
+  name: string;
+  kind: 'rule' | 'proxy';
+  providerType: string;
+  /** 修正后的本地路径 */
+  path: string | null;
+  status: 'missing' | 'downloaded' | 'download_failed';
+  /** 处理建议或下载失败原因 */
+  message: string;
+}
+
+/**
+ * 导出分享链接的结果
+ */
+export interface ShareLinkExport {
+  links: { name: string; link: string }[];
+  /** 所有链接按行拼接 */
+  text: string;
+  /** 不支持导出的节点 */
+  skipped: string[];
+}
+
+/**
+ * 节点安全问题的严重程度
+ */
+export type SecuritySeverity = 'low' | 'medium' | 'high';
+
+/**
+ * 节点安全问题
+ */
+export interface SecurityFinding {
+  /** 节点名称 */
+  proxy: string;
+  severity: SecuritySeverity;
+  /** skip-cert-verify / weak-cipher / missing-sni / plaintext */
+  kind: string;
+  message: string;
 }
 
 /**
@@ -350,6 +729,8 @@ export interface ProfileConfig {
   'proxy-providers': Record<string, ProxyProvider>;
   'rule-providers': Record<string, RuleProvider>;
   rules: string[];
+  /** 子规则，由 SUB-RULE 规则引用（为空时省略） */
+  'sub-rules'?: Record<string, string[]>;
 }
 
 /**
@@ -428,6 +809,17 @@ export interface ConflictInfo {
   conflictItems?: ConflictItem[];
 }
 
+/**
+ * 强制上传进度（webdav-upload-progress 事件）
+ */
+export interface UploadProgress {
+  /** 阶段：pack 打包本地文件，upload 上传远端文件 */
+  stage: 'pack' | 'upload';
+  file: string;
+  current: number;
+  total: number;
+}
+
 /**
  * 同步结果
  */
@@ -450,6 +842,34 @@ export interface SyncResult {
   conflictInfo?: ConflictInfo;
 }
 
+/**
+ * 探测到的可用 WebDAV 地址
+ */
+export interface WebDavEndpoint {
+  /** 可用的 WebDAV 地址（无末尾斜杠） */
+  url: string;
+  /** 识别到的服务商 */
+  provider?: string | null;
+  /** 是否与输入的地址不同 */
+  corrected: boolean;
+}
+
+/**
+ * 同步预览（只比较不传输）
+ */
+export interface SyncPreview {
+  action: 'none' | 'upload' | 'download' | 'conflict';
+  message: string;
+  toUpload: string[];
+  toDownload: string[];
+  /** 下载时会被删除的本地文件 */
+  toDeleteLocal: string[];
+  /** 上传时会从远端快照中移除的文件 */
+  toDeleteRemote: string[];
+  hasConflict: boolean;
+  conflictInfo?: ConflictInfo;
+}
+
 /**
  * MiHomo 用户设置（存储在 settings.json 中）
  */
@@ -462,16 +882,76 @@ export interface MihomoSettings {
   mixedPort: number;
   /** 允许局域网连接 */
   allowLan: boolean;
+  /** 局域网共享监听地址，未设置时为 * */
+  bindAddress?: string | null;
+  /** 允许访问的局域网网段（CIDR），为空时不限制 */
+  lanAllowedIps?: string[];
+  /** script 模式使用的脚本 */
+  script?: ScriptConfig | null;
   /** 启用 IPv6 */
   ipv6: boolean;
   /** TCP 并发 */
   tcpConcurrent: boolean;
   /** 进程查找模式 */
   findProcessMode: string;
+  /** 出站绑定网卡，未设置时由核心自动选择 */
+  interfaceName?: string | null;
+  /** 出站 routing-mark（仅 Linux） */
+  routingMark?: number | null;
+  /** TCP keep-alive 探测间隔（秒） */
+  keepAliveInterval?: number | null;
+  /** TCP keep-alive 空闲时间（秒） */
+  keepAliveIdle?: number | null;
+  /** 关闭 TCP keep-alive */
+  disableKeepAlive?: boolean;
+  /** 核心下载资源的 User-Agent，未设置时使用核心默认值 */
+  globalUa?: string | null;
+  /** Provider 下载的 ETag 支持，未设置时使用核心默认值（开启） */
+  etagSupport?: boolean | null;
+  /** 覆盖所有 proxy-provider 的健康检查间隔（秒），未设置时使用 Profile 中的值 */
+  providerHealthCheckInterval?: number | null;
+  /** 由核心自动更新 GEO 数据（开启后应用不再自动更新规则数据库） */
+  geoAutoUpdate?: boolean;
+  /** 核心自动更新 GEO 数据的间隔（小时） */
+  geoUpdateInterval?: number | null;
+  /** HTTPS 外部控制器监听地址，设置后通过 HTTPS 连接核心 */
+  externalControllerTls?: string | null;
+  /** 控制器 TLS 证书路径（PEM） */
+  tlsCertificate?: string | null;
+  /** 控制器 TLS 私钥路径（PKCS#8 PEM） */
+  tlsPrivateKey?: string | null;
+  /** 面板目录，安装面板后设置 */
+  externalUi?: string | null;
+  /** 面板下载地址 */
+  externalUiUrl?: string | null;
   /** TUN 模式配置 */
   tun: TunConfig;
   /** DNS 配置 */
   dns: DnsConfig;
+  /** 域名嗅探配置 */
+  sniffer?: SnifferConfig;
+  /** 由核心持久化策略组选中的节点 */
+  storeSelected?: boolean;
+}
+
+/**
+ * 局域网共享设置
+ */
+export interface LanAccessSettings {
+  allowLan: boolean;
+  bindAddress: string | null;
+  allowedIps: string[];
+}
+
+/**
+ * 端口冲突
+ */
+export interface PortConflict {
+  port: number;
+  /** duplicate: 多个服务配置了同一端口；in_use: 已被其他程序占用；unavailable: 无法检测 */
+  kind: 'duplicate' | 'in_use' | 'unavailable';
+  services: string[];
+  message: string;
 }
 
 /**
@@ -482,15 +962,219 @@ export interface AppSettings {
   autoStart: boolean;
   systemProxy: boolean;
   closeToTray: boolean;
+  /** 启动时不显示主窗口，只显示托盘图标 */
+  startMinimized?: boolean;
   /** 使用 JsDelivr 加速 GitHub 资源 */
   useJsdelivr?: boolean;
   ruleDatabases: RuleDatabaseItem[];
   /** WebDAV 同步配置 */
   webdav?: WebDavConfig;
+  /** 自动切换最快节点 */
+  autoSwitch?: AutoSwitchSettings;
+  /** 连接数上限 */
+  connectionLimit?: ConnectionLimitSettings;
+  /** 延迟着色阈值 */
+  latencyThresholds?: LatencyThresholds;
+  /** 策略组选择预设 */
+  selectionPresets?: SelectionPreset[];
+  /** 按时段选择节点 */
+  selectionSchedule?: SelectionScheduleSettings;
+  /** 订阅到期/流量提醒 */
+  subscriptionAlert?: SubscriptionAlertSettings;
+  /** 允许订阅更新为空（默认拒绝用 0 个节点覆盖已有节点） */
+  allowEmptySubscription?: boolean;
+  /** 切换配置后自动预热远程 Provider */
+  warmProvidersOnActivate?: boolean;
+  /** 首次启用订阅时应用其建议的默认节点（select 策略组的 default 字段，默认开启） */
+  applySuggestedSelection?: boolean;
+  /** 激活配置、保存配置或切换 TUN 模式出现内部错误时生成崩溃报告（已脱敏） */
+  crashReportsEnabled?: boolean;
+  /** 日志/连接数据流断开后的重连间隔（秒），连续失败时翻倍退避 */
+  streamReconnectInterval?: number;
+  /** 核心进程优先级（启动和重启核心时应用） */
+  corePriority?: CorePriority;
+  /** macOS 按应用分流规则（Network Extension） */
+  appProxyRules?: AppProxyRules;
+  /** 网络切换后的处理策略 */
+  networkChangePolicy?: NetworkChangePolicy;
+  /** 诊断数据的内存限制 */
+  observability?: ObservabilitySettings;
   /** MiHomo 用户设置 */
   mihomo?: MihomoSettings;
 }
 
+/**
+ * 策略组选择预设
+ */
+export interface SelectionPreset {
+  name: string;
+  /** 策略组名称 -> 选中的节点 */
+  selections: Record<string, string>;
+  savedAt: string;
+}
+
+/**
+ * 应用选择预设的结果
+ */
+export interface SelectionPresetResult {
+  /** 已恢复选择的策略组 */
+  applied: string[];
+  skipped: { group: string; proxy: string; reason: string }[];
+}
+
+/**
+ * 重置节点选择的结果
+ */
+export interface SelectionResetResult {
+  changed: { group: string; from: string | null; to: string }[];
+  skipped: { group: string; proxy: string; reason: string }[];
+}
+
+/**
+ * 按时段选择节点的规则（本地时间 HH:MM，结束早于开始表示跨天）
+ */
+export interface SelectionScheduleEntry {
+  start: string;
+  end: string;
+  group: string;
+  proxy: string;
+}
+
+/**
+ * 按时段选择节点配置
+ */
+export interface SelectionScheduleSettings {
+  enabled: boolean;
+  entries: SelectionScheduleEntry[];
+}
+
+/**
+ * 按时段切换节点事件（selection-schedule-applied）
+ */
+export interface SelectionScheduleEvent {
+  group: string;
+  proxy: string;
+  /** 命中的时段，如 09:00-18:00 */
+  window: string;
+  /** 是否已切换；策略组或节点不存在时为 false */
+  applied: boolean;
+  error: string | null;
+}
+
+/**
+ * 订阅到期/流量提醒配置
+ */
+export interface SubscriptionAlertSettings {
+  enabled: boolean;
+  /** 距到期不足该天数时提醒 */
+  expireDays: number;
+  /** 已用流量超过该百分比时提醒 */
+  usagePercent: number;
+}
+
+/**
+ * 自动切换最快节点配置
+ */
+export interface AutoSwitchSettings {
+  enabled: boolean;
+  /** 目标策略组（select 类型） */
+  group: string;
+  /** 测速间隔（秒） */
+  intervalSecs: number;
+  /** 切换阈值（毫秒） */
+  thresholdMs: number;
+  /** 测速地址 */
+  testUrl: string;
+}
+
+/**
+ * 自动切换事件（auto-switch-changed）
+ */
+export interface AutoSwitchEvent {
+  group: string;
+  from: string | null;
+  to: string;
+  delay: number;
+  previousDelay: number | null;
+}
+
+/**
+ * 核心进程优先级：提高优先级需要管理员权限
+ */
+export type CorePriority = 'low' | 'normal' | 'high';
+
+/**
+ * 连接数超限时的处理方式：warn 只提醒，close-idle 关闭最早建立的空闲连接
+ */
+export type ConnectionLimitAction = 'warn' | 'close-idle';
+
+/**
+ * 连接数上限配置
+ */
+export interface ConnectionLimitSettings {
+  enabled: boolean;
+  /** 连接总数阈值 */
+  threshold: number;
+  action: ConnectionLimitAction;
+}
+
+/**
+ * 延迟着色阈值（毫秒）：低于 good 为绿色，低于 medium 为黄色，其余为红色
+ */
+export interface LatencyThresholds {
+  good: number;
+  medium: number;
+}
+
+/**
+ * 连接数超限事件（connection-limit-exceeded）
+ */
+export interface ConnectionLimitEvent {
+  total: number;
+  threshold: number;
+  /** 已关闭的空闲连接数 */
+  closed: number;
+}
+
+/**
+ * 网络切换（Wi-Fi/有线切换、VPN 连接或断开）后的处理策略
+ */
+export interface NetworkChangePolicy {
+  enabled: boolean;
+  /** 系统代理已开启时重新应用到当前网络服务 */
+  reapplySystemProxy: boolean;
+  /** 绑定的出站网卡不可用时恢复为自动选择 */
+  redetectInterface: boolean;
+  /** 清除核心 DNS 缓存 */
+  flushDns: boolean;
+}
+
+/**
+ * 诊断数据（流量历史、规则命中、连接追踪、日志缓存）的内存限制
+ */
+export interface ObservabilitySettings {
+  /** 总内存预算（MB），超出时丢弃最早的数据 */
+  memoryBudgetMb: number;
+  /** 流量历史保留时长（分钟） */
+  trafficRetentionMinutes: number;
+  /** 是否统计规则命中次数（默认关闭） */
+  ruleHitCounting?: boolean;
+}
+
+/**
+ * 网络切换事件（network-changed）
+ */
+export interface NetworkChangedEvent {
+  /** 切换后可用的网卡 */
+  interfaces: string[];
+  proxyReapplied: boolean;
+  /** 因不可用而临时恢复为自动选择的绑定网卡 */
+  interfaceReset: string | null;
+  /** 重新连接后恢复绑定的网卡 */
+  interfaceRestored: string | null;
+  dnsFlushed: boolean;
+}
+
 /**
  * 规则数据库配置
  */
@@ -583,6 +1267,18 @@ export const DEFAULT_RULE_DATABASES: RuleDatabaseItem[] = [
   },
 ];
 
+/**
+ * 窗口与托盘的启动行为
+ */
+export interface WindowBehavior {
+  /** 开机自启动 */
+  autoStart: boolean;
+  /** 启动时最小化到托盘 */
+  startMinimized: boolean;
+  /** 关闭主窗口时隐藏到托盘而不是退出 */
+  closeToTray: boolean;
+}
+
 /**
  * 默认应用设置
  */
@@ -601,3 +1297,67 @@ export interface AppVersionInfo {
   version: string;
   coreVersion: string;
 }
+
+/**
+ * 进入安全模式的原因：requested 为通过 CONFLUX_SAFE_MODE 手动要求，launchFailures 为核心连续多次启动失败
+ */
+export type SafeModeReason = 'requested' | 'launchFailures';
+
+/**
+ * 本次启动各阶段的耗时
+ */
+export interface StartupTimings {
+  /** 初始化应用状态的总耗时（不含后台启动的 Sub-Store），完成前为 null */
+  initMs: number | null;
+  phases: {
+    name: 'geodata' | 'config' | 'secret' | 'core-status' | 'substore';
+    durationMs: number;
+  }[];
+}
+
+/**
+ * 数据目录信息
+ */
+export interface DataDirInfo {
+  /** 当前使用的数据目录 */
+  dataDir: string;
+  /** 覆盖目录（CONFLUX_DATA_DIR 或设置中指定），未覆盖时为 null */
+  overrideDir: string | null;
+  /** 覆盖来自环境变量（此时设置中的目录不生效） */
+  fromEnv: boolean;
+}
+
+/**
+ * 本地数据备份结果
+ */
+export interface DataBackupResult {
+  path: string;
+  files: number;
+  /** 备份文件大小（字节） */
+  size: number;
+}
+
+/**
+ * 崩溃报告列表项
+ */
+export interface CrashReportSummary {
+  id: string;
+  /** 生成时间（Unix 毫秒时间戳） */
+  createdAt: number;
+  /** 出错的命令 */
+  command: string;
+  error: string;
+}
+
+/**
+ * 崩溃报告（已脱敏）
+ */
+export interface CrashReport extends CrashReportSummary {
+  appVersion: string;
+  os: string;
+  arch: string;
+  coreState: string;
+  activeProfileId: string | null;
+  /** 最近的核心日志和应用日志 */
+  logs: string[];
+}
@@ -11,9 +11,46 @@ export interface LocalIpInfo {
   ipv6: string[];
 }
 
+export interface NetworkInterface {
+  name: string;
+  addresses: string[];
+  /** 是否已启用并连接，null 表示无法获取 */
+  up: boolean | null;
+  isLoopback: boolean;
+}
+
+/** 门户检测结果：online 正常联网，portal 需要网页认证，unreachable 请求失败 */
+export type CaptiveProbeResult = 'online' | 'portal' | 'unreachable';
+
+export interface CaptivePortalStatus {
+  /** 不经过代理的检测结果 */
+  direct: CaptiveProbeResult;
+  /** 经过核心代理端口的检测结果，核心未运行时为 null */
+  proxied: CaptiveProbeResult | null;
+  portalDetected: boolean;
+  /** 门户重定向地址 */
+  portalUrl: string | null;
+  /** 是否正在绕过门户 */
+  bypassActive: boolean;
+}
+
 export interface NetworkExtensionStatus {
   supported: boolean;
   installed: boolean;
   enabled: boolean;
   message: string;
 }
+
+/** 开启系统代理前的系统代理配置 */
+export interface PreviousSystemProxy {
+  enabled: boolean;
+  host: string;
+  port: number;
+  /** 不走代理的地址 */
+  bypass: string[];
+  socksEnabled: boolean;
+  socksHost: string;
+  socksPort: number;
+  /** Windows 原始 ProxyServer 值 */
+  server?: string;
+}
@@ -3,6 +3,43 @@
  */
 export type RunMode = 'normal' | 'service' | 'admin_win' | 'helper_mac';
 
+/**
+ * 代理接入状态：未运行 / 仅代理端口 / 已接管系统流量（系统代理或 TUN）
+ */
+export type ProxyIntegration = 'off' | 'proxy_only' | 'system_integrated';
+
+/**
+ * 核心运行状态（core-state-changed 事件）
+ */
+export type CoreState = 'stopped' | 'starting' | 'running' | 'restarting' | 'crashed';
+
+/**
+ * 外部进程
+ */
+export interface ExternalProcess {
+  pid: number;
+  name: string;
+}
+
+/**
+ * 外部核心检测结果（启动时检测到会发送 external-core-detected 事件）
+ */
+export interface ExternalCoreReport {
+  /** 被其他进程占用的 Conflux 端口 */
+  portConflicts: {
+    port: number;
+    purpose: 'controller' | 'mixed' | 'http' | 'socks';
+    process: ExternalProcess;
+  }[];
+  /** 不是由 Conflux 启动的 mihomo/Clash 核心进程 */
+  processes: ExternalProcess[];
+}
+
+/**
+ * 发现外部核心时的处理方式：接管（结束外部进程）或共存
+ */
+export type ExternalCoreAction = 'takeOver' | 'coexist';
+
 /**
  * 代理状态
  */
@@ -18,6 +55,18 @@ export interface ProxyStatus {
   ipv6?: boolean;
   tcp_concurrent?: boolean;
   run_mode?: RunMode;
+  integration?: ProxyIntegration;
+}
+
+/**
+ * 与核心运行时状态对齐的结果
+ */
+export interface StateReconcile {
+  /** 发现并修正的差异，如 mode: rule -> global */
+  changes: string[];
+  /** 核心当前各策略组选中的节点 */
+  selections: Record<string, string>;
+  status: ProxyStatus;
 }
 
 /**
@@ -39,6 +88,9 @@ export interface ProxyGroup {
   type: string;
   now?: string;
   all: string[];
+  /** 策略组配置了 hidden: true */
+  hidden: boolean;
+  icon?: string;
 }
 
 /**
@@ -99,6 +151,69 @@ export interface ConnectionsResponse {
   uploadTotal: number;
 }
 
+/**
+ * 单独测试节点的结果
+ */
+export interface StandaloneNodeTest {
+  reachable: boolean;
+  /** 延迟（毫秒），不可达时为 null */
+  delay: number | null;
+  /** 不可达的原因 */
+  error: string | null;
+}
+
+/**
+ * 经代理链路的分段延迟（毫秒）
+ */
+export interface ChainLatency {
+  url: string;
+  /** 核心 DNS 解析耗时（目标为 IP 或查询失败时为空） */
+  dnsMs: number | null;
+  /** 经混合端口 CONNECT 到目标的耗时（包含节点建连及中转） */
+  connectMs: number;
+  /** TLS 握手耗时（仅 https） */
+  tlsMs: number | null;
+  ttfbMs: number;
+  totalMs: number;
+  status: number;
+}
+
+/**
+ * 连接分页排序字段
+ */
+export type ConnectionSortKey = 'upload' | 'download' | 'duration' | 'host';
+
+/**
+ * 分页后的连接列表（total 为过滤后的总数）
+ */
+export interface ConnectionsPage {
+  connections: Connection[];
+  total: number;
+  downloadTotal: number;
+  uploadTotal: number;
+}
+
+/**
+ * 规则影响预估：当前活跃连接中会被该规则命中的连接
+ */
+export interface RuleImpact {
+  /** 规则目标，规则未写目标时为空 */
+  target: string;
+  matched: Connection[];
+  /** 当前活跃连接总数 */
+  total: number;
+}
+
+/**
+ * Provider 预热结果
+ */
+export interface ProviderWarmResult {
+  name: string;
+  kind: 'proxy' | 'rule';
+  success: boolean;
+  error?: string | null;
+}
+
 /**
  * 代理模式类型
  */
@@ -135,4 +250,128 @@ export interface RulesResponse {
 export interface VersionInfo {
   version: string;
   meta: boolean;
+  /** 固定的核心版本（本机设置） */
+  pinnedVersion?: string | null;
+}
+
+/**
+ * 核心配置能力
+ */
+export interface CoreConfigSchema {
+  version: string;
+  /** 功能名 -> 是否支持 */
+  capabilities: Record<string, boolean>;
+  /** 当前运行时配置（/configs） */
+  config: Record<string, unknown>;
+}
+
+/**
+ * 临时直连状态
+ */
+export interface TemporaryDirectStatus {
+  active: boolean;
+  /** 到期后恢复的模式 */
+  previousMode: string | null;
+  /** 到期时间（Unix 毫秒时间戳） */
+  expiresAt: number | null;
+}
+
+/**
+ * 连接追踪记录的一条新连接
+ */
+export interface TraceEntry {
+  id: string;
+  /** 首次观察到的时间（Unix 毫秒时间戳） */
+  seenAt: number;
+  host: string;
+  destinationIp: string;
+  destinationPort: string;
+  network: string;
+  /** 入站类型（HTTP / Socks5 / Tun 等） */
+  connType: string;
+  process: string | null;
+  rule: string;
+  rulePayload: string;
+  /** 代理链（从最终出站到策略组） */
+  chains: string[];
+}
+
+/**
+ * 连接追踪状态
+ */
+export interface TraceStatus {
+  active: boolean;
+  /** 开始时间（Unix 毫秒时间戳） */
+  startedAt: number | null;
+  /** 自动结束时间（Unix 毫秒时间戳） */
+  expiresAt: number | null;
+  /** 已记录的连接数 */
+  entries: number;
+}
+
+/**
+ * 单条规则的命中次数
+ */
+export interface RuleHitCount {
+  /** 规则，如 DomainSuffix,google.com、Match */
+  rule: string;
+  hits: number;
+}
+
+/**
+ * 规则命中统计（本次核心会话）
+ */
+export interface RuleHitStats {
+  /** 是否正在统计 */
+  enabled: boolean;
+  /** 统计开始时间（Unix 毫秒时间戳） */
+  since: number;
+  /** 统计到的连接总数 */
+  total: number;
+  /** 按命中次数降序 */
+  rules: RuleHitCount[];
+}
+
+/**
+ * 单个诊断数据缓冲区的占用
+ */
+export interface BufferUsage {
+  name: 'traffic' | 'logs' | 'trace' | 'rule-hits';
+  entries: number;
+  /** 估算的内存占用（字节） */
+  bytes: number;
+}
+
+/**
+ * 诊断数据的内存占用
+ */
+export interface ObservabilityStats {
+  budgetBytes: number;
+  totalBytes: number;
+  buffers: BufferUsage[];
+}
+
+/**
+ * TUN 诊断状态
+ */
+export type TunDiagnosisStatus =
+  | 'core_not_running'
+  | 'tun_disabled'
+  | 'interface_missing'
+  | 'routes_missing'
+  | 'traffic_not_captured'
+  | 'working';
+
+/**
+ * TUN 诊断报告
+ */
+export interface TunDiagnosis {
+  status: TunDiagnosisStatus;
+  interfaceName: string | null;
+  interfaceAddress: string | null;
+  /** 是否存在指向 TUN 的路由 */
+  routesFound: boolean;
+  /** 测试连接是否经过 TUN（未执行测试时为 null） */
+  testCaptured: boolean | null;
+  message: string;
 }
@@ -77,6 +77,12 @@ describe('getDelayColorClass', () => {
     const result = getDelayColorClass(null);
     expect(result).toContain('gray');
   });
+
+  it('应该使用自定义阈值', () => {
+    const thresholds = { good: 50, medium: 100 };
+    expect(getDelayColorClass(80, thresholds)).toContain('amber');
+    expect(getDelayColorClass(150, thresholds)).toContain('red');
+  });
 });
 
 describe('formatDuration', () => {
@@ -1,3 +1,5 @@
+import type { LatencyThresholds } from '@/types/config';
+
 /**
  * 格式化字节数为人类可读的格式
  */
@@ -30,20 +32,46 @@ export function formatDelay(delay: number | null | undefined): string {
   return `${delay} ms`;
 }
 
+/**
+ * 默认延迟着色阈值（与后端 LatencyThresholds 默认值一致）
+ */
+export const DEFAULT_LATENCY_THRESHOLDS: LatencyThresholds = { good: 200, medium: 500 };
+
+/**
+ * 延迟等级：none 表示未测速或超时
+ */
+export type DelayLevel = 'none' | 'good' | 'medium' | 'bad';
+
+/**
+ * 按阈值划分延迟等级
+ * @param thresholds 设置中的延迟着色阈值
+ */
+export function getDelayLevel(
+  delay: number | null | undefined,
+  thresholds: LatencyThresholds = DEFAULT_LATENCY_THRESHOLDS
+): DelayLevel {
+  if (delay === null || delay === undefined || delay < 0) return 'none';
+  if (delay < thresholds.good) return 'good';
+  if (delay < thresholds.medium) return 'medium';
+  return 'bad';
+}
+
+const DELAY_COLOR_CLASSES: Record<DelayLevel, string> = {
+  none: 'bg-gray-100 text-gray-500 dark:bg-zinc-800 dark:text-gray-400',
+  good: 'bg-emerald-500/10 text-emerald-600 dark:text-emerald-400',
+  medium: 'bg-amber-500/10 text-amber-600 dark:text-amber-400',
+  bad: 'bg-red-500/10 text-red-600 dark:text-red-400',
+};
+
 /**
  * 获取延迟对应的颜色类名
+ * @param thresholds 设置中的延迟着色阈值
  */
-export function getDelayColorClass(delay: number | null | undefined): string {
-  if (delay === null || delay === undefined || delay < 0) {
-    return 'bg-gray-100 text-gray-500 dark:bg-zinc-800 dark:text-gray-400';
-  }
-  if (delay < 200) {
-    return 'bg-emerald-500/10 text-emerald-600 dark:text-emerald-400';
-  }
-  if (delay < 500) {
-    return 'bg-amber-500/10 text-amber-600 dark:text-amber-400';
-  }
-  return 'bg-red-500/10 text-red-600 dark:text-red-400';
+export function getDelayColorClass(
+  delay: number | null | undefined,
+  thresholds: LatencyThresholds = DEFAULT_LATENCY_THRESHOLDS
+): string {
+  return DELAY_COLOR_CLASSES[getDelayLevel(delay, thresholds)];
 }
 
 /**